@@ -0,0 +1,381 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Synthetic load of liability extrinsics: agreements and reports are signed before
+//! submission, so measured time covers only transaction pool and block production.
+
+use crate::stats::{Outcome, Stats};
+use codec::Encode;
+use futures::{future, stream, Future, StreamExt};
+use robonomics_liability_client::{
+    self as liability, CreateCall, DemandCall, Error, FinalizeCall, Included, MatchOrdersCall,
+    OfferCall,
+};
+use robonomics_protocol::runtime::{AccountId, Robonomics};
+use sp_core::{hashing::blake2_256, sr25519, Pair};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+use substrate_subxt::{
+    balances::{BalancesEventsDecoder, TransferCall},
+    system::System,
+    Client, EventsDecoder, PairSigner,
+};
+
+/// Parameters of benchmark load.
+#[derive(Clone, Debug)]
+pub struct Load {
+    /// Count of liabilities created by unsigned `create`.
+    pub liabilities: usize,
+    /// Count of liabilities created by matching signed orders.
+    pub orders: usize,
+    /// Maximal count of extrinsics waiting for inclusion.
+    pub concurrency: usize,
+    /// Deadline of extrinsic inclusion.
+    pub timeout: Duration,
+    /// Salt of liability parameters, so runs on the same chain don't collide.
+    pub salt: u64,
+    /// Funds transferred to each account for order deposits and fees.
+    pub endowment: u128,
+    /// Normal dispatch weight limit of block.
+    pub block_weight: u64,
+}
+
+/// Key pairs `//<seed>//<i>` of benchmark accounts.
+pub fn keypairs(seed: &str, count: usize) -> Vec<sr25519::Pair> {
+    (0..count)
+        .map(|i| {
+            sr25519::Pair::from_string(&format!("//{}//{}", seed, i), None)
+                .expect("derivation path is valid")
+        })
+        .collect()
+}
+
+/// IPFS hash like unique parameter of liability or report.
+fn multihash(salt: u64, kind: &str, i: usize) -> Vec<u8> {
+    let mut hash = vec![0x12, 0x20];
+    hash.extend_from_slice(&blake2_256(&(salt, kind, i as u64).encode()));
+    hash
+}
+
+/// Submit extrinsic with inclusion deadline, returns outcome and included event.
+async fn timed<E, F>(timeout: Duration, submission: F) -> (Outcome, Option<E>)
+where
+    F: Future<Output = liability::Result<Included<Robonomics, E>>>,
+{
+    let start = Instant::now();
+    match async_std::future::timeout(timeout, submission).await {
+        Ok(Ok(included)) => (
+            Outcome::Included {
+                latency: start.elapsed(),
+                block: included.block,
+                weight: included.weight,
+            },
+            Some(included.event),
+        ),
+        // Transaction pool refuses invalid, exhausting or duplicated extrinsics by RPC error
+        Ok(Err(Error::Subxt(e @ substrate_subxt::Error::Rpc(_)))) => {
+            (Outcome::Rejected(e.to_string()), None)
+        }
+        Ok(Err(e)) => (Outcome::Failed(e.to_string()), None),
+        Err(_) => (Outcome::TimedOut, None),
+    }
+}
+
+/// Submit pre-signed calls keeping `concurrency` of them in flight, returns included events.
+async fn flood<C, E, F, S>(stats: &mut Stats, calls: Vec<C>, load: &Load, submit: S) -> Vec<E>
+where
+    S: Fn(C) -> F,
+    F: Future<Output = liability::Result<Included<Robonomics, E>>>,
+{
+    let start = Instant::now();
+    let results: Vec<_> = stream::iter(calls)
+        .map(|call| timed(load.timeout, submit(call)))
+        .buffer_unordered(load.concurrency)
+        .collect()
+        .await;
+    stats.elapsed = start.elapsed();
+    results
+        .into_iter()
+        .filter_map(|(outcome, event)| {
+            stats.record(outcome);
+            event
+        })
+        .collect()
+}
+
+/// Reports of liabilities signed by promisor keys.
+fn sign_reports<'a, I>(
+    load: &Load,
+    keys: &[sr25519::Pair],
+    liabilities: I,
+) -> Vec<FinalizeCall<Robonomics>>
+where
+    I: Iterator<Item = (u64, &'a AccountId)>,
+{
+    let promisors: HashMap<AccountId, &sr25519::Pair> = keys
+        .iter()
+        .map(|key| (liability::account(key), key))
+        .collect();
+    liabilities
+        .filter_map(|(index, promisor)| {
+            let key = promisors.get(promisor)?;
+            let report = multihash(load.salt, "report", index as usize);
+            Some(FinalizeCall::signed(index, report, *key))
+        })
+        .collect()
+}
+
+/// Unsigned path: flood of `create` agreements signed by both parties, then flood
+/// of `finalize` reports of created liabilities.
+pub async fn unsigned(
+    client: &Client<Robonomics>,
+    keys: &[sr25519::Pair],
+    load: &Load,
+) -> (Stats, Stats) {
+    let mut creates = Stats::new("unsigned create", load.block_weight);
+    let mut finalizes = Stats::new("unsigned finalize", load.block_weight);
+
+    let calls = (0..load.liabilities)
+        .map(|i| {
+            let promisee = &keys[i % keys.len()];
+            let promisor = &keys[(i + 1) % keys.len()];
+            let technics = multihash(load.salt, "unsigned", i);
+            CreateCall::signed(technics, (), promisee, promisor)
+        })
+        .collect();
+    let created = flood(&mut creates, calls, load, |call| {
+        liability::create(client, call)
+    })
+    .await;
+
+    let reports = sign_reports(
+        load,
+        keys,
+        created.iter().map(|event| (event.index, &event.promisor)),
+    );
+    flood(&mut finalizes, reports, load, |call| {
+        liability::finalize(client, call)
+    })
+    .await;
+
+    (creates, finalizes)
+}
+
+/// Signed-origin path: liabilities are created by matching signed demand and offer, every
+/// pair of accounts runs its orders in sequence, since account extrinsics wait for nonce.
+/// Reports of matched liabilities are flooded by unsigned `finalize`.
+pub async fn signed(
+    client: &Client<Robonomics>,
+    keys: &[sr25519::Pair],
+    load: &Load,
+) -> (Stats, Stats, Stats) {
+    let mut orders = Stats::new("signed demand and offer", load.block_weight);
+    let mut matches = Stats::new("signed match_orders", load.block_weight);
+    let mut finalizes = Stats::new("signed path finalize", load.block_weight);
+
+    let lanes: Vec<&[sr25519::Pair]> = keys.chunks_exact(2).collect();
+    let start = Instant::now();
+    let results = future::join_all(lanes.iter().enumerate().map(|(lane, pair)| {
+        let count = (load.orders + lanes.len() - 1 - lane) / lanes.len();
+        run_lane(client, &pair[0], &pair[1], lane, lanes.len(), count, load)
+    }))
+    .await;
+    orders.elapsed = start.elapsed();
+    matches.elapsed = orders.elapsed;
+
+    let mut matched = Vec::new();
+    for result in results {
+        result.orders.into_iter().for_each(|o| orders.record(o));
+        result.matches.into_iter().for_each(|o| matches.record(o));
+        matched.extend(result.matched);
+    }
+
+    let reports = sign_reports(
+        load,
+        keys,
+        matched.iter().map(|(index, promisor)| (*index, promisor)),
+    );
+    flood(&mut finalizes, reports, load, |call| {
+        liability::finalize(client, call)
+    })
+    .await;
+
+    (orders, matches, finalizes)
+}
+
+/// Outcomes of lane orders.
+struct Lane {
+    orders: Vec<Outcome>,
+    matches: Vec<Outcome>,
+    /// Index and promisor of matched liabilities.
+    matched: Vec<(u64, AccountId)>,
+}
+
+/// Post demand of promisee and offer of promisor, then match them by promisee.
+async fn run_lane(
+    client: &Client<Robonomics>,
+    promisee: &sr25519::Pair,
+    promisor: &sr25519::Pair,
+    lane: usize,
+    lanes: usize,
+    count: usize,
+    load: &Load,
+) -> Lane {
+    let promisee_signer = PairSigner::new(promisee.clone());
+    let promisor_signer = PairSigner::new(promisor.clone());
+    let mut result = Lane {
+        orders: Vec::new(),
+        matches: Vec::new(),
+        matched: Vec::new(),
+    };
+
+    for i in 0..count {
+        let technics = multihash(load.salt, "signed", lane + i * lanes);
+        // Benchmark orders don't expire
+        let demand = DemandCall::signed(technics.clone(), (), u32::max_value(), promisee);
+        let offer = OfferCall::signed(technics, (), u32::max_value(), promisor);
+        let ((demand_outcome, demand), (offer_outcome, offer)) = future::join(
+            timed(
+                load.timeout,
+                liability::demand(client, demand, &promisee_signer),
+            ),
+            timed(
+                load.timeout,
+                liability::offer(client, offer, &promisor_signer),
+            ),
+        )
+        .await;
+        result.orders.push(demand_outcome);
+        result.orders.push(offer_outcome);
+        let (demand, offer) = match (demand, offer) {
+            (Some(demand), Some(offer)) => (demand, offer),
+            _ => continue,
+        };
+
+        let call = MatchOrdersCall {
+            demand_id: demand.id,
+            offer_id: offer.id,
+            _runtime: PhantomData,
+        };
+        let (outcome, matched) = timed(
+            load.timeout,
+            liability::match_orders(client, call, &promisee_signer),
+        )
+        .await;
+        result.matches.push(outcome);
+        if let Some(matched) = matched {
+            result
+                .matched
+                .push((matched.index, liability::account(promisor)));
+        }
+    }
+    result
+}
+
+/// Transfer endowment from sponsor to benchmark accounts, transfers are signed with
+/// consecutive nonces and submitted together.
+pub async fn endow(
+    client: &Client<Robonomics>,
+    sponsor: &sr25519::Pair,
+    keys: &[sr25519::Pair],
+    amount: u128,
+) -> Result<(), String> {
+    let mut signer = PairSigner::new(sponsor.clone());
+    let nonce = client
+        .account(&liability::account(sponsor), None)
+        .await
+        .map_err(|e| format!("unable to read sponsor nonce: {}", e))?
+        .nonce;
+    signer.set_nonce(nonce);
+
+    let mut transfers = Vec::new();
+    for key in keys {
+        let to: <Robonomics as System>::Address = liability::account(key).into();
+        let extrinsic = client
+            .create_signed(TransferCall { to: &to, amount }, &signer)
+            .await
+            .map_err(|e| format!("unable to sign transfer: {}", e))?;
+        transfers.push(extrinsic);
+        signer.increment_nonce();
+    }
+
+    future::try_join_all(transfers.into_iter().map(|extrinsic| {
+        let mut decoder = EventsDecoder::<Robonomics>::new(client.metadata().clone());
+        decoder.with_balances();
+        client.submit_and_watch_extrinsic(extrinsic, decoder)
+    }))
+    .await
+    .map_err(|e| format!("endowment isn't transferred: {}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use robonomics_liability_client::report_payload;
+    use sp_runtime::traits::Verify;
+
+    fn load() -> Load {
+        Load {
+            liabilities: 4,
+            orders: 2,
+            concurrency: 4,
+            timeout: Duration::from_secs(1),
+            salt: 42,
+            endowment: 0,
+            block_weight: 1_000,
+        }
+    }
+
+    #[test]
+    fn test_keypairs() {
+        let keys = keypairs("bench", 3);
+        assert_eq!(keys.len(), 3);
+        assert_ne!(keys[0].public(), keys[1].public());
+        assert_eq!(keypairs("bench", 1)[0].public(), keys[0].public());
+        assert_ne!(keypairs("other", 1)[0].public(), keys[0].public());
+    }
+
+    #[test]
+    fn test_multihash() {
+        let hash = multihash(42, "unsigned", 0);
+        assert_eq!(hash.len(), 34);
+        assert_eq!(&hash[..2], &[0x12, 0x20]);
+        assert_ne!(hash, multihash(42, "unsigned", 1));
+        assert_ne!(hash, multihash(42, "signed", 0));
+        assert_ne!(hash, multihash(43, "unsigned", 0));
+    }
+
+    #[test]
+    fn test_sign_reports() {
+        let keys = keypairs("bench", 2);
+        let promisor = liability::account(&keys[1]);
+        let stranger = liability::account(&keypairs("stranger", 1)[0]);
+        let liabilities = vec![(0, &promisor), (1, &stranger)];
+
+        // Reports are signed only for liabilities of benchmark keys
+        let reports = sign_reports(&load(), &keys, liabilities.into_iter());
+        assert_eq!(reports.len(), 1);
+        let report = multihash(42, "report", 0);
+        assert_eq!(reports[0].index, 0);
+        assert_eq!(reports[0].report, report);
+        assert!(reports[0]
+            .proof
+            .verify(&report_payload(&0u64, &report)[..], &promisor));
+    }
+}
@@ -0,0 +1,169 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Synthetic load benchmark of Robonomics liability pipeline.
+//!
+//! Benchmark derives accounts `//<seed>//<i>`, signs agreements and reports in advance and
+//! floods remote node by them through RPC. The unsigned path creates liabilities by
+//! `create` with both parties proofs, the signed-origin path matches signed demands and
+//! offers of endowed accounts, both are finalized by unsigned `finalize`. Summary of each
+//! phase reports pool rejection rate, inclusion latency and block fullness, i.e. share of
+//! block normal weight limit taken by benchmark extrinsics.
+//!
+//! Small scale run is a smoke test of dev node, it fails when any extrinsic is lost:
+//!
+//! ```text
+//! robonomics --dev --tmp &
+//! robonomics-liability-bench --accounts 4 --liabilities 8 --orders 2 --max-loss 0
+//! ```
+
+use sp_core::{sr25519, Pair};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use structopt::StructOpt;
+
+mod load;
+mod stats;
+
+use load::Load;
+use stats::Stats;
+
+/// Robonomics runtime currency unit.
+const XRT: u128 = 1_000_000_000;
+
+/// Count of attempts to connect remote node, e.g. just started in CI.
+const CONNECT_ATTEMPTS: usize = 30;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "robonomics-liability-bench")]
+struct Opt {
+    /// Robonomics node WebSocket endpoint.
+    #[structopt(long, value_name = "REMOTE_URI", default_value = "ws://127.0.0.1:9944")]
+    remote: String,
+    /// Count of benchmark accounts.
+    #[structopt(long, value_name = "N", default_value = "16")]
+    accounts: usize,
+    /// Count of liabilities created by unsigned path.
+    #[structopt(long, value_name = "M", default_value = "64")]
+    liabilities: usize,
+    /// Count of liabilities created by signed-origin path.
+    #[structopt(long, value_name = "K", default_value = "8")]
+    orders: usize,
+    /// Benchmarked path.
+    #[structopt(
+        long,
+        value_name = "PATH",
+        possible_values = &["unsigned", "signed", "all"],
+        default_value = "all"
+    )]
+    path: String,
+    /// Maximal count of extrinsics waiting for inclusion.
+    #[structopt(long, value_name = "COUNT", default_value = "64")]
+    concurrency: usize,
+    /// Extrinsic inclusion deadline in seconds.
+    #[structopt(long, value_name = "SECS", default_value = "120")]
+    timeout: u64,
+    /// Seed of benchmark accounts derivation.
+    #[structopt(long, value_name = "SEED", default_value = "bench")]
+    seed: String,
+    /// Secret URI of account endowing signed-origin path accounts.
+    #[structopt(long, value_name = "SURI", default_value = "//Alice")]
+    sponsor: String,
+    /// Endowment of signed-origin path account in XRT.
+    #[structopt(long, value_name = "XRT", default_value = "10")]
+    endowment: u128,
+    /// Normal dispatch weight limit of block.
+    #[structopt(long, value_name = "WEIGHT", default_value = "1500000000000")]
+    block_weight: u64,
+    /// Fail when share of lost extrinsics of any phase exceeds given percent.
+    #[structopt(long, value_name = "PERCENT")]
+    max_loss: Option<f64>,
+}
+
+#[async_std::main]
+async fn main() -> Result<(), String> {
+    env_logger::init();
+    let opt = Opt::from_args();
+    if opt.accounts < 2 {
+        Err("at least two accounts are required")?
+    }
+
+    let load = Load {
+        liabilities: opt.liabilities,
+        orders: opt.orders,
+        concurrency: opt.concurrency.max(1),
+        timeout: Duration::from_secs(opt.timeout),
+        salt: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |t| t.as_nanos() as u64),
+        endowment: opt.endowment * XRT,
+        block_weight: opt.block_weight,
+    };
+    let keys = load::keypairs(&opt.seed, opt.accounts);
+    let client = connect(&opt.remote).await?;
+
+    let mut phases: Vec<Stats> = Vec::new();
+    if opt.path != "signed" {
+        let (creates, finalizes) = load::unsigned(&client, &keys, &load).await;
+        phases.push(creates);
+        phases.push(finalizes);
+    }
+    if opt.path != "unsigned" {
+        let sponsor = sr25519::Pair::from_string(&opt.sponsor, None)
+            .map_err(|e| format!("bad sponsor secret URI: {:?}", e))?;
+        load::endow(&client, &sponsor, &keys, load.endowment).await?;
+        let (orders, matches, finalizes) = load::signed(&client, &keys, &load).await;
+        phases.push(orders);
+        phases.push(matches);
+        phases.push(finalizes);
+    }
+
+    for stats in &phases {
+        println!("{}", stats);
+    }
+    if let Some(max_loss) = opt.max_loss {
+        for stats in &phases {
+            if stats.loss_rate() * 100.0 > max_loss {
+                Err(format!(
+                    "{} lost {:.1}% of extrinsics",
+                    stats.name,
+                    stats.loss_rate() * 100.0
+                ))?
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn connect(
+    remote: &str,
+) -> Result<substrate_subxt::Client<robonomics_protocol::runtime::Robonomics>, String> {
+    let mut attempt = 1;
+    loop {
+        match substrate_subxt::ClientBuilder::new()
+            .set_url(remote)
+            .build()
+            .await
+        {
+            Ok(client) => return Ok(client),
+            Err(e) if attempt >= CONNECT_ATTEMPTS => {
+                return Err(format!("unable to connect {}: {}", remote, e))
+            }
+            Err(_) => async_std::task::sleep(Duration::from_secs(1)).await,
+        }
+        attempt += 1;
+    }
+}
@@ -0,0 +1,286 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Outcomes of benchmark extrinsics and their summary.
+
+use sp_core::H256;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+/// Result of submitted extrinsic.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome {
+    /// Extrinsic included in block after submission latency.
+    Included {
+        latency: Duration,
+        block: H256,
+        weight: u64,
+    },
+    /// Extrinsic isn't accepted by transaction pool.
+    Rejected(String),
+    /// Extrinsic is included but dispatch failed, or its result isn't received.
+    Failed(String),
+    /// Extrinsic isn't included before deadline.
+    TimedOut,
+}
+
+/// Load of block by benchmark extrinsics.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BlockLoad {
+    /// Count of benchmark extrinsics in block.
+    pub extrinsics: usize,
+    /// Total dispatch weight of benchmark extrinsics.
+    pub weight: u64,
+}
+
+/// Outcomes of benchmark phase, e.g. unsigned `create` flood.
+#[derive(Clone, Debug)]
+pub struct Stats {
+    /// Phase name.
+    pub name: &'static str,
+    /// Time from the first submission to the last outcome.
+    pub elapsed: Duration,
+    /// Normal dispatch weight limit of block.
+    pub block_weight: u64,
+    outcomes: Vec<Outcome>,
+}
+
+impl Stats {
+    pub fn new(name: &'static str, block_weight: u64) -> Self {
+        Stats {
+            name,
+            elapsed: Duration::default(),
+            block_weight,
+            outcomes: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, outcome: Outcome) {
+        if let Outcome::Rejected(ref e) | Outcome::Failed(ref e) = outcome {
+            log::debug!("{} extrinsic isn't included: {}", self.name, e);
+        }
+        self.outcomes.push(outcome);
+    }
+
+    pub fn submitted(&self) -> usize {
+        self.outcomes.len()
+    }
+
+    pub fn included(&self) -> usize {
+        self.count(|o| matches!(o, Outcome::Included { .. }))
+    }
+
+    pub fn rejected(&self) -> usize {
+        self.count(|o| matches!(o, Outcome::Rejected(_)))
+    }
+
+    pub fn failed(&self) -> usize {
+        self.count(|o| matches!(o, Outcome::Failed(_)))
+    }
+
+    pub fn timed_out(&self) -> usize {
+        self.count(|o| matches!(o, Outcome::TimedOut))
+    }
+
+    /// Share of submitted extrinsics rejected by transaction pool.
+    pub fn rejection_rate(&self) -> f64 {
+        ratio(self.rejected(), self.submitted())
+    }
+
+    /// Share of submitted extrinsics that aren't included.
+    pub fn loss_rate(&self) -> f64 {
+        ratio(self.submitted() - self.included(), self.submitted())
+    }
+
+    /// Included extrinsics per second.
+    pub fn throughput(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.included() as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    /// Inclusion latency percentile, `p` is in range `0..=100`.
+    pub fn latency(&self, p: usize) -> Option<Duration> {
+        let mut latencies: Vec<Duration> = self
+            .outcomes
+            .iter()
+            .filter_map(|o| match o {
+                Outcome::Included { latency, .. } => Some(*latency),
+                _ => None,
+            })
+            .collect();
+        if latencies.is_empty() {
+            return None;
+        }
+        latencies.sort();
+        let rank = (p.min(100) * latencies.len() + 99) / 100;
+        Some(latencies[rank.max(1) - 1])
+    }
+
+    /// Load of blocks including benchmark extrinsics.
+    pub fn blocks(&self) -> BTreeMap<H256, BlockLoad> {
+        let mut blocks = BTreeMap::<H256, BlockLoad>::new();
+        for outcome in &self.outcomes {
+            if let Outcome::Included { block, weight, .. } = outcome {
+                let load = blocks.entry(*block).or_default();
+                load.extrinsics += 1;
+                load.weight += weight;
+            }
+        }
+        blocks
+    }
+
+    /// Mean and maximal share of block weight limit used by benchmark extrinsics.
+    pub fn fullness(&self) -> (f64, f64) {
+        let blocks = self.blocks();
+        if blocks.is_empty() || self.block_weight == 0 {
+            return (0.0, 0.0);
+        }
+        let shares: Vec<f64> = blocks
+            .values()
+            .map(|load| load.weight as f64 / self.block_weight as f64)
+            .collect();
+        let max = shares.iter().cloned().fold(0.0, f64::max);
+        (shares.iter().sum::<f64>() / shares.len() as f64, max)
+    }
+
+    fn count<F: Fn(&Outcome) -> bool>(&self, f: F) -> usize {
+        self.outcomes.iter().filter(|o| f(o)).count()
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ms = |p| {
+            self.latency(p)
+                .map_or("-".to_string(), |l| format!("{}ms", l.as_millis()))
+        };
+        let (mean, max) = self.fullness();
+        writeln!(f, "{}:", self.name)?;
+        writeln!(
+            f,
+            "  submitted {}, included {}, rejected {} ({:.1}%), failed {}, timed out {}",
+            self.submitted(),
+            self.included(),
+            self.rejected(),
+            self.rejection_rate() * 100.0,
+            self.failed(),
+            self.timed_out(),
+        )?;
+        writeln!(
+            f,
+            "  throughput {:.2} tx/s in {:.1}s",
+            self.throughput(),
+            self.elapsed.as_secs_f64()
+        )?;
+        writeln!(
+            f,
+            "  inclusion latency p50 {}, p90 {}, p99 {}, max {}",
+            ms(50),
+            ms(90),
+            ms(99),
+            ms(100)
+        )?;
+        write!(
+            f,
+            "  {} blocks, fullness mean {:.1}%, max {:.1}%",
+            self.blocks().len(),
+            mean * 100.0,
+            max * 100.0
+        )
+    }
+}
+
+fn ratio(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn included(ms: u64, block: u8, weight: u64) -> Outcome {
+        Outcome::Included {
+            latency: Duration::from_millis(ms),
+            block: H256::repeat_byte(block),
+            weight,
+        }
+    }
+
+    #[test]
+    fn test_stats_counters() {
+        let mut stats = Stats::new("create", 1_000);
+        assert_eq!(stats.rejection_rate(), 0.0);
+        assert_eq!(stats.latency(50), None);
+
+        stats.record(included(100, 1, 100));
+        stats.record(Outcome::Rejected("pool is full".into()));
+        stats.record(Outcome::Failed("dispatch error".into()));
+        stats.record(Outcome::TimedOut);
+        stats.elapsed = Duration::from_secs(2);
+
+        assert_eq!(stats.submitted(), 4);
+        assert_eq!(stats.included(), 1);
+        assert_eq!(stats.rejected(), 1);
+        assert_eq!(stats.failed(), 1);
+        assert_eq!(stats.timed_out(), 1);
+        assert_eq!(stats.rejection_rate(), 0.25);
+        assert_eq!(stats.loss_rate(), 0.75);
+        assert_eq!(stats.throughput(), 0.5);
+    }
+
+    #[test]
+    fn test_stats_latency() {
+        let mut stats = Stats::new("create", 1_000);
+        for ms in (1..=10).rev() {
+            stats.record(included(ms * 100, 1, 0));
+        }
+        assert_eq!(stats.latency(0), Some(Duration::from_millis(100)));
+        assert_eq!(stats.latency(50), Some(Duration::from_millis(500)));
+        assert_eq!(stats.latency(90), Some(Duration::from_millis(900)));
+        assert_eq!(stats.latency(99), Some(Duration::from_millis(1_000)));
+        assert_eq!(stats.latency(100), Some(Duration::from_millis(1_000)));
+    }
+
+    #[test]
+    fn test_stats_fullness() {
+        let mut stats = Stats::new("create", 1_000);
+        stats.record(included(100, 1, 200));
+        stats.record(included(100, 1, 300));
+        stats.record(included(100, 2, 100));
+        stats.record(Outcome::TimedOut);
+
+        let blocks = stats.blocks();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(
+            blocks[&H256::repeat_byte(1)],
+            BlockLoad {
+                extrinsics: 2,
+                weight: 500
+            }
+        );
+        assert_eq!(stats.fullness(), (0.3, 0.5));
+    }
+}
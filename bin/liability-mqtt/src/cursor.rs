@@ -0,0 +1,135 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Cursor of published liability transitions.
+
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::Path;
+
+/// Position of liability transition: transitions of blocks before `block` and the first
+/// `published` transitions of `block` are published.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Cursor {
+    /// Block number.
+    pub block: u32,
+    /// Count of transitions of the block.
+    pub published: u32,
+}
+
+impl Cursor {
+    /// Position of the next transition of given block.
+    pub fn next(self, block: u32) -> Self {
+        if block == self.block {
+            Cursor {
+                block,
+                published: self.published + 1,
+            }
+        } else {
+            Cursor {
+                block,
+                published: 1,
+            }
+        }
+    }
+
+    /// Returns `true` when transition at given position is published.
+    pub fn covers(&self, position: &Cursor) -> bool {
+        self.block > position.block
+            || (self.block == position.block && self.published >= position.published)
+    }
+
+    /// Read cursor file, returns `None` when it doesn't exist.
+    pub fn load(path: &Path) -> io::Result<Option<Self>> {
+        let data = match std::fs::read(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        serde_json::from_slice(&data)
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Replace cursor file, the new one is renamed over it to survive interruption.
+    pub fn store(&self, path: &Path) -> io::Result<()> {
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        std::fs::write(&tmp, serde_json::to_vec(self)?)?;
+        std::fs::rename(&tmp, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_position() {
+        let start = Cursor {
+            block: 10,
+            published: 2,
+        };
+        let mut position = Cursor {
+            block: 10,
+            published: 0,
+        };
+        // Replayed transitions of cursor block are skipped
+        position = position.next(10);
+        assert!(start.covers(&position));
+        position = position.next(10);
+        assert!(start.covers(&position));
+        position = position.next(10);
+        assert_eq!(
+            position,
+            Cursor {
+                block: 10,
+                published: 3
+            }
+        );
+        assert!(!start.covers(&position));
+        position = position.next(12);
+        assert_eq!(
+            position,
+            Cursor {
+                block: 12,
+                published: 1
+            }
+        );
+        assert!(!start.covers(&position));
+    }
+
+    #[test]
+    fn test_cursor_file() {
+        let path = std::env::temp_dir().join(format!("liability-mqtt-{}", std::process::id()));
+        assert_eq!(Cursor::load(&path).unwrap(), None);
+
+        let cursor = Cursor {
+            block: 42,
+            published: 3,
+        };
+        cursor.store(&path).unwrap();
+        assert_eq!(Cursor::load(&path).unwrap(), Some(cursor));
+        cursor.next(43).store(&path).unwrap();
+        assert_eq!(Cursor::load(&path).unwrap(), Some(cursor.next(43)));
+
+        std::fs::write(&path, b"not a cursor").unwrap();
+        assert!(Cursor::load(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,212 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Forwarder of Robonomics liability events to MQTT broker.
+//!
+//! Liability transitions of finalized blocks are published as retained JSON messages to
+//! `<prefix>/<index>/state` topics with QoS 1. Position of the last transition acknowledged
+//! by broker is kept in cursor file, so forwarder restarts from it without gaps. Delivery is
+//! at-least-once: a transition is published again only when forwarder stops between broker
+//! acknowledgement and cursor write.
+//!
+//! Devices publish signed liability reports to report topic, e.g.
+//! `{"index": 3, "report": "Qm...", "signature": "0x..."}`, they're submitted as `finalize`.
+
+use futures::future::{self, Either};
+use futures::{channel::mpsc, stream::FuturesUnordered, StreamExt};
+use robonomics_liability_client::FinalizeCall;
+use robonomics_protocol::runtime::Robonomics;
+use rumqttc::{AsyncClient, Incoming, MqttOptions, QoS};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::time::Duration;
+use structopt::StructOpt;
+
+mod cursor;
+mod message;
+
+use cursor::Cursor;
+use message::{report_call, state_topic, transition_position};
+
+/// Delay before MQTT broker reconnection.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Capacity of MQTT requests queue.
+const REQUESTS_CAPACITY: usize = 64;
+
+#[derive(Debug, StructOpt, Clone)]
+#[structopt(name = "robonomics-liability-mqtt")]
+struct Opt {
+    /// Robonomics node WebSocket endpoint.
+    #[structopt(long, value_name = "REMOTE_URI", default_value = "ws://127.0.0.1:9944")]
+    remote: String,
+    /// MQTT broker host.
+    #[structopt(long, value_name = "HOST", default_value = "127.0.0.1")]
+    mqtt_host: String,
+    /// MQTT broker port.
+    #[structopt(long, value_name = "PORT", default_value = "1883")]
+    mqtt_port: u16,
+    /// MQTT client identifier, broker keeps session of it between restarts.
+    #[structopt(long, value_name = "ID", default_value = "robonomics-liability")]
+    client_id: String,
+    /// Prefix of liability state topics.
+    #[structopt(long, value_name = "TOPIC", default_value = "robonomics/liability")]
+    topic_prefix: String,
+    /// Topic of signed liability reports.
+    #[structopt(
+        long,
+        value_name = "TOPIC",
+        default_value = "robonomics/liability/report"
+    )]
+    report_topic: String,
+    /// File of published transitions cursor.
+    #[structopt(long, value_name = "PATH", default_value = "liability-mqtt.cursor")]
+    cursor: PathBuf,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), String> {
+    env_logger::init();
+    let opt = Opt::from_args();
+
+    let start = Cursor::load(&opt.cursor)
+        .map_err(|e| format!("unable to read cursor {}: {}", opt.cursor.display(), e))?;
+    if let Some(start) = start {
+        log::info!(
+            "Resume from block {} after {} published transitions",
+            start.block,
+            start.published
+        );
+    }
+
+    let mut options = MqttOptions::new(opt.client_id.clone(), opt.mqtt_host.clone(), opt.mqtt_port);
+    options.set_keep_alive(30).set_clean_session(false);
+    let (client, mut eventloop) = AsyncClient::new(options, REQUESTS_CAPACITY);
+    client
+        .subscribe(opt.report_topic.clone(), QoS::AtLeastOnce)
+        .await
+        .map_err(|e| format!("unable to subscribe {}: {}", opt.report_topic, e))?;
+
+    let (incoming_tx, incoming_rx) = mpsc::unbounded();
+    tokio::spawn(async move {
+        loop {
+            match eventloop.poll().await {
+                Ok((Some(incoming), _)) => {
+                    if incoming_tx.unbounded_send(incoming).is_err() {
+                        break;
+                    }
+                }
+                Ok(_) => (),
+                Err(e) => {
+                    log::warn!("MQTT connection failed: {:?}", e);
+                    tokio::time::delay_for(RECONNECT_DELAY).await;
+                }
+            }
+        }
+    });
+
+    let (transition_tx, transition_rx) = mpsc::unbounded();
+    let watch = robonomics_protocol::liability::watch(
+        opt.remote.clone(),
+        None,
+        start.map(|cursor| cursor.block),
+        true,
+        move |transition| {
+            transition_tx
+                .unbounded_send(transition)
+                .map_err(|_| String::from("forwarder stopped").into())
+        },
+    );
+    let forward = forward(opt, client, start, transition_rx, incoming_rx);
+
+    futures::pin_mut!(watch, forward);
+    match future::select(watch, forward).await {
+        Either::Left((result, _)) => result.map_err(|e| e.to_string()),
+        Either::Right((result, _)) => result,
+    }
+}
+
+/// Publish liability transitions and submit reports of devices.
+async fn forward(
+    opt: Opt,
+    client: AsyncClient,
+    start: Option<Cursor>,
+    mut transitions: mpsc::UnboundedReceiver<robonomics_protocol::liability::Transition>,
+    mut incoming: mpsc::UnboundedReceiver<Incoming>,
+) -> Result<(), String> {
+    let mut position = Cursor::default();
+    // Positions of published transitions waiting for broker acknowledgement, in publish order
+    let mut pending = VecDeque::new();
+    let mut submits = FuturesUnordered::new();
+    loop {
+        futures::select! {
+            transition = transitions.next() => {
+                let transition = transition.ok_or("liability watcher stopped")?;
+                let (index, block) = transition_position(&transition);
+                position = position.next(block);
+                // Transitions of cursor block are replayed after restart
+                if start.map_or(false, |start| start.covers(&position)) {
+                    continue;
+                }
+                client
+                    .publish(
+                        state_topic(&opt.topic_prefix, index),
+                        QoS::AtLeastOnce,
+                        true,
+                        transition.to_json().to_string(),
+                    )
+                    .await
+                    .map_err(|e| format!("unable to publish liability {}: {}", index, e))?;
+                pending.push_back(position);
+            },
+            packet = incoming.next() => match packet.ok_or("MQTT event loop stopped")? {
+                Incoming::PubAck(_) => {
+                    if let Some(acked) = pending.pop_front() {
+                        if let Err(e) = acked.store(&opt.cursor) {
+                            log::warn!("Unable to write cursor {}: {}", opt.cursor.display(), e);
+                        }
+                    }
+                }
+                Incoming::Publish(publish) if publish.topic == opt.report_topic => {
+                    match report_call(&publish.payload) {
+                        Ok(call) => submits.push(submit(opt.remote.clone(), call)),
+                        Err(e) => log::warn!("Report message is ignored: {}", e),
+                    }
+                }
+                _ => (),
+            },
+            result = submits.select_next_some() => match result {
+                Ok(index) => log::info!("Liability {} finalized", index),
+                Err(e) => log::warn!("{}", e),
+            },
+        }
+    }
+}
+
+/// Submit `finalize` of device report to remote node.
+async fn submit(remote: String, call: FinalizeCall<Robonomics>) -> Result<u64, String> {
+    let index = call.index;
+    let client = substrate_subxt::ClientBuilder::<Robonomics>::new()
+        .set_url(&remote)
+        .build()
+        .await
+        .map_err(|e| format!("liability {} isn't finalized: {}", index, e))?;
+    robonomics_liability_client::finalize(&client, call)
+        .await
+        .map_err(|e| format!("liability {} isn't finalized: {}", index, e))?;
+    Ok(index)
+}
@@ -0,0 +1,128 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! MQTT messages of liability forwarder.
+
+use robonomics_liability_client::FinalizeCall;
+use robonomics_protocol::liability::{decode_cid, Transition};
+use robonomics_protocol::runtime::Robonomics;
+use serde::Deserialize;
+use sp_core::sr25519;
+use sp_runtime::MultiSignature;
+
+/// Topic of liability state, `<prefix>/<index>/state`.
+pub fn state_topic(prefix: &str, index: u64) -> String {
+    format!("{}/{}/state", prefix.trim_end_matches('/'), index)
+}
+
+/// Index and block of liability transition.
+pub fn transition_position(transition: &Transition) -> (u64, u32) {
+    match transition {
+        Transition::Created { index, block, .. } => (*index, *block),
+        Transition::Reported { index, block, .. } => (*index, *block),
+    }
+}
+
+/// Liability report signed by device, e.g.
+/// `{"index": 3, "report": "Qm...", "signature": "0x..."}`.
+///
+/// Signature is sr25519 signature of promisor over SCALE encoded index and report multihash,
+/// the same as `finalize` proof.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct ReportMessage {
+    /// Liability index.
+    index: u64,
+    /// IPFS hash of liability report.
+    report: String,
+    /// Hex encoded promisor signature.
+    signature: String,
+}
+
+/// Unsigned `finalize` call of report message.
+pub fn report_call(payload: &[u8]) -> Result<FinalizeCall<Robonomics>, String> {
+    let message: ReportMessage =
+        serde_json::from_slice(payload).map_err(|e| format!("bad report message: {}", e))?;
+    let report = decode_cid(&message.report).map_err(|e| e.to_string())?;
+    let signature = hex::decode(message.signature.trim_start_matches("0x"))
+        .map_err(|e| format!("bad report signature: {}", e))?;
+    if signature.len() != 64 {
+        Err(format!(
+            "bad report signature: 64 bytes expected, got {}",
+            signature.len()
+        ))?
+    }
+    let mut raw = [0u8; 64];
+    raw.copy_from_slice(&signature);
+    Ok(FinalizeCall {
+        index: message.index,
+        report,
+        proof: MultiSignature::Sr25519(sr25519::Signature::from_raw(raw)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sp_core::Pair;
+
+    const CID: &str = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4";
+
+    #[test]
+    fn test_state_topic() {
+        assert_eq!(
+            state_topic("robonomics/liability", 7),
+            "robonomics/liability/7/state"
+        );
+        assert_eq!(
+            state_topic("robonomics/liability/", 7),
+            "robonomics/liability/7/state"
+        );
+    }
+
+    #[test]
+    fn test_report_call() {
+        let promisor = sr25519::Pair::from_string("//Bob", None).unwrap();
+        let report = decode_cid(CID).unwrap();
+        let signed = FinalizeCall::<Robonomics>::signed(3, report.clone(), &promisor);
+        let signature = match &signed.proof {
+            MultiSignature::Sr25519(signature) => hex::encode(signature),
+            _ => unreachable!(),
+        };
+
+        let payload = format!(
+            r#"{{"index": 3, "report": "{}", "signature": "0x{}"}}"#,
+            CID, signature
+        );
+        let call = report_call(payload.as_bytes()).unwrap();
+        assert_eq!(call, signed);
+        assert_eq!(call.report, report);
+
+        let short = format!(
+            r#"{{"index": 3, "report": "{}", "signature": "0x{}"}}"#,
+            CID,
+            &signature[..64]
+        );
+        assert!(report_call(short.as_bytes()).is_err());
+        let bad_cid = format!(
+            r#"{{"index": 3, "report": "Qm", "signature": "0x{}"}}"#,
+            signature
+        );
+        assert!(report_call(bad_cid.as_bytes()).is_err());
+        assert!(report_call(br#"{"index": 3}"#).is_err());
+    }
+}
@@ -20,9 +20,10 @@
 use node_primitives::{AccountId, Balance, Block, Signature};
 use pallet_im_online::sr25519::AuthorityId as ImOnlineId;
 use robonomics_runtime::{
-    wasm_binary_unwrap, AuthorityDiscoveryConfig, BabeConfig, BalancesConfig, CouncilConfig,
-    ElectionsConfig, GenesisConfig, GrandpaConfig, ImOnlineConfig, IndicesConfig, SessionConfig,
-    SessionKeys, StakerStatus, StakingConfig, SudoConfig, SystemConfig,
+    constants::currency::XRT, wasm_binary_unwrap, AuthorityDiscoveryConfig, BabeConfig,
+    BalancesConfig, CouncilConfig, ElectionsConfig, GenesisConfig, GrandpaConfig, ImOnlineConfig,
+    IndicesConfig, LiabilityConfig, SessionConfig, SessionKeys, StakerStatus, StakingConfig,
+    SudoConfig, SystemConfig,
 };
 use sc_chain_spec::ChainSpecExtension;
 use sc_service::ChainType;
@@ -65,7 +66,7 @@ impl RobonomicsChain for Box<dyn sc_chain_spec::ChainSpec> {
             return RobonomicsFamily::Parachain;
         }
 
-        if self.id() == "dev" {
+        if self.id() == "dev" || self.id() == LIABILITY_DEV_ID {
             return RobonomicsFamily::Development;
         }
 
@@ -74,6 +75,7 @@ impl RobonomicsChain for Box<dyn sc_chain_spec::ChainSpec> {
 }
 
 const DAO_IPCI_ID: &str = "ipci";
+const LIABILITY_DEV_ID: &str = "liability-dev";
 /*
 const IPCI_PROTOCOL_ID: &str = "mito";
 const IPCI_PROPERTIES: &str = r#"
@@ -152,6 +154,35 @@ fn session_keys(
     }
 }
 
+/// Well-known development accounts.
+fn dev_accounts() -> Vec<AccountId> {
+    vec![
+        get_account_id_from_seed::<sr25519::Public>("Alice"),
+        get_account_id_from_seed::<sr25519::Public>("Bob"),
+        get_account_id_from_seed::<sr25519::Public>("Charlie"),
+        get_account_id_from_seed::<sr25519::Public>("Dave"),
+        get_account_id_from_seed::<sr25519::Public>("Eve"),
+        get_account_id_from_seed::<sr25519::Public>("Ferdie"),
+        get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
+        get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
+        get_account_id_from_seed::<sr25519::Public>("Charlie//stash"),
+        get_account_id_from_seed::<sr25519::Public>("Dave//stash"),
+        get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
+        get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
+    ]
+}
+
+/// Demo liability: Alice asks Bob to execute task QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4
+fn demo_liability() -> (Vec<u8>, (), AccountId, AccountId) {
+    (
+        hex_literal::hex!["12207abedce68733427074ef3a776e426deb297a8b8b3704b47bce64e4be4e0b4f7d"]
+            .to_vec(),
+        (),
+        get_account_id_from_seed::<sr25519::Public>("Alice"),
+        get_account_id_from_seed::<sr25519::Public>("Bob"),
+    )
+}
+
 fn development_genesis(
     initial_authorities: Vec<(
         AccountId,
@@ -167,35 +198,57 @@ fn development_genesis(
     const ENDOWMENT: Balance = 1_000_000_000_000_000_000;
 
     let endowed_accounts: Vec<(AccountId, Balance)> = endowed_accounts
-        .unwrap_or_else(|| {
-            vec![
-                get_account_id_from_seed::<sr25519::Public>("Alice"),
-                get_account_id_from_seed::<sr25519::Public>("Bob"),
-                get_account_id_from_seed::<sr25519::Public>("Charlie"),
-                get_account_id_from_seed::<sr25519::Public>("Dave"),
-                get_account_id_from_seed::<sr25519::Public>("Eve"),
-                get_account_id_from_seed::<sr25519::Public>("Ferdie"),
-                get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
-                get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
-                get_account_id_from_seed::<sr25519::Public>("Charlie//stash"),
-                get_account_id_from_seed::<sr25519::Public>("Dave//stash"),
-                get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
-                get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
-            ]
-        })
+        .unwrap_or_else(dev_accounts)
         .iter()
         .cloned()
         .map(|acc| (acc, ENDOWMENT))
         .collect();
 
+    let liability = LiabilityConfig {
+        liabilities: vec![demo_liability()],
+        lighthouses: vec![],
+        market_fees: vec![],
+    };
+
     mk_genesis(
         initial_authorities,
         endowed_accounts,
+        liability,
         sudo_key,
         wasm_binary_unwrap().to_vec(),
     )
 }
 
+/// Liability market development genesis: dev accounts are funded, Charlie is lighthouse
+/// of the single market and the demo liability is open.
+fn liability_dev_genesis() -> GenesisConfig {
+    const ENDOWMENT: Balance = 1_000_000 * XRT;
+    const LIGHTHOUSE_STAKE: Balance = 100 * XRT;
+
+    let endowed_accounts = dev_accounts()
+        .into_iter()
+        .map(|acc| (acc, ENDOWMENT))
+        .collect();
+
+    let liability = LiabilityConfig {
+        liabilities: vec![demo_liability()],
+        lighthouses: vec![(
+            0,
+            get_account_id_from_seed::<sr25519::Public>("Charlie"),
+            LIGHTHOUSE_STAKE,
+        )],
+        market_fees: vec![(0, Perbill::from_percent(1))],
+    };
+
+    mk_genesis(
+        vec![get_authority_keys_from_seed("Alice")],
+        endowed_accounts,
+        liability,
+        get_account_id_from_seed::<sr25519::Public>("Alice"),
+        wasm_binary_unwrap().to_vec(),
+    )
+}
+
 /// Helper function to create GenesisConfig
 fn mk_genesis(
     initial_authorities: Vec<(
@@ -207,6 +260,7 @@ fn mk_genesis(
         AuthorityDiscoveryId,
     )>,
     endowed_accounts: Vec<(AccountId, Balance)>,
+    liability: LiabilityConfig,
     sudo_key: AccountId,
     code: Vec<u8>,
 ) -> GenesisConfig {
@@ -259,6 +313,7 @@ fn mk_genesis(
         pallet_collective_Instance1: Some(CouncilConfig::default()),
         pallet_treasury: Some(Default::default()),
         pallet_sudo: Some(SudoConfig { key: sudo_key }),
+        pallet_robonomics_liability: Some(liability),
     }
 }
 
@@ -288,3 +343,43 @@ pub fn development_config() -> ChainSpec {
         Default::default(),
     )
 }
+
+/// Liability market development config (single validator Alice)
+pub fn liability_dev_config() -> ChainSpec {
+    ChainSpec::from_genesis(
+        "Liability Development",
+        LIABILITY_DEV_ID,
+        ChainType::Development,
+        liability_dev_genesis,
+        vec![],
+        None,
+        None,
+        None,
+        Default::default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use robonomics_runtime::{Liability, System};
+    use sp_runtime::BuildStorage;
+
+    #[test]
+    fn test_liability_dev_genesis() {
+        let storage = liability_dev_config().build_storage().unwrap();
+        sp_io::TestExternalities::new(storage).execute_with(|| {
+            let (technics, _, promisee, promisor) = demo_liability();
+            let info = Liability::liability_info(0).unwrap();
+            assert_eq!(info.technics, technics);
+            assert_eq!(info.promisee, promisee);
+            assert_eq!(info.promisor, promisor);
+            assert!(Liability::liability_info(1).is_none());
+
+            let charlie = get_account_id_from_seed::<sr25519::Public>("Charlie");
+            assert_eq!(Liability::lighthouses(0), vec![charlie.clone()]);
+            assert_eq!(System::account(&charlie).data.reserved, 100 * XRT);
+            assert_eq!(Liability::market_fee(0), Perbill::from_percent(1));
+        });
+    }
+}
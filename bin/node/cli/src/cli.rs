@@ -34,6 +34,16 @@ pub struct Cli {
     #[structopt(flatten)]
     pub run: sc_cli::RunCmd,
 
+    /// IPFS gateway path of liability results, e.g. `http://127.0.0.1:8080/ipns/<robot key>`.
+    /// Enables off-chain worker that finalizes liabilities of local `liab` keys.
+    #[structopt(long, value_name = "URL")]
+    pub liability_ipfs_gateway: Option<String>,
+
+    /// Match liability market orders by off-chain worker when local `liab` key
+    /// is scheduled lighthouse of market.
+    #[structopt(long)]
+    pub liability_lighthouse: bool,
+
     /// Polkadot relaychain arguments.
     #[cfg(feature = "parachain")]
     #[structopt(raw = true)]
@@ -49,7 +59,39 @@ pub enum Subcommand {
     /// Robonomics Framework I/O operations.
     #[cfg(feature = "robonomics-cli")]
     Io(robonomics_cli::IoCmd),
+    /// Robonomics liability operations.
+    Liability(LiabilityCmd),
     /// Benchmarking runtime pallets.
     #[cfg(feature = "benchmarking-cli")]
     Benchmark(frame_benchmarking_cli::BenchmarkCmd),
 }
+
+/// Robonomics liability operations.
+#[derive(Debug, StructOpt)]
+pub struct LiabilityCmd {
+    /// Liability operation to run.
+    #[structopt(subcommand)]
+    pub subcommand: LiabilitySubcommand,
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub shared_params: sc_cli::SharedParams,
+}
+
+impl sc_cli::CliConfiguration for LiabilityCmd {
+    fn shared_params(&self) -> &sc_cli::SharedParams {
+        &self.shared_params
+    }
+}
+
+/// Possible liability subcommands.
+#[derive(Debug, StructOpt)]
+pub enum LiabilitySubcommand {
+    /// Liability operations using remote node.
+    #[cfg(feature = "robonomics-cli")]
+    #[structopt(flatten)]
+    Remote(robonomics_cli::LiabilityOperation),
+    /// Write liabilities at block of local database into JSON lines snapshot.
+    Export(crate::snapshot::ExportCmd),
+    /// Compare liabilities at block of local database with snapshot.
+    Verify(crate::snapshot::VerifyCmd),
+}
@@ -21,7 +21,7 @@ use crate::parachain;
 use crate::{
     chain_spec::*,
     service::{self, ipci, robonomics},
-    Cli, Subcommand,
+    Cli, LiabilitySubcommand, Subcommand,
 };
 use sc_cli::{ChainSpec, Role, RuntimeVersion, SubstrateCli};
 use sc_service::PartialComponents;
@@ -58,6 +58,7 @@ impl SubstrateCli for Cli {
     fn load_spec(&self, id: &str) -> std::result::Result<Box<dyn sc_service::ChainSpec>, String> {
         Ok(match id {
             "dev" => Box::new(development_config()),
+            "liability-dev" => Box::new(liability_dev_config()),
             "ipci" => Box::new(ipci_config()),
             #[cfg(feature = "parachain")]
             "" | "parachain" => Box::new(parachain::chain_spec::robonomics_parachain_config()),
@@ -94,9 +95,11 @@ pub fn run() -> sc_cli::Result<()> {
                 }
 
                 RobonomicsFamily::Development => {
-                    runner.run_node_until_exit(|config| match config.role {
+                    let liability_gateway = cli.liability_ipfs_gateway.clone();
+                    let liability_lighthouse = cli.liability_lighthouse;
+                    runner.run_node_until_exit(move |config| match config.role {
                         Role::Light => robonomics::new_light(config),
-                        _ => robonomics::new_full(config),
+                        _ => robonomics::new_full(config, liability_gateway, liability_lighthouse),
                     })
                 }
 
@@ -168,6 +171,45 @@ pub fn run() -> sc_cli::Result<()> {
             let runner = cli.create_runner(subcommand)?;
             runner.sync_run(|_| subcommand.run().map_err(|e| e.to_string().into()))
         }
+        Some(Subcommand::Liability(cmd)) => match &cmd.subcommand {
+            #[cfg(feature = "robonomics-cli")]
+            LiabilitySubcommand::Remote(subcommand) => {
+                let runner = cli.create_runner(cmd)?;
+                runner.sync_run(|_| subcommand.run().map_err(|e| e.to_string().into()))
+            }
+            LiabilitySubcommand::Export(subcommand) => {
+                let runner = cli.create_runner(subcommand)?;
+                match runner.config().chain_spec.family() {
+                    RobonomicsFamily::Development => runner.sync_run(|config| {
+                        let PartialComponents { client, .. } = service::new_partial::<
+                            robonomics_runtime::RuntimeApi,
+                            robonomics::Executor,
+                        >(&config)?;
+                        subcommand.run(client)
+                    }),
+                    _ => Err(format!(
+                        "chain spec {} has no liabilities",
+                        runner.config().chain_spec.id()
+                    ))?,
+                }
+            }
+            LiabilitySubcommand::Verify(subcommand) => {
+                let runner = cli.create_runner(subcommand)?;
+                match runner.config().chain_spec.family() {
+                    RobonomicsFamily::Development => runner.sync_run(|config| {
+                        let PartialComponents { client, .. } = service::new_partial::<
+                            robonomics_runtime::RuntimeApi,
+                            robonomics::Executor,
+                        >(&config)?;
+                        subcommand.run(client)
+                    }),
+                    _ => Err(format!(
+                        "chain spec {} has no liabilities",
+                        runner.config().chain_spec.id()
+                    ))?,
+                }
+            }
+        },
         #[cfg(feature = "benchmarking-cli")]
         Some(Subcommand::Benchmark(subcommand)) => {
             let runner = cli.create_runner(subcommand)?;
@@ -33,6 +33,8 @@ mod browser;
 mod cli;
 #[cfg(feature = "cli")]
 mod command;
+#[cfg(feature = "cli")]
+mod snapshot;
 
 #[cfg(feature = "browser")]
 pub use browser::*;
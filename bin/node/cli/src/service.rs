@@ -18,8 +18,8 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over Substrate service.
 
 use futures::prelude::*;
-use node_primitives::{AccountId, Balance, Block, Index};
-use sc_client_api::{ExecutorProvider, RemoteBackend};
+use node_primitives::{AccountId, Balance, Block, BlockNumber, Index};
+use sc_client_api::{Backend, ExecutorProvider, RemoteBackend};
 use sc_consensus_babe;
 use sc_finality_grandpa::{self as grandpa, FinalityProofProvider as GrandpaFinalityProofProvider};
 use sc_network::{Event, NetworkService};
@@ -29,7 +29,10 @@ use sc_service::{
     RpcHandlers, TaskManager,
 };
 use sp_api::ConstructRuntimeApi;
-use sp_core::traits::BareCryptoStorePtr;
+use sp_core::{
+    offchain::{OffchainStorage, STORAGE_PREFIX},
+    traits::BareCryptoStorePtr,
+};
 use sp_inherents::InherentDataProviders;
 use sp_runtime::traits::{BlakeTwo256, Block as BlockT};
 use std::sync::Arc;
@@ -51,7 +54,16 @@ pub trait RuntimeApiCollection:
     + sp_finality_grandpa::GrandpaApi<Block>
     + sp_block_builder::BlockBuilder<Block>
     + pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance>
-    + frame_system_rpc_runtime_api::AccountNonceApi<Block, AccountId, Index>
+    + pallet_robonomics_liability_rpc_runtime_api::LiabilityApi<
+        Block,
+        u64,
+        Vec<u8>,
+        (),
+        Vec<u8>,
+        AccountId,
+        BlockNumber,
+        Balance,
+    > + frame_system_rpc_runtime_api::AccountNonceApi<Block, AccountId, Index>
     + sp_api::Metadata<Block>
     + sp_offchain::OffchainWorkerApi<Block>
     + sp_session::SessionKeys<Block>
@@ -69,7 +81,16 @@ where
         + sp_finality_grandpa::GrandpaApi<Block>
         + sp_block_builder::BlockBuilder<Block>
         + pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance>
-        + frame_system_rpc_runtime_api::AccountNonceApi<Block, AccountId, Index>
+        + pallet_robonomics_liability_rpc_runtime_api::LiabilityApi<
+            Block,
+            u64,
+            Vec<u8>,
+            (),
+            Vec<u8>,
+            AccountId,
+            BlockNumber,
+            Balance,
+        > + frame_system_rpc_runtime_api::AccountNonceApi<Block, AccountId, Index>
         + sp_api::Metadata<Block>
         + sp_offchain::OffchainWorkerApi<Block>
         + sp_session::SessionKeys<Block>
@@ -166,6 +187,9 @@ where
         let pool = transaction_pool.clone();
         let select_chain = select_chain.clone();
         let keystore = keystore.clone();
+        let subscription_executor =
+            sc_rpc::SubscriptionTaskExecutor::new(task_manager.spawn_handle());
+        let offchain_storage = backend.offchain_storage();
 
         let rpc_extensions_builder = move |deny_unsafe| {
             let deps = node_rpc::FullDeps {
@@ -182,6 +206,8 @@ where
                     shared_voter_state: shared_voter_state.clone(),
                     shared_authority_set: shared_authority_set.clone(),
                 },
+                subscription_executor: subscription_executor.clone(),
+                offchain_storage: offchain_storage.clone(),
             };
 
             node_rpc::create_full(deps)
@@ -204,8 +230,14 @@ where
 }
 
 /// Creates a full service from the configuration.
+///
+/// Liability off-chain worker finalizes liabilities using results from `liability_gateway`,
+/// it's disabled when gateway isn't set. Market orders are matched by off-chain worker
+/// when `liability_lighthouse` is set.
 pub fn new_full_base<Runtime, Executor>(
     config: Configuration,
+    liability_gateway: Option<String>,
+    liability_lighthouse: bool,
 ) -> Result<
     (
         TaskManager,
@@ -260,6 +292,21 @@ where
         );
     }
 
+    if let Some(mut storage) = backend.offchain_storage() {
+        let key = pallet_robonomics_liability_rpc_runtime_api::OFFCHAIN_GATEWAY_KEY;
+        match liability_gateway {
+            Some(gateway) => storage.set(STORAGE_PREFIX, key, gateway.as_bytes()),
+            None => storage.remove(STORAGE_PREFIX, key),
+        }
+
+        let key = pallet_robonomics_liability_rpc_runtime_api::OFFCHAIN_LIGHTHOUSE_KEY;
+        if liability_lighthouse {
+            storage.set(STORAGE_PREFIX, key, &[1]);
+        } else {
+            storage.remove(STORAGE_PREFIX, key);
+        }
+    }
+
     let role = config.role.clone();
     let force_authoring = config.force_authoring;
     let name = config.network.node_name.clone();
@@ -267,6 +314,25 @@ where
     let prometheus_registry = config.prometheus_registry().cloned();
     let telemetry_connection_sinks = sc_service::TelemetryConnectionSinks::default();
 
+    if let Some(registry) = prometheus_registry.as_ref() {
+        match pallet_robonomics_liability_metrics::Metrics::register(registry) {
+            Ok(metrics) => task_manager.spawn_handle().spawn(
+                "liability-metrics",
+                pallet_robonomics_liability_metrics::run::<
+                    Block,
+                    _,
+                    u64,
+                    Vec<u8>,
+                    (),
+                    Vec<u8>,
+                    AccountId,
+                    Balance,
+                >(client.clone(), metrics),
+            ),
+            Err(e) => log::warn!("Unable to register liability metrics: {}", e),
+        }
+    }
+
     sc_service::spawn_tasks(sc_service::SpawnTasksParams {
         config,
         backend: backend.clone(),
@@ -316,7 +382,7 @@ where
     }
 
     // Spawn authority discovery module.
-    if matches!(role, Role::Authority{..} | Role::Sentry {..}) {
+    if matches!(role, Role::Authority { .. } | Role::Sentry { .. }) {
         let (sentries, authority_discovery_role) = match role {
             sc_service::config::Role::Authority { ref sentry_nodes } => (
                 sentry_nodes.clone(),
@@ -557,7 +623,7 @@ pub mod ipci {
 
     /// Create a new IPCI service for a full node.
     pub fn new_full(config: Configuration) -> Result<TaskManager> {
-        super::new_full_base::<RuntimeApi, Executor>(config)
+        super::new_full_base::<RuntimeApi, Executor>(config, None, false)
             .map(|(task_manager, _, _, _, _)| task_manager)
     }
 
@@ -589,9 +655,17 @@ pub mod robonomics {
     );
 
     /// Create a new Robonomics service for a full node.
-    pub fn new_full(config: Configuration) -> Result<TaskManager> {
-        super::new_full_base::<RuntimeApi, Executor>(config)
-            .map(|(task_manager, _, _, _, _)| task_manager)
+    pub fn new_full(
+        config: Configuration,
+        liability_gateway: Option<String>,
+        liability_lighthouse: bool,
+    ) -> Result<TaskManager> {
+        super::new_full_base::<RuntimeApi, Executor>(
+            config,
+            liability_gateway,
+            liability_lighthouse,
+        )
+        .map(|(task_manager, _, _, _, _)| task_manager)
     }
 
     pub fn new_light(config: Configuration) -> Result<TaskManager> {
@@ -0,0 +1,375 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Liability snapshot: all liabilities at block exported to file, e.g. before storage
+//! migration, and chain state verified against it after upgrade.
+//!
+//! Snapshot is JSON lines file: header `{"version":1,"block":..,"hash":".."}` is followed
+//! by liabilities in index order, one per line. So liabilities are read from chain state
+//! page by page and written or compared one by one, they aren't loaded into memory at once.
+
+use node_primitives::{AccountId, Balance, Block, BlockNumber};
+use pallet_robonomics_liability_rpc_runtime_api::{LiabilityApi, LiabilityInfo, LiabilityState};
+use sc_cli::{CliConfiguration, DatabaseParams, PruningParams, SharedParams};
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{crypto::Ss58Codec, H256};
+use sp_runtime::generic::BlockId;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Version of snapshot file schema.
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// Count of liabilities requested by one runtime call, runtime cuts pages to its cap.
+const PAGE_SIZE: u32 = 1024;
+
+/// Snapshot file header.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct Header {
+    /// Version of snapshot schema.
+    version: u32,
+    /// Number of exported block.
+    block: BlockNumber,
+    /// Hash of exported block.
+    hash: H256,
+}
+
+/// Snapshot liability entry.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct Entry {
+    /// Liability index.
+    index: u64,
+    /// Hex encoded technical parameter.
+    technics: String,
+    /// Hex encoded SCALE of economical parameter.
+    economics: String,
+    /// Promisee address.
+    promisee: String,
+    /// Promisor address.
+    promisor: String,
+    /// Lifecycle state.
+    state: LiabilityState,
+    /// Creation block, zero when it wasn't tracked.
+    created_at: BlockNumber,
+    /// Report block.
+    finalized_at: Option<BlockNumber>,
+    /// Hex encoded technical report.
+    report: Option<String>,
+}
+
+/// Liability snapshot export command.
+#[derive(Debug, structopt::StructOpt)]
+pub struct ExportCmd {
+    /// Number of exported block, the best block by default.
+    #[structopt(long, value_name = "BLOCK_NUMBER")]
+    at: Option<BlockNumber>,
+    /// Snapshot file.
+    #[structopt(long, value_name = "PATH", parse(from_os_str))]
+    out: PathBuf,
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    shared_params: SharedParams,
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pruning_params: PruningParams,
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    database_params: DatabaseParams,
+}
+
+/// Liability snapshot verification command.
+#[derive(Debug, structopt::StructOpt)]
+pub struct VerifyCmd {
+    /// Snapshot file.
+    #[structopt(long, value_name = "PATH", parse(from_os_str))]
+    snapshot: PathBuf,
+    /// Number of verified block, the best block by default.
+    #[structopt(long, value_name = "BLOCK_NUMBER")]
+    at: Option<BlockNumber>,
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    shared_params: SharedParams,
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pruning_params: PruningParams,
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    database_params: DatabaseParams,
+}
+
+impl CliConfiguration for ExportCmd {
+    fn shared_params(&self) -> &SharedParams {
+        &self.shared_params
+    }
+
+    fn pruning_params(&self) -> Option<&PruningParams> {
+        Some(&self.pruning_params)
+    }
+
+    fn database_params(&self) -> Option<&DatabaseParams> {
+        Some(&self.database_params)
+    }
+}
+
+impl CliConfiguration for VerifyCmd {
+    fn shared_params(&self) -> &SharedParams {
+        &self.shared_params
+    }
+
+    fn pruning_params(&self) -> Option<&PruningParams> {
+        Some(&self.pruning_params)
+    }
+
+    fn database_params(&self) -> Option<&DatabaseParams> {
+        Some(&self.database_params)
+    }
+}
+
+impl ExportCmd {
+    /// Write liabilities at block into snapshot file.
+    pub fn run<C>(&self, client: Arc<C>) -> sc_cli::Result<()>
+    where
+        C: ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+        C::Api: LiabilityApi<Block, u64, Vec<u8>, (), Vec<u8>, AccountId, BlockNumber, Balance>,
+    {
+        let (block, hash) = block(&*client, self.at)?;
+        let mut out = BufWriter::new(File::create(&self.out)?);
+        let header = Header {
+            version: SNAPSHOT_VERSION,
+            block,
+            hash,
+        };
+        write_line(&mut out, &header)?;
+
+        let mut count = 0;
+        for entry in entries(&*client, BlockId::hash(hash)) {
+            write_line(&mut out, &entry?)?;
+            count += 1;
+        }
+        out.flush()?;
+        println!(
+            "{} liabilities of block #{} ({}) exported to {}",
+            count,
+            block,
+            hash,
+            self.out.display()
+        );
+        Ok(())
+    }
+}
+
+impl VerifyCmd {
+    /// Compare liabilities at block with snapshot, prints every difference.
+    pub fn run<C>(&self, client: Arc<C>) -> sc_cli::Result<()>
+    where
+        C: ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+        C::Api: LiabilityApi<Block, u64, Vec<u8>, (), Vec<u8>, AccountId, BlockNumber, Balance>,
+    {
+        let path = self.snapshot.display();
+        let mut lines = BufReader::new(File::open(&self.snapshot)?).lines();
+        let header: Header = match lines.next() {
+            Some(line) => {
+                parse_line(&line?).map_err(|e| format!("bad snapshot header of {}: {}", path, e))?
+            }
+            None => Err(format!("snapshot {} is empty", path))?,
+        };
+        if header.version != SNAPSHOT_VERSION {
+            Err(format!(
+                "snapshot {} version {} isn't supported",
+                path, header.version
+            ))?
+        }
+
+        let (block, hash) = block(&*client, self.at)?;
+        let mut expected = lines
+            .enumerate()
+            .map(|(line, entry)| -> sc_cli::Result<Entry> {
+                Ok(parse_line(&entry?)
+                    .map_err(|e| format!("bad snapshot entry at line {}: {}", line + 2, e))?)
+            });
+        let mut actual = entries(&*client, BlockId::hash(hash));
+
+        let mut diff = Diff::default();
+        let mut next_expected = expected.next().transpose()?;
+        let mut next_actual = actual.next().transpose()?;
+        loop {
+            // Both sides are ordered by index, so they're merged
+            let (exp, act) = match (next_expected.take(), next_actual.take()) {
+                (None, None) => break,
+                (exp, act) => (exp, act),
+            };
+            let exp_index = exp.as_ref().map(|entry| entry.index);
+            let act_index = act.as_ref().map(|entry| entry.index);
+            if exp_index == act_index {
+                diff.compared += 1;
+                if exp != act {
+                    diff.changed += 1;
+                    print_diff("changed", exp_index, exp.as_ref(), act.as_ref());
+                }
+                next_expected = expected.next().transpose()?;
+                next_actual = actual.next().transpose()?;
+            } else if act_index.map_or(true, |act| exp_index.map_or(false, |exp| exp < act)) {
+                diff.missing += 1;
+                print_diff("missing", exp_index, exp.as_ref(), None);
+                next_expected = expected.next().transpose()?;
+                next_actual = act;
+            } else {
+                diff.added += 1;
+                print_diff("added", act_index, None, act.as_ref());
+                next_expected = exp;
+                next_actual = actual.next().transpose()?;
+            }
+        }
+
+        println!(
+            "snapshot of block #{} ({}) against block #{} ({}): \
+             {} equal, {} changed, {} missing, {} added",
+            header.block,
+            header.hash,
+            block,
+            hash,
+            diff.compared - diff.changed,
+            diff.changed,
+            diff.missing,
+            diff.added,
+        );
+        if diff.changed + diff.missing + diff.added > 0 {
+            Err("liabilities don't match snapshot")?
+        }
+        Ok(())
+    }
+}
+
+/// Counts of compared liabilities.
+#[derive(Default)]
+struct Diff {
+    compared: u64,
+    changed: u64,
+    missing: u64,
+    added: u64,
+}
+
+fn print_diff(kind: &str, index: Option<u64>, snapshot: Option<&Entry>, state: Option<&Entry>) {
+    println!(
+        "{}",
+        serde_json::json!({
+            "diff": kind,
+            "index": index,
+            "snapshot": snapshot,
+            "state": state,
+        })
+    );
+}
+
+/// Number and hash of given block, the best block when it isn't set.
+fn block<C: HeaderBackend<Block>>(
+    client: &C,
+    at: Option<BlockNumber>,
+) -> sc_cli::Result<(BlockNumber, H256)> {
+    match at {
+        None => {
+            let info = client.info();
+            Ok((info.best_number, info.best_hash))
+        }
+        Some(number) => {
+            let hash = client
+                .hash(number)
+                .map_err(|e| format!("unable to read block #{}: {}", number, e))?
+                .ok_or_else(|| format!("block #{} isn't found", number))?;
+            Ok((number, hash))
+        }
+    }
+}
+
+/// Liabilities of block in index order, they're read page by page.
+fn entries<'a, C>(
+    client: &'a C,
+    at: BlockId<Block>,
+) -> impl Iterator<Item = sc_cli::Result<Entry>> + 'a
+where
+    C: ProvideRuntimeApi<Block>,
+    C::Api: LiabilityApi<Block, u64, Vec<u8>, (), Vec<u8>, AccountId, BlockNumber, Balance>,
+{
+    let mut page = Vec::new().into_iter();
+    let mut next = Some(0);
+    std::iter::from_fn(move || loop {
+        if let Some((index, info)) = page.next() {
+            return Some(entry(client, &at, index, info));
+        }
+        let start = next.take()?;
+        match client.runtime_api().list(&at, start, PAGE_SIZE) {
+            Ok(list) => {
+                if list.has_more {
+                    next = Some(list.next);
+                }
+                page = list.items.into_iter();
+            }
+            Err(e) => return Some(Err(format!("unable to list liabilities: {:?}", e).into())),
+        }
+    })
+}
+
+fn entry<C>(
+    client: &C,
+    at: &BlockId<Block>,
+    index: u64,
+    info: LiabilityInfo<Vec<u8>, (), AccountId, BlockNumber, Balance>,
+) -> sc_cli::Result<Entry>
+where
+    C: ProvideRuntimeApi<Block>,
+    C::Api: LiabilityApi<Block, u64, Vec<u8>, (), Vec<u8>, AccountId, BlockNumber, Balance>,
+{
+    let report = match info.state {
+        LiabilityState::Open | LiabilityState::Imported => None,
+        LiabilityState::Finalized => client
+            .runtime_api()
+            .get_report(at, index)
+            .map_err(|e| format!("unable to read report of liability {}: {:?}", index, e))?
+            .map(|report| hex_string(&report.report)),
+    };
+    Ok(Entry {
+        index,
+        technics: hex_string(&info.technics),
+        economics: hex_string(&codec::Encode::encode(&info.economics)),
+        promisee: info.promisee.to_ss58check(),
+        promisor: info.promisor.to_ss58check(),
+        state: info.state,
+        created_at: info.created_at,
+        finalized_at: info.finalized_at,
+        report,
+    })
+}
+
+fn hex_string(data: &[u8]) -> String {
+    format!("0x{}", hex::encode(data))
+}
+
+fn write_line<T: Serialize>(out: &mut impl Write, value: &T) -> sc_cli::Result<()> {
+    serde_json::to_writer(&mut *out, value).map_err(|e| e.to_string())?;
+    writeln!(out)?;
+    Ok(())
+}
+
+fn parse_line<T: serde::de::DeserializeOwned>(line: &str) -> Result<T, String> {
+    serde_json::from_str(line).map_err(|e| e.to_string())
+}
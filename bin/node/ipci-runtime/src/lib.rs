@@ -46,12 +46,18 @@ use pallet_grandpa::{
     fg_primitives, AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList,
 };
 use pallet_im_online::sr25519::AuthorityId as ImOnlineId;
+use pallet_robonomics_liability_rpc_runtime_api::{
+    AccountStats, CostInfo, CreatedBetween, EraStats, EventPayload, FinalizationLeaf,
+    LiabilityConstants, LiabilityCounters, LiabilityEvent, LiabilityInfo, LiabilityTimeline,
+    MarketVolume, MatchInfo, MmrProof, ModelRanking, ModelStats, OrderBookDepth, OrderBookEntry,
+    Page, Payouts, ReportInfo, Tag, Transition,
+};
 use pallet_session::historical as pallet_session_historical;
 use pallet_transaction_payment::{Multiplier, TargetedFeeAdjustment};
 use pallet_transaction_payment_rpc_runtime_api::RuntimeDispatchInfo;
 use sp_api::impl_runtime_apis;
 use sp_authority_discovery::AuthorityId as AuthorityDiscoveryId;
-use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
+use sp_core::{crypto::KeyTypeId, OpaqueMetadata, H160};
 use sp_inherents::{CheckInherentsResult, InherentData};
 use sp_runtime::curve::PiecewiseLinear;
 use sp_runtime::traits::{
@@ -431,9 +437,16 @@ impl pallet_offences::Trait for Runtime {
     type WeightInfo = ();
 }
 
+parameter_types! {
+    pub const DatalogWindowSize: u64 = 128;
+    pub const DatalogMaximumRecordLength: u32 = 512;
+}
+
 impl pallet_robonomics_datalog::Trait for Runtime {
     type Time = Timestamp;
     type Record = Vec<u8>;
+    type WindowSize = DatalogWindowSize;
+    type MaximumRecordLength = DatalogMaximumRecordLength;
     type Event = Event;
 }
 
@@ -731,6 +744,180 @@ impl_runtime_apis! {
         }
     }
 
+    // DAO IPCI has no liability module, this implementation keeps node services
+    // shared with Robonomics runtime and always returns nothing.
+    impl pallet_robonomics_liability_rpc_runtime_api::LiabilityApi<
+        Block,
+        u64,
+        Vec<u8>,
+        (),
+        Vec<u8>,
+        AccountId,
+        BlockNumber,
+        Balance,
+    > for Runtime {
+        fn get_liability(_index: u64) -> Option<LiabilityInfo<Vec<u8>, (), AccountId, BlockNumber, Balance>> {
+            None
+        }
+
+        fn get_report(_index: u64) -> Option<ReportInfo<Vec<u8>, BlockNumber>> {
+            None
+        }
+
+        fn list(start: u64, _count: u32) -> Page<u64, (u64, LiabilityInfo<Vec<u8>, (), AccountId, BlockNumber, Balance>)> {
+            Page {
+                items: Vec::new(),
+                next: start,
+                has_more: false,
+            }
+        }
+
+        fn list_by_account(
+            _account: AccountId,
+            start: u64,
+            _count: u32,
+        ) -> Page<u64, (u64, LiabilityInfo<Vec<u8>, (), AccountId, BlockNumber, Balance>)> {
+            Page {
+                items: Vec::new(),
+                next: start,
+                has_more: false,
+            }
+        }
+
+        fn list_by_promisor(
+            _promisor: AccountId,
+            start: u64,
+            _count: u32,
+        ) -> Page<u64, (u64, LiabilityInfo<Vec<u8>, (), AccountId, BlockNumber, Balance>)> {
+            Page {
+                items: Vec::new(),
+                next: start,
+                has_more: false,
+            }
+        }
+
+        fn list_by_tag(
+            _tag: Tag,
+            start: u64,
+            _count: u32,
+        ) -> Page<u64, (u64, LiabilityInfo<Vec<u8>, (), AccountId, BlockNumber, Balance>)> {
+            Page {
+                items: Vec::new(),
+                next: start,
+                has_more: false,
+            }
+        }
+
+        fn constants() -> LiabilityConstants<BlockNumber, Balance> {
+            Default::default()
+        }
+
+        fn estimate_cost(economics: ()) -> CostInfo<()> {
+            CostInfo {
+                create_weight: 0,
+                finalize_weight: 0,
+                escrow: economics,
+            }
+        }
+
+        fn list_created_between(
+            _from: BlockNumber,
+            _to: BlockNumber,
+            _count: u32,
+        ) -> CreatedBetween<u64, BlockNumber> {
+            CreatedBetween {
+                indices: Vec::new(),
+                truncated: false,
+                next: None,
+            }
+        }
+
+        fn history(_index: u64) -> Vec<(BlockNumber, Transition<AccountId>)> {
+            Vec::new()
+        }
+
+        fn counters() -> LiabilityCounters<Balance> {
+            Default::default()
+        }
+
+        fn account_stats(_account: AccountId) -> AccountStats<Balance> {
+            Default::default()
+        }
+
+        fn block_events() -> Vec<LiabilityEvent<u64, (), AccountId, BlockNumber>> {
+            Vec::new()
+        }
+
+        fn event_payload(_index: u64, _hash: Hash) -> Option<EventPayload<Vec<u8>, Vec<u8>>> {
+            None
+        }
+
+        fn best_ask(_market: u32) -> Option<OrderBookEntry<Balance>> {
+            None
+        }
+
+        fn best_bid(_market: u32) -> Option<OrderBookEntry<Balance>> {
+            None
+        }
+
+        fn depth(_market: u32, _levels: u32) -> OrderBookDepth<Balance> {
+            OrderBookDepth {
+                asks: Vec::new(),
+                bids: Vec::new(),
+            }
+        }
+
+        fn recent_matches(_market: u32, _count: u32) -> Vec<MatchInfo<u64, BlockNumber, Balance>> {
+            Vec::new()
+        }
+
+        fn volume(_market: u32, _blocks: BlockNumber) -> MarketVolume<BlockNumber, Balance> {
+            Default::default()
+        }
+
+        fn era_stats(_era: u32) -> Option<EraStats<BlockNumber, Balance>> {
+            None
+        }
+
+        fn timeline(_index: u64) -> Option<LiabilityTimeline<BlockNumber>> {
+            None
+        }
+
+        fn finalization_proof(_index: u64) -> Option<(FinalizationLeaf<u64, AccountId>, MmrProof)> {
+            None
+        }
+
+        fn preview(_index: u64, _success: bool) -> Option<Payouts<AccountId, Balance>> {
+            None
+        }
+
+        fn model_stats(_model: Hash) -> ModelStats<Balance> {
+            Default::default()
+        }
+
+        fn top_models(_by: ModelRanking, _count: u32) -> Vec<(Hash, ModelStats<Balance>)> {
+            Vec::new()
+        }
+
+        fn imported_from(_index: u64) -> Option<H160> {
+            None
+        }
+    }
+
+    impl pallet_robonomics_datalog_runtime_api::DatalogApi<
+        Block,
+        AccountId,
+        BlockNumber,
+        Moment,
+        Vec<u8>,
+    > for Runtime {
+        fn window(
+            account: AccountId,
+        ) -> Vec<pallet_robonomics_datalog_runtime_api::DatalogItem<BlockNumber, Moment, Vec<u8>>> {
+            Datalog::datalog(&account)
+        }
+    }
+
     impl sp_session::SessionKeys<Block> for Runtime {
         fn generate_session_keys(seed: Option<Vec<u8>>) -> Vec<u8> {
             SessionKeys::generate(seed)
@@ -55,6 +55,12 @@ use pallet_grandpa::{
     fg_primitives, AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList,
 };
 use pallet_im_online::sr25519::AuthorityId as ImOnlineId;
+use pallet_robonomics_liability::{
+    AccountStats, CostInfo, CreatedBetween, DryRun, EraStats, EventPayload, FinalizationLeaf,
+    LiabilityConstants, LiabilityCounters, LiabilityEvent, LiabilityInfo, LiabilityTimeline,
+    MarketVolume, MatchInfo, MmrProof, ModelRanking, ModelStats, OrderBookDepth, OrderBookEntry, Page, Payouts,
+    ReportInfo, Tag, Transition,
+};
 use pallet_session::historical as pallet_session_historical;
 use pallet_transaction_payment::{Multiplier, TargetedFeeAdjustment};
 use pallet_transaction_payment_rpc_runtime_api::RuntimeDispatchInfo;
@@ -63,7 +69,7 @@ use sp_authority_discovery::AuthorityId as AuthorityDiscoveryId;
 use sp_core::{
     crypto::KeyTypeId,
     u32_trait::{_2, _4},
-    OpaqueMetadata,
+    OpaqueMetadata, H160,
 };
 use sp_inherents::{CheckInherentsResult, InherentData};
 use sp_runtime::curve::PiecewiseLinear;
@@ -75,10 +81,10 @@ use sp_runtime::transaction_validity::{
     TransactionPriority, TransactionSource, TransactionValidity,
 };
 use sp_runtime::{
-    create_runtime_str, generic, impl_opaque_keys, ApplyExtrinsicResult, FixedPointNumber,
-    ModuleId, Perbill, Percent, Permill, Perquintill,
+    create_runtime_str, generic, impl_opaque_keys, ApplyExtrinsicResult, DispatchError,
+    FixedPointNumber, ModuleId, Perbill, Percent, Permill, Perquintill,
 };
-use sp_std::prelude::*;
+use sp_std::{convert::TryFrom, prelude::*};
 #[cfg(feature = "std")]
 use sp_version::NativeVersion;
 use sp_version::RuntimeVersion;
@@ -94,10 +100,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
     // and set impl_version to equal spec_version. If only runtime
     // implementation changes and behavior does not, then leave spec_version as
     // is and increment impl_version.
-    spec_version: 1,
-    impl_version: 1,
+    spec_version: 3,
+    impl_version: 3,
     apis: RUNTIME_API_VERSIONS,
-    transaction_version: 1,
+    transaction_version: 2,
 };
 
 /// The version infromation used to identify this runtime when compiled natively.
@@ -540,8 +546,157 @@ impl pallet_elections_phragmen::Trait for Runtime {
     type WeightInfo = ();
 }
 
+parameter_types! {
+    pub const LiabilityStatsPeriod: BlockNumber = 1 * HOURS;
+    pub const LiabilityStatsEraLength: BlockNumber = 1 * DAYS;
+    pub const LiabilityStatsRetention: u32 = 90;
+    pub const LiabilityCreatedRetention: BlockNumber = 7 * DAYS;
+    pub const LiabilityDigestBloomBits: u32 = 1024;
+    pub const LiabilityDigestBloomHashes: u8 = 11;
+    pub const LiabilityMaxUnsignedPerBlock: u32 = 256;
+    pub const LiabilityMaxPairPerBlock: u32 = 4;
+    pub const LiabilityMaxTechnicsLength: u32 = 512;
+    pub const LiabilityMaxReportLength: u32 = 512;
+    pub const LiabilityMaxTags: u32 = 4;
+    pub const LiabilityMaxTaggedLiabilities: u32 = 1_000;
+    pub const LiabilityMaxAllowedPromisors: u32 = 16;
+    pub const LiabilityMaxHooksWeight: Weight = WEIGHT_PER_SECOND / 20;
+    pub const LiabilityPromisorWhitelist: bool = false;
+    pub const LiabilityIdentityThreshold: Balance = 100 * XRT;
+    pub const LiabilityMinForceFinalizeAge: BlockNumber = 7 * DAYS;
+    pub const LiabilityMinReportDelay: BlockNumber = 1;
+    pub const LiabilityOrderDeposit: Balance = 1 * XRT;
+    pub const LiabilityMaxOrdersPerMarket: u32 = 64;
+    pub const LiabilityMaxOpenOrdersPerAccount: u32 = 32;
+    pub const LiabilityReportPreimageDeposit: Balance = 1 * XRT;
+    pub const LiabilityReportChallengeWindow: BlockNumber = 7 * DAYS;
+    pub const LiabilityMinFillQuantity: u32 = 1;
+    pub const LiabilityExpiredOrderSlash: Perbill = Perbill::from_percent(5);
+    pub const LiabilityMaxSweepItemsPerBlock: u32 = 64;
+    pub const LiabilityMaxRecentMatches: u32 = 256;
+    pub const LiabilityLighthouseMinStake: Balance = 100 * XRT;
+    pub const LiabilityMaxLighthouses: u32 = 16;
+    pub const LiabilityLighthouseSlot: BlockNumber = 10 * MINUTES;
+    pub const LiabilityLighthouseFeeShare: Perbill = Perbill::from_percent(20);
+    pub const LiabilityMatchCommitWindow: BlockNumber = 1 * MINUTES;
+    pub const LiabilityMaxMatchCommits: u32 = 16;
+    pub const LiabilityBreachClaimPeriod: BlockNumber = 7 * DAYS;
+    pub const LiabilityOrderBreachSlash: Balance = 10 * XRT;
+    pub const LiabilityFundingDeadline: BlockNumber = 1 * DAYS;
+    pub const LiabilityRevealWindow: BlockNumber = 1 * HOURS;
+    pub const LiabilityClaimTolerance: BlockNumber = 10 * MINUTES;
+    pub const LiabilityWitnessMinStake: Balance = 100 * XRT;
+    pub const LiabilityMaxWitnesses: u32 = 16;
+    pub const LiabilityWitnessSlash: Balance = 50 * XRT;
+    pub const LiabilityPoolMinStake: Balance = 10 * XRT;
+    pub const LiabilityMaxPoolSize: u32 = 64;
+    pub const LiabilityAssignmentDelay: BlockNumber = 1 * MINUTES;
+    pub const LiabilityAcknowledgementWindow: BlockNumber = 1 * HOURS;
+    pub const LiabilityAcknowledgementDeposit: Balance = 1 * XRT;
+    pub const LiabilityMaxLiabilityLifetime: BlockNumber = 365 * DAYS;
+    pub const LiabilityExpiryIncentive: Balance = 100 * GLUSHKOV;
+    pub const LiabilityZeroValueDeposit: Balance = 100 * GLUSHKOV;
+    pub const LiabilityMaxZeroValuePerEra: u32 = 100;
+    pub const LiabilityValueFee: Perbill = Perbill::from_perthousand(1);
+    pub const LiabilityMinValueFee: Balance = 1 * GLUSHKOV;
+    pub const LiabilityMaxValueFee: Balance = 10 * XRT;
+    pub const LiabilityCreatePriority: TransactionPriority = TransactionPriority::max_value() / 8;
+    pub const LiabilityFinalizePriority: TransactionPriority = TransactionPriority::max_value() / 4;
+}
+
+/// Accounts with identity judged reasonable or known good by any registrar.
+pub struct JudgedIdentity;
+impl pallet_robonomics_liability::traits::IdentityVerifier<AccountId> for JudgedIdentity {
+    fn is_verified(who: &AccountId) -> bool {
+        Identity::identity(who).map_or(false, |registration| {
+            registration.judgements.iter().any(|(_, judgement)| {
+                matches!(
+                    judgement,
+                    pallet_identity::Judgement::Reasonable | pallet_identity::Judgement::KnownGood
+                )
+            })
+        })
+    }
+}
+
 impl pallet_robonomics_liability::Trait for Runtime {
     type Event = Event;
+    type Balance = Balance;
+    type SettledValue = pallet_robonomics_liability::economics::Priceless;
+    type StatsPeriod = LiabilityStatsPeriod;
+    type StatsEraLength = LiabilityStatsEraLength;
+    type StatsRetention = LiabilityStatsRetention;
+    type CreatedRetention = LiabilityCreatedRetention;
+    type DigestBloomBits = LiabilityDigestBloomBits;
+    type DigestBloomHashes = LiabilityDigestBloomHashes;
+    type MaxUnsignedPerBlock = LiabilityMaxUnsignedPerBlock;
+    type MaxPairPerBlock = LiabilityMaxPairPerBlock;
+    type MaxTechnicsLength = LiabilityMaxTechnicsLength;
+    type MaxReportLength = LiabilityMaxReportLength;
+    type MaxTags = LiabilityMaxTags;
+    type MaxTaggedLiabilities = LiabilityMaxTaggedLiabilities;
+    type MaxAllowedPromisors = LiabilityMaxAllowedPromisors;
+    type PromisorWhitelist = LiabilityPromisorWhitelist;
+    type AdminOrigin = frame_system::EnsureRoot<AccountId>;
+    type IdentityVerifier = JudgedIdentity;
+    type IdentityThreshold = LiabilityIdentityThreshold;
+    type ReputationProvider = ();
+    type Robots = ();
+    type MinForceFinalizeAge = LiabilityMinForceFinalizeAge;
+    type MinReportDelay = LiabilityMinReportDelay;
+    type Currency = Balances;
+    type OrderDeposit = LiabilityOrderDeposit;
+    type MaxOrdersPerMarket = LiabilityMaxOrdersPerMarket;
+    type MaxOpenOrdersPerAccount = LiabilityMaxOpenOrdersPerAccount;
+    type ReportPreimageDeposit = LiabilityReportPreimageDeposit;
+    type ReportChallengeWindow = LiabilityReportChallengeWindow;
+    type MinFillQuantity = LiabilityMinFillQuantity;
+    type ExpiredOrderSlash = LiabilityExpiredOrderSlash;
+    type MaxSweepItemsPerBlock = LiabilityMaxSweepItemsPerBlock;
+    type MaxRecentMatches = LiabilityMaxRecentMatches;
+    // Matching is unpaid while work is free
+    type MatchingReward = pallet_robonomics_liability::economics::Priceless;
+    type Market = pallet_robonomics_liability::market::SingleMarket;
+    type LighthouseMinStake = LiabilityLighthouseMinStake;
+    type MaxLighthouses = LiabilityMaxLighthouses;
+    type LighthouseSlot = LiabilityLighthouseSlot;
+    type LighthouseFeeShare = LiabilityLighthouseFeeShare;
+    type MatchCommitWindow = LiabilityMatchCommitWindow;
+    type MaxMatchCommits = LiabilityMaxMatchCommits;
+    type MarketFeeDestination = Treasury;
+    // Communism has no value, `ChargeLiabilityFee` is left out of `SignedExtra`
+    type ValueFee = LiabilityValueFee;
+    type MinValueFee = LiabilityMinValueFee;
+    type MaxValueFee = LiabilityMaxValueFee;
+    type BreachClaimPeriod = LiabilityBreachClaimPeriod;
+    type OrderBreachSlash = LiabilityOrderBreachSlash;
+    type FundingDeadline = LiabilityFundingDeadline;
+    type RevealWindow = LiabilityRevealWindow;
+    type ClaimTolerance = LiabilityClaimTolerance;
+    type WitnessMinStake = LiabilityWitnessMinStake;
+    type MaxWitnesses = LiabilityMaxWitnesses;
+    type WitnessSlash = LiabilityWitnessSlash;
+    // Epoch randomness of BABE is known in advance, promisee could predict selection
+    type Randomness = RandomnessCollectiveFlip;
+    type PoolMinStake = LiabilityPoolMinStake;
+    type MaxPoolSize = LiabilityMaxPoolSize;
+    type AssignmentDelay = LiabilityAssignmentDelay;
+    type AcknowledgementWindow = LiabilityAcknowledgementWindow;
+    type AcknowledgementDeposit = LiabilityAcknowledgementDeposit;
+    type MaxLiabilityLifetime = LiabilityMaxLiabilityLifetime;
+    type ExpiryIncentive = LiabilityExpiryIncentive;
+    type ZeroValueDeposit = LiabilityZeroValueDeposit;
+    type MaxZeroValuePerEra = LiabilityMaxZeroValuePerEra;
+    type CreatePriority = LiabilityCreatePriority;
+    type FinalizePriority = LiabilityFinalizePriority;
+    // Current message broker has no XCM `Transact`, remote liabilities are disabled
+    type RemoteOrigin = frame_system::EnsureNever<AccountId>;
+    type BridgeOrigin = frame_system::EnsureRoot<AccountId>;
+    type Legacy = pallet_robonomics_liability::legacy::LegacyObjective;
+    type AuthorityId = pallet_robonomics_liability::crypto::ReportAuthId;
+    type Hooks = ();
+    type MaxHooksWeight = LiabilityMaxHooksWeight;
+    type WeightInfo = pallet_robonomics_liability::weights::SubstrateWeight<Runtime>;
     type Technics = pallet_robonomics_liability::technics::PureIPFS;
     type Economics = pallet_robonomics_liability::economics::Communism;
     type Liability = pallet_robonomics_liability::signed::SignedLiability<
@@ -553,14 +708,58 @@ impl pallet_robonomics_liability::Trait for Runtime {
     >;
 }
 
+parameter_types! {
+    pub const DatalogWindowSize: u64 = 128;
+    pub const DatalogMaximumRecordLength: u32 = 512;
+}
+
 impl pallet_robonomics_datalog::Trait for Runtime {
     type Time = Timestamp;
     type Record = Vec<u8>;
+    type WindowSize = DatalogWindowSize;
+    type MaximumRecordLength = DatalogMaximumRecordLength;
     type Event = Event;
 }
 
+parameter_types! {
+    pub const LaunchFee: Balance = 1 * GLUSHKOV;
+    pub const MinLaunchInterval: BlockNumber = 1;
+}
+
 impl pallet_robonomics_launch::Trait for Runtime {
     type Parameter = bool;
+    type Currency = Balances;
+    type LaunchFee = LaunchFee;
+    type FeeDestination = Treasury;
+    type MinLaunchInterval = MinLaunchInterval;
+    type Event = Event;
+}
+
+parameter_types! {
+    pub const RwsSubscriptionPrice: Balance = 10 * XRT;
+    pub const RwsSubscriptionPeriod: pallet_robonomics_rws::EraIndex = 30 * 24;
+    pub const RwsEraLength: BlockNumber = 1 * HOURS;
+    pub const RwsWeightQuota: Weight = WEIGHT_PER_SECOND;
+    pub const RwsMaxDevices: u32 = 64;
+}
+
+/// Liability calls are free for devices of RWS subscription.
+pub struct LiabilityCalls;
+impl frame_support::traits::Filter<Call> for LiabilityCalls {
+    fn filter(call: &Call) -> bool {
+        matches!(call, Call::Liability(_))
+    }
+}
+
+impl pallet_robonomics_rws::Trait for Runtime {
+    type Currency = Balances;
+    type SubscriptionPrice = RwsSubscriptionPrice;
+    type SubscriptionPeriod = RwsSubscriptionPeriod;
+    type PaymentDestination = Treasury;
+    type EraLength = RwsEraLength;
+    type WeightQuota = RwsWeightQuota;
+    type MaxDevices = RwsMaxDevices;
+    type FeelessCall = LiabilityCalls;
     type Event = Event;
 }
 
@@ -595,7 +794,7 @@ where
             frame_system::CheckEra::<Runtime>::from(generic::Era::mortal(period, current_block)),
             frame_system::CheckNonce::<Runtime>::from(nonce),
             frame_system::CheckWeight::<Runtime>::new(),
-            pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(tip),
+            pallet_robonomics_rws::ChargeRwsPayment::<Runtime>::from(tip),
         );
         let raw_payload = SignedPayload::new(call, extra)
             .map_err(|e| {
@@ -662,9 +861,10 @@ construct_runtime!(
         Scheduler: pallet_scheduler::{Module, Call, Storage, Event<T>},
 
         // Robonomics Network modules.
-        Liability: pallet_robonomics_liability::{Module, Call, Storage, Event<T>, ValidateUnsigned},
+        Liability: pallet_robonomics_liability::{Module, Call, Storage, Event<T>, Config<T>, ValidateUnsigned},
         Datalog: pallet_robonomics_datalog::{Module, Call, Storage, Event<T>},
         Launch: pallet_robonomics_launch::{Module, Call, Storage, Event<T>},
+        Rws: pallet_robonomics_rws::{Module, Call, Storage, Event<T>},
 
         // Sudo. Usable initially.
         Sudo: pallet_sudo::{Module, Call, Storage, Event<T>, Config<T>},
@@ -694,7 +894,7 @@ pub type SignedExtra = (
     frame_system::CheckEra<Runtime>,
     frame_system::CheckNonce<Runtime>,
     frame_system::CheckWeight<Runtime>,
-    pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+    pallet_robonomics_rws::ChargeRwsPayment<Runtime>,
 );
 
 /// Unchecked extrinsic type as expected by this runtime.
@@ -866,6 +1066,193 @@ impl_runtime_apis! {
         }
     }
 
+    impl pallet_robonomics_liability_rpc_runtime_api::LiabilityApi<
+        Block,
+        u64,
+        Vec<u8>,
+        (),
+        Vec<u8>,
+        AccountId,
+        BlockNumber,
+        Balance,
+    > for Runtime {
+        fn get_liability(index: u64) -> Option<LiabilityInfo<Vec<u8>, (), AccountId, BlockNumber, Balance>> {
+            Liability::liability_info(index)
+        }
+
+        fn get_report(index: u64) -> Option<ReportInfo<Vec<u8>, BlockNumber>> {
+            Liability::report_info(index)
+        }
+
+        fn list(start: u64, count: u32) -> Page<u64, (u64, LiabilityInfo<Vec<u8>, (), AccountId, BlockNumber, Balance>)> {
+            Liability::liability_list(start, count)
+        }
+
+        fn list_by_account(
+            account: AccountId,
+            start: u64,
+            count: u32,
+        ) -> Page<u64, (u64, LiabilityInfo<Vec<u8>, (), AccountId, BlockNumber, Balance>)> {
+            Liability::account_liability_list(&account, start, count)
+        }
+
+        fn list_by_promisor(
+            promisor: AccountId,
+            start: u64,
+            count: u32,
+        ) -> Page<u64, (u64, LiabilityInfo<Vec<u8>, (), AccountId, BlockNumber, Balance>)> {
+            Liability::promisor_liability_list(&promisor, start, count)
+        }
+
+        fn list_by_tag(
+            tag: Tag,
+            start: u64,
+            count: u32,
+        ) -> Page<u64, (u64, LiabilityInfo<Vec<u8>, (), AccountId, BlockNumber, Balance>)> {
+            Liability::tag_liability_list(tag, start, count)
+        }
+
+        fn constants() -> LiabilityConstants<BlockNumber, Balance> {
+            Liability::constants()
+        }
+
+        fn estimate_cost(economics: ()) -> CostInfo<()> {
+            Liability::estimate_cost(economics)
+        }
+
+        fn list_created_between(
+            from: BlockNumber,
+            to: BlockNumber,
+            count: u32,
+        ) -> CreatedBetween<u64, BlockNumber> {
+            Liability::created_between(from, to, count)
+        }
+
+        fn history(index: u64) -> Vec<(BlockNumber, Transition<AccountId>)> {
+            Liability::history_of(index)
+        }
+
+        fn counters() -> LiabilityCounters<Balance> {
+            Liability::counters()
+        }
+
+        fn account_stats(account: AccountId) -> AccountStats<Balance> {
+            Liability::stats_of(account)
+        }
+
+        fn block_events() -> Vec<LiabilityEvent<u64, (), AccountId, BlockNumber>> {
+            System::events()
+                .into_iter()
+                .filter_map(|record| match record.event {
+                    Event::pallet_robonomics_liability(event) => LiabilityEvent::try_from(event).ok(),
+                    _ => None,
+                })
+                .collect()
+        }
+
+        fn event_payload(index: u64, hash: Hash) -> Option<EventPayload<Vec<u8>, Vec<u8>>> {
+            Liability::event_payload(index, hash)
+        }
+
+        fn best_ask(market: u32) -> Option<OrderBookEntry<Balance>> {
+            Liability::best_ask(market)
+        }
+
+        fn best_bid(market: u32) -> Option<OrderBookEntry<Balance>> {
+            Liability::best_bid(market)
+        }
+
+        fn depth(market: u32, levels: u32) -> OrderBookDepth<Balance> {
+            Liability::depth(market, levels)
+        }
+
+        fn recent_matches(market: u32, count: u32) -> Vec<MatchInfo<u64, BlockNumber, Balance>> {
+            Liability::recent_matches(market, count)
+        }
+
+        fn volume(market: u32, blocks: BlockNumber) -> MarketVolume<BlockNumber, Balance> {
+            Liability::volume(market, blocks)
+        }
+
+        fn era_stats(era: u32) -> Option<EraStats<BlockNumber, Balance>> {
+            Liability::era_stats(era)
+        }
+
+        fn timeline(index: u64) -> Option<LiabilityTimeline<BlockNumber>> {
+            Liability::timeline(index)
+        }
+
+        fn finalization_proof(index: u64) -> Option<(FinalizationLeaf<u64, AccountId>, MmrProof)> {
+            Liability::finalization_proof(index)
+        }
+
+        fn preview(index: u64, success: bool) -> Option<Payouts<AccountId, Balance>> {
+            Liability::preview(index, success)
+        }
+
+        fn model_stats(model: Hash) -> ModelStats<Balance> {
+            Liability::model_stats(model)
+        }
+
+        fn top_models(by: ModelRanking, count: u32) -> Vec<(Hash, ModelStats<Balance>)> {
+            Liability::top_models(by, count)
+        }
+
+        fn imported_from(index: u64) -> Option<H160> {
+            Liability::imported_from(index)
+        }
+    }
+
+    impl pallet_robonomics_liability_rpc_runtime_api::LiabilityDryRunApi<
+        Block,
+        u64,
+        Vec<u8>,
+        (),
+        Vec<u8>,
+        AccountId,
+        Signature,
+    > for Runtime {
+        fn dry_run_create(
+            technics: Vec<u8>,
+            economics: (),
+            promisee: AccountId,
+            promisor: AccountId,
+            promisee_proof: Signature,
+            promisor_proof: Signature,
+        ) -> Result<DryRun<u64>, DispatchError> {
+            Liability::dry_run_create(
+                technics,
+                economics,
+                promisee,
+                promisor,
+                promisee_proof,
+                promisor_proof,
+            )
+        }
+
+        fn dry_run_finalize(
+            index: u64,
+            report: Vec<u8>,
+            proof: Signature,
+        ) -> Result<DryRun<u64>, DispatchError> {
+            Liability::dry_run_finalize(index, report, proof)
+        }
+    }
+
+    impl pallet_robonomics_datalog_runtime_api::DatalogApi<
+        Block,
+        AccountId,
+        BlockNumber,
+        Moment,
+        Vec<u8>,
+    > for Runtime {
+        fn window(
+            account: AccountId,
+        ) -> Vec<pallet_robonomics_datalog_runtime_api::DatalogItem<BlockNumber, Moment, Vec<u8>>> {
+            Datalog::datalog(&account)
+        }
+    }
+
     impl sp_session::SessionKeys<Block> for Runtime {
         fn generate_session_keys(seed: Option<Vec<u8>>) -> Vec<u8> {
             SessionKeys::generate(seed)
@@ -930,6 +1317,7 @@ impl_runtime_apis! {
             add_benchmark!(params, batches, pallet_indices, Indices);
             add_benchmark!(params, batches, pallet_multisig, Multisig);
             add_benchmark!(params, batches, pallet_offences, OffencesBench::<Runtime>);
+            add_benchmark!(params, batches, pallet_robonomics_liability, Liability);
             add_benchmark!(params, batches, pallet_scheduler, Scheduler);
             add_benchmark!(params, batches, pallet_session, SessionBench::<Runtime>);
             add_benchmark!(params, batches, pallet_staking, Staking);
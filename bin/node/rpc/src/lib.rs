@@ -40,6 +40,7 @@ use sc_consensus_epochs::SharedEpochChanges;
 use sc_finality_grandpa::{SharedAuthoritySet, SharedVoterState};
 use sc_finality_grandpa_rpc::GrandpaRpcHandler;
 use sc_keystore::KeyStorePtr;
+use sc_rpc::SubscriptionTaskExecutor;
 pub use sc_rpc_api::DenyUnsafe;
 use sp_api::ProvideRuntimeApi;
 use sp_block_builder::BlockBuilder;
@@ -79,7 +80,7 @@ pub struct GrandpaDeps {
 }
 
 /// Full client dependencies.
-pub struct FullDeps<C, P, SC> {
+pub struct FullDeps<C, P, SC, OS> {
     /// The client instance to use.
     pub client: Arc<C>,
     /// Transaction pool instance.
@@ -92,29 +93,49 @@ pub struct FullDeps<C, P, SC> {
     pub babe: BabeDeps,
     /// GRANDPA specific dependencies.
     pub grandpa: GrandpaDeps,
+    /// Executor for RPC subscriptions.
+    pub subscription_executor: SubscriptionTaskExecutor,
+    /// Off-chain storage, if node has it.
+    pub offchain_storage: Option<OS>,
 }
 
 /// A IO handler that uses all Full RPC extensions.
 pub type IoHandler = jsonrpc_core::IoHandler<sc_rpc::Metadata>;
 
 /// Instantiate all Full RPC extensions.
-pub fn create_full<C, P, M, SC>(deps: FullDeps<C, P, SC>) -> jsonrpc_core::IoHandler<M>
+pub fn create_full<C, P, SC, OS>(deps: FullDeps<C, P, SC, OS>) -> IoHandler
 where
     C: ProvideRuntimeApi<Block>,
+    C: sc_client_api::BlockchainEvents<Block>,
+    C: sc_client_api::ProofProvider<Block>,
     C: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError> + 'static,
     C: Send + Sync + 'static,
     C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
     C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
+    C::Api: pallet_robonomics_liability_rpc::LiabilityRuntimeApi<
+        Block,
+        u64,
+        Vec<u8>,
+        (),
+        Vec<u8>,
+        AccountId,
+        BlockNumber,
+        Balance,
+    >,
     C::Api: BabeApi<Block>,
     C::Api: BlockBuilder<Block>,
     P: TransactionPool + 'static,
-    M: jsonrpc_core::Metadata + Default,
     SC: SelectChain<Block> + 'static,
+    OS: sp_core::offchain::OffchainStorage + 'static,
 {
+    use jsonrpc_pubsub::manager::SubscriptionManager;
+    use pallet_robonomics_liability_rpc::{
+        Liability, LiabilityApi, LiabilityArchive, LiabilityArchiveApi, Market, MarketApi,
+    };
     use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
     use substrate_frame_rpc_system::{FullSystem, SystemApi};
 
-    let mut io = jsonrpc_core::IoHandler::default();
+    let mut io = IoHandler::default();
     let FullDeps {
         client,
         pool,
@@ -122,6 +143,8 @@ where
         deny_unsafe,
         babe,
         grandpa,
+        subscription_executor,
+        offchain_storage,
     } = deps;
     let BabeDeps {
         keystore,
@@ -141,6 +164,21 @@ where
     io.extend_with(TransactionPaymentApi::to_delegate(TransactionPayment::new(
         client.clone(),
     )));
+    io.extend_with(LiabilityApi::to_delegate(Liability::<
+        _,
+        (Block, Vec<u8>, Vec<u8>, AccountId),
+    >::new(
+        client.clone(),
+        SubscriptionManager::new(Arc::new(subscription_executor)),
+    )));
+    io.extend_with(MarketApi::to_delegate(Market::<
+        _,
+        (Block, Vec<u8>, (), Vec<u8>, AccountId),
+    >::new(client.clone())));
+    if let Some(storage) = offchain_storage {
+        let archive = LiabilityArchive::<_, Vec<u8>>::new(storage);
+        io.extend_with(LiabilityArchiveApi::<u64>::to_delegate(archive));
+    }
     io.extend_with(sc_consensus_babe_rpc::BabeApi::to_delegate(
         BabeRpcHandler::new(
             client,
@@ -0,0 +1,171 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Harness of end-to-end tests on local development chain: node binary is started as
+//! child process with `--dev --tmp`, extrinsics are submitted to its transaction pool
+//! by the same client that agents use.
+//!
+//! Node binary is taken from `ROBONOMICS_NODE` environment variable, tests using node
+//! are ignored by default:
+//!
+//! ```text
+//! ROBONOMICS_NODE=target/release/robonomics cargo test -p robonomics-node-testing -- --ignored
+//! ```
+
+use robonomics_protocol::runtime::{AccountId, Robonomics};
+use sp_core::{sr25519, Pair};
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+use substrate_subxt::{Client, ClientBuilder};
+
+pub use robonomics_liability_client as liability;
+
+/// Count of node connection attempts, one per second.
+const CONNECT_ATTEMPTS: usize = 60;
+
+/// Development chain node killed when dropped.
+pub struct DevNode {
+    process: Child,
+    rpc_port: u16,
+    ws_port: u16,
+}
+
+impl DevNode {
+    /// Start node binary given by `ROBONOMICS_NODE` on free HTTP and WebSocket ports.
+    pub fn spawn() -> Self {
+        let node = std::env::var("ROBONOMICS_NODE").expect("ROBONOMICS_NODE isn't set");
+        let rpc_port = free_port();
+        let ws_port = free_port();
+        let process = Command::new(node)
+            .args(&["--dev", "--tmp", "--port", "0", "--rpc-port"])
+            .arg(rpc_port.to_string())
+            .arg("--ws-port")
+            .arg(ws_port.to_string())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("unable to start node");
+        DevNode {
+            process,
+            rpc_port,
+            ws_port,
+        }
+    }
+
+    /// WebSocket RPC endpoint of node.
+    pub fn url(&self) -> String {
+        format!("ws://127.0.0.1:{}", self.ws_port)
+    }
+
+    /// Connect client to node, waits until node RPC is started.
+    pub async fn client(&self) -> Client<Robonomics> {
+        let url = self.url();
+        for _ in 0..CONNECT_ATTEMPTS {
+            match ClientBuilder::<Robonomics>::new()
+                .set_url(url.as_str())
+                .build()
+                .await
+            {
+                Ok(client) => return client,
+                Err(_) => async_std::task::sleep(Duration::from_secs(1)).await,
+            }
+        }
+        panic!("dev node isn't started at {}", url)
+    }
+
+    /// Call JSON-RPC method over HTTP, returns whole response object to check `result`
+    /// or `error` of it.
+    pub fn rpc(&self, method: &str, params: serde_json::Value) -> serde_json::Value {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        })
+        .to_string();
+        let mut stream =
+            TcpStream::connect(("127.0.0.1", self.rpc_port)).expect("node HTTP RPC is started");
+        write!(
+            stream,
+            "POST / HTTP/1.0\r\nHost: 127.0.0.1\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\n\r\n{}",
+            request.len(),
+            request
+        )
+        .expect("request is sent");
+        let mut response = String::new();
+        stream
+            .read_to_string(&mut response)
+            .expect("response is received");
+        let body = response
+            .splitn(2, "\r\n\r\n")
+            .nth(1)
+            .expect("HTTP response has body");
+        serde_json::from_str(body).expect("JSON-RPC response")
+    }
+
+    /// Call runtime API method by `state_call`, arguments and result are SCALE encoded.
+    pub fn state_call(&self, method: &str, args: &[u8]) -> Vec<u8> {
+        let response = self.rpc(
+            "state_call",
+            serde_json::json!([method, sp_core::bytes::to_hex(args, false)]),
+        );
+        let result = response["result"]
+            .as_str()
+            .unwrap_or_else(|| panic!("{} failed: {}", method, response));
+        sp_core::bytes::from_hex(result).expect("hex encoded result")
+    }
+}
+
+impl Drop for DevNode {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+/// Development account key, e.g. `Alice`.
+pub fn dev_key(name: &str) -> sr25519::Pair {
+    sr25519::Pair::from_string(&format!("//{}", name), None).expect("valid dev seed")
+}
+
+/// Free balance of account.
+pub async fn free_balance(client: &Client<Robonomics>, account: &AccountId) -> u128 {
+    client
+        .account(account, None)
+        .await
+        .expect("account is readable")
+        .data
+        .free
+}
+
+/// IPFS multihash of sha256 digest filled by seed.
+pub fn multihash(seed: u8) -> Vec<u8> {
+    let mut hash = vec![0x12, 0x20];
+    hash.extend_from_slice(&[seed; 32]);
+    hash
+}
+
+/// Port that is free at the moment, it's released before node binds it.
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .map(|address| address.port())
+        .expect("free local port")
+}
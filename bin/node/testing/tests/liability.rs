@@ -0,0 +1,218 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! End-to-end liability flows on development chain.
+
+use codec::{Compact, Decode, Encode};
+use robonomics_node_testing::{dev_key, free_balance, liability::*, multihash, DevNode};
+use robonomics_protocol::runtime::{AccountId, Robonomics};
+use serde_json::json;
+use sp_core::{bytes::to_hex, crypto::Ss58Codec};
+
+#[test]
+fn compact_index_encoding() {
+    let promisor = dev_key("Charlie");
+    let report = multihash(2);
+    for &(index, index_len) in &[(1, 1), (u64::max_value(), 9)] {
+        let call = FinalizeCall::<Robonomics>::signed(index, report.clone(), &promisor);
+        let fixed_len = report.encoded_size() + call.proof.encoded_size();
+        assert_eq!(call.encode().len(), fixed_len + index_len);
+
+        let encoded = (Compact(index), report_hash(&report), 7u32).encode();
+        let event = NewReportEvent::<Robonomics>::decode(&mut &encoded[..]).unwrap();
+        assert_eq!(event.index, index);
+        assert_eq!(event.block, 7);
+    }
+
+    assert!(ensure_compact_index(COMPACT_INDEX_SPEC_VERSION).is_ok());
+    assert!(ensure_compact_index(COMPACT_INDEX_SPEC_VERSION - 1).is_err());
+}
+
+#[async_std::test]
+#[ignore]
+async fn create_and_finalize() {
+    let node = DevNode::spawn();
+    let client = node.client().await;
+
+    let promisee = dev_key("Bob");
+    let promisor = dev_key("Charlie");
+    let promisee_balance = free_balance(&client, &account(&promisee)).await;
+    let promisor_balance = free_balance(&client, &account(&promisor)).await;
+    let technics = multihash(1);
+
+    let call = CreateCall::<Robonomics>::signed(technics.clone(), (), &promisee, &promisor);
+    let created = create(&client, call).await.unwrap();
+    assert_eq!(created.event.technics, technics_hash(&technics));
+    assert_eq!(created.event.promisee, account(&promisee));
+    assert_eq!(created.event.promisor, account(&promisor));
+    let index = created.event.index;
+
+    let store = LiabilitiesStore::<Robonomics> { index };
+    let record = client.fetch(&store, None).await.unwrap().unwrap();
+    assert_eq!(record.state, RecordState::Open);
+    assert_eq!(record.promisor, account(&promisor));
+
+    // Report proof of another key is rejected by transaction pool
+    let report = multihash(2);
+    let call = FinalizeCall::<Robonomics>::signed(index, report.clone(), &promisee);
+    assert!(finalize(&client, call).await.is_err());
+
+    let call = FinalizeCall::<Robonomics>::signed(index, report.clone(), &promisor);
+    let reported = finalize(&client, call).await.unwrap();
+    assert_eq!(reported.event.index, index);
+    assert_eq!(reported.event.report, report_hash(&report));
+
+    let record = client.fetch(&store, None).await.unwrap().unwrap();
+    assert_eq!(record.state, RecordState::Reported);
+    let store = ReportPreimageStore::<Robonomics> {
+        hash: record.report_hash.unwrap(),
+        _runtime: Default::default(),
+    };
+    let preimage = client.fetch(&store, None).await.unwrap().unwrap();
+    assert_eq!(preimage.report, report);
+    assert_eq!(preimage.depositor, None);
+
+    // Unsigned extrinsics are free and liability economics is priceless
+    assert_eq!(
+        free_balance(&client, &account(&promisee)).await,
+        promisee_balance
+    );
+    assert_eq!(
+        free_balance(&client, &account(&promisor)).await,
+        promisor_balance
+    );
+}
+
+#[async_std::test]
+#[ignore]
+async fn runtime_liability_info() {
+    let node = DevNode::spawn();
+    let client = node.client().await;
+
+    let promisee = dev_key("Bob");
+    let promisor = dev_key("Charlie");
+    let technics = multihash(3);
+    let call = CreateCall::<Robonomics>::signed(technics.clone(), (), &promisee, &promisor);
+    let index = create(&client, call).await.unwrap().event.index;
+
+    // Communism is priceless and traded on single market
+    let summary = EconomicsSummary {
+        value: None,
+        settled: 0,
+        market: 0,
+        deferred_funding: false,
+    };
+
+    let encoded = node.state_call("LiabilityApi_get_liability", &index.encode());
+    let info: Option<LiabilityInfo<Vec<u8>, (), AccountId, u32, u128>> =
+        Decode::decode(&mut &encoded[..]).unwrap();
+    let info = info.unwrap();
+    assert_eq!(info.technics, technics);
+    assert_eq!(info.technics_hash, technics_hash(&technics));
+    assert_eq!(info.economics_summary, summary);
+    assert_eq!(info.promisee, account(&promisee));
+    assert_eq!(info.promisor, account(&promisor));
+    assert_eq!(info.state, LiabilityState::Open);
+
+    let encoded = node.state_call("LiabilityApi_get_liability", &(index + 1).encode());
+    let info: Option<LiabilityInfo<Vec<u8>, (), AccountId, u32, u128>> =
+        Decode::decode(&mut &encoded[..]).unwrap();
+    assert!(info.is_none());
+}
+
+/// Account of SS58 address returned by node, whatever address format node uses.
+fn ss58_account(address: &serde_json::Value) -> AccountId {
+    AccountId::from_ss58check(address.as_str().unwrap()).unwrap()
+}
+
+#[async_std::test]
+#[ignore]
+async fn liability_rpc() {
+    let node = DevNode::spawn();
+    let client = node.client().await;
+
+    let promisee = dev_key("Bob");
+    let promisor = dev_key("Charlie");
+    let mut indexes = Vec::new();
+    for &seed in &[4, 5] {
+        let call = CreateCall::<Robonomics>::signed(multihash(seed), (), &promisee, &promisor);
+        indexes.push(create(&client, call).await.unwrap().event.index);
+    }
+
+    let info = &node.rpc("liability_getInfo", json!([indexes[0]]))["result"];
+    assert_eq!(info["index"], json!(indexes[0]));
+    assert_eq!(info["technics"], json!(to_hex(&multihash(4), false)));
+    assert_eq!(info["technicsHash"], json!(technics_hash(&multihash(4))));
+    assert_eq!(info["economics"], json!(null));
+    assert_eq!(
+        info["economicsSummary"],
+        json!({"value": null, "settled": 0, "market": 0, "deferredFunding": false})
+    );
+    assert_eq!(ss58_account(&info["promisee"]), account(&promisee));
+    assert_eq!(ss58_account(&info["promisor"]), account(&promisor));
+    assert_eq!(info["state"], json!("open"));
+    assert_eq!(info["finalizedAt"], json!(null));
+
+    let response = node.rpc("liability_getInfo", json!([indexes[1] + 1]));
+    assert_eq!(response["result"], json!(null));
+
+    // Pages of promisor liabilities are linked by `next` cursor
+    let promisor_address = account(&promisor).to_ss58check();
+    let page = &node.rpc(
+        "liability_listByAccount",
+        json!([promisor_address, indexes[0], 1]),
+    )["result"];
+    assert_eq!(page["liabilities"].as_array().unwrap().len(), 1);
+    assert_eq!(page["liabilities"][0]["index"], json!(indexes[0]));
+    assert_eq!(page["next"], json!(indexes[0] + 1));
+    assert_eq!(page["hasMore"], json!(true));
+    assert_eq!(
+        page["stats"],
+        json!({
+            "asPromisor": {"completed": 0, "failed": 0, "disputed": 0},
+            "asPromisee": {"completed": 0, "failed": 0, "disputed": 0},
+            "settled": 0,
+        })
+    );
+    let page = &node.rpc(
+        "liability_listByAccount",
+        json!([promisor_address, page["next"], 1]),
+    )["result"];
+    assert_eq!(page["liabilities"][0]["index"], json!(indexes[1]));
+    assert_eq!(page["hasMore"], json!(false));
+
+    let cost = &node.rpc("liability_estimateCost", json!([null]))["result"];
+    assert!(cost["createWeight"].as_u64().unwrap() > 0);
+    assert!(cost["finalizeWeight"].as_u64().unwrap() > 0);
+    assert_eq!(cost["escrow"], json!(null));
+
+    // Bad address is invalid params, runtime call at unknown block is server error 1
+    let response = node.rpc("liability_listByAccount", json!(["bad", 0, 1]));
+    assert_eq!(response["error"]["code"], json!(-32602));
+    let unknown = format!("0x{}", "ff".repeat(32));
+    for (method, params) in vec![
+        ("liability_getInfo", json!([0, unknown])),
+        (
+            "liability_listByAccount",
+            json!([promisor_address, 0, 1, unknown]),
+        ),
+        ("liability_estimateCost", json!([null, unknown])),
+    ] {
+        let response = node.rpc(method, params);
+        assert_eq!(response["error"]["code"], json!(1), "{}", method);
+    }
+}
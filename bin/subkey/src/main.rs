@@ -818,7 +818,7 @@ where
             frame_system::CheckEra::<Runtime>::from(Era::Immortal),
             frame_system::CheckNonce::<Runtime>::from(i),
             frame_system::CheckWeight::<Runtime>::new(),
-            pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(f),
+            pallet_robonomics_rws::ChargeRwsPayment::<Runtime>::from(f),
         )
     };
     let raw_payload = SignedPayload::from_raw(
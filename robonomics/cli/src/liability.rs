@@ -0,0 +1,338 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Robonomics liability CLI interface.
+
+#![deny(missing_docs)]
+
+use crate::error::Result;
+use async_std::task;
+use base58::ToBase58;
+use robonomics_protocol::liability::{self, decode_cid, Finalization, Job, Transition, KEY_TYPE};
+use robonomics_protocol::pubsub::{self, Multiaddr, PubSub};
+use robonomics_protocol::runtime::AccountId;
+use sp_core::{
+    crypto::{Pair, Ss58Codec},
+    sr25519,
+};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Robonomics liability operations.
+#[derive(structopt::StructOpt, Debug)]
+pub struct LiabilityCmd {
+    /// Liability operation to run.
+    #[structopt(subcommand)]
+    pub operation: Operation,
+    #[allow(missing_docs)]
+    #[structopt(flatten)]
+    pub shared_params: sc_cli::SharedParams,
+}
+
+impl sc_cli::CliConfiguration for LiabilityCmd {
+    fn shared_params(&self) -> &sc_cli::SharedParams {
+        &self.shared_params
+    }
+}
+
+impl LiabilityCmd {
+    /// Run liability operation.
+    pub fn run(&self) -> Result<()> {
+        self.operation.run()
+    }
+}
+
+/// Liability operation command.
+#[derive(structopt::StructOpt, Debug)]
+pub enum Operation {
+    /// Sign liability described by JSON file and submit it, prints index of new liability.
+    Create(CreateCmd),
+    /// Add result file to IPFS and publish it as liability report, prints report hash.
+    Finalize(FinalizeCmd),
+    /// Print liability transitions of account in finalized blocks, one JSON line per each.
+    Watch(WatchCmd),
+    /// Create liabilities of matched pubsub orders and publish liability transitions back.
+    Bridge(BridgeCmd),
+}
+
+impl Operation {
+    /// Run liability operation.
+    pub fn run(&self) -> Result<()> {
+        match self {
+            Operation::Create(create) => create.run(),
+            Operation::Finalize(finalize) => finalize.run(),
+            Operation::Watch(watch) => watch.run(),
+            Operation::Bridge(bridge) => bridge.run(),
+        }
+    }
+}
+
+/// Liability create command.
+#[derive(structopt::StructOpt, Clone, Debug)]
+pub struct CreateCmd {
+    /// Liability job JSON file, e.g.
+    /// `{"technics": "Qm...", "price": 0, "promisee": "5F...", "promisor": "5G..."}`.
+    #[structopt(long, value_name = "PATH", parse(from_os_str))]
+    params: PathBuf,
+    /// Promisee account seed URI, when not set the key is taken from keystore.
+    #[structopt(long, value_name = "SECRET_URI")]
+    promisee_suri: Option<String>,
+    /// Promisor account seed URI, when not set the key is taken from keystore.
+    #[structopt(long, value_name = "SECRET_URI")]
+    promisor_suri: Option<String>,
+    /// Node keystore path with `liab` keys of parties.
+    #[structopt(long, value_name = "PATH", parse(from_os_str))]
+    keystore_path: Option<PathBuf>,
+    /// Robonomics node WebSocket endpoint.
+    #[structopt(long, value_name = "REMOTE_URI", default_value = "ws://127.0.0.1:9944")]
+    remote: String,
+}
+
+/// Liability job JSON description.
+#[derive(serde::Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct JobFile {
+    /// IPFS hash of liability objective.
+    technics: String,
+    /// Price of liability, zero by default.
+    #[serde(default)]
+    price: u128,
+    /// Promisee address.
+    promisee: String,
+    /// Promisor address.
+    promisor: String,
+}
+
+impl CreateCmd {
+    /// Sign and submit liability.
+    pub fn run(&self) -> Result<()> {
+        let path = self.params.display();
+        let file = std::fs::File::open(&self.params)
+            .map_err(|e| format!("unable to open liability job {}: {}", path, e))?;
+        let job: JobFile = serde_json::from_reader(file)
+            .map_err(|e| format!("bad liability job {}: {}", path, e))?;
+
+        let technics = decode_cid(&job.technics)?;
+        let promisee = party_key(
+            "promisee",
+            &job.promisee,
+            &self.promisee_suri,
+            &self.keystore_path,
+        )?;
+        let promisor = party_key(
+            "promisor",
+            &job.promisor,
+            &self.promisor_suri,
+            &self.keystore_path,
+        )?;
+
+        let job = Job {
+            technics,
+            price: job.price,
+        };
+        let index = task::block_on(liability::create(
+            self.remote.clone(),
+            job,
+            promisee,
+            promisor,
+        ))?;
+        println!("{}", index);
+        Ok(())
+    }
+}
+
+/// Liability finalize command.
+#[derive(structopt::StructOpt, Clone, Debug)]
+pub struct FinalizeCmd {
+    /// Index of finalized liability.
+    #[structopt(long, value_name = "LIABILITY_INDEX")]
+    index: u64,
+    /// Liability result file.
+    #[structopt(long, value_name = "PATH", parse(from_os_str))]
+    file: PathBuf,
+    /// Promisor address of liability.
+    #[structopt(long, value_name = "SS58_ADDRESS")]
+    promisor: String,
+    /// Promisor account seed URI, when not set the key is taken from keystore.
+    #[structopt(long, value_name = "SECRET_URI")]
+    promisor_suri: Option<String>,
+    /// Node keystore path with `liab` key of promisor.
+    #[structopt(long, value_name = "PATH", parse(from_os_str))]
+    keystore_path: Option<PathBuf>,
+    /// IPFS HTTP API endpoint.
+    #[structopt(long, value_name = "URI", default_value = "http://127.0.0.1:5001")]
+    ipfs_api: String,
+    /// Robonomics node WebSocket endpoint.
+    #[structopt(long, value_name = "REMOTE_URI", default_value = "ws://127.0.0.1:9944")]
+    remote: String,
+}
+
+impl FinalizeCmd {
+    /// Upload result and submit report.
+    pub fn run(&self) -> Result<()> {
+        let promisor = party_key(
+            "promisor",
+            &self.promisor,
+            &self.promisor_suri,
+            &self.keystore_path,
+        )?;
+        let finalization = task::block_on(liability::finalize_with_file(
+            self.remote.clone(),
+            self.ipfs_api.clone(),
+            self.index,
+            &self.file,
+            promisor,
+        ))?;
+        match finalization {
+            Finalization::Reported(_, report) => println!("{}", report.to_base58()),
+            Finalization::AlreadyFinalized => {
+                eprintln!("liability {} is already finalized", self.index)
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Key of liability party from seed URI or node keystore, it should match party address.
+fn party_key(
+    party: &str,
+    address: &str,
+    suri: &Option<String>,
+    keystore_path: &Option<PathBuf>,
+) -> Result<sr25519::Pair> {
+    let public = sr25519::Public::from_ss58check(address)
+        .map_err(|_| format!("bad {} address {}", party, address))?;
+    let pair = match (suri, keystore_path) {
+        (Some(suri), _) => sr25519::Pair::from_string(suri, None)
+            .map_err(|_| format!("bad {} secret URI", party))?,
+        (None, Some(path)) => sc_keystore::Store::open(path.clone(), None)
+            .map_err(|e| format!("unable to open keystore {}: {}", path.display(), e))?
+            .read()
+            .key_pair_by_type::<sr25519::Pair>(&public, KEY_TYPE)
+            .map_err(|_| format!("{} key {} isn't found in keystore", party, address))?,
+        (None, None) => Err(format!(
+            "{} key isn't set, use --{}-suri or --keystore-path",
+            party, party
+        ))?,
+    };
+    if pair.public() != public {
+        Err(format!(
+            "{} secret URI doesn't match address {}",
+            party, address
+        ))?
+    }
+    Ok(pair)
+}
+
+/// Liability watch command.
+#[derive(structopt::StructOpt, Clone, Debug)]
+pub struct WatchCmd {
+    /// Promisee or promisor address of watched liabilities.
+    #[structopt(long, value_name = "SS58_ADDRESS")]
+    account: String,
+    /// Process finalized blocks starting from given one, current finalized block by default.
+    #[structopt(long, value_name = "BLOCK_NUMBER")]
+    from_block: Option<u32>,
+    /// Read report of liability from chain state and write it into file named by index.
+    #[structopt(long)]
+    fetch_reports: bool,
+    /// Directory of fetched report files.
+    #[structopt(long, value_name = "PATH", default_value = ".", parse(from_os_str))]
+    reports_dir: PathBuf,
+    /// Robonomics node WebSocket endpoint.
+    #[structopt(long, value_name = "REMOTE_URI", default_value = "ws://127.0.0.1:9944")]
+    remote: String,
+}
+
+impl WatchCmd {
+    /// Watch liability transitions until failure.
+    pub fn run(&self) -> Result<()> {
+        let account = AccountId::from_ss58check(&self.account)
+            .map_err(|_| format!("bad account address {}", self.account))?;
+        let reports_dir = self.reports_dir.clone();
+        task::block_on(liability::watch(
+            self.remote.clone(),
+            Some(account),
+            self.from_block,
+            self.fetch_reports,
+            move |transition| {
+                if let Transition::Reported {
+                    index,
+                    report: Some(report),
+                    ..
+                } = &transition
+                {
+                    let path = reports_dir.join(index.to_string());
+                    std::fs::write(&path, report)
+                        .map_err(|e| format!("unable to write report {}: {}", path.display(), e))?;
+                }
+                println!("{}", transition.to_json());
+                Ok(())
+            },
+        ))?;
+        Ok(())
+    }
+}
+
+/// Liability bridge command.
+#[derive(structopt::StructOpt, Clone, Debug)]
+pub struct BridgeCmd {
+    /// PubSub topic of signed order messages.
+    #[structopt(long, value_name = "TOPIC_NAME", default_value = "robonomics/orders")]
+    order_topic: String,
+    /// PubSub topic of liability transitions.
+    #[structopt(
+        long,
+        value_name = "TOPIC_NAME",
+        default_value = "robonomics/liabilities"
+    )]
+    result_topic: String,
+    /// Listen address for incoming connections.
+    #[structopt(long, value_name = "MULTIADDR", default_value = "/ip4/0.0.0.0/tcp/0")]
+    listen: Multiaddr,
+    /// Indicates PubSub nodes for first connections.
+    #[structopt(long, value_name = "MULTIADDR", use_delimiter = true)]
+    bootnodes: Vec<Multiaddr>,
+    /// How often node should check another nodes availability, in secs.
+    #[structopt(long, value_name = "HEARTBEAT_SECS", default_value = "5")]
+    heartbeat_secs: u64,
+    /// Robonomics node WebSocket endpoint.
+    #[structopt(long, value_name = "REMOTE_URI", default_value = "ws://127.0.0.1:9944")]
+    remote: String,
+}
+
+impl BridgeCmd {
+    /// Run bridge until failure.
+    pub fn run(&self) -> Result<()> {
+        let heartbeat = Duration::from_secs(self.heartbeat_secs);
+        let (pubsub, worker) = pubsub::Gossipsub::new(heartbeat)?;
+        let _ = pubsub.listen(self.listen.clone());
+        for addr in self.bootnodes.clone() {
+            let _ = pubsub.connect(addr);
+        }
+        task::spawn(pubsub::discovery::start(pubsub.clone()));
+        task::spawn(worker);
+
+        task::block_on(robonomics_protocol::bridge::run(
+            pubsub,
+            self.remote.clone(),
+            self.order_topic.clone(),
+            self.result_topic.clone(),
+        ))?;
+        Ok(())
+    }
+}
@@ -20,9 +20,11 @@
 pub mod error;
 
 mod io;
+mod liability;
 mod sink;
 mod source;
 
 pub use io::IoCmd;
+pub use liability::{LiabilityCmd, Operation as LiabilityOperation};
 pub use sink::SinkCmd;
 pub use source::SourceCmd;
@@ -0,0 +1,47 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Runtime API definition for datalog module.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Codec, Decode, Encode};
+use sp_runtime::RuntimeDebug;
+use sp_std::prelude::*;
+
+/// Datalog record of account window.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct DatalogItem<BlockNumber, Moment, Record> {
+    /// Record block.
+    pub block: BlockNumber,
+    /// Record block timestamp.
+    pub timestamp: Moment,
+    /// Data record, e.g. IPFS hash of telemetry.
+    pub record: Record,
+}
+
+sp_api::decl_runtime_apis! {
+    /// Read access to account datalogs.
+    pub trait DatalogApi<AccountId, BlockNumber, Moment, Record> where
+        AccountId: Codec,
+        BlockNumber: Codec,
+        Moment: Codec,
+        Record: Codec,
+    {
+        /// Datalog window of account, the oldest record first.
+        fn window(account: AccountId) -> Vec<DatalogItem<BlockNumber, Moment, Record>>;
+    }
+}
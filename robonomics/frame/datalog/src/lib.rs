@@ -18,25 +18,78 @@
 //! Simple Robonomics datalog runtime module. This can be compiled with `#[no_std]`, ready for Wasm.
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use codec::{Codec, EncodeLike};
-use frame_support::{decl_event, decl_module, decl_storage, traits::Time};
+use codec::{Codec, Decode, Encode, EncodeLike};
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage, ensure,
+    storage::migration::StorageIterator,
+    traits::{Get, Time},
+    weights::Weight,
+};
 use frame_system::ensure_signed;
-use sp_runtime::traits::Member;
+use sp_runtime::{traits::Member, RuntimeDebug};
 use sp_std::prelude::*;
 
+pub use pallet_robonomics_datalog_runtime_api::DatalogItem;
+
 /// Type synonym for timestamp data type.
 pub type MomentOf<T> = <<T as Trait>::Time as Time>::Moment;
 
+/// Datalog item of module runtime.
+pub type ItemOf<T> =
+    DatalogItem<<T as frame_system::Trait>::BlockNumber, MomentOf<T>, <T as Trait>::Record>;
+
 /// Datalog module main trait.
 pub trait Trait: frame_system::Trait {
     /// Timestamp source.
     type Time: Time;
     /// Datalog record data type.
     type Record: Codec + EncodeLike + Member;
+    /// Depth of account datalog window, the oldest record is dropped when it's full.
+    type WindowSize: Get<u64>;
+    /// Maximal length of encoded record.
+    type MaximumRecordLength: Get<u32>;
     /// The overarching event type.
     type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
 }
 
+/// Positions of account datalog window: records from `start` inclusive to `end` exclusive.
+/// Positions grow monotonically, so window survives change of its depth.
+#[derive(Encode, Decode, Default, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct RingBufferIndex {
+    /// Position of the oldest record.
+    pub start: u64,
+    /// Position of the next record.
+    pub end: u64,
+}
+
+impl RingBufferIndex {
+    /// Count of records in window.
+    pub fn len(&self) -> u64 {
+        self.end - self.start
+    }
+
+    /// Returns `true` when window has no records.
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/// Storage releases of the module.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Releases {
+    /// Unbounded vector of account records, untracked storage version.
+    V1_0_0,
+    /// Bounded window of account records, chains started with it stay on default
+    /// release until the first upgrade, which finds nothing to migrate.
+    V2_0_0,
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1_0_0
+    }
+}
+
 decl_event! {
     pub enum Event<T>
     where AccountId = <T as frame_system::Trait>::AccountId,
@@ -50,47 +103,138 @@ decl_event! {
     }
 }
 
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        /// Encoded record is longer than `MaximumRecordLength`.
+        RecordTooLong,
+    }
+}
+
 decl_storage! {
     trait Store for Module<T: Trait> as Datalog {
-        /// Time tagged data of given account.
-        Datalog get(fn datalog): map hasher(blake2_128_concat)
-                                 T::AccountId => Vec<(MomentOf<T>, T::Record)>;
+        /// Window positions of given account.
+        DatalogIndex get(fn datalog_index): map hasher(blake2_128_concat)
+                                            T::AccountId => RingBufferIndex;
+        /// Time tagged data of given account by window position.
+        DatalogEntry get(fn datalog_entry): double_map hasher(blake2_128_concat) T::AccountId,
+                                            hasher(twox_64_concat) u64 => Option<ItemOf<T>>;
+        /// Storage release, migrations on runtime upgrade are dispatched by it.
+        StorageVersion get(fn storage_version): Releases;
     }
 }
 
 decl_module! {
     pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        type Error = Error<T>;
+
         fn deposit_event() = default;
 
-        /// Store new data into blockchain.
-        #[weight = 5_000_000]
+        fn on_runtime_upgrade() -> Weight {
+            if <StorageVersion>::get() == Releases::V1_0_0 {
+                <StorageVersion>::put(Releases::V2_0_0);
+                Self::migrate_windows()
+            } else {
+                T::DbWeight::get().reads(1)
+            }
+        }
+
+        /// Store new data into blockchain, the oldest record of full window is dropped.
+        #[weight = 5_000_000 + T::DbWeight::get().reads_writes(1, 3)]
         fn record(origin, record: T::Record) {
             let sender = ensure_signed(origin)?;
-            let now = T::Time::now();
-            <Datalog<T>>::mutate(
-                sender.clone(),
-                |m| m.push((now.clone(), record.clone()))
+            ensure!(
+                record.encoded_size() <= T::MaximumRecordLength::get() as usize,
+                Error::<T>::RecordTooLong
             );
+            let now = T::Time::now();
+            Self::push(&sender, DatalogItem {
+                block: <frame_system::Module<T>>::block_number(),
+                timestamp: now.clone(),
+                record: record.clone(),
+            });
             Self::deposit_event(RawEvent::NewRecord(sender, now, record));
         }
 
         /// Clear account datalog.
-        #[weight = 100_000]
+        #[weight = 100_000 + T::DbWeight::get().writes(T::WindowSize::get() + 1)]
         fn erase(origin) {
             let sender = ensure_signed(origin)?;
-            <Datalog<T>>::remove(sender.clone());
+            let index = <DatalogIndex<T>>::take(&sender);
+            for position in index.start..index.end {
+                <DatalogEntry<T>>::remove(&sender, position);
+            }
             Self::deposit_event(RawEvent::Erased(sender));
         }
     }
 }
 
+impl<T: Trait> Module<T> {
+    /// Datalog window of account, the oldest record first.
+    pub fn datalog(account: &T::AccountId) -> Vec<ItemOf<T>> {
+        let index = <DatalogIndex<T>>::get(account);
+        (index.start..index.end)
+            .filter_map(|position| <DatalogEntry<T>>::get(account, position))
+            .collect()
+    }
+
+    /// Append item to account window and drop records beyond its depth.
+    fn push(account: &T::AccountId, item: ItemOf<T>) {
+        let depth = T::WindowSize::get().max(1);
+        let mut index = <DatalogIndex<T>>::get(account);
+        <DatalogEntry<T>>::insert(account, index.end, item);
+        index.end += 1;
+        // Window of decreased depth is shrunk by the first record after upgrade
+        while index.len() > depth {
+            <DatalogEntry<T>>::remove(account, index.start);
+            index.start += 1;
+        }
+        <DatalogIndex<T>>::insert(account, index);
+    }
+
+    /// Move release 1 vectors of records into windows, records of unknown block
+    /// get block zero and only the latest `WindowSize` records are kept.
+    fn migrate_windows() -> Weight {
+        let mut reads: Weight = 1;
+        let mut writes: Weight = 1;
+        // Release 1 map doesn't keep account in value, it's decoded from `blake2_128_concat` key
+        let old: Vec<(Vec<u8>, Vec<(MomentOf<T>, T::Record)>)> =
+            StorageIterator::<Vec<(MomentOf<T>, T::Record)>>::new(b"Datalog", b"Datalog")
+                .drain()
+                .collect();
+        for (key, records) in old {
+            reads += 1;
+            let account = match key
+                .get(16..)
+                .and_then(|mut k| T::AccountId::decode(&mut k).ok())
+            {
+                Some(account) => account,
+                None => continue,
+            };
+            for (timestamp, record) in records {
+                Self::push(
+                    &account,
+                    DatalogItem {
+                        block: Default::default(),
+                        timestamp,
+                        record,
+                    },
+                );
+                writes += 3;
+            }
+        }
+        T::DbWeight::get().reads_writes(reads, writes)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     use base58::FromBase58;
     use frame_support::{
-        assert_err, assert_ok, impl_outer_origin, parameter_types, weights::Weight,
+        assert_err, assert_noop, assert_ok, impl_outer_origin, parameter_types,
+        storage::migration::put_storage_value, traits::OnRuntimeUpgrade, weights::Weight,
+        Blake2_128Concat, IterableStorageDoubleMap, StorageHasher,
     };
     use node_primitives::Moment;
     use sp_core::H256;
@@ -149,9 +293,16 @@ mod tests {
         type WeightInfo = ();
     }
 
+    parameter_types! {
+        pub const WindowSize: u64 = 3;
+        pub const MaximumRecordLength: u32 = 64;
+    }
+
     impl Trait for Runtime {
         type Time = Timestamp;
         type Record = Vec<u8>;
+        type WindowSize = WindowSize;
+        type MaximumRecordLength = MaximumRecordLength;
         type Event = ();
     }
 
@@ -162,16 +313,29 @@ mod tests {
         storage.into()
     }
 
+    type System = frame_system::Module<Runtime>;
     type Timestamp = pallet_timestamp::Module<Runtime>;
     type Datalog = Module<Runtime>;
 
+    fn item(block: u64, record: Vec<u8>) -> ItemOf<Runtime> {
+        DatalogItem {
+            block,
+            timestamp: 0,
+            record,
+        }
+    }
+
     #[test]
     fn test_store_data() {
         new_test_ext().execute_with(|| {
             let sender = 1;
             let record = vec![42];
             assert_ok!(Datalog::record(Origin::signed(sender), record.clone()));
-            assert_eq!(Datalog::datalog(sender), vec![(0, record)]);
+            assert_eq!(Datalog::datalog(&sender), vec![item(0, record)]);
+            assert_eq!(
+                Datalog::datalog_index(sender),
+                RingBufferIndex { start: 0, end: 1 }
+            );
         })
     }
 
@@ -181,9 +345,10 @@ mod tests {
             let sender = 1;
             let record = vec![1, 2, 3];
             assert_ok!(Datalog::record(Origin::signed(sender), record.clone()));
-            assert_eq!(Datalog::datalog(sender), vec![(0, record)]);
+            assert_eq!(Datalog::datalog(&sender), vec![item(0, record)]);
             assert_ok!(Datalog::erase(Origin::signed(sender)));
-            assert_eq!(Datalog::datalog(sender).is_empty(), true);
+            assert_eq!(Datalog::datalog(&sender).is_empty(), true);
+            assert_eq!(Datalog::datalog_index(sender), Default::default());
         })
     }
 
@@ -205,23 +370,122 @@ mod tests {
                 .from_base58()
                 .unwrap();
             assert_ok!(Datalog::record(Origin::signed(sender), record.clone()));
-            assert_eq!(Datalog::datalog(sender), vec![(0, record.clone())]);
+            assert_eq!(Datalog::datalog(&sender), vec![item(0, record.clone())]);
             let record2 = "zdj7WWYAEceQ6ncfPZeRFjozov4dC7FaxU7SuMwzW4VuYBDta"
                 .from_base58()
                 .unwrap();
             assert_ok!(Datalog::record(Origin::signed(sender), record2.clone()));
             assert_eq!(
-                Datalog::datalog(sender),
-                vec![(0, record.clone()), (0, record2.clone()),]
+                Datalog::datalog(&sender),
+                vec![item(0, record.clone()), item(0, record2.clone()),]
             );
             let record3 = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz2"
                 .from_base58()
                 .unwrap();
             assert_ok!(Datalog::record(Origin::signed(sender), record3.clone()));
             assert_eq!(
-                Datalog::datalog(sender),
-                vec![(0, record), (0, record2), (0, record3),]
+                Datalog::datalog(&sender),
+                vec![item(0, record), item(0, record2), item(0, record3),]
             );
         })
     }
+
+    #[test]
+    fn test_window_wraparound() {
+        new_test_ext().execute_with(|| {
+            let sender = 1;
+            for block in 1..=5 {
+                System::set_block_number(block);
+                assert_ok!(Datalog::record(Origin::signed(sender), vec![block as u8]));
+            }
+
+            // The oldest records are dropped, the window keeps record order
+            assert_eq!(
+                Datalog::datalog(&sender),
+                vec![item(3, vec![3]), item(4, vec![4]), item(5, vec![5])]
+            );
+            assert_eq!(
+                Datalog::datalog_index(sender),
+                RingBufferIndex { start: 2, end: 5 }
+            );
+            assert_eq!(Datalog::datalog_entry(sender, 0), None);
+            assert_eq!(Datalog::datalog_entry(sender, 1), None);
+            assert_eq!(Datalog::datalog_entry(sender, 2), Some(item(3, vec![3])));
+
+            // Windows of accounts are independent
+            assert_ok!(Datalog::record(Origin::signed(2), vec![42]));
+            assert_eq!(Datalog::datalog(&2), vec![item(5, vec![42])]);
+            assert_eq!(Datalog::datalog(&sender).len(), 3);
+        })
+    }
+
+    #[test]
+    fn test_window_depth_bound() {
+        new_test_ext().execute_with(|| {
+            let sender = 1;
+            for i in 0..10u8 {
+                assert_ok!(Datalog::record(Origin::signed(sender), vec![i]));
+                let stored = <DatalogEntry<Runtime>>::iter_prefix(&sender).count() as u64;
+                assert_eq!(stored, (i as u64 + 1).min(WindowSize::get()));
+                assert_eq!(Datalog::datalog_index(sender).len(), stored);
+            }
+
+            // Erase of wrapped window removes all its entries
+            assert_ok!(Datalog::erase(Origin::signed(sender)));
+            assert_eq!(<DatalogEntry<Runtime>>::iter_prefix(&sender).count(), 0);
+
+            // Window restarts after erase
+            assert_ok!(Datalog::record(Origin::signed(sender), vec![1]));
+            assert_eq!(
+                Datalog::datalog_index(sender),
+                RingBufferIndex { start: 0, end: 1 }
+            );
+        })
+    }
+
+    #[test]
+    fn test_record_too_long() {
+        new_test_ext().execute_with(|| {
+            let sender = 1;
+            // Encoded vector has one byte length prefix
+            assert_noop!(
+                Datalog::record(Origin::signed(sender), vec![0; 64]),
+                Error::<Runtime>::RecordTooLong
+            );
+            assert_ok!(Datalog::record(Origin::signed(sender), vec![0; 63]));
+        })
+    }
+
+    #[test]
+    fn test_migrate_windows() {
+        new_test_ext().execute_with(|| {
+            let sender: u64 = 1;
+            let records: Vec<(Moment, Vec<u8>)> =
+                (1..=4).map(|i| (i * 10, vec![i as u8])).collect();
+            let key = Blake2_128Concat::hash(&sender.encode());
+            put_storage_value(b"Datalog", b"Datalog", &key, records);
+
+            Datalog::on_runtime_upgrade();
+            assert_eq!(Datalog::storage_version(), Releases::V2_0_0);
+            assert_eq!(
+                Datalog::datalog(&sender),
+                (2..=4)
+                    .map(|i| DatalogItem {
+                        block: 0,
+                        timestamp: i * 10,
+                        record: vec![i as u8],
+                    })
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(
+                StorageIterator::<Vec<(Moment, Vec<u8>)>>::new(b"Datalog", b"Datalog").count(),
+                0
+            );
+
+            // Migration runs once
+            assert_ok!(Datalog::erase(Origin::signed(sender)));
+            Datalog::on_runtime_upgrade();
+            assert!(Datalog::datalog(&sender).is_empty());
+        })
+    }
 }
@@ -19,15 +19,35 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::{Codec, EncodeLike};
-use frame_support::{decl_event, decl_module, decl_storage};
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage, ensure,
+    traits::{Currency, ExistenceRequirement, Get, OnUnbalanced, WithdrawReason},
+};
 use frame_system::ensure_signed;
-use sp_runtime::traits::Member;
+use sp_runtime::traits::{Member, Saturating, Zero};
 use sp_std::prelude::*;
 
+/// Type synonym for launch fee balance.
+pub type BalanceOf<T> =
+    <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+
+/// Type synonym for withdrawn launch fee.
+pub type NegativeImbalanceOf<T> =
+    <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::NegativeImbalance;
+
 /// Launch module main trait.
 pub trait Trait: frame_system::Trait {
     /// Robot launch parameter data type.
     type Parameter: Codec + EncodeLike + Member;
+    /// Currency of launch fee.
+    type Currency: Currency<Self::AccountId>;
+    /// Fee paid by sender for each launch, launches are free when it's zero.
+    type LaunchFee: Get<BalanceOf<Self>>;
+    /// Destination of launch fees, e.g. treasury.
+    type FeeDestination: OnUnbalanced<NegativeImbalanceOf<Self>>;
+    /// Minimal count of blocks between launches of the same robot by the same sender,
+    /// launches aren't limited when it's zero.
+    type MinLaunchInterval: Get<Self::BlockNumber>;
     /// The overarching event type.
     type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
 }
@@ -48,18 +68,58 @@ decl_event! {
     }
 }
 
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        /// Robot was launched by sender less than `MinLaunchInterval` blocks ago.
+        TooFrequentLaunch,
+    }
+}
+
 decl_storage! {
-    trait Store for Module<T: Trait> as Launch {}
+    trait Store for Module<T: Trait> as Launch {
+        /// Block of the latest launch by sender and robot, kept only when launches are limited.
+        LastLaunch get(fn last_launch):
+            double_map hasher(blake2_128_concat) T::AccountId,
+                       hasher(blake2_128_concat) T::AccountId => Option<T::BlockNumber>;
+    }
 }
 
 decl_module! {
     pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        type Error = Error<T>;
+
         fn deposit_event() = default;
 
-        /// Launch a robot with given parameter.
-        #[weight = 5_000_000]
+        /// Launch a robot with given parameter, launch fee is withdrawn from sender.
+        #[weight = 5_000_000 + T::DbWeight::get().reads_writes(2, 2)]
         fn launch(origin, robot: T::AccountId, param: T::Parameter) {
             let sender = ensure_signed(origin)?;
+
+            let now = <frame_system::Module<T>>::block_number();
+            let interval = T::MinLaunchInterval::get();
+            if !interval.is_zero() {
+                if let Some(last) = <LastLaunch<T>>::get(&sender, &robot) {
+                    ensure!(
+                        now >= last.saturating_add(interval),
+                        Error::<T>::TooFrequentLaunch
+                    );
+                }
+            }
+
+            let fee = T::LaunchFee::get();
+            if !fee.is_zero() {
+                let imbalance = T::Currency::withdraw(
+                    &sender,
+                    fee,
+                    WithdrawReason::Fee.into(),
+                    ExistenceRequirement::KeepAlive,
+                )?;
+                T::FeeDestination::on_unbalanced(imbalance);
+            }
+
+            if !interval.is_zero() {
+                <LastLaunch<T>>::insert(&sender, &robot, now);
+            }
             Self::deposit_event(RawEvent::NewLaunch(sender, robot, param));
         }
     }
@@ -70,11 +130,11 @@ mod tests {
     use super::*;
 
     use frame_support::{
-        assert_err, assert_ok, impl_outer_origin, parameter_types, weights::Weight,
+        assert_err, assert_noop, assert_ok, impl_outer_origin, parameter_types, weights::Weight,
     };
-    use node_primitives::Moment;
     use sp_core::H256;
     use sp_runtime::{testing::Header, traits::IdentityLookup, DispatchError, Perbill};
+    use std::cell::RefCell;
 
     impl_outer_origin! {
         pub enum Origin for Runtime {}
@@ -107,7 +167,7 @@ mod tests {
         type AvailableBlockRatio = AvailableBlockRatio;
         type Version = ();
         type ModuleToIndex = ();
-        type AccountData = ();
+        type AccountData = pallet_balances::AccountData<u64>;
         type OnNewAccount = ();
         type OnKilledAccount = ();
         type DbWeight = ();
@@ -119,39 +179,144 @@ mod tests {
     }
 
     parameter_types! {
-        pub const MinimumPeriod: Moment = 5;
+        pub const ExistentialDeposit: u64 = 1;
+    }
+
+    impl pallet_balances::Trait for Runtime {
+        type Balance = u64;
+        type Event = ();
+        type DustRemoval = ();
+        type ExistentialDeposit = ExistentialDeposit;
+        type AccountStore = System;
+        type WeightInfo = ();
+    }
+
+    thread_local! {
+        static LAUNCH_FEE: RefCell<u64> = RefCell::new(0);
+        static MIN_LAUNCH_INTERVAL: RefCell<u64> = RefCell::new(0);
+    }
+
+    /// Launch fee switchable by tests.
+    pub struct LaunchFee;
+    impl Get<u64> for LaunchFee {
+        fn get() -> u64 {
+            LAUNCH_FEE.with(|fee| *fee.borrow())
+        }
+    }
+
+    /// Launch interval switchable by tests.
+    pub struct MinLaunchInterval;
+    impl Get<u64> for MinLaunchInterval {
+        fn get() -> u64 {
+            MIN_LAUNCH_INTERVAL.with(|interval| *interval.borrow())
+        }
+    }
+
+    /// Launch fees are sent to treasury account.
+    pub struct MockTreasury;
+    impl OnUnbalanced<pallet_balances::NegativeImbalance<Runtime>> for MockTreasury {
+        fn on_nonzero_unbalanced(amount: pallet_balances::NegativeImbalance<Runtime>) {
+            Balances::resolve_creating(&TREASURY, amount);
+        }
     }
 
     impl Trait for Runtime {
         type Parameter = bool;
+        type Currency = Balances;
+        type LaunchFee = LaunchFee;
+        type FeeDestination = MockTreasury;
+        type MinLaunchInterval = MinLaunchInterval;
         type Event = ();
     }
 
-    fn new_test_ext() -> sp_io::TestExternalities {
-        let storage = frame_system::GenesisConfig::default()
+    const TREASURY: u64 = 100;
+
+    fn new_test_ext(fee: u64, interval: u64) -> sp_io::TestExternalities {
+        LAUNCH_FEE.with(|v| *v.borrow_mut() = fee);
+        MIN_LAUNCH_INTERVAL.with(|v| *v.borrow_mut() = interval);
+        let mut storage = frame_system::GenesisConfig::default()
             .build_storage::<Runtime>()
             .unwrap();
+        pallet_balances::GenesisConfig::<Runtime> {
+            balances: vec![(1, 100), (2, 6)],
+        }
+        .assimilate_storage(&mut storage)
+        .unwrap();
         storage.into()
     }
 
+    type System = frame_system::Module<Runtime>;
+    type Balances = pallet_balances::Module<Runtime>;
     type Launch = Module<Runtime>;
+    type BalancesError = pallet_balances::Error<Runtime, pallet_balances::DefaultInstance>;
 
     #[test]
     fn test_launch() {
-        new_test_ext().execute_with(|| {
+        new_test_ext(0, 0).execute_with(|| {
             let sender = 1;
             let robot = 42;
             assert_ok!(Launch::launch(Origin::signed(sender), robot, true));
+            assert_ok!(Launch::launch(Origin::signed(sender), robot, false));
+            // Free and unlimited launches keep nothing
+            assert_eq!(Balances::free_balance(sender), 100);
+            assert_eq!(Launch::last_launch(sender, robot), None);
         })
     }
 
     #[test]
     fn test_bad_origin() {
-        new_test_ext().execute_with(|| {
+        new_test_ext(0, 0).execute_with(|| {
             assert_err!(
                 Launch::launch(Origin::none(), 0, false),
                 DispatchError::BadOrigin
             );
         })
     }
+
+    #[test]
+    fn test_launch_fee() {
+        new_test_ext(3, 0).execute_with(|| {
+            assert_ok!(Launch::launch(Origin::signed(1), 42, true));
+            assert_eq!(Balances::free_balance(1), 97);
+            assert_eq!(Balances::free_balance(TREASURY), 3);
+
+            // Fee doesn't kill sender account
+            assert_ok!(Launch::launch(Origin::signed(2), 42, true));
+            assert_eq!(Balances::free_balance(2), 3);
+            assert_noop!(
+                Launch::launch(Origin::signed(2), 42, true),
+                BalancesError::KeepAlive
+            );
+            assert_noop!(
+                Launch::launch(Origin::signed(3), 42, true),
+                BalancesError::InsufficientBalance
+            );
+            assert_eq!(Balances::free_balance(TREASURY), 6);
+        })
+    }
+
+    #[test]
+    fn test_launch_rate_limit() {
+        new_test_ext(1, 10).execute_with(|| {
+            System::set_block_number(5);
+            assert_ok!(Launch::launch(Origin::signed(1), 42, true));
+            assert_eq!(Launch::last_launch(1, 42), Some(5));
+
+            // Limited launch is rejected before fee is charged
+            System::set_block_number(14);
+            assert_noop!(
+                Launch::launch(Origin::signed(1), 42, false),
+                Error::<Runtime>::TooFrequentLaunch
+            );
+            assert_eq!(Balances::free_balance(1), 99);
+
+            // Limit is kept per sender and robot pair
+            assert_ok!(Launch::launch(Origin::signed(1), 43, false));
+            assert_ok!(Launch::launch(Origin::signed(2), 42, false));
+
+            System::set_block_number(15);
+            assert_ok!(Launch::launch(Origin::signed(1), 42, false));
+            assert_eq!(Launch::last_launch(1, 42), Some(15));
+        })
+    }
 }
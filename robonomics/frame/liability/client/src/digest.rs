@@ -0,0 +1,110 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Checking accounts against liability filter of block header digest, e.g. by devices
+//! following headers only.
+//!
+//! Runtime deposits filter of parties of liabilities changed in block into its digest,
+//! see `DigestBloomBits` constant. Account not in filter has no liability changes in block,
+//! so block events are fetched only for blocks filter of which contains account. Filter
+//! has false positives, fetched events are still checked by account.
+//!
+//! ```ignore
+//! let header = client.header(Some(hash)).await?.unwrap();
+//! let events = fetch_if_touched(&header.digest, &account, || client.events(hash)).await?;
+//! ```
+
+use crate::Result;
+use codec::{Decode, Encode};
+use futures::Future;
+pub use pallet_robonomics_liability_rpc_runtime_api::{AccountBloom, BLOOM_DIGEST_PREFIX};
+use sp_runtime::generic::{Digest, DigestItem};
+
+/// Liability filter of block digest, `None` when block has no liability changes.
+pub fn digest_bloom<Hash: Encode + Decode>(digest: &Digest<Hash>) -> Option<AccountBloom> {
+    digest.logs.iter().find_map(|item| match item {
+        DigestItem::Other(payload) => AccountBloom::from_digest(payload),
+        _ => None,
+    })
+}
+
+/// Liability of account is probably changed in block of given digest.
+pub fn is_touched<Hash: Encode + Decode, AccountId: Encode>(
+    digest: &Digest<Hash>,
+    account: &AccountId,
+) -> bool {
+    digest_bloom(digest).map_or(false, |bloom| bloom.contains(account))
+}
+
+/// Fetch e.g. events of block only when liability of account is probably changed in it,
+/// `None` for block without changes of account.
+pub async fn fetch_if_touched<Hash, AccountId, F, FF, E>(
+    digest: &Digest<Hash>,
+    account: &AccountId,
+    fetch: F,
+) -> Result<Option<E>>
+where
+    Hash: Encode + Decode,
+    AccountId: Encode,
+    F: FnOnce() -> FF,
+    FF: Future<Output = Result<E>>,
+{
+    if is_touched(digest, account) {
+        fetch().await.map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{executor::block_on, future};
+    use sp_core::H256;
+
+    fn digest(accounts: &[[u8; 32]]) -> Digest<H256> {
+        let mut bloom = AccountBloom::new(1024, 11);
+        for account in accounts {
+            bloom.insert(account);
+        }
+        Digest {
+            logs: vec![
+                DigestItem::Other(b"other".to_vec()),
+                DigestItem::Other(bloom.to_digest()),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_fetch_if_touched() {
+        let digest = digest(&[[1; 32], [2; 32]]);
+        assert!(is_touched(&digest, &[1u8; 32]));
+        assert!(!is_touched(&digest, &[3u8; 32]));
+        assert!(!is_touched(&Digest::<H256>::default(), &[1u8; 32]));
+
+        let fetched = block_on(fetch_if_touched(&digest, &[2u8; 32], || {
+            future::ok::<_, crate::Error>(vec![7u8])
+        }));
+        assert_eq!(fetched.unwrap(), Some(vec![7]));
+        let skipped = block_on(fetch_if_touched(
+            &digest,
+            &[3u8; 32],
+            || -> future::Ready<crate::Result<()>> { panic!("untouched block is fetched") },
+        ));
+        assert!(skipped.unwrap().is_none());
+    }
+}
@@ -0,0 +1,526 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Typed client of the Robonomics liability module for agents.
+//!
+//! Liability calls are encoded from plain structs and submitted by JSON-RPC connection
+//! of [substrate-subxt](https://github.com/paritytech/substrate-subxt) client, so agents
+//! don't depend on the runtime or the module itself. Parties proofs are signed over
+//! the same payloads the module checks, extrinsic events are decoded into typed results.
+//!
+//! ```ignore
+//! let client = ClientBuilder::<Robonomics>::new().set_url(remote).build().await?;
+//! let call = CreateCall::<Robonomics>::signed(technics, (), &promisee, &promisor);
+//! let created = create(&client, call).await?;
+//! let call = FinalizeCall::<Robonomics>::signed(created.event.index, report, &promisor);
+//! let reported = finalize(&client, call).await?;
+//! ```
+
+pub mod digest;
+pub mod module;
+pub mod page;
+pub mod upload;
+
+pub use digest::{digest_bloom, fetch_if_touched, is_touched, AccountBloom};
+pub use module::*;
+pub use page::{follow_pages, Page, MAX_PAGE_ITEMS};
+pub use pallet_robonomics_liability_rpc_runtime_api::{
+    expiring_params_payload, liability_id, params_payload, report_hash, report_payload,
+    technics_hash, EconomicsSummary, LiabilityInfo, LiabilityState, ValidityError,
+};
+pub use upload::{add_file, finalize_with_file, Finalization};
+
+use jsonrpsee::{
+    client::RequestError,
+    common::{Error as RpcError, ErrorCode},
+};
+use sp_core::crypto::Pair;
+use sp_runtime::{
+    traits::{IdentifyAccount, SignedExtension},
+    AccountId32, MultiSignature, MultiSigner,
+};
+use substrate_subxt::{
+    balances::{Balances, BalancesEventsDecoder},
+    system::{ExtrinsicSuccessEvent, System},
+    Client, EventsDecoder, ExtrinsicSuccess, Runtime, SignedExtra, Signer, UncheckedExtrinsic,
+};
+
+/// Liability client Result typedef.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Liability client errors.
+#[derive(Debug, derive_more::Display, derive_more::From)]
+pub enum Error {
+    /// Node RPC or extrinsic dispatch error.
+    Subxt(substrate_subxt::Error),
+    /// Liability event decoding error.
+    Codec(codec::Error),
+    /// Extrinsic is included without expected liability event.
+    #[display(fmt = "extrinsic {} has no {} event", _0, _1)]
+    MissingEvent(String, &'static str),
+    /// Result file reading error.
+    Io(std::io::Error),
+    /// IPFS HTTP API error.
+    #[display(fmt = "IPFS error: {}", _0)]
+    Ipfs(String),
+    /// Runtime encodes liability index fixed-width, its spec version is given.
+    #[display(fmt = "runtime spec version {} has fixed-width index", _0)]
+    UnsupportedRuntime(u32),
+    /// Unsigned liability call is rejected by transaction pool for given reason.
+    #[display(fmt = "liability call rejected: {:?}", _0)]
+    Rejected(ValidityError),
+}
+
+impl Error {
+    /// Error of submission, custom validity code of rejected call is translated into
+    /// `Rejected` reason.
+    fn submission(err: substrate_subxt::Error) -> Self {
+        match custom_validity_code(&err).and_then(ValidityError::from_code) {
+            Some(reason) => Error::Rejected(reason),
+            None => Error::Subxt(err),
+        }
+    }
+}
+
+/// RPC error code of extrinsic rejected by transaction pool as invalid.
+const POOL_INVALID_TX: i64 = 1010;
+
+/// Node reports `InvalidTransaction::Custom` validity of rejected extrinsic in RPC error
+/// data as this prefix followed by the code.
+const CUSTOM_ERROR_PREFIX: &str = "Custom error: ";
+
+/// Custom validity code of extrinsic rejected by transaction pool, if any.
+fn custom_validity_code(err: &substrate_subxt::Error) -> Option<u8> {
+    match err {
+        substrate_subxt::Error::Rpc(RequestError::Request(RpcError {
+            code: ErrorCode::ServerError(POOL_INVALID_TX),
+            data: Some(data),
+            ..
+        })) => {
+            let data = data.as_str()?;
+            if !data.starts_with(CUSTOM_ERROR_PREFIX) {
+                return None;
+            }
+            data[CUSTOM_ERROR_PREFIX.len()..].parse().ok()
+        }
+        _ => None,
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Subxt(ref err) => Some(err),
+            Error::Codec(ref err) => Some(err),
+            Error::Io(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Liability extrinsic included in block.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Included<T: System, E> {
+    /// Block hash.
+    pub block: T::Hash,
+    /// Extrinsic hash.
+    pub extrinsic: T::Hash,
+    /// Dispatch weight of extrinsic.
+    pub weight: u64,
+    /// Liability event emitted by extrinsic.
+    pub event: E,
+}
+
+/// Result of `create` extrinsic.
+pub type Created<T> = Included<T, NewLiabilityEvent<T>>;
+
+/// Result of `finalize` extrinsic.
+pub type Reported<T> = Included<T, NewReportEvent<T>>;
+
+/// Result of `demand` or `offer` extrinsic.
+pub type Posted<T> = Included<T, OrderPostedEvent<T>>;
+
+/// Result of `match_orders` extrinsic.
+pub type Matched<T> = Included<T, MatchedEvent<T>>;
+
+/// The first `spec_version` of Robonomics runtimes encoding liability index compactly
+/// in calls and events, earlier runtimes reject calls of this client.
+pub const COMPACT_INDEX_SPEC_VERSION: u32 = 2;
+
+/// Check that runtime of given `spec_version`, e.g. returned by `state_getRuntimeVersion`,
+/// encodes liability index as this client does.
+pub fn ensure_compact_index(spec_version: u32) -> Result<()> {
+    if spec_version < COMPACT_INDEX_SPEC_VERSION {
+        Err(Error::UnsupportedRuntime(spec_version))
+    } else {
+        Ok(())
+    }
+}
+
+/// Signer of extrinsics sent by account, e.g. `PairSigner`.
+pub type AccountSigner<'a, T> = &'a (dyn Signer<T> + Send + Sync);
+
+/// Sign liability parameters by party key.
+pub fn sign_params<P, TechnicalParam, EconomicalParam>(
+    pair: &P,
+    technics: &TechnicalParam,
+    economics: &EconomicalParam,
+) -> MultiSignature
+where
+    P: Pair,
+    MultiSignature: From<P::Signature>,
+    TechnicalParam: codec::Encode,
+    EconomicalParam: codec::Encode,
+{
+    pair.sign(&params_payload(technics, economics)).into()
+}
+
+/// Lifetime in blocks of expiring parties proofs made by agents, about an hour
+/// of 6 seconds blocks.
+pub const DEFAULT_PROOF_HORIZON: u32 = 600;
+
+/// Sign liability parameters by party key, signature is valid until `expires_at` block
+/// inclusive, e.g. best block plus `DEFAULT_PROOF_HORIZON`.
+pub fn sign_params_until<P, TechnicalParam, EconomicalParam, BlockNumber>(
+    pair: &P,
+    technics: &TechnicalParam,
+    economics: &EconomicalParam,
+    expires_at: &BlockNumber,
+) -> MultiSignature
+where
+    P: Pair,
+    MultiSignature: From<P::Signature>,
+    TechnicalParam: codec::Encode,
+    EconomicalParam: codec::Encode,
+    BlockNumber: codec::Encode,
+{
+    pair.sign(&expiring_params_payload(technics, economics, expires_at))
+        .into()
+}
+
+/// Sign liability report by promisor key.
+pub fn sign_report<P, Index, TechnicalReport>(
+    pair: &P,
+    index: &Index,
+    report: &TechnicalReport,
+) -> MultiSignature
+where
+    P: Pair,
+    MultiSignature: From<P::Signature>,
+    Index: codec::Encode,
+    TechnicalReport: codec::Encode,
+{
+    pair.sign(&report_payload(index, report)).into()
+}
+
+/// Account of party key.
+pub fn account<P: Pair>(pair: &P) -> AccountId32
+where
+    MultiSigner: From<P::Public>,
+{
+    MultiSigner::from(pair.public()).into_account()
+}
+
+impl<T: Liability> CreateCall<T>
+where
+    T::AccountId: From<AccountId32>,
+{
+    /// Liability of given parameters signed by both parties.
+    pub fn signed<P>(
+        technics: T::TechnicalParam,
+        economics: T::EconomicalParam,
+        promisee: &P,
+        promisor: &P,
+    ) -> Self
+    where
+        P: Pair,
+        MultiSigner: From<P::Public>,
+        MultiSignature: From<P::Signature>,
+    {
+        CreateCall {
+            promisee_proof: sign_params(promisee, &technics, &economics),
+            promisor_proof: sign_params(promisor, &technics, &economics),
+            promisee: account(promisee).into(),
+            promisor: account(promisor).into(),
+            technics,
+            economics,
+        }
+    }
+}
+
+impl<T: Liability> CreateExpiringCall<T>
+where
+    T::AccountId: From<AccountId32>,
+{
+    /// Liability of given parameters signed by both parties until `expires_at` block.
+    pub fn signed<P>(
+        technics: T::TechnicalParam,
+        economics: T::EconomicalParam,
+        expires_at: <T as System>::BlockNumber,
+        promisee: &P,
+        promisor: &P,
+    ) -> Self
+    where
+        P: Pair,
+        MultiSigner: From<P::Public>,
+        MultiSignature: From<P::Signature>,
+    {
+        CreateExpiringCall {
+            promisee_proof: sign_params_until(promisee, &technics, &economics, &expires_at),
+            promisor_proof: sign_params_until(promisor, &technics, &economics, &expires_at),
+            promisee: account(promisee).into(),
+            promisor: account(promisor).into(),
+            technics,
+            economics,
+            expires_at,
+        }
+    }
+}
+
+impl<T: Liability> FinalizeCall<T> {
+    /// Liability report signed by promisor.
+    pub fn signed<P>(index: T::LiabilityIndex, report: T::TechnicalReport, promisor: &P) -> Self
+    where
+        P: Pair,
+        MultiSignature: From<P::Signature>,
+    {
+        FinalizeCall {
+            proof: sign_report(promisor, &index, &report),
+            index,
+            report,
+        }
+    }
+}
+
+impl<T: Liability> DemandCall<T> {
+    /// Demand of single unit liability signed by promisee.
+    pub fn signed<P>(
+        technics: T::TechnicalParam,
+        economics: T::EconomicalParam,
+        deadline: <T as System>::BlockNumber,
+        promisee: &P,
+    ) -> Self
+    where
+        P: Pair,
+        MultiSignature: From<P::Signature>,
+    {
+        DemandCall {
+            proof: sign_params(promisee, &technics, &economics),
+            technics,
+            economics,
+            quantity: 1,
+            deadline,
+        }
+    }
+}
+
+impl<T: Liability> OfferCall<T> {
+    /// Offer of single unit liability signed by promisor.
+    pub fn signed<P>(
+        technics: T::TechnicalParam,
+        economics: T::EconomicalParam,
+        deadline: <T as System>::BlockNumber,
+        promisor: &P,
+    ) -> Self
+    where
+        P: Pair,
+        MultiSignature: From<P::Signature>,
+    {
+        OfferCall {
+            proof: sign_params(promisor, &technics, &economics),
+            technics,
+            economics,
+            quantity: 1,
+            deadline,
+        }
+    }
+}
+
+/// Submit unsigned `create` and wait for its inclusion, returns created liability.
+pub async fn create<T>(client: &Client<T>, call: CreateCall<T>) -> Result<Created<T>>
+where
+    T: Runtime + Balances + Liability,
+{
+    let result = submit_unsigned(client, call).await?;
+    included(result, "NewLiability")
+}
+
+/// Submit unsigned `create_expiring` and wait for its inclusion, returns created liability.
+pub async fn create_expiring<T>(
+    client: &Client<T>,
+    call: CreateExpiringCall<T>,
+) -> Result<Created<T>>
+where
+    T: Runtime + Balances + Liability,
+{
+    let result = submit_unsigned(client, call).await?;
+    included(result, "NewLiability")
+}
+
+/// Submit unsigned `finalize` and wait for its inclusion, returns published report.
+pub async fn finalize<T>(client: &Client<T>, call: FinalizeCall<T>) -> Result<Reported<T>>
+where
+    T: Runtime + Balances + Liability,
+{
+    let result = submit_unsigned(client, call).await?;
+    included(result, "NewReport")
+}
+
+/// Submit `demand` signed by promisee and wait for its inclusion, returns posted order.
+pub async fn demand<T>(
+    client: &Client<T>,
+    call: DemandCall<T>,
+    signer: AccountSigner<'_, T>,
+) -> Result<Posted<T>>
+where
+    T: Runtime + Balances + Liability,
+    <<T::Extra as SignedExtra<T>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+{
+    let result = submit_signed(client, call, signer).await?;
+    included(result, "OrderPosted")
+}
+
+/// Submit `offer` signed by promisor and wait for its inclusion, returns posted order.
+pub async fn offer<T>(
+    client: &Client<T>,
+    call: OfferCall<T>,
+    signer: AccountSigner<'_, T>,
+) -> Result<Posted<T>>
+where
+    T: Runtime + Balances + Liability,
+    <<T::Extra as SignedExtra<T>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+{
+    let result = submit_signed(client, call, signer).await?;
+    included(result, "OrderPosted")
+}
+
+/// Submit `match_orders` and wait for its inclusion, returns created liability.
+pub async fn match_orders<T>(
+    client: &Client<T>,
+    call: MatchOrdersCall<T>,
+    signer: AccountSigner<'_, T>,
+) -> Result<Matched<T>>
+where
+    T: Runtime + Balances + Liability,
+    <<T::Extra as SignedExtra<T>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+{
+    let result = submit_signed(client, call, signer).await?;
+    included(result, "Matched")
+}
+
+/// Decoder of liability events, market types aren't associated types of `Liability`.
+fn events_decoder<T>(client: &Client<T>) -> EventsDecoder<T>
+where
+    T: Runtime + Balances + Liability,
+{
+    let mut decoder = EventsDecoder::<T>::new(client.metadata().clone());
+    decoder.with_balances();
+    decoder.with_liability();
+    decoder.register_type_size::<OrderId>("OrderId");
+    decoder.register_type_size::<MarketId>("MarketId");
+    decoder.register_type_size::<u8>("Side");
+    decoder.register_type_size::<<T as Balances>::Balance>("Funds");
+    decoder.register_type_size::<u32>("Perbill");
+    decoder
+}
+
+async fn submit_signed<T, C>(
+    client: &Client<T>,
+    call: C,
+    signer: AccountSigner<'_, T>,
+) -> Result<ExtrinsicSuccess<T>>
+where
+    T: Runtime + Balances + Liability,
+    C: substrate_subxt::Call<T> + Send + Sync,
+    <<T::Extra as SignedExtra<T>>::Extra as SignedExtension>::AdditionalSigned: Send + Sync,
+{
+    let extrinsic = client.create_signed(call, signer).await?;
+    Ok(client
+        .submit_and_watch_extrinsic(extrinsic, events_decoder(client))
+        .await?)
+}
+
+async fn submit_unsigned<T, C>(client: &Client<T>, call: C) -> Result<ExtrinsicSuccess<T>>
+where
+    T: Runtime + Balances + Liability,
+    C: substrate_subxt::Call<T> + Send + Sync,
+{
+    let decoder = events_decoder(client);
+    let extrinsic = UncheckedExtrinsic::<T>::new_unsigned(client.encode(call)?);
+    client
+        .submit_and_watch_extrinsic(extrinsic, decoder)
+        .await
+        .map_err(Error::submission)
+}
+
+fn included<T, E>(result: ExtrinsicSuccess<T>, name: &'static str) -> Result<Included<T, E>>
+where
+    T: System,
+    E: substrate_subxt::Event<T>,
+{
+    let event = result
+        .find_event::<E>()?
+        .ok_or_else(|| Error::MissingEvent(format!("{:?}", result.extrinsic), name))?;
+    let weight = result
+        .find_event::<ExtrinsicSuccessEvent<T>>()?
+        .map_or(0, |success| success.info.weight);
+    Ok(Included {
+        block: result.block,
+        extrinsic: result.extrinsic,
+        weight,
+        event,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rpc_error(code: i64, data: &str) -> substrate_subxt::Error {
+        substrate_subxt::Error::Rpc(RequestError::Request(RpcError {
+            code: ErrorCode::ServerError(code),
+            message: "Invalid Transaction".into(),
+            data: Some(data.into()),
+        }))
+    }
+
+    #[test]
+    fn test_custom_validity_code() {
+        let err = rpc_error(POOL_INVALID_TX, "Custom error: 2");
+        assert_eq!(custom_validity_code(&err), Some(2));
+        assert!(matches!(
+            Error::submission(err),
+            Error::Rejected(ValidityError::BadPromisorProof)
+        ));
+        assert_eq!(
+            custom_validity_code(&rpc_error(POOL_INVALID_TX, "Custom error: 11")),
+            Some(11)
+        );
+        assert_eq!(
+            custom_validity_code(&rpc_error(POOL_INVALID_TX, "Custom error: 300")),
+            None
+        );
+        assert_eq!(
+            custom_validity_code(&rpc_error(POOL_INVALID_TX, "Transaction is outdated")),
+            None
+        );
+        // Custom error text of other RPC errors isn't validity code
+        assert_eq!(custom_validity_code(&rpc_error(1, "Custom error: 2")), None);
+        let err = substrate_subxt::Error::Other("Custom error: 2".into());
+        assert_eq!(custom_validity_code(&err), None);
+    }
+}
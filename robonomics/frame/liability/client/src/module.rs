@@ -0,0 +1,314 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! SubXt compatible robonomics-liability pallet abstration.
+
+use codec::{Codec, Decode, Encode, EncodeLike, HasCompact};
+pub use pallet_robonomics_liability_rpc_runtime_api::{RecordState, Tag};
+use sp_core::H256;
+use sp_runtime::{traits::Member, MultiSignature};
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use substrate_subxt::{
+    balances::{Balances, BalancesEventsDecoder},
+    system::{System, SystemEventsDecoder},
+};
+use substrate_subxt_proc_macro::{module, Call, Event, Store};
+
+/// The subset of the `pallet_robonomics_liability::Trait` that a client must implement.
+#[module]
+pub trait Liability: System + Balances {
+    type LiabilityIndex: Codec + EncodeLike + Member + Default + HasCompact;
+    type TechnicalParam: Codec + EncodeLike + Member + Default;
+    type EconomicalParam: Codec + EncodeLike + Member + Default;
+    type TechnicalReport: Codec + EncodeLike + Member + Default;
+}
+
+/// Create liability of given parameters signed by both parties, it's unsigned extrinsic.
+#[derive(Clone, Debug, Eq, PartialEq, Call, Encode)]
+pub struct CreateCall<T: Liability> {
+    /// Technical parameter of liability.
+    pub technics: T::TechnicalParam,
+    /// Economical parameter of liability.
+    pub economics: T::EconomicalParam,
+    /// Promisee account.
+    pub promisee: <T as System>::AccountId,
+    /// Promisor account.
+    pub promisor: <T as System>::AccountId,
+    /// Promisee signature of liability parameters.
+    pub promisee_proof: MultiSignature,
+    /// Promisor signature of liability parameters.
+    pub promisor_proof: MultiSignature,
+}
+
+/// Create liability of given parameters signed by both parties until given block,
+/// it's unsigned extrinsic.
+#[derive(Clone, Debug, Eq, PartialEq, Call, Encode)]
+pub struct CreateExpiringCall<T: Liability> {
+    /// Technical parameter of liability.
+    pub technics: T::TechnicalParam,
+    /// Economical parameter of liability.
+    pub economics: T::EconomicalParam,
+    /// Promisee account.
+    pub promisee: <T as System>::AccountId,
+    /// Promisor account.
+    pub promisor: <T as System>::AccountId,
+    /// The last block when parties proofs are valid.
+    pub expires_at: <T as System>::BlockNumber,
+    /// Promisee signature of liability parameters and expiry block.
+    pub promisee_proof: MultiSignature,
+    /// Promisor signature of liability parameters and expiry block.
+    pub promisor_proof: MultiSignature,
+}
+
+/// Publish liability report signed by promisor, it's unsigned extrinsic.
+#[derive(Clone, Debug, Eq, PartialEq, Call, Encode)]
+pub struct FinalizeCall<T: Liability> {
+    /// Liability index, it's compact encoded.
+    #[codec(compact)]
+    pub index: T::LiabilityIndex,
+    /// Technical report of liability.
+    pub report: T::TechnicalReport,
+    /// Promisor signature of liability report.
+    pub proof: MultiSignature,
+}
+
+/// Sequential identifier of market order.
+pub type OrderId = u64;
+
+/// Identifier of market, e.g. robot model.
+pub type MarketId = u32;
+
+/// Side of market order book.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Encode, Decode)]
+pub enum Side {
+    /// Demands of promisees.
+    Demand,
+    /// Offers of promisors.
+    Offer,
+}
+
+/// Post promisee demand signed over parameters of the whole quantity.
+#[derive(Clone, Debug, Eq, PartialEq, Call, Encode)]
+pub struct DemandCall<T: Liability> {
+    /// Technical parameter of liability.
+    pub technics: T::TechnicalParam,
+    /// Economical parameter of single unit liability.
+    pub economics: T::EconomicalParam,
+    /// Quantity of units.
+    pub quantity: u32,
+    /// Order couldn't be matched after this block.
+    pub deadline: <T as System>::BlockNumber,
+    /// Promisee signature of liability parameters.
+    pub proof: MultiSignature,
+}
+
+/// Post promisor offer signed over parameters of the whole quantity.
+#[derive(Clone, Debug, Eq, PartialEq, Call, Encode)]
+pub struct OfferCall<T: Liability> {
+    /// Technical parameter of liability.
+    pub technics: T::TechnicalParam,
+    /// Economical parameter of single unit liability.
+    pub economics: T::EconomicalParam,
+    /// Quantity of units.
+    pub quantity: u32,
+    /// Order couldn't be matched after this block.
+    pub deadline: <T as System>::BlockNumber,
+    /// Promisor signature of liability parameters.
+    pub proof: MultiSignature,
+}
+
+/// Match demand and offer into liability.
+#[derive(Clone, Debug, Eq, PartialEq, Call, Encode)]
+pub struct MatchOrdersCall<T: Liability> {
+    /// Demand order.
+    pub demand_id: OrderId,
+    /// Offer order.
+    pub offer_id: OrderId,
+    /// Runtime marker.
+    pub _runtime: PhantomData<T>,
+}
+
+/// New liability created.
+#[derive(Clone, Debug, Eq, PartialEq, Event, Decode)]
+pub struct NewLiabilityEvent<T: Liability> {
+    /// Liability index, it's compact encoded.
+    #[codec(compact)]
+    pub index: T::LiabilityIndex,
+    /// Content-derived liability id.
+    pub id: H256,
+    /// Technical parameter hash of liability.
+    pub technics: H256,
+    /// Economical parameter of liability.
+    pub economics: T::EconomicalParam,
+    /// Promisee account.
+    pub promisee: <T as System>::AccountId,
+    /// Promisor account.
+    pub promisor: <T as System>::AccountId,
+    /// Creation block.
+    pub block: <T as System>::BlockNumber,
+    /// Tags of liability.
+    pub tags: Vec<Tag>,
+}
+
+/// Liability report published.
+#[derive(Clone, Debug, Eq, PartialEq, Event, Decode)]
+pub struct NewReportEvent<T: Liability> {
+    /// Liability index, it's compact encoded.
+    #[codec(compact)]
+    pub index: T::LiabilityIndex,
+    /// Technical report hash of liability.
+    pub report: H256,
+    /// Report block.
+    pub block: <T as System>::BlockNumber,
+}
+
+/// Market order posted.
+#[derive(Clone, Debug, Eq, PartialEq, Event, Decode)]
+pub struct OrderPostedEvent<T: Liability> {
+    /// Order identifier.
+    pub id: OrderId,
+    /// Order book side.
+    pub side: Side,
+    /// Market of order.
+    pub market: MarketId,
+    /// Technical parameter hash of liability.
+    pub technics: H256,
+    /// Economical parameter of single unit liability.
+    pub economics: T::EconomicalParam,
+    /// Quantity of units.
+    pub quantity: u32,
+    /// Single unit price.
+    pub price: <T as Balances>::Balance,
+    /// Order creator.
+    pub sender: <T as System>::AccountId,
+    /// Order deadline.
+    pub deadline: <T as System>::BlockNumber,
+}
+
+/// Demand and offer matched.
+#[derive(Clone, Debug, Eq, PartialEq, Event, Decode)]
+pub struct MatchedEvent<T: Liability> {
+    /// Demand order.
+    pub demand_id: OrderId,
+    /// Offer order.
+    pub offer_id: OrderId,
+    /// Index of created liability, it's compact encoded.
+    #[codec(compact)]
+    pub index: T::LiabilityIndex,
+    /// Market of orders.
+    pub market: MarketId,
+    /// Single unit price.
+    pub price: <T as Balances>::Balance,
+    /// Filled quantity.
+    pub quantity: u32,
+    /// Matching account.
+    pub matcher: <T as System>::AccountId,
+}
+
+/// Stored liability with its state and report hash, agreement fields follow the record header
+/// as they are encoded by runtime `SignedLiability`.
+#[derive(Clone, Debug, Eq, PartialEq, Encode, Decode)]
+pub struct LiabilityRecord<T: Liability> {
+    /// Technical report hash, `None` until it's published.
+    pub report_hash: Option<H256>,
+    /// Finalization state.
+    pub state: RecordState,
+    /// Block when liability created.
+    pub created_at: <T as System>::BlockNumber,
+    /// Block when liability finalized.
+    pub finalized_at: Option<<T as System>::BlockNumber>,
+    /// Technical parameter of liability.
+    pub technics: T::TechnicalParam,
+    /// Economical parameter of liability.
+    pub economics: T::EconomicalParam,
+    /// Promisee account.
+    pub promisee: <T as System>::AccountId,
+    /// Promisor account.
+    pub promisor: <T as System>::AccountId,
+}
+
+impl<T: Liability> Default for LiabilityRecord<T> {
+    fn default() -> Self {
+        LiabilityRecord {
+            report_hash: None,
+            state: RecordState::Open,
+            created_at: Default::default(),
+            finalized_at: None,
+            technics: Default::default(),
+            economics: Default::default(),
+            promisee: Default::default(),
+            promisor: Default::default(),
+        }
+    }
+}
+
+impl<T: Liability> LiabilityRecord<T> {
+    /// Liability report is published or liability is finalized by root.
+    pub fn is_finalized(&self) -> bool {
+        self.state != RecordState::Open
+    }
+}
+
+/// Liability record by index.
+#[derive(Clone, Debug, Eq, PartialEq, Store, Encode)]
+pub struct LiabilitiesStore<T: Liability> {
+    /// Liability index.
+    #[store(returns = LiabilityRecord<T>)]
+    pub index: T::LiabilityIndex,
+}
+
+/// Technical report kept by its hash.
+#[derive(Clone, Debug, Eq, PartialEq, Encode, Decode)]
+pub struct ReportPreimage<T: Liability> {
+    /// Technical report of complete works.
+    pub report: T::TechnicalReport,
+    /// Account that noted preimage and its deposit, `None` for preimages of accepted reports.
+    pub depositor: Option<(<T as System>::AccountId, <T as Balances>::Balance)>,
+    /// Preimage could be pruned after this block.
+    pub expires_at: <T as System>::BlockNumber,
+}
+
+impl<T: Liability> Default for ReportPreimage<T> {
+    fn default() -> Self {
+        ReportPreimage {
+            report: Default::default(),
+            depositor: None,
+            expires_at: Default::default(),
+        }
+    }
+}
+
+/// Report preimage by report hash, it's kept during challenge window of report.
+#[derive(Clone, Debug, Eq, PartialEq, Store, Encode)]
+pub struct ReportPreimageStore<T: Liability> {
+    /// Report hash.
+    #[store(returns = ReportPreimage<T>)]
+    pub hash: H256,
+    /// Runtime marker.
+    pub _runtime: PhantomData<T>,
+}
+
+/// Liabilities where account is promisee or promisor.
+#[derive(Clone, Debug, Eq, PartialEq, Store, Encode)]
+pub struct AccountLiabilitiesStore<'a, T: Liability> {
+    /// Promisee or promisor account.
+    #[store(returns = ())]
+    pub account: &'a <T as System>::AccountId,
+    /// Liability index.
+    pub index: T::LiabilityIndex,
+}
@@ -0,0 +1,123 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Following cursors of paged runtime APIs, e.g. `list_by_account`.
+//!
+//! Runtime cuts each page to `MAX_PAGE_ITEMS` items, so listing is fetched page by page
+//! from cursor of previous one until the last page. Adapters hide pages behind a single
+//! sequence of items, pruned entries are already skipped by runtime.
+//!
+//! ```ignore
+//! let liabilities = follow_pages(0, |start| api.list_by_account(account.clone(), start, 100));
+//! let indices: Vec<_> = liabilities.map_ok(|(index, _)| index).try_collect().await?;
+//! ```
+
+use crate::Result;
+use futures::{stream, Future, Stream, StreamExt};
+pub use pallet_robonomics_liability_rpc_runtime_api::{Page, MAX_PAGE_ITEMS};
+
+/// Items of pages starting from `start` cursor, the next page is fetched when items
+/// of previous one are taken. Fetch error is the last item of stream.
+pub fn follow_pages<C, I, F, FF>(start: C, fetch: F) -> impl Stream<Item = Result<I>>
+where
+    F: FnMut(C) -> FF,
+    FF: Future<Output = Result<Page<C, I>>>,
+{
+    stream::unfold(Some((start, fetch)), |state| async move {
+        let (cursor, mut fetch) = state?;
+        match fetch(cursor).await {
+            Ok(page) => {
+                let next = if page.has_more {
+                    Some((page.next, fetch))
+                } else {
+                    None
+                };
+                let items: Vec<_> = page.items.into_iter().map(Ok).collect();
+                Some((items, next))
+            }
+            Err(error) => Some((vec![Err(error)], None)),
+        }
+    })
+    .flat_map(stream::iter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+    use futures::{executor::block_on, future, TryStreamExt};
+    use std::cell::Cell;
+
+    /// Page of dataset where each tenth entry is pruned, cut to the runtime cap.
+    fn list(total: u64, start: u64, count: u32) -> Page<u64, u64> {
+        let count = count.min(MAX_PAGE_ITEMS) as usize;
+        let mut items = Vec::new();
+        let mut next = start;
+        while next < total && items.len() < count {
+            if next % 10 != 3 {
+                items.push(next);
+            }
+            next += 1;
+        }
+        Page {
+            items,
+            next,
+            has_more: next < total,
+        }
+    }
+
+    #[test]
+    fn test_follow_pages() {
+        let total = 3 * MAX_PAGE_ITEMS as u64 + 7;
+        let calls = Cell::new(0);
+        let items = follow_pages(0, |start| {
+            calls.set(calls.get() + 1);
+            future::ready(Ok(list(total, start, u32::max_value())))
+        });
+        let items: Vec<u64> = block_on(items.try_collect()).unwrap();
+        assert_eq!(
+            items,
+            (0..total).filter(|i| i % 10 != 3).collect::<Vec<_>>()
+        );
+        assert_eq!(calls.get(), 3);
+
+        // Pages are fetched on demand
+        calls.set(0);
+        let first: Vec<_> = block_on(
+            follow_pages(0, |start| {
+                calls.set(calls.get() + 1);
+                future::ready(Ok(list(total, start, 10)))
+            })
+            .take(15)
+            .collect(),
+        );
+        assert_eq!(first.len(), 15);
+        assert_eq!(calls.get(), 2);
+
+        // Listing stops at fetch error
+        let failing = follow_pages(0, |start| {
+            future::ready(if start < 200 {
+                Ok(list(total, start, 150))
+            } else {
+                Err(Error::MissingEvent("page".into(), "list"))
+            })
+        });
+        let items: Vec<_> = block_on(failing.collect());
+        assert_eq!(items.len(), 2 * MAX_PAGE_ITEMS as usize + 1);
+        assert!(items.last().unwrap().is_err());
+    }
+}
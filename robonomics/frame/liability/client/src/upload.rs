@@ -0,0 +1,319 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Upload liability result file to IPFS and finalize liability in one call.
+//!
+//! Result file is added by IPFS HTTP API, e.g. `http://127.0.0.1:5001` of local daemon,
+//! multihash of added object is the liability report. Unsigned `finalize` is retried on
+//! transient RPC failures, liability state is checked before each submission, so liability
+//! finalized meanwhile, e.g. by off-chain worker or by lost response, isn't reported twice.
+
+use crate::{finalize, Error, FinalizeCall, LiabilitiesStore, Liability, Reported, Result};
+use base58::FromBase58;
+use futures::Future;
+use ipfs_api::{IpfsClient, TryFromUri};
+use sp_core::crypto::Pair;
+use sp_runtime::MultiSignature;
+use std::path::Path;
+use std::time::Duration;
+use substrate_subxt::{balances::Balances, Client, Runtime};
+
+/// Count of `finalize` submissions before transient RPC failure is returned.
+pub const SUBMIT_ATTEMPTS: usize = 3;
+
+/// Delay between `finalize` submissions, about a block time.
+pub const RETRY_DELAY: Duration = Duration::from_secs(6);
+
+/// Outcome of liability finalization.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Finalization<T: Liability> {
+    /// Report is published by this call: included extrinsic and published report, the event
+    /// carries only report hash.
+    Reported(Reported<T>, T::TechnicalReport),
+    /// Liability is already finalized, report isn't submitted.
+    AlreadyFinalized,
+}
+
+/// Add file to IPFS by HTTP API, returns multihash of added object.
+pub async fn add_file(ipfs_api: &str, path: &Path) -> Result<Vec<u8>> {
+    let client = IpfsClient::from_str(ipfs_api)
+        .map_err(|e| Error::Ipfs(format!("bad API endpoint {}: {}", ipfs_api, e)))?;
+    let file = std::fs::File::open(path)?;
+    // IPFS client runs on tokio reactor, so it's driven by own runtime on blocking thread
+    let added = async_std::task::spawn_blocking(move || {
+        let mut runtime = tokio::runtime::Runtime::new()?;
+        runtime
+            .block_on(client.add(file))
+            .map_err(|e| Error::Ipfs(e.to_string()))
+    })
+    .await?;
+    added
+        .hash
+        .from_base58()
+        .map_err(|_| Error::Ipfs(format!("bad hash of added object: {}", added.hash)))
+}
+
+/// Add result file to IPFS and publish its multihash as liability report signed by promisor.
+pub async fn finalize_with_file<T, P>(
+    client: &Client<T>,
+    ipfs_api: &str,
+    index: T::LiabilityIndex,
+    path: &Path,
+    promisor: &P,
+) -> Result<Finalization<T>>
+where
+    T: Runtime + Balances + Liability,
+    T::TechnicalReport: From<Vec<u8>>,
+    P: Pair,
+    MultiSignature: From<P::Signature>,
+{
+    if is_finalized(client, &index).await? {
+        return Ok(Finalization::AlreadyFinalized);
+    }
+    let report = T::TechnicalReport::from(add_file(ipfs_api, path).await?);
+    let call = FinalizeCall::<T>::signed(index.clone(), report.clone(), promisor);
+
+    let reported = submit_idempotent(
+        || is_finalized(client, &index),
+        || finalize(client, call.clone()),
+        SUBMIT_ATTEMPTS,
+        RETRY_DELAY,
+    )
+    .await?;
+    Ok(reported.map_or(Finalization::AlreadyFinalized, |reported| {
+        Finalization::Reported(reported, report)
+    }))
+}
+
+async fn is_finalized<T: Runtime + Liability>(
+    client: &Client<T>,
+    index: &T::LiabilityIndex,
+) -> Result<bool> {
+    let store = LiabilitiesStore::<T> {
+        index: index.clone(),
+    };
+    let record = client.fetch(&store, None).await?;
+    Ok(record.map_or(false, |record| record.is_finalized()))
+}
+
+/// Node connection failures, i.e. submission could be retried.
+fn is_transient(error: &Error) -> bool {
+    match error {
+        Error::Subxt(substrate_subxt::Error::Rpc(_)) => true,
+        Error::Subxt(substrate_subxt::Error::Io(_)) => true,
+        _ => false,
+    }
+}
+
+/// Submit until success, returns `None` when liability is finalized before or after
+/// failed submission.
+async fn submit_idempotent<R, F, FF, S, SF>(
+    mut is_finalized: F,
+    mut submit: S,
+    attempts: usize,
+    delay: Duration,
+) -> Result<Option<R>>
+where
+    F: FnMut() -> FF,
+    FF: Future<Output = Result<bool>>,
+    S: FnMut() -> SF,
+    SF: Future<Output = Result<R>>,
+{
+    let mut attempt = 1;
+    loop {
+        let error = match submit().await {
+            Ok(result) => return Ok(Some(result)),
+            Err(error) => error,
+        };
+        // Rejected or lost submission of already finalized liability
+        if is_finalized().await? {
+            return Ok(None);
+        }
+        if attempt >= attempts || !is_transient(&error) {
+            return Err(error);
+        }
+        attempt += 1;
+        futures_timer::Delay::new(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use std::cell::Cell;
+    use std::io::{Read, Write};
+
+    const CID: &str = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4";
+
+    fn connection_reset() -> Result<()> {
+        let error = std::io::Error::new(std::io::ErrorKind::ConnectionReset, "connection reset");
+        Err(substrate_subxt::Error::Io(error).into())
+    }
+
+    fn result_file(name: &str, content: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+        std::fs::File::create(&path)
+            .unwrap()
+            .write_all(content)
+            .unwrap();
+        path
+    }
+
+    /// Mocked IPFS HTTP API answering single `add` request.
+    fn ipfs_mock(hash: &'static str) -> (String, std::thread::JoinHandle<Vec<u8>>) {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}", server.server_addr());
+        let handle = std::thread::spawn(move || {
+            let mut request = server.recv().unwrap();
+            assert!(request.url().starts_with("/api/v0/add"));
+            let mut body = Vec::new();
+            request.as_reader().read_to_end(&mut body).unwrap();
+            let response = format!(r#"{{"Name":"result","Hash":"{}","Size":"20"}}"#, hash);
+            request
+                .respond(tiny_http::Response::from_string(response))
+                .unwrap();
+            body
+        });
+        (endpoint, handle)
+    }
+
+    #[test]
+    fn test_add_file() {
+        let path = result_file("add-file", b"liability result");
+        let (endpoint, ipfs) = ipfs_mock(CID);
+        let multihash = block_on(add_file(&endpoint, &path)).unwrap();
+        assert_eq!(multihash, CID.from_base58().unwrap());
+        assert_eq!(&multihash[..2], &[0x12, 0x20]);
+        // Multipart body carries file content
+        let body = ipfs.join().unwrap();
+        assert!(body
+            .windows(b"liability result".len())
+            .any(|w| w == b"liability result"));
+    }
+
+    #[test]
+    fn test_add_file_bad_hash() {
+        let path = result_file("bad-hash", b"liability result");
+        let (endpoint, _) = ipfs_mock("not-a-base58-hash!");
+        assert!(matches!(
+            block_on(add_file(&endpoint, &path)),
+            Err(Error::Ipfs(_))
+        ));
+    }
+
+    #[test]
+    fn test_ipfs_unavailable() {
+        let path = result_file("ipfs-unavailable", b"liability result");
+        // Port of dropped listener refuses connections
+        let endpoint = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}", listener.local_addr().unwrap())
+        };
+        assert!(matches!(
+            block_on(add_file(&endpoint, &path)),
+            Err(Error::Ipfs(_))
+        ));
+        assert!(matches!(
+            block_on(add_file("not an uri", &path)),
+            Err(Error::Ipfs(_))
+        ));
+        assert!(matches!(
+            block_on(add_file(&endpoint, Path::new("/nonexistent/result"))),
+            Err(Error::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_retry_transient_failure() {
+        let submits = Cell::new(0);
+        let result = block_on(submit_idempotent(
+            || async { Ok(false) },
+            || {
+                submits.set(submits.get() + 1);
+                let attempt = submits.get();
+                async move {
+                    if attempt < SUBMIT_ATTEMPTS {
+                        connection_reset()
+                    } else {
+                        Ok(())
+                    }
+                }
+            },
+            SUBMIT_ATTEMPTS,
+            Duration::from_millis(0),
+        ));
+        assert_eq!(result.unwrap(), Some(()));
+        assert_eq!(submits.get(), SUBMIT_ATTEMPTS);
+
+        // Attempts are exhausted
+        submits.set(0);
+        let result = block_on(submit_idempotent(
+            || async { Ok(false) },
+            || {
+                submits.set(submits.get() + 1);
+                async { connection_reset() }
+            },
+            SUBMIT_ATTEMPTS,
+            Duration::from_millis(0),
+        ));
+        assert!(matches!(result, Err(Error::Subxt(_))));
+        assert_eq!(submits.get(), SUBMIT_ATTEMPTS);
+    }
+
+    #[test]
+    fn test_already_finalized_race() {
+        // Submission response is lost but liability is finalized
+        let submits = Cell::new(0);
+        let result = block_on(submit_idempotent(
+            || async { Ok(true) },
+            || {
+                submits.set(submits.get() + 1);
+                async { connection_reset() }
+            },
+            SUBMIT_ATTEMPTS,
+            Duration::from_millis(0),
+        ));
+        assert_eq!(result.unwrap(), None);
+        assert_eq!(submits.get(), 1);
+
+        // Report of another submitter is included first, own one is rejected
+        let result = block_on(submit_idempotent(
+            || async { Ok(true) },
+            || async { Err::<(), _>(Error::MissingEvent("0x00".into(), "NewReport")) },
+            SUBMIT_ATTEMPTS,
+            Duration::from_millis(0),
+        ));
+        assert_eq!(result.unwrap(), None);
+    }
+
+    #[test]
+    fn test_permanent_failure() {
+        let submits = Cell::new(0);
+        let result = block_on(submit_idempotent(
+            || async { Ok(false) },
+            || {
+                submits.set(submits.get() + 1);
+                async { Err::<(), _>(Error::MissingEvent("0x00".into(), "NewReport")) }
+            },
+            SUBMIT_ATTEMPTS,
+            Duration::from_millis(0),
+        ));
+        assert!(matches!(result, Err(Error::MissingEvent(..))));
+        assert_eq!(submits.get(), 1);
+    }
+}
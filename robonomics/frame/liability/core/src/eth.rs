@@ -0,0 +1,779 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Ethereum state proofs of liabilities of the legacy robonomics contracts.
+//!
+//! Proof is a set of RLP encoded Merkle-Patricia trie nodes, nodes are looked up by their
+//! `keccak256` hash, so single set proves accounts of state trie together with slots of
+//! contracts storage tries:
+//!
+//! - state trie maps `keccak256(address)` to RLP list of account nonce, balance, storage
+//!   root and code hash;
+//! - storage trie maps `keccak256(slot)` to RLP encoded slot word without leading zeros,
+//!   zero words aren't kept;
+//! - legacy factory marks liability contracts it deployed in `isLiability` mapping, so
+//!   proof of liability fields is accepted only for contracts registered by the factory.
+//!
+//! Storage slots of liability fields are listed in `layout`.
+
+use codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_core::{H160, H256};
+use sp_io::hashing::keccak_256;
+use sp_runtime::RuntimeDebug;
+use sp_std::{collections::btree_map::BTreeMap, prelude::*};
+
+/// Root of empty trie, `keccak256(rlp(""))`.
+pub const EMPTY_TRIE_ROOT: H256 = H256([
+    0x56, 0xe8, 0x1f, 0x17, 0x1b, 0xcc, 0x55, 0xa6, 0xff, 0x83, 0x45, 0xe6, 0x92, 0xc0, 0xf8, 0x6e,
+    0x5b, 0x48, 0xe0, 0x1b, 0x99, 0x6c, 0xad, 0xc0, 0x01, 0x62, 0x2f, 0xb5, 0xe3, 0x63, 0xb4, 0x21,
+]);
+
+/// Maximal length of `bytes` field of legacy liability read from storage.
+pub const MAX_LEGACY_BYTES: usize = 1024;
+
+/// Storage layout of legacy liability contracts.
+pub mod layout {
+    /// Slot of `mapping(address => bool) isLiability` of factory.
+    pub const FACTORY_IS_LIABILITY: u64 = 6;
+    /// Slot of `bytes model`.
+    pub const MODEL: u64 = 0;
+    /// Slot of `bytes objective`.
+    pub const OBJECTIVE: u64 = 1;
+    /// Slot of `bytes result`.
+    pub const RESULT: u64 = 2;
+    /// Slot of `uint256 cost`, slot 3 keeps address of token.
+    pub const COST: u64 = 4;
+    /// Slot of `address promisor`, slots 5 to 8 keep fees and order hashes.
+    pub const PROMISOR: u64 = 9;
+    /// Slot of `address promisee`.
+    pub const PROMISEE: u64 = 10;
+    /// Slot of packed `bool isSuccess` and `bool isFinalized`, slots 11 and 12 keep
+    /// lighthouse and validator.
+    pub const FLAGS: u64 = 13;
+}
+
+/// Finalized liability of legacy Ethereum robonomics contracts.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct LegacyRecord {
+    /// Address of liability contract.
+    pub address: H160,
+    /// IPFS hash of robot behavioral model.
+    pub model: Vec<u8>,
+    /// IPFS hash of liability objective.
+    pub objective: Vec<u8>,
+    /// IPFS hash of liability result.
+    pub result: Vec<u8>,
+    /// Cost of liability in tokens of contract.
+    pub cost: u128,
+    /// Party that executed the work.
+    pub promisor: H160,
+    /// Party that paid for the work.
+    pub promisee: H160,
+    /// Economical outcome of liability.
+    pub success: bool,
+}
+
+/// Malformed or incomplete proof.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum TrieError {
+    /// Node on path of key isn't in proof.
+    MissingNode,
+    /// Node isn't valid RLP encoded trie node.
+    InvalidNode,
+    /// Account or slot value isn't valid.
+    InvalidValue,
+}
+
+/// Legacy liability verification failure.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum LegacyError {
+    /// Proof doesn't prove needed keys.
+    Proof(TrieError),
+    /// Contract isn't registered by legacy factory.
+    NotLiability,
+    /// Liability isn't finalized on Ethereum.
+    NotFinalized,
+    /// Record differs from contract storage.
+    Mismatch,
+}
+
+impl From<TrieError> for LegacyError {
+    fn from(error: TrieError) -> Self {
+        LegacyError::Proof(error)
+    }
+}
+
+/// Decoded RLP item borrowed from encoding.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Rlp<'a> {
+    /// Byte string.
+    Bytes(&'a [u8]),
+    /// List, payload is concatenation of encoded items.
+    List(&'a [u8]),
+}
+
+/// Decode leading RLP item of input, returns the item and the rest of input.
+pub fn decode_rlp(input: &[u8]) -> Result<(Rlp<'_>, &[u8]), TrieError> {
+    let (&prefix, rest) = input.split_first().ok_or(TrieError::InvalidNode)?;
+    let (is_list, offset, length) = match prefix {
+        0x00..=0x7f => return Ok((Rlp::Bytes(&input[..1]), rest)),
+        0x80..=0xb7 => (false, 0, usize::from(prefix - 0x80)),
+        0xb8..=0xbf => {
+            let size = usize::from(prefix - 0xb7);
+            (false, size, be_length(rest, size)?)
+        }
+        0xc0..=0xf7 => (true, 0, usize::from(prefix - 0xc0)),
+        _ => {
+            let size = usize::from(prefix - 0xf7);
+            (true, size, be_length(rest, size)?)
+        }
+    };
+    let end = offset.checked_add(length).ok_or(TrieError::InvalidNode)?;
+    if rest.len() < end {
+        return Err(TrieError::InvalidNode);
+    }
+    let payload = &rest[offset..end];
+    let item = if is_list {
+        Rlp::List(payload)
+    } else {
+        Rlp::Bytes(payload)
+    };
+    Ok((item, &rest[end..]))
+}
+
+/// Big-endian length of long RLP item.
+fn be_length(input: &[u8], size: usize) -> Result<usize, TrieError> {
+    if size > sp_std::mem::size_of::<usize>() || input.len() < size {
+        return Err(TrieError::InvalidNode);
+    }
+    Ok(input[..size]
+        .iter()
+        .fold(0, |length, byte| (length << 8) | usize::from(*byte)))
+}
+
+/// Items of RLP list payload.
+pub fn rlp_items(payload: &[u8]) -> Result<Vec<Rlp<'_>>, TrieError> {
+    let mut items = Vec::new();
+    let mut rest = payload;
+    while !rest.is_empty() {
+        let (item, next) = decode_rlp(rest)?;
+        items.push(item);
+        rest = next;
+    }
+    Ok(items)
+}
+
+/// RLP encoding of byte string.
+pub fn rlp_bytes(data: &[u8]) -> Vec<u8> {
+    if data.len() == 1 && data[0] < 0x80 {
+        return data.to_vec();
+    }
+    let mut encoded = rlp_prefix(0x80, data.len());
+    encoded.extend_from_slice(data);
+    encoded
+}
+
+/// RLP encoding of list of encoded items.
+pub fn rlp_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let payload = items.concat();
+    let mut encoded = rlp_prefix(0xc0, payload.len());
+    encoded.extend(payload);
+    encoded
+}
+
+fn rlp_prefix(offset: u8, length: usize) -> Vec<u8> {
+    if length < 56 {
+        return vec![offset + length as u8];
+    }
+    let be = (length as u64).to_be_bytes();
+    let skip = be.iter().take_while(|byte| **byte == 0).count();
+    let mut prefix = vec![offset + 55 + (8 - skip) as u8];
+    prefix.extend_from_slice(&be[skip..]);
+    prefix
+}
+
+/// Trie nodes of proof by their hash.
+pub struct ProofNodes<'a>(BTreeMap<H256, &'a [u8]>);
+
+impl<'a> ProofNodes<'a> {
+    /// Index nodes of proof.
+    pub fn new(nodes: &'a [Vec<u8>]) -> Self {
+        ProofNodes(
+            nodes
+                .iter()
+                .map(|node| (keccak_256(node).into(), &node[..]))
+                .collect(),
+        )
+    }
+
+    fn node(&self, hash: &H256) -> Result<Vec<Rlp<'a>>, TrieError> {
+        let encoded: &'a [u8] = *self.0.get(hash).ok_or(TrieError::MissingNode)?;
+        match decode_rlp(encoded)? {
+            (Rlp::List(payload), rest) if rest.is_empty() => rlp_items(payload),
+            _ => Err(TrieError::InvalidNode),
+        }
+    }
+
+    /// Child node referenced by hash or inlined, `None` for empty reference.
+    fn child(&self, reference: Rlp<'a>) -> Result<Option<Vec<Rlp<'a>>>, TrieError> {
+        match reference {
+            Rlp::Bytes(hash) if hash.is_empty() => Ok(None),
+            Rlp::Bytes(hash) if hash.len() == 32 => self.node(&H256::from_slice(hash)).map(Some),
+            Rlp::List(payload) => rlp_items(payload).map(Some),
+            _ => Err(TrieError::InvalidNode),
+        }
+    }
+}
+
+fn nibbles(key: &[u8]) -> Vec<u8> {
+    key.iter()
+        .flat_map(|byte| sp_std::iter::once(byte >> 4).chain(sp_std::iter::once(byte & 0x0f)))
+        .collect()
+}
+
+/// Hex-prefix encoded path of leaf or extension node, returns `true` for leaf.
+fn decode_path(encoded: &[u8]) -> Result<(bool, Vec<u8>), TrieError> {
+    let (&first, rest) = encoded.split_first().ok_or(TrieError::InvalidNode)?;
+    let flag = first >> 4;
+    if flag > 3 || (flag & 1 == 0 && first & 0x0f != 0) {
+        return Err(TrieError::InvalidNode);
+    }
+    let mut path = Vec::with_capacity(rest.len() * 2 + 1);
+    if flag & 1 == 1 {
+        path.push(first & 0x0f);
+    }
+    path.extend(nibbles(rest));
+    Ok((flag & 2 != 0, path))
+}
+
+/// Value of trie with given root at key, `None` when proof shows that key is absent.
+pub fn read_trie<'a>(
+    root: &H256,
+    key: &[u8],
+    proof: &ProofNodes<'a>,
+) -> Result<Option<&'a [u8]>, TrieError> {
+    if *root == EMPTY_TRIE_ROOT {
+        return Ok(None);
+    }
+    let key = nibbles(key);
+    let mut path = &key[..];
+    let mut node = proof.node(root)?;
+    loop {
+        match node.len() {
+            17 => match path.split_first() {
+                None => {
+                    return match node[16] {
+                        Rlp::Bytes(value) if value.is_empty() => Ok(None),
+                        Rlp::Bytes(value) => Ok(Some(value)),
+                        Rlp::List(_) => Err(TrieError::InvalidNode),
+                    }
+                }
+                Some((nibble, rest)) => {
+                    path = rest;
+                    node = match proof.child(node[usize::from(*nibble)])? {
+                        Some(child) => child,
+                        None => return Ok(None),
+                    };
+                }
+            },
+            2 => {
+                let (is_leaf, partial) = match node[0] {
+                    Rlp::Bytes(encoded) => decode_path(encoded)?,
+                    Rlp::List(_) => return Err(TrieError::InvalidNode),
+                };
+                if is_leaf {
+                    return match node[1] {
+                        Rlp::Bytes(value) if path == &partial[..] => Ok(Some(value)),
+                        Rlp::Bytes(_) => Ok(None),
+                        Rlp::List(_) => Err(TrieError::InvalidNode),
+                    };
+                }
+                if !path.starts_with(&partial) {
+                    return Ok(None);
+                }
+                path = &path[partial.len()..];
+                node = proof.child(node[1])?.ok_or(TrieError::InvalidNode)?;
+            }
+            _ => return Err(TrieError::InvalidNode),
+        }
+    }
+}
+
+/// Storage root of account in state trie, empty trie root for absent account.
+pub fn storage_root(
+    state_root: &H256,
+    address: &H160,
+    proof: &ProofNodes,
+) -> Result<H256, TrieError> {
+    let account = match read_trie(state_root, &keccak_256(address.as_bytes()), proof)? {
+        Some(account) => account,
+        None => return Ok(EMPTY_TRIE_ROOT),
+    };
+    match decode_rlp(account)? {
+        (Rlp::List(payload), rest) if rest.is_empty() => match rlp_items(payload)?[..] {
+            [_, _, Rlp::Bytes(root), _] if root.len() == 32 => Ok(H256::from_slice(root)),
+            _ => Err(TrieError::InvalidValue),
+        },
+        _ => Err(TrieError::InvalidValue),
+    }
+}
+
+/// Word of contract storage at slot, zero for absent slot.
+pub fn read_slot(root: &H256, slot: &H256, proof: &ProofNodes) -> Result<H256, TrieError> {
+    let value = match read_trie(root, &keccak_256(slot.as_bytes()), proof)? {
+        Some(value) => value,
+        None => return Ok(H256::zero()),
+    };
+    match decode_rlp(value)? {
+        (Rlp::Bytes(word), rest) if rest.is_empty() && word.len() <= 32 => {
+            let mut padded = H256::zero();
+            padded[32 - word.len()..].copy_from_slice(word);
+            Ok(padded)
+        }
+        _ => Err(TrieError::InvalidValue),
+    }
+}
+
+/// Storage slot of Solidity value type field.
+pub fn slot(position: u64) -> H256 {
+    word_of(u128::from(position))
+}
+
+/// Storage slot of value of Solidity `mapping(address => _)` at given position.
+pub fn mapping_slot(key: &H160, position: u64) -> H256 {
+    keccak_256(&[address_word(key).as_bytes(), slot(position).as_bytes()].concat()).into()
+}
+
+/// Storage word of address.
+pub fn address_word(address: &H160) -> H256 {
+    let mut word = H256::zero();
+    word[12..].copy_from_slice(address.as_bytes());
+    word
+}
+
+/// Storage word of unsigned integer.
+pub fn word_of(value: u128) -> H256 {
+    let mut word = H256::zero();
+    word[16..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// Slot of `offset` word of Solidity long `bytes` data.
+fn data_slot(position: u64, offset: u64) -> H256 {
+    let mut data = keccak_256(slot(position).as_bytes());
+    let mut carry = offset as u128;
+    for byte in data.iter_mut().rev() {
+        let sum = u128::from(*byte) + carry;
+        *byte = sum as u8;
+        carry = sum >> 8;
+        if carry == 0 {
+            break;
+        }
+    }
+    data.into()
+}
+
+/// Solidity `bytes` field of contract storage at given position.
+///
+/// Short data is kept in field slot with length doubled in the lowest byte, long data is
+/// kept from slot `keccak256(position)` with field slot `2 * length + 1`.
+pub fn read_bytes(root: &H256, position: u64, proof: &ProofNodes) -> Result<Vec<u8>, TrieError> {
+    let head = read_slot(root, &slot(position), proof)?;
+    if head[31] & 1 == 0 {
+        let length = usize::from(head[31] / 2);
+        if length > 31 {
+            return Err(TrieError::InvalidValue);
+        }
+        return Ok(head[..length].to_vec());
+    }
+    if head[..24].iter().any(|byte| *byte != 0) {
+        return Err(TrieError::InvalidValue);
+    }
+    let mut encoded = [0; 8];
+    encoded.copy_from_slice(&head[24..]);
+    let length = (u64::from_be_bytes(encoded) / 2) as usize;
+    if length < 32 || length > MAX_LEGACY_BYTES {
+        return Err(TrieError::InvalidValue);
+    }
+    let mut data = Vec::with_capacity(length + 31);
+    for offset in 0..(length as u64 + 31) / 32 {
+        data.extend_from_slice(read_slot(root, &data_slot(position, offset), proof)?.as_bytes());
+    }
+    data.truncate(length);
+    Ok(data)
+}
+
+/// Verify that liability contract registered by legacy factory is finalized with given
+/// record in state with given root.
+pub fn verify_legacy(
+    state_root: &H256,
+    factory: &H160,
+    record: &LegacyRecord,
+    proof: &ProofNodes,
+) -> Result<(), LegacyError> {
+    let factory_root = storage_root(state_root, factory, proof)?;
+    let registered = read_slot(
+        &factory_root,
+        &mapping_slot(&record.address, layout::FACTORY_IS_LIABILITY),
+        proof,
+    )?;
+    if registered != word_of(1) {
+        return Err(LegacyError::NotLiability);
+    }
+
+    let root = storage_root(state_root, &record.address, proof)?;
+    let flags = read_slot(&root, &slot(layout::FLAGS), proof)?;
+    if flags[30] != 1 {
+        return Err(LegacyError::NotFinalized);
+    }
+    let matches = read_bytes(&root, layout::MODEL, proof)? == record.model
+        && read_bytes(&root, layout::OBJECTIVE, proof)? == record.objective
+        && read_bytes(&root, layout::RESULT, proof)? == record.result
+        && read_slot(&root, &slot(layout::COST), proof)? == word_of(record.cost)
+        && read_slot(&root, &slot(layout::PROMISOR), proof)? == address_word(&record.promisor)
+        && read_slot(&root, &slot(layout::PROMISEE), proof)? == address_word(&record.promisee)
+        && flags[31] == record.success as u8;
+    if !matches {
+        return Err(LegacyError::Mismatch);
+    }
+    Ok(())
+}
+
+/// Encoded root node and all the nodes of trie with given keys and values, e.g. to make
+/// proofs for tests and tools.
+#[cfg(feature = "std")]
+pub fn build_trie(entries: &[(Vec<u8>, Vec<u8>)]) -> (H256, Vec<Vec<u8>>) {
+    let mut paths: Vec<_> = entries
+        .iter()
+        .map(|(key, value)| (nibbles(key), &value[..]))
+        .collect();
+    paths.sort();
+    if paths.is_empty() {
+        return (EMPTY_TRIE_ROOT, vec![]);
+    }
+    let mut nodes = vec![];
+    let root = build_node(&paths, 0, &mut nodes);
+    let hash = keccak_256(&root).into();
+    nodes.push(root);
+    (hash, nodes)
+}
+
+#[cfg(feature = "std")]
+fn build_node(paths: &[(Vec<u8>, &[u8])], depth: usize, nodes: &mut Vec<Vec<u8>>) -> Vec<u8> {
+    let encode_path = |path: &[u8], is_leaf: bool| {
+        let flag = (path.len() % 2) as u8 + if is_leaf { 2 } else { 0 };
+        let mut encoded = vec![flag << 4];
+        if path.len() % 2 == 1 {
+            encoded[0] |= path[0];
+        }
+        for pair in path[path.len() % 2..].chunks(2) {
+            encoded.push((pair[0] << 4) | pair[1]);
+        }
+        rlp_bytes(&encoded)
+    };
+    if let [(path, value)] = paths {
+        return rlp_list(&[encode_path(&path[depth..], true), rlp_bytes(value)]);
+    }
+    let (first, last) = (&paths[0].0[depth..], &paths[paths.len() - 1].0[depth..]);
+    let common = first
+        .iter()
+        .zip(last.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    if common > 0 {
+        let child = build_node(paths, depth + common, nodes);
+        let child = reference(child, nodes);
+        return rlp_list(&[encode_path(&first[..common], false), child]);
+    }
+
+    let mut items = vec![];
+    for nibble in 0..16 {
+        let group: Vec<_> = paths
+            .iter()
+            .filter(|(path, _)| path.len() > depth && path[depth] == nibble)
+            .cloned()
+            .collect();
+        if group.is_empty() {
+            items.push(rlp_bytes(&[]));
+        } else {
+            let child = build_node(&group, depth + 1, nodes);
+            items.push(reference(child, nodes));
+        }
+    }
+    let value = paths
+        .iter()
+        .find(|(path, _)| path.len() == depth)
+        .map_or(&[][..], |(_, value)| *value);
+    items.push(rlp_bytes(value));
+    rlp_list(&items)
+}
+
+/// Reference of parent node to child, nodes shorter than hash are inlined.
+#[cfg(feature = "std")]
+fn reference(encoded: Vec<u8>, nodes: &mut Vec<Vec<u8>>) -> Vec<u8> {
+    if encoded.len() < 32 {
+        return encoded;
+    }
+    let hash = keccak_256(&encoded);
+    nodes.push(encoded);
+    rlp_bytes(&hash)
+}
+
+/// Storage words of legacy liability contract keeping given record.
+#[cfg(feature = "std")]
+pub fn legacy_slots(record: &LegacyRecord) -> Vec<(H256, H256)> {
+    let mut slots = vec![];
+    for (position, data) in &[
+        (layout::MODEL, &record.model),
+        (layout::OBJECTIVE, &record.objective),
+        (layout::RESULT, &record.result),
+    ] {
+        let mut head = H256::zero();
+        if data.len() < 32 {
+            head[..data.len()].copy_from_slice(data);
+            head[31] = (data.len() * 2) as u8;
+        } else {
+            head = word_of(data.len() as u128 * 2 + 1);
+            for (offset, chunk) in data.chunks(32).enumerate() {
+                let mut word = H256::zero();
+                word[..chunk.len()].copy_from_slice(chunk);
+                slots.push((data_slot(*position, offset as u64), word));
+            }
+        }
+        slots.push((slot(*position), head));
+    }
+    let mut flags = H256::zero();
+    flags[30] = 1;
+    flags[31] = record.success as u8;
+    slots.push((slot(layout::COST), word_of(record.cost)));
+    slots.push((slot(layout::PROMISOR), address_word(&record.promisor)));
+    slots.push((slot(layout::PROMISEE), address_word(&record.promisee)));
+    slots.push((slot(layout::FLAGS), flags));
+    slots
+}
+
+/// Root and all the nodes of Ethereum state with legacy factory and contracts of given
+/// liabilities, e.g. to make proofs for tests and tools.
+#[cfg(feature = "std")]
+pub fn legacy_state(factory: &H160, records: &[LegacyRecord]) -> (H256, Vec<Vec<u8>>) {
+    let mut nodes = vec![];
+    let mut storage = |slots: Vec<(H256, H256)>| {
+        let entries: Vec<_> = slots
+            .into_iter()
+            .filter(|(_, word)| !word.is_zero())
+            .map(|(slot, word)| {
+                let value = word.as_bytes();
+                let skip = value.iter().take_while(|byte| **byte == 0).count();
+                (
+                    keccak_256(slot.as_bytes()).to_vec(),
+                    rlp_bytes(&value[skip..]),
+                )
+            })
+            .collect();
+        let (root, trie) = build_trie(&entries);
+        nodes.extend(trie);
+        rlp_list(&[
+            rlp_bytes(&[1]),
+            rlp_bytes(&[]),
+            rlp_bytes(root.as_bytes()),
+            rlp_bytes(&keccak_256(&[])),
+        ])
+    };
+
+    let registered = records
+        .iter()
+        .map(|record| {
+            (
+                mapping_slot(&record.address, layout::FACTORY_IS_LIABILITY),
+                word_of(1),
+            )
+        })
+        .collect();
+    let mut accounts = vec![(keccak_256(factory.as_bytes()).to_vec(), storage(registered))];
+    for record in records {
+        let account = storage(legacy_slots(record));
+        accounts.push((keccak_256(record.address.as_bytes()).to_vec(), account));
+    }
+    let (root, state) = build_trie(&accounts);
+    nodes.extend(state);
+    (root, nodes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    fn record(seed: u8, result: Vec<u8>) -> LegacyRecord {
+        LegacyRecord {
+            address: H160::repeat_byte(seed),
+            model: b"QmModel".to_vec(),
+            objective: vec![seed; 46],
+            result,
+            cost: 1_000_000_000_000_000_000,
+            promisor: H160::repeat_byte(0xa0),
+            promisee: H160::repeat_byte(0xb0),
+            success: true,
+        }
+    }
+
+    #[test]
+    fn test_rlp() {
+        assert_eq!(rlp_bytes(&[]), vec![0x80]);
+        assert_eq!(rlp_bytes(&[0x7f]), vec![0x7f]);
+        assert_eq!(rlp_bytes(b"dog"), b"\x83dog".to_vec());
+        assert_eq!(
+            rlp_list(&[rlp_bytes(b"cat"), rlp_bytes(b"dog")]),
+            b"\xc8\x83cat\x83dog".to_vec()
+        );
+        let long = vec![7; 60];
+        let encoded = rlp_bytes(&long);
+        assert_eq!(&encoded[..2], &[0xb8, 60]);
+        assert_eq!(decode_rlp(&encoded), Ok((Rlp::Bytes(&long[..]), &[][..])));
+
+        let list = rlp_list(&[rlp_bytes(b"cat"), rlp_list(&[]), encoded.clone()]);
+        let payload = match decode_rlp(&list) {
+            Ok((Rlp::List(payload), rest)) if rest.is_empty() => payload,
+            other => panic!("unexpected {:?}", other),
+        };
+        assert_eq!(
+            rlp_items(payload),
+            Ok(vec![
+                Rlp::Bytes(b"cat"),
+                Rlp::List(&[]),
+                Rlp::Bytes(&long[..])
+            ])
+        );
+        assert_eq!(decode_rlp(&encoded[..30]), Err(TrieError::InvalidNode));
+        assert_eq!(decode_rlp(&[]), Err(TrieError::InvalidNode));
+    }
+
+    #[test]
+    fn test_trie_root() {
+        assert_eq!(EMPTY_TRIE_ROOT, H256(keccak_256(&[0x80])));
+        assert_eq!(build_trie(&[]).0, EMPTY_TRIE_ROOT);
+        let entries = vec![
+            (b"doe".to_vec(), b"reindeer".to_vec()),
+            (b"dog".to_vec(), b"puppy".to_vec()),
+            (b"dogglesworth".to_vec(), b"cat".to_vec()),
+        ];
+        let (root, nodes) = build_trie(&entries);
+        assert_eq!(
+            root,
+            hex!["8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3"].into()
+        );
+
+        let proof = ProofNodes::new(&nodes);
+        for (key, value) in &entries {
+            assert_eq!(read_trie(&root, key, &proof), Ok(Some(&value[..])));
+        }
+        assert_eq!(read_trie(&root, b"do", &proof), Ok(None));
+        assert_eq!(read_trie(&root, b"horse", &proof), Ok(None));
+        assert_eq!(read_trie(&EMPTY_TRIE_ROOT, b"dog", &proof), Ok(None));
+        assert_eq!(
+            read_trie(&root, b"dog", &ProofNodes::new(&nodes[1..])),
+            Err(TrieError::MissingNode)
+        );
+    }
+
+    #[test]
+    fn test_read_bytes() {
+        let short = record(1, b"QmShort".to_vec());
+        let long = record(2, vec![9; 70]);
+        assert!(legacy_slots(&short).contains(&(
+            slot(layout::RESULT),
+            H256(hex![
+                "516d53686f72740000000000000000000000000000000000000000000000000e"
+            ])
+        )));
+        assert!(legacy_slots(&long).contains(&(slot(layout::RESULT), word_of(141))));
+
+        let factory = H160::repeat_byte(0xf0);
+        let (root, nodes) = legacy_state(&factory, &[short.clone(), long.clone()]);
+        let proof = ProofNodes::new(&nodes);
+        let storage = storage_root(&root, &long.address, &proof).unwrap();
+        assert_eq!(
+            read_bytes(&storage, layout::RESULT, &proof),
+            Ok(vec![9; 70])
+        );
+        assert_eq!(
+            read_bytes(&storage, layout::OBJECTIVE, &proof),
+            Ok(vec![2; 46])
+        );
+        assert_eq!(read_bytes(&storage, 100, &proof), Ok(vec![]));
+        assert_eq!(
+            storage_root(&root, &H160::repeat_byte(0xee), &proof),
+            Ok(EMPTY_TRIE_ROOT)
+        );
+    }
+
+    #[test]
+    fn test_verify_legacy() {
+        let factory = H160::repeat_byte(0xf0);
+        let registered = record(1, b"QmResult".to_vec());
+        let (root, nodes) = legacy_state(&factory, &[registered.clone()]);
+        let proof = ProofNodes::new(&nodes);
+        assert_eq!(verify_legacy(&root, &factory, &registered, &proof), Ok(()));
+
+        // Each field is bound by proof
+        let tampered = vec![
+            LegacyRecord {
+                result: b"QmForged".to_vec(),
+                ..registered.clone()
+            },
+            LegacyRecord {
+                cost: 1,
+                ..registered.clone()
+            },
+            LegacyRecord {
+                promisor: registered.promisee,
+                ..registered.clone()
+            },
+            LegacyRecord {
+                success: false,
+                ..registered.clone()
+            },
+        ];
+        for record in tampered {
+            assert_eq!(
+                verify_legacy(&root, &factory, &record, &proof),
+                Err(LegacyError::Mismatch)
+            );
+        }
+
+        // Contract with liability storage isn't accepted until factory registers it
+        let (other_root, other_nodes) =
+            legacy_state(&H160::repeat_byte(0xf1), &[registered.clone()]);
+        assert_eq!(
+            verify_legacy(
+                &other_root,
+                &factory,
+                &registered,
+                &ProofNodes::new(&other_nodes)
+            ),
+            Err(LegacyError::NotLiability)
+        );
+        assert_eq!(
+            verify_legacy(&root, &factory, &registered, &ProofNodes::new(&nodes[..1])),
+            Err(LegacyError::Proof(TrieError::MissingNode))
+        );
+    }
+}
@@ -0,0 +1,563 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Liability proof payloads and their verification.
+//!
+//! It's the single source of proof format for liability module and its clients: bridges
+//! and light clients verify liability proofs by it without runtime dependencies.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_core::{ecdsa, ed25519, sr25519, H256};
+use sp_runtime::{
+    traits::{IdentifyAccount, Lazy, Verify},
+    MultiSignature, RuntimeDebug,
+};
+use sp_std::prelude::*;
+
+pub mod bloom;
+pub mod eth;
+pub mod mmr;
+
+/// Category of liability works, liabilities are indexed by their tags for search.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, RuntimeDebug)]
+pub enum Tag {
+    /// Delivery of goods.
+    Delivery,
+    /// Measurements by robot sensors.
+    Sensing,
+    /// Computational works.
+    Compute,
+    /// Inspection of objects or sites.
+    Inspection,
+    /// Maintenance and repair works.
+    Maintenance,
+}
+
+/// Payload of liability parameters signed by both parties.
+pub fn params_payload<TechnicalParam: Encode, EconomicalParam: Encode>(
+    technics: &TechnicalParam,
+    economics: &EconomicalParam,
+) -> Vec<u8> {
+    (technics, economics).encode()
+}
+
+/// Payload of liability parameters signed by party until given block, inclusive.
+pub fn expiring_params_payload<TechnicalParam, EconomicalParam, BlockNumber>(
+    technics: &TechnicalParam,
+    economics: &EconomicalParam,
+    expires_at: &BlockNumber,
+) -> Vec<u8>
+where
+    TechnicalParam: Encode,
+    EconomicalParam: Encode,
+    BlockNumber: Encode,
+{
+    (technics, economics, expires_at).encode()
+}
+
+/// Payload of tagged liability parameters signed by promisee, so tags are part of agreement.
+pub fn tagged_params_payload<TechnicalParam: Encode, EconomicalParam: Encode>(
+    technics: &TechnicalParam,
+    economics: &EconomicalParam,
+    tags: &[Tag],
+) -> Vec<u8> {
+    (technics, economics, tags).encode()
+}
+
+/// Domain of liability parameters with relay bounty, bounty proof isn't valid as any
+/// other proof.
+pub const BOUNTY_DOMAIN: &[u8] = b"robonomics::liability::bounty";
+
+/// Payload of liability parameters signed by promisee, so relay bounty escrowed from it
+/// is part of agreement.
+pub fn bounty_params_payload<TechnicalParam: Encode, EconomicalParam: Encode, Balance: Encode>(
+    technics: &TechnicalParam,
+    economics: &EconomicalParam,
+    bounty: &Balance,
+) -> Vec<u8> {
+    (BOUNTY_DOMAIN, technics, economics, bounty).encode()
+}
+
+/// Promisors acceptable to promisee. Requirement is signed by promisee with liability
+/// parameters, so it's enforced without trusted storage.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum PromisorRequirement<AccountId> {
+    /// Promisor is one of listed accounts, count of them is bounded by runtime.
+    AllowList(Vec<AccountId>),
+    /// Promisor is robot registered by given owner, e.g. its manufacturer.
+    Manufacturer(AccountId),
+    /// Promisor is registered robot with given metadata hash, e.g. model id.
+    Model(H256),
+}
+
+impl<AccountId: PartialEq> PromisorRequirement<AccountId> {
+    /// Count of listed promisors, zero for registry requirements.
+    pub fn allowed_count(&self) -> usize {
+        match self {
+            PromisorRequirement::AllowList(allowed) => allowed.len(),
+            _ => 0,
+        }
+    }
+
+    /// Returns `true` when promisor with given registry owner and metadata hash satisfies
+    /// requirement, both are `None` for unregistered promisor.
+    pub fn is_satisfied(
+        &self,
+        promisor: &AccountId,
+        owner: Option<&AccountId>,
+        model: Option<&H256>,
+    ) -> bool {
+        match self {
+            PromisorRequirement::AllowList(allowed) => allowed.contains(promisor),
+            PromisorRequirement::Manufacturer(manufacturer) => owner == Some(manufacturer),
+            PromisorRequirement::Model(required) => model == Some(required),
+        }
+    }
+}
+
+/// Domain of liability parameters with promisor requirement, requirement proof isn't valid
+/// as any other proof.
+pub const RESTRICT_DOMAIN: &[u8] = b"robonomics::liability::restrict";
+
+/// Payload of liability parameters signed by promisee, so requirement to promisor is part
+/// of agreement.
+pub fn restricted_params_payload<TechnicalParam, EconomicalParam, AccountId>(
+    technics: &TechnicalParam,
+    economics: &EconomicalParam,
+    requirement: &PromisorRequirement<AccountId>,
+) -> Vec<u8>
+where
+    TechnicalParam: Encode,
+    EconomicalParam: Encode,
+    AccountId: Encode,
+{
+    (RESTRICT_DOMAIN, technics, economics, requirement).encode()
+}
+
+/// Payload of liability report signed by promisor.
+pub fn report_payload<Index: Encode, TechnicalReport: Encode>(
+    index: &Index,
+    report: &TechnicalReport,
+) -> Vec<u8> {
+    (index, report).encode()
+}
+
+/// Payload of liability report attesting firmware that produced it, signed by promisor.
+pub fn attested_report_payload<Index: Encode, TechnicalReport: Encode, Hash: Encode>(
+    index: &Index,
+    report: &TechnicalReport,
+    firmware: &Hash,
+) -> Vec<u8> {
+    (index, report, firmware).encode()
+}
+
+/// Domain of liability cancel payload, cancel proof isn't valid as any other proof.
+pub const CANCEL_DOMAIN: &[u8] = b"robonomics::liability::cancel";
+
+/// Payload of liability cancel signed by party until given block, inclusive.
+pub fn cancel_payload<Index: Encode, BlockNumber: Encode>(
+    index: &Index,
+    expires_at: &BlockNumber,
+) -> Vec<u8> {
+    (CANCEL_DOMAIN, index, expires_at).encode()
+}
+
+/// Domain of report arrival claim payload, claim proof isn't valid as any other proof.
+pub const CLAIM_DOMAIN: &[u8] = b"robonomics::liability::claim";
+
+/// Payload of claim signed by promisor that liability report arrived at given block.
+pub fn claim_payload<Index: Encode, BlockNumber: Encode>(
+    index: &Index,
+    claimed_at: &BlockNumber,
+) -> Vec<u8> {
+    (CLAIM_DOMAIN, index, claimed_at).encode()
+}
+
+/// Domain of economics amendment payload, amendment proof isn't valid as any other proof.
+pub const AMEND_DOMAIN: &[u8] = b"robonomics::liability::amend";
+
+/// Payload of amendment of liability economics signed by both parties, nonce is count of
+/// previous amendments of liability, so amendment proof isn't replayed.
+pub fn amend_payload<Index: Encode, EconomicalParam: Encode>(
+    index: &Index,
+    nonce: u32,
+    economics: &EconomicalParam,
+) -> Vec<u8> {
+    (AMEND_DOMAIN, index, nonce, economics).encode()
+}
+
+/// Domain of acknowledgement payload, acknowledgement proof isn't valid as any other proof.
+pub const ACK_DOMAIN: &[u8] = b"robonomics::liability::acknowledge";
+
+/// Payload of acknowledgement signed by promisor that it's online and serves liability.
+pub fn acknowledge_payload<Index: Encode>(index: &Index) -> Vec<u8> {
+    (ACK_DOMAIN, index).encode()
+}
+
+/// Domain of witnessing consent payload, consent proof isn't valid as any other proof.
+pub const WITNESS_DOMAIN: &[u8] = b"robonomics::liability::witness";
+
+/// Payload of promisor consent to settle liability in its favor only when report is
+/// notarized by given count of witnesses.
+pub fn witnessing_payload<Index: Encode>(index: &Index, quorum: u32) -> Vec<u8> {
+    (WITNESS_DOMAIN, index, quorum).encode()
+}
+
+/// Domain of report notarization payload, notarization proof isn't valid as any other proof.
+pub const NOTARY_DOMAIN: &[u8] = b"robonomics::liability::notary";
+
+/// Payload of witness notarization that liability report with given hash was retrievable
+/// and matched it.
+pub fn notarize_payload<Index: Encode, Hash: Encode>(index: &Index, report_hash: &Hash) -> Vec<u8> {
+    (NOTARY_DOMAIN, index, report_hash).encode()
+}
+
+/// Domain of promisor key rotation payload, rotation proof isn't valid as any other proof.
+pub const ROTATE_DOMAIN: &[u8] = b"robonomics::liability::rotate";
+
+/// Payload of robot owner consent to replace robot key with the new one in its open
+/// liabilities.
+pub fn rotate_payload<AccountId: Encode>(old: &AccountId, new: &AccountId) -> Vec<u8> {
+    (ROTATE_DOMAIN, old, new).encode()
+}
+
+/// Domain of blinded liability payloads and party commitments, they aren't valid as any
+/// other proof.
+pub const BLIND_DOMAIN: &[u8] = b"robonomics::liability::blind";
+
+/// Commitment to liability party kept instead of its account in privacy mode: hash of
+/// encoded account and per-liability salt.
+pub fn party_commitment<AccountId: Encode>(account: &AccountId, salt: &H256) -> H256 {
+    (BLIND_DOMAIN, account, salt)
+        .using_encoded(sp_io::hashing::blake2_256)
+        .into()
+}
+
+/// Payload of blinded liability parameters signed by both parties: commitments to parties
+/// and payout account of successful liability.
+pub fn blinded_params_payload<TechnicalParam, EconomicalParam, AccountId>(
+    technics: &TechnicalParam,
+    economics: &EconomicalParam,
+    promisee: &H256,
+    promisor: &H256,
+    payout: &AccountId,
+) -> Vec<u8>
+where
+    TechnicalParam: Encode,
+    EconomicalParam: Encode,
+    AccountId: Encode,
+{
+    (
+        BLIND_DOMAIN,
+        technics,
+        economics,
+        promisee,
+        promisor,
+        payout,
+    )
+        .encode()
+}
+
+/// Domain of blinded liability report payload, blinded liabilities are indexed apart from
+/// others, so their report proof isn't valid as `report_payload` proof.
+pub const BLIND_REPORT_DOMAIN: &[u8] = b"robonomics::liability::blind-report";
+
+/// Payload of blinded liability report signed by promisor.
+pub fn blinded_report_payload<Index: Encode, TechnicalReport: Encode>(
+    index: &Index,
+    report: &TechnicalReport,
+) -> Vec<u8> {
+    (BLIND_REPORT_DOMAIN, index, report).encode()
+}
+
+/// Hash function of prehashed proofs: secure elements signing fixed size messages only sign
+/// digest of proof payload. It's encoded as a single byte identifier, unknown identifiers
+/// aren't decoded.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum PayloadHasher {
+    /// BLAKE2b-256, hash function of the chain.
+    Blake2_256 = 0,
+    /// Keccak-256, as used by Ethereum.
+    Keccak256 = 1,
+    /// SHA2-256.
+    Sha2_256 = 2,
+}
+
+impl PayloadHasher {
+    /// Digest of proof payload signed in prehashed mode.
+    pub fn digest(&self, payload: &[u8]) -> [u8; 32] {
+        match self {
+            PayloadHasher::Blake2_256 => sp_io::hashing::blake2_256(payload),
+            PayloadHasher::Keccak256 => sp_io::hashing::keccak_256(payload),
+            PayloadHasher::Sha2_256 => sp_io::hashing::sha2_256(payload),
+        }
+    }
+}
+
+/// Digest of proof payload, e.g. `params_payload`, signed in prehashed mode by given hash
+/// function.
+pub fn payload_digest(hasher: PayloadHasher, payload: &[u8]) -> [u8; 32] {
+    hasher.digest(payload)
+}
+
+/// Domain of report commitment payload, commitment proof isn't valid as any other proof.
+pub const COMMIT_DOMAIN: &[u8] = b"robonomics::liability::commit";
+
+/// Commitment to liability report: hash of encoded report followed by salt bytes.
+pub fn report_commitment<TechnicalReport: Encode>(report: &TechnicalReport, salt: &H256) -> H256 {
+    let mut preimage = report.encode();
+    preimage.extend_from_slice(salt.as_bytes());
+    sp_io::hashing::blake2_256(&preimage).into()
+}
+
+/// Payload of liability report commitment signed by promisor.
+pub fn commit_payload<Index: Encode, Commitment: Encode>(
+    index: &Index,
+    commitment: &Commitment,
+) -> Vec<u8> {
+    (COMMIT_DOMAIN, index, commitment).encode()
+}
+
+/// Signature scheme of agreement proof, proofs of different schemes differ
+/// in verification cost.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum SignatureScheme {
+    /// Schnorr signature over Ristretto25519.
+    Sr25519,
+    /// Edwards curve signature.
+    Ed25519,
+    /// Recoverable ECDSA signature over secp256k1.
+    Ecdsa,
+}
+
+/// Proof that knows its signature scheme, so its verification is charged by scheme.
+pub trait ProofScheme {
+    /// Signature scheme of the proof.
+    fn scheme(&self) -> SignatureScheme;
+}
+
+impl ProofScheme for MultiSignature {
+    fn scheme(&self) -> SignatureScheme {
+        match self {
+            MultiSignature::Sr25519(_) => SignatureScheme::Sr25519,
+            MultiSignature::Ed25519(_) => SignatureScheme::Ed25519,
+            MultiSignature::Ecdsa(_) => SignatureScheme::Ecdsa,
+        }
+    }
+}
+
+impl ProofScheme for sr25519::Signature {
+    fn scheme(&self) -> SignatureScheme {
+        SignatureScheme::Sr25519
+    }
+}
+
+impl ProofScheme for ed25519::Signature {
+    fn scheme(&self) -> SignatureScheme {
+        SignatureScheme::Ed25519
+    }
+}
+
+impl ProofScheme for ecdsa::Signature {
+    fn scheme(&self) -> SignatureScheme {
+        SignatureScheme::Ecdsa
+    }
+}
+
+/// Proof made by signature of payload or, in prehashed mode, of payload digest by hash
+/// function selected by its identifier, see `PayloadHasher`. It's a drop-in proof type of
+/// `SignedLiability` for promisors using secure elements that sign digests only.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum PrehashedProof<S> {
+    /// Signature of proof payload.
+    Plain(S),
+    /// Signature of proof payload digest.
+    Prehashed(PayloadHasher, S),
+}
+
+impl<S> From<S> for PrehashedProof<S> {
+    fn from(signature: S) -> Self {
+        PrehashedProof::Plain(signature)
+    }
+}
+
+impl<S: Verify> Verify for PrehashedProof<S> {
+    type Signer = S::Signer;
+
+    fn verify<L: Lazy<[u8]>>(
+        &self,
+        mut msg: L,
+        signer: &<Self::Signer as IdentifyAccount>::AccountId,
+    ) -> bool {
+        match self {
+            PrehashedProof::Plain(signature) => signature.verify(msg, signer),
+            PrehashedProof::Prehashed(hasher, signature) => {
+                signature.verify(&hasher.digest(msg.get())[..], signer)
+            }
+        }
+    }
+}
+
+impl<S: ProofScheme> ProofScheme for PrehashedProof<S> {
+    fn scheme(&self) -> SignatureScheme {
+        match self {
+            PrehashedProof::Plain(signature) | PrehashedProof::Prehashed(_, signature) => {
+                signature.scheme()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sp_core::Pair;
+    use sp_runtime::{AccountId32, MultiSigner};
+
+    fn pair(seed: &str) -> sr25519::Pair {
+        sr25519::Pair::from_string(seed, None).expect("valid seed URI")
+    }
+
+    fn account(seed: &str) -> AccountId32 {
+        MultiSigner::from(pair(seed).public()).into_account()
+    }
+
+    #[test]
+    fn test_payload_domains() {
+        let index = 7u64;
+        assert_eq!(
+            params_payload(&b"QmTask".to_vec(), &()),
+            b"QmTask".to_vec().encode()
+        );
+        assert_eq!(report_payload(&index, &()), index.encode());
+        assert_eq!(
+            acknowledge_payload(&index),
+            [&ACK_DOMAIN.encode()[..], &index.encode()[..]].concat()
+        );
+
+        // Proofs of the same liability by different domains don't match
+        let payloads = [
+            cancel_payload(&index, &1u64),
+            claim_payload(&index, &1u64),
+            commit_payload(&index, &1u64),
+            witnessing_payload(&index, 1),
+            notarize_payload(&index, &1u64),
+            amend_payload(&index, 1, &1u64),
+            blinded_report_payload(&index, &1u64),
+            rotate_payload(&index, &1u64),
+            bounty_params_payload(&index, &1u64, &1u64),
+            restricted_params_payload(&index, &1u64, &PromisorRequirement::Manufacturer(1u64)),
+        ];
+        for (i, payload) in payloads.iter().enumerate() {
+            for other in payloads.iter().skip(i + 1) {
+                assert_ne!(payload, other);
+            }
+        }
+    }
+
+    #[test]
+    fn test_promisor_requirement() {
+        let (alice, bob) = (account("//Alice"), account("//Bob"));
+        let model = H256::repeat_byte(1);
+        let allowed = PromisorRequirement::AllowList(vec![alice.clone()]);
+        assert_eq!(allowed.allowed_count(), 1);
+        assert!(allowed.is_satisfied(&alice, None, None));
+        assert!(!allowed.is_satisfied(&bob, Some(&alice), Some(&model)));
+
+        let manufacturer = PromisorRequirement::Manufacturer(alice.clone());
+        assert_eq!(manufacturer.allowed_count(), 0);
+        assert!(manufacturer.is_satisfied(&bob, Some(&alice), None));
+        assert!(!manufacturer.is_satisfied(&alice, None, None));
+        assert!(!manufacturer.is_satisfied(&bob, Some(&bob), Some(&model)));
+
+        let modelled = PromisorRequirement::<AccountId32>::Model(model);
+        assert!(modelled.is_satisfied(&bob, Some(&bob), Some(&model)));
+        assert!(!modelled.is_satisfied(&bob, Some(&alice), Some(&H256::zero())));
+        assert!(!modelled.is_satisfied(&bob, None, None));
+
+        // Requirement is signed with its variant and domain
+        let payload = restricted_params_payload(&b"QmTask".to_vec(), &(), &allowed);
+        assert_eq!(
+            payload,
+            (RESTRICT_DOMAIN, b"QmTask".to_vec(), (), 0u8, vec![alice]).encode()
+        );
+        assert_ne!(
+            payload,
+            restricted_params_payload(&b"QmTask".to_vec(), &(), &manufacturer)
+        );
+    }
+
+    #[test]
+    fn test_prehashed_proof() {
+        let technics = b"QmPrehashed".to_vec();
+        let payload = params_payload(&technics, &());
+        let (alice, bob) = (account("//Alice"), account("//Bob"));
+        assert_eq!(
+            payload_digest(PayloadHasher::Sha2_256, &payload),
+            sp_io::hashing::sha2_256(&payload)
+        );
+
+        // Each hash function proves payload by its own digest
+        let hashers = [
+            PayloadHasher::Blake2_256,
+            PayloadHasher::Keccak256,
+            PayloadHasher::Sha2_256,
+        ];
+        for hasher in &hashers {
+            let signature = pair("//Bob").sign(&hasher.digest(&payload));
+            let proof = PrehashedProof::Prehashed(*hasher, MultiSignature::from(signature));
+            assert!(proof.verify(&payload[..], &bob));
+            assert!(!proof.verify(&payload[..], &alice));
+            assert!(!proof.verify(&b"another payload"[..], &bob));
+            assert_eq!(proof.scheme(), SignatureScheme::Sr25519);
+        }
+
+        // Signature of keccak digest isn't valid in blake2 or plain mode
+        let signature: MultiSignature = pair("//Bob")
+            .sign(&PayloadHasher::Keccak256.digest(&payload))
+            .into();
+        let blake2 = PrehashedProof::Prehashed(PayloadHasher::Blake2_256, signature.clone());
+        assert!(!blake2.verify(&payload[..], &bob));
+        assert!(!PrehashedProof::Plain(signature).verify(&payload[..], &bob));
+
+        // Plain proof is signature of payload itself
+        let signature: MultiSignature = pair("//Bob").sign(&payload).into();
+        assert!(PrehashedProof::from(signature.clone()).verify(&payload[..], &bob));
+        let keccak = PrehashedProof::Prehashed(PayloadHasher::Keccak256, signature);
+        assert!(!keccak.verify(&payload[..], &bob));
+
+        // Hash function is encoded by its identifier, unknown ones are rejected
+        let mut encoded = keccak.encode();
+        assert_eq!(&encoded[..2], &[1, 1]);
+        encoded[1] = 2;
+        let decoded = PrehashedProof::<MultiSignature>::decode(&mut &encoded[..]);
+        assert!(matches!(
+            decoded,
+            Ok(PrehashedProof::Prehashed(PayloadHasher::Sha2_256, _))
+        ));
+        encoded[1] = 3;
+        assert!(PrehashedProof::<MultiSignature>::decode(&mut &encoded[..]).is_err());
+    }
+}
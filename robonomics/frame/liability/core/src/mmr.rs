@@ -0,0 +1,311 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Merkle mountain range of finalized liabilities, bridged chains verify liability outcome
+//! by inclusion proof against relayed root.
+//!
+//! Encoding is fixed, contracts of bridged chains rely on it:
+//!
+//! - leaf is `keccak256(index ++ promisor ++ report_hash ++ success)` of SCALE encoded
+//!   `FinalizationLeaf`, for `u64` index and 32 bytes account it's 73 bytes: little-endian
+//!   index, account, report hash (zero for liability finalized without report) and
+//!   `0x01` byte for success or `0x00` for failure;
+//! - parent node is `keccak256(left ++ right)`;
+//! - nodes are numbered from zero in order of appending, parents right after their
+//!   right child;
+//! - root bags peaks from the right, `keccak256(peak_0 ++ keccak256(peak_1 ++ peak_2))`
+//!   for three peaks, root of single peak is the peak itself and zero for empty range.
+
+use codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_core::H256;
+use sp_runtime::RuntimeDebug;
+use sp_std::prelude::*;
+
+/// Outcome of finalized liability appended to the range.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct FinalizationLeaf<Index, AccountId> {
+    /// Index of liability.
+    pub index: Index,
+    /// Promisor of liability.
+    pub promisor: AccountId,
+    /// Hash of technical report, zero when liability is finalized without report.
+    pub report_hash: H256,
+    /// Economical outcome of liability.
+    pub success: bool,
+}
+
+impl<Index: Encode, AccountId: Encode> FinalizationLeaf<Index, AccountId> {
+    /// Hash of leaf, the range node.
+    pub fn hash(&self) -> H256 {
+        self.using_encoded(sp_io::hashing::keccak_256).into()
+    }
+}
+
+/// Inclusion proof of leaf in range of given count of leaves.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct MmrProof {
+    /// Position of leaf in order of appending.
+    pub leaf_index: u64,
+    /// Count of leaves of the range proof is made for.
+    pub leaf_count: u64,
+    /// Siblings of nodes on path from leaf up to its peak.
+    pub items: Vec<H256>,
+    /// Peaks of the range, the highest first.
+    pub peaks: Vec<H256>,
+}
+
+/// Parent node of two nodes.
+pub fn merge(left: &H256, right: &H256) -> H256 {
+    (left, right)
+        .using_encoded(sp_io::hashing::keccak_256)
+        .into()
+}
+
+/// Count of nodes of range with given count of leaves.
+pub fn mmr_size(leaf_count: u64) -> u64 {
+    2 * leaf_count - u64::from(leaf_count.count_ones())
+}
+
+/// Positions and heights of peaks of range with given count of leaves, the highest first.
+pub fn peaks(leaf_count: u64) -> Vec<(u64, u32)> {
+    let mut offset = 0;
+    (0..64)
+        .rev()
+        .filter(|height| (leaf_count >> *height) & 1 == 1)
+        .map(|height| {
+            let size = (2 << height) - 1;
+            offset += size;
+            (offset - 1, height)
+        })
+        .collect()
+}
+
+/// Root of range by its peaks, the highest first.
+pub fn bag_peaks(peaks: &[H256]) -> H256 {
+    peaks
+        .iter()
+        .rev()
+        .fold(None, |bagged, peak| {
+            Some(bagged.map_or(*peak, |bagged| merge(peak, &bagged)))
+        })
+        .unwrap_or_default()
+}
+
+/// Append leaf to range of `leaf_count` leaves, nodes are read and written by position.
+pub fn append_leaf(
+    leaf_count: u64,
+    leaf: H256,
+    node: impl Fn(u64) -> H256,
+    mut insert: impl FnMut(u64, H256),
+) {
+    let mut position = mmr_size(leaf_count);
+    let mut hash = leaf;
+    insert(position, hash);
+
+    // Each trailing one of leaf index is a mountain completed by the leaf
+    let mut height = 0;
+    while (leaf_count >> height) & 1 == 1 {
+        hash = merge(&node(position + 1 - (2 << height)), &hash);
+        position += 1;
+        insert(position, hash);
+        height += 1;
+    }
+}
+
+/// Mountain of leaf: number of its peak, index of leaf in mountain and mountain height.
+fn mountain_of(leaf_index: u64, leaf_count: u64) -> Option<(usize, u64, u32)> {
+    if leaf_index >= leaf_count {
+        return None;
+    }
+    let mut first_leaf = 0;
+    for (number, (_, height)) in peaks(leaf_count).into_iter().enumerate() {
+        if leaf_index < first_leaf + (1 << height) {
+            return Some((number, leaf_index - first_leaf, height));
+        }
+        first_leaf += 1 << height;
+    }
+    None
+}
+
+/// Positions of siblings on path from leaf up to its peak, `None` when leaf isn't
+/// in range.
+pub fn proof_positions(leaf_index: u64, leaf_count: u64) -> Option<Vec<u64>> {
+    let (number, mut local, mut height) = mountain_of(leaf_index, leaf_count)?;
+    let mut root = peaks(leaf_count)[number].0;
+    let mut positions = Vec::with_capacity(height as usize);
+    while height > 0 {
+        let (left, right) = (root - (1 << height), root - 1);
+        let half = 1 << (height - 1);
+        if local < half {
+            positions.push(right);
+            root = left;
+        } else {
+            positions.push(left);
+            root = right;
+            local -= half;
+        }
+        height -= 1;
+    }
+    positions.reverse();
+    Some(positions)
+}
+
+/// Verify inclusion of leaf in range with given root.
+pub fn verify_proof(root: &H256, leaf: &H256, proof: &MmrProof) -> bool {
+    let (number, local, height) = match mountain_of(proof.leaf_index, proof.leaf_count) {
+        Some(mountain) => mountain,
+        None => return false,
+    };
+    if proof.items.len() != height as usize
+        || proof.peaks.len() != proof.leaf_count.count_ones() as usize
+    {
+        return false;
+    }
+    let peak = proof
+        .items
+        .iter()
+        .enumerate()
+        .fold(*leaf, |node, (level, sibling)| {
+            if (local >> level) & 1 == 0 {
+                merge(&node, sibling)
+            } else {
+                merge(sibling, &node)
+            }
+        });
+    proof.peaks[number] == peak && bag_peaks(&proof.peaks) == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    fn leaf(index: u64) -> FinalizationLeaf<u64, [u8; 32]> {
+        FinalizationLeaf {
+            index,
+            promisor: [1; 32],
+            report_hash: H256::repeat_byte(2),
+            success: true,
+        }
+    }
+
+    fn build(leaf_count: u64) -> (Vec<H256>, H256) {
+        let mut nodes = vec![];
+        for index in 0..leaf_count {
+            let hash = leaf(index).hash();
+            let mut appended = vec![];
+            append_leaf(
+                index,
+                hash,
+                |p| nodes[p as usize],
+                |p, h| appended.push((p, h)),
+            );
+            for (position, hash) in appended {
+                assert_eq!(position, nodes.len() as u64);
+                nodes.push(hash);
+            }
+        }
+        assert_eq!(nodes.len() as u64, mmr_size(leaf_count));
+        let peaks: Vec<_> = peaks(leaf_count)
+            .into_iter()
+            .map(|(position, _)| nodes[position as usize])
+            .collect();
+        (nodes, bag_peaks(&peaks))
+    }
+
+    #[test]
+    fn test_leaf_encoding() {
+        let encoded = leaf(1).encode();
+        assert_eq!(encoded.len(), 73);
+        assert_eq!(&encoded[..8], &[1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(&encoded[8..40], &[1; 32]);
+        assert_eq!(&encoded[40..72], &[2; 32]);
+        assert_eq!(encoded[72], 1);
+        assert_eq!(
+            leaf(1).hash(),
+            hex!["fd20b53357f4dcbd804ac037cbd9a97df0e22e258fc3f8880ae4438d52995488"].into()
+        );
+        let failed = FinalizationLeaf {
+            success: false,
+            ..leaf(0)
+        };
+        assert_eq!(
+            failed.hash(),
+            hex!["d14692322402c8919aabbb0bbd61a30a371ad34bfd01c0c472e7830f8de21e79"].into()
+        );
+    }
+
+    #[test]
+    fn test_mmr_root() {
+        assert_eq!(build(0).1, H256::zero());
+        assert_eq!(build(1).1, leaf(0).hash());
+        assert_eq!(
+            build(1).1,
+            hex!["dd882df290956133bfe675baf56ae784f2e126f1d09348741991d95431c22eca"].into()
+        );
+        assert_eq!(
+            build(2).1,
+            hex!["066ac8d0addcd747081d3164223a19915cf4222d5b31105eea9b8c90385151f4"].into()
+        );
+        assert_eq!(
+            build(3).1,
+            hex!["32c5d62f347383976ec241138b2733d076afd6b30fdbc915ff7458a43df30526"].into()
+        );
+        assert_eq!(peaks(11), vec![(14, 3), (17, 1), (18, 0)]);
+    }
+
+    #[test]
+    fn test_mmr_proof() {
+        for leaf_count in 1..=11 {
+            let (nodes, root) = build(leaf_count);
+            let peaks: Vec<_> = peaks(leaf_count)
+                .into_iter()
+                .map(|(position, _)| nodes[position as usize])
+                .collect();
+            for leaf_index in 0..leaf_count {
+                let items = proof_positions(leaf_index, leaf_count)
+                    .unwrap()
+                    .into_iter()
+                    .map(|position| nodes[position as usize])
+                    .collect();
+                let proof = MmrProof {
+                    leaf_index,
+                    leaf_count,
+                    items,
+                    peaks: peaks.clone(),
+                };
+                let hash = leaf(leaf_index).hash();
+                assert!(verify_proof(&root, &hash, &proof));
+
+                // Proof is bound to leaf and its position
+                assert!(!verify_proof(&root, &leaf(leaf_count).hash(), &proof));
+                let moved = MmrProof {
+                    leaf_index: (leaf_index + 1) % leaf_count,
+                    ..proof.clone()
+                };
+                assert!(leaf_count == 1 || !verify_proof(&root, &hash, &moved));
+            }
+            assert_eq!(proof_positions(leaf_count, leaf_count), None);
+        }
+    }
+}
@@ -0,0 +1,87 @@
+/* Generated by cbindgen, don't edit by hand. */
+
+#ifndef ROBONOMICS_LIABILITY_H
+#define ROBONOMICS_LIABILITY_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Error: null pointer is passed as non-empty input or fixed size buffer.
+#define LIABILITY_ERR_NULL -1
+
+// Error: output buffer is shorter than result.
+#define LIABILITY_ERR_BUFFER -2
+
+// Length of sr25519 seed, mini secret key.
+#define LIABILITY_SEED_LEN 32
+
+// Length of sr25519 public key, it's account id.
+#define LIABILITY_PUBLIC_LEN 32
+
+// Length of sr25519 signature.
+#define LIABILITY_SIGNATURE_LEN 64
+
+// Length of firmware hash attested by report.
+#define LIABILITY_HASH_LEN 32
+
+// Payload of liability parameters signed by both promisee and promisor.
+//
+// # Safety
+//
+// `technics` and `economics` point to `technics_len` and `economics_len` readable bytes,
+// non-null `out` points to `out_cap` writable bytes.
+ptrdiff_t liability_params_payload(const uint8_t *technics,
+                                   size_t technics_len,
+                                   const uint8_t *economics,
+                                   size_t economics_len,
+                                   uint8_t *out,
+                                   size_t out_cap);
+
+// Payload of liability report signed by promisor.
+//
+// # Safety
+//
+// `report` points to `report_len` readable bytes, non-null `out` points to `out_cap`
+// writable bytes.
+ptrdiff_t liability_report_payload(uint64_t index,
+                                   const uint8_t *report,
+                                   size_t report_len,
+                                   uint8_t *out,
+                                   size_t out_cap);
+
+// Payload of liability report attesting firmware that produced it, signed by promisor.
+//
+// # Safety
+//
+// `report` points to `report_len` readable bytes, `firmware` to `LIABILITY_HASH_LEN`
+// readable bytes, non-null `out` points to `out_cap` writable bytes.
+ptrdiff_t liability_attested_report_payload(uint64_t index,
+                                            const uint8_t *report,
+                                            size_t report_len,
+                                            const uint8_t *firmware,
+                                            uint8_t *out,
+                                            size_t out_cap);
+
+// Write public key of sr25519 seed into `out_public`, returns `LIABILITY_PUBLIC_LEN`.
+//
+// # Safety
+//
+// `seed` points to `LIABILITY_SEED_LEN` readable bytes, `out_public` to
+// `LIABILITY_PUBLIC_LEN` writable bytes.
+ptrdiff_t liability_public_sr25519(const uint8_t *seed, uint8_t *out_public);
+
+// Sign payload by sr25519 seed into `out_sig`, returns `LIABILITY_SIGNATURE_LEN`.
+//
+// # Safety
+//
+// `seed` points to `LIABILITY_SEED_LEN` readable bytes, `payload` to `payload_len`
+// readable bytes and `out_sig` to `LIABILITY_SIGNATURE_LEN` writable bytes.
+ptrdiff_t liability_sign_sr25519(const uint8_t *seed,
+                                 const uint8_t *payload,
+                                 size_t payload_len,
+                                 uint8_t *out_sig);
+
+#endif /* ROBONOMICS_LIABILITY_H */
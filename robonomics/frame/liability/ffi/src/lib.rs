@@ -0,0 +1,334 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! C ABI of liability proofs for promisor firmware that doesn't link Rust.
+//!
+//! Payloads are built by `robonomics_liability_core`, the same code module verifies proofs
+//! by, for runtimes with `u64` liability index and raw bytes technics and reports. Economics
+//! is passed SCALE encoded, it's appended to payload as is. Signature of sr25519 account is
+//! the `Sr25519` variant of `MultiSignature` proof.
+//!
+//! Buffers are owned by caller. Payload function writes payload into `out` buffer of
+//! `out_cap` bytes and returns its length, with null `out` it only returns the length, so
+//! caller could allocate buffer. Negative result is an error, nothing is written then.
+//! Fixed size inputs and outputs, e.g. seed and signature, point to buffers of that size.
+//!
+//! Header `include/robonomics_liability.h` is generated by cbindgen, see `cbindgen.toml`.
+
+use codec::{Encode, Output};
+use robonomics_liability_core::{attested_report_payload, params_payload, report_payload};
+use sp_core::{crypto::Pair, sr25519};
+use std::{ptr, slice};
+
+/// Error: null pointer is passed as non-empty input or fixed size buffer.
+pub const LIABILITY_ERR_NULL: isize = -1;
+
+/// Error: output buffer is shorter than result.
+pub const LIABILITY_ERR_BUFFER: isize = -2;
+
+/// Length of sr25519 seed, mini secret key.
+pub const LIABILITY_SEED_LEN: usize = 32;
+
+/// Length of sr25519 public key, it's account id.
+pub const LIABILITY_PUBLIC_LEN: usize = 32;
+
+/// Length of sr25519 signature.
+pub const LIABILITY_SIGNATURE_LEN: usize = 64;
+
+/// Length of firmware hash attested by report.
+pub const LIABILITY_HASH_LEN: usize = 32;
+
+/// SCALE encoded value, it's encoded as is.
+struct Encoded<'a>(&'a [u8]);
+
+impl Encode for Encoded<'_> {
+    fn size_hint(&self) -> usize {
+        self.0.len()
+    }
+
+    fn encode_to<T: Output>(&self, dest: &mut T) {
+        dest.write(self.0)
+    }
+}
+
+/// Input of given length, empty input could be null.
+unsafe fn input<'a>(data: *const u8, len: usize) -> Option<&'a [u8]> {
+    if len == 0 {
+        Some(&[])
+    } else if data.is_null() {
+        None
+    } else {
+        Some(slice::from_raw_parts(data, len))
+    }
+}
+
+/// Write result into caller buffer, see module docs for returned value.
+unsafe fn output(result: &[u8], out: *mut u8, out_cap: usize) -> isize {
+    if out.is_null() {
+        return result.len() as isize;
+    }
+    if out_cap < result.len() {
+        return LIABILITY_ERR_BUFFER;
+    }
+    ptr::copy_nonoverlapping(result.as_ptr(), out, result.len());
+    result.len() as isize
+}
+
+/// Key pair of fixed size seed.
+unsafe fn pair(seed: *const u8) -> Option<sr25519::Pair> {
+    let seed = input(seed, LIABILITY_SEED_LEN)?;
+    let mut mini_secret = [0u8; LIABILITY_SEED_LEN];
+    mini_secret.copy_from_slice(seed);
+    Some(sr25519::Pair::from_seed(&mini_secret))
+}
+
+/// Payload of liability parameters signed by both promisee and promisor.
+///
+/// # Safety
+///
+/// `technics` and `economics` point to `technics_len` and `economics_len` readable bytes,
+/// non-null `out` points to `out_cap` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn liability_params_payload(
+    technics: *const u8,
+    technics_len: usize,
+    economics: *const u8,
+    economics_len: usize,
+    out: *mut u8,
+    out_cap: usize,
+) -> isize {
+    match (
+        input(technics, technics_len),
+        input(economics, economics_len),
+    ) {
+        (Some(technics), Some(economics)) => output(
+            &params_payload(&technics, &Encoded(economics)),
+            out,
+            out_cap,
+        ),
+        _ => LIABILITY_ERR_NULL,
+    }
+}
+
+/// Payload of liability report signed by promisor.
+///
+/// # Safety
+///
+/// `report` points to `report_len` readable bytes, non-null `out` points to `out_cap`
+/// writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn liability_report_payload(
+    index: u64,
+    report: *const u8,
+    report_len: usize,
+    out: *mut u8,
+    out_cap: usize,
+) -> isize {
+    match input(report, report_len) {
+        Some(report) => output(&report_payload(&index, &report), out, out_cap),
+        None => LIABILITY_ERR_NULL,
+    }
+}
+
+/// Payload of liability report attesting firmware that produced it, signed by promisor.
+///
+/// # Safety
+///
+/// `report` points to `report_len` readable bytes, `firmware` to `LIABILITY_HASH_LEN`
+/// readable bytes, non-null `out` points to `out_cap` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn liability_attested_report_payload(
+    index: u64,
+    report: *const u8,
+    report_len: usize,
+    firmware: *const u8,
+    out: *mut u8,
+    out_cap: usize,
+) -> isize {
+    match (
+        input(report, report_len),
+        input(firmware, LIABILITY_HASH_LEN),
+    ) {
+        (Some(report), Some(firmware)) => output(
+            &attested_report_payload(&index, &report, &Encoded(firmware)),
+            out,
+            out_cap,
+        ),
+        _ => LIABILITY_ERR_NULL,
+    }
+}
+
+/// Write public key of sr25519 seed into `out_public`, returns `LIABILITY_PUBLIC_LEN`.
+///
+/// # Safety
+///
+/// `seed` points to `LIABILITY_SEED_LEN` readable bytes, `out_public` to
+/// `LIABILITY_PUBLIC_LEN` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn liability_public_sr25519(seed: *const u8, out_public: *mut u8) -> isize {
+    match pair(seed) {
+        Some(pair) if !out_public.is_null() => {
+            output(pair.public().as_ref(), out_public, LIABILITY_PUBLIC_LEN)
+        }
+        _ => LIABILITY_ERR_NULL,
+    }
+}
+
+/// Sign payload by sr25519 seed into `out_sig`, returns `LIABILITY_SIGNATURE_LEN`.
+///
+/// # Safety
+///
+/// `seed` points to `LIABILITY_SEED_LEN` readable bytes, `payload` to `payload_len`
+/// readable bytes and `out_sig` to `LIABILITY_SIGNATURE_LEN` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn liability_sign_sr25519(
+    seed: *const u8,
+    payload: *const u8,
+    payload_len: usize,
+    out_sig: *mut u8,
+) -> isize {
+    match (pair(seed), input(payload, payload_len)) {
+        (Some(pair), Some(payload)) if !out_sig.is_null() => {
+            let signature = pair.sign(payload);
+            output(signature.as_ref(), out_sig, LIABILITY_SIGNATURE_LEN)
+        }
+        _ => LIABILITY_ERR_NULL,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(index: u64, report: &[u8]) -> Vec<u8> {
+        let len = unsafe {
+            liability_report_payload(index, report.as_ptr(), report.len(), ptr::null_mut(), 0)
+        };
+        let mut out = vec![0u8; len as usize];
+        let written = unsafe {
+            liability_report_payload(
+                index,
+                report.as_ptr(),
+                report.len(),
+                out.as_mut_ptr(),
+                len as usize,
+            )
+        };
+        assert_eq!(written, len);
+        out
+    }
+
+    #[test]
+    fn test_payloads() {
+        let technics = b"QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4".to_vec();
+        let economics = 42u128;
+        let encoded = economics.encode();
+        let mut out = [0u8; 128];
+        let len = unsafe {
+            liability_params_payload(
+                technics.as_ptr(),
+                technics.len(),
+                encoded.as_ptr(),
+                encoded.len(),
+                out.as_mut_ptr(),
+                out.len(),
+            )
+        };
+        assert_eq!(
+            &out[..len as usize],
+            &params_payload(&technics, &economics)[..]
+        );
+
+        assert_eq!(report(7, b"done"), report_payload(&7u64, &b"done".to_vec()));
+        assert_eq!(report(0, &[]), report_payload(&0u64, &Vec::<u8>::new()));
+
+        let firmware = sp_core::H256::repeat_byte(3);
+        let len = unsafe {
+            liability_attested_report_payload(
+                7,
+                b"done".as_ptr(),
+                4,
+                firmware.as_ptr(),
+                out.as_mut_ptr(),
+                out.len(),
+            )
+        };
+        assert_eq!(
+            &out[..len as usize],
+            &attested_report_payload(&7u64, &b"done".to_vec(), &firmware)[..]
+        );
+    }
+
+    #[test]
+    fn test_buffers() {
+        // Nothing is written to short buffer
+        let mut out = [0u8; 8];
+        let result =
+            unsafe { liability_report_payload(7, b"done".as_ptr(), 4, out.as_mut_ptr(), 8) };
+        assert_eq!(result, LIABILITY_ERR_BUFFER);
+        assert_eq!(out, [0u8; 8]);
+
+        // Null is empty input only
+        let result = unsafe { liability_report_payload(7, ptr::null(), 4, out.as_mut_ptr(), 8) };
+        assert_eq!(result, LIABILITY_ERR_NULL);
+        let result = unsafe { liability_report_payload(7, ptr::null(), 0, out.as_mut_ptr(), 8) };
+        assert_eq!(result, 9);
+
+        let seed = [1u8; LIABILITY_SEED_LEN];
+        let mut signature = [0u8; LIABILITY_SIGNATURE_LEN];
+        let result =
+            unsafe { liability_sign_sr25519(ptr::null(), out.as_ptr(), 8, signature.as_mut_ptr()) };
+        assert_eq!(result, LIABILITY_ERR_NULL);
+        let result =
+            unsafe { liability_sign_sr25519(seed.as_ptr(), out.as_ptr(), 8, ptr::null_mut()) };
+        assert_eq!(result, LIABILITY_ERR_NULL);
+        let result = unsafe { liability_public_sr25519(seed.as_ptr(), ptr::null_mut()) };
+        assert_eq!(result, LIABILITY_ERR_NULL);
+    }
+
+    #[test]
+    fn test_sign_sr25519() {
+        let seed = [1u8; LIABILITY_SEED_LEN];
+        let payload = report(7, b"done");
+        let mut public = [0u8; LIABILITY_PUBLIC_LEN];
+        let mut signature = [0u8; LIABILITY_SIGNATURE_LEN];
+        unsafe {
+            assert_eq!(
+                liability_public_sr25519(seed.as_ptr(), public.as_mut_ptr()),
+                LIABILITY_PUBLIC_LEN as isize
+            );
+            assert_eq!(
+                liability_sign_sr25519(
+                    seed.as_ptr(),
+                    payload.as_ptr(),
+                    payload.len(),
+                    signature.as_mut_ptr()
+                ),
+                LIABILITY_SIGNATURE_LEN as isize
+            );
+        }
+        let public = sr25519::Public::from_raw(public);
+        assert_eq!(public, sr25519::Pair::from_seed(&seed).public());
+        let signature = sr25519::Signature::from_raw(signature);
+        assert!(sr25519::Pair::verify(&signature, &payload, &public));
+        assert!(!sr25519::Pair::verify(
+            &signature,
+            &report(8, b"done"),
+            &public
+        ));
+    }
+}
@@ -0,0 +1,384 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Prometheus metrics of liability module throughput.
+//!
+//! Imported blocks are decoded by liability runtime API and their counts wait for finality,
+//! so counters never include liabilities of retracted forks. Gauges follow the best block.
+
+use futures::{stream, StreamExt};
+use log::warn;
+use pallet_robonomics_liability_rpc_runtime_api::{LiabilityApi, LiabilityEvent};
+use prometheus_endpoint::{
+    register, Counter, Gauge, Histogram, HistogramOpts, PrometheusError, Registry, U64,
+};
+use sc_client_api::BlockchainEvents;
+use sp_api::ProvideRuntimeApi;
+use sp_runtime::{
+    generic::BlockId,
+    traits::{Block as BlockT, Header as HeaderT, NumberFor, UniqueSaturatedInto},
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Buckets of report delay histogram, in blocks.
+const REPORT_DELAY_BUCKETS: [f64; 10] =
+    [1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0, 1000.0];
+
+/// Liability counts of imported block.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct BlockCounts {
+    /// Count of created liabilities.
+    pub created: u64,
+    /// Count of published reports.
+    pub reported: u64,
+    /// Blocks from creation to report of liabilities reported in block.
+    pub report_delays: Vec<u64>,
+}
+
+/// Imported blocks waiting for finality.
+pub struct Pending<Hash, Number> {
+    blocks: HashMap<Hash, (Hash, Number, BlockCounts)>,
+}
+
+impl<Hash, Number> Default for Pending<Hash, Number> {
+    fn default() -> Self {
+        Pending {
+            blocks: HashMap::new(),
+        }
+    }
+}
+
+impl<Hash, Number> Pending<Hash, Number>
+where
+    Hash: std::hash::Hash + Eq + Clone,
+    Number: PartialOrd,
+{
+    /// Remember counts of imported block, repeated import of block replaces them.
+    pub fn import(&mut self, hash: Hash, parent: Hash, number: Number, counts: BlockCounts) {
+        self.blocks.insert(hash, (parent, number, counts));
+    }
+
+    /// Counts of blocks finalized up to given one in ascending order, blocks of forks
+    /// retracted by it are dropped.
+    pub fn finalize(&mut self, hash: Hash, number: Number) -> Vec<BlockCounts> {
+        let mut route = Vec::new();
+        let mut cursor = hash;
+        while let Some((parent, _, counts)) = self.blocks.remove(&cursor) {
+            route.push(counts);
+            cursor = parent;
+        }
+        route.reverse();
+        self.blocks.retain(|_, (_, pending, _)| *pending > number);
+        route
+    }
+
+    /// Count of blocks waiting for finality.
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+}
+
+/// Liability metrics under `robonomics_liability_` prefix.
+#[derive(Clone)]
+pub struct Metrics {
+    created: Counter<U64>,
+    finalized: Counter<U64>,
+    created_in_block: Gauge<U64>,
+    finalized_in_block: Gauge<U64>,
+    open: Gauge<U64>,
+    report_delay: Histogram,
+}
+
+impl Metrics {
+    /// Register liability metrics in node registry.
+    pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+        Ok(Metrics {
+            created: register(
+                Counter::new(
+                    "robonomics_liability_created_total",
+                    "Liabilities created in finalized blocks",
+                )?,
+                registry,
+            )?,
+            finalized: register(
+                Counter::new(
+                    "robonomics_liability_finalized_total",
+                    "Liability reports published in finalized blocks",
+                )?,
+                registry,
+            )?,
+            created_in_block: register(
+                Gauge::new(
+                    "robonomics_liability_created_per_block",
+                    "Liabilities created in the best block",
+                )?,
+                registry,
+            )?,
+            finalized_in_block: register(
+                Gauge::new(
+                    "robonomics_liability_finalized_per_block",
+                    "Liability reports published in the best block",
+                )?,
+                registry,
+            )?,
+            open: register(
+                Gauge::new(
+                    "robonomics_liability_open",
+                    "Liabilities without report at the best block",
+                )?,
+                registry,
+            )?,
+            report_delay: register(
+                Histogram::with_opts(
+                    HistogramOpts::new(
+                        "robonomics_liability_report_delay_blocks",
+                        "Blocks from liability creation to its report, by finalized reports",
+                    )
+                    .buckets(REPORT_DELAY_BUCKETS.to_vec()),
+                )?,
+                registry,
+            )?,
+        })
+    }
+
+    /// Note counts of the new best block and open liabilities at it.
+    fn note_best(&self, counts: &BlockCounts, open: u64) {
+        self.created_in_block.set(counts.created);
+        self.finalized_in_block.set(counts.reported);
+        self.open.set(open);
+    }
+
+    /// Note counts of finalized block.
+    fn note_finalized(&self, counts: &BlockCounts) {
+        self.created.inc_by(counts.created);
+        self.finalized.inc_by(counts.reported);
+        for delay in &counts.report_delays {
+            self.report_delay.observe(*delay as f64);
+        }
+    }
+}
+
+/// Block notification of client.
+enum Notification<Block: BlockT> {
+    Import(Block::Hash, Block::Header, bool),
+    Finality(Block::Hash, NumberFor<Block>),
+}
+
+/// Update liability metrics by client block notifications.
+pub async fn run<
+    Block,
+    C,
+    Index,
+    TechnicalParam,
+    EconomicalParam,
+    TechnicalReport,
+    AccountId,
+    Balance,
+>(
+    client: Arc<C>,
+    metrics: Metrics,
+) where
+    Block: BlockT,
+    C: BlockchainEvents<Block> + ProvideRuntimeApi<Block>,
+    C::Api: LiabilityApi<
+        Block,
+        Index,
+        TechnicalParam,
+        EconomicalParam,
+        TechnicalReport,
+        AccountId,
+        NumberFor<Block>,
+        Balance,
+    >,
+    Index: codec::Codec,
+    TechnicalParam: codec::Codec,
+    EconomicalParam: codec::Codec,
+    TechnicalReport: codec::Codec,
+    AccountId: codec::Codec,
+    Balance: codec::Codec,
+{
+    let imports = client.import_notification_stream().map(|notification| {
+        Notification::<Block>::Import(
+            notification.hash,
+            notification.header,
+            notification.is_new_best,
+        )
+    });
+    let finality = client.finality_notification_stream().map(|notification| {
+        Notification::<Block>::Finality(notification.hash, *notification.header.number())
+    });
+
+    let mut pending = Pending::default();
+    let mut notifications = stream::select(imports, finality);
+    while let Some(notification) = notifications.next().await {
+        match notification {
+            Notification::Import(hash, header, is_new_best) => {
+                let at = BlockId::hash(hash);
+                let counts = block_counts(&*client, &at);
+                if is_new_best {
+                    match client.runtime_api().counters(&at) {
+                        Ok(counters) => {
+                            let closed = counters.finalized.saturating_add(counters.cancelled);
+                            metrics.note_best(&counts, counters.created.saturating_sub(closed));
+                        }
+                        Err(e) => warn!("Unable to fetch liability counters at {}: {:?}", hash, e),
+                    }
+                }
+                pending.import(hash, *header.parent_hash(), *header.number(), counts);
+            }
+            Notification::Finality(hash, number) => {
+                for counts in pending.finalize(hash, number) {
+                    metrics.note_finalized(&counts);
+                }
+            }
+        }
+    }
+}
+
+/// Liability counts of given block.
+fn block_counts<
+    Block,
+    C,
+    Index,
+    TechnicalParam,
+    EconomicalParam,
+    TechnicalReport,
+    AccountId,
+    Balance,
+>(
+    client: &C,
+    at: &BlockId<Block>,
+) -> BlockCounts
+where
+    Block: BlockT,
+    C: ProvideRuntimeApi<Block>,
+    C::Api: LiabilityApi<
+        Block,
+        Index,
+        TechnicalParam,
+        EconomicalParam,
+        TechnicalReport,
+        AccountId,
+        NumberFor<Block>,
+        Balance,
+    >,
+    Index: codec::Codec,
+    TechnicalParam: codec::Codec,
+    EconomicalParam: codec::Codec,
+    TechnicalReport: codec::Codec,
+    AccountId: codec::Codec,
+    Balance: codec::Codec,
+{
+    let api = client.runtime_api();
+    let events = match api.block_events(at) {
+        Ok(events) => events,
+        Err(e) => {
+            warn!("Unable to fetch liability events at {}: {:?}", at, e);
+            return BlockCounts::default();
+        }
+    };
+
+    let mut counts = BlockCounts::default();
+    for event in events {
+        match event {
+            LiabilityEvent::Created { .. } => counts.created += 1,
+            LiabilityEvent::Reported {
+                index,
+                block_number,
+                ..
+            } => {
+                counts.reported += 1;
+                // Zero creation block means that it wasn't tracked
+                let created_at = api
+                    .get_liability(at, index)
+                    .ok()
+                    .flatten()
+                    .map(|info| info.created_at)
+                    .filter(|created_at| *created_at > 0u32.into());
+                if let Some(created_at) = created_at {
+                    let delay: u64 = block_number
+                        .saturating_sub(created_at)
+                        .unique_saturated_into();
+                    counts.report_delays.push(delay);
+                }
+            }
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(created: u64) -> BlockCounts {
+        BlockCounts {
+            created,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_finalized_route() {
+        let mut pending = Pending::<u8, u32>::default();
+        pending.import(1, 0, 1, counts(1));
+        pending.import(2, 1, 2, counts(2));
+        pending.import(3, 2, 3, counts(3));
+
+        // Finalized block and its unfinalized ancestors are counted once in order
+        assert_eq!(pending.finalize(2, 2), vec![counts(1), counts(2)]);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending.finalize(2, 2), vec![]);
+        assert_eq!(pending.finalize(3, 3), vec![counts(3)]);
+        assert_eq!(pending.len(), 0);
+    }
+
+    #[test]
+    fn test_retracted_fork() {
+        let mut pending = Pending::<u8, u32>::default();
+        pending.import(1, 0, 1, counts(1));
+        // Fork block was the best one, then it's retracted by canonical chain
+        pending.import(10, 1, 2, counts(10));
+        pending.import(2, 1, 2, counts(2));
+        pending.import(3, 2, 3, counts(3));
+        // Block imported again replaces its counts
+        pending.import(3, 2, 3, counts(4));
+
+        assert_eq!(
+            pending.finalize(3, 3),
+            vec![counts(1), counts(2), counts(4)]
+        );
+        // Retracted fork is dropped
+        assert_eq!(pending.len(), 0);
+    }
+
+    #[test]
+    fn test_pending_descendants() {
+        let mut pending = Pending::<u8, u32>::default();
+        pending.import(1, 0, 1, counts(1));
+        pending.import(2, 1, 2, counts(2));
+        pending.import(20, 1, 2, counts(20));
+        pending.import(3, 2, 3, counts(3));
+
+        // Descendants of finalized block wait for finality, both forks at its height dropped
+        assert_eq!(pending.finalize(1, 1), vec![counts(1)]);
+        assert_eq!(pending.len(), 3);
+        assert_eq!(pending.finalize(2, 2), vec![counts(2)]);
+        assert_eq!(pending.len(), 1);
+    }
+}
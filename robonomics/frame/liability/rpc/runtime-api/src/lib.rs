@@ -0,0 +1,1169 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Runtime API definition for liability module.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Codec, Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_core::{Hasher, H160, H256};
+use sp_runtime::{
+    transaction_validity::{InvalidTransaction, TransactionValidity, TransactionValidityError},
+    DispatchError, Perbill, RuntimeDebug,
+};
+use sp_std::prelude::*;
+use sp_trie::{read_trie_value, HashDBT, Layout, MemoryDB, EMPTY_PREFIX};
+
+pub use robonomics_liability_core::bloom::{AccountBloom, BLOOM_DIGEST_PREFIX};
+pub use robonomics_liability_core::eth::LegacyRecord;
+pub use robonomics_liability_core::mmr::{FinalizationLeaf, MmrProof};
+pub use robonomics_liability_core::{
+    acknowledge_payload, amend_payload, attested_report_payload, blinded_params_payload,
+    blinded_report_payload, bounty_params_payload, cancel_payload, claim_payload, commit_payload,
+    expiring_params_payload, notarize_payload, params_payload, party_commitment, payload_digest,
+    report_commitment, report_payload, restricted_params_payload, rotate_payload,
+    tagged_params_payload, witnessing_payload, PayloadHasher, PromisorRequirement, Tag, ACK_DOMAIN,
+    AMEND_DOMAIN, BLIND_DOMAIN, BLIND_REPORT_DOMAIN, BOUNTY_DOMAIN, CANCEL_DOMAIN, CLAIM_DOMAIN,
+    COMMIT_DOMAIN, NOTARY_DOMAIN, RESTRICT_DOMAIN, ROTATE_DOMAIN, WITNESS_DOMAIN,
+};
+
+/// Off-chain storage key prefix of indexed liability reports.
+pub const OFFCHAIN_REPORT_PREFIX: &[u8] = b"robonomics::liability::report";
+
+/// Off-chain storage key of liability report with given index.
+pub fn offchain_report_key<Index: Encode>(index: &Index) -> Vec<u8> {
+    (OFFCHAIN_REPORT_PREFIX, index).encode()
+}
+
+/// Off-chain persistent storage key of IPFS gateway used by liability off-chain worker,
+/// raw UTF-8 URL. Liabilities aren't finalized by off-chain worker when it isn't set.
+pub const OFFCHAIN_GATEWAY_KEY: &[u8] = b"robonomics::liability::ipfs-gateway";
+
+/// Off-chain persistent storage key of lighthouse mode flag, market orders are matched
+/// by off-chain worker using local lighthouse keys when it's set.
+pub const OFFCHAIN_LIGHTHOUSE_KEY: &[u8] = b"robonomics::liability::lighthouse";
+
+/// Storage key of liability record holding report hash with given index, the same as
+/// `Liabilities` key of liability module.
+pub fn report_storage_key<Index: Encode>(index: &Index) -> Vec<u8> {
+    let mut key = sp_io::hashing::twox_128(b"Liability").to_vec();
+    key.extend_from_slice(&sp_io::hashing::twox_128(b"Liabilities"));
+    index.using_encoded(|index| {
+        key.extend_from_slice(&sp_io::hashing::blake2_128(index));
+        key.extend_from_slice(index);
+    });
+    key
+}
+
+/// Hash of technical report, key of report preimage kept by liability module.
+pub fn report_hash<TechnicalReport: Encode>(report: &TechnicalReport) -> H256 {
+    report.using_encoded(sp_io::hashing::blake2_256).into()
+}
+
+/// Hash of technical parameter, liability module events carry it instead of parameter itself.
+pub fn technics_hash<TechnicalParam: Encode>(technics: &TechnicalParam) -> H256 {
+    technics.using_encoded(sp_io::hashing::blake2_256).into()
+}
+
+/// Content-derived liability id, it doesn't depend on chain-local index so it's the same
+/// on every network. Nonce is count of liabilities created for promisor before this one.
+pub fn liability_id<AccountId, TechnicalParam, EconomicalParam, Nonce>(
+    promisee: &AccountId,
+    promisor: &AccountId,
+    technics: &TechnicalParam,
+    economics: &EconomicalParam,
+    nonce: &Nonce,
+) -> H256
+where
+    AccountId: Encode,
+    TechnicalParam: Encode,
+    EconomicalParam: Encode,
+    Nonce: Encode,
+{
+    (promisee, promisor, technics, economics, nonce)
+        .using_encoded(sp_io::hashing::blake2_256)
+        .into()
+}
+
+/// Commitment to match of market orders: hash of encoded orders ids, matcher account and
+/// salt, so commitment of one account couldn't be revealed by another one.
+pub fn match_commitment<OrderId: Encode, AccountId: Encode>(
+    demand_id: &OrderId,
+    offer_id: &OrderId,
+    matcher: &AccountId,
+    salt: &H256,
+) -> H256 {
+    sp_io::hashing::blake2_256(&(demand_id, offer_id, matcher, salt).encode()).into()
+}
+
+/// Commitment to vote of juror on disputed report of liability: hash of encoded liability
+/// index, juror account, vote and salt, so vote couldn't be copied before it's revealed.
+pub fn vote_commitment<Index: Encode, AccountId: Encode>(
+    index: &Index,
+    juror: &AccountId,
+    uphold: bool,
+    salt: &H256,
+) -> H256 {
+    sp_io::hashing::blake2_256(&(index, juror, uphold, salt).encode()).into()
+}
+
+/// Liability report proof verification error.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum ReportProofError {
+    /// Proof nodes don't match the state root.
+    InvalidProof,
+    /// Proven value isn't a report hash.
+    DecodeFailure,
+}
+
+/// Check storage read proof of liability report hash against block state root.
+///
+/// Returns `None` when proof shows that report with given index doesn't exist. Hash is
+/// decoded from the leading slot of proven `LiabilityRecord`, so agreement type isn't needed;
+/// report bytes are checked against it with `report_hash`.
+pub fn verify_report_proof<H, Index, ReportHash>(
+    root: &H::Out,
+    proof: Vec<Vec<u8>>,
+    index: &Index,
+) -> Result<Option<ReportHash>, ReportProofError>
+where
+    H: Hasher,
+    Index: Encode,
+    ReportHash: Decode,
+{
+    let mut db = MemoryDB::<H>::default();
+    for node in proof {
+        db.insert(EMPTY_PREFIX, &node);
+    }
+    let value = read_trie_value::<Layout<H>, _>(&db, root, &report_storage_key(index))
+        .map_err(|_| ReportProofError::InvalidProof)?;
+    value
+        .map(|encoded| Option::<ReportHash>::decode(&mut &encoded[..]))
+        .transpose()
+        .map(Option::flatten)
+        .map_err(|_| ReportProofError::DecodeFailure)
+}
+
+/// Reason of unsigned liability call rejected by transaction pool, it's reported as
+/// `InvalidTransaction::Custom` code. Codes are stable between releases: new reasons are
+/// appended, codes of removed ones aren't reused.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[repr(u8)]
+pub enum ValidityError {
+    /// Promisee proof doesn't match the call.
+    BadPromiseeProof = 1,
+    /// Promisor proof doesn't match the call.
+    BadPromisorProof = 2,
+    /// Proofs or window of the call expired, e.g. expiring parameters or reveal window.
+    Expired = 3,
+    /// Agreement is already created for current nonce of promisor.
+    NonceMismatch = 4,
+    /// Unsigned calls of the block or its parties exceed rate limits.
+    RateLimited = 5,
+    /// Report proof doesn't match the call.
+    BadReportProof = 6,
+    /// Liability is already finalized, or the step of the call is already taken.
+    AlreadySettled = 7,
+    /// Liability of the call doesn't exist.
+    UnknownLiability = 8,
+    /// Party isn't permitted to make liability, e.g. blacklisted or unapproved promisor.
+    NotPermitted = 9,
+    /// Revealed report doesn't match its commitment.
+    CommitmentMismatch = 10,
+    /// Report preimage of the call isn't noted.
+    UnknownPreimage = 11,
+    /// Liability of the call is pruned, its index is never reused.
+    Pruned = 12,
+}
+
+impl ValidityError {
+    /// Custom validity code of the reason.
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+
+    /// Reason of given custom validity code, if any.
+    pub fn from_code(code: u8) -> Option<Self> {
+        Some(match code {
+            1 => ValidityError::BadPromiseeProof,
+            2 => ValidityError::BadPromisorProof,
+            3 => ValidityError::Expired,
+            4 => ValidityError::NonceMismatch,
+            5 => ValidityError::RateLimited,
+            6 => ValidityError::BadReportProof,
+            7 => ValidityError::AlreadySettled,
+            8 => ValidityError::UnknownLiability,
+            9 => ValidityError::NotPermitted,
+            10 => ValidityError::CommitmentMismatch,
+            11 => ValidityError::UnknownPreimage,
+            12 => ValidityError::Pruned,
+            _ => return None,
+        })
+    }
+}
+
+impl From<ValidityError> for InvalidTransaction {
+    fn from(error: ValidityError) -> Self {
+        InvalidTransaction::Custom(error.code())
+    }
+}
+
+impl From<ValidityError> for TransactionValidityError {
+    fn from(error: ValidityError) -> Self {
+        TransactionValidityError::Invalid(error.into())
+    }
+}
+
+impl From<ValidityError> for TransactionValidity {
+    fn from(error: ValidityError) -> Self {
+        Err(error.into())
+    }
+}
+
+/// Finalization state of stored liability.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum RecordState {
+    /// Report isn't published yet.
+    Open,
+    /// Report published by promisor.
+    Reported,
+    /// Finalized by root without report with given economical outcome.
+    Forced(bool),
+    /// Cancelled before report, e.g. by parties or when it wasn't funded before deadline.
+    Cancelled,
+    /// Imported finalized from legacy Ethereum contracts with given economical outcome.
+    Imported(bool),
+}
+
+/// Liability with its lifecycle kept in single storage value.
+///
+/// Report hash is the leading field, light clients decode it from record prefix. Report
+/// itself is kept as preimage of the hash.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct LiabilityRecord<Agreement, ReportHash, BlockNumber> {
+    /// Hash of technical report of complete works, `None` until it's published.
+    pub report_hash: Option<ReportHash>,
+    /// Finalization state.
+    pub state: RecordState,
+    /// Block when liability created, zero for liabilities created before it was tracked.
+    pub created_at: BlockNumber,
+    /// Block when liability finalized, zero for liabilities finalized before it was tracked.
+    pub finalized_at: Option<BlockNumber>,
+    /// Agreement of liability parties.
+    pub agreement: Agreement,
+}
+
+/// Liability lifecycle state.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum LiabilityState {
+    /// Liability created, report isn't published yet.
+    Open,
+    /// Liability report published.
+    Finalized,
+    /// Finalized liability imported from legacy Ethereum contracts, it's read-only.
+    Imported,
+}
+
+/// Economical terms of liability decoded by runtime, they're read the same way whatever
+/// economical parameter type is.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct EconomicsSummary<Balance> {
+    /// Value of monetary economics, `None` for non-monetary economics.
+    pub value: Option<Balance>,
+    /// Value counted as settled when liability is finalized successfully.
+    pub settled: Balance,
+    /// Market of liability.
+    pub market: u32,
+    /// Escrow is reserved when liability is funded by promisee, not at creation.
+    pub deferred_funding: bool,
+}
+
+/// Decoded liability view.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct LiabilityInfo<TechnicalParam, EconomicalParam, AccountId, BlockNumber, Balance> {
+    /// Technical parameter of agreement.
+    pub technics: TechnicalParam,
+    /// Hash of technical parameter, see `technics_hash`.
+    pub technics_hash: H256,
+    /// Economical parameter of agreement.
+    pub economics: EconomicalParam,
+    /// Economical terms of agreement.
+    pub economics_summary: EconomicsSummary<Balance>,
+    /// Party that pays for the work.
+    pub promisee: AccountId,
+    /// Party that executes the work.
+    pub promisor: AccountId,
+    /// Current lifecycle state.
+    pub state: LiabilityState,
+    /// Block when liability created, zero for liabilities created before it was tracked.
+    pub created_at: BlockNumber,
+    /// Block when liability report published.
+    pub finalized_at: Option<BlockNumber>,
+}
+
+/// Decoded liability report view.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct ReportInfo<TechnicalReport, BlockNumber> {
+    /// Technical report of complete works.
+    pub report: TechnicalReport,
+    /// Block when report published.
+    pub finalized_at: BlockNumber,
+}
+
+/// Blocks that liability deadlines are enforced at, `None` when liability isn't subject
+/// to the rule, e.g. it was created before the rule existed or its step is already passed.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct LiabilityTimeline<BlockNumber> {
+    /// Block when liability created, zero sentinel as in `LiabilityInfo`.
+    pub created_at: BlockNumber,
+    /// The last block promisee could fund deferred funding liability in.
+    pub funding_deadline: Option<BlockNumber>,
+    /// The last block promisor could acknowledge liability in, promisee could reclaim it after.
+    pub acknowledgement_deadline: Option<BlockNumber>,
+    /// The last block promisor could reveal committed report in.
+    pub reveal_deadline: Option<BlockNumber>,
+    /// The last block of challenge window of witnessed report.
+    pub challenge_end: Option<BlockNumber>,
+    /// The last block liability could stay open in, any account could expire it after.
+    pub lifetime_end: Option<BlockNumber>,
+}
+
+/// Liability execution cost estimation.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct CostInfo<EconomicalParam> {
+    /// Weight of liability `create` call.
+    pub create_weight: u64,
+    /// Weight of liability `finalize` call.
+    pub finalize_weight: u64,
+    /// Promisee funds locked during liability execution.
+    pub escrow: EconomicalParam,
+}
+
+/// Configuration of liability module, clients check calls against it before signing.
+///
+/// Markets configured by governance override deposits and windows of their liabilities.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+pub struct LiabilityConstants<BlockNumber, Balance> {
+    /// Maximal count of unsigned `create` and `finalize` calls in a block.
+    pub max_unsigned_per_block: u32,
+    /// Maximal count of liabilities created in a block by the same promisee and promisor.
+    pub max_pair_per_block: u32,
+    /// Maximal encoded size in bytes of technical parameter.
+    pub max_technics_length: u32,
+    /// Maximal encoded size in bytes of liability report.
+    pub max_report_length: u32,
+    /// Maximal count of tags of liability.
+    pub max_tags: u32,
+    /// Count of the latest liabilities indexed by each tag.
+    pub max_tagged_liabilities: u32,
+    /// Only approved promisors could take new liabilities.
+    pub promisor_whitelist: bool,
+    /// Liability value above which promisee should have verified identity.
+    pub identity_threshold: Balance,
+    /// Minimal age in blocks of liability that could be force finalized.
+    pub min_force_finalize_age: BlockNumber,
+    /// Base deposit of market order.
+    pub order_deposit: Balance,
+    /// Maximal count of open orders posted by account.
+    pub max_open_orders_per_account: u32,
+    /// Maximal count of open orders of each side of market book.
+    pub max_orders_per_market: u32,
+    /// Minimal quantity of market order fill.
+    pub min_fill_quantity: u32,
+    /// Share of order deposit slashed when order expires.
+    pub expired_order_slash: Perbill,
+    /// Deposit of noted report preimage.
+    pub report_preimage_deposit: Balance,
+    /// Count of blocks liability report could be disputed.
+    pub report_challenge_window: BlockNumber,
+    /// Count of the latest matches kept for each market.
+    pub max_recent_matches: u32,
+    /// Minimal stake of lighthouse.
+    pub lighthouse_min_stake: Balance,
+    /// Maximal count of lighthouses of market.
+    pub max_lighthouses: u32,
+    /// Count of blocks each lighthouse of market is scheduled for.
+    pub lighthouse_slot: BlockNumber,
+    /// Share of market fee paid to matching lighthouse.
+    pub lighthouse_fee_share: Perbill,
+    /// Count of blocks match commitment could be revealed in.
+    pub match_commit_window: BlockNumber,
+    /// Maximal count of match commitments of account waiting for reveal.
+    pub max_match_commits: u32,
+    /// Count of blocks after signed order expiry its breach could be claimed.
+    pub breach_claim_period: BlockNumber,
+    /// Funds slashed from signed orders bond of reneging party.
+    pub order_breach_slash: Balance,
+    /// Count of blocks promisee has to fund deferred funding liability.
+    pub funding_deadline: BlockNumber,
+    /// Count of blocks promisor has to reveal committed report.
+    pub reveal_window: BlockNumber,
+    /// Maximal distance between claimed report arrival and its inclusion block.
+    pub claim_tolerance: BlockNumber,
+    /// Minimal stake of report witness.
+    pub witness_min_stake: Balance,
+    /// Maximal count of registered witnesses.
+    pub max_witnesses: u32,
+    /// Funds slashed from witness that notarized overturned report.
+    pub witness_slash: Balance,
+    /// Count of blocks of statistics era, zero disables era snapshots.
+    pub stats_era_length: BlockNumber,
+    /// Count of the last eras statistics snapshots are kept for.
+    pub stats_retention: u32,
+    /// Minimal stake of promisor joining assignment pool of market.
+    pub pool_min_stake: Balance,
+    /// Maximal count of promisors in assignment pool of market.
+    pub max_pool_size: u32,
+    /// Count of blocks between assignment request and promisor selection.
+    pub assignment_delay: BlockNumber,
+    /// Count of blocks promisor has to acknowledge new liability in, zero disables it.
+    pub acknowledgement_window: BlockNumber,
+    /// Deposit reserved from promisor until liability is acknowledged.
+    pub acknowledgement_deposit: Balance,
+    /// Count of blocks liability could stay open after creation, zero disables expiry.
+    pub max_liability_lifetime: BlockNumber,
+    /// Part of promisor acknowledgement deposit paid to caller of liability expiry.
+    pub expiry_incentive: Balance,
+    /// Count of bits of accounts filter of block digest, zero when it isn't deposited.
+    pub digest_bloom_bits: u32,
+    /// Count of hashes of account in accounts filter of block digest.
+    pub digest_bloom_hashes: u8,
+    /// Minimal age in blocks of liability that could be reported.
+    pub min_report_delay: BlockNumber,
+    /// Deposit reserved from promisee of zero value liability until it's closed.
+    pub zero_value_deposit: Balance,
+    /// Maximal count of zero value liabilities of promisee per statistics era, zero when
+    /// it isn't limited.
+    pub max_zero_value_per_era: u32,
+}
+
+/// Liability module event decoded from block events.
+///
+/// Variable-length fields are committed to by hash, see `event_payload` runtime API.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum LiabilityEvent<Index, EconomicalParam, AccountId, BlockNumber> {
+    /// New liability created.
+    Created {
+        index: Index,
+        id: H256,
+        technics: H256,
+        economics: EconomicalParam,
+        promisee: AccountId,
+        promisor: AccountId,
+        block_number: BlockNumber,
+        tags: Vec<Tag>,
+    },
+    /// Liability report published.
+    Reported {
+        index: Index,
+        report: H256,
+        block_number: BlockNumber,
+    },
+}
+
+/// Variable-length liability field resolved from hash carried by event.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum EventPayload<TechnicalParam, TechnicalReport> {
+    /// Technical parameter of liability.
+    Technics(TechnicalParam),
+    /// Technical report of liability.
+    Report(TechnicalReport),
+}
+
+/// Account that submitted liability transaction, it's kept for attribution of disputes.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum Submitter<AccountId> {
+    /// Account that signed transaction.
+    Signed(AccountId),
+    /// Account named by submitter of unsigned transaction. It isn't authenticated, anyone
+    /// relaying the transaction could name any account, so it's never used to authorize.
+    Unauthenticated(AccountId),
+}
+
+/// Liability lifecycle transition recorded in liability history.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum Transition<AccountId> {
+    /// Liability created by agreement of both parties.
+    Created,
+    /// Liability report published by promisor.
+    Reported(AccountId),
+    /// Liability finalized by root without report, with given economical outcome.
+    ForceFinalized(bool),
+    /// Escrow of deferred funding liability reserved by promisee.
+    Funded,
+    /// Liability cancelled before report.
+    Cancelled,
+    /// Commitment to liability report published by promisor, report is revealed later.
+    Committed(H256),
+    /// Liability acknowledged by promisor in acknowledgement window.
+    Acknowledged,
+    /// Liability economics amended by both parties, with count of amendments so far.
+    Amended(u32),
+    /// Liability report of the preceding transition submitted by given account.
+    Submitted(Submitter<AccountId>),
+    /// Promisor key of liability rotated to given account by robot owner.
+    Rotated(AccountId),
+    /// Finalized liability imported from legacy Ethereum contract with given address.
+    Imported(H160),
+}
+
+/// Network-level liability statistics.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+pub struct LiabilityCounters<Balance> {
+    /// Total count of created liabilities.
+    pub created: u64,
+    /// Total count of finalized liabilities.
+    pub finalized: u64,
+    /// Total count of cancelled liabilities.
+    pub cancelled: u64,
+    /// Total value of escrow settled by finalized liabilities.
+    pub settled: Balance,
+}
+
+/// Count of finalized liabilities of account by outcome.
+#[derive(Encode, Decode, Clone, Copy, Default, PartialEq, Eq, RuntimeDebug)]
+pub struct OutcomeCounts {
+    /// Count of successfully finalized liabilities.
+    pub completed: u32,
+    /// Count of failed liabilities.
+    pub failed: u32,
+    /// Count of liabilities force finalized as failed.
+    pub disputed: u32,
+}
+
+/// Execution statistics of technics model, e.g. behavioral model of robot.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+pub struct ModelStats<Balance> {
+    /// Count of started liabilities of model.
+    pub executions: u32,
+    /// Outcomes of finalized liabilities of model.
+    pub outcomes: OutcomeCounts,
+    /// Total value settled by successfully finalized liabilities of model.
+    pub settled: Balance,
+}
+
+/// Ranking of technics models leaderboard.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum ModelRanking {
+    /// Count of started liabilities.
+    Executions,
+    /// Count of successfully finalized liabilities.
+    Completed,
+    /// Share of successfully finalized liabilities among finalized ones, in billionths.
+    SuccessRate,
+    /// Total settled value.
+    Settled,
+}
+
+impl ModelRanking {
+    /// Rankings models are placed at.
+    pub const ALL: [ModelRanking; 4] = [
+        ModelRanking::Executions,
+        ModelRanking::Completed,
+        ModelRanking::SuccessRate,
+        ModelRanking::Settled,
+    ];
+}
+
+/// Liability statistics of account.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+pub struct AccountStats<Balance> {
+    /// Outcomes of liabilities where account is promisor.
+    pub as_promisor: OutcomeCounts,
+    /// Outcomes of liabilities where account is promisee.
+    pub as_promisee: OutcomeCounts,
+    /// Total value settled by successfully finalized liabilities of account.
+    pub settled: Balance,
+}
+
+/// Page of liabilities created within a block range.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct CreatedBetween<Index, BlockNumber> {
+    /// Indices of liabilities in creation order.
+    pub indices: Vec<Index>,
+    /// Set when beginning of the range is out of retention window and already pruned.
+    pub truncated: bool,
+    /// First block of the next page, if any.
+    pub next: Option<BlockNumber>,
+}
+
+/// Maximal count of items returned by single call of list runtime APIs, larger requests
+/// are cut to it.
+pub const MAX_PAGE_ITEMS: u32 = 100;
+
+/// Maximal count of entries scanned by single call of list runtime APIs, page is cut
+/// earlier when it skips pruned entries.
+pub const MAX_PAGE_SCAN: u32 = 1_000;
+
+/// Page of list runtime API.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Page<Cursor, Item> {
+    /// Items of the page, at most `MAX_PAGE_ITEMS` ones.
+    pub items: Vec<Item>,
+    /// Start of the next page. Pruned entries are skipped, so cursor stays valid, and
+    /// the last page cursor lists entries appended later.
+    pub next: Cursor,
+    /// Set when entries after the page exist at the moment of call.
+    pub has_more: bool,
+}
+
+/// Market order at the top of order book side.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct OrderBookEntry<Balance> {
+    /// Market order id.
+    pub order_id: u64,
+    /// Settled value of order economics.
+    pub price: Balance,
+}
+
+/// Orders of order book side with the same price.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct PriceLevel<Balance> {
+    /// Settled value of orders economics.
+    pub price: Balance,
+    /// Count of open orders.
+    pub orders: u32,
+}
+
+/// Price levels of market order book, the best first.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct OrderBookDepth<Balance> {
+    /// Price levels of promisee demands.
+    pub asks: Vec<PriceLevel<Balance>>,
+    /// Price levels of promisor offers.
+    pub bids: Vec<PriceLevel<Balance>>,
+}
+
+/// Match of market demand and offer.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct MatchInfo<Index, BlockNumber, Balance> {
+    /// Matched demand id.
+    pub demand_id: u64,
+    /// Matched offer id.
+    pub offer_id: u64,
+    /// Index of liability created by match.
+    pub index: Index,
+    /// Settled value of orders single unit economics.
+    pub price: Balance,
+    /// Count of units filled by match.
+    pub quantity: u32,
+    /// Settled value of liability.
+    pub value: Balance,
+    /// Block of match.
+    pub block_number: BlockNumber,
+}
+
+/// Matches of market within block window.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct MarketVolume<BlockNumber, Balance> {
+    /// First block of window.
+    pub from: BlockNumber,
+    /// Count of matches.
+    pub matches: u32,
+    /// Count of filled units.
+    pub quantity: u64,
+    /// Settled value of created liabilities.
+    pub value: Balance,
+    /// Window starts before the oldest retained match, so older matches aren't counted.
+    pub truncated: bool,
+}
+
+/// Running totals of matches of all markets.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct MarketTotals<Balance> {
+    /// Count of matches.
+    pub matches: u64,
+    /// Count of filled units.
+    pub quantity: u64,
+    /// Settled value of created liabilities.
+    pub value: Balance,
+    /// Market fees paid by promisees, lighthouse shares included.
+    pub fees: Balance,
+}
+
+/// Running totals of liability statistics at the end of era, statistics of the era itself
+/// are the difference to the previous era snapshot.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct EraStats<BlockNumber, Balance> {
+    /// The last block of era.
+    pub until: BlockNumber,
+    /// Network-level liability statistics.
+    pub counters: LiabilityCounters<Balance>,
+    /// Outcomes of finalized liabilities that promisors reputation is built from.
+    pub outcomes: OutcomeCounts,
+    /// Matches and fees of all markets.
+    pub market: MarketTotals<Balance>,
+}
+
+/// Track record of liability promisor.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct ReputationInfo {
+    /// Bounded reputation score.
+    pub score: u32,
+    /// Count of successfully finalized liabilities.
+    pub completed: u32,
+    /// Count of failed liabilities.
+    pub failed: u32,
+    /// Count of liabilities force finalized as failed, i.e. lost disputes.
+    pub disputed: u32,
+    /// Count of promisee ratings.
+    pub ratings: u32,
+    /// Sum of promisee ratings, average rating is `rating_sum / ratings`.
+    pub rating_sum: u64,
+    /// Era of the last score update, score decays from it.
+    pub era: u32,
+}
+
+/// Sequential identifier of robot registry entry.
+pub type RobotId = u64;
+
+/// Set of capability ids, e.g. robot hardware declared in registry or capabilities required
+/// by job. Capability `id` is bit `id % 8` of byte `id / 8`, missing and trailing zero bytes
+/// are absent capabilities, so new ids don't change encoding of sets made before.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct Capabilities(pub Vec<u8>);
+
+impl Capabilities {
+    /// Set of given capability ids.
+    pub fn from_ids(ids: &[u16]) -> Self {
+        let mut bits = Vec::new();
+        for id in ids {
+            let byte = (*id / 8) as usize;
+            if bits.len() <= byte {
+                bits.resize(byte + 1, 0);
+            }
+            bits[byte] |= 1 << (*id % 8);
+        }
+        Capabilities(bits)
+    }
+
+    /// Check that capability is in the set.
+    pub fn contains(&self, id: u16) -> bool {
+        self.0
+            .get((id / 8) as usize)
+            .map_or(false, |byte| byte & (1 << (id % 8)) != 0)
+    }
+
+    /// Check that set has no capabilities.
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|byte| *byte == 0)
+    }
+
+    /// Check that each capability of `required` is in the set.
+    pub fn covers(&self, required: &Capabilities) -> bool {
+        required.0.iter().enumerate().all(|(i, required)| {
+            let declared = self.0.get(i).copied().unwrap_or_default();
+            required & !declared == 0
+        })
+    }
+}
+
+/// Robot registry entry: identity of robot account.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct RobotInfo<AccountId, Hash, Balance> {
+    /// Robot account, e.g. liability promisor.
+    pub robot: AccountId,
+    /// Robot owner, it registered the robot.
+    pub owner: AccountId,
+    /// Serial number of robot, unique among robots of owner.
+    pub serial: Vec<u8>,
+    /// Hash of robot metadata, e.g. IPFS hash of model description.
+    pub metadata: Hash,
+    /// Deposit reserved from owner until robot is deregistered.
+    pub deposit: Balance,
+}
+
+/// Outcome of liability call dry run: liability the call would create or finalize and
+/// weight it would take.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct DryRun<Index> {
+    /// Index of liability.
+    pub index: Index,
+    /// Dispatch weight of the call.
+    pub weight: u64,
+}
+
+/// Funds moved by finalization of liability with given outcome, computed without moving them.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Payouts<AccountId, Balance> {
+    /// Funds transferred from the first account to the second one, in order of transfer.
+    pub transfers: Vec<(AccountId, AccountId, Balance)>,
+    /// Reserved funds released back to account.
+    pub refunds: Vec<(AccountId, Balance)>,
+}
+
+impl<AccountId, Balance> Default for Payouts<AccountId, Balance> {
+    fn default() -> Self {
+        Payouts {
+            transfers: Vec::new(),
+            refunds: Vec::new(),
+        }
+    }
+}
+
+sp_api::decl_runtime_apis! {
+    /// Read access to liabilities stored on-chain.
+    pub trait LiabilityApi<Index, TechnicalParam, EconomicalParam, TechnicalReport, AccountId, BlockNumber, Balance> where
+        Index: Codec,
+        TechnicalParam: Codec,
+        EconomicalParam: Codec,
+        TechnicalReport: Codec,
+        AccountId: Codec,
+        BlockNumber: Codec,
+        Balance: Codec,
+    {
+        /// Get liability by index.
+        fn get_liability(index: Index) -> Option<LiabilityInfo<TechnicalParam, EconomicalParam, AccountId, BlockNumber, Balance>>;
+
+        /// Get liability report by index.
+        fn get_report(index: Index) -> Option<ReportInfo<TechnicalReport, BlockNumber>>;
+
+        /// List page of at most `count` liabilities starting from `start` index.
+        fn list(start: Index, count: u32) -> Page<Index, (Index, LiabilityInfo<TechnicalParam, EconomicalParam, AccountId, BlockNumber, Balance>)>;
+
+        /// List page of at most `count` liabilities of given account (as promisee or promisor)
+        /// with index not less than `start`.
+        fn list_by_account(account: AccountId, start: Index, count: u32) -> Page<Index, (Index, LiabilityInfo<TechnicalParam, EconomicalParam, AccountId, BlockNumber, Balance>)>;
+
+        /// List page of at most `count` liabilities of given promisor with local index not
+        /// less than `start`, page cursor is local index too.
+        fn list_by_promisor(promisor: AccountId, start: Index, count: u32) -> Page<Index, (Index, LiabilityInfo<TechnicalParam, EconomicalParam, AccountId, BlockNumber, Balance>)>;
+
+        /// List page of at most `count` of the latest liabilities with given tag with index
+        /// not less than `start`, older ones are pruned from tag index.
+        fn list_by_tag(tag: Tag, start: Index, count: u32) -> Page<Index, (Index, LiabilityInfo<TechnicalParam, EconomicalParam, AccountId, BlockNumber, Balance>)>;
+
+        /// Configuration of liability module.
+        fn constants() -> LiabilityConstants<BlockNumber, Balance>;
+
+        /// Estimate cost of liability with given economics.
+        fn estimate_cost(economics: EconomicalParam) -> CostInfo<EconomicalParam>;
+
+        /// List liabilities created in blocks from `from` to `to` inclusive. Page is finished on
+        /// block boundary when it has at least `count` indices or `MAX_PAGE_SCAN` blocks.
+        fn list_created_between(from: BlockNumber, to: BlockNumber, count: u32) -> CreatedBetween<Index, BlockNumber>;
+
+        /// Lifecycle transitions of liability with given index, oldest first.
+        fn history(index: Index) -> Vec<(BlockNumber, Transition<AccountId>)>;
+
+        /// Network-level liability statistics.
+        fn counters() -> LiabilityCounters<Balance>;
+
+        /// Liability statistics of given account.
+        fn account_stats(account: AccountId) -> AccountStats<Balance>;
+
+        /// Liability module events deposited in the block.
+        fn block_events() -> Vec<LiabilityEvent<Index, EconomicalParam, AccountId, BlockNumber>>;
+
+        /// Technics or report of liability with given index committed to by `hash` in its events,
+        /// `None` when hash doesn't match or report preimage is pruned.
+        fn event_payload(index: Index, hash: H256) -> Option<EventPayload<TechnicalParam, TechnicalReport>>;
+
+        /// Best demand of market, it has the highest price.
+        fn best_ask(market: u32) -> Option<OrderBookEntry<Balance>>;
+
+        /// Best offer of market, it has the lowest price.
+        fn best_bid(market: u32) -> Option<OrderBookEntry<Balance>>;
+
+        /// At most `levels` best price levels of market demands and offers.
+        fn depth(market: u32, levels: u32) -> OrderBookDepth<Balance>;
+
+        /// At most `count` recent matches of market, the latest first.
+        fn recent_matches(market: u32, count: u32) -> Vec<MatchInfo<Index, BlockNumber, Balance>>;
+
+        /// Matches of market in the last `blocks` blocks including the current one.
+        fn volume(market: u32, blocks: BlockNumber) -> MarketVolume<BlockNumber, Balance>;
+
+        /// Statistics snapshot at the end of given era, `None` when era isn't ended yet or
+        /// its snapshot is out of retention.
+        fn era_stats(era: u32) -> Option<EraStats<BlockNumber, Balance>>;
+
+        /// Deadlines of liability with given index computed from its state and module
+        /// configuration.
+        fn timeline(index: Index) -> Option<LiabilityTimeline<BlockNumber>>;
+
+        /// Leaf of finalized liability with given index and its inclusion proof against
+        /// the current root of finalizations range.
+        fn finalization_proof(index: Index) -> Option<(FinalizationLeaf<Index, AccountId>, MmrProof)>;
+
+        /// Funds moved when open liability with given index is finalized with given outcome,
+        /// `None` when it couldn't be finalized yet.
+        fn preview(index: Index, success: bool) -> Option<Payouts<AccountId, Balance>>;
+
+        /// Execution statistics of technics model with given hash.
+        fn model_stats(model: H256) -> ModelStats<Balance>;
+
+        /// At most `count` the best technics models by given ranking, the highest first.
+        fn top_models(by: ModelRanking, count: u32) -> Vec<(H256, ModelStats<Balance>)>;
+
+        /// Address of legacy Ethereum contract of imported liability with given index.
+        fn imported_from(index: Index) -> Option<H160>;
+    }
+
+    /// Checks of unsigned liability calls before broadcast: calls are dispatched as in block
+    /// and their changes are discarded.
+    pub trait LiabilityDryRunApi<Index, TechnicalParam, EconomicalParam, TechnicalReport, AccountId, Proof> where
+        Index: Codec,
+        TechnicalParam: Codec,
+        EconomicalParam: Codec,
+        TechnicalReport: Codec,
+        AccountId: Codec,
+        Proof: Codec,
+    {
+        /// Dry run of `create` call, its proofs, reserves and rate limits are checked.
+        fn dry_run_create(
+            technics: TechnicalParam,
+            economics: EconomicalParam,
+            promisee: AccountId,
+            promisor: AccountId,
+            promisee_proof: Proof,
+            promisor_proof: Proof,
+        ) -> Result<DryRun<Index>, DispatchError>;
+
+        /// Dry run of `finalize` call, its proof, settlement and rate limits are checked.
+        fn dry_run_finalize(index: Index, report: TechnicalReport, proof: Proof) -> Result<DryRun<Index>, DispatchError>;
+    }
+
+    /// Read access to reputation of liability promisors.
+    pub trait ReputationApi<AccountId> where
+        AccountId: Codec,
+    {
+        /// Reputation of given promisor account.
+        fn reputation_of(account: AccountId) -> ReputationInfo;
+    }
+
+    /// Read access to robot registry.
+    pub trait RobotRegistryApi<AccountId, Hash, Balance> where
+        AccountId: Codec,
+        Hash: Codec,
+        Balance: Codec,
+    {
+        /// Registry entry of robot with given owner and serial number.
+        fn robot_by_serial(owner: AccountId, serial: Vec<u8>) -> Option<(RobotId, RobotInfo<AccountId, Hash, Balance>)>;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sp_core::{crypto::AccountId32, crypto::Ss58Codec, Blake2Hasher};
+    use sp_trie::{TrieDBMut, TrieMut};
+
+    #[test]
+    fn test_capabilities() {
+        let declared = Capabilities::from_ids(&[0, 3, 9]);
+        assert_eq!(declared, Capabilities(vec![0b1001, 0b10]));
+        assert!(declared.contains(9));
+        assert!(!declared.contains(8));
+        assert!(!declared.contains(200));
+
+        assert!(declared.covers(&Capabilities::default()));
+        assert!(declared.covers(&Capabilities::from_ids(&[3, 9])));
+        assert!(!declared.covers(&Capabilities::from_ids(&[3, 10])));
+        assert!(!declared.covers(&Capabilities::from_ids(&[100])));
+
+        // Trailing zero bytes are absent capabilities of newer ids
+        assert!(declared.covers(&Capabilities(vec![0b1, 0, 0, 0])));
+        assert!(Capabilities(vec![0b1]).covers(&Capabilities(vec![0b1, 0])));
+        assert!(Capabilities(vec![0, 0]).is_empty());
+        assert!(!declared.is_empty());
+    }
+
+    #[test]
+    fn test_liability_info_serde() {
+        let info = LiabilityInfo {
+            technics: H256::repeat_byte(0xab),
+            technics_hash: H256::repeat_byte(0xcd),
+            economics: 42u128,
+            economics_summary: EconomicsSummary {
+                value: Some(42u64),
+                settled: 42,
+                market: 3,
+                deferred_funding: false,
+            },
+            promisee: AccountId32::from([1; 32]),
+            promisor: AccountId32::from([2; 32]),
+            state: LiabilityState::Finalized,
+            created_at: 10u32,
+            finalized_at: Some(12u32),
+        };
+        let json = serde_json::to_value(&info).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "technics": format!("0x{}", "ab".repeat(32)),
+                "technicsHash": format!("0x{}", "cd".repeat(32)),
+                "economics": 42,
+                "economicsSummary": {
+                    "value": 42,
+                    "settled": 42,
+                    "market": 3,
+                    "deferredFunding": false,
+                },
+                "promisee": info.promisee.to_ss58check(),
+                "promisor": info.promisor.to_ss58check(),
+                "state": "finalized",
+                "createdAt": 10,
+                "finalizedAt": 12,
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<LiabilityInfo<_, _, _, _, _>>(json).unwrap(),
+            info
+        );
+    }
+
+    fn report_trie(reports: &[(u64, Vec<u8>)]) -> (H256, Vec<Vec<u8>>) {
+        let mut db = MemoryDB::<Blake2Hasher>::default();
+        let mut root = H256::default();
+        {
+            let mut trie = TrieDBMut::<Layout<Blake2Hasher>>::new(&mut db, &mut root);
+            for (index, report) in reports {
+                let record = LiabilityRecord {
+                    report_hash: Some(report_hash(report)),
+                    state: RecordState::Reported,
+                    created_at: 1u32,
+                    finalized_at: Some(2u32),
+                    agreement: (H256::repeat_byte(1), 42u128),
+                };
+                trie.insert(&report_storage_key(index), &record.encode())
+                    .unwrap();
+            }
+            let open = LiabilityRecord::<_, H256, _> {
+                report_hash: None,
+                state: RecordState::Open,
+                created_at: 1u32,
+                finalized_at: None,
+                agreement: (),
+            };
+            trie.insert(&report_storage_key(&45u64), &open.encode())
+                .unwrap();
+            trie.insert(b":code", b"runtime").unwrap();
+        }
+        let proof = db
+            .drain()
+            .into_iter()
+            .filter(|(_, (_, rc))| *rc > 0)
+            .map(|(_, (node, _))| node)
+            .collect();
+        (root, proof)
+    }
+
+    #[test]
+    fn test_report_proof() {
+        let report = vec![0xde, 0xad];
+        let (root, proof) = report_trie(&[(42, report.clone()), (43, vec![0xbe, 0xef])]);
+        assert_eq!(
+            verify_report_proof::<Blake2Hasher, _, H256>(&root, proof.clone(), &42u64),
+            Ok(Some(report_hash(&report)))
+        );
+
+        // Missing index is proven to be absent
+        assert_eq!(
+            verify_report_proof::<Blake2Hasher, _, H256>(&root, proof.clone(), &44u64),
+            Ok(None)
+        );
+
+        // Open liability has no report yet
+        assert_eq!(
+            verify_report_proof::<Blake2Hasher, _, H256>(&root, proof.clone(), &45u64),
+            Ok(None)
+        );
+
+        // Proof isn't accepted with the wrong root
+        assert_eq!(
+            verify_report_proof::<Blake2Hasher, _, H256>(&H256::repeat_byte(1), proof, &42u64),
+            Err(ReportProofError::InvalidProof)
+        );
+
+        // Proof without nodes proves nothing
+        assert_eq!(
+            verify_report_proof::<Blake2Hasher, _, H256>(&root, Vec::new(), &42u64),
+            Err(ReportProofError::InvalidProof)
+        );
+    }
+
+    #[test]
+    fn test_report_info_serde() {
+        let report = ReportInfo {
+            report: H256::repeat_byte(0xcd),
+            finalized_at: 12u32,
+        };
+        let json = serde_json::to_string(&report).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ReportInfo<_, _>>(&json).unwrap(),
+            report
+        );
+    }
+
+    #[test]
+    fn test_validity_error_codes() {
+        // Codes are part of client interface, they never change
+        let codes = [
+            (ValidityError::BadPromiseeProof, 1),
+            (ValidityError::BadPromisorProof, 2),
+            (ValidityError::Expired, 3),
+            (ValidityError::NonceMismatch, 4),
+            (ValidityError::RateLimited, 5),
+            (ValidityError::BadReportProof, 6),
+            (ValidityError::AlreadySettled, 7),
+            (ValidityError::UnknownLiability, 8),
+            (ValidityError::NotPermitted, 9),
+            (ValidityError::CommitmentMismatch, 10),
+            (ValidityError::UnknownPreimage, 11),
+            (ValidityError::Pruned, 12),
+        ];
+        for (error, code) in codes.iter() {
+            assert_eq!(error.code(), *code);
+            assert_eq!(ValidityError::from_code(*code), Some(*error));
+            assert_eq!(
+                InvalidTransaction::from(*error),
+                InvalidTransaction::Custom(*code)
+            );
+        }
+        assert_eq!(ValidityError::from_code(0), None);
+        assert_eq!(ValidityError::from_code(13), None);
+        assert_eq!(
+            TransactionValidity::from(ValidityError::RateLimited),
+            Err(TransactionValidityError::Invalid(
+                InvalidTransaction::Custom(5)
+            ))
+        );
+    }
+}
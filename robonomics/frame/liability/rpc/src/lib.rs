@@ -0,0 +1,1348 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! RPC interface for the liability module.
+
+use codec::{Codec, Decode, Encode};
+use futures::{
+    channel::mpsc, compat::Compat, future, FutureExt, StreamExt, TryFutureExt, TryStreamExt,
+};
+use jsonrpc_core::futures::{
+    future::Future as Future01, sink::Sink as Sink01, stream::Stream as Stream01,
+};
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{manager::SubscriptionManager, typed::Subscriber, SubscriptionId};
+use log::warn;
+use sc_client_api::{BlockchainEvents, ProofProvider};
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{
+    crypto::Ss58Codec,
+    offchain::{OffchainStorage, STORAGE_PREFIX},
+    Bytes, H256,
+};
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use std::sync::Arc;
+
+pub use pallet_robonomics_liability_rpc_runtime_api::LiabilityApi as LiabilityRuntimeApi;
+use pallet_robonomics_liability_rpc_runtime_api::{
+    offchain_report_key, report_storage_key, AccountStats, CostInfo, EconomicsSummary,
+    LiabilityCounters, LiabilityEvent, LiabilityInfo, LiabilityState, MarketVolume, MatchInfo,
+    OrderBookDepth, OrderBookEntry, OutcomeCounts, PriceLevel, Tag,
+};
+
+/// Runtime API call failed.
+const RUNTIME_ERROR: i64 = 1;
+
+/// Storage proof generation failed.
+const PROOF_ERROR: i64 = 2;
+
+/// Count of notifications buffered for single subscriber, slower subscribers are dropped.
+const EVENTS_BUFFER_SIZE: usize = 1024;
+
+/// Liability lifecycle state.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcLiabilityState {
+    /// Liability created, report isn't published yet.
+    Open,
+    /// Liability report published.
+    Finalized,
+    /// Finalized liability imported from legacy Ethereum contracts.
+    Imported,
+}
+
+impl From<LiabilityState> for RpcLiabilityState {
+    fn from(state: LiabilityState) -> Self {
+        match state {
+            LiabilityState::Open => RpcLiabilityState::Open,
+            LiabilityState::Finalized => RpcLiabilityState::Finalized,
+            LiabilityState::Imported => RpcLiabilityState::Imported,
+        }
+    }
+}
+
+/// Liability JSON view: hex encoded technics and SS58 encoded accounts.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcLiabilityInfo<Index, EconomicalParam, BlockNumber, Balance> {
+    /// Liability index.
+    pub index: Index,
+    /// Technical parameter of agreement.
+    pub technics: Bytes,
+    /// Hash of technical parameter.
+    pub technics_hash: H256,
+    /// Economical parameter of agreement.
+    pub economics: EconomicalParam,
+    /// Economical terms of agreement.
+    pub economics_summary: EconomicsSummary<Balance>,
+    /// SS58 address of party that pays for the work.
+    pub promisee: String,
+    /// SS58 address of party that executes the work.
+    pub promisor: String,
+    /// Current lifecycle state.
+    pub state: RpcLiabilityState,
+    /// Block when liability created.
+    pub created_at: BlockNumber,
+    /// Block when liability report published.
+    pub finalized_at: Option<BlockNumber>,
+}
+
+impl<Index, EconomicalParam, BlockNumber, Balance>
+    RpcLiabilityInfo<Index, EconomicalParam, BlockNumber, Balance>
+{
+    /// Make JSON view of runtime liability info.
+    pub fn new<TechnicalParam, AccountId>(
+        index: Index,
+        info: LiabilityInfo<TechnicalParam, EconomicalParam, AccountId, BlockNumber, Balance>,
+    ) -> Self
+    where
+        TechnicalParam: AsRef<[u8]>,
+        AccountId: Ss58Codec,
+    {
+        RpcLiabilityInfo {
+            index,
+            technics: info.technics.as_ref().to_vec().into(),
+            technics_hash: info.technics_hash,
+            economics: info.economics,
+            economics_summary: info.economics_summary,
+            promisee: info.promisee.to_ss58check(),
+            promisor: info.promisor.to_ss58check(),
+            state: info.state.into(),
+            created_at: info.created_at,
+            finalized_at: info.finalized_at,
+        }
+    }
+}
+
+/// Liability cost JSON view.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcCostInfo<EconomicalParam> {
+    /// Weight of liability `create` call.
+    pub create_weight: u64,
+    /// Weight of liability `finalize` call.
+    pub finalize_weight: u64,
+    /// Promisee funds locked during liability execution.
+    pub escrow: EconomicalParam,
+}
+
+impl<EconomicalParam> From<CostInfo<EconomicalParam>> for RpcCostInfo<EconomicalParam> {
+    fn from(cost: CostInfo<EconomicalParam>) -> Self {
+        RpcCostInfo {
+            create_weight: cost.create_weight,
+            finalize_weight: cost.finalize_weight,
+            escrow: cost.escrow,
+        }
+    }
+}
+
+/// Liability statistics JSON view.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcLiabilityCounters<Balance> {
+    /// Total count of created liabilities.
+    pub created: u64,
+    /// Total count of finalized liabilities.
+    pub finalized: u64,
+    /// Total count of cancelled liabilities.
+    pub cancelled: u64,
+    /// Total value of escrow settled by finalized liabilities.
+    pub settled: Balance,
+}
+
+impl<Balance> From<LiabilityCounters<Balance>> for RpcLiabilityCounters<Balance> {
+    fn from(counters: LiabilityCounters<Balance>) -> Self {
+        RpcLiabilityCounters {
+            created: counters.created,
+            finalized: counters.finalized,
+            cancelled: counters.cancelled,
+            settled: counters.settled,
+        }
+    }
+}
+
+/// Count of finalized liabilities by outcome JSON view.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcOutcomeCounts {
+    /// Count of successfully finalized liabilities.
+    pub completed: u32,
+    /// Count of failed liabilities.
+    pub failed: u32,
+    /// Count of liabilities force finalized as failed.
+    pub disputed: u32,
+}
+
+impl From<OutcomeCounts> for RpcOutcomeCounts {
+    fn from(counts: OutcomeCounts) -> Self {
+        RpcOutcomeCounts {
+            completed: counts.completed,
+            failed: counts.failed,
+            disputed: counts.disputed,
+        }
+    }
+}
+
+/// Account liability statistics JSON view.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountStats<Balance> {
+    /// Outcomes of liabilities where account is promisor.
+    pub as_promisor: RpcOutcomeCounts,
+    /// Outcomes of liabilities where account is promisee.
+    pub as_promisee: RpcOutcomeCounts,
+    /// Total value settled by successfully finalized liabilities of account.
+    pub settled: Balance,
+}
+
+impl<Balance> From<AccountStats<Balance>> for RpcAccountStats<Balance> {
+    fn from(stats: AccountStats<Balance>) -> Self {
+        RpcAccountStats {
+            as_promisor: stats.as_promisor.into(),
+            as_promisee: stats.as_promisee.into(),
+            settled: stats.settled,
+        }
+    }
+}
+
+/// Liabilities of account with its statistics JSON view.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountLiabilities<Index, EconomicalParam, BlockNumber, Balance> {
+    /// Liability statistics of account.
+    pub stats: RpcAccountStats<Balance>,
+    /// Page of account liabilities.
+    pub liabilities: Vec<RpcLiabilityInfo<Index, EconomicalParam, BlockNumber, Balance>>,
+    /// Start of the next page.
+    pub next: Index,
+    /// Set when account has liabilities after the page.
+    pub has_more: bool,
+}
+
+/// Market order book JSON view.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcOrderBook<Balance> {
+    /// Best demand of market, it has the highest price.
+    pub best_ask: Option<RpcOrderBookEntry<Balance>>,
+    /// Best offer of market, it has the lowest price.
+    pub best_bid: Option<RpcOrderBookEntry<Balance>>,
+    /// Demand price levels, the best first.
+    pub asks: Vec<RpcPriceLevel<Balance>>,
+    /// Offer price levels, the best first.
+    pub bids: Vec<RpcPriceLevel<Balance>>,
+}
+
+impl<Balance> RpcOrderBook<Balance> {
+    /// Make JSON view of runtime order book.
+    pub fn new(
+        best_ask: Option<OrderBookEntry<Balance>>,
+        best_bid: Option<OrderBookEntry<Balance>>,
+        depth: OrderBookDepth<Balance>,
+    ) -> Self {
+        RpcOrderBook {
+            best_ask: best_ask.map(Into::into),
+            best_bid: best_bid.map(Into::into),
+            asks: depth.asks.into_iter().map(Into::into).collect(),
+            bids: depth.bids.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// Market order JSON view.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcOrderBookEntry<Balance> {
+    /// Market order id.
+    pub order_id: u64,
+    /// Settled value of order economics.
+    pub price: Balance,
+}
+
+impl<Balance> From<OrderBookEntry<Balance>> for RpcOrderBookEntry<Balance> {
+    fn from(entry: OrderBookEntry<Balance>) -> Self {
+        RpcOrderBookEntry {
+            order_id: entry.order_id,
+            price: entry.price,
+        }
+    }
+}
+
+/// Order book price level JSON view.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcPriceLevel<Balance> {
+    /// Settled value of orders economics.
+    pub price: Balance,
+    /// Count of open orders.
+    pub orders: u32,
+}
+
+impl<Balance> From<PriceLevel<Balance>> for RpcPriceLevel<Balance> {
+    fn from(level: PriceLevel<Balance>) -> Self {
+        RpcPriceLevel {
+            price: level.price,
+            orders: level.orders,
+        }
+    }
+}
+
+/// Best orders of market JSON view.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcBestPrices<Balance> {
+    /// Best demand of market, it has the highest price.
+    pub best_ask: Option<RpcOrderBookEntry<Balance>>,
+    /// Best offer of market, it has the lowest price.
+    pub best_bid: Option<RpcOrderBookEntry<Balance>>,
+}
+
+/// Market match JSON view.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcMatchInfo<Index, BlockNumber, Balance> {
+    /// Matched demand id.
+    pub demand_id: u64,
+    /// Matched offer id.
+    pub offer_id: u64,
+    /// Index of liability created by match.
+    pub index: Index,
+    /// Settled value of orders single unit economics.
+    pub price: Balance,
+    /// Count of units filled by match.
+    pub quantity: u32,
+    /// Settled value of liability.
+    pub value: Balance,
+    /// Block of match.
+    pub block_number: BlockNumber,
+}
+
+impl<Index, BlockNumber, Balance> From<MatchInfo<Index, BlockNumber, Balance>>
+    for RpcMatchInfo<Index, BlockNumber, Balance>
+{
+    fn from(info: MatchInfo<Index, BlockNumber, Balance>) -> Self {
+        RpcMatchInfo {
+            demand_id: info.demand_id,
+            offer_id: info.offer_id,
+            index: info.index,
+            price: info.price,
+            quantity: info.quantity,
+            value: info.value,
+            block_number: info.block_number,
+        }
+    }
+}
+
+/// Market volume JSON view.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcMarketVolume<BlockNumber, Balance> {
+    /// First block of window.
+    pub from: BlockNumber,
+    /// Count of matches.
+    pub matches: u32,
+    /// Count of filled units.
+    pub quantity: u64,
+    /// Settled value of created liabilities.
+    pub value: Balance,
+    /// Matches older than retained ones fall within window and aren't counted.
+    pub truncated: bool,
+}
+
+impl<BlockNumber, Balance> From<MarketVolume<BlockNumber, Balance>>
+    for RpcMarketVolume<BlockNumber, Balance>
+{
+    fn from(volume: MarketVolume<BlockNumber, Balance>) -> Self {
+        RpcMarketVolume {
+            from: volume.from,
+            matches: volume.matches,
+            quantity: volume.quantity,
+            value: volume.value,
+            truncated: volume.truncated,
+        }
+    }
+}
+
+/// Storage read proof of liability report.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcReportProof<BlockHash> {
+    /// Block which state root the proof is checked against.
+    pub at: BlockHash,
+    /// Trie nodes proving `Liabilities` record holding the report hash, or its absence.
+    pub proof: Vec<Bytes>,
+}
+
+/// Liability lifecycle event JSON view.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RpcLiabilityEvent<Index, EconomicalParam, BlockNumber> {
+    /// New liability created.
+    #[serde(rename_all = "camelCase")]
+    Created {
+        /// Liability index.
+        index: Index,
+        /// Content-derived liability id.
+        id: H256,
+        /// Hash of technical parameter of agreement.
+        technics: H256,
+        /// Economical parameter of agreement.
+        economics: EconomicalParam,
+        /// SS58 address of party that pays for the work.
+        promisee: String,
+        /// SS58 address of party that executes the work.
+        promisor: String,
+        /// Block when liability created.
+        block_number: BlockNumber,
+        /// Tags of liability agreed by promisee.
+        tags: Vec<Tag>,
+    },
+    /// Liability report published.
+    #[serde(rename_all = "camelCase")]
+    Reported {
+        /// Liability index.
+        index: Index,
+        /// Hash of technical report of agreement.
+        report: H256,
+        /// Block when liability report published.
+        block_number: BlockNumber,
+    },
+}
+
+impl<Index, EconomicalParam, BlockNumber> RpcLiabilityEvent<Index, EconomicalParam, BlockNumber> {
+    /// Make JSON view of runtime liability event.
+    pub fn new<AccountId: Ss58Codec>(
+        event: LiabilityEvent<Index, EconomicalParam, AccountId, BlockNumber>,
+    ) -> Self {
+        match event {
+            LiabilityEvent::Created {
+                index,
+                id,
+                technics,
+                economics,
+                promisee,
+                promisor,
+                block_number,
+                tags,
+            } => RpcLiabilityEvent::Created {
+                index,
+                id,
+                technics,
+                economics,
+                promisee: promisee.to_ss58check(),
+                promisor: promisor.to_ss58check(),
+                block_number,
+                tags,
+            },
+            LiabilityEvent::Reported {
+                index,
+                report,
+                block_number,
+            } => RpcLiabilityEvent::Reported {
+                index,
+                report,
+                block_number,
+            },
+        }
+    }
+}
+
+/// Liability events subscription filter, all events are passed when fields are empty.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EventFilter<Index> {
+    /// SS58 address of promisee or promisor.
+    pub account: Option<String>,
+    /// Lowest liability index, inclusive.
+    pub from_index: Option<Index>,
+    /// Highest liability index, inclusive.
+    pub to_index: Option<Index>,
+}
+
+impl<Index> Default for EventFilter<Index> {
+    fn default() -> Self {
+        EventFilter {
+            account: None,
+            from_index: None,
+            to_index: None,
+        }
+    }
+}
+
+/// Decoded version of `EventFilter`.
+struct EventMatcher<Index, AccountId> {
+    account: Option<AccountId>,
+    from_index: Option<Index>,
+    to_index: Option<Index>,
+}
+
+impl<Index: PartialOrd, AccountId: PartialEq + Ss58Codec> EventMatcher<Index, AccountId> {
+    fn new(filter: EventFilter<Index>) -> Result<Self> {
+        let account = match filter.account {
+            Some(account) => Some(decode_account(&account)?),
+            None => None,
+        };
+        Ok(EventMatcher {
+            account,
+            from_index: filter.from_index,
+            to_index: filter.to_index,
+        })
+    }
+
+    fn index_matches(&self, index: &Index) -> bool {
+        self.from_index.as_ref().map_or(true, |from| index >= from)
+            && self.to_index.as_ref().map_or(true, |to| index <= to)
+    }
+
+    fn account_matches(&self, promisee: &AccountId, promisor: &AccountId) -> bool {
+        self.account
+            .as_ref()
+            .map_or(true, |account| account == promisee || account == promisor)
+    }
+}
+
+fn decode_account<AccountId: Ss58Codec>(account: &str) -> Result<AccountId> {
+    AccountId::from_ss58check(account).map_err(|e| RpcError {
+        code: ErrorCode::InvalidParams,
+        message: "Bad SS58 account address.".into(),
+        data: Some(format!("{:?}", e).into()),
+    })
+}
+
+/// Liability RPC methods.
+#[rpc]
+pub trait LiabilityApi<BlockHash, Index, EconomicalParam, BlockNumber, Balance> {
+    /// RPC metadata
+    type Metadata;
+
+    /// Get liability by index.
+    #[rpc(name = "liability_getInfo")]
+    fn get_info(
+        &self,
+        index: Index,
+        at: Option<BlockHash>,
+    ) -> Result<Option<RpcLiabilityInfo<Index, EconomicalParam, BlockNumber, Balance>>>;
+
+    /// List page of liabilities of given SS58 account starting from `start` index,
+    /// with account statistics. Page is cut to `MAX_PAGE_ITEMS` liabilities, the next one
+    /// starts from returned `next` index.
+    #[rpc(name = "liability_listByAccount")]
+    fn list_by_account(
+        &self,
+        account: String,
+        start: Index,
+        count: u32,
+        at: Option<BlockHash>,
+    ) -> Result<RpcAccountLiabilities<Index, EconomicalParam, BlockNumber, Balance>>;
+
+    /// Estimate cost of liability with given economics.
+    #[rpc(name = "liability_estimateCost")]
+    fn estimate_cost(
+        &self,
+        economics: EconomicalParam,
+        at: Option<BlockHash>,
+    ) -> Result<RpcCostInfo<EconomicalParam>>;
+
+    /// Get network-level liability statistics.
+    #[rpc(name = "liability_getCounters")]
+    fn get_counters(&self, at: Option<BlockHash>) -> Result<RpcLiabilityCounters<Balance>>;
+
+    /// Get best orders and at most `levels` best price levels of market order book.
+    #[rpc(name = "liability_getOrderBook")]
+    fn get_order_book(
+        &self,
+        market: u32,
+        levels: u32,
+        at: Option<BlockHash>,
+    ) -> Result<RpcOrderBook<Balance>>;
+
+    /// Prove liability report existence (or absence) at given block.
+    #[rpc(name = "liability_proveReport")]
+    fn prove_report(
+        &self,
+        index: Index,
+        at: Option<BlockHash>,
+    ) -> Result<RpcReportProof<BlockHash>>;
+
+    /// Subscribe to liability events of finalized blocks.
+    #[pubsub(
+        subscription = "liability_events",
+        subscribe,
+        name = "liability_subscribeEvents"
+    )]
+    fn subscribe_events(
+        &self,
+        metadata: Self::Metadata,
+        subscriber: Subscriber<RpcLiabilityEvent<Index, EconomicalParam, BlockNumber>>,
+        filter: Option<EventFilter<Index>>,
+    );
+
+    /// Unsubscribe from liability events.
+    #[pubsub(
+        subscription = "liability_events",
+        unsubscribe,
+        name = "liability_unsubscribeEvents"
+    )]
+    fn unsubscribe_events(
+        &self,
+        metadata: Option<Self::Metadata>,
+        id: SubscriptionId,
+    ) -> Result<bool>;
+}
+
+/// An implementation of liability specific RPC methods.
+///
+/// `P` is a tuple of block, technical parameter, technical report and account types,
+/// runtime API is parametrized by them but JSON views aren't.
+pub struct Liability<C, P> {
+    client: Arc<C>,
+    manager: SubscriptionManager,
+    _marker: std::marker::PhantomData<P>,
+}
+
+impl<C, P> Liability<C, P> {
+    /// Create new `Liability` with the given reference to the client
+    /// and subscriptions manager.
+    pub fn new(client: Arc<C>, manager: SubscriptionManager) -> Self {
+        Liability {
+            client,
+            manager,
+            _marker: Default::default(),
+        }
+    }
+}
+
+fn runtime_error(e: impl std::fmt::Debug) -> RpcError {
+    RpcError {
+        code: ErrorCode::ServerError(RUNTIME_ERROR),
+        message: "Unable to query liability module.".into(),
+        data: Some(format!("{:?}", e).into()),
+    }
+}
+
+impl<
+        C,
+        Block,
+        Index,
+        TechnicalParam,
+        EconomicalParam,
+        TechnicalReport,
+        AccountId,
+        BlockNumber,
+        Balance,
+    > LiabilityApi<<Block as BlockT>::Hash, Index, EconomicalParam, BlockNumber, Balance>
+    for Liability<C, (Block, TechnicalParam, TechnicalReport, AccountId)>
+where
+    Block: BlockT,
+    C: Send + Sync + 'static,
+    C: ProvideRuntimeApi<Block>,
+    C: HeaderBackend<Block>,
+    C: BlockchainEvents<Block>,
+    C: ProofProvider<Block>,
+    C::Api: LiabilityRuntimeApi<
+        Block,
+        Index,
+        TechnicalParam,
+        EconomicalParam,
+        TechnicalReport,
+        AccountId,
+        BlockNumber,
+        Balance,
+    >,
+    Index: Codec + Clone + PartialOrd + Send + Sync + 'static,
+    TechnicalParam: Codec + AsRef<[u8]> + Send + Sync + 'static,
+    EconomicalParam: Codec + Clone + Send + Sync + 'static,
+    TechnicalReport: Codec + AsRef<[u8]> + Send + Sync + 'static,
+    AccountId: Codec + Clone + PartialEq + Ss58Codec + Send + Sync + 'static,
+    BlockNumber: Codec + Clone + Send + Sync + 'static,
+    Balance: Codec + Send + Sync + 'static,
+{
+    type Metadata = sc_rpc::Metadata;
+
+    fn get_info(
+        &self,
+        index: Index,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<RpcLiabilityInfo<Index, EconomicalParam, BlockNumber, Balance>>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        let info = api
+            .get_liability(&at, index.clone())
+            .map_err(runtime_error)?;
+        Ok(info.map(|info| RpcLiabilityInfo::new(index, info)))
+    }
+
+    fn list_by_account(
+        &self,
+        account: String,
+        start: Index,
+        count: u32,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<RpcAccountLiabilities<Index, EconomicalParam, BlockNumber, Balance>> {
+        let account = decode_account::<AccountId>(&account)?;
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        let page = api
+            .list_by_account(&at, account.clone(), start, count)
+            .map_err(runtime_error)?;
+        let stats = api.account_stats(&at, account).map_err(runtime_error)?;
+        Ok(RpcAccountLiabilities {
+            stats: stats.into(),
+            liabilities: page
+                .items
+                .into_iter()
+                .map(|(index, info)| RpcLiabilityInfo::new(index, info))
+                .collect(),
+            next: page.next,
+            has_more: page.has_more,
+        })
+    }
+
+    fn estimate_cost(
+        &self,
+        economics: EconomicalParam,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<RpcCostInfo<EconomicalParam>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        let cost = api.estimate_cost(&at, economics).map_err(runtime_error)?;
+        Ok(cost.into())
+    }
+
+    fn get_counters(
+        &self,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<RpcLiabilityCounters<Balance>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        let counters = api.counters(&at).map_err(runtime_error)?;
+        Ok(counters.into())
+    }
+
+    fn get_order_book(
+        &self,
+        market: u32,
+        levels: u32,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<RpcOrderBook<Balance>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        let best_ask = api.best_ask(&at, market).map_err(runtime_error)?;
+        let best_bid = api.best_bid(&at, market).map_err(runtime_error)?;
+        let depth = api.depth(&at, market, levels).map_err(runtime_error)?;
+        Ok(RpcOrderBook::new(best_ask, best_bid, depth))
+    }
+
+    fn prove_report(
+        &self,
+        index: Index,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<RpcReportProof<<Block as BlockT>::Hash>> {
+        let at = at.unwrap_or_else(|| self.client.info().finalized_hash);
+        let key = report_storage_key(&index);
+        let proof = self
+            .client
+            .read_proof(&BlockId::hash(at), &mut std::iter::once(key.as_slice()))
+            .map_err(|e| RpcError {
+                code: ErrorCode::ServerError(PROOF_ERROR),
+                message: "Unable to generate report proof.".into(),
+                data: Some(format!("{:?}", e).into()),
+            })?;
+        Ok(RpcReportProof {
+            at,
+            proof: proof.iter_nodes().map(Into::into).collect(),
+        })
+    }
+
+    fn subscribe_events(
+        &self,
+        _metadata: Self::Metadata,
+        subscriber: Subscriber<RpcLiabilityEvent<Index, EconomicalParam, BlockNumber>>,
+        filter: Option<EventFilter<Index>>,
+    ) {
+        let matcher = match EventMatcher::<Index, AccountId>::new(filter.unwrap_or_default()) {
+            Ok(matcher) => matcher,
+            Err(e) => {
+                let _ = subscriber.reject(e);
+                return;
+            }
+        };
+
+        let client = self.client.clone();
+        let (mut tx, rx) = mpsc::channel(EVENTS_BUFFER_SIZE);
+        let producer = self
+            .client
+            .finality_notification_stream()
+            .map(move |notification| {
+                finalized_events::<_, Block, _, TechnicalParam, _, TechnicalReport, _, _, Balance>(
+                    &*client,
+                    notification.hash,
+                    &matcher,
+                )
+            })
+            .take_while(move |events| {
+                let sent = events
+                    .iter()
+                    .all(|event| tx.try_send(event.clone()).is_ok());
+                if !sent {
+                    warn!("Liability events subscriber is too slow, subscription dropped");
+                }
+                future::ready(sent)
+            })
+            .for_each(|_| future::ready(()));
+
+        self.manager.add(subscriber, |sink| {
+            let events = Compat::new(rx.map(|event| Ok::<_, ()>(Ok(event))));
+            let consumer = sink
+                .sink_map_err(|e| warn!("Error sending liability events: {:?}", e))
+                .send_all(events)
+                .map(|_| ());
+            producer
+                .unit_error()
+                .boxed()
+                .compat()
+                .join(consumer)
+                .map(|_| ())
+        });
+    }
+
+    fn unsubscribe_events(
+        &self,
+        _metadata: Option<Self::Metadata>,
+        id: SubscriptionId,
+    ) -> Result<bool> {
+        Ok(self.manager.cancel(id))
+    }
+}
+
+/// Liability events of given finalized block that pass the filter.
+fn finalized_events<
+    C,
+    Block,
+    Index,
+    TechnicalParam,
+    EconomicalParam,
+    TechnicalReport,
+    AccountId,
+    BlockNumber,
+    Balance,
+>(
+    client: &C,
+    hash: <Block as BlockT>::Hash,
+    matcher: &EventMatcher<Index, AccountId>,
+) -> Vec<RpcLiabilityEvent<Index, EconomicalParam, BlockNumber>>
+where
+    Block: BlockT,
+    C: ProvideRuntimeApi<Block>,
+    C::Api: LiabilityRuntimeApi<
+        Block,
+        Index,
+        TechnicalParam,
+        EconomicalParam,
+        TechnicalReport,
+        AccountId,
+        BlockNumber,
+        Balance,
+    >,
+    Index: Codec + Clone + PartialOrd,
+    TechnicalParam: Codec,
+    EconomicalParam: Codec,
+    TechnicalReport: Codec,
+    AccountId: Codec + PartialEq + Ss58Codec,
+    BlockNumber: Codec,
+    Balance: Codec,
+{
+    let api = client.runtime_api();
+    let at = BlockId::hash(hash);
+    let events = match api.block_events(&at) {
+        Ok(events) => events,
+        Err(e) => {
+            warn!("Unable to fetch liability events at {}: {:?}", hash, e);
+            return Vec::new();
+        }
+    };
+
+    events
+        .into_iter()
+        .filter(|event| match event {
+            LiabilityEvent::Created {
+                index,
+                promisee,
+                promisor,
+                ..
+            } => matcher.index_matches(index) && matcher.account_matches(promisee, promisor),
+            LiabilityEvent::Reported { index, .. } => {
+                matcher.index_matches(index)
+                    && (matcher.account.is_none()
+                        || api
+                            .get_liability(&at, index.clone())
+                            .ok()
+                            .flatten()
+                            .map_or(false, |info| {
+                                matcher.account_matches(&info.promisee, &info.promisor)
+                            }))
+            }
+        })
+        .map(RpcLiabilityEvent::new)
+        .collect()
+}
+
+/// Liability market data RPC methods.
+#[rpc]
+pub trait MarketApi<BlockHash, Index, BlockNumber, Balance> {
+    /// Get best demand and offer of market.
+    #[rpc(name = "market_getBestPrices")]
+    fn get_best_prices(&self, market: u32, at: Option<BlockHash>)
+        -> Result<RpcBestPrices<Balance>>;
+
+    /// Get at most `count` recent matches of market, the latest first.
+    #[rpc(name = "market_getRecentMatches")]
+    fn get_recent_matches(
+        &self,
+        market: u32,
+        count: u32,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<RpcMatchInfo<Index, BlockNumber, Balance>>>;
+
+    /// Get matches volume of market in the last `blocks` blocks.
+    #[rpc(name = "market_getVolume")]
+    fn get_volume(
+        &self,
+        market: u32,
+        blocks: BlockNumber,
+        at: Option<BlockHash>,
+    ) -> Result<RpcMarketVolume<BlockNumber, Balance>>;
+}
+
+/// An implementation of liability market data RPC methods.
+///
+/// `P` is a tuple of block, technical parameter, economical parameter, technical report
+/// and account types, runtime API is parametrized by them but market views aren't.
+pub struct Market<C, P> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<P>,
+}
+
+impl<C, P> Market<C, P> {
+    /// Create new `Market` with the given reference to the client.
+    pub fn new(client: Arc<C>) -> Self {
+        Market {
+            client,
+            _marker: Default::default(),
+        }
+    }
+}
+
+impl<
+        C,
+        Block,
+        Index,
+        TechnicalParam,
+        EconomicalParam,
+        TechnicalReport,
+        AccountId,
+        BlockNumber,
+        Balance,
+    > MarketApi<<Block as BlockT>::Hash, Index, BlockNumber, Balance>
+    for Market<
+        C,
+        (
+            Block,
+            TechnicalParam,
+            EconomicalParam,
+            TechnicalReport,
+            AccountId,
+        ),
+    >
+where
+    Block: BlockT,
+    C: Send + Sync + 'static,
+    C: ProvideRuntimeApi<Block>,
+    C: HeaderBackend<Block>,
+    C::Api: LiabilityRuntimeApi<
+        Block,
+        Index,
+        TechnicalParam,
+        EconomicalParam,
+        TechnicalReport,
+        AccountId,
+        BlockNumber,
+        Balance,
+    >,
+    Index: Codec + Send + Sync + 'static,
+    TechnicalParam: Codec + Send + Sync + 'static,
+    EconomicalParam: Codec + Send + Sync + 'static,
+    TechnicalReport: Codec + Send + Sync + 'static,
+    AccountId: Codec + Send + Sync + 'static,
+    BlockNumber: Codec + Send + Sync + 'static,
+    Balance: Codec + Send + Sync + 'static,
+{
+    fn get_best_prices(
+        &self,
+        market: u32,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<RpcBestPrices<Balance>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        let best_ask = api.best_ask(&at, market).map_err(runtime_error)?;
+        let best_bid = api.best_bid(&at, market).map_err(runtime_error)?;
+        Ok(RpcBestPrices {
+            best_ask: best_ask.map(Into::into),
+            best_bid: best_bid.map(Into::into),
+        })
+    }
+
+    fn get_recent_matches(
+        &self,
+        market: u32,
+        count: u32,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<RpcMatchInfo<Index, BlockNumber, Balance>>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        let matches = api
+            .recent_matches(&at, market, count)
+            .map_err(runtime_error)?;
+        Ok(matches.into_iter().map(Into::into).collect())
+    }
+
+    fn get_volume(
+        &self,
+        market: u32,
+        blocks: BlockNumber,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<RpcMarketVolume<BlockNumber, Balance>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        let volume = api.volume(&at, market, blocks).map_err(runtime_error)?;
+        Ok(volume.into())
+    }
+}
+
+/// Liability archive RPC methods, available when node has off-chain storage.
+#[rpc]
+pub trait LiabilityArchiveApi<Index> {
+    /// Get liability report indexed off-chain, hex encoded.
+    ///
+    /// Works on nodes running with off-chain indexing enabled, even when report
+    /// isn't in the state anymore.
+    #[rpc(name = "liability_getArchivedReport")]
+    fn get_archived_report(&self, index: Index) -> Result<Option<Bytes>>;
+}
+
+/// An implementation of liability archive RPC methods.
+pub struct LiabilityArchive<S, R> {
+    storage: S,
+    _marker: std::marker::PhantomData<R>,
+}
+
+impl<S, R> LiabilityArchive<S, R> {
+    /// Create new `LiabilityArchive` with the given off-chain storage.
+    pub fn new(storage: S) -> Self {
+        LiabilityArchive {
+            storage,
+            _marker: Default::default(),
+        }
+    }
+}
+
+impl<S, R, Index> LiabilityArchiveApi<Index> for LiabilityArchive<S, R>
+where
+    S: OffchainStorage + 'static,
+    R: Decode + AsRef<[u8]> + Send + Sync + 'static,
+    Index: Encode,
+{
+    fn get_archived_report(&self, index: Index) -> Result<Option<Bytes>> {
+        let encoded = match self
+            .storage
+            .get(STORAGE_PREFIX, &offchain_report_key(&index))
+        {
+            Some(encoded) => encoded,
+            None => return Ok(None),
+        };
+        let report = R::decode(&mut &encoded[..]).map_err(|e| RpcError {
+            code: ErrorCode::ServerError(RUNTIME_ERROR),
+            message: "Unable to decode archived report.".into(),
+            data: Some(format!("{:?}", e).into()),
+        })?;
+        Ok(Some(report.as_ref().to_vec().into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sp_core::crypto::AccountId32;
+
+    #[test]
+    fn test_liability_info_json() {
+        let info = LiabilityInfo {
+            technics: vec![0xde, 0xad],
+            technics_hash: H256::repeat_byte(0xab),
+            economics: 42u64,
+            economics_summary: EconomicsSummary {
+                value: Some(42u64),
+                settled: 42,
+                market: 0,
+                deferred_funding: true,
+            },
+            promisee: AccountId32::from([1; 32]),
+            promisor: AccountId32::from([2; 32]),
+            state: LiabilityState::Finalized,
+            created_at: 10u32,
+            finalized_at: Some(12u32),
+        };
+        let json = serde_json::to_value(RpcLiabilityInfo::new(7u64, info.clone())).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "index": 7,
+                "technics": "0xdead",
+                "technicsHash": format!("0x{}", "ab".repeat(32)),
+                "economics": 42,
+                "economicsSummary": {
+                    "value": 42,
+                    "settled": 42,
+                    "market": 0,
+                    "deferredFunding": true,
+                },
+                "promisee": info.promisee.to_ss58check(),
+                "promisor": info.promisor.to_ss58check(),
+                "state": "finalized",
+                "createdAt": 10,
+                "finalizedAt": 12,
+            })
+        );
+    }
+
+    #[test]
+    fn test_account_liabilities_json() {
+        let stats = AccountStats {
+            as_promisor: OutcomeCounts {
+                completed: 2,
+                failed: 0,
+                disputed: 1,
+            },
+            as_promisee: OutcomeCounts::default(),
+            settled: 20u64,
+        };
+        let json = serde_json::to_value(RpcAccountLiabilities::<u64, u64, u32, u64> {
+            stats: stats.into(),
+            liabilities: vec![],
+            next: 3,
+            has_more: false,
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "stats": {
+                    "asPromisor": { "completed": 2, "failed": 0, "disputed": 1 },
+                    "asPromisee": { "completed": 0, "failed": 0, "disputed": 0 },
+                    "settled": 20,
+                },
+                "liabilities": [],
+                "next": 3,
+                "hasMore": false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_liability_event_json() {
+        let promisee = AccountId32::from([1; 32]);
+        let promisor = AccountId32::from([2; 32]);
+        let created: LiabilityEvent<u64, u64, AccountId32, u32> = LiabilityEvent::Created {
+            index: 3,
+            id: H256::repeat_byte(0x1d),
+            technics: H256::repeat_byte(0xbe),
+            economics: 42,
+            promisee: promisee.clone(),
+            promisor: promisor.clone(),
+            block_number: 5,
+            tags: vec![Tag::Delivery, Tag::Sensing],
+        };
+        assert_eq!(
+            serde_json::to_value(RpcLiabilityEvent::new(created)).unwrap(),
+            serde_json::json!({
+                "type": "created",
+                "index": 3,
+                "id": format!("0x{}", "1d".repeat(32)),
+                "technics": format!("0x{}", "be".repeat(32)),
+                "economics": 42,
+                "promisee": promisee.to_ss58check(),
+                "promisor": promisor.to_ss58check(),
+                "blockNumber": 5,
+                "tags": ["delivery", "sensing"],
+            })
+        );
+
+        let reported: LiabilityEvent<u64, u64, AccountId32, u32> = LiabilityEvent::Reported {
+            index: 3,
+            report: H256::repeat_byte(0xca),
+            block_number: 6,
+        };
+        assert_eq!(
+            serde_json::to_value(RpcLiabilityEvent::new(reported)).unwrap(),
+            serde_json::json!({
+                "type": "reported",
+                "index": 3,
+                "report": format!("0x{}", "ca".repeat(32)),
+                "blockNumber": 6,
+            })
+        );
+    }
+
+    #[test]
+    fn test_order_book_json() {
+        let depth = OrderBookDepth {
+            asks: vec![PriceLevel {
+                price: 20u64,
+                orders: 2,
+            }],
+            bids: Vec::new(),
+        };
+        let best_ask = Some(OrderBookEntry {
+            order_id: 1,
+            price: 20u64,
+        });
+        assert_eq!(
+            serde_json::to_value(RpcOrderBook::new(best_ask, None, depth)).unwrap(),
+            serde_json::json!({
+                "bestAsk": { "orderId": 1, "price": 20 },
+                "bestBid": null,
+                "asks": [{ "price": 20, "orders": 2 }],
+                "bids": [],
+            })
+        );
+    }
+
+    #[test]
+    fn test_market_json() {
+        let info = MatchInfo {
+            demand_id: 0,
+            offer_id: 1,
+            index: 5u64,
+            price: 10u64,
+            quantity: 3,
+            value: 30u64,
+            block_number: 4u32,
+        };
+        assert_eq!(
+            serde_json::to_value(RpcMatchInfo::from(info)).unwrap(),
+            serde_json::json!({
+                "demandId": 0,
+                "offerId": 1,
+                "index": 5,
+                "price": 10,
+                "quantity": 3,
+                "value": 30,
+                "blockNumber": 4,
+            })
+        );
+
+        let volume = MarketVolume {
+            from: 2u32,
+            matches: 1,
+            quantity: 3,
+            value: 30u64,
+            truncated: false,
+        };
+        assert_eq!(
+            serde_json::to_value(RpcMarketVolume::from(volume)).unwrap(),
+            serde_json::json!({
+                "from": 2,
+                "matches": 1,
+                "quantity": 3,
+                "value": 30,
+                "truncated": false,
+            })
+        );
+
+        let prices = RpcBestPrices::<u64> {
+            best_ask: None,
+            best_bid: Some(
+                OrderBookEntry {
+                    order_id: 1,
+                    price: 10,
+                }
+                .into(),
+            ),
+        };
+        assert_eq!(
+            serde_json::to_value(prices).unwrap(),
+            serde_json::json!({
+                "bestAsk": null,
+                "bestBid": { "orderId": 1, "price": 10 },
+            })
+        );
+    }
+
+    #[test]
+    fn test_archived_report() {
+        use sp_core::offchain::storage::InMemOffchainStorage;
+
+        let mut storage = InMemOffchainStorage::default();
+        let report = vec![0xca_u8, 0xfe];
+        storage.set(
+            STORAGE_PREFIX,
+            &offchain_report_key(&1u64),
+            &report.encode(),
+        );
+        let archive = LiabilityArchive::<_, Vec<u8>>::new(storage);
+
+        assert_eq!(
+            archive.get_archived_report(1u64).unwrap(),
+            Some(report.into())
+        );
+        assert_eq!(archive.get_archived_report(2u64).unwrap(), None);
+    }
+
+    #[test]
+    fn test_event_filter() {
+        let promisee = AccountId32::from([1; 32]);
+        let promisor = AccountId32::from([2; 32]);
+        let stranger = AccountId32::from([3; 32]);
+
+        let filter: EventFilter<u64> =
+            serde_json::from_value(serde_json::json!({ "fromIndex": 2, "toIndex": 4 })).unwrap();
+        let matcher = EventMatcher::<u64, AccountId32>::new(filter).unwrap();
+        assert!(!matcher.index_matches(&1));
+        assert!(matcher.index_matches(&2));
+        assert!(matcher.index_matches(&4));
+        assert!(!matcher.index_matches(&5));
+        assert!(matcher.account_matches(&promisee, &promisor));
+
+        let filter = EventFilter::<u64> {
+            account: Some(promisor.to_ss58check()),
+            ..Default::default()
+        };
+        let matcher = EventMatcher::<u64, AccountId32>::new(filter).unwrap();
+        assert!(matcher.index_matches(&100));
+        assert!(matcher.account_matches(&promisee, &promisor));
+        assert!(!matcher.account_matches(&promisee, &stranger));
+
+        let filter = EventFilter::<u64> {
+            account: Some("bad address".into()),
+            ..Default::default()
+        };
+        assert!(EventMatcher::<u64, AccountId32>::new(filter).is_err());
+    }
+}
@@ -0,0 +1,1188 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Liability module benchmarks.
+//!
+//! Parties, technics and reports are decoded from encodings of sr25519 accounts,
+//! `MultiSignature` proofs and byte vectors, as used by `SignedLiability` in the runtime.
+//!
+//! Liability storage accesses, reads/writes, before and after `Liabilities` records of
+//! release 7 (counters, statistics and history aren't changed):
+//!
+//! | Call             | Release 6 | Release 7 |
+//! |------------------|-----------|-----------|
+//! | `create`         | 1/4       | 1/3       |
+//! | `finalize`       | 3/3       | 1/1       |
+//! | `force_finalize` | 4/3       | 1/1       |
+//! | `rate`           | 4/0       | 1/0       |
+//!
+//! Since release 9 `finalize` writes report into `ReportPreimage` as well, 2/2 in total,
+//! record keeps its hash.
+//!
+//! Calls are benchmarked with sr25519 proofs, `verify_*` benchmarks measure proof
+//! verification of each `MultiSignature` scheme, so calls are charged by scheme used.
+
+use codec::{Decode, Encode};
+use frame_benchmarking::{account, benchmarks};
+use frame_support::traits::Currency;
+use frame_system::RawOrigin;
+use sp_core::{ecdsa, ed25519, sr25519};
+use sp_runtime::{
+    traits::{Bounded, IdentifyAccount, TrailingZeroInput},
+    MultiSignature, MultiSigner,
+};
+
+use crate::*;
+
+/// Maximal length of byte vector fitting into given encoded size, compact length prefix
+/// takes at most 4 bytes for bounded sizes.
+fn max_bytes(max_encoded: u32) -> u32 {
+    max_encoded.saturating_sub(4)
+}
+
+fn decode<T: Decode>(value: impl Encode) -> T {
+    value
+        .using_encoded(|mut encoded| T::decode(&mut encoded))
+        .expect("benchmark parameter should be decodable to runtime type")
+}
+
+/// Party account and its signature of given message.
+fn sign<T: Trait>(seed: &str, message: impl Encode) -> (AccountId<T>, ProofParam<T>) {
+    sign_with::<T>(SignatureScheme::Sr25519, seed, message)
+}
+
+/// Party account and its signature of given message by key of given scheme.
+fn sign_with<T: Trait>(
+    scheme: SignatureScheme,
+    seed: &str,
+    message: impl Encode,
+) -> (AccountId<T>, ProofParam<T>) {
+    let seed = Some(seed.as_bytes().to_vec());
+    let (signer, signature): (MultiSigner, Option<MultiSignature>) = match scheme {
+        SignatureScheme::Sr25519 => {
+            let public = sp_io::crypto::sr25519_generate(KEY_TYPE, seed);
+            let signature: Option<sr25519::Signature> = message
+                .using_encoded(|message| sp_io::crypto::sr25519_sign(KEY_TYPE, &public, message));
+            (public.into(), signature.map(Into::into))
+        }
+        SignatureScheme::Ed25519 => {
+            let public = sp_io::crypto::ed25519_generate(KEY_TYPE, seed);
+            let signature: Option<ed25519::Signature> = message
+                .using_encoded(|message| sp_io::crypto::ed25519_sign(KEY_TYPE, &public, message));
+            (public.into(), signature.map(Into::into))
+        }
+        SignatureScheme::Ecdsa => {
+            let public = sp_io::crypto::ecdsa_generate(KEY_TYPE, seed);
+            let signature: Option<ecdsa::Signature> = message
+                .using_encoded(|message| sp_io::crypto::ecdsa_sign(KEY_TYPE, &public, message));
+            (public.into(), signature.map(Into::into))
+        }
+    };
+    let signature = signature.expect("benchmark key should be in keystore");
+    (decode(signer.into_account()), decode(signature))
+}
+
+/// Liability with technical parameter of `p` bytes, its promisee and promisee proof
+/// of parameters by key of given scheme.
+fn signed_params<T: Trait>(
+    scheme: SignatureScheme,
+    p: u32,
+) -> (T::Liability, AccountId<T>, ProofParam<T>) {
+    let technics: TechnicalParam<T> = decode(vec![0u8; p as usize]);
+    let economics = economics::<T>();
+    let (promisee, proof) = sign_with::<T>(scheme, "//Alice", (&technics, &economics));
+    let (promisor, _) = sign::<T>("//Bob", ());
+    let liability = T::Liability::new(technics, economics, promisee.clone(), promisor);
+    (liability, promisee, proof)
+}
+
+fn economics<T: Trait>() -> EconomicalParam<T> {
+    EconomicalParam::<T>::decode(&mut TrailingZeroInput::new(&[][..]))
+        .expect("economical parameter should be decodable from zeroes")
+}
+
+benchmarks! {
+    _ { }
+
+    create {
+        let t in 0 .. max_bytes(T::MaxTechnicsLength::get());
+        let technics: TechnicalParam<T> = decode(vec![0u8; t as usize]);
+        let economics = economics::<T>();
+        let (promisee, promisee_proof) = sign::<T>("//Alice", (&technics, &economics));
+        let (promisor, promisor_proof) = sign::<T>("//Bob", (&technics, &economics));
+        <ApprovedPromisors<T>>::insert(&promisor, ());
+        // Zero value liability reserves promisee deposit
+        T::Currency::make_free_balance_be(&promisee, BalanceOf::<T>::max_value() / 2u32.into());
+    }: _(
+        RawOrigin::None,
+        technics,
+        economics,
+        promisee,
+        promisor,
+        promisee_proof,
+        promisor_proof
+    )
+    verify {
+        assert_eq!(<LatestIndex<T>>::get(), One::one());
+    }
+
+    create_rate_limited {
+        let technics: TechnicalParam<T> = decode(Vec::<u8>::new());
+        let economics = economics::<T>();
+        let (promisee, promisee_proof) = sign::<T>("//Alice", ());
+        let (promisor, promisor_proof) = sign::<T>("//Bob", ());
+        <UnsignedInBlock>::put(T::MaxUnsignedPerBlock::get());
+    }: {
+        assert!(Module::<T>::create(
+            RawOrigin::None.into(),
+            technics,
+            economics,
+            promisee,
+            promisor,
+            promisee_proof,
+            promisor_proof,
+        ).is_err());
+    }
+    verify {
+        assert!(<LatestIndex<T>>::get().is_zero());
+    }
+
+    create_blacklisted {
+        let technics: TechnicalParam<T> = decode(Vec::<u8>::new());
+        let economics = economics::<T>();
+        let (promisee, promisee_proof) = sign::<T>("//Alice", ());
+        let (promisor, promisor_proof) = sign::<T>("//Bob", ());
+        <Blacklist<T>>::insert(&promisor, true);
+    }: {
+        assert!(Module::<T>::create(
+            RawOrigin::None.into(),
+            technics,
+            economics,
+            promisee,
+            promisor,
+            promisee_proof,
+            promisor_proof,
+        ).is_err());
+    }
+    verify {
+        assert!(<LatestIndex<T>>::get().is_zero());
+    }
+
+    create_unaffordable {
+        let technics: TechnicalParam<T> = decode(Vec::<u8>::new());
+        let economics = economics::<T>();
+        let (promisee, promisee_proof) = sign::<T>("//Alice", ());
+        let (promisor, promisor_proof) = sign::<T>("//Bob", ());
+        <ApprovedPromisors<T>>::insert(&promisor, ());
+    }: {
+        assert!(Module::<T>::create_with_bounty(
+            RawOrigin::None.into(),
+            technics,
+            economics,
+            promisee,
+            promisor,
+            BalanceOf::<T>::max_value(),
+            promisee_proof,
+            promisor_proof,
+        ).is_err());
+    }
+    verify {
+        assert!(<LatestIndex<T>>::get().is_zero());
+    }
+
+    create_duplicate {
+        let technics: TechnicalParam<T> = decode(Vec::<u8>::new());
+        let economics = economics::<T>();
+        let (promisee, promisee_proof) = sign::<T>("//Alice", ());
+        let (promisor, promisor_proof) = sign::<T>("//Bob", ());
+        <ApprovedPromisors<T>>::insert(&promisor, ());
+        let liability = T::Liability::new(
+            technics.clone(),
+            economics.clone(),
+            promisee.clone(),
+            promisor.clone(),
+        );
+        let block_number = frame_system::Module::<T>::block_number();
+        <AgreementsInBlock<T>>::insert(block_number, Module::<T>::agreement_hash(&liability), ());
+    }: {
+        assert!(Module::<T>::create(
+            RawOrigin::None.into(),
+            technics,
+            economics,
+            promisee,
+            promisor,
+            promisee_proof,
+            promisor_proof,
+        ).is_err());
+    }
+    verify {
+        assert!(<LatestIndex<T>>::get().is_zero());
+    }
+
+    finalize {
+        let r in 0 .. max_bytes(T::MaxReportLength::get());
+        let technics: TechnicalParam<T> =
+            decode(vec![0u8; max_bytes(T::MaxTechnicsLength::get()) as usize]);
+        let (promisee, _) = sign::<T>("//Alice", ());
+        let (promisor, _) = sign::<T>("//Bob", ());
+        let liability = T::Liability::new(technics, economics::<T>(), promisee, promisor);
+        let (index, _) = Module::<T>::store_liability(liability, Zero::zero());
+        frame_system::Module::<T>::set_block_number(T::MinReportDelay::get());
+        let report: TechnicalReport<T> = decode(vec![0u8; r as usize]);
+        let (_, proof) = sign::<T>("//Bob", (&index, &report));
+    }: _(RawOrigin::None, index, report, proof)
+    verify {
+        assert!(Module::<T>::is_finalized(index));
+    }
+
+    // Invalid reports are attacker-controlled, they are rejected before economical
+    // processing and any storage write.
+    finalize_bad_proof {
+        let r in 0 .. max_bytes(T::MaxReportLength::get());
+        let technics: TechnicalParam<T> =
+            decode(vec![0u8; max_bytes(T::MaxTechnicsLength::get()) as usize]);
+        let (promisee, _) = sign::<T>("//Alice", ());
+        let (promisor, _) = sign::<T>("//Bob", ());
+        let liability = T::Liability::new(technics, economics::<T>(), promisee, promisor);
+        let (index, _) = Module::<T>::store_liability(liability, Zero::zero());
+        frame_system::Module::<T>::set_block_number(T::MinReportDelay::get());
+        let report: TechnicalReport<T> = decode(vec![0u8; r as usize]);
+        let (_, proof) = sign::<T>("//Alice", (&index, &report));
+    }: {
+        assert!(Module::<T>::finalize(RawOrigin::None.into(), index, report, proof).is_err());
+    }
+    verify {
+        assert!(!Module::<T>::is_finalized(index));
+    }
+
+    force_finalize {
+        let technics: TechnicalParam<T> = decode(Vec::<u8>::new());
+        let (promisee, _) = sign::<T>("//Alice", ());
+        let (promisor, _) = sign::<T>("//Bob", ());
+        let liability = T::Liability::new(technics, economics::<T>(), promisee, promisor);
+        let (index, _) = Module::<T>::store_liability(liability, Zero::zero());
+        frame_system::Module::<T>::set_block_number(T::MinForceFinalizeAge::get());
+    }: _(RawOrigin::Root, index, true)
+    verify {
+        assert_eq!(Module::<T>::forced_outcome(index), Some(true));
+    }
+
+    cancel {
+        let technics: TechnicalParam<T> = decode(Vec::<u8>::new());
+        let (promisee, _) = sign::<T>("//Alice", ());
+        let (promisor, _) = sign::<T>("//Bob", ());
+        let liability = T::Liability::new(technics, economics::<T>(), promisee, promisor);
+        let (index, _) = Module::<T>::store_liability(liability, Zero::zero());
+        let expires_at = BlockNumber::<T>::zero();
+        let (_, promisee_proof) = sign::<T>("//Alice", (CANCEL_DOMAIN, &index, &expires_at));
+        let (_, promisor_proof) = sign::<T>("//Bob", (CANCEL_DOMAIN, &index, &expires_at));
+    }: _(RawOrigin::None, index, expires_at, Some(promisee_proof), Some(promisor_proof))
+    verify {
+        assert_eq!(Module::<T>::counters().cancelled, 1);
+    }
+
+    amend_economics {
+        let technics: TechnicalParam<T> = decode(Vec::<u8>::new());
+        let (promisee, _) = sign::<T>("//Alice", ());
+        let (promisor, _) = sign::<T>("//Bob", ());
+        let economics = economics::<T>();
+        let liability = T::Liability::new(technics, economics.clone(), promisee.clone(), promisor);
+        let (index, _) = Module::<T>::store_liability(liability, Zero::zero());
+        let (_, promisee_proof) = sign::<T>("//Alice", (AMEND_DOMAIN, &index, 0u32, &economics));
+        let (_, promisor_proof) = sign::<T>("//Bob", (AMEND_DOMAIN, &index, 0u32, &economics));
+    }: _(RawOrigin::Signed(promisee), index, economics, promisee_proof, promisor_proof)
+    verify {
+        assert_eq!(Module::<T>::amendments_of(index), 1);
+    }
+
+    commit_report {
+        let technics: TechnicalParam<T> = decode(Vec::<u8>::new());
+        let (promisee, _) = sign::<T>("//Alice", ());
+        let (promisor, _) = sign::<T>("//Bob", ());
+        let liability = T::Liability::new(technics, economics::<T>(), promisee, promisor);
+        let (index, _) = Module::<T>::store_liability(liability, Zero::zero());
+        frame_system::Module::<T>::set_block_number(T::MinReportDelay::get());
+        let commitment = H256::repeat_byte(1);
+        let (_, proof) = sign::<T>("//Bob", (COMMIT_DOMAIN, &index, &commitment));
+    }: _(RawOrigin::None, index, commitment, proof)
+    verify {
+        assert!(<ReportCommitments<T>>::contains_key(index));
+    }
+
+    reveal_report {
+        let r in 0 .. max_bytes(T::MaxReportLength::get());
+        let technics: TechnicalParam<T> = decode(Vec::<u8>::new());
+        let (promisee, _) = sign::<T>("//Alice", ());
+        let (promisor, _) = sign::<T>("//Bob", ());
+        let liability = T::Liability::new(technics, economics::<T>(), promisee, promisor);
+        let (index, _) = Module::<T>::store_liability(liability, Zero::zero());
+        frame_system::Module::<T>::set_block_number(T::MinReportDelay::get());
+        let report: TechnicalReport<T> = decode(vec![0u8; r as usize]);
+        let salt = H256::repeat_byte(1);
+        let commitment = report_commitment(&report, &salt);
+        let (_, proof) = sign::<T>("//Bob", (COMMIT_DOMAIN, &index, &commitment));
+        Module::<T>::commit_report(RawOrigin::None.into(), index, commitment, proof)?;
+    }: _(RawOrigin::None, index, report, salt)
+    verify {
+        assert!(Module::<T>::is_finalized(index));
+    }
+
+    demand {
+        let t in 0 .. max_bytes(T::MaxTechnicsLength::get());
+        let technics: TechnicalParam<T> = decode(vec![0u8; t as usize]);
+        let economics = economics::<T>();
+        let (promisee, proof) = sign::<T>("//Alice", (&technics, &economics));
+        T::Currency::make_free_balance_be(&promisee, BalanceOf::<T>::max_value() / 2u32.into());
+    }: _(RawOrigin::Signed(promisee), technics, economics, 1, 10u32.into(), proof)
+    verify {
+        assert!(<Asks<T>>::contains_key(0));
+    }
+
+    cancel_demand {
+        let technics: TechnicalParam<T> = decode(Vec::<u8>::new());
+        let economics = economics::<T>();
+        let (promisee, proof) = sign::<T>("//Alice", (&technics, &economics));
+        T::Currency::make_free_balance_be(&promisee, BalanceOf::<T>::max_value() / 2u32.into());
+        Module::<T>::demand(
+            RawOrigin::Signed(promisee.clone()).into(),
+            technics,
+            economics,
+            1,
+            10u32.into(),
+            proof,
+        )?;
+    }: _(RawOrigin::Signed(promisee), 0)
+    verify {
+        assert!(!<Asks<T>>::contains_key(0));
+    }
+
+    offer {
+        let t in 0 .. max_bytes(T::MaxTechnicsLength::get());
+        let technics: TechnicalParam<T> = decode(vec![0u8; t as usize]);
+        let economics = economics::<T>();
+        let (promisor, proof) = sign::<T>("//Bob", (&technics, &economics));
+        T::Currency::make_free_balance_be(&promisor, BalanceOf::<T>::max_value() / 2u32.into());
+    }: _(RawOrigin::Signed(promisor), technics, economics, 1, 10u32.into(), proof)
+    verify {
+        assert!(<Bids<T>>::contains_key(0));
+    }
+
+    cancel_offer {
+        let technics: TechnicalParam<T> = decode(Vec::<u8>::new());
+        let economics = economics::<T>();
+        let (promisor, proof) = sign::<T>("//Bob", (&technics, &economics));
+        T::Currency::make_free_balance_be(&promisor, BalanceOf::<T>::max_value() / 2u32.into());
+        Module::<T>::offer(
+            RawOrigin::Signed(promisor.clone()).into(),
+            technics,
+            economics,
+            1,
+            10u32.into(),
+            proof,
+        )?;
+    }: _(RawOrigin::Signed(promisor), 0)
+    verify {
+        assert!(!<Bids<T>>::contains_key(0));
+    }
+
+    match_orders {
+        let technics: TechnicalParam<T> = decode(Vec::<u8>::new());
+        let economics = economics::<T>();
+        let (promisee, promisee_proof) = sign::<T>("//Alice", (&technics, &economics));
+        let (promisor, promisor_proof) = sign::<T>("//Bob", (&technics, &economics));
+        let (caller, _) = sign::<T>("//Charlie", ());
+        for who in &[&promisee, &promisor] {
+            T::Currency::make_free_balance_be(who, BalanceOf::<T>::max_value() / 4u32.into());
+        }
+        if T::PromisorWhitelist::get() {
+            <ApprovedPromisors<T>>::insert(&promisor, ());
+        }
+        Module::<T>::demand(
+            RawOrigin::Signed(promisee).into(),
+            technics.clone(),
+            economics.clone(),
+            1,
+            10u32.into(),
+            promisee_proof,
+        )?;
+        Module::<T>::offer(
+            RawOrigin::Signed(promisor).into(),
+            technics,
+            economics,
+            1,
+            10u32.into(),
+            promisor_proof,
+        )?;
+    }: _(RawOrigin::Signed(caller), 0, 1)
+    verify {
+        assert!(<MatchedOrders<T>>::contains_key(1));
+    }
+
+    commit_match {
+        let (caller, _) = sign::<T>("//Charlie", ());
+        let commitment = match_commitment(&0u64, &1u64, &caller, &H256::repeat_byte(1));
+    }: _(RawOrigin::Signed(caller.clone()), commitment)
+    verify {
+        assert!(<MatchCommitments<T>>::contains_key(&caller, commitment));
+    }
+
+    reveal_match {
+        let technics: TechnicalParam<T> = decode(Vec::<u8>::new());
+        let economics = economics::<T>();
+        let (promisee, promisee_proof) = sign::<T>("//Alice", (&technics, &economics));
+        let (promisor, promisor_proof) = sign::<T>("//Bob", (&technics, &economics));
+        let (caller, _) = sign::<T>("//Charlie", ());
+        for who in &[&promisee, &promisor] {
+            T::Currency::make_free_balance_be(who, BalanceOf::<T>::max_value() / 4u32.into());
+        }
+        if T::PromisorWhitelist::get() {
+            <ApprovedPromisors<T>>::insert(&promisor, ());
+        }
+        Module::<T>::demand(
+            RawOrigin::Signed(promisee).into(),
+            technics.clone(),
+            economics.clone(),
+            1,
+            10u32.into(),
+            promisee_proof,
+        )?;
+        Module::<T>::offer(
+            RawOrigin::Signed(promisor).into(),
+            technics,
+            economics,
+            1,
+            10u32.into(),
+            promisor_proof,
+        )?;
+        let salt = H256::repeat_byte(1);
+        let commitment = match_commitment(&0u64, &1u64, &caller, &salt);
+        Module::<T>::commit_match(RawOrigin::Signed(caller.clone()).into(), commitment)?;
+        frame_system::Module::<T>::set_block_number(One::one());
+    }: _(RawOrigin::Signed(caller), 0, 1, salt)
+    verify {
+        assert!(<MatchedOrders<T>>::contains_key(1));
+    }
+
+    register_lighthouse {
+        let l in 0 .. T::MaxLighthouses::get().saturating_sub(1);
+        let stake = T::LighthouseMinStake::get();
+        for i in 0 .. l {
+            let lighthouse: AccountId<T> = account("lighthouse", i, 0);
+            T::Currency::make_free_balance_be(&lighthouse, BalanceOf::<T>::max_value() / 4u32.into());
+            Module::<T>::register_lighthouse(RawOrigin::Signed(lighthouse).into(), 0, stake)?;
+        }
+        let (caller, _) = sign::<T>("//Charlie", ());
+        T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value() / 4u32.into());
+    }: _(RawOrigin::Signed(caller.clone()), 0, stake)
+    verify {
+        assert!(<LighthouseStake<T>>::contains_key(0, caller));
+    }
+
+    unregister_lighthouse {
+        let (caller, _) = sign::<T>("//Charlie", ());
+        T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value() / 4u32.into());
+        Module::<T>::register_lighthouse(
+            RawOrigin::Signed(caller.clone()).into(),
+            0,
+            T::LighthouseMinStake::get(),
+        )?;
+    }: _(RawOrigin::Signed(caller.clone()), 0)
+    verify {
+        assert!(!<LighthouseStake<T>>::contains_key(0, caller));
+    }
+
+    register_witness {
+        let w in 0 .. T::MaxWitnesses::get().saturating_sub(1);
+        let stake = T::WitnessMinStake::get();
+        for i in 0 .. w {
+            let witness: AccountId<T> = account("witness", i, 0);
+            T::Currency::make_free_balance_be(&witness, BalanceOf::<T>::max_value() / 4u32.into());
+            Module::<T>::register_witness(RawOrigin::Signed(witness).into(), stake)?;
+        }
+        let (caller, _) = sign::<T>("//Charlie", ());
+        T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value() / 4u32.into());
+    }: _(RawOrigin::Signed(caller.clone()), stake)
+    verify {
+        assert!(<WitnessStake<T>>::contains_key(caller));
+    }
+
+    unregister_witness {
+        let (caller, _) = sign::<T>("//Charlie", ());
+        T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value() / 4u32.into());
+        Module::<T>::register_witness(
+            RawOrigin::Signed(caller.clone()).into(),
+            T::WitnessMinStake::get(),
+        )?;
+    }: _(RawOrigin::Signed(caller.clone()))
+    verify {
+        assert!(!<WitnessStake<T>>::contains_key(caller));
+    }
+
+    join_pool {
+        let p in 0 .. T::MaxPoolSize::get().saturating_sub(1);
+        let technics: TechnicalParam<T> = decode(Vec::<u8>::new());
+        let economics = economics::<T>();
+        let market_id = Module::<T>::market_of(&technics, &economics);
+        let (promisor, proof) = sign::<T>("//Bob", (&technics, &economics));
+        T::Currency::make_free_balance_be(&promisor, BalanceOf::<T>::max_value() / 4u32.into());
+        Module::<T>::offer(
+            RawOrigin::Signed(promisor.clone()).into(),
+            technics,
+            economics,
+            1,
+            10u32.into(),
+            proof,
+        )?;
+        let stake = T::PoolMinStake::get();
+        let pool = (0 .. p)
+            .map(|i| (account("member", i, 0), 0, stake, Zero::zero()))
+            .collect::<Vec<_>>();
+        <Pools<T>>::insert(market_id, pool);
+    }: _(RawOrigin::Signed(promisor), market_id, 0, stake)
+    verify {
+        assert_eq!(<Pools<T>>::get(market_id).len() as u32, p + 1);
+    }
+
+    leave_pool {
+        let p in 1 .. T::MaxPoolSize::get();
+        let (promisor, _) = sign::<T>("//Bob", ());
+        T::Currency::make_free_balance_be(&promisor, BalanceOf::<T>::max_value() / 4u32.into());
+        let stake = T::PoolMinStake::get();
+        NamedReserve::<T>::reserve_named(&RESERVE_ID, &promisor, stake)?;
+        let mut pool = (1 .. p)
+            .map(|i| (account("member", i, 0), 0, stake, Zero::zero()))
+            .collect::<Vec<_>>();
+        pool.push((promisor.clone(), 0, stake, Zero::zero()));
+        <Pools<T>>::insert(0, pool);
+    }: _(RawOrigin::Signed(promisor), 0)
+    verify {
+        assert_eq!(<Pools<T>>::get(0).len() as u32, p - 1);
+    }
+
+    request_assignment {
+        let technics: TechnicalParam<T> = decode(Vec::<u8>::new());
+        let economics = economics::<T>();
+        let market_id = Module::<T>::market_of(&technics, &economics);
+        let (promisee, proof) = sign::<T>("//Alice", (&technics, &economics));
+        T::Currency::make_free_balance_be(&promisee, BalanceOf::<T>::max_value() / 4u32.into());
+        Module::<T>::demand(
+            RawOrigin::Signed(promisee.clone()).into(),
+            technics,
+            economics,
+            1,
+            10u32.into(),
+            proof,
+        )?;
+        let member = (account("member", 0, 0), 1, T::PoolMinStake::get(), Zero::zero());
+        <Pools<T>>::insert(market_id, vec![member]);
+    }: _(RawOrigin::Signed(promisee), 0)
+    verify {
+        assert!(<PendingAssignment<T>>::contains_key(0));
+    }
+
+    acknowledge {
+        let technics: TechnicalParam<T> = decode(Vec::<u8>::new());
+        let (promisee, _) = sign::<T>("//Alice", ());
+        let (promisor, _) = sign::<T>("//Bob", ());
+        T::Currency::make_free_balance_be(&promisor, BalanceOf::<T>::max_value() / 4u32.into());
+        let deposit = T::AcknowledgementDeposit::get();
+        NamedReserve::<T>::reserve_named(&RESERVE_ID, &promisor, deposit)?;
+        let liability = T::Liability::new(technics, economics::<T>(), promisee, promisor);
+        let (index, _) = Module::<T>::store_liability(liability, Zero::zero());
+        <PendingAcknowledgement<T>>::insert(index, (BlockNumber::<T>::zero(), deposit));
+        let (_, proof) = sign::<T>("//Bob", (ACK_DOMAIN, &index));
+    }: _(RawOrigin::None, index, proof)
+    verify {
+        assert!(!<PendingAcknowledgement<T>>::contains_key(index));
+    }
+
+    reclaim {
+        let technics: TechnicalParam<T> = decode(Vec::<u8>::new());
+        let (promisee, _) = sign::<T>("//Alice", ());
+        let (promisor, _) = sign::<T>("//Bob", ());
+        T::Currency::make_free_balance_be(&promisee, BalanceOf::<T>::max_value() / 4u32.into());
+        T::Currency::make_free_balance_be(&promisor, BalanceOf::<T>::max_value() / 4u32.into());
+        let deposit = T::AcknowledgementDeposit::get();
+        NamedReserve::<T>::reserve_named(&RESERVE_ID, &promisor, deposit)?;
+        let liability = T::Liability::new(technics, economics::<T>(), promisee.clone(), promisor);
+        let (index, _) = Module::<T>::store_liability(liability, Zero::zero());
+        <PendingAcknowledgement<T>>::insert(index, (BlockNumber::<T>::zero(), deposit));
+        frame_system::Module::<T>::set_block_number(One::one());
+    }: _(RawOrigin::Signed(promisee), index)
+    verify {
+        assert_eq!(Module::<T>::counters().cancelled, 1);
+    }
+
+    force_expire {
+        let technics: TechnicalParam<T> = decode(Vec::<u8>::new());
+        let (promisee, _) = sign::<T>("//Alice", ());
+        let (promisor, _) = sign::<T>("//Bob", ());
+        let (caller, _) = sign::<T>("//Charlie", ());
+        T::Currency::make_free_balance_be(&promisee, BalanceOf::<T>::max_value() / 4u32.into());
+        T::Currency::make_free_balance_be(&promisor, BalanceOf::<T>::max_value() / 4u32.into());
+        let deposit = T::AcknowledgementDeposit::get();
+        NamedReserve::<T>::reserve_named(&RESERVE_ID, &promisor, deposit)?;
+        let liability = T::Liability::new(technics, economics::<T>(), promisee, promisor);
+        let (index, _) = Module::<T>::store_liability(liability, Zero::zero());
+        <PendingAcknowledgement<T>>::insert(index, (BlockNumber::<T>::zero(), deposit));
+        frame_system::Module::<T>::set_block_number(
+            T::MaxLiabilityLifetime::get().saturating_add(One::one()),
+        );
+    }: _(RawOrigin::Signed(caller), index)
+    verify {
+        assert_eq!(Module::<T>::counters().cancelled, 1);
+    }
+
+    set_preferences {
+        let (caller, _) = sign::<T>("//Bob", ());
+        let (payout, _) = sign::<T>("//Alice", ());
+        let prefs = AccountPrefs {
+            arbitrator: Some(payout.clone()),
+            payout: Some(payout),
+            topic: Some(H256::repeat_byte(1)),
+        };
+    }: _(RawOrigin::Signed(caller.clone()), prefs)
+    verify {
+        assert!(<Preferences<T>>::contains_key(caller));
+    }
+
+    require_witnesses {
+        let technics: TechnicalParam<T> = decode(Vec::<u8>::new());
+        let (promisee, _) = sign::<T>("//Alice", ());
+        let (promisor, _) = sign::<T>("//Bob", ());
+        let liability = T::Liability::new(technics, economics::<T>(), promisee.clone(), promisor);
+        let (index, _) = Module::<T>::store_liability(liability, Zero::zero());
+        let quorum = T::MaxWitnesses::get();
+        let (_, proof) = sign::<T>("//Bob", (WITNESS_DOMAIN, &index, quorum));
+    }: _(RawOrigin::Signed(promisee), index, quorum, proof)
+    verify {
+        assert_eq!(Module::<T>::witness_quorum(index), Some(quorum));
+    }
+
+    notarize {
+        let technics: TechnicalParam<T> = decode(Vec::<u8>::new());
+        let (promisee, _) = sign::<T>("//Alice", ());
+        let (promisor, _) = sign::<T>("//Bob", ());
+        let liability = T::Liability::new(technics, economics::<T>(), promisee, promisor);
+        let (index, _) = Module::<T>::store_liability(liability, Zero::zero());
+        frame_system::Module::<T>::set_block_number(T::MinReportDelay::get());
+        <WitnessQuorum<T>>::insert(index, 1);
+        let report: TechnicalReport<T> = decode(Vec::<u8>::new());
+        let (_, proof) = sign::<T>("//Bob", (&index, &report));
+        Module::<T>::finalize(RawOrigin::None.into(), index, report.clone(), proof)?;
+        let (caller, _) = sign::<T>("//Charlie", ());
+        T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value() / 4u32.into());
+        Module::<T>::register_witness(
+            RawOrigin::Signed(caller.clone()).into(),
+            T::WitnessMinStake::get(),
+        )?;
+        let (_, proof) = sign::<T>("//Charlie", (NOTARY_DOMAIN, &index, report_hash(&report)));
+    }: _(RawOrigin::Signed(caller.clone()), index, proof)
+    verify {
+        assert_eq!(Module::<T>::witness_pending(caller), 1);
+    }
+
+    overturn_report {
+        let w in 0 .. T::MaxWitnesses::get();
+        let technics: TechnicalParam<T> = decode(Vec::<u8>::new());
+        let (promisee, _) = sign::<T>("//Alice", ());
+        let (promisor, _) = sign::<T>("//Bob", ());
+        let liability = T::Liability::new(technics, economics::<T>(), promisee, promisor);
+        let (index, _) = Module::<T>::store_liability(liability, Zero::zero());
+        frame_system::Module::<T>::set_block_number(T::MinReportDelay::get());
+        <WitnessQuorum<T>>::insert(index, 1);
+        let report: TechnicalReport<T> = decode(Vec::<u8>::new());
+        let (_, proof) = sign::<T>("//Bob", (&index, &report));
+        Module::<T>::finalize(RawOrigin::None.into(), index, report, proof)?;
+        let mut notaries = Vec::new();
+        for i in 0 .. w {
+            let witness: AccountId<T> = account("witness", i, 0);
+            T::Currency::make_free_balance_be(&witness, BalanceOf::<T>::max_value() / 4u32.into());
+            Module::<T>::register_witness(
+                RawOrigin::Signed(witness.clone()).into(),
+                T::WitnessMinStake::get(),
+            )?;
+            <WitnessPending<T>>::insert(&witness, 1);
+            notaries.push(witness);
+        }
+        <Notarizations<T>>::insert(index, notaries);
+    }: _(RawOrigin::Root, index)
+    verify {
+        assert_eq!(Module::<T>::settlement_deadline(index), None);
+    }
+
+    verify_sr25519 {
+        let p in 0 .. max_bytes(T::MaxTechnicsLength::get());
+        let (liability, promisee, proof) = signed_params::<T>(SignatureScheme::Sr25519, p);
+    }: {
+        assert!(liability.check_params(&proof, &promisee));
+    }
+
+    verify_ed25519 {
+        let p in 0 .. max_bytes(T::MaxTechnicsLength::get());
+        let (liability, promisee, proof) = signed_params::<T>(SignatureScheme::Ed25519, p);
+    }: {
+        assert!(liability.check_params(&proof, &promisee));
+    }
+
+    verify_ecdsa {
+        let p in 0 .. max_bytes(T::MaxTechnicsLength::get());
+        let (liability, promisee, proof) = signed_params::<T>(SignatureScheme::Ecdsa, p);
+    }: {
+        assert!(liability.check_params(&proof, &promisee));
+    }
+
+    set_market_fee {
+        let origin = T::AdminOrigin::successful_origin();
+    }: {
+        Module::<T>::set_market_fee(origin, 0, Perbill::from_percent(1))?;
+    }
+    verify {
+        assert_eq!(Module::<T>::market_fee(0), Perbill::from_percent(1));
+    }
+
+    create_market {
+        let origin = T::AdminOrigin::successful_origin();
+        let config = MarketConfig {
+            fee: Perbill::from_percent(1),
+            ..Module::<T>::default_market_config()
+        };
+    }: {
+        Module::<T>::create_market(origin, 1, config.clone())?;
+    }
+    verify {
+        assert_eq!(Module::<T>::market_config(1), config);
+    }
+
+    update_market {
+        let config = MarketConfig {
+            fee: Perbill::from_percent(1),
+            ..Module::<T>::default_market_config()
+        };
+        <Markets<T>>::insert(1, Module::<T>::default_market_config());
+        let origin = T::AdminOrigin::successful_origin();
+    }: {
+        Module::<T>::update_market(origin, 1, config.clone())?;
+    }
+    verify {
+        assert_eq!(Module::<T>::market_config(1), config);
+    }
+
+    bond_orders {
+        let (caller, _) = sign::<T>("//Bob", ());
+        T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value() / 4u32.into());
+    }: _(RawOrigin::Signed(caller.clone()), T::OrderBreachSlash::get())
+    verify {
+        assert_eq!(<OrderBond<T>>::get(caller), T::OrderBreachSlash::get());
+    }
+
+    unbond_orders {
+        let (caller, _) = sign::<T>("//Bob", ());
+        T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value() / 4u32.into());
+        Module::<T>::bond_orders(RawOrigin::Signed(caller.clone()).into(), T::OrderBreachSlash::get())?;
+        <BondUnlockAt<T>>::insert(&caller, BlockNumber::<T>::zero());
+    }: _(RawOrigin::Signed(caller.clone()))
+    verify {
+        assert!(!<OrderBond<T>>::contains_key(caller));
+    }
+
+    claim_order_breach {
+        let technics: TechnicalParam<T> = decode(Vec::<u8>::new());
+        let (promisee, placeholder) = sign::<T>("//Alice", ());
+        let (promisor, _) = sign::<T>("//Bob", ());
+        T::Currency::make_free_balance_be(&promisor, BalanceOf::<T>::max_value() / 4u32.into());
+        Module::<T>::bond_orders(RawOrigin::Signed(promisor.clone()).into(), T::OrderBreachSlash::get())?;
+
+        let signed_order = |seed, side, sender| {
+            let mut order = SignedOrder {
+                side,
+                technics: technics.clone(),
+                economics: economics::<T>(),
+                sender,
+                expiry: Zero::zero(),
+                nonce: 0,
+                signature: placeholder.clone(),
+            };
+            order.signature = sign::<T>(seed, order.payload()).1;
+            order
+        };
+        let order = signed_order("//Alice", Side::Demand, promisee.clone());
+        let counter_order = signed_order("//Bob", Side::Offer, promisor.clone());
+        frame_system::Module::<T>::set_block_number(One::one());
+    }: _(RawOrigin::Signed(promisee), order, counter_order)
+    verify {
+        assert!(<ClaimedOrders<T>>::contains_key(promisor, 0));
+    }
+
+    add_promisor {
+        let (who, _) = sign::<T>("//Bob", ());
+        let origin = T::AdminOrigin::successful_origin();
+    }: {
+        Module::<T>::add_promisor(origin, who.clone())?;
+    }
+    verify {
+        assert!(<ApprovedPromisors<T>>::contains_key(who));
+    }
+
+    remove_promisor {
+        let (who, _) = sign::<T>("//Bob", ());
+        <ApprovedPromisors<T>>::insert(&who, ());
+        let origin = T::AdminOrigin::successful_origin();
+    }: {
+        Module::<T>::remove_promisor(origin, who.clone())?;
+    }
+    verify {
+        assert!(!<ApprovedPromisors<T>>::contains_key(who));
+    }
+
+    blacklist {
+        let (who, _) = sign::<T>("//Bob", ());
+        let origin = T::AdminOrigin::successful_origin();
+    }: {
+        Module::<T>::blacklist(origin, who.clone())?;
+    }
+    verify {
+        assert!(<Blacklist<T>>::get(who));
+    }
+
+    unblacklist {
+        let (who, _) = sign::<T>("//Bob", ());
+        <Blacklist<T>>::insert(&who, true);
+        let origin = T::AdminOrigin::successful_origin();
+    }: {
+        Module::<T>::unblacklist(origin, who.clone())?;
+    }
+    verify {
+        assert!(!<Blacklist<T>>::get(who));
+    }
+
+    approve_firmware {
+        let firmware = T::Hash::default();
+        let origin = T::AdminOrigin::successful_origin();
+    }: {
+        Module::<T>::approve_firmware(origin, 0, firmware)?;
+    }
+    verify {
+        assert!(<ApprovedFirmware<T>>::get(0, firmware));
+    }
+
+    revoke_firmware {
+        let firmware = T::Hash::default();
+        <ApprovedFirmware<T>>::insert(0, firmware, true);
+        let origin = T::AdminOrigin::successful_origin();
+    }: {
+        Module::<T>::revoke_firmware(origin, 0, firmware)?;
+    }
+    verify {
+        assert!(!<ApprovedFirmware<T>>::get(0, firmware));
+    }
+
+    note_report_preimage {
+        let r in 0 .. max_bytes(T::MaxReportLength::get());
+        let (caller, _) = sign::<T>("//Bob", ());
+        T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value() / 4u32.into());
+        let report: TechnicalReport<T> = decode(vec![0u8; r as usize]);
+        let hash = report_hash(&report);
+    }: _(RawOrigin::Signed(caller), report)
+    verify {
+        assert!(<ReportPreimage<T>>::contains_key(hash));
+    }
+
+    prune_report_preimage {
+        let (caller, _) = sign::<T>("//Bob", ());
+        T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value() / 4u32.into());
+        let report: TechnicalReport<T> =
+            decode(vec![0u8; max_bytes(T::MaxReportLength::get()) as usize]);
+        let hash = report_hash(&report);
+        Module::<T>::note_report_preimage(RawOrigin::Signed(caller.clone()).into(), report)?;
+        let now = frame_system::Module::<T>::block_number();
+        frame_system::Module::<T>::set_block_number(
+            now.saturating_add(T::ReportChallengeWindow::get()).saturating_add(One::one())
+        );
+    }: _(RawOrigin::Signed(caller), hash)
+    verify {
+        assert!(!<ReportPreimage<T>>::contains_key(hash));
+    }
+
+    prune_liability {
+        let technics: TechnicalParam<T> = decode(Vec::<u8>::new());
+        let (promisee, _) = sign::<T>("//Alice", ());
+        let (promisor, _) = sign::<T>("//Bob", ());
+        let liability = T::Liability::new(technics, economics::<T>(), promisee, promisor);
+        let (index, _) = Module::<T>::store_liability(liability, Zero::zero());
+        Module::<T>::settle(index, true)?;
+        // Pruned index is dropped from full lists of each of its tags
+        let tags = vec![
+            Tag::Delivery,
+            Tag::Sensing,
+            Tag::Compute,
+            Tag::Inspection,
+            Tag::Maintenance,
+        ];
+        let max_tagged = T::MaxTaggedLiabilities::get();
+        for tag in tags.iter() {
+            let mut indices: Vec<_> = (1..max_tagged).map(LiabilityIndex::<T>::from).collect();
+            indices.push(index);
+            <ByTag<T>>::insert(tag, indices);
+        }
+        <TagsOf<T>>::insert(index, tags);
+        // Pruned index is inserted before many separate ranges of earlier tombstones
+        let ranges: Vec<_> = (1..=1_000u32)
+            .map(|i| {
+                let start = LiabilityIndex::<T>::from(2 * i);
+                (start, start + One::one())
+            })
+            .collect();
+        <LatestIndex<T>>::put(LiabilityIndex::<T>::from(2_001u32));
+        <Tombstones<T>>::put(ranges);
+        let origin = T::AdminOrigin::successful_origin();
+    }: {
+        Module::<T>::prune_liability(origin, index)?;
+    }
+    verify {
+        assert!(Module::<T>::is_pruned(index));
+        assert!(!<TagsOf<T>>::contains_key(index));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{new_test_ext, Runtime};
+    use frame_support::assert_ok;
+    use sp_core::{testing::KeyStore, traits::KeystoreExt};
+    use std::sync::{Arc, Mutex};
+    use tracing::{
+        span::{Attributes, Id, Record},
+        Event, Metadata, Subscriber,
+    };
+
+    fn new_benchmark_ext() -> sp_io::TestExternalities {
+        let mut ext = new_test_ext();
+        ext.register_extension(KeystoreExt(KeyStore::new()));
+        ext
+    }
+
+    #[test]
+    fn test_benchmarks() {
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_create::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_create_rate_limited::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_create_blacklisted::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_create_unaffordable::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_create_duplicate::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_finalize::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_finalize_bad_proof::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_force_finalize::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_cancel::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_commit_report::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_reveal_report::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_commit_match::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_reveal_match::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_verify_sr25519::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_verify_ed25519::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_verify_ecdsa::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_demand::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_cancel_demand::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_offer::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_cancel_offer::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_match_orders::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_register_lighthouse::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_unregister_lighthouse::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_register_witness::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_unregister_witness::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_join_pool::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_leave_pool::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_request_assignment::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_acknowledge::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_reclaim::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_force_expire::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_set_preferences::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_amend_economics::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_require_witnesses::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_notarize::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_overturn_report::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_set_market_fee::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_create_market::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_update_market::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_bond_orders::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_unbond_orders::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_claim_order_breach::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_add_promisor::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_remove_promisor::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_blacklist::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_unblacklist::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_approve_firmware::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_revoke_firmware::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_note_report_preimage::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_prune_report_preimage::<Runtime>());
+        });
+        new_benchmark_ext().execute_with(|| {
+            assert_ok!(test_benchmark_prune_liability::<Runtime>());
+        });
+    }
+
+    /// Names of created spans, span ids are their positions.
+    #[derive(Clone, Default)]
+    struct SpanRecorder(Arc<Mutex<Vec<&'static str>>>);
+
+    impl Subscriber for SpanRecorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut names = self.0.lock().unwrap();
+            names.push(span.metadata().name());
+            Id::from_u64(names.len() as u64)
+        }
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+        fn event(&self, _: &Event<'_>) {}
+        fn enter(&self, _: &Id) {}
+        fn exit(&self, _: &Id) {}
+    }
+
+    fn spans_of(benchmark: impl FnOnce()) -> Vec<&'static str> {
+        let recorder = SpanRecorder::default();
+        tracing::subscriber::with_default(recorder.clone(), benchmark);
+        let names = recorder.0.lock().unwrap().clone();
+        names
+    }
+
+    #[test]
+    fn test_benchmark_spans() {
+        let phases = [
+            "liability::verify_proofs",
+            "liability::economics",
+            "liability::storage",
+        ];
+        let spans = spans_of(|| {
+            new_benchmark_ext().execute_with(|| {
+                assert_ok!(test_benchmark_create::<Runtime>());
+            })
+        });
+        for phase in phases.iter() {
+            assert!(spans.contains(phase), "create has no {} span", phase);
+        }
+        let spans = spans_of(|| {
+            new_benchmark_ext().execute_with(|| {
+                assert_ok!(test_benchmark_finalize::<Runtime>());
+            })
+        });
+        for phase in phases.iter() {
+            assert!(spans.contains(phase), "finalize has no {} span", phase);
+        }
+    }
+}
@@ -17,8 +17,17 @@
 ///////////////////////////////////////////////////////////////////////////////
 //! Set of approaches to handle economical aspects of agreement.
 
-use crate::traits::Economical;
-use frame_support::traits::Currency;
+use crate::market::MarketId;
+use crate::traits::{
+    Attestable, Deferrable, Divisible, Economical, Marketable, NamedReservableCurrency, Party,
+    Payable, Reputable, Settlement, Valuable,
+};
+use crate::{Payouts, RESERVE_ID};
+use frame_support::{ensure, traits::BalanceStatus};
+use sp_runtime::{
+    traits::{Convert, Saturating, UniqueSaturatedFrom, UniqueSaturatedInto, Zero},
+    DispatchResult, RuntimeDebug,
+};
 
 ///
 /// Well, when we get communism it'll all be fucking great.
@@ -26,15 +35,558 @@ use frame_support::traits::Currency;
 /// Everything will be free there, everything will be an upper.
 /// We'll probably not even have to die.
 ///
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
 pub struct Communism;
 impl Economical for Communism {
     // No parameters, because everything is free.
     type Parameter = ();
 }
 
-/// Open market as approach for liability price estimation.
+impl Divisible for Communism {
+    fn scale(_unit: &(), _quantity: u32) {}
+}
+
+impl Reputable for Communism {}
+
+impl Attestable for Communism {}
+
+impl<B> Valuable<B> for Communism {}
+
+impl Marketable for Communism {}
+
+impl Deferrable for Communism {}
+
+impl<A> Payable<A> for Communism {}
+
+impl<A> Settlement<A> for Communism {
+    fn on_start(_economics: &(), _promisee: &A, _promisor: &A) -> DispatchResult {
+        Ok(())
+    }
+
+    fn on_finish(_economics: &(), _promisee: &A, _promisor: &A, _success: bool) -> DispatchResult {
+        Ok(())
+    }
+}
+
+/// Value of free work for liability statistics, it's always zero.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct Priceless;
+impl<P, B: Zero> Convert<P, B> for Priceless {
+    fn convert(_: P) -> B {
+        Zero::zero()
+    }
+}
+
+/// Open market as approach for liability price estimation, price is reserved from promisee
+/// under `RESERVE_ID`, e.g. by `NamedReserve` of liability module currency.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
 pub struct OpenMarket<T, A>(sp_std::marker::PhantomData<(T, A)>);
-impl<T: Currency<A>, A> Economical for OpenMarket<T, A> {
+impl<T: NamedReservableCurrency<A>, A> Economical for OpenMarket<T, A> {
     // Price as economical parameter for liability.
-    type Parameter = <T as Currency<A>>::Balance;
+    type Parameter = T::Balance;
+}
+
+impl<T: NamedReservableCurrency<A>, A> Divisible for OpenMarket<T, A> {
+    fn scale(unit: &Self::Parameter, quantity: u32) -> Self::Parameter {
+        unit.saturating_mul(quantity.into())
+    }
+}
+
+impl<T: NamedReservableCurrency<A>, A> Reputable for OpenMarket<T, A> {}
+
+impl<T: NamedReservableCurrency<A>, A> Attestable for OpenMarket<T, A> {}
+
+impl<T: NamedReservableCurrency<A>, A> Marketable for OpenMarket<T, A> {}
+
+impl<T: NamedReservableCurrency<A>, A> Deferrable for OpenMarket<T, A> {}
+
+impl<T: NamedReservableCurrency<A>, A> Payable<A> for OpenMarket<T, A> {}
+
+impl<T, A, B> Valuable<B> for OpenMarket<T, A>
+where
+    T: NamedReservableCurrency<A>,
+    B: UniqueSaturatedFrom<u128>,
+{
+    fn value(economics: &Self::Parameter) -> Option<B> {
+        let price: u128 = (*economics).unique_saturated_into();
+        Some(B::unique_saturated_from(price))
+    }
+}
+
+impl<T: NamedReservableCurrency<A>, A: Clone> Settlement<A> for OpenMarket<T, A> {
+    fn on_start(economics: &Self::Parameter, promisee: &A, _promisor: &A) -> DispatchResult {
+        T::reserve_named(&RESERVE_ID, promisee, *economics)
+    }
+
+    fn on_finish(
+        economics: &Self::Parameter,
+        promisee: &A,
+        promisor: &A,
+        success: bool,
+    ) -> DispatchResult {
+        let payouts = Self::preview::<T::Balance>(economics, promisee, promisor, success);
+        for (from, to, value) in payouts.transfers {
+            let status = BalanceStatus::Free;
+            T::repatriate_reserved_named(&RESERVE_ID, &from, &to, value, status)?;
+        }
+        for (who, value) in payouts.refunds {
+            ensure!(
+                T::unreserve_named(&RESERVE_ID, &who, value).is_zero(),
+                "reserved less than expected"
+            );
+        }
+        Ok(())
+    }
+
+    /// Escrowed price is paid to promisor on success and refunded to promisee on failure.
+    fn preview<B: UniqueSaturatedFrom<u128>>(
+        economics: &Self::Parameter,
+        promisee: &A,
+        promisor: &A,
+        success: bool,
+    ) -> Payouts<A, B> {
+        let price: u128 = (*economics).unique_saturated_into();
+        let price = B::unique_saturated_from(price);
+        let mut payouts = Payouts::default();
+        if success {
+            payouts
+                .transfers
+                .push((promisee.clone(), promisor.clone(), price));
+        } else {
+            payouts.refunds.push((promisee.clone(), price));
+        }
+        payouts
+    }
+
+    /// Fee of promisee is taken from escrow. Promisor keeps no collateral, so its fee is
+    /// reserved from promisor funds and paid from there. Fee is limited by price, the rest
+    /// of escrow is refunded.
+    fn on_cancel<B: UniqueSaturatedInto<u128>>(
+        economics: &Self::Parameter,
+        promisee: &A,
+        promisor: &A,
+        fee: Option<(Party, B)>,
+    ) -> DispatchResult {
+        ensure!(
+            T::reserved_balance_named(&RESERVE_ID, promisee) >= *economics,
+            "reserved less than expected"
+        );
+        let fee = fee.map(|(party, fee)| {
+            let fee: u128 = fee.unique_saturated_into();
+            (
+                party,
+                T::Balance::unique_saturated_from(fee).min(*economics),
+            )
+        });
+
+        let status = BalanceStatus::Free;
+        let refund = match fee {
+            Some((Party::Promisee, fee)) => {
+                T::repatriate_reserved_named(&RESERVE_ID, promisee, promisor, fee, status)?;
+                economics.saturating_sub(fee)
+            }
+            Some((Party::Promisor, fee)) => {
+                T::reserve_named(&RESERVE_ID, promisor, fee)?;
+                T::repatriate_reserved_named(&RESERVE_ID, promisor, promisee, fee, status)?;
+                *economics
+            }
+            None => *economics,
+        };
+        T::unreserve_named(&RESERVE_ID, promisee, refund);
+        Ok(())
+    }
+
+    /// Only difference of prices is reserved from promisee or released to it.
+    fn on_amend(
+        economics: &Self::Parameter,
+        amended: &Self::Parameter,
+        promisee: &A,
+        _promisor: &A,
+    ) -> DispatchResult {
+        if amended > economics {
+            T::reserve_named(&RESERVE_ID, promisee, amended.saturating_sub(*economics))
+        } else {
+            ensure!(
+                T::reserved_balance_named(&RESERVE_ID, promisee) >= *economics,
+                "reserved less than expected"
+            );
+            T::unreserve_named(&RESERVE_ID, promisee, economics.saturating_sub(*amended));
+            Ok(())
+        }
+    }
+}
+
+/// Economical parameter of given approach with minimal promisor reputation, requirement is
+/// part of parameter, so it's signed by both parties.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct MinReputation<E>(sp_std::marker::PhantomData<E>);
+impl<E: Economical> Economical for MinReputation<E> {
+    // Parameter of approach and minimal promisor reputation score.
+    type Parameter = (E::Parameter, u32);
+}
+
+impl<E: Divisible> Divisible for MinReputation<E> {
+    fn scale(unit: &Self::Parameter, quantity: u32) -> Self::Parameter {
+        (E::scale(&unit.0, quantity), unit.1)
+    }
+}
+
+impl<E: Economical> Reputable for MinReputation<E> {
+    fn min_reputation(economics: &Self::Parameter) -> u32 {
+        economics.1
+    }
+}
+
+impl<E: Settlement<A>, A> Settlement<A> for MinReputation<E> {
+    fn on_start(economics: &Self::Parameter, promisee: &A, promisor: &A) -> DispatchResult {
+        E::on_start(&economics.0, promisee, promisor)
+    }
+
+    fn on_finish(
+        economics: &Self::Parameter,
+        promisee: &A,
+        promisor: &A,
+        success: bool,
+    ) -> DispatchResult {
+        E::on_finish(&economics.0, promisee, promisor, success)
+    }
+
+    fn preview<B: UniqueSaturatedFrom<u128>>(
+        economics: &Self::Parameter,
+        promisee: &A,
+        promisor: &A,
+        success: bool,
+    ) -> Payouts<A, B> {
+        E::preview(&economics.0, promisee, promisor, success)
+    }
+
+    fn on_cancel<B: UniqueSaturatedInto<u128>>(
+        economics: &Self::Parameter,
+        promisee: &A,
+        promisor: &A,
+        fee: Option<(Party, B)>,
+    ) -> DispatchResult {
+        E::on_cancel(&economics.0, promisee, promisor, fee)
+    }
+
+    fn on_amend(
+        economics: &Self::Parameter,
+        amended: &Self::Parameter,
+        promisee: &A,
+        promisor: &A,
+    ) -> DispatchResult {
+        E::on_amend(&economics.0, &amended.0, promisee, promisor)
+    }
+}
+
+impl<E: Attestable> Attestable for MinReputation<E> {
+    fn strict_firmware(economics: &Self::Parameter) -> bool {
+        E::strict_firmware(&economics.0)
+    }
+}
+
+impl<E: Valuable<B>, B> Valuable<B> for MinReputation<E> {
+    fn value(economics: &Self::Parameter) -> Option<B> {
+        E::value(&economics.0)
+    }
+}
+
+impl<E: Marketable> Marketable for MinReputation<E> {
+    fn market(economics: &Self::Parameter) -> Option<MarketId> {
+        E::market(&economics.0)
+    }
+}
+
+impl<E: Deferrable> Deferrable for MinReputation<E> {
+    fn deferred_funding(economics: &Self::Parameter) -> bool {
+        E::deferred_funding(&economics.0)
+    }
+}
+
+impl<E: Payable<A>, A> Payable<A> for MinReputation<E> {
+    fn payout(economics: &Self::Parameter) -> Option<A> {
+        E::payout(&economics.0)
+    }
+}
+
+/// Economical parameter of given approach with strict firmware flag, reports of strict
+/// liabilities should attest firmware approved for robot model. Flag is part of parameter,
+/// so it's signed by both parties.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct StrictFirmware<E>(sp_std::marker::PhantomData<E>);
+impl<E: Economical> Economical for StrictFirmware<E> {
+    // Parameter of approach and strict firmware flag.
+    type Parameter = (E::Parameter, bool);
+}
+
+impl<E: Divisible> Divisible for StrictFirmware<E> {
+    fn scale(unit: &Self::Parameter, quantity: u32) -> Self::Parameter {
+        (E::scale(&unit.0, quantity), unit.1)
+    }
+}
+
+impl<E: Reputable> Reputable for StrictFirmware<E> {
+    fn min_reputation(economics: &Self::Parameter) -> u32 {
+        E::min_reputation(&economics.0)
+    }
+}
+
+impl<E: Economical> Attestable for StrictFirmware<E> {
+    fn strict_firmware(economics: &Self::Parameter) -> bool {
+        economics.1
+    }
+}
+
+impl<E: Valuable<B>, B> Valuable<B> for StrictFirmware<E> {
+    fn value(economics: &Self::Parameter) -> Option<B> {
+        E::value(&economics.0)
+    }
+}
+
+impl<E: Marketable> Marketable for StrictFirmware<E> {
+    fn market(economics: &Self::Parameter) -> Option<MarketId> {
+        E::market(&economics.0)
+    }
+}
+
+impl<E: Deferrable> Deferrable for StrictFirmware<E> {
+    fn deferred_funding(economics: &Self::Parameter) -> bool {
+        E::deferred_funding(&economics.0)
+    }
+}
+
+impl<E: Payable<A>, A> Payable<A> for StrictFirmware<E> {
+    fn payout(economics: &Self::Parameter) -> Option<A> {
+        E::payout(&economics.0)
+    }
+}
+
+impl<E: Settlement<A>, A> Settlement<A> for StrictFirmware<E> {
+    fn on_start(economics: &Self::Parameter, promisee: &A, promisor: &A) -> DispatchResult {
+        E::on_start(&economics.0, promisee, promisor)
+    }
+
+    fn on_finish(
+        economics: &Self::Parameter,
+        promisee: &A,
+        promisor: &A,
+        success: bool,
+    ) -> DispatchResult {
+        E::on_finish(&economics.0, promisee, promisor, success)
+    }
+
+    fn preview<B: UniqueSaturatedFrom<u128>>(
+        economics: &Self::Parameter,
+        promisee: &A,
+        promisor: &A,
+        success: bool,
+    ) -> Payouts<A, B> {
+        E::preview(&economics.0, promisee, promisor, success)
+    }
+
+    fn on_cancel<B: UniqueSaturatedInto<u128>>(
+        economics: &Self::Parameter,
+        promisee: &A,
+        promisor: &A,
+        fee: Option<(Party, B)>,
+    ) -> DispatchResult {
+        E::on_cancel(&economics.0, promisee, promisor, fee)
+    }
+
+    fn on_amend(
+        economics: &Self::Parameter,
+        amended: &Self::Parameter,
+        promisee: &A,
+        promisor: &A,
+    ) -> DispatchResult {
+        E::on_amend(&economics.0, &amended.0, promisee, promisor)
+    }
+}
+
+/// Economical parameter of given approach with market of liability, it takes fee and
+/// challenge window of market configuration. Market is part of parameter, so it's signed
+/// by both parties.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct InMarket<E>(sp_std::marker::PhantomData<E>);
+impl<E: Economical> Economical for InMarket<E> {
+    // Parameter of approach and market of liability.
+    type Parameter = (E::Parameter, MarketId);
+}
+
+impl<E: Divisible> Divisible for InMarket<E> {
+    fn scale(unit: &Self::Parameter, quantity: u32) -> Self::Parameter {
+        (E::scale(&unit.0, quantity), unit.1)
+    }
+}
+
+impl<E: Reputable> Reputable for InMarket<E> {
+    fn min_reputation(economics: &Self::Parameter) -> u32 {
+        E::min_reputation(&economics.0)
+    }
+}
+
+impl<E: Attestable> Attestable for InMarket<E> {
+    fn strict_firmware(economics: &Self::Parameter) -> bool {
+        E::strict_firmware(&economics.0)
+    }
+}
+
+impl<E: Valuable<B>, B> Valuable<B> for InMarket<E> {
+    fn value(economics: &Self::Parameter) -> Option<B> {
+        E::value(&economics.0)
+    }
+}
+
+impl<E: Economical> Marketable for InMarket<E> {
+    fn market(economics: &Self::Parameter) -> Option<MarketId> {
+        Some(economics.1)
+    }
+}
+
+impl<E: Deferrable> Deferrable for InMarket<E> {
+    fn deferred_funding(economics: &Self::Parameter) -> bool {
+        E::deferred_funding(&economics.0)
+    }
+}
+
+impl<E: Payable<A>, A> Payable<A> for InMarket<E> {
+    fn payout(economics: &Self::Parameter) -> Option<A> {
+        E::payout(&economics.0)
+    }
+}
+
+impl<E: Settlement<A>, A> Settlement<A> for InMarket<E> {
+    fn on_start(economics: &Self::Parameter, promisee: &A, promisor: &A) -> DispatchResult {
+        E::on_start(&economics.0, promisee, promisor)
+    }
+
+    fn on_finish(
+        economics: &Self::Parameter,
+        promisee: &A,
+        promisor: &A,
+        success: bool,
+    ) -> DispatchResult {
+        E::on_finish(&economics.0, promisee, promisor, success)
+    }
+
+    fn preview<B: UniqueSaturatedFrom<u128>>(
+        economics: &Self::Parameter,
+        promisee: &A,
+        promisor: &A,
+        success: bool,
+    ) -> Payouts<A, B> {
+        E::preview(&economics.0, promisee, promisor, success)
+    }
+
+    fn on_cancel<B: UniqueSaturatedInto<u128>>(
+        economics: &Self::Parameter,
+        promisee: &A,
+        promisor: &A,
+        fee: Option<(Party, B)>,
+    ) -> DispatchResult {
+        E::on_cancel(&economics.0, promisee, promisor, fee)
+    }
+
+    fn on_amend(
+        economics: &Self::Parameter,
+        amended: &Self::Parameter,
+        promisee: &A,
+        promisor: &A,
+    ) -> DispatchResult {
+        E::on_amend(&economics.0, &amended.0, promisee, promisor)
+    }
+}
+
+/// Economical parameter of given approach with deferred funding flag, liability with flag
+/// set is started when promisee funds it. Flag is part of parameter, so promisor proof
+/// states that promisor accepts deferred funding.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct DeferredFunding<E>(sp_std::marker::PhantomData<E>);
+impl<E: Economical> Economical for DeferredFunding<E> {
+    // Parameter of approach and deferred funding flag.
+    type Parameter = (E::Parameter, bool);
+}
+
+impl<E: Divisible> Divisible for DeferredFunding<E> {
+    fn scale(unit: &Self::Parameter, quantity: u32) -> Self::Parameter {
+        (E::scale(&unit.0, quantity), unit.1)
+    }
+}
+
+impl<E: Reputable> Reputable for DeferredFunding<E> {
+    fn min_reputation(economics: &Self::Parameter) -> u32 {
+        E::min_reputation(&economics.0)
+    }
+}
+
+impl<E: Attestable> Attestable for DeferredFunding<E> {
+    fn strict_firmware(economics: &Self::Parameter) -> bool {
+        E::strict_firmware(&economics.0)
+    }
+}
+
+impl<E: Valuable<B>, B> Valuable<B> for DeferredFunding<E> {
+    fn value(economics: &Self::Parameter) -> Option<B> {
+        E::value(&economics.0)
+    }
+}
+
+impl<E: Marketable> Marketable for DeferredFunding<E> {
+    fn market(economics: &Self::Parameter) -> Option<MarketId> {
+        E::market(&economics.0)
+    }
+}
+
+impl<E: Economical> Deferrable for DeferredFunding<E> {
+    fn deferred_funding(economics: &Self::Parameter) -> bool {
+        economics.1
+    }
+}
+
+impl<E: Payable<A>, A> Payable<A> for DeferredFunding<E> {
+    fn payout(economics: &Self::Parameter) -> Option<A> {
+        E::payout(&economics.0)
+    }
+}
+
+impl<E: Settlement<A>, A> Settlement<A> for DeferredFunding<E> {
+    fn on_start(economics: &Self::Parameter, promisee: &A, promisor: &A) -> DispatchResult {
+        E::on_start(&economics.0, promisee, promisor)
+    }
+
+    fn on_finish(
+        economics: &Self::Parameter,
+        promisee: &A,
+        promisor: &A,
+        success: bool,
+    ) -> DispatchResult {
+        E::on_finish(&economics.0, promisee, promisor, success)
+    }
+
+    fn preview<B: UniqueSaturatedFrom<u128>>(
+        economics: &Self::Parameter,
+        promisee: &A,
+        promisor: &A,
+        success: bool,
+    ) -> Payouts<A, B> {
+        E::preview(&economics.0, promisee, promisor, success)
+    }
+
+    fn on_cancel<B: UniqueSaturatedInto<u128>>(
+        economics: &Self::Parameter,
+        promisee: &A,
+        promisor: &A,
+        fee: Option<(Party, B)>,
+    ) -> DispatchResult {
+        E::on_cancel(&economics.0, promisee, promisor, fee)
+    }
+
+    fn on_amend(
+        economics: &Self::Parameter,
+        amended: &Self::Parameter,
+        promisee: &A,
+        promisor: &A,
+    ) -> DispatchResult {
+        E::on_amend(&economics.0, &amended.0, promisee, promisor)
+    }
 }
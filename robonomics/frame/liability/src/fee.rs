@@ -0,0 +1,164 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Fee of signed liability calls scaled by liability value.
+//!
+//! Weight fee of creating or finalizing liability is the same for micro-job and for
+//! expensive one. `ChargeLiabilityFee` charges on top of it `ValueFee` share of liability
+//! value, not less than `MinValueFee` and not more than `MaxValueFee`. Value is taken from
+//! economics of `create_remote` call and from economics of stored liability for
+//! `finalize_claimed` and `finalize_relayed`. Other calls and liabilities with
+//! non-monetary economics are charged by standard fee only.
+//!
+//! Extension is placed into `SignedExtra` next to transaction payment extension, it has
+//! no encoded data. Charged fee goes to `MarketFeeDestination` and isn't refunded.
+
+use codec::{Decode, Encode};
+use frame_support::traits::{
+    Currency, ExistenceRequirement, Get, IsSubType, OnUnbalanced, WithdrawReason,
+};
+use sp_runtime::{
+    traits::{
+        DispatchInfoOf, Saturating, SignedExtension, UniqueSaturatedFrom, UniqueSaturatedInto, Zero,
+    },
+    transaction_validity::{
+        InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+    },
+};
+use sp_std::marker::PhantomData;
+
+use crate::traits::Valuable;
+use crate::{AccountId, BalanceOf, Call, Liabilities, Trait};
+
+/// Charges value scaled part of liability calls fee, see module docs.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, Default)]
+pub struct ChargeLiabilityFee<T: Trait + Send + Sync>(PhantomData<T>);
+
+impl<T: Trait + Send + Sync> ChargeLiabilityFee<T> {
+    /// Utility constructor.
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+
+    /// Value of liability created or finalized by call, `None` for other calls and
+    /// non-monetary economics.
+    pub fn value_of(call: &Call<T>) -> Option<T::Balance> {
+        match call {
+            Call::create_remote(_, _, economics, _, _) => T::Economics::value(economics),
+            Call::finalize_claimed(index, ..) | Call::finalize_relayed(index, ..) => {
+                <Liabilities<T>>::get(index)
+                    .and_then(|record| T::Economics::value(record.agreement.economics()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Value scaled part of call fee, zero when call is charged by standard fee only.
+    pub fn value_fee(call: &Call<T>) -> BalanceOf<T> {
+        Self::value_of(call).map_or_else(Zero::zero, |value| {
+            let value: u128 = value.unique_saturated_into();
+            T::ValueFee::get()
+                .mul_floor(BalanceOf::<T>::unique_saturated_from(value))
+                .max(T::MinValueFee::get())
+                .min(T::MaxValueFee::get())
+        })
+    }
+}
+
+impl<T: Trait + Send + Sync> ChargeLiabilityFee<T>
+where
+    <T as frame_system::Trait>::Call: IsSubType<Call<T>>,
+{
+    /// Value scaled part of runtime call fee.
+    pub fn fee_of(call: &<T as frame_system::Trait>::Call) -> BalanceOf<T> {
+        call.is_sub_type()
+            .map_or_else(Zero::zero, |call| Self::value_fee(call))
+    }
+}
+
+impl<T: Trait + Send + Sync> sp_std::fmt::Debug for ChargeLiabilityFee<T> {
+    #[cfg(feature = "std")]
+    fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+        write!(f, "ChargeLiabilityFee")
+    }
+    #[cfg(not(feature = "std"))]
+    fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+        Ok(())
+    }
+}
+
+impl<T: Trait + Send + Sync> SignedExtension for ChargeLiabilityFee<T>
+where
+    <T as frame_system::Trait>::Call: IsSubType<Call<T>>,
+{
+    const IDENTIFIER: &'static str = "ChargeLiabilityFee";
+    type AccountId = AccountId<T>;
+    type Call = <T as frame_system::Trait>::Call;
+    type AdditionalSigned = ();
+    type Pre = ();
+
+    fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+        Ok(())
+    }
+
+    fn validate(
+        &self,
+        who: &Self::AccountId,
+        call: &Self::Call,
+        _info: &DispatchInfoOf<Self::Call>,
+        _len: usize,
+    ) -> TransactionValidity {
+        let fee = Self::fee_of(call);
+        if !fee.is_zero() {
+            // Fee is withdrawn keeping account alive
+            let free = T::Currency::free_balance(who);
+            if free < fee.saturating_add(T::Currency::minimum_balance())
+                || T::Currency::ensure_can_withdraw(
+                    who,
+                    fee,
+                    WithdrawReason::TransactionPayment.into(),
+                    free.saturating_sub(fee),
+                )
+                .is_err()
+            {
+                return Err(InvalidTransaction::Payment.into());
+            }
+        }
+        Ok(ValidTransaction::default())
+    }
+
+    fn pre_dispatch(
+        self,
+        who: &Self::AccountId,
+        call: &Self::Call,
+        _info: &DispatchInfoOf<Self::Call>,
+        _len: usize,
+    ) -> Result<(), TransactionValidityError> {
+        let fee = Self::fee_of(call);
+        if !fee.is_zero() {
+            let imbalance = T::Currency::withdraw(
+                who,
+                fee,
+                WithdrawReason::TransactionPayment.into(),
+                ExistenceRequirement::KeepAlive,
+            )
+            .map_err(|_| InvalidTransaction::Payment)?;
+            T::MarketFeeDestination::on_unbalanced(imbalance);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,304 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Invariants of module state checked by `try-runtime` after migrations and by tests.
+//!
+//! Checks only read storage, so they could be run at any block. Each violated property is
+//! told by its own error. Liabilities are checked on the latest `TRY_STATE_SAMPLE` indices,
+//! market orders and reserves of all accounts.
+//!
+//! Escrow of monetary liability economics is its value reserved from promisee under
+//! `RESERVE_ID`, e.g. by `OpenMarket`, while liability is funded and not settled. Tracked
+//! reserve of account under `RESERVE_ID` is the sum of escrows and funds reserved by the
+//! module itself. Other identifiers, e.g. jury `JURY_RESERVE_ID`, are only checked to be
+//! backed by reserved balance.
+
+use frame_support::{
+    ensure,
+    traits::{Currency, ReservableCurrency},
+    IterableStorageDoubleMap, IterableStorageMap, StorageDoubleMap, StorageMap, StorageValue,
+};
+use sp_runtime::traits::{One, Saturating, UniqueSaturatedFrom, UniqueSaturatedInto, Zero};
+use sp_std::{collections::btree_map::BTreeMap, prelude::*};
+
+use crate::traits::{Agreement, NamedReservableCurrency, RobotRegistry, Valuable};
+use crate::{
+    AccountId, AccountLiabilities, AmendmentsOf, Asks, BalanceOf, Bids, BlindedLiabilities, ByTag,
+    ClaimedArrival, Counters, EconomicalParam, FirmwareOf, HistoryOf, ImportedFrom, IndexToId,
+    LatestIndex, Liabilities, LiabilityIndex, LiabilityMarket, LiabilityRobot, LighthouseStake,
+    Module, NamedReserve, NamedReserves, OpenOrders, OrderBond, PayoutOf, PendingAcknowledgement,
+    PendingFunding, PendingSettlement, Pools, PromisorCount, PromisorLiabilities, RecordState,
+    RelayBounty, ReportPreimage, ReputationSnapshot, TagsOf, Tombstones, Trait, WitnessStake,
+    ZeroValueDeposits, RESERVE_ID,
+};
+
+/// Count of the latest liabilities checked by `try_state`.
+pub const TRY_STATE_SAMPLE: u32 = 256;
+
+impl<T: Trait> Module<T> {
+    /// Check global invariants of module state.
+    pub fn try_state() -> Result<(), &'static str> {
+        Self::try_account_indices()?;
+        Self::try_open_orders()?;
+        Self::try_reserves()?;
+        Self::try_tombstones()?;
+        Self::try_counters()?;
+        Ok(())
+    }
+
+    /// The latest allocated liability indices, at most `TRY_STATE_SAMPLE` of them.
+    fn sampled_indices() -> impl Iterator<Item = LiabilityIndex<T>> {
+        let latest = <LatestIndex<T>>::get();
+        let sample = LiabilityIndex::<T>::unique_saturated_from(TRY_STATE_SAMPLE);
+        let mut index = latest.saturating_sub(sample);
+        sp_std::iter::from_fn(move || {
+            if index >= latest {
+                return None;
+            }
+            let current = index;
+            index += One::one();
+            Some(current)
+        })
+    }
+
+    /// Open liability is indexed for both parties and exactly once by promisor local index,
+    /// promisor count is above every local index of promisor.
+    fn try_account_indices() -> Result<(), &'static str> {
+        for index in Self::sampled_indices() {
+            let record = match <Liabilities<T>>::get(index) {
+                Some(record) => record,
+                None => continue,
+            };
+            let (promisee, promisor) = (record.agreement.promisee(), record.agreement.promisor());
+            let local: Vec<_> = <PromisorLiabilities<T>>::iter_prefix(promisor).collect();
+            let count = <PromisorCount<T>>::get(promisor);
+            ensure!(
+                local.iter().all(|(local_index, _)| *local_index < count),
+                "promisor local index isn't below promisor count"
+            );
+            if record.state != RecordState::Open {
+                continue;
+            }
+            ensure!(
+                <AccountLiabilities<T>>::contains_key(promisee, index)
+                    && <AccountLiabilities<T>>::contains_key(promisor, index),
+                "open liability isn't indexed for its parties"
+            );
+            ensure!(
+                local.iter().filter(|(_, global)| *global == index).count() == 1,
+                "open liability isn't indexed once by promisor"
+            );
+        }
+        Ok(())
+    }
+
+    /// Open orders count of each account is the count of its listed demands and offers.
+    fn try_open_orders() -> Result<(), &'static str> {
+        let mut counted = BTreeMap::<AccountId<T>, u32>::new();
+        for (_, order) in <Asks<T>>::iter().chain(<Bids<T>>::iter()) {
+            *counted.entry(order.sender).or_default() += 1;
+        }
+        let stored: BTreeMap<_, _> = <OpenOrders<T>>::iter().collect();
+        ensure!(stored == counted, "open orders count mismatch");
+        Ok(())
+    }
+
+    /// Escrow of liability economics reserved while it's funded and not settled.
+    fn escrow_of(economics: &EconomicalParam<T>) -> BalanceOf<T> {
+        <T::Economics as Valuable<T::Balance>>::value(economics)
+            .map(|value| {
+                let value: u128 = value.unique_saturated_into();
+                BalanceOf::<T>::unique_saturated_from(value)
+            })
+            .unwrap_or_else(Zero::zero)
+    }
+
+    /// Tracked reserve of account is backed by its reserved balance and equals the sum of
+    /// escrows of its liabilities, orders funds, bounties, deposits and stakes reserved by
+    /// the module and robot deposits of registry. Escrows of blinded liabilities not
+    /// reported yet are only checked in total.
+    fn try_reserves() -> Result<(), &'static str> {
+        let mut tracked = BTreeMap::<AccountId<T>, BalanceOf<T>>::new();
+        for (_, who, amount) in <NamedReserves<T>>::iter() {
+            let total = tracked.entry(who).or_insert_with(Zero::zero);
+            *total = total.saturating_add(amount);
+        }
+        for (who, total) in tracked.iter() {
+            ensure!(
+                *total <= T::Currency::reserved_balance(who),
+                "tracked reserve isn't backed by reserved balance"
+            );
+        }
+
+        let mut required = BTreeMap::<AccountId<T>, BalanceOf<T>>::new();
+        let mut require = |who: AccountId<T>, amount: BalanceOf<T>| {
+            let total = required.entry(who).or_insert_with(Zero::zero);
+            *total = total.saturating_add(amount);
+        };
+        for (_, order) in <Asks<T>>::iter().chain(<Bids<T>>::iter()) {
+            require(order.sender, order.deposit.saturating_add(order.escrow));
+        }
+        for (index, record) in <Liabilities<T>>::iter() {
+            // Escrow is reserved when liability is funded and released when it's settled
+            let open =
+                record.state == RecordState::Open && !<PendingFunding<T>>::contains_key(index);
+            if open || <PendingSettlement<T>>::contains_key(index) {
+                let economics = record.agreement.economics();
+                require(
+                    record.agreement.promisee().clone(),
+                    Self::escrow_of(economics),
+                );
+            }
+        }
+        for (index, bounty) in <RelayBounty<T>>::iter().chain(<ZeroValueDeposits<T>>::iter()) {
+            if let Some(record) = <Liabilities<T>>::get(index) {
+                require(record.agreement.promisee().clone(), bounty);
+            }
+        }
+        for (index, (_, deposit)) in <PendingAcknowledgement<T>>::iter() {
+            if let Some(record) = <Liabilities<T>>::get(index) {
+                require(record.agreement.promisor().clone(), deposit);
+            }
+        }
+        for (_, lighthouse, stake) in <LighthouseStake<T>>::iter() {
+            require(lighthouse, stake);
+        }
+        for (_, pool) in <Pools<T>>::iter() {
+            for (member, _, stake, _) in pool {
+                require(member, stake);
+            }
+        }
+        for (witness, stake) in <WitnessStake<T>>::iter() {
+            require(witness, stake);
+        }
+        for (who, bond) in <OrderBond<T>>::iter() {
+            require(who, bond);
+        }
+        for (_, preimage) in <ReportPreimage<T>>::iter() {
+            if let Some((depositor, deposit)) = preimage.depositor {
+                require(depositor, deposit);
+            }
+        }
+        for (owner, deposit) in T::Robots::deposits() {
+            require(owner, deposit);
+        }
+
+        // Promisee of blinded liability isn't known, its escrow is only accounted in total
+        let mut blinded: BalanceOf<T> = Zero::zero();
+        for (_, liability) in <BlindedLiabilities<T>>::iter() {
+            if liability.report.is_none() {
+                blinded = blinded.saturating_add(Self::escrow_of(&liability.economics));
+            }
+        }
+        let mut unaccounted: BalanceOf<T> = Zero::zero();
+        for (who, reserved) in <NamedReserves<T>>::iter_prefix(RESERVE_ID) {
+            let amount = required.remove(&who).unwrap_or_else(Zero::zero);
+            ensure!(
+                reserved >= amount,
+                "tracked reserve doesn't equal module reservations"
+            );
+            unaccounted = unaccounted.saturating_add(reserved - amount);
+        }
+        ensure!(
+            required.values().all(Zero::is_zero) && unaccounted == blinded,
+            "tracked reserve doesn't equal module reservations"
+        );
+        Ok(())
+    }
+
+    /// Tombstones are sorted disjoint ranges of allocated indices without records and
+    /// per-index entries removed by pruning, no record is stored beyond allocated indices.
+    fn try_tombstones() -> Result<(), &'static str> {
+        let ranges = <Tombstones<T>>::get();
+        let latest = <LatestIndex<T>>::get();
+        ensure!(
+            !<Liabilities<T>>::contains_key(latest),
+            "record is stored at unallocated index"
+        );
+        let mut previous_end = None;
+        for (start, end) in ranges {
+            ensure!(
+                start < end && end <= latest,
+                "tombstone range isn't of allocated indices"
+            );
+            ensure!(
+                previous_end.map_or(true, |previous_end| previous_end < start),
+                "tombstone ranges aren't sorted and merged"
+            );
+            let mut index = start;
+            while index < end {
+                ensure!(
+                    !<Liabilities<T>>::contains_key(index) && !<HistoryOf<T>>::contains_key(index),
+                    "tombstoned index has live record"
+                );
+                ensure!(
+                    !<TagsOf<T>>::contains_key(index)
+                        && !<IndexToId<T>>::contains_key(index)
+                        && !<PayoutOf<T>>::contains_key(index)
+                        && !<LiabilityRobot<T>>::contains_key(index)
+                        && !<ReputationSnapshot<T>>::contains_key(index)
+                        && !<AmendmentsOf<T>>::contains_key(index)
+                        && !<ImportedFrom<T>>::contains_key(index)
+                        && !<LiabilityMarket<T>>::contains_key(index)
+                        && !<FirmwareOf<T>>::contains_key(index)
+                        && !<ClaimedArrival<T>>::contains_key(index),
+                    "tombstoned index has per-index entry"
+                );
+                index += One::one();
+            }
+            previous_end = Some(end);
+        }
+
+        let indexed = <AccountLiabilities<T>>::iter().map(|(_, index, _)| index);
+        let tagged = <ByTag<T>>::iter().flat_map(|(_, indices)| indices);
+        for index in indexed.chain(tagged) {
+            ensure!(!Self::is_pruned(index), "tombstoned index is indexed");
+        }
+        Ok(())
+    }
+
+    /// Count of created liabilities is the count of allocated indices, closed liabilities
+    /// of sample are counted by finalized and cancelled counters.
+    fn try_counters() -> Result<(), &'static str> {
+        let counters = <Counters<T>>::get();
+        let latest: u64 = <LatestIndex<T>>::get().unique_saturated_into();
+        ensure!(
+            counters.created == latest,
+            "created counter isn't count of indices"
+        );
+        let closed_total = counters.finalized.saturating_add(counters.cancelled);
+        ensure!(
+            closed_total <= counters.created,
+            "closed counters exceed created counter"
+        );
+
+        // Liabilities waiting for witnessed settlement aren't counted yet
+        let closed_sample = Self::sampled_indices()
+            .filter(|index| {
+                <Liabilities<T>>::get(index).map_or(false, |record| {
+                    record.state != RecordState::Open
+                        && !<PendingSettlement<T>>::contains_key(index)
+                })
+            })
+            .count() as u64;
+        ensure!(
+            closed_sample <= closed_total,
+            "closed liabilities of sample exceed closed counters"
+        );
+        Ok(())
+    }
+}
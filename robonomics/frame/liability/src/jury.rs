@@ -0,0 +1,606 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Jury of staked jurors resolving disputed witnessed reports, for markets that don't trust
+//! a single arbitrator or governance.
+//!
+//! Account joins juror pool with `JurorStake` reserved. Juror leaves pool at once, its stake
+//! is kept reserved for `UnbondingPeriod` blocks and until disputes it's drawn for are
+//! decided, so it's slashed for votes given before leaving.
+//!
+//! Liability party disputes witnessed report of jury market during its challenge window:
+//! `DisputeFee` is reserved from it and report isn't settled at the end of the window.
+//! Dispute carries typed `Evidence`: reason code, optional reference to off-chain evidence
+//! and optional counter-liability, so disputes could be triaged without parsing blobs.
+//! Jury of `JurySize` jurors other than parties is drawn from pool by on-chain randomness.
+//! Jurors commit to their votes during `CommitWindow` blocks and reveal them during
+//! `RevealWindow` blocks after, so votes couldn't be copied.
+//!
+//! Majority of revealed votes decides: upheld report is settled in promisor favor, overturned
+//! one as failed with its witnesses slashed. Jurors voted with majority share the fee and
+//! `MisalignedSlash` of stakes of jurors voted against it or not revealed. Dispute with less
+//! than `JuryQuorum` revealed votes or a tie is escalated to governance: fee is returned,
+//! jurors not revealed are slashed and report is held until `AdminOrigin` overturns
+//! or upholds it.
+
+use codec::{Compact, Decode, Encode};
+use frame_support::{
+    debug, decl_error, decl_event, decl_module, decl_storage, ensure,
+    traits::{BalanceStatus, EnsureOrigin, Get, Randomness, ReservableCurrency},
+    weights::Weight,
+    StorageDoubleMap, StorageMap, StorageValue,
+};
+use frame_system::ensure_signed;
+use sp_core::H256;
+use sp_runtime::{
+    traits::{One, Saturating, UniqueSaturatedFrom, Zero},
+    DispatchResult, Perbill, RuntimeDebug,
+};
+use sp_std::prelude::*;
+
+use crate::market::MarketId;
+use crate::reserve::NamedReserve;
+use crate::traits::{Agreement, NamedReservableCurrency, ReserveIdentifier};
+use crate::{
+    vote_commitment, AccountId, BalanceOf, BlockNumber, LiabilityIndex, WeightInfo, LOG_TARGET,
+};
+
+/// Randomness subject of jury drawing for disputed liability.
+pub const JURY_SUBJECT: &[u8] = b"liability/jury";
+
+/// Identifier of juror stakes and dispute fees reserved by module, they're tracked apart
+/// from liability `RESERVE_ID` reservations.
+pub const JURY_RESERVE_ID: ReserveIdentifier = *b"liabjury";
+
+/// Machine-readable reason of dispute.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum ReasonCode {
+    /// Promisor reported results never delivered.
+    NotDelivered,
+    /// Results delivered to wrong location.
+    WrongLocation,
+    /// Results data is corrupted.
+    DataCorrupt,
+    /// Results delivered after agreed deadline.
+    LateDelivery,
+    /// Other reason, it's described by off-chain evidence.
+    Other,
+}
+
+/// Evidence of dispute validated structurally when dispute is opened.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Evidence<LiabilityIndex> {
+    /// Reason of dispute.
+    pub reason: ReasonCode,
+    /// Hash of off-chain evidence, required for `ReasonCode::Other`.
+    pub reference: Option<H256>,
+    /// Existing liability other than disputed one, e.g. delivery the report contradicts.
+    pub counter_liability: Option<LiabilityIndex>,
+}
+
+/// Evidence of current runtime.
+pub type EvidenceOf<T> = Evidence<LiabilityIndex<T>>;
+
+/// Dispute of witnessed report before jury.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Dispute<AccountId, Balance, BlockNumber, LiabilityIndex> {
+    /// Liability party opened the dispute and paid its fee.
+    pub challenger: AccountId,
+    /// Evidence of challenger.
+    pub evidence: Evidence<LiabilityIndex>,
+    /// Fee reserved from challenger, it's shared by jurors voted with majority.
+    pub fee: Balance,
+    /// Jurors drawn for the dispute.
+    pub jury: Vec<AccountId>,
+    /// The last block of vote commitments.
+    pub commit_end: BlockNumber,
+    /// The last block of vote reveals, votes are counted at the next block.
+    pub reveal_end: BlockNumber,
+}
+
+/// Dispute of current runtime.
+pub type DisputeOf<T> = Dispute<AccountId<T>, BalanceOf<T>, BlockNumber<T>, LiabilityIndex<T>>;
+
+/// Jury module main trait.
+pub trait Trait: crate::Trait {
+    /// Stake reserved from juror, see `join_jury`.
+    type JurorStake: Get<BalanceOf<Self>>;
+
+    /// Maximal count of jurors in pool.
+    type MaxJurors: Get<u32>;
+
+    /// Count of blocks stake of juror left pool is kept reserved.
+    type UnbondingPeriod: Get<BlockNumber<Self>>;
+
+    /// Count of jurors drawn for dispute, even count is rounded up to odd one.
+    type JurySize: Get<u32>;
+
+    /// Minimal count of revealed votes deciding dispute.
+    type JuryQuorum: Get<u32>;
+
+    /// Count of blocks jurors commit to votes after dispute is opened.
+    type CommitWindow: Get<BlockNumber<Self>>;
+
+    /// Count of blocks jurors reveal votes after commit window.
+    type RevealWindow: Get<BlockNumber<Self>>;
+
+    /// Fee reserved from liability party opening dispute.
+    type DisputeFee: Get<BalanceOf<Self>>;
+
+    /// Part of juror stake slashed when juror votes against majority or doesn't reveal.
+    type MisalignedSlash: Get<Perbill>;
+
+    /// The overarching event type.
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+}
+
+decl_event! {
+    pub enum Event<T>
+    where AccountId = AccountId<T>,
+          Balance = BalanceOf<T>,
+          BlockNumber = BlockNumber<T>,
+          LiabilityIndex = LiabilityIndex<T>,
+    {
+        /// Disputes of market witnessed reports decided by jury or not: market, enabled.
+        JuryMarketSet(MarketId, bool),
+
+        /// Juror joined pool: juror, stake.
+        JurorJoined(AccountId, Balance),
+
+        /// Juror left pool, its stake is unbonding: juror, the last block of unbonding.
+        JurorLeft(AccountId, BlockNumber),
+
+        /// Stake of juror returned: juror, returned funds.
+        StakeWithdrawn(AccountId, Balance),
+
+        /// Witnessed report disputed and jury drawn: index, challenger, reason, jury.
+        DisputeOpened(Compact<LiabilityIndex>, AccountId, ReasonCode, Vec<AccountId>),
+
+        /// Juror committed to its vote: index, juror.
+        VoteCommitted(Compact<LiabilityIndex>, AccountId),
+
+        /// Juror revealed its vote: index, juror, report is upheld.
+        VoteRevealed(Compact<LiabilityIndex>, AccountId, bool),
+
+        /// Dispute decided by majority of jury: index, report is upheld, reason of dispute.
+        DisputeDecided(Compact<LiabilityIndex>, bool, ReasonCode),
+
+        /// Jury didn't reach quorum or decision, report is held until governance resolves
+        /// it: index, reason of dispute.
+        DisputeEscalated(Compact<LiabilityIndex>, ReasonCode),
+
+        /// Juror voted with majority rewarded: juror, funds.
+        JurorRewarded(AccountId, Balance),
+
+        /// Juror voted against majority or not revealed slashed: juror, funds.
+        JurorSlashed(AccountId, Balance),
+    }
+}
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        /// Account is already juror or its stake is unbonding.
+        AlreadyJuror,
+        /// Account isn't juror of pool.
+        NotJuror,
+        /// Juror pool has `MaxJurors` jurors.
+        PoolFull,
+        /// Stake of account isn't unbonding.
+        NotUnbonding,
+        /// Unbonding period of stake isn't passed yet.
+        StakeUnbonding,
+        /// Juror is drawn for dispute not decided yet.
+        JurorBusy,
+        /// Disputes of liability market aren't decided by jury.
+        NotJuryMarket,
+        /// Only liability party disputes its report.
+        NotParty,
+        /// Liability is already disputed.
+        AlreadyDisputed,
+        /// Pool has less than `JurySize` jurors other than liability parties.
+        NotEnoughJurors,
+        /// Challenger can't afford `DisputeFee`.
+        CannotAffordFee,
+        /// Evidence of `ReasonCode::Other` has no reference, or reference is zero hash.
+        MissingReference,
+        /// Counter-liability of evidence is unknown or it's the disputed liability.
+        BadCounterLiability,
+        /// Liability isn't disputed.
+        UnknownDispute,
+        /// Account isn't drawn for jury of dispute.
+        NotDrawn,
+        /// Commit window of dispute is passed.
+        CommitClosed,
+        /// Dispute isn't in its reveal window.
+        RevealClosed,
+        /// Juror already committed to its vote.
+        AlreadyCommitted,
+        /// Juror didn't commit to vote.
+        NotCommitted,
+        /// Juror already revealed its vote.
+        AlreadyRevealed,
+        /// Revealed vote doesn't match its commitment, see `vote_commitment`.
+        BadReveal,
+    }
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as LiabilityJury {
+        /// Markets disputes of which are decided by jury.
+        JuryMarkets get(fn is_jury_market): map hasher(twox_64_concat) MarketId => bool;
+        /// Jurors of pool, jury is drawn from them.
+        Jurors get(fn jurors): Vec<AccountId<T>>;
+        /// Stake reserved from juror, slashes are taken from it.
+        JurorStakes get(fn juror_stake): map hasher(blake2_128_concat) AccountId<T> => Option<BalanceOf<T>>;
+        /// The last block of stake unbonding of juror left pool.
+        UnbondingUntil get(fn unbonding_until): map hasher(blake2_128_concat)
+                                                AccountId<T> => Option<BlockNumber<T>>;
+        /// Count of undecided disputes juror is drawn for.
+        JurorDisputes get(fn juror_disputes): map hasher(blake2_128_concat) AccountId<T> => u32;
+        /// Dispute of liability witnessed report.
+        Disputes get(fn dispute): map hasher(blake2_128_concat) LiabilityIndex<T> => Option<DisputeOf<T>>;
+        /// Vote commitments of jurors, see `vote_commitment`.
+        VoteCommitments get(fn vote_commitment_of): double_map hasher(blake2_128_concat) LiabilityIndex<T>,
+                                                    hasher(blake2_128_concat) AccountId<T> => Option<H256>;
+        /// Revealed votes of jurors, vote is set when report is upheld.
+        Votes get(fn vote): double_map hasher(blake2_128_concat) LiabilityIndex<T>,
+                            hasher(blake2_128_concat) AccountId<T> => Option<bool>;
+        /// Disputes counted at given block, i.e. the next block after their reveal window.
+        DisputesEndingAt: map hasher(twox_64_concat) BlockNumber<T> => Vec<LiabilityIndex<T>>;
+        /// Count of disputes of reason decided by jury: reports overturned, reports upheld.
+        ReasonStats get(fn reason_stats): map hasher(twox_64_concat) ReasonCode => (u32, u32);
+    }
+}
+
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        type Error = Error<T>;
+
+        fn deposit_event() = default;
+
+        fn on_initialize(n: T::BlockNumber) -> Weight {
+            let ending = <DisputesEndingAt<T>>::take(n);
+            ending.into_iter().fold(T::DbWeight::get().reads_writes(1, 1), |weight, index| {
+                weight.saturating_add(Self::count_votes(index))
+            })
+        }
+
+        /// Set disputes of market witnessed reports decided by jury or not, open disputes
+        /// are decided anyway.
+        #[weight = 50_000_000]
+        fn set_jury_market(origin, market_id: MarketId, enabled: bool) {
+            T::AdminOrigin::ensure_origin(origin)?;
+            if enabled {
+                <JuryMarkets>::insert(market_id, true);
+            } else {
+                <JuryMarkets>::remove(market_id);
+            }
+            Self::deposit_event(RawEvent::JuryMarketSet(market_id, enabled));
+        }
+
+        /// Join juror pool, `JurorStake` is reserved from sender.
+        #[weight = 50_000_000]
+        fn join_jury(origin) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!<JurorStakes<T>>::contains_key(&sender), Error::<T>::AlreadyJuror);
+            let mut jurors = <Jurors<T>>::get();
+            ensure!(
+                jurors.len() < T::MaxJurors::get() as usize,
+                Error::<T>::PoolFull
+            );
+
+            let stake = T::JurorStake::get();
+            NamedReserve::<T>::reserve_named(&JURY_RESERVE_ID, &sender, stake)?;
+            jurors.push(sender.clone());
+            <Jurors<T>>::put(jurors);
+            <JurorStakes<T>>::insert(&sender, stake);
+            Self::deposit_event(RawEvent::JurorJoined(sender, stake));
+        }
+
+        /// Leave juror pool, sender isn't drawn anymore and its stake is unbonding
+        /// for `UnbondingPeriod` blocks.
+        #[weight = 50_000_000]
+        fn leave_jury(origin) {
+            let sender = ensure_signed(origin)?;
+            let mut jurors = <Jurors<T>>::get();
+            let position = jurors
+                .iter()
+                .position(|juror| juror == &sender)
+                .ok_or(Error::<T>::NotJuror)?;
+            jurors.remove(position);
+            <Jurors<T>>::put(jurors);
+
+            let now = <frame_system::Module<T>>::block_number();
+            let until = now.saturating_add(T::UnbondingPeriod::get());
+            <UnbondingUntil<T>>::insert(&sender, until);
+            Self::deposit_event(RawEvent::JurorLeft(sender, until));
+        }
+
+        /// Return stake of sender left pool after its unbonding period, when disputes
+        /// sender is drawn for are decided.
+        #[weight = 50_000_000]
+        fn withdraw_stake(origin) {
+            let sender = ensure_signed(origin)?;
+            let until = <UnbondingUntil<T>>::get(&sender).ok_or(Error::<T>::NotUnbonding)?;
+            let now = <frame_system::Module<T>>::block_number();
+            ensure!(now > until, Error::<T>::StakeUnbonding);
+            ensure!(<JurorDisputes<T>>::get(&sender) == 0, Error::<T>::JurorBusy);
+
+            let stake = <JurorStakes<T>>::take(&sender).unwrap_or_else(Zero::zero);
+            <UnbondingUntil<T>>::remove(&sender);
+            NamedReserve::<T>::unreserve_named(&JURY_RESERVE_ID, &sender, stake);
+            Self::deposit_event(RawEvent::StakeWithdrawn(sender, stake));
+        }
+
+        /// Dispute witnessed report of jury market during its challenge window by
+        /// liability party with given evidence, `DisputeFee` is reserved from sender and
+        /// jury is drawn.
+        #[weight = 100_000_000]
+        fn open_dispute(origin, #[compact] index: LiabilityIndex<T>, evidence: EvidenceOf<T>) {
+            let sender = ensure_signed(origin)?;
+            ensure!(!<Disputes<T>>::contains_key(index), Error::<T>::AlreadyDisputed);
+            let record = <crate::Module<T>>::record_of(index)?;
+            let liability = &record.agreement;
+            let (promisee, promisor) = (liability.promisee(), liability.promisor());
+            ensure!(
+                promisee == &sender || promisor == &sender,
+                Error::<T>::NotParty
+            );
+            Self::check_evidence(index, &evidence)?;
+            let (market_id, _) = <crate::Module<T>>::liability_market(index, liability);
+            ensure!(<JuryMarkets>::get(market_id), Error::<T>::NotJuryMarket);
+
+            let candidates: Vec<_> = <Jurors<T>>::get()
+                .into_iter()
+                .filter(|juror| juror != promisee && juror != promisor)
+                .collect();
+            let size = (T::JurySize::get() | 1) as usize;
+            ensure!(candidates.len() >= size, Error::<T>::NotEnoughJurors);
+            let fee = T::DisputeFee::get();
+            ensure!(
+                T::Currency::can_reserve(&sender, fee),
+                Error::<T>::CannotAffordFee
+            );
+
+            <crate::Module<T>>::hold_settlement(index)?;
+            NamedReserve::<T>::reserve_named(&JURY_RESERVE_ID, &sender, fee)?;
+            let random = T::Randomness::random(&(JURY_SUBJECT, index).encode());
+            let jury = draw_jury(candidates, size, random.as_ref());
+            for juror in jury.iter() {
+                <JurorDisputes<T>>::mutate(juror, |disputes| *disputes += 1);
+            }
+
+            let now = <frame_system::Module<T>>::block_number();
+            let commit_end = now.saturating_add(T::CommitWindow::get());
+            let reveal_end = commit_end.saturating_add(T::RevealWindow::get());
+            <DisputesEndingAt<T>>::mutate(reveal_end.saturating_add(One::one()), |indices| {
+                indices.push(index)
+            });
+            let reason = evidence.reason;
+            <Disputes<T>>::insert(index, Dispute {
+                challenger: sender.clone(),
+                evidence,
+                fee,
+                jury: jury.clone(),
+                commit_end,
+                reveal_end,
+            });
+            Self::deposit_event(RawEvent::DisputeOpened(index.into(), sender, reason, jury));
+        }
+
+        /// Commit to vote on disputed report by drawn juror during commit window,
+        /// see `vote_commitment`.
+        #[weight = 50_000_000]
+        fn commit_vote(origin, #[compact] index: LiabilityIndex<T>, commitment: H256) {
+            let sender = ensure_signed(origin)?;
+            let dispute = <Disputes<T>>::get(index).ok_or(Error::<T>::UnknownDispute)?;
+            ensure!(dispute.jury.contains(&sender), Error::<T>::NotDrawn);
+            let now = <frame_system::Module<T>>::block_number();
+            ensure!(now <= dispute.commit_end, Error::<T>::CommitClosed);
+            ensure!(
+                !<VoteCommitments<T>>::contains_key(index, &sender),
+                Error::<T>::AlreadyCommitted
+            );
+            <VoteCommitments<T>>::insert(index, &sender, commitment);
+            Self::deposit_event(RawEvent::VoteCommitted(index.into(), sender));
+        }
+
+        /// Reveal committed vote on disputed report during reveal window, `uphold` is set
+        /// when report should be settled in promisor favor.
+        #[weight = 50_000_000]
+        fn reveal_vote(origin, #[compact] index: LiabilityIndex<T>, uphold: bool, salt: H256) {
+            let sender = ensure_signed(origin)?;
+            let dispute = <Disputes<T>>::get(index).ok_or(Error::<T>::UnknownDispute)?;
+            let now = <frame_system::Module<T>>::block_number();
+            ensure!(
+                now > dispute.commit_end && now <= dispute.reveal_end,
+                Error::<T>::RevealClosed
+            );
+            let commitment =
+                <VoteCommitments<T>>::get(index, &sender).ok_or(Error::<T>::NotCommitted)?;
+            ensure!(
+                !<Votes<T>>::contains_key(index, &sender),
+                Error::<T>::AlreadyRevealed
+            );
+            ensure!(
+                vote_commitment(&index, &sender, uphold, &salt) == commitment,
+                Error::<T>::BadReveal
+            );
+            <Votes<T>>::insert(index, &sender, uphold);
+            Self::deposit_event(RawEvent::VoteRevealed(index.into(), sender, uphold));
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Share of jury decided disputes of reason that overturned report, it weights
+    /// disputes of the reason against promisor.
+    pub fn acceptance_rate(reason: ReasonCode) -> Perbill {
+        let (overturned, upheld) = <ReasonStats>::get(reason);
+        Perbill::from_rational_approximation(overturned, overturned.saturating_add(upheld))
+    }
+
+    /// Check evidence structure of dispute of given liability.
+    fn check_evidence(index: LiabilityIndex<T>, evidence: &EvidenceOf<T>) -> DispatchResult {
+        match evidence.reference {
+            Some(reference) => ensure!(!reference.is_zero(), Error::<T>::MissingReference),
+            None => ensure!(
+                evidence.reason != ReasonCode::Other,
+                Error::<T>::MissingReference
+            ),
+        }
+        if let Some(counter) = evidence.counter_liability {
+            ensure!(
+                counter != index && <crate::Liabilities<T>>::contains_key(counter),
+                Error::<T>::BadCounterLiability
+            );
+        }
+        Ok(())
+    }
+
+    /// Count votes of dispute at the end of its reveal window, decided dispute resolves its
+    /// report.
+    fn count_votes(index: LiabilityIndex<T>) -> Weight {
+        let dispute = match <Disputes<T>>::take(index) {
+            Some(dispute) => dispute,
+            None => return T::DbWeight::get().reads(1),
+        };
+        let votes: Vec<_> = dispute
+            .jury
+            .iter()
+            .map(|juror| (juror.clone(), <Votes<T>>::take(index, juror)))
+            .collect();
+        <VoteCommitments<T>>::remove_prefix(index);
+        for juror in dispute.jury.iter() {
+            <JurorDisputes<T>>::mutate_exists(juror, |disputes| {
+                *disputes = disputes
+                    .and_then(|count| count.checked_sub(1))
+                    .filter(|count| *count > 0)
+            });
+        }
+        let weight = T::DbWeight::get()
+            .reads_writes(1 + 3 * votes.len() as Weight, 2 + 4 * votes.len() as Weight);
+
+        let upheld = votes.iter().filter(|(_, vote)| *vote == Some(true)).count();
+        let overturned = votes
+            .iter()
+            .filter(|(_, vote)| *vote == Some(false))
+            .count();
+        if upheld + overturned < T::JuryQuorum::get() as usize || upheld == overturned {
+            NamedReserve::<T>::unreserve_named(&JURY_RESERVE_ID, &dispute.challenger, dispute.fee);
+            for (juror, _) in votes.iter().filter(|(_, vote)| vote.is_none()) {
+                Self::slash_juror(juror, &[]);
+            }
+            Self::deposit_event(RawEvent::DisputeEscalated(
+                index.into(),
+                dispute.evidence.reason,
+            ));
+            return weight;
+        }
+
+        // Fee and slashes of misaligned jurors are shared by jurors voted with majority
+        let uphold = upheld > overturned;
+        let (aligned, misaligned): (Vec<_>, Vec<_>) = votes
+            .into_iter()
+            .partition(|(_, vote)| *vote == Some(uphold));
+        let aligned: Vec<_> = aligned.into_iter().map(|(juror, _)| juror).collect();
+        let shared = Self::share(&dispute.challenger, dispute.fee, &aligned);
+        NamedReserve::<T>::unreserve_named(
+            &JURY_RESERVE_ID,
+            &dispute.challenger,
+            dispute.fee.saturating_sub(shared),
+        );
+        for (juror, _) in misaligned {
+            Self::slash_juror(&juror, &aligned);
+        }
+
+        <ReasonStats>::mutate(dispute.evidence.reason, |(overturned, upheld)| {
+            let decided = if uphold { upheld } else { overturned };
+            *decided = decided.saturating_add(1)
+        });
+        Self::deposit_event(RawEvent::DisputeDecided(
+            index.into(),
+            uphold,
+            dispute.evidence.reason,
+        ));
+        if let Err(e) = <crate::Module<T>>::resolve_settlement(index, uphold) {
+            debug::native::warn!(
+                target: LOG_TARGET,
+                "disputed report isn't resolved: index={:?}, error={:?}",
+                index,
+                e,
+            );
+        }
+        weight.saturating_add(<T as crate::Trait>::WeightInfo::overturn_report(
+            T::MaxWitnesses::get(),
+        ))
+    }
+
+    /// Slash `MisalignedSlash` of juror stake, slashed funds are shared by given jurors
+    /// or burned without them.
+    fn slash_juror(juror: &AccountId<T>, beneficiaries: &[AccountId<T>]) {
+        let stake = <JurorStakes<T>>::get(juror).unwrap_or_else(Zero::zero);
+        let slash = T::MisalignedSlash::get() * stake;
+        let slashed = if beneficiaries.is_empty() {
+            let (_, not_slashed) =
+                NamedReserve::<T>::slash_reserved_named(&JURY_RESERVE_ID, juror, slash);
+            slash.saturating_sub(not_slashed)
+        } else {
+            Self::share(juror, slash, beneficiaries)
+        };
+        <JurorStakes<T>>::insert(juror, stake.saturating_sub(slashed));
+        Self::deposit_event(RawEvent::JurorSlashed(juror.clone(), slashed));
+    }
+
+    /// Pay equal parts of funds reserved from account to given jurors, returns paid funds.
+    /// Remainder of division is left reserved.
+    fn share(from: &AccountId<T>, amount: BalanceOf<T>, jurors: &[AccountId<T>]) -> BalanceOf<T> {
+        if jurors.is_empty() {
+            return Zero::zero();
+        }
+        let part = amount / BalanceOf::<T>::unique_saturated_from(jurors.len() as u128);
+        let mut paid = Zero::zero();
+        for juror in jurors {
+            let not_moved = NamedReserve::<T>::repatriate_reserved_named(
+                &JURY_RESERVE_ID,
+                from,
+                juror,
+                part,
+                BalanceStatus::Free,
+            )
+            .unwrap_or(part);
+            let moved = part.saturating_sub(not_moved);
+            paid = paid.saturating_add(moved);
+            Self::deposit_event(RawEvent::JurorRewarded(juror.clone(), moved));
+        }
+        paid
+    }
+}
+
+/// Draw `size` distinct candidates by partial Fisher-Yates shuffle, position of each draw
+/// is taken from hash of seed and draw number.
+pub fn draw_jury<A>(mut candidates: Vec<A>, size: usize, seed: &[u8]) -> Vec<A> {
+    let size = size.min(candidates.len());
+    for i in 0..size {
+        let hash = sp_io::hashing::blake2_256(&(seed, i as u32).encode());
+        let point = u64::decode(&mut &hash[..]).unwrap_or_default();
+        let j = i + (point % (candidates.len() - i) as u64) as usize;
+        candidates.swap(i, j);
+    }
+    candidates.truncate(size);
+    candidates
+}
@@ -0,0 +1,123 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Liabilities imported from the legacy Ethereum robonomics contracts.
+//!
+//! Bridge relays finalized legacy liability together with proof of its contract storage in
+//! Ethereum state, see `robonomics_liability_core::eth`. State root and legacy factory are
+//! anchored by governance. Imported liability is stored finalized with outcome of contract
+//! and it's read-only: its economics isn't run, it's never reported, settled or cancelled.
+//! Each contract is imported once, later imports of the same address fail.
+
+use frame_support::{ensure, weights::Weight, StorageMap, StorageValue};
+use robonomics_liability_core::eth::{verify_legacy, LegacyRecord, ProofNodes};
+use sp_core::H160;
+use sp_runtime::DispatchError;
+use sp_std::prelude::*;
+
+use crate::traits::{Agreement, FromLegacy};
+use crate::{
+    Counters, Error, ImportedFrom, LegacyAnchor, LegacyImports, Liabilities, LiabilityIndex,
+    Module, RawEvent, RecordState, Trait, Transition, CREATE_WEIGHT,
+};
+
+/// Maximal count of trie nodes of legacy liability proof.
+pub const MAX_LEGACY_PROOF_NODES: u32 = 128;
+
+/// Weight of hashing and decoding byte of legacy liability proof.
+pub const LEGACY_PROOF_BYTE_WEIGHT: Weight = 10_000;
+
+/// Legacy liabilities with IPFS objective as technics and free economics. Ethereum
+/// accounts are mapped as EVM addresses by Frontier, `blake2_256("evm:" ++ address)`.
+pub struct LegacyObjective;
+
+impl<T, E, A> FromLegacy<T, E, A> for LegacyObjective
+where
+    T: From<Vec<u8>>,
+    E: Default,
+    A: From<[u8; 32]>,
+{
+    fn from_legacy(record: &LegacyRecord) -> Option<(T, E, A, A)> {
+        Some((
+            record.objective.clone().into(),
+            E::default(),
+            evm_account(&record.promisee),
+            evm_account(&record.promisor),
+        ))
+    }
+}
+
+/// Account of Ethereum address, `blake2_256("evm:" ++ address)`.
+pub fn evm_account<A: From<[u8; 32]>>(address: &H160) -> A {
+    let mut data = b"evm:".to_vec();
+    data.extend_from_slice(address.as_bytes());
+    sp_io::hashing::blake2_256(&data).into()
+}
+
+impl<T: Trait> Module<T> {
+    /// Weight of `import_legacy` with proof of given encoded length.
+    pub fn import_legacy_weight(proof_length: u32) -> Weight {
+        CREATE_WEIGHT
+            .saturating_add(LEGACY_PROOF_BYTE_WEIGHT.saturating_mul(proof_length.into()))
+            .saturating_add(T::DbWeight::get().reads_writes(4, 13))
+    }
+
+    /// Verify legacy liability by proof against anchored state, store it finalized and
+    /// return its index.
+    pub(crate) fn import_legacy_record(
+        legacy: LegacyRecord,
+        proof: Vec<Vec<u8>>,
+    ) -> Result<LiabilityIndex<T>, DispatchError> {
+        let (factory, state_root) = <LegacyAnchor>::get().ok_or(Error::<T>::LegacyStateUnset)?;
+        ensure!(
+            !<LegacyImports<T>>::contains_key(&legacy.address),
+            Error::<T>::AlreadyImported
+        );
+        ensure!(
+            proof.len() <= MAX_LEGACY_PROOF_NODES as usize,
+            Error::<T>::LegacyProofTooLarge
+        );
+        verify_legacy(&state_root, &factory, &legacy, &ProofNodes::new(&proof))
+            .map_err(|_| Error::<T>::BadLegacyProof)?;
+        let (technics, economics, promisee, promisor) =
+            T::Legacy::from_legacy(&legacy).ok_or(Error::<T>::UnsupportedLegacy)?;
+        Self::ensure_technics_length(&technics)?;
+
+        let block_number = <frame_system::Module<T>>::block_number();
+        let liability = T::Liability::new(technics, economics, promisee, promisor);
+        let (index, _) = Self::store_liability(liability, block_number);
+        <Liabilities<T>>::mutate(index, |record| {
+            if let Some(record) = record {
+                record.state = RecordState::Imported(legacy.success);
+                record.finalized_at = Some(block_number);
+                Self::schedule_prune(index, &record.agreement, block_number);
+            }
+        });
+        <Counters<T>>::mutate(|counters| {
+            if legacy.success {
+                counters.finalized += 1;
+            } else {
+                counters.cancelled += 1;
+            }
+        });
+        Self::record_transition(index, block_number, Transition::Imported(legacy.address));
+        <LegacyImports<T>>::insert(legacy.address, index);
+        <ImportedFrom<T>>::insert(index, legacy.address);
+        Self::deposit_event(RawEvent::LegacyImported(index.into(), legacy.address));
+        Ok(index)
+    }
+}
@@ -18,22 +18,85 @@
 //! The Robonomics runtime module. This can be compiled with `#[no_std]`, ready for Wasm.
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use codec::{Codec, Decode, Encode};
-use frame_support::{decl_error, decl_event, decl_module, decl_storage, ensure, StorageValue};
-use frame_system::ensure_none;
-use sp_runtime::transaction_validity::{
-    InvalidTransaction, TransactionPriority, TransactionSource, TransactionValidity,
-    ValidTransaction,
+use codec::{Compact, Decode, Encode, FullCodec};
+use frame_support::{
+    debug, decl_error, decl_event, decl_module, decl_storage,
+    dispatch::{DispatchErrorWithPostInfo, DispatchResultWithPostInfo, WithPostDispatchInfo},
+    ensure,
+    storage::{with_transaction, TransactionOutcome},
+    traits::{
+        BalanceStatus, Currency, EnsureOrigin, ExistenceRequirement, Get, Imbalance, OnUnbalanced,
+        Randomness, ReservableCurrency,
+    },
+    weights::{GetDispatchInfo, Weight},
+    IterableStorageDoubleMap, Parameter, StorageDoubleMap, StorageMap, StorageValue,
 };
-use sp_std::prelude::*;
+use frame_system::{
+    ensure_none, ensure_root, ensure_signed,
+    offchain::{AppCrypto, CreateSignedTransaction, SigningTypes},
+};
+use robonomics_liability_core::mmr;
+use sp_core::{crypto::KeyTypeId, H160, H256};
+use sp_runtime::{
+    traits::{
+        AtLeast32BitUnsigned, Convert, One, Saturating, UniqueSaturatedFrom, UniqueSaturatedInto,
+        Zero,
+    },
+    transaction_validity::{
+        InvalidTransaction, TransactionPriority, TransactionSource, TransactionValidity,
+        TransactionValidityError, ValidTransaction,
+    },
+    DispatchError, DispatchResult, Perbill, RuntimeDebug,
+};
+use sp_std::{convert::TryFrom, prelude::*};
 
 /// Import module traits.
 pub mod traits;
+use market::{select_weighted, CancellationFee, MarketConfig, MarketId, Order, OrderId, Side};
+use reserve::NamedReserve;
+use signed::SignedOrder;
 use traits::*;
 
+pub use pallet_robonomics_liability_rpc_runtime_api::{
+    liability_id, match_commitment, offchain_report_key, report_hash, report_storage_key,
+    technics_hash, verify_report_proof, vote_commitment, AccountBloom, AccountStats, Capabilities,
+    CostInfo, CreatedBetween, DryRun, EconomicsSummary, EraStats, EventPayload, FinalizationLeaf,
+    LegacyRecord, LiabilityConstants, LiabilityCounters, LiabilityEvent, LiabilityInfo,
+    LiabilityRecord, LiabilityState, LiabilityTimeline, MarketTotals, MarketVolume, MatchInfo,
+    MmrProof, ModelRanking, ModelStats, OrderBookDepth, OrderBookEntry, OutcomeCounts, Page,
+    Payouts, PriceLevel, RecordState, ReportInfo, ReportProofError, ReputationInfo, RobotId,
+    RobotInfo, Submitter, Transition, ValidityError, BLOOM_DIGEST_PREFIX, MAX_PAGE_ITEMS,
+    MAX_PAGE_SCAN, OFFCHAIN_GATEWAY_KEY, OFFCHAIN_LIGHTHOUSE_KEY,
+};
+pub use robonomics_liability_core::{
+    acknowledge_payload, amend_payload, attested_report_payload, blinded_params_payload,
+    blinded_report_payload, bounty_params_payload, cancel_payload, claim_payload, commit_payload,
+    expiring_params_payload, notarize_payload, params_payload, party_commitment, payload_digest,
+    report_commitment, report_payload, restricted_params_payload, rotate_payload,
+    tagged_params_payload, witnessing_payload, PayloadHasher, PromisorRequirement, Tag, ACK_DOMAIN,
+    AMEND_DOMAIN, BLIND_DOMAIN, BLIND_REPORT_DOMAIN, BOUNTY_DOMAIN, CANCEL_DOMAIN, CLAIM_DOMAIN,
+    COMMIT_DOMAIN, NOTARY_DOMAIN, RESTRICT_DOMAIN, ROTATE_DOMAIN, WITNESS_DOMAIN,
+};
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
 pub mod economics;
+pub mod fee;
+#[cfg(any(test, feature = "try-runtime"))]
+pub mod invariants;
+pub mod jury;
+pub mod legacy;
+pub mod market;
+pub mod migration;
+pub mod offchain;
+pub mod registry;
+pub mod reputation;
+pub mod reserve;
 pub mod signed;
 pub mod technics;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod testing;
+pub mod weights;
 
 /// Type synonym for technical trait parameter.
 pub type TechnicalParam<T> = <<T as Trait>::Technics as Technical>::Parameter;
@@ -54,441 +117,18130 @@ pub type LiabilityIndex<T> =
 /// Current runtime account identificator.
 pub type AccountId<T> = <T as frame_system::Trait>::AccountId;
 
-/// Liability module main trait.
-pub trait Trait: frame_system::Trait {
-    /// Technical aspects of agreement.
-    type Technics: Technical;
+/// Current runtime block number.
+pub type BlockNumber<T> = <T as frame_system::Trait>::BlockNumber;
 
-    /// Economical aspects of agreement.
-    type Economics: Economical;
+/// Decoded liability view for current runtime.
+pub type LiabilityInfoOf<T> = LiabilityInfo<
+    TechnicalParam<T>,
+    EconomicalParam<T>,
+    AccountId<T>,
+    BlockNumber<T>,
+    <T as Trait>::Balance,
+>;
 
-    /// How to make and process agreement between two parties.
-    type Liability: Codec
-        + Processing
-        + Agreement<Self::Technics, Self::Economics, AccountId = AccountId<Self>>;
+/// Page of liabilities list for current runtime, items are liabilities with their indices.
+pub type LiabilityPageOf<T> = Page<LiabilityIndex<T>, (LiabilityIndex<T>, LiabilityInfoOf<T>)>;
 
-    /// The overarching event type.
-    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
-}
+/// Stored liability record for current runtime.
+pub type LiabilityRecordOf<T> = LiabilityRecord<<T as Trait>::Liability, H256, BlockNumber<T>>;
 
-decl_event! {
-    pub enum Event<T>
-    where AccountId = AccountId<T>,
-          LiabilityIndex = LiabilityIndex<T>,
-          TechnicalParam = TechnicalParam<T>,
-          EconomicalParam = EconomicalParam<T>,
-          TechnicalReport = TechnicalReport<T>,
-    {
-        /// Yay! New liability created.
-        NewLiability(LiabilityIndex, TechnicalParam, EconomicalParam, AccountId, AccountId),
+/// Leaf of finalizations range for current runtime.
+pub type FinalizationLeafOf<T> = FinalizationLeaf<LiabilityIndex<T>, AccountId<T>>;
 
-        /// Liability report published.
-        NewReport(LiabilityIndex, TechnicalReport),
-    }
-}
+/// Stored report preimage for current runtime.
+pub type PreimageOf<T> = Preimage<TechnicalReport<T>, AccountId<T>, BalanceOf<T>, BlockNumber<T>>;
 
-decl_error! {
-    pub enum Error for Module<T: Trait> {
-        /// Promisor agreement proof verification failed
-        BadPromisorProof,
-        /// Promisee agreement proof verification failed
-        BadPromiseeProof,
-        /// Promisor report proof verification failed
-        BadReportProof,
-        /// Unable to decode liability at given index
-        LiabilityDecodeFailure,
-    }
-}
+/// Stored account preferences for current runtime.
+pub type AccountPrefsOf<T> = AccountPrefs<AccountId<T>>;
 
-decl_storage! {
-    trait Store for Module<T: Trait> as Liability {
-        /// Latest liability index.
-        LatestIndex get(fn latest_index): LiabilityIndex<T>;
-        /// SCALE-encoded liability parameters.
-        LiabilityOf get(fn liability_of): map hasher(blake2_128_concat)
-                                          LiabilityIndex<T> => Vec<u8>;
-        /// Set `true` when liability report already send.
-        IsFinalized get(fn is_finalized): map hasher(blake2_128_concat)
-                                          LiabilityIndex<T> => bool;
-        /// SCALE-encoded liability report.
-        ReportOf    get(fn report_of): map hasher(blake2_128_concat)
-                                       LiabilityIndex<T> => Vec<u8>;
-    }
-}
+/// Liability in privacy mode for current runtime.
+pub type BlindedLiabilityOf<T> =
+    BlindedLiability<TechnicalParam<T>, EconomicalParam<T>, AccountId<T>, BlockNumber<T>>;
 
-decl_module! {
-    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
-        fn deposit_event() = default;
+/// Market funds type of current runtime.
+pub type BalanceOf<T> = <<T as Trait>::Currency as Currency<AccountId<T>>>::Balance;
 
-        /// Create agreement between two parties.
-        #[weight = 200_000_000]
-        fn create(
-            origin,
-            technics: TechnicalParam<T>,
-            economics: EconomicalParam<T>,
-            promisee: AccountId<T>,
-            promisor: AccountId<T>,
-            promisee_proof: ProofParam<T>,
-            promisor_proof: ProofParam<T>,
-        ) {
-            ensure_none(origin)?;
+/// Market funds imbalance of current runtime.
+pub type NegativeImbalanceOf<T> =
+    <<T as Trait>::Currency as Currency<AccountId<T>>>::NegativeImbalance;
 
-            // Create liability
-            let liability = T::Liability::new(
-                technics.clone(),
-                economics.clone(),
-                promisee.clone(),
-                promisor.clone(),
-            );
+/// Signed market order of current runtime.
+pub type SignedOrderOf<T> =
+    SignedOrder<TechnicalParam<T>, EconomicalParam<T>, AccountId<T>, BlockNumber<T>, ProofParam<T>>;
 
-            // Check promisee proof
-            if !liability.check_params(&promisee_proof, &promisee) {
-                Err(Error::<T>::BadPromiseeProof)?
-            }
+/// Market order of current runtime.
+pub type OrderOf<T> = Order<
+    TechnicalParam<T>,
+    EconomicalParam<T>,
+    AccountId<T>,
+    BlockNumber<T>,
+    ProofParam<T>,
+    BalanceOf<T>,
+>;
 
-            // Check promisor proof
-            if !liability.check_params(&promisor_proof, &promisor) {
-                Err(Error::<T>::BadPromisorProof)?
-            }
+/// Market configuration of current runtime.
+pub type MarketConfigOf<T> = MarketConfig<BalanceOf<T>, BlockNumber<T>>;
 
-            // Run economical processing
-            liability.on_start()?;
+/// Market match view for current runtime.
+pub type MatchInfoOf<T> = MatchInfo<LiabilityIndex<T>, BlockNumber<T>, <T as Trait>::Balance>;
 
-            // Store liability params as bytestring
-            let latest_index = <LatestIndex<T>>::get();
-            liability.using_encoded(|encoded|
-                <LiabilityOf<T>>::insert(latest_index, Vec::from(encoded))
-            );
-            <LatestIndex<T>>::put(latest_index + 1.into());
+/// Era statistics snapshot for current runtime.
+pub type EraStatsOf<T> = EraStats<BlockNumber<T>, <T as Trait>::Balance>;
 
-            // Emit event
-            Self::deposit_event(RawEvent::NewLiability(
-                latest_index,
-                technics,
-                economics,
-                promisee,
-                promisor,
-            ));
-        }
+/// Decoded liability report view for current runtime.
+pub type ReportInfoOf<T> = ReportInfo<TechnicalReport<T>, BlockNumber<T>>;
 
-        /// Publish technical report of complite works.
-        #[weight = 200_000_000]
-        fn finalize(
-            origin,
-            index: LiabilityIndex<T>,
-            report: TechnicalReport<T>,
-            proof: ProofParam<T>,
-        ) {
-            ensure_none(origin)?;
+/// Promisee requirement to promisor for current runtime.
+pub type PromisorRequirementOf<T> = PromisorRequirement<AccountId<T>>;
 
-            // Is liability already finalized?
-            ensure!(!<IsFinalized<T>>::get(index), "already finalized");
+/// Log target of liability module, e.g. `-lruntime::liability=trace`.
+pub const LOG_TARGET: &str = "runtime::liability";
 
-            // Decode liability from storage
-            if let Ok(liability) = T::Liability::decode(&mut &<LiabilityOf<T>>::get(index)[..]) {
-                // Check report proof
-                if !liability.check_report(&index, &report, &proof) {
-                    Err(Error::<T>::BadReportProof)?
-                }
+/// Encoded size limit of liability module event with 32 bytes accounts, variable-length
+/// fields are committed to by hash to keep block events small.
+pub const MAX_EVENT_SIZE: usize = 160;
 
-                // Run economical processing
-                // TODO: get parameter from oracle
-                liability.on_finish(true)?;
+/// Identifier of funds reserved by liability module: order deposits and escrow, lighthouse
+/// stakes, signed orders bonds and report preimage deposits.
+pub const RESERVE_ID: ReserveIdentifier = *b"liablty0";
 
-                // Store report as bytestring
-                report.using_encoded(|bytes| <ReportOf<T>>::insert(index, Vec::from(bytes)));
+/// Randomness subject of promisor selection for demand, see `request_assignment`.
+pub const ASSIGNMENT_SUBJECT: &[u8] = b"liability/assign";
 
-                // Set finalized flag
-                <IsFinalized<T>>::insert(index, true);
+/// Key type of liability promisor keys used by off-chain worker.
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"liab");
 
-                // Emit event
-                Self::deposit_event(RawEvent::NewReport(index, report));
-            } else {
-                Err(Error::<T>::LiabilityDecodeFailure)?
-            }
-        }
+/// Application crypto of liability off-chain worker keys.
+pub mod crypto {
+    use super::KEY_TYPE;
+    use sp_runtime::{
+        app_crypto::{app_crypto, sr25519},
+        MultiSignature, MultiSigner,
+    };
+
+    app_crypto!(sr25519, KEY_TYPE);
+
+    /// Promisor key that signs liability reports in off-chain worker.
+    pub struct ReportAuthId;
+    impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for ReportAuthId {
+        type RuntimeAppPublic = Public;
+        type GenericSignature = sp_core::sr25519::Signature;
+        type GenericPublic = sp_core::sr25519::Public;
     }
 }
 
-impl<T: Trait> frame_support::unsigned::ValidateUnsigned for Module<T> {
-    type Call = Call<T>;
+/// Storage releases of the module.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Releases {
+    /// Liabilities by global index, untracked storage version.
+    V1_0_0,
+    /// Liabilities by promisor and local index, block numbers and account index.
+    V2_0_0,
+    /// Liabilities and reports stored as typed values instead of byte vectors.
+    V3_0_0,
+    /// Aggregate liability counters.
+    V4_0_0,
+    /// Liabilities indexed by creation block.
+    V5_0_0,
+    /// Liabilities and reports bounded by `MaxTechnicsLength` and `MaxReportLength`.
+    V6_0_0,
+    /// Liability parameters, state, block numbers and report merged into `Liabilities`.
+    V7_0_0,
+    /// Expired orders swept by `ExpirySweepCursor` instead of carried over list.
+    V8_0_0,
+    /// Reports moved to `ReportPreimage`, `Liabilities` records keep report hash.
+    V9_0_0,
+    /// Funds reserved by module tracked under `RESERVE_ID` in `NamedReserves`.
+    V10_0_0,
+    /// Market fees moved to `Markets` configurations.
+    V11_0_0,
+    /// Market configurations with cancellation fee.
+    V12_0_0,
+    /// Unsigned calls limits removed at the end of block instead of the start of the next one.
+    V13_0_0,
+    /// Market configurations with report retention, report preimages queued by expiry block
+    /// in `PreimagesExpiringAt`.
+    V14_0_0,
+    /// Block indexed lists swept from cursors bounded by `MaxSweepItemsPerBlock`.
+    V15_0_0,
+    /// Finalized liabilities queued in `PrunableAt` by the end of market report retention.
+    V16_0_0,
+}
 
-    fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
-        match call {
-            Call::create(
-                technics,
-                economics,
-                promisee,
-                promisor,
-                promisee_proof,
-                promisor_proof,
-            ) => {
-                let liability = T::Liability::new(
-                    technics.clone(),
-                    economics.clone(),
-                    promisee.clone(),
-                    promisor.clone(),
-                );
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1_0_0
+    }
+}
 
-                if !liability.check_params(promisee_proof, promisee) {
-                    return InvalidTransaction::BadProof.into();
-                }
+/// Storage release of the current module code.
+pub const STORAGE_VERSION: Releases = Releases::V16_0_0;
 
-                if !liability.check_params(promisor_proof, promisor) {
-                    return InvalidTransaction::BadProof.into();
-                }
+/// Maximal count of liabilities of promisor visited by a call rotating its key, the rest
+/// is rotated by `continue_rotation`.
+pub const MAX_ROTATION_BATCH: u32 = 32;
 
-                Ok(ValidTransaction {
-                    priority: TransactionPriority::max_value(),
-                    requires: Default::default(),
-                    provides: vec![(technics, economics, promisee, promisor).encode()],
-                    longevity: 64_u64,
-                    propagate: true,
-                })
-            }
+/// Maximal count of technics models kept at leaderboard of each ranking, the lowest are
+/// evicted.
+pub const MAX_LEADERBOARD_LENGTH: u32 = 100;
 
-            Call::finalize(index, report, proof) => {
-                match T::Liability::decode(&mut &<LiabilityOf<T>>::get(*index)[..]) {
-                    Ok(liability) => {
-                        if !liability.check_report(index, report, proof) {
-                            return InvalidTransaction::BadProof.into();
-                        }
+/// Maximal count of transitions kept in liability history, the oldest are dropped.
+pub const MAX_HISTORY_LENGTH: u32 = 16;
 
-                        Ok(ValidTransaction {
-                            priority: TransactionPriority::max_value(),
-                            requires: Default::default(),
-                            provides: vec![(index, report).encode()],
-                            longevity: 64_u64,
-                            propagate: true,
-                        })
-                    }
-                    _ => InvalidTransaction::Call.into(),
-                }
-            }
+/// Technical report kept in state by its hash, see `ReportPreimage`.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Preimage<TechnicalReport, AccountId, Balance, BlockNumber> {
+    /// Technical report of complete works.
+    pub report: TechnicalReport,
+    /// Account that noted preimage and its deposit, `None` for preimages of accepted
+    /// reports noted by `finalize`.
+    pub depositor: Option<(AccountId, Balance)>,
+    /// Preimage could be pruned after this block.
+    pub expires_at: BlockNumber,
+}
 
-            _ => InvalidTransaction::Call.into(),
-        }
+/// Liability in privacy mode, see `create_blinded`: parties are kept as commitments to
+/// their accounts, successful liability pays payout account named in signed agreement.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct BlindedLiability<TechnicalParam, EconomicalParam, AccountId, BlockNumber> {
+    /// Technical parameter of agreement.
+    pub technics: TechnicalParam,
+    /// Economical parameter of agreement.
+    pub economics: EconomicalParam,
+    /// Commitment to promisee account, see `party_commitment`.
+    pub promisee: H256,
+    /// Commitment to promisor account.
+    pub promisor: H256,
+    /// Account paid for successful liability instead of promisor.
+    pub payout: AccountId,
+    /// Block of liability creation.
+    pub created_at: BlockNumber,
+    /// Hash of liability report, it's set when liability is finalized.
+    pub report: Option<H256>,
+}
+
+/// Default auxiliary parameters of account liabilities, see `set_preferences`.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct AccountPrefs<AccountId> {
+    /// Arbitrator preferred by account.
+    pub arbitrator: Option<AccountId>,
+    /// Account paid instead of account for liabilities it promised, unless economics
+    /// names another one.
+    pub payout: Option<AccountId>,
+    /// Hash of opaque topic account is notified on.
+    pub topic: Option<H256>,
+}
+
+impl<AccountId> AccountPrefs<AccountId> {
+    /// Returns `true` when no preference is set.
+    pub fn is_empty(&self) -> bool {
+        self.arbitrator.is_none() && self.payout.is_none() && self.topic.is_none()
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::economics::Communism;
-    use super::signed::{ProofSigner, SignedLiability};
-    use super::technics::PureIPFS;
-    use super::*;
-    use crate as liability;
-    use base58::FromBase58;
-    use frame_support::{
-        assert_err, assert_ok, impl_outer_event, impl_outer_origin, parameter_types,
-        weights::Weight,
-    };
-    use node_primitives::{AccountId, Signature};
-    use sp_core::{crypto::Pair, sr25519, H256};
-    use sp_runtime::{
-        testing::Header,
-        traits::{IdentifyAccount, IdentityLookup, Verify},
-        Perbill,
-    };
+/// Work budget of single `on_initialize` sweep, at most `MaxSweepItemsPerBlock` items.
+/// Sweep out of budget keeps its cursor in storage and continues in the next block.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+struct SweepBudget {
+    /// Count of items left.
+    left: u32,
+    /// Storage reads of sweep.
+    reads: Weight,
+    /// Storage writes of sweep.
+    writes: Weight,
+    /// Weight of sweep items besides storage access.
+    computed: Weight,
+}
 
-    impl_outer_event! {
-        pub enum MetaEvent for Runtime {
-            frame_system<T>, liability<T>,
+impl SweepBudget {
+    fn new<T: Trait>() -> Self {
+        SweepBudget {
+            left: T::MaxSweepItemsPerBlock::get(),
+            reads: 0,
+            writes: 0,
+            computed: 0,
         }
     }
 
-    impl_outer_origin! {
-        pub enum Origin for Runtime {}
+    /// Take an item of budget, `false` when budget is exhausted.
+    fn take(&mut self) -> bool {
+        if self.left == 0 {
+            return false;
+        }
+        self.left -= 1;
+        true
     }
 
-    #[derive(Clone, PartialEq, Eq, Debug)]
-    pub struct Runtime;
-
-    parameter_types! {
-        pub const BlockHashCount: u64 = 250;
-        pub const MaximumBlockWeight: Weight = 1024;
-        pub const MaximumBlockLength: u32 = 2 * 1024;
-        pub const AvailableBlockRatio: Perbill = Perbill::one();
+    /// Account storage access of sweep.
+    fn access(&mut self, reads: Weight, writes: Weight) {
+        self.reads = self.reads.saturating_add(reads);
+        self.writes = self.writes.saturating_add(writes);
     }
 
-    impl frame_system::Trait for Runtime {
-        type Origin = Origin;
-        type Index = u64;
-        type BlockNumber = u64;
-        type Call = ();
-        type Hash = H256;
-        type Hashing = ::sp_runtime::traits::BlakeTwo256;
-        type AccountId = AccountId;
-        type Lookup = IdentityLookup<Self::AccountId>;
-        type Header = Header;
-        type Event = MetaEvent;
-        type BlockHashCount = BlockHashCount;
-        type MaximumBlockWeight = MaximumBlockWeight;
-        type MaximumBlockLength = MaximumBlockLength;
-        type AvailableBlockRatio = AvailableBlockRatio;
-        type Version = ();
-        type ModuleToIndex = ();
-        type AccountData = ();
-        type OnNewAccount = ();
-        type OnKilledAccount = ();
-        type DbWeight = ();
-        type BlockExecutionWeight = ();
-        type ExtrinsicBaseWeight = ();
-        type MaximumExtrinsicWeight = ();
-        type BaseCallFilter = ();
-        type SystemWeightInfo = ();
+    /// Account weight of sweep item, e.g. weight of call it does the same as.
+    fn charge(&mut self, weight: Weight) {
+        self.computed = self.computed.saturating_add(weight);
     }
 
-    impl Trait for Runtime {
-        type Event = MetaEvent;
-        type Technics = PureIPFS;
-        type Economics = Communism;
-        type Liability = SignedLiability<
-            Self::Technics,
-            Self::Economics,
-            Signature,
-            <Signature as Verify>::Signer,
-            AccountId,
-        >;
+    fn weight<T: frame_system::Trait>(&self) -> Weight {
+        T::DbWeight::get()
+            .reads_writes(self.reads, self.writes)
+            .saturating_add(self.computed)
     }
+}
 
-    fn new_test_ext() -> sp_io::TestExternalities {
-        let storage = frame_system::GenesisConfig::default()
-            .build_storage::<Runtime>()
-            .unwrap();
-        storage.into()
-    }
+/// Base weight of liability `create` call.
+pub const CREATE_WEIGHT: Weight = 200_000_000;
 
-    type Liability = Module<Runtime>;
+/// Base weight of liability `finalize` call.
+pub const FINALIZE_WEIGHT: Weight = 200_000_000;
 
-    #[test]
-    fn test_initial_setup() {
-        new_test_ext().execute_with(|| {
-            assert_eq!(Liability::latest_index(), 0);
-        });
-    }
+/// Weight functions of liability module calls.
+///
+/// Calls verifying proofs are measured with sr25519 proofs, their weights are adjusted
+/// to schemes of proofs actually used with `verify_*` weights.
+pub trait WeightInfo {
+    /// Create liability with technical parameter of `t` encoded bytes.
+    fn create(t: u32) -> Weight;
+    /// Finalize liability with report of `r` encoded bytes.
+    fn finalize(r: u32) -> Weight;
+    /// Reject finalize with invalid proof of report of `r` encoded bytes.
+    fn finalize_bad_proof(r: u32) -> Weight;
+    /// Verify sr25519 proof of `p` encoded payload bytes.
+    fn verify_sr25519(p: u32) -> Weight;
+    /// Verify ed25519 proof of `p` encoded payload bytes.
+    fn verify_ed25519(p: u32) -> Weight;
+    /// Verify ecdsa proof of `p` encoded payload bytes.
+    fn verify_ecdsa(p: u32) -> Weight;
+    /// Approve promisor account.
+    fn add_promisor() -> Weight;
+    /// Revoke promisor account approval.
+    fn remove_promisor() -> Weight;
+    /// Finalize liability without report.
+    fn force_finalize() -> Weight;
+    /// Post demand with technical parameter of `t` encoded bytes.
+    fn demand(t: u32) -> Weight;
+    /// Remove demand.
+    fn cancel_demand() -> Weight;
+    /// Post offer with technical parameter of `t` encoded bytes.
+    fn offer(t: u32) -> Weight;
+    /// Remove offer.
+    fn cancel_offer() -> Weight;
+    /// Set market fee.
+    fn set_market_fee() -> Weight;
+    /// Create market configuration.
+    fn create_market() -> Weight;
+    /// Update market configuration.
+    fn update_market() -> Weight;
+    /// Fund deferred funding liability.
+    fn fund() -> Weight;
+    /// Cancel liability before report.
+    fn cancel() -> Weight;
+    /// Amend liability economics.
+    fn amend_economics() -> Weight;
+    /// Commit to liability report.
+    fn commit_report() -> Weight;
+    /// Reveal committed report of `r` encoded bytes.
+    fn reveal_report(r: u32) -> Weight;
+    /// Create liability of matched demand and offer.
+    fn match_orders() -> Weight;
+    /// Commit to match of demand and offer.
+    fn commit_match() -> Weight;
+    /// Create liability of committed match of demand and offer.
+    fn reveal_match() -> Weight;
+    /// Register report witness.
+    fn register_witness() -> Weight;
+    /// Set account preferences.
+    fn set_preferences() -> Weight;
+    /// Unregister report witness.
+    fn unregister_witness() -> Weight;
+    /// Join assignment pool of market.
+    fn join_pool() -> Weight;
+    /// Leave assignment pool of market.
+    fn leave_pool() -> Weight;
+    /// Request promisor assignment of demand.
+    fn request_assignment() -> Weight;
+    /// Acknowledge liability by promisor.
+    fn acknowledge() -> Weight;
+    /// Reclaim unacknowledged liability by promisee.
+    fn reclaim() -> Weight;
+    /// Cancel liability open for more than its lifetime.
+    fn force_expire() -> Weight;
+    /// Require witness quorum for liability settlement.
+    fn require_witnesses() -> Weight;
+    /// Notarize liability report.
+    fn notarize() -> Weight;
+    /// Overturn witnessed report notarized by `w` witnesses.
+    fn overturn_report(w: u32) -> Weight;
+    /// Register lighthouse of market.
+    fn register_lighthouse() -> Weight;
+    /// Unregister lighthouse of market.
+    fn unregister_lighthouse() -> Weight;
+    /// Bond funds of signed orders.
+    fn bond_orders() -> Weight;
+    /// Unlock or return bond of signed orders.
+    fn unbond_orders() -> Weight;
+    /// Claim breach of signed orders.
+    fn claim_order_breach() -> Weight;
+    /// Blacklist account.
+    fn blacklist() -> Weight;
+    /// Remove account from blacklist.
+    fn unblacklist() -> Weight;
+    /// Approve firmware of robot model.
+    fn approve_firmware() -> Weight;
+    /// Revoke firmware approval of robot model.
+    fn revoke_firmware() -> Weight;
+    /// Note report preimage of `r` encoded bytes.
+    fn note_report_preimage(r: u32) -> Weight;
+    /// Remove expired report preimage.
+    fn prune_report_preimage() -> Weight;
+    /// Prune record of finalized liability.
+    fn prune_liability() -> Weight;
+}
 
-    fn get_params_proof(
-        uri: &str,
-        technics: &TechnicalParam<Runtime>,
-        economics: &EconomicalParam<Runtime>,
-    ) -> (AccountId, ProofParam<Runtime>) {
-        let pair = sr25519::Pair::from_string(uri, None).unwrap();
-        let sender = <Signature as Verify>::Signer::from(pair.public()).into_account();
-        let signature = <ProofSigner<sr25519::Pair> as ProofBuilder<
-            <Runtime as Trait>::Technics,
-            <Runtime as Trait>::Economics,
-            LiabilityIndex<Runtime>,
-            _,
-            _,
-        >>::proof_params(technics, economics, pair)
-        .into();
-        (sender, signature)
+/// Constant weights of tests, runtimes take `weights::SubstrateWeight`.
+impl WeightInfo for () {
+    fn create(_t: u32) -> Weight {
+        CREATE_WEIGHT
     }
-
-    fn get_report_proof(
-        uri: &str,
-        index: &LiabilityIndex<Runtime>,
-        report: &TechnicalReport<Runtime>,
-    ) -> ProofParam<Runtime> {
-        let pair = sr25519::Pair::from_string(uri, None).unwrap();
-        <ProofSigner<sr25519::Pair> as ProofBuilder<
-            <Runtime as Trait>::Technics,
-            <Runtime as Trait>::Economics,
-            LiabilityIndex<Runtime>,
-            _,
-            _,
-        >>::proof_report(index, report, pair)
-        .into()
+    fn finalize(_r: u32) -> Weight {
+        FINALIZE_WEIGHT
     }
-
-    #[test]
-    fn test_liability_proofs() {
-        let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
-            .from_base58()
-            .unwrap();
-        let economics = ();
-        let (sender, params_proof) = get_params_proof("//Alice", &technics, &economics);
-        let liability =
-            <Runtime as Trait>::Liability::new(technics, economics, sender.clone(), sender.clone());
-        assert_eq!(liability.check_params(&params_proof, &sender), true);
-
-        let index = 1;
-        let report = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
-            .from_base58()
-            .unwrap();
-        let report_proof = get_report_proof("//Alice", &index, &report);
-        assert_eq!(liability.check_report(&index, &report, &report_proof), true);
+    fn finalize_bad_proof(_r: u32) -> Weight {
+        100_000_000
     }
-
-    #[test]
-    fn test_liability_lifecycle() {
-        new_test_ext().execute_with(|| {
-            assert_eq!(Liability::latest_index(), 0);
-
-            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
-                .from_base58()
-                .unwrap();
-            let economics = ();
-
-            let (promisee, promisee_proof) = get_params_proof("//Alice", &technics, &economics);
-            let (promisor, promisor_proof) = get_params_proof("//Bob", &technics, &economics);
-
-            assert_err!(
-                Liability::create(
-                    Origin::none(),
-                    technics.clone(),
-                    economics.clone(),
+    fn verify_sr25519(_p: u32) -> Weight {
+        50_000_000
+    }
+    fn verify_ed25519(_p: u32) -> Weight {
+        45_000_000
+    }
+    fn verify_ecdsa(_p: u32) -> Weight {
+        60_000_000
+    }
+    fn add_promisor() -> Weight {
+        50_000_000
+    }
+    fn remove_promisor() -> Weight {
+        50_000_000
+    }
+    fn force_finalize() -> Weight {
+        FINALIZE_WEIGHT
+    }
+    fn demand(_t: u32) -> Weight {
+        100_000_000
+    }
+    fn cancel_demand() -> Weight {
+        50_000_000
+    }
+    fn offer(_t: u32) -> Weight {
+        100_000_000
+    }
+    fn cancel_offer() -> Weight {
+        50_000_000
+    }
+    fn set_market_fee() -> Weight {
+        50_000_000
+    }
+    fn create_market() -> Weight {
+        50_000_000
+    }
+    fn update_market() -> Weight {
+        50_000_000
+    }
+    fn fund() -> Weight {
+        FINALIZE_WEIGHT
+    }
+    fn cancel() -> Weight {
+        FINALIZE_WEIGHT
+    }
+    fn amend_economics() -> Weight {
+        FINALIZE_WEIGHT
+    }
+    fn commit_report() -> Weight {
+        FINALIZE_WEIGHT
+    }
+    fn reveal_report(_r: u32) -> Weight {
+        FINALIZE_WEIGHT
+    }
+    fn match_orders() -> Weight {
+        CREATE_WEIGHT
+    }
+    fn commit_match() -> Weight {
+        50_000_000
+    }
+    fn reveal_match() -> Weight {
+        CREATE_WEIGHT
+    }
+    fn set_preferences() -> Weight {
+        50_000_000
+    }
+    fn register_witness() -> Weight {
+        50_000_000
+    }
+    fn unregister_witness() -> Weight {
+        50_000_000
+    }
+    fn join_pool() -> Weight {
+        50_000_000
+    }
+    fn leave_pool() -> Weight {
+        50_000_000
+    }
+    fn request_assignment() -> Weight {
+        50_000_000
+    }
+    fn acknowledge() -> Weight {
+        50_000_000
+    }
+    fn reclaim() -> Weight {
+        50_000_000
+    }
+    fn force_expire() -> Weight {
+        50_000_000
+    }
+    fn require_witnesses() -> Weight {
+        100_000_000
+    }
+    fn notarize() -> Weight {
+        50_000_000
+    }
+    fn overturn_report(w: u32) -> Weight {
+        FINALIZE_WEIGHT.saturating_add((w as Weight).saturating_mul(50_000_000))
+    }
+    fn register_lighthouse() -> Weight {
+        50_000_000
+    }
+    fn unregister_lighthouse() -> Weight {
+        50_000_000
+    }
+    fn bond_orders() -> Weight {
+        50_000_000
+    }
+    fn unbond_orders() -> Weight {
+        50_000_000
+    }
+    fn claim_order_breach() -> Weight {
+        100_000_000
+    }
+    fn blacklist() -> Weight {
+        50_000_000
+    }
+    fn unblacklist() -> Weight {
+        50_000_000
+    }
+    fn approve_firmware() -> Weight {
+        50_000_000
+    }
+    fn revoke_firmware() -> Weight {
+        50_000_000
+    }
+    fn note_report_preimage(_r: u32) -> Weight {
+        50_000_000
+    }
+    fn prune_report_preimage() -> Weight {
+        50_000_000
+    }
+    fn prune_liability() -> Weight {
+        50_000_000
+    }
+}
+
+/// Weight of proof verification of given scheme, payload of `p` encoded bytes.
+pub fn verify_weight<W: WeightInfo>(scheme: SignatureScheme, p: u32) -> Weight {
+    match scheme {
+        SignatureScheme::Sr25519 => W::verify_sr25519(p),
+        SignatureScheme::Ed25519 => W::verify_ed25519(p),
+        SignatureScheme::Ecdsa => W::verify_ecdsa(p),
+    }
+}
+
+/// Liability module main trait.
+pub trait Trait: frame_system::Trait + CreateSignedTransaction<Call<Self>> {
+    /// Technical aspects of agreement.
+    type Technics: Technical;
+
+    /// Economical aspects of agreement.
+    type Economics: Divisible
+        + Reputable
+        + Attestable
+        + Valuable<Self::Balance>
+        + Marketable
+        + Deferrable
+        + Payable<AccountId<Self>>;
+
+    /// How to make and process agreement between two parties.
+    type Liability: FullCodec
+        + Processing<AccountId<Self>>
+        + Agreement<
+            Self::Technics,
+            Self::Economics,
+            AccountId = AccountId<Self>,
+            Proof = <Self as SigningTypes>::Signature,
+        >;
+
+    /// Funds type of liability statistics.
+    type Balance: Parameter + AtLeast32BitUnsigned + Default + Copy;
+
+    /// Value settled by finalized liability with given economical parameter.
+    type SettledValue: Convert<EconomicalParam<Self>, Self::Balance>;
+
+    /// Period in blocks of `EpochStats` event, zero disables it.
+    type StatsPeriod: Get<BlockNumber<Self>>;
+
+    /// Count of blocks of statistics era, running totals are snapshotted at its end, zero
+    /// disables snapshots.
+    type StatsEraLength: Get<BlockNumber<Self>>;
+
+    /// Count of the last eras statistics snapshots are kept for.
+    type StatsRetention: Get<u32>;
+
+    /// Count of blocks `CreatedInBlock` index is kept for, zero keeps it forever.
+    type CreatedRetention: Get<BlockNumber<Self>>;
+
+    /// Count of bits of filter of accounts touched by liability transitions deposited into
+    /// block digest, see `AccountBloom`, zero disables it.
+    type DigestBloomBits: Get<u32>;
+
+    /// Count of hashes of account in digest filter, at most `MAX_BLOOM_HASHES`.
+    type DigestBloomHashes: Get<u8>;
+
+    /// Maximal count of unsigned `create` and `finalize` calls in a block.
+    type MaxUnsignedPerBlock: Get<u32>;
+
+    /// Maximal count of liabilities created in a block by the same promisee and promisor.
+    type MaxPairPerBlock: Get<u32>;
+
+    /// Maximal encoded size in bytes of technical parameter of liability or market order.
+    type MaxTechnicsLength: Get<u32>;
+
+    /// Maximal encoded size in bytes of liability report.
+    type MaxReportLength: Get<u32>;
+
+    /// Maximal count of tags of liability.
+    type MaxTags: Get<u32>;
+
+    /// Count of the latest liabilities of tag kept in `ByTag`, the oldest are pruned.
+    type MaxTaggedLiabilities: Get<u32>;
+
+    /// Maximal count of promisors listed by promisee requirement.
+    type MaxAllowedPromisors: Get<u32>;
+
+    /// Only approved promisors could take new liabilities when `true`.
+    type PromisorWhitelist: Get<bool>;
+
+    /// Origin that approves promisors.
+    type AdminOrigin: EnsureOrigin<Self::Origin>;
+
+    /// Identity verification of promisees.
+    type IdentityVerifier: IdentityVerifier<AccountId<Self>>;
+
+    /// Promisee should have verified identity when liability value exceeds this threshold.
+    type IdentityThreshold: Get<Self::Balance>;
+
+    /// Reputation of promisors checked against requirement of liability economics.
+    type ReputationProvider: ReputationProvider<AccountId<Self>>;
+
+    /// Registry resolving robot identity of liability promisors.
+    type Robots: RobotRegistry<AccountId<Self>, BalanceOf<Self>>;
+
+    /// Minimal age in blocks of liability that could be force finalized, markets
+    /// configured by governance set their own.
+    type MinForceFinalizeAge: Get<BlockNumber<Self>>;
+
+    /// Minimal age in blocks of liability that could be reported, zero allows report in the
+    /// block of creation.
+    type MinReportDelay: Get<BlockNumber<Self>>;
+
+    /// Currency of market order deposits.
+    type Currency: ReservableCurrency<AccountId<Self>>;
+
+    /// Deposit reserved for each market order until it's removed. Deposit of account order
+    /// grows quadratically with its open orders: `OrderDeposit * (open + 1)^2`. Markets
+    /// configured by governance set their own.
+    type OrderDeposit: Get<BalanceOf<Self>>;
+
+    /// Maximal count of open orders of both sides posted by account.
+    type MaxOpenOrdersPerAccount: Get<u32>;
+
+    /// Deposit reserved from account noting report preimage until it's pruned.
+    type ReportPreimageDeposit: Get<BalanceOf<Self>>;
+
+    /// Count of blocks report preimage is kept in state after it's noted or its report is
+    /// accepted, liability report could be disputed with its bytes during this window.
+    /// Reports of markets configured by governance are kept for their report retention.
+    type ReportChallengeWindow: Get<BlockNumber<Self>>;
+
+    /// Maximal count of open orders of each side of market book.
+    type MaxOrdersPerMarket: Get<u32>;
+
+    /// Minimal quantity of market order fill, residual below it is closed.
+    type MinFillQuantity: Get<u32>;
+
+    /// Share of order deposit slashed to `MarketFeeDestination` when order expires.
+    type ExpiredOrderSlash: Get<Perbill>;
+
+    /// Maximal count of items processed by each `on_initialize` sweep in a block: items
+    /// and empty blocks of block indexed lists, e.g. `OrdersExpiringAt`, pruned blocks of
+    /// `CreatedInBlock`. The rest is processed in the next blocks.
+    type MaxSweepItemsPerBlock: Get<u32>;
+
+    /// Count of the latest matches of market kept in `RecentMatches`.
+    type MaxRecentMatches: Get<u32>;
+
+    /// Reward of account that matched demand with given economical parameter, it's reserved
+    /// from promisee with demand deposit.
+    type MatchingReward: Convert<EconomicalParam<Self>, BalanceOf<Self>>;
+
+    /// Market of liability technical parameter, e.g. robot model.
+    type Market: Convert<TechnicalParam<Self>, MarketId>;
+
+    /// Minimal stake reserved by lighthouse registered to market.
+    type LighthouseMinStake: Get<BalanceOf<Self>>;
+
+    /// Maximal count of lighthouses registered to market.
+    type MaxLighthouses: Get<u32>;
+
+    /// Count of blocks each lighthouse of market is scheduled for in turn.
+    type LighthouseSlot: Get<BlockNumber<Self>>;
+
+    /// Share of market fee paid to matching lighthouse, the rest goes to `MarketFeeDestination`.
+    type LighthouseFeeShare: Get<Perbill>;
+
+    /// Count of blocks after commitment to match of orders it could be revealed by
+    /// `reveal_match`, stale commitments are removed after them.
+    type MatchCommitWindow: Get<BlockNumber<Self>>;
+
+    /// Maximal count of match commitments of account waiting for reveal.
+    type MaxMatchCommits: Get<u32>;
+
+    /// Destination of market fees, e.g. treasury.
+    type MarketFeeDestination: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+    /// Share of liability value charged by `ChargeLiabilityFee` on top of standard fee.
+    type ValueFee: Get<Perbill>;
+
+    /// Minimal value scaled part of fee of liability with monetary value.
+    type MinValueFee: Get<BalanceOf<Self>>;
+
+    /// Maximal value scaled part of fee.
+    type MaxValueFee: Get<BalanceOf<Self>>;
+
+    /// Count of blocks after signed order expiry its breach could be claimed, it's also
+    /// the lock of unbonded signed orders funds.
+    type BreachClaimPeriod: Get<BlockNumber<Self>>;
+
+    /// Funds slashed from signed orders bond of party that reneged on agreement.
+    type OrderBreachSlash: Get<BalanceOf<Self>>;
+
+    /// Count of blocks promisee has to fund deferred funding liability, it's cancelled
+    /// after them.
+    type FundingDeadline: Get<BlockNumber<Self>>;
+
+    /// Count of blocks promisor has to reveal committed report, liability is finalized
+    /// as failed after them.
+    type RevealWindow: Get<BlockNumber<Self>>;
+
+    /// Maximal distance in blocks between report arrival claimed by promisor and its
+    /// inclusion block, see `finalize_claimed`.
+    type ClaimTolerance: Get<BlockNumber<Self>>;
+
+    /// Minimal stake reserved by report witness.
+    type WitnessMinStake: Get<BalanceOf<Self>>;
+
+    /// Maximal count of registered report witnesses, it's also the maximal quorum.
+    type MaxWitnesses: Get<u32>;
+
+    /// Funds slashed to promisee from each witness that notarized overturned report.
+    type WitnessSlash: Get<BalanceOf<Self>>;
+
+    /// Source of randomness for selection of promisor assigned to demand.
+    type Randomness: Randomness<Self::Hash>;
+
+    /// Minimal stake reserved by promisor joining assignment pool of market.
+    type PoolMinStake: Get<BalanceOf<Self>>;
+
+    /// Maximal count of promisors in assignment pool of market.
+    type MaxPoolSize: Get<u32>;
+
+    /// Count of blocks between assignment request and promisor selection, randomness of
+    /// selection block isn't known when assignment is requested.
+    type AssignmentDelay: Get<BlockNumber<Self>>;
+
+    /// Count of blocks promisor has to acknowledge new liability in, otherwise promisee
+    /// could reclaim it. Zero disables acknowledgement.
+    type AcknowledgementWindow: Get<BlockNumber<Self>>;
+
+    /// Deposit reserved from promisor of new liability until it's acknowledged, it's paid
+    /// to promisee of reclaimed liability.
+    type AcknowledgementDeposit: Get<BalanceOf<Self>>;
+
+    /// Count of blocks liability could stay open after creation, any account could
+    /// `force_expire` it later. Zero disables expiry.
+    type MaxLiabilityLifetime: Get<BlockNumber<Self>>;
+
+    /// Part of promisor acknowledgement deposit paid to caller of `force_expire`.
+    type ExpiryIncentive: Get<BalanceOf<Self>>;
+
+    /// Deposit reserved from promisee of zero value liability, see `is_zero_value`, until
+    /// it's settled, cancelled or pruned.
+    type ZeroValueDeposit: Get<BalanceOf<Self>>;
+
+    /// Maximal count of zero value liabilities of promisee per statistics era. Zero disables
+    /// the limit.
+    type MaxZeroValuePerEra: Get<u32>;
+
+    /// Base transaction pool priority of unsigned `create`, increased by liability value
+    /// but always lower than `FinalizePriority`.
+    type CreatePriority: Get<TransactionPriority>;
+
+    /// Transaction pool priority of unsigned `finalize`.
+    type FinalizePriority: Get<TransactionPriority>;
+
+    /// Origin of liabilities requested from other chains, e.g. sovereign account of sibling
+    /// parachain, returns promisee account of the remote beneficiary.
+    type RemoteOrigin: EnsureOrigin<Self::Origin, Success = AccountId<Self>>;
+
+    /// Origin of bridge relaying liabilities of legacy Ethereum contracts.
+    type BridgeOrigin: EnsureOrigin<Self::Origin>;
+
+    /// Representation of imported legacy liabilities, see `legacy::LegacyObjective`.
+    type Legacy: FromLegacy<TechnicalParam<Self>, EconomicalParam<Self>, AccountId<Self>>;
+
+    /// Promisor keys used by off-chain worker to sign liability reports, lighthouse keys
+    /// used to sign `match_orders`.
+    type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
+
+    /// Liability lifecycle handlers of other modules.
+    type Hooks: OnLiabilityCreated<LiabilityIndex<Self>, AccountId<Self>>
+        + OnReportAccepted<LiabilityIndex<Self>, AccountId<Self>>;
+
+    /// Maximal weight of lifecycle handlers called for liability creation or report, writes
+    /// of handler consumed more than the rest of it are reverted.
+    type MaxHooksWeight: Get<Weight>;
+
+    /// The overarching event type.
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+
+    /// Weight information for extrinsics in this module.
+    type WeightInfo: WeightInfo;
+}
+
+decl_event! {
+    pub enum Event<T>
+    where AccountId = AccountId<T>,
+          LiabilityIndex = LiabilityIndex<T>,
+          EconomicalParam = EconomicalParam<T>,
+          BlockNumber = BlockNumber<T>,
+          Balance = <T as Trait>::Balance,
+          Funds = BalanceOf<T>,
+          Hash = <T as frame_system::Trait>::Hash,
+    {
+        /// Yay! New liability created: index, id, technics hash, economics, promisee, promisor,
+        /// block, tags.
+        NewLiability(Compact<LiabilityIndex>, H256, H256, EconomicalParam, AccountId, AccountId, BlockNumber, Vec<Tag>),
+
+        /// Liability report published: index, report hash, block.
+        NewReport(Compact<LiabilityIndex>, H256, BlockNumber),
+
+        /// Liability created with technics derived from report of parent: index, parent
+        /// index.
+        LiabilityChained(Compact<LiabilityIndex>, Compact<LiabilityIndex>),
+
+        /// Periodic liability statistics.
+        EpochStats(LiabilityCounters<Balance>),
+
+        /// Promisor account approved.
+        PromisorAdded(AccountId),
+
+        /// Promisor account approval revoked.
+        PromisorRemoved(AccountId),
+
+        /// Account excluded from new liabilities and orders.
+        AccountBlacklisted(AccountId),
+
+        /// Account removed from blacklist.
+        AccountUnblacklisted(AccountId),
+
+        /// Firmware approved for robot model: model, firmware hash.
+        FirmwareApproved(MarketId, Hash),
+
+        /// Firmware approval for robot model revoked: model, firmware hash.
+        FirmwareRevoked(MarketId, Hash),
+
+        /// Liability report attested firmware that produced it: index, firmware hash.
+        FirmwareAttested(Compact<LiabilityIndex>, Hash),
+
+        /// Liability finalized by root without report: index, success, block.
+        ForceFinalized(Compact<LiabilityIndex>, bool, BlockNumber),
+
+        /// Deferred funding liability funded by promisee and started: index.
+        LiabilityFunded(Compact<LiabilityIndex>),
+
+        /// Deferred funding liability cancelled, it wasn't funded before deadline: index.
+        FundingExpired(Compact<LiabilityIndex>),
+
+        /// Liability cancelled before report: index, party of unilateral cancel, none for
+        /// mutual one, and fee paid by it to counterparty.
+        LiabilityCancelled(Compact<LiabilityIndex>, Option<Party>, Funds),
+
+        /// Commitment to liability report published: index, commitment, the last block
+        /// of reveal window.
+        ReportCommitted(Compact<LiabilityIndex>, H256, BlockNumber),
+
+        /// Committed report wasn't revealed in time, liability is finalized as failed: index.
+        RevealExpired(Compact<LiabilityIndex>),
+
+        /// Report witness registered: account, stake.
+        WitnessRegistered(AccountId, Funds),
+
+        /// Report witness unregistered, its stake is returned: account.
+        WitnessUnregistered(AccountId),
+
+        /// Liability is settled in promisor favor only with report notarized by witnesses:
+        /// index, quorum.
+        WitnessingRequired(Compact<LiabilityIndex>, u32),
+
+        /// Liability report notarized by witness: index, witness.
+        Notarized(Compact<LiabilityIndex>, AccountId),
+
+        /// Witnessed liability settled after challenge window: index, success.
+        ReportSettled(Compact<LiabilityIndex>, bool),
+
+        /// Witnessed report overturned during challenge window, liability is settled
+        /// as failed: index.
+        ReportOverturned(Compact<LiabilityIndex>),
+
+        /// Witnessed report upheld by its resolution, e.g. by jury or governance, liability
+        /// is settled in promisor favor: index.
+        ReportUpheld(Compact<LiabilityIndex>),
+
+        /// Record of finalized liability pruned, its index is never reused: index.
+        LiabilityPruned(Compact<LiabilityIndex>),
+
+        /// Witness that notarized overturned report slashed: witness, slashed funds.
+        WitnessSlashed(AccountId, Funds),
+
+        /// Match of market orders committed: matcher, commitment.
+        MatchCommitted(AccountId, H256),
+
+        /// Liability report arrival claimed by promisor is accepted: index, arrival block.
+        ArrivalClaimed(Compact<LiabilityIndex>, BlockNumber),
+
+        /// Liability report submitter is known: index, submitter.
+        ReportSubmitted(Compact<LiabilityIndex>, Submitter<AccountId>),
+
+        /// Market order posted: id, side, market, technics hash, single unit economics, quantity,
+        /// price, creator, deadline.
+        OrderPosted(OrderId, Side, MarketId, H256, EconomicalParam, u32, Balance, AccountId, BlockNumber),
+
+        /// Market order removed by its creator, after deadline, by eviction from full book
+        /// or when its residual is below minimal fill: id, side, market, open quantity.
+        OrderCancelled(OrderId, Side, MarketId, u32),
+
+        /// Demand and offer matched: demand id, offer id, liability index, market,
+        /// single unit price, filled quantity, matching account.
+        Matched(OrderId, OrderId, Compact<LiabilityIndex>, MarketId, Balance, u32, AccountId),
+
+        /// Lighthouse registered to market with given stake.
+        LighthouseRegistered(MarketId, AccountId, Funds),
+
+        /// Lighthouse unregistered from market, stake returned.
+        LighthouseUnregistered(MarketId, AccountId),
+
+        /// Matching reward paid: demand id, lighthouse, reward.
+        LighthouseRewarded(OrderId, AccountId, Funds),
+
+        /// Market fee set by admin.
+        MarketFeeSet(MarketId, Perbill),
+
+        /// Market configuration created by admin.
+        MarketCreated(MarketId),
+
+        /// Market configuration updated by admin, existing liabilities keep the former one.
+        MarketUpdated(MarketId),
+
+        /// Market fee paid on matching: demand id, lighthouse, lighthouse share, treasury share.
+        MatchFeePaid(OrderId, AccountId, Funds, Funds),
+
+        /// Market order removed after deadline: id, side, market, open quantity, share
+        /// of deposit slashed.
+        OrderExpired(OrderId, Side, MarketId, u32, Funds),
+
+        /// Signed orders bond increased.
+        OrderBonded(AccountId, Funds),
+
+        /// Signed orders bond unlocking, it's returned from given block.
+        BondUnlocking(AccountId, BlockNumber),
+
+        /// Signed orders bond returned.
+        OrderUnbonded(AccountId, Funds),
+
+        /// Signed orders breach claimed: claimant, reneging party, slashed funds.
+        OrderBreachClaimed(AccountId, AccountId, Funds),
+
+        /// Liability with technics exceeding `MaxTechnicsLength` removed by storage migration,
+        /// open one is finalized as failed: index.
+        OversizedLiabilityRejected(Compact<LiabilityIndex>),
+
+        /// Report exceeding `MaxReportLength` removed by storage migration: index.
+        OversizedReportRejected(Compact<LiabilityIndex>),
+
+        /// Report preimage noted: hash, depositor.
+        ReportPreimageNoted(H256, AccountId),
+
+        /// Expired report preimage removed: hash.
+        ReportPreimagePruned(H256),
+
+        /// Relay bounty of liability paid to relayer of its report: index, relayer, bounty.
+        RelayBountyPaid(Compact<LiabilityIndex>, AccountId, Funds),
+
+        /// Relay bounty of liability returned to promisee: index, bounty.
+        RelayBountyRefunded(Compact<LiabilityIndex>, Funds),
+
+        /// Promisor joined assignment pool of market: account, market, standing offer, stake.
+        PoolJoined(AccountId, MarketId, OrderId, Funds),
+
+        /// Promisor left assignment pool of market: account, market.
+        PoolLeft(AccountId, MarketId),
+
+        /// Promisor assignment of demand requested: demand, selection block.
+        AssignmentRequested(OrderId, BlockNumber),
+
+        /// Demand assigned to promisor of assignment pool: demand, promisor.
+        DemandAssigned(OrderId, AccountId),
+
+        /// No promisor of assignment pool qualifies for demand, it's left in market book:
+        /// demand.
+        AssignmentFailed(OrderId),
+
+        /// Liability economics amended by both parties: index, amended economics.
+        EconomicsAmended(Compact<LiabilityIndex>, EconomicalParam),
+
+        /// Liability acknowledged by promisor, its deposit is returned: index.
+        LiabilityAcknowledged(Compact<LiabilityIndex>),
+
+        /// Unacknowledged liability cancelled by promisee: index, promisor deposit paid.
+        LiabilityReclaimed(Compact<LiabilityIndex>, Funds),
+
+        /// Liability open for more than its lifetime cancelled: index, caller, incentive paid.
+        LiabilityExpired(Compact<LiabilityIndex>, AccountId, Funds),
+
+        /// Blinded liability created: index, promisee and promisor commitments.
+        BlindedLiabilityCreated(Compact<LiabilityIndex>, H256, H256),
+
+        /// Blinded liability finalized: index, report hash.
+        BlindedLiabilityFinalized(Compact<LiabilityIndex>, H256),
+
+        /// Outcome of finalized liability appended to finalizations range: index, leaf index,
+        /// new root.
+        FinalizationLeafAdded(Compact<LiabilityIndex>, u64, H256),
+
+        /// Open liabilities of promisor key rotated: old key, new key, all of them are rotated.
+        PromisorRotated(AccountId, AccountId, bool),
+
+        /// Liability record can't be decoded and is quarantined until governance resolves it:
+        /// index.
+        CorruptEntry(Compact<LiabilityIndex>),
+
+        /// Preferences of account set: account.
+        PreferencesSet(AccountId),
+
+        /// Preferences of account removed: account.
+        PreferencesCleared(AccountId),
+
+        /// Settled value of liability passed from promisor to its payout account: index,
+        /// payout account, value.
+        PayoutRedirected(Compact<LiabilityIndex>, AccountId, Funds),
+
+        /// Ethereum state root of legacy liabilities anchored: legacy factory, state root.
+        LegacyStateAnchored(H160, H256),
+
+        /// Finalized liability imported from legacy Ethereum contract: index, contract
+        /// address.
+        LegacyImported(Compact<LiabilityIndex>, H160),
+
+        /// Lifecycle handler of liability failed, its writes are reverted: index, position
+        /// of handler in `Hooks`, error.
+        HookFailed(Compact<LiabilityIndex>, u32, DispatchError),
+    }
+}
+
+impl<AccountId, LiabilityIndex, EconomicalParam, BlockNumber, Balance, Funds, Hash>
+    TryFrom<RawEvent<AccountId, LiabilityIndex, EconomicalParam, BlockNumber, Balance, Funds, Hash>>
+    for LiabilityEvent<LiabilityIndex, EconomicalParam, AccountId, BlockNumber>
+{
+    type Error = ();
+
+    /// Lifecycle events of single liability are converted, statistics events aren't.
+    fn try_from(
+        event: RawEvent<
+            AccountId,
+            LiabilityIndex,
+            EconomicalParam,
+            BlockNumber,
+            Balance,
+            Funds,
+            Hash,
+        >,
+    ) -> Result<Self, Self::Error> {
+        match event {
+            RawEvent::NewLiability(
+                index,
+                id,
+                technics,
+                economics,
+                promisee,
+                promisor,
+                block_number,
+                tags,
+            ) => Ok(LiabilityEvent::Created {
+                index: index.0,
+                id,
+                technics,
+                economics,
+                promisee,
+                promisor,
+                block_number,
+                tags,
+            }),
+            RawEvent::NewReport(index, report, block_number) => Ok(LiabilityEvent::Reported {
+                index: index.0,
+                report,
+                block_number,
+            }),
+            RawEvent::EpochStats(_)
+            | RawEvent::PromisorAdded(_)
+            | RawEvent::PromisorRemoved(_)
+            | RawEvent::LiabilityChained(..)
+            | RawEvent::AccountBlacklisted(_)
+            | RawEvent::AccountUnblacklisted(_)
+            | RawEvent::FirmwareApproved(..)
+            | RawEvent::FirmwareRevoked(..)
+            | RawEvent::FirmwareAttested(..)
+            | RawEvent::ForceFinalized(..)
+            | RawEvent::LiabilityFunded(_)
+            | RawEvent::FundingExpired(_)
+            | RawEvent::LiabilityCancelled(..)
+            | RawEvent::ReportCommitted(..)
+            | RawEvent::RevealExpired(_)
+            | RawEvent::ArrivalClaimed(..)
+            | RawEvent::ReportSubmitted(..)
+            | RawEvent::MatchCommitted(..)
+            | RawEvent::WitnessRegistered(..)
+            | RawEvent::WitnessUnregistered(_)
+            | RawEvent::WitnessingRequired(..)
+            | RawEvent::Notarized(..)
+            | RawEvent::ReportSettled(..)
+            | RawEvent::ReportOverturned(_)
+            | RawEvent::ReportUpheld(_)
+            | RawEvent::LiabilityPruned(_)
+            | RawEvent::WitnessSlashed(..)
+            | RawEvent::OrderPosted(..)
+            | RawEvent::OrderCancelled(..)
+            | RawEvent::Matched(..)
+            | RawEvent::LighthouseRegistered(..)
+            | RawEvent::LighthouseUnregistered(..)
+            | RawEvent::LighthouseRewarded(..)
+            | RawEvent::MarketFeeSet(..)
+            | RawEvent::MarketCreated(_)
+            | RawEvent::MarketUpdated(_)
+            | RawEvent::MatchFeePaid(..)
+            | RawEvent::OrderExpired(..)
+            | RawEvent::OrderBonded(..)
+            | RawEvent::BondUnlocking(..)
+            | RawEvent::OrderUnbonded(..)
+            | RawEvent::OrderBreachClaimed(..)
+            | RawEvent::OversizedLiabilityRejected(_)
+            | RawEvent::OversizedReportRejected(_)
+            | RawEvent::ReportPreimageNoted(..)
+            | RawEvent::ReportPreimagePruned(_)
+            | RawEvent::RelayBountyPaid(..)
+            | RawEvent::RelayBountyRefunded(..)
+            | RawEvent::PoolJoined(..)
+            | RawEvent::PoolLeft(..)
+            | RawEvent::AssignmentRequested(..)
+            | RawEvent::DemandAssigned(..)
+            | RawEvent::AssignmentFailed(_)
+            | RawEvent::EconomicsAmended(..)
+            | RawEvent::LiabilityAcknowledged(_)
+            | RawEvent::LiabilityReclaimed(..)
+            | RawEvent::LiabilityExpired(..)
+            | RawEvent::BlindedLiabilityCreated(..)
+            | RawEvent::BlindedLiabilityFinalized(..)
+            | RawEvent::FinalizationLeafAdded(..)
+            | RawEvent::PromisorRotated(..)
+            | RawEvent::CorruptEntry(..)
+            | RawEvent::PreferencesSet(_)
+            | RawEvent::PreferencesCleared(_)
+            | RawEvent::PayoutRedirected(..)
+            | RawEvent::LegacyStateAnchored(..)
+            | RawEvent::LegacyImported(..)
+            | RawEvent::HookFailed(..) => Err(()),
+        }
+    }
+}
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        /// Promisor agreement proof verification failed
+        BadPromisorProof,
+        /// Promisee agreement proof verification failed
+        BadPromiseeProof,
+        /// Promisor report proof verification failed
+        BadReportProof,
+        /// Unable to decode liability at given index
+        LiabilityDecodeFailure,
+        /// Liability with given index doesn't exist
+        UnknownLiability,
+        /// Parent liability has no accepted report or it can't seed chained liability technics
+        UnchainableReport,
+        /// Block limit of unsigned liability calls reached
+        TooManyUnsignedCalls,
+        /// Block limit of liabilities between the same parties reached
+        TooManyPairLiabilities,
+        /// Promisor account isn't approved
+        UnapprovedPromisor,
+        /// Promisee should have verified identity for liability of this value
+        IdentityRequired,
+        /// Liability is already finalized
+        AlreadyFinalized,
+        /// Liability is too young to be force finalized
+        LiabilityTooYoung,
+        /// Liability is too young to be reported
+        ReportTooEarly,
+        /// Remote request id is already used by promisee
+        DuplicateRequest,
+        /// Promisee created maximal count of zero value liabilities in this era
+        TooManyZeroValue,
+        /// The same agreement is already created in this block
+        DuplicateAgreement,
+        /// Order proof verification failed
+        BadOrderProof,
+        /// Order deadline is already passed
+        DeadlineInPast,
+        /// Order isn't known
+        UnknownOrder,
+        /// Order could be removed only by its creator before deadline
+        NotOrderOwner,
+        /// Demand and offer have different liability parameters
+        OrderMismatch,
+        /// Order deadline is passed
+        OrderExpired,
+        /// Order is already matched
+        OrderConsumed,
+        /// Lighthouse stake is lower than minimal one
+        StakeTooLow,
+        /// Account is already lighthouse of market
+        AlreadyRegistered,
+        /// Market lighthouses limit reached
+        TooManyLighthouses,
+        /// Account isn't lighthouse of market
+        NotLighthouse,
+        /// Market book is full of orders with better prices
+        OrderBookFull,
+        /// Order quantity is lower than minimal fill
+        InvalidQuantity,
+        /// Account has too many open orders
+        TooManyOpenOrders,
+        /// Signed order isn't expired yet
+        OrderNotExpired,
+        /// Breach claim period of signed order passed
+        ClaimPeriodPassed,
+        /// Signed order is already claimed
+        OrderClaimed,
+        /// Liability of signed orders agreement was created before expiry
+        NoBreach,
+        /// Account has no signed orders bond
+        NotBonded,
+        /// Signed orders bond is still locked
+        BondLocked,
+        /// Promisor reputation is lower than required by liability economics
+        ReputationTooLow,
+        /// Account is blacklisted by governance
+        Blacklisted,
+        /// Report of liability should attest firmware
+        FirmwareRequired,
+        /// Firmware isn't approved for robot model of liability
+        FirmwareNotApproved,
+        /// Account isn't blacklisted
+        NotBlacklisted,
+        /// Technical parameter exceeds `MaxTechnicsLength`
+        TechnicsTooLong,
+        /// Liability has more than `MaxTags` tags
+        TooManyTags,
+        /// Liability has the same tag twice
+        DuplicateTag,
+        /// Liability report exceeds `MaxReportLength`
+        ReportTooLong,
+        /// Report preimage with given hash isn't noted
+        UnknownPreimage,
+        /// Report preimage is already noted
+        PreimageExists,
+        /// Report preimage challenge window isn't passed
+        PreimageNotExpired,
+        /// Parties proofs expired before liability creation
+        ProofExpired,
+        /// Market configuration already exists
+        MarketExists,
+        /// Market configuration isn't created
+        UnknownMarket,
+        /// Liability isn't funded by promisee yet
+        FundingPending,
+        /// Liability doesn't wait for funding
+        NotPendingFunding,
+        /// Only liability promisee could do it
+        NotPromisee,
+        /// Cancel isn't proven by any party
+        CancelProofRequired,
+        /// Report of liability is committed, it's finalized by reveal only
+        ReportCommitted,
+        /// Report of liability isn't committed
+        NoCommitment,
+        /// Revealed report and salt don't match commitment
+        CommitmentMismatch,
+        /// Reveal window of committed report is passed
+        RevealWindowPassed,
+        /// Claimed report arrival is earlier than liability creation
+        ClaimBeforeCreation,
+        /// Claimed report arrival is older than `ClaimTolerance` blocks
+        ClaimTooOld,
+        /// Claimed report arrival is later than `ClaimTolerance` blocks ahead
+        ClaimInFuture,
+        /// Report arrival claim isn't signed by promisor
+        BadClaimProof,
+        /// Match commitment of account already exists
+        MatchCommitted,
+        /// Match isn't committed by account
+        NoMatchCommitment,
+        /// Match is revealed in the block of its commitment
+        MatchCommitTooRecent,
+        /// Match commitment is older than `MatchCommitWindow` blocks
+        MatchCommitExpired,
+        /// Account has `MaxMatchCommits` match commitments waiting for reveal
+        TooManyMatchCommits,
+        /// Account is already registered witness
+        AlreadyWitness,
+        /// Account isn't registered witness
+        NotWitness,
+        /// Count of registered witnesses reached `MaxWitnesses`
+        TooManyWitnesses,
+        /// Witness has notarizations of unsettled reports
+        WitnessBusy,
+        /// Witness quorum should be from one to `MaxWitnesses`
+        InvalidQuorum,
+        /// Liability already requires witnesses
+        AlreadyWitnessed,
+        /// Liability party couldn't witness its report
+        PartyWitness,
+        /// Report isn't waiting for settlement or its challenge window is passed
+        NotarizationClosed,
+        /// Report is already notarized by witness
+        AlreadyNotarized,
+        /// Notarization isn't signed by witness
+        BadNotaryProof,
+        /// Account is already in assignment pool of market
+        AlreadyPooled,
+        /// Account isn't in assignment pool of market
+        NotPooled,
+        /// Assignment pool of market has `MaxPoolSize` promisors
+        PoolFull,
+        /// Assignment pool of market has no promisors
+        EmptyPool,
+        /// Assignment of demand is already requested
+        AssignmentPending,
+        /// Amended economics changes market or funding mode of liability
+        AmendmentMismatch,
+        /// Liability doesn't wait for acknowledgement
+        NotPendingAcknowledgement,
+        /// Acknowledgement window of liability is over
+        AcknowledgementExpired,
+        /// Promisor could acknowledge liability yet
+        AcknowledgementWindowOpen,
+        /// Liability is open less than maximal lifetime
+        LifetimeNotExceeded,
+        /// Witnessed report of liability is disputed in challenge window
+        SettlementDisputed,
+        /// Party can't afford relay bounty or acknowledgement deposit of liability
+        CannotAffordDeposit,
+        /// Disclosed party account doesn't match its commitment
+        PartyCommitmentMismatch,
+        /// Promisor key is rotated, the new key serves its liabilities
+        PromisorKeyRotated,
+        /// Liabilities of promisor key are already rotated or key isn't rotated
+        NoPendingRotation,
+        /// Liability record isn't quarantined, it's settled as usual
+        NotQuarantined,
+        /// Witnessed report isn't held from settlement
+        SettlementNotHeld,
+        /// Promisor robot doesn't declare capabilities required by technics
+        CapabilitiesMismatch,
+        /// Liability record is pruned, its index is never reused
+        Pruned,
+        /// Liability isn't finalized or its report isn't settled yet
+        NotFinalized,
+        /// Promisor doesn't satisfy requirement signed by promisee
+        PromisorNotAllowed,
+        /// Requirement lists more than `MaxAllowedPromisors` promisors
+        TooManyAllowedPromisors,
+        /// Ethereum state root of legacy liabilities isn't anchored
+        LegacyStateUnset,
+        /// Legacy liability contract is already imported
+        AlreadyImported,
+        /// Proof of legacy liability has more than `MAX_LEGACY_PROOF_NODES` nodes
+        LegacyProofTooLarge,
+        /// Legacy liability isn't proven by anchored Ethereum state
+        BadLegacyProof,
+        /// Legacy liability can't be represented by module parameters
+        UnsupportedLegacy,
+    }
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as Liability {
+        /// Latest liability index.
+        LatestIndex get(fn latest_index): LiabilityIndex<T>;
+        /// Liability parameters with its state, block numbers and report hash, so a call reads
+        /// single value per liability.
+        Liabilities get(fn liability_record): map hasher(blake2_128_concat)
+                                              LiabilityIndex<T> => Option<LiabilityRecordOf<T>>;
+        /// Liability index by its content-derived id, see `liability_id`.
+        IdToIndex get(fn index_of_id): map hasher(identity) H256 => Option<LiabilityIndex<T>>;
+        /// Content-derived id of liability, none for liabilities created before ids.
+        IndexToId get(fn id_of): map hasher(blake2_128_concat) LiabilityIndex<T> => Option<H256>;
+        /// Global index of liability by promisor and its local index.
+        PromisorLiabilities get(fn promisor_liability): double_map hasher(blake2_128_concat) AccountId<T>,
+                                                        hasher(twox_64_concat) LiabilityIndex<T>
+                                                        => Option<LiabilityIndex<T>>;
+        /// Count of liabilities created for promisor, next local index.
+        PromisorCount get(fn promisor_count): map hasher(blake2_128_concat)
+                                              AccountId<T> => LiabilityIndex<T>;
+        /// Technical reports by hash, kept at least `ReportChallengeWindow` blocks after
+        /// they're noted or report retention of market after they're accepted, so report
+        /// of liability record is known during it.
+        ReportPreimage get(fn report_preimage): map hasher(identity) H256 => Option<PreimageOf<T>>;
+        /// Hashes of report preimages expiring at given block, preimage expiry extended later
+        /// is queued at its new block as well.
+        PreimagesExpiringAt: map hasher(twox_64_concat) BlockNumber<T> => Vec<H256>;
+        /// Blocks before this one are pruned from `PreimagesExpiringAt` with their expired
+        /// preimages.
+        PreimagesPrunedUntil get(fn preimages_pruned_until): BlockNumber<T>;
+        /// Finalized liabilities by the last block of report retention of their market, they're
+        /// pruned after it.
+        PrunableAt: map hasher(twox_64_concat) BlockNumber<T> => Vec<LiabilityIndex<T>>;
+        /// Blocks before this one are pruned from `PrunableAt` with their liabilities.
+        LiabilitiesPrunedUntil get(fn liabilities_pruned_until): BlockNumber<T>;
+        /// Bounty reserved by promisee for relayer of liability report, see
+        /// `finalize_relayed`.
+        RelayBounty get(fn relay_bounty): map hasher(blake2_128_concat)
+                                          LiabilityIndex<T> => Option<BalanceOf<T>>;
+        /// Deposit reserved by promisee of open zero value liability.
+        ZeroValueDeposits get(fn zero_value_deposit): map hasher(blake2_128_concat)
+                                                      LiabilityIndex<T> => Option<BalanceOf<T>>;
+        /// Statistics era and count of zero value liabilities created by promisee in it.
+        ZeroValueCount get(fn zero_value_count): map hasher(blake2_128_concat)
+                                                 AccountId<T> => (u32, u32);
+        /// Firmware hash attested by liability report.
+        FirmwareOf get(fn firmware_of): map hasher(blake2_128_concat)
+                                        LiabilityIndex<T> => Option<T::Hash>;
+        /// Arrival block of liability report claimed by promisor, the earlier of claimed
+        /// and inclusion blocks, see `report_arrival`.
+        ClaimedArrival get(fn claimed_arrival): map hasher(blake2_128_concat)
+                                                LiabilityIndex<T> => Option<BlockNumber<T>>;
+        /// Firmware approved by governance for robot model, i.e. market.
+        ApprovedFirmware get(fn is_firmware_approved): double_map hasher(twox_64_concat) MarketId,
+                                                       hasher(identity) T::Hash => bool;
+        /// Liabilities where account is promisee or promisor.
+        AccountLiabilities: double_map hasher(blake2_128_concat) AccountId<T>,
+                                       hasher(twox_64_concat) LiabilityIndex<T> => ();
+        /// Lifecycle transitions of liability, at most `MAX_HISTORY_LENGTH` latest ones.
+        ///
+        /// Empty for liabilities created before history was recorded.
+        HistoryOf get(fn history_of): map hasher(blake2_128_concat)
+                                      LiabilityIndex<T> => Vec<(BlockNumber<T>, Transition<AccountId<T>>)>;
+        /// Tags of liability covered by promisee proof, only for tagged liabilities.
+        TagsOf get(fn tags_of): map hasher(blake2_128_concat) LiabilityIndex<T> => Vec<Tag>;
+        /// The latest `MaxTaggedLiabilities` liabilities with tag, the oldest first.
+        ByTag get(fn by_tag): map hasher(twox_64_concat) Tag => Vec<LiabilityIndex<T>>;
+        /// Liabilities created in the block.
+        CreatedInBlock get(fn created_in_block): map hasher(twox_64_concat)
+                                                 BlockNumber<T> => Vec<LiabilityIndex<T>>;
+        /// Blocks before this one are pruned from `CreatedInBlock`.
+        CreatedPrunedUntil get(fn created_pruned_until): BlockNumber<T>;
+        /// Count of unsigned liability calls dispatched in current block.
+        UnsignedInBlock get(fn unsigned_in_block): u32;
+        /// Liabilities changed in current block, their parties are deposited into digest filter.
+        TouchedInBlock get(fn touched_in_block): Vec<LiabilityIndex<T>>;
+        /// Count of liabilities created in the block by promisee and promisor pair.
+        PairCreatesInBlock get(fn pair_creates_in_block): double_map hasher(twox_64_concat) BlockNumber<T>,
+                                                          hasher(blake2_128_concat) (AccountId<T>, AccountId<T>) => u32;
+        /// Count of market orders, next order id.
+        OrderCount get(fn order_count): OrderId;
+        /// Open demands of promisees.
+        Asks get(fn ask): map hasher(twox_64_concat) OrderId => Option<OrderOf<T>>;
+        /// Open offers of promisors.
+        Bids get(fn bid): map hasher(twox_64_concat) OrderId => Option<OrderOf<T>>;
+        /// Open demands of market sorted by price from the highest, orders of the same price
+        /// by posting time. Price is settled value of order economics.
+        AsksByModel get(fn asks_by_model): map hasher(twox_64_concat) MarketId => Vec<(T::Balance, OrderId)>;
+        /// Open offers of market sorted by price from the lowest, orders of the same price
+        /// by posting time.
+        BidsByModel get(fn bids_by_model): map hasher(twox_64_concat) MarketId => Vec<(T::Balance, OrderId)>;
+        /// Liability created by the last fill of consumed order.
+        MatchedOrders get(fn matched_order): map hasher(twox_64_concat) OrderId => Option<LiabilityIndex<T>>;
+        /// Requirement to promisors of open demand, it's removed with demand.
+        DemandRequirements get(fn demand_requirement): map hasher(twox_64_concat) OrderId => Option<PromisorRequirementOf<T>>;
+        /// The latest `MaxRecentMatches` matches of market, the oldest first.
+        RecentMatches: map hasher(twox_64_concat) MarketId => Vec<MatchInfoOf<T>>;
+        /// Block of the latest match evicted from `RecentMatches` of market.
+        EvictedMatchAt: map hasher(twox_64_concat) MarketId => Option<BlockNumber<T>>;
+        /// Lighthouses of market in schedule order, see `scheduled_lighthouse`.
+        Lighthouses get(fn lighthouses): map hasher(twox_64_concat) MarketId => Vec<AccountId<T>>;
+        /// Stake reserved by lighthouse of market.
+        LighthouseStake get(fn lighthouse_stake): double_map hasher(twox_64_concat) MarketId,
+                                                  hasher(blake2_128_concat) AccountId<T> => Option<BalanceOf<T>>;
+        /// Configurations of markets managed by governance, markets without one take
+        /// `default_market_config`.
+        Markets: map hasher(twox_64_concat) MarketId => Option<MarketConfigOf<T>>;
+        /// Market of liability and its configuration taken at creation.
+        LiabilityMarket: map hasher(blake2_128_concat)
+                         LiabilityIndex<T> => Option<(MarketId, MarketConfigOf<T>)>;
+        /// Orders expired at given block, i.e. the next block after their deadline.
+        /// Consumed and removed orders aren't dropped from index, block entry is removed
+        /// when it's swept.
+        OrdersExpiringAt get(fn orders_expiring_at): map hasher(twox_64_concat) BlockNumber<T> => Vec<OrderId>;
+        /// Block of `OrdersExpiringAt` swept next and count of its orders already expired.
+        ExpirySweepCursor get(fn expiry_sweep_cursor): (BlockNumber<T>, u32);
+        /// Count of open orders posted by account.
+        OpenOrders get(fn open_orders): map hasher(blake2_128_concat) AccountId<T> => u32;
+        /// Block of the latest liability of agreement, see `agreement_hash`.
+        AgreementCreatedAt: map hasher(identity) [u8; 32] => Option<BlockNumber<T>>;
+        /// Funds reserved by account to be slashed by breach claims of its signed orders.
+        OrderBond get(fn order_bond): map hasher(blake2_128_concat) AccountId<T> => BalanceOf<T>;
+        /// Block from which signed orders bond of account is returned.
+        BondUnlockAt get(fn bond_unlock_at): map hasher(blake2_128_concat) AccountId<T> => Option<BlockNumber<T>>;
+        /// Signed orders by creator and nonce that were claimed.
+        ClaimedOrders: double_map hasher(blake2_128_concat) AccountId<T>, hasher(twox_64_concat) u64 => ();
+        /// Hashes of agreements created in a block, see `agreement_hash`.
+        AgreementsInBlock: double_map hasher(twox_64_concat) BlockNumber<T>,
+                                      hasher(identity) [u8; 32] => ();
+        /// Minimal promisor reputation required by liability economics and promisor
+        /// reputation seen at creation, only for liabilities with requirement.
+        ReputationSnapshot get(fn reputation_snapshot): map hasher(blake2_128_concat)
+                                                       LiabilityIndex<T> => Option<(u32, u32)>;
+        /// Registry entry of liability promisor at creation, only for registered robots.
+        LiabilityRobot get(fn liability_robot): map hasher(blake2_128_concat)
+                                               LiabilityIndex<T> => Option<RobotId>;
+        /// Index of liability created by remote promisee request with given id.
+        RemoteRequests get(fn remote_request): double_map hasher(blake2_128_concat) AccountId<T>,
+                                               hasher(twox_64_concat) u64 => Option<LiabilityIndex<T>>;
+        /// Promisors approved to take new liabilities when whitelist is enabled.
+        ApprovedPromisors get(fn approved_promisors): map hasher(blake2_128_concat) AccountId<T> => ();
+        /// Accounts excluded from new liabilities and orders by governance.
+        Blacklist get(fn is_blacklisted): map hasher(blake2_128_concat) AccountId<T> => bool;
+        /// Network-level liability statistics.
+        Counters get(fn counters): LiabilityCounters<T::Balance>;
+        /// Liability statistics of account.
+        StatsOf get(fn stats_of): map hasher(blake2_128_concat) AccountId<T> => AccountStats<T::Balance>;
+        /// Outcomes of all finalized liabilities.
+        Outcomes get(fn outcomes): OutcomeCounts;
+        /// Execution statistics of technics model, see `Technical::model`.
+        ModelStatistics get(fn model_stats): map hasher(blake2_128_concat) H256 => ModelStats<T::Balance>;
+        /// Scores and hashes of the best technics models by ranking, the highest first and at
+        /// most `MAX_LEADERBOARD_LENGTH`.
+        ModelLeaderboard get(fn model_leaderboard): map hasher(twox_64_concat) ModelRanking => Vec<(u128, H256)>;
+        /// Running totals of matches of all markets.
+        MarketStats get(fn market_totals): MarketTotals<T::Balance>;
+        /// Snapshots of running totals at the end of era, the last `StatsRetention` eras
+        /// are kept.
+        EraSnapshots get(fn era_stats): map hasher(twox_64_concat) u32 => Option<EraStatsOf<T>>;
+        /// Liabilities (`false`) and reports (`true`) rejected by storage migration as oversized,
+        /// their events are deposited by the next `on_initialize`.
+        OversizedRejections: Vec<(LiabilityIndex<T>, bool)>;
+        /// Funds of account reserved by module under identifier, see `NamedReserve`.
+        NamedReserves get(fn named_reserve): double_map hasher(twox_64_concat) ReserveIdentifier,
+                                             hasher(blake2_128_concat) AccountId<T> => BalanceOf<T>;
+        /// Funding deadline of deferred funding liabilities that aren't funded yet.
+        PendingFunding get(fn funding_deadline): map hasher(blake2_128_concat)
+                                                 LiabilityIndex<T> => Option<BlockNumber<T>>;
+        /// Liabilities cancelled at given block unless funded, i.e. the next block after
+        /// their funding deadline.
+        FundingExpiringAt: map hasher(twox_64_concat) BlockNumber<T> => Vec<LiabilityIndex<T>>;
+        /// Block of `FundingExpiringAt` swept next and count of its liabilities already swept.
+        FundingSweepCursor get(fn funding_sweep_cursor): (BlockNumber<T>, u32);
+        /// Commitment to report of liability and the last block of its reveal window.
+        ReportCommitments get(fn commitment_of): map hasher(blake2_128_concat)
+                                                 LiabilityIndex<T> => Option<(H256, BlockNumber<T>)>;
+        /// Liabilities finalized as failed at given block unless revealed, i.e. the next
+        /// block after their reveal window.
+        RevealExpiringAt: map hasher(twox_64_concat) BlockNumber<T> => Vec<LiabilityIndex<T>>;
+        /// Block of `RevealExpiringAt` swept next and count of its liabilities already swept.
+        RevealSweepCursor get(fn reveal_sweep_cursor): (BlockNumber<T>, u32);
+        /// Registered report witnesses.
+        Witnesses get(fn witnesses): Vec<AccountId<T>>;
+        /// Stake reserved by report witness, it's reduced by slashes.
+        WitnessStake get(fn witness_stake): map hasher(blake2_128_concat) AccountId<T> => Option<BalanceOf<T>>;
+        /// Count of notarizations of witness made for reports not settled yet.
+        WitnessPending get(fn witness_pending): map hasher(blake2_128_concat) AccountId<T> => u32;
+        /// Count of witnesses required to settle liability in promisor favor.
+        WitnessQuorum get(fn witness_quorum): map hasher(blake2_128_concat)
+                                              LiabilityIndex<T> => Option<u32>;
+        /// Witnesses that notarized report of liability waiting for settlement.
+        Notarizations get(fn notarizations): map hasher(blake2_128_concat)
+                                             LiabilityIndex<T> => Vec<AccountId<T>>;
+        /// The last block of challenge window of witnessed report waiting for settlement.
+        PendingSettlement get(fn settlement_deadline): map hasher(blake2_128_concat)
+                                                       LiabilityIndex<T> => Option<BlockNumber<T>>;
+        /// Witnessed liabilities settled at given block unless overturned, i.e. the next
+        /// block after their challenge window.
+        SettlementsAt: map hasher(twox_64_concat) BlockNumber<T> => Vec<LiabilityIndex<T>>;
+        /// Block of `SettlementsAt` swept next and count of its liabilities already swept.
+        SettlementSweepCursor get(fn settlement_sweep_cursor): (BlockNumber<T>, u32);
+        /// Witnessed reports held from settlement at the end of challenge window until
+        /// they're resolved, e.g. disputed before jury.
+        HeldSettlements get(fn is_settlement_held): map hasher(blake2_128_concat)
+                                                    LiabilityIndex<T> => bool;
+        /// Block of match commitment by matcher account, see `match_commitment`.
+        MatchCommitments get(fn match_committed_at): double_map hasher(blake2_128_concat) AccountId<T>,
+                                                     hasher(identity) H256 => Option<BlockNumber<T>>;
+        /// Match commitments removed at given block unless revealed, i.e. the next block
+        /// after their reveal window.
+        MatchCommitsExpiringAt: map hasher(twox_64_concat) BlockNumber<T> => Vec<(AccountId<T>, H256)>;
+        /// Block of `MatchCommitsExpiringAt` swept next and count of its commitments already
+        /// swept.
+        MatchCommitSweepCursor get(fn match_commit_sweep_cursor): (BlockNumber<T>, u32);
+        /// Count of match commitments of account waiting for reveal.
+        MatchCommitCount get(fn match_commit_count): map hasher(blake2_128_concat) AccountId<T> => u32;
+        /// Assignment pool of market: promisor, its standing offer, stake and join block.
+        Pools get(fn pool): map hasher(twox_64_concat) MarketId
+                            => Vec<(AccountId<T>, OrderId, BalanceOf<T>, BlockNumber<T>)>;
+        /// Block of promisor assignment request of demand until promisor is selected.
+        PendingAssignment get(fn pending_assignment): map hasher(twox_64_concat)
+                                                      OrderId => Option<BlockNumber<T>>;
+        /// Demands with promisor selected at given block.
+        AssignmentsAt: map hasher(twox_64_concat) BlockNumber<T> => Vec<OrderId>;
+        /// Block of `AssignmentsAt` swept next and count of its demands already swept.
+        AssignmentSweepCursor get(fn assignment_sweep_cursor): (BlockNumber<T>, u32);
+        /// Acknowledgement deadline of new liability and deposit reserved from its promisor
+        /// until it's acknowledged.
+        PendingAcknowledgement get(fn pending_acknowledgement): map hasher(blake2_128_concat)
+                               LiabilityIndex<T> => Option<(BlockNumber<T>, BalanceOf<T>)>;
+        /// Count of economics amendments of liability, nonce of the next amendment proofs.
+        AmendmentsOf get(fn amendments_of): map hasher(blake2_128_concat) LiabilityIndex<T> => u32;
+        /// Default auxiliary parameters of account liabilities.
+        Preferences get(fn preferences): map hasher(blake2_128_concat)
+                                         AccountId<T> => Option<AccountPrefsOf<T>>;
+        /// Account paid instead of promisor of liability, fixed at liability creation.
+        PayoutOf get(fn payout_of): map hasher(blake2_128_concat)
+                                    LiabilityIndex<T> => Option<AccountId<T>>;
+        /// Count of blinded liabilities, index of the next one.
+        BlindedCount get(fn blinded_count): LiabilityIndex<T>;
+        /// Blinded liabilities by their own index.
+        BlindedLiabilities get(fn blinded_liability): map hasher(blake2_128_concat)
+                                                      LiabilityIndex<T> => Option<BlindedLiabilityOf<T>>;
+        /// Hashes of started blinded agreements, so agreement proofs aren't replayed.
+        BlindedAgreements: map hasher(blake2_128_concat) H256 => ();
+        /// Count of leaves of finalizations range, see `robonomics_liability_core::mmr`.
+        FinalizationLeafCount get(fn finalization_leaf_count): u64;
+        /// Nodes of finalizations range by their position.
+        FinalizationNodes: map hasher(identity) u64 => H256;
+        /// Leaves of finalizations range by their index.
+        FinalizationLeaves: map hasher(identity) u64 => Option<FinalizationLeafOf<T>>;
+        /// Index of finalizations range leaf of liability.
+        FinalizationLeafIndex get(fn finalization_leaf_index): map hasher(blake2_128_concat)
+                                                               LiabilityIndex<T> => Option<u64>;
+        /// Root of finalizations range, it's relayed to bridged chains.
+        FinalizationRoot get(fn finalization_root): H256;
+        /// The new key of rotated promisor key, old key can't report or take liabilities.
+        RotatedPromisor get(fn rotated_promisor): map hasher(blake2_128_concat)
+                                                  AccountId<T> => Option<AccountId<T>>;
+        /// Local index of the next liability of rotated promisor key to rotate.
+        PendingRotation get(fn pending_rotation): map hasher(blake2_128_concat)
+                                                  AccountId<T> => Option<LiabilityIndex<T>>;
+        /// Liabilities with record that can't be decoded, they're settled by `force_resolve`
+        /// only.
+        Quarantined get(fn is_quarantined): map hasher(twox_64_concat) LiabilityIndex<T> => bool;
+        /// Sorted disjoint ranges `[start, end)` of pruned liability indices. Pruned index
+        /// is never allocated again, so ranges are never removed.
+        Tombstones get(fn tombstones): Vec<(LiabilityIndex<T>, LiabilityIndex<T>)>;
+        /// Legacy factory and Ethereum state root anchored by governance, legacy liabilities
+        /// are imported by proofs against it.
+        LegacyAnchor get(fn legacy_anchor): Option<(H160, H256)>;
+        /// Imported liabilities by address of legacy Ethereum contract. It's kept when
+        /// liability is pruned, so contract is never imported again.
+        LegacyImports get(fn legacy_import): map hasher(blake2_128_concat)
+                                             H160 => Option<LiabilityIndex<T>>;
+        /// Address of legacy Ethereum contract of imported liability.
+        ImportedFrom get(fn imported_from): map hasher(blake2_128_concat)
+                                            LiabilityIndex<T> => Option<H160>;
+        /// Storage release, migrations on runtime upgrade are dispatched by it.
+        StorageVersion get(fn storage_version) build(|_| STORAGE_VERSION): Releases;
+    }
+    add_extra_genesis {
+        /// Liabilities created at genesis: technics, economics, promisee, promisor.
+        ///
+        /// Genesis is trusted, so parties proofs aren't required.
+        config(liabilities): Vec<(TechnicalParam<T>, EconomicalParam<T>, AccountId<T>, AccountId<T>)>;
+        /// Lighthouses registered at genesis: market, lighthouse and its stake reserved
+        /// from genesis balance.
+        config(lighthouses): Vec<(MarketId, AccountId<T>, BalanceOf<T>)>;
+        /// Fees of markets set at genesis.
+        config(market_fees): Vec<(MarketId, Perbill)>;
+        build(|config: &GenesisConfig<T>| {
+            for (technics, economics, promisee, promisor) in config.liabilities.iter() {
+                let liability = T::Liability::new(
+                    technics.clone(),
+                    economics.clone(),
                     promisee.clone(),
                     promisor.clone(),
-                    promisor_proof.clone(),
-                    promisor_proof.clone(),
-                ),
-                Error::<Runtime>::BadPromiseeProof
+                );
+                liability.on_start().expect("genesis liability processing failed");
+                Module::<T>::store_liability(liability, Zero::zero());
+            }
+            for (market_id, lighthouse, stake) in config.lighthouses.iter() {
+                NamedReserve::<T>::reserve_named(&RESERVE_ID, lighthouse, *stake)
+                    .expect("genesis lighthouse stake is reserved");
+                <Lighthouses<T>>::mutate(market_id, |lighthouses| {
+                    lighthouses.push(lighthouse.clone())
+                });
+                <LighthouseStake<T>>::insert(market_id, lighthouse, stake);
+            }
+            for (market_id, fee) in config.market_fees.iter() {
+                let market = MarketConfig {
+                    fee: *fee,
+                    ..Module::<T>::default_market_config()
+                };
+                <Markets<T>>::insert(market_id, market);
+            }
+        });
+    }
+}
+
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        /// Period in blocks of `EpochStats` event, zero disables it.
+        const StatsPeriod: BlockNumber<T> = T::StatsPeriod::get();
+
+        /// Count of blocks of statistics era, running totals are snapshotted at its end, zero
+        /// disables snapshots.
+        const StatsEraLength: BlockNumber<T> = T::StatsEraLength::get();
+
+        /// Count of the last eras statistics snapshots are kept for.
+        const StatsRetention: u32 = T::StatsRetention::get();
+
+        /// Count of blocks `CreatedInBlock` index is kept for, zero keeps it forever.
+        const CreatedRetention: BlockNumber<T> = T::CreatedRetention::get();
+
+        /// Count of bits of filter of accounts touched by liability transitions deposited into
+        /// block digest, see `AccountBloom`, zero disables it.
+        const DigestBloomBits: u32 = T::DigestBloomBits::get();
+
+        /// Count of hashes of account in digest filter, at most `MAX_BLOOM_HASHES`.
+        const DigestBloomHashes: u8 = T::DigestBloomHashes::get();
+
+        /// Maximal count of unsigned `create` and `finalize` calls in a block.
+        const MaxUnsignedPerBlock: u32 = T::MaxUnsignedPerBlock::get();
+
+        /// Maximal count of liabilities created in a block by the same promisee and promisor.
+        const MaxPairPerBlock: u32 = T::MaxPairPerBlock::get();
+
+        /// Maximal encoded size in bytes of technical parameter of liability or market order.
+        const MaxTechnicsLength: u32 = T::MaxTechnicsLength::get();
+
+        /// Maximal encoded size in bytes of liability report.
+        const MaxReportLength: u32 = T::MaxReportLength::get();
+
+        /// Maximal count of tags of liability.
+        const MaxTags: u32 = T::MaxTags::get();
+
+        /// Count of the latest liabilities of tag kept in `ByTag`, the oldest are pruned.
+        const MaxTaggedLiabilities: u32 = T::MaxTaggedLiabilities::get();
+
+        /// Maximal count of promisors listed by promisee requirement.
+        const MaxAllowedPromisors: u32 = T::MaxAllowedPromisors::get();
+
+        /// Maximal weight of lifecycle handlers called for liability creation or report.
+        const MaxHooksWeight: Weight = T::MaxHooksWeight::get();
+
+        /// Only approved promisors could take new liabilities when `true`.
+        const PromisorWhitelist: bool = T::PromisorWhitelist::get();
+
+        /// Promisee should have verified identity when liability value exceeds this threshold.
+        const IdentityThreshold: T::Balance = T::IdentityThreshold::get();
+
+        /// Minimal age in blocks of liability that could be force finalized, markets
+        /// configured by governance set their own.
+        const MinForceFinalizeAge: BlockNumber<T> = T::MinForceFinalizeAge::get();
+
+        /// Minimal age in blocks of liability that could be reported.
+        const MinReportDelay: BlockNumber<T> = T::MinReportDelay::get();
+
+        /// Deposit reserved for each market order until it's removed. Deposit of account order
+        /// grows quadratically with its open orders: `OrderDeposit * (open + 1)^2`. Markets
+        /// configured by governance set their own.
+        const OrderDeposit: BalanceOf<T> = T::OrderDeposit::get();
+
+        /// Maximal count of open orders of both sides posted by account.
+        const MaxOpenOrdersPerAccount: u32 = T::MaxOpenOrdersPerAccount::get();
+
+        /// Deposit reserved from account noting report preimage until it's pruned.
+        const ReportPreimageDeposit: BalanceOf<T> = T::ReportPreimageDeposit::get();
+
+        /// Count of blocks report preimage is kept in state after it's noted or its report is
+        /// accepted, liability report could be disputed with its bytes during this window.
+        /// Reports of markets configured by governance are kept for their report retention.
+        const ReportChallengeWindow: BlockNumber<T> = T::ReportChallengeWindow::get();
+
+        /// Maximal count of open orders of each side of market book.
+        const MaxOrdersPerMarket: u32 = T::MaxOrdersPerMarket::get();
+
+        /// Minimal quantity of market order fill, residual below it is closed.
+        const MinFillQuantity: u32 = T::MinFillQuantity::get();
+
+        /// Share of order deposit slashed to `MarketFeeDestination` when order expires.
+        const ExpiredOrderSlash: Perbill = T::ExpiredOrderSlash::get();
+
+        /// Maximal count of items processed by each `on_initialize` sweep in a block: items
+        /// and empty blocks of block indexed lists, e.g. `OrdersExpiringAt`, pruned blocks of
+        /// `CreatedInBlock`. The rest is processed in the next blocks.
+        const MaxSweepItemsPerBlock: u32 = T::MaxSweepItemsPerBlock::get();
+
+        /// Count of the latest matches of market kept in `RecentMatches`.
+        const MaxRecentMatches: u32 = T::MaxRecentMatches::get();
+
+        /// Minimal stake reserved by lighthouse registered to market.
+        const LighthouseMinStake: BalanceOf<T> = T::LighthouseMinStake::get();
+
+        /// Maximal count of lighthouses registered to market.
+        const MaxLighthouses: u32 = T::MaxLighthouses::get();
+
+        /// Count of blocks each lighthouse of market is scheduled for in turn.
+        const LighthouseSlot: BlockNumber<T> = T::LighthouseSlot::get();
+
+        /// Share of market fee paid to matching lighthouse, the rest goes to `MarketFeeDestination`.
+        const LighthouseFeeShare: Perbill = T::LighthouseFeeShare::get();
+
+        /// Count of blocks after commitment to match of orders it could be revealed by
+        /// `reveal_match`, stale commitments are removed after them.
+        const MatchCommitWindow: BlockNumber<T> = T::MatchCommitWindow::get();
+
+        /// Maximal count of match commitments of account waiting for reveal.
+        const MaxMatchCommits: u32 = T::MaxMatchCommits::get();
+
+        /// Share of liability value charged by `ChargeLiabilityFee` on top of standard fee.
+        const ValueFee: Perbill = T::ValueFee::get();
+
+        /// Minimal value scaled part of fee of liability with monetary value.
+        const MinValueFee: BalanceOf<T> = T::MinValueFee::get();
+
+        /// Maximal value scaled part of fee.
+        const MaxValueFee: BalanceOf<T> = T::MaxValueFee::get();
+
+        /// Count of blocks after signed order expiry its breach could be claimed, it's also
+        /// the lock of unbonded signed orders funds.
+        const BreachClaimPeriod: BlockNumber<T> = T::BreachClaimPeriod::get();
+
+        /// Funds slashed from signed orders bond of party that reneged on agreement.
+        const OrderBreachSlash: BalanceOf<T> = T::OrderBreachSlash::get();
+
+        /// Count of blocks promisee has to fund deferred funding liability, it's cancelled
+        /// after them.
+        const FundingDeadline: BlockNumber<T> = T::FundingDeadline::get();
+
+        /// Count of blocks promisor has to reveal committed report, liability is finalized
+        /// as failed after them.
+        const RevealWindow: BlockNumber<T> = T::RevealWindow::get();
+
+        /// Maximal distance in blocks between report arrival claimed by promisor and its
+        /// inclusion block, see `finalize_claimed`.
+        const ClaimTolerance: BlockNumber<T> = T::ClaimTolerance::get();
+
+        /// Minimal stake reserved by report witness.
+        const WitnessMinStake: BalanceOf<T> = T::WitnessMinStake::get();
+
+        /// Maximal count of registered report witnesses, it's also the maximal quorum.
+        const MaxWitnesses: u32 = T::MaxWitnesses::get();
+
+        /// Funds slashed to promisee from each witness that notarized overturned report.
+        const WitnessSlash: BalanceOf<T> = T::WitnessSlash::get();
+
+        /// Minimal stake reserved by promisor joining assignment pool of market.
+        const PoolMinStake: BalanceOf<T> = T::PoolMinStake::get();
+
+        /// Maximal count of promisors in assignment pool of market.
+        const MaxPoolSize: u32 = T::MaxPoolSize::get();
+
+        /// Count of blocks between assignment request and promisor selection, randomness of
+        /// selection block isn't known when assignment is requested.
+        const AssignmentDelay: BlockNumber<T> = T::AssignmentDelay::get();
+
+        /// Count of blocks promisor has to acknowledge new liability in, otherwise promisee
+        /// could reclaim it. Zero disables acknowledgement.
+        const AcknowledgementWindow: BlockNumber<T> = T::AcknowledgementWindow::get();
+
+        /// Deposit reserved from promisor of new liability until it's acknowledged, it's paid
+        /// to promisee of reclaimed liability.
+        const AcknowledgementDeposit: BalanceOf<T> = T::AcknowledgementDeposit::get();
+
+        /// Count of blocks liability could stay open after creation, any account could
+        /// `force_expire` it later. Zero disables expiry.
+        const MaxLiabilityLifetime: BlockNumber<T> = T::MaxLiabilityLifetime::get();
+
+        /// Part of promisor acknowledgement deposit paid to caller of `force_expire`.
+        const ExpiryIncentive: BalanceOf<T> = T::ExpiryIncentive::get();
+
+        /// Deposit reserved from promisee of zero value liability until it's closed.
+        const ZeroValueDeposit: BalanceOf<T> = T::ZeroValueDeposit::get();
+
+        /// Maximal count of zero value liabilities of promisee per statistics era.
+        const MaxZeroValuePerEra: u32 = T::MaxZeroValuePerEra::get();
+
+        /// Base transaction pool priority of unsigned `create`, increased by liability value
+        /// but always lower than `FinalizePriority`.
+        const CreatePriority: TransactionPriority = T::CreatePriority::get();
+
+        /// Transaction pool priority of unsigned `finalize`.
+        const FinalizePriority: TransactionPriority = T::FinalizePriority::get();
+
+        fn deposit_event() = default;
+
+        fn on_runtime_upgrade() -> Weight {
+            migration::migrate::<T>()
+        }
+
+        fn on_initialize(n: T::BlockNumber) -> Weight {
+            // Events deposited on runtime upgrade are dropped by block initialization
+            for (index, is_report) in <OversizedRejections<T>>::take() {
+                if is_report {
+                    Self::deposit_event(RawEvent::OversizedReportRejected(index.into()));
+                } else {
+                    Self::deposit_event(RawEvent::OversizedLiabilityRejected(index.into()));
+                }
+            }
+
+            // Weight of `on_finalize` is reserved at block initialization
+            Self::prune_created_index(n)
+                .weight::<T>()
+                .saturating_add(Self::sweep_expired_orders(n).weight::<T>())
+                .saturating_add(Self::expire_funding(n).weight::<T>())
+                .saturating_add(Self::expire_reveals(n).weight::<T>())
+                .saturating_add(Self::expire_match_commits(n).weight::<T>())
+                .saturating_add(Self::settle_witnessed(n).weight::<T>())
+                .saturating_add(Self::snapshot_era(n))
+                .saturating_add(Self::assign_demands(n).weight::<T>())
+                .saturating_add(T::DbWeight::get().reads_writes(1, 1))
+                .saturating_add(Self::on_finalize_weight())
+        }
+
+        /// Finalize liabilities of local promisor keys using results published on IPFS,
+        /// see `offchain` module.
+        fn offchain_worker(n: T::BlockNumber) {
+            offchain::auto_finalize::<T>(n);
+            offchain::auto_match::<T>(n);
+        }
+
+        fn on_finalize(n: T::BlockNumber) {
+            Self::clear_block_state(n);
+            Self::deposit_bloom();
+
+            // Idle hook isn't available in this FRAME release, weight of normal class left
+            // after extrinsics is used the same way at block finalization
+            let limit = T::AvailableBlockRatio::get() * T::MaximumBlockWeight::get();
+            let consumed = <frame_system::Module<T>>::all_extrinsics_weight();
+            let remaining = limit.saturating_sub(consumed);
+            let idle_weight = Self::on_idle(n, remaining);
+            <frame_system::Module<T>>::register_extra_weight_unchecked(idle_weight);
+
+            let period = T::StatsPeriod::get();
+            if !period.is_zero() && (n % period).is_zero() {
+                Self::deposit_event(RawEvent::EpochStats(<Counters<T>>::get()));
+            }
+        }
+
+        /// Create agreement between two parties.
+        #[weight = {
+            let t = technics.using_encoded(|t| t.len() as u32);
+            let proofs = [promisee_proof, promisor_proof];
+            Module::<T>::proofs_weight(T::WeightInfo::create(t), &proofs, t)
+        }]
+        fn create(
+            origin,
+            technics: TechnicalParam<T>,
+            economics: EconomicalParam<T>,
+            promisee: AccountId<T>,
+            promisor: AccountId<T>,
+            promisee_proof: ProofParam<T>,
+            promisor_proof: ProofParam<T>,
+        ) {
+            ensure_none(origin)?;
+            let liability = T::Liability::new(technics, economics, promisee, promisor);
+            Self::create_agreement(
+                liability,
+                None,
+                Vec::new(),
+                None,
+                None,
+                promisee_proof,
+                promisor_proof,
+            )?;
+        }
+
+        /// Create agreement between two parties with tags for search, promisee proof should
+        /// cover them, see `tagged_params_payload`.
+        #[weight = {
+            let t = technics.using_encoded(|t| t.len() as u32);
+            let g = tags.len() as Weight;
+            let proofs = [promisee_proof, promisor_proof];
+            Module::<T>::proofs_weight(T::WeightInfo::create(t), &proofs, t)
+                .saturating_add(T::DbWeight::get().reads_writes(g, g + 1))
+        }]
+        fn create_tagged(
+            origin,
+            technics: TechnicalParam<T>,
+            economics: EconomicalParam<T>,
+            tags: Vec<Tag>,
+            promisee: AccountId<T>,
+            promisor: AccountId<T>,
+            promisee_proof: ProofParam<T>,
+            promisor_proof: ProofParam<T>,
+        ) {
+            ensure_none(origin)?;
+            let liability = T::Liability::new(technics, economics, promisee, promisor);
+            Self::create_agreement(
+                liability,
+                None,
+                tags,
+                None,
+                None,
+                promisee_proof,
+                promisor_proof,
+            )?;
+        }
+
+        /// Create agreement between two parties with promisee requirement to promisor: list
+        /// of allowed promisors or registry owner or metadata of promisor robot. Promisee
+        /// proof should cover it, see `restricted_params_payload`.
+        #[weight = {
+            let t = technics.using_encoded(|t| t.len() as u32);
+            let r = requirement.using_encoded(|r| r.len() as u32);
+            let proofs = [promisee_proof, promisor_proof];
+            Module::<T>::proofs_weight(T::WeightInfo::create(t), &proofs, t.saturating_add(r))
+                .saturating_add(T::DbWeight::get().reads(4))
+        }]
+        fn create_restricted(
+            origin,
+            technics: TechnicalParam<T>,
+            economics: EconomicalParam<T>,
+            promisee: AccountId<T>,
+            promisor: AccountId<T>,
+            requirement: PromisorRequirementOf<T>,
+            promisee_proof: ProofParam<T>,
+            promisor_proof: ProofParam<T>,
+        ) {
+            ensure_none(origin)?;
+            let liability = T::Liability::new(technics, economics, promisee, promisor);
+            Self::create_agreement(
+                liability,
+                None,
+                Vec::new(),
+                None,
+                Some(&requirement),
+                promisee_proof,
+                promisor_proof,
+            )?;
+        }
+
+        /// Create agreement between two parties with bounty reserved from promisee for
+        /// relayer of liability report, promisee proof should cover it, see
+        /// `bounty_params_payload`. Bounty is paid by `finalize_relayed` and returned to
+        /// promisee when liability is finalized otherwise.
+        #[weight = {
+            let t = technics.using_encoded(|t| t.len() as u32);
+            let proofs = [promisee_proof, promisor_proof];
+            Module::<T>::proofs_weight(T::WeightInfo::create(t), &proofs, t)
+                .saturating_add(T::DbWeight::get().reads_writes(1, 2))
+        }]
+        fn create_with_bounty(
+            origin,
+            technics: TechnicalParam<T>,
+            economics: EconomicalParam<T>,
+            promisee: AccountId<T>,
+            promisor: AccountId<T>,
+            bounty: BalanceOf<T>,
+            promisee_proof: ProofParam<T>,
+            promisor_proof: ProofParam<T>,
+        ) {
+            ensure_none(origin)?;
+            let liability = T::Liability::new(technics, economics, promisee, promisor);
+            Self::create_agreement(
+                liability,
+                None,
+                Vec::new(),
+                Some(bounty),
+                None,
+                promisee_proof,
+                promisor_proof,
+            )?;
+        }
+
+        /// Create agreement between two parties with proofs valid until `expires_at` block
+        /// inclusive, see `expiring_params_payload`.
+        ///
+        /// Parties choose the horizon they're ready to be committed to an offer, e.g.
+        /// current block plus transaction pool longevity of the call.
+        #[weight = {
+            let t = technics.using_encoded(|t| t.len() as u32);
+            let proofs = [promisee_proof, promisor_proof];
+            Module::<T>::proofs_weight(T::WeightInfo::create(t), &proofs, t)
+        }]
+        fn create_expiring(
+            origin,
+            technics: TechnicalParam<T>,
+            economics: EconomicalParam<T>,
+            promisee: AccountId<T>,
+            promisor: AccountId<T>,
+            expires_at: BlockNumber<T>,
+            promisee_proof: ProofParam<T>,
+            promisor_proof: ProofParam<T>,
+        ) {
+            ensure_none(origin)?;
+            let liability = T::Liability::new(technics, economics, promisee, promisor);
+            Self::create_agreement(
+                liability,
+                Some(expires_at),
+                Vec::new(),
+                None,
+                None,
+                promisee_proof,
+                promisor_proof,
+            )?;
+        }
+
+        /// Create liability requested from another chain, e.g. by XCM `Transact` of sibling
+        /// parachain. Remote origin is converted to promisee account, so promisee proof
+        /// isn't required. Index of new liability is kept in `RemoteRequests` by `request_id`.
+        #[weight = {
+            let t = technics.using_encoded(|t| t.len() as u32);
+            Module::<T>::proofs_weight(T::WeightInfo::create(t), &[promisor_proof], t)
+        }]
+        fn create_remote(
+            origin,
+            request_id: u64,
+            technics: TechnicalParam<T>,
+            economics: EconomicalParam<T>,
+            promisor: AccountId<T>,
+            promisor_proof: ProofParam<T>,
+        ) -> DispatchResultWithPostInfo {
+            let promisee = T::RemoteOrigin::ensure_origin(origin)?;
+
+            // Repeated request is rejected by a single read, the rest of weight is refunded
+            let duplicate_weight = T::DbWeight::get().reads(1);
+            Self::ensure_technics_length(&technics).map_err(|e| e.with_weight(duplicate_weight))?;
+            ensure!(
+                !<RemoteRequests<T>>::contains_key(&promisee, request_id),
+                Error::<T>::DuplicateRequest.with_weight(duplicate_weight)
+            );
+            Self::ensure_not_blacklisted(&promisee)?;
+            Self::ensure_not_blacklisted(&promisor)?;
+            Self::ensure_approved(&promisor)?;
+            Self::ensure_capable(&technics, &promisor)?;
+            Self::ensure_identity(&economics, &promisee)?;
+
+            let liability = T::Liability::new(technics, economics, promisee.clone(), promisor);
+
+            // Check promisor proof
+            if !liability.check_params(&promisor_proof, liability.promisor()) {
+                Err(Error::<T>::BadPromisorProof)?
+            }
+
+            let index = Self::start_liability(liability, Vec::new())?;
+            <RemoteRequests<T>>::insert(promisee, request_id, index);
+            Ok(().into())
+        }
+
+        /// Create agreement of pipeline: technics is derived from accepted report of
+        /// `parent` liability, see `Technical::derive_next`, parties sign `create` proofs
+        /// of derived technics.
+        #[weight = {
+            let t = T::MaxTechnicsLength::get();
+            let proofs = [promisee_proof, promisor_proof];
+            Module::<T>::proofs_weight(T::WeightInfo::create(t), &proofs, t)
+                .saturating_add(T::DbWeight::get().reads(2))
+        }]
+        fn create_chained(
+            origin,
+            #[compact] parent: LiabilityIndex<T>,
+            economics: EconomicalParam<T>,
+            promisee: AccountId<T>,
+            promisor: AccountId<T>,
+            promisee_proof: ProofParam<T>,
+            promisor_proof: ProofParam<T>,
+        ) {
+            let technics = Self::chained_technics(parent)?;
+            Self::create(
+                origin,
+                technics,
+                economics,
+                promisee,
+                promisor,
+                promisee_proof,
+                promisor_proof,
+            )?;
+            let index = <LatestIndex<T>>::get() - One::one();
+            Self::deposit_event(RawEvent::LiabilityChained(index.into(), parent.into()));
+        }
+
+        /// Publish technical report of complite works.
+        #[weight = {
+            let r = report.using_encoded(|r| r.len() as u32);
+            Module::<T>::proofs_weight(T::WeightInfo::finalize(r), &[proof], r)
+        }]
+        fn finalize(
+            origin,
+            #[compact] index: LiabilityIndex<T>,
+            report: TechnicalReport<T>,
+            proof: ProofParam<T>,
+        ) -> DispatchResultWithPostInfo {
+            ensure_none(origin)?;
+            Self::accept_report(index, report, None, proof)
+        }
+
+        /// Publish technical report with firmware hash attested by promisor, it's required
+        /// for liabilities with strict firmware economics.
+        #[weight = {
+            let r = report.using_encoded(|r| r.len() as u32);
+            Module::<T>::proofs_weight(T::WeightInfo::finalize(r), &[proof], r)
+        }]
+        fn finalize_attested(
+            origin,
+            #[compact] index: LiabilityIndex<T>,
+            report: TechnicalReport<T>,
+            firmware: T::Hash,
+            proof: ProofParam<T>,
+        ) -> DispatchResultWithPostInfo {
+            ensure_none(origin)?;
+            Self::accept_report(index, report, Some(firmware), proof)
+        }
+
+        /// Publish technical report as `finalize` naming its submitter, so submission could
+        /// be attributed in disputes. Submitter isn't covered by report proof, it's recorded
+        /// as `Submitter::Unauthenticated` and never used to authorize.
+        #[weight = {
+            let r = report.using_encoded(|r| r.len() as u32);
+            Module::<T>::proofs_weight(T::WeightInfo::finalize(r), &[proof], r)
+                .saturating_add(T::DbWeight::get().writes(1))
+        }]
+        fn finalize_attributed(
+            origin,
+            #[compact] index: LiabilityIndex<T>,
+            report: TechnicalReport<T>,
+            proof: ProofParam<T>,
+            submitter: AccountId<T>,
+        ) -> DispatchResultWithPostInfo {
+            ensure_none(origin)?;
+            let post_info = Self::accept_report(index, report, None, proof)?;
+            Self::note_submitter(index, Submitter::Unauthenticated(submitter));
+            Ok(post_info)
+        }
+
+        /// Publish technical report of liability referenced by its content-derived id,
+        /// report proof is made for liability index as in `finalize`.
+        #[weight = {
+            let r = report.using_encoded(|r| r.len() as u32);
+            Module::<T>::proofs_weight(T::WeightInfo::finalize(r), &[proof], r)
+                .saturating_add(T::DbWeight::get().reads(1))
+        }]
+        fn finalize_by_id(
+            origin,
+            id: H256,
+            report: TechnicalReport<T>,
+            proof: ProofParam<T>,
+        ) -> DispatchResultWithPostInfo {
+            ensure_none(origin)?;
+            let index = <IdToIndex<T>>::get(id)
+                .ok_or(Error::<T>::UnknownLiability.with_weight(T::DbWeight::get().reads(1)))?;
+            Self::accept_report(index, report, None, proof)
+        }
+
+        /// Publish technical report with promisor claim of its arrival block, see
+        /// `claim_payload`, so relayer delaying inclusion doesn't make the report late.
+        /// Claim should be at most `ClaimTolerance` blocks away from inclusion block and
+        /// not earlier than liability creation, the earlier of them is report arrival.
+        /// Transaction is signed, so it isn't limited by unsigned calls of block.
+        #[weight = {
+            let r = report.using_encoded(|r| r.len() as u32);
+            Module::<T>::proofs_weight(T::WeightInfo::finalize(r), &[proof, claim_proof], r)
+                .saturating_add(T::DbWeight::get().writes(1))
+        }]
+        fn finalize_claimed(
+            origin,
+            #[compact] index: LiabilityIndex<T>,
+            report: TechnicalReport<T>,
+            firmware: Option<T::Hash>,
+            proof: ProofParam<T>,
+            claimed_at: BlockNumber<T>,
+            claim_proof: ProofParam<T>,
+        ) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+            let record = Self::check_open_report(index, &report, firmware.as_ref(), &proof)?;
+            let arrival = Self::check_claim(index, &record, claimed_at, &claim_proof)?;
+            let post_info = Self::store_report(index, record, report, firmware)?;
+            <ClaimedArrival<T>>::insert(index, arrival);
+            Self::deposit_event(RawEvent::ArrivalClaimed(index.into(), arrival));
+            Self::note_submitter(index, Submitter::Signed(sender));
+            Ok(post_info)
+        }
+
+        /// Publish technical report of liability with relay bounty, the first relayer
+        /// of valid promisor proof is paid the bounty, see `create_with_bounty`. Proof
+        /// covers the report, so relayer can't alter it and still claim. Bounty returns
+        /// to promisee when promisor relays its report.
+        #[weight = {
+            let r = report.using_encoded(|r| r.len() as u32);
+            Module::<T>::proofs_weight(T::WeightInfo::finalize(r), &[proof], r)
+                .saturating_add(T::DbWeight::get().reads_writes(2, 3))
+        }]
+        fn finalize_relayed(
+            origin,
+            #[compact] index: LiabilityIndex<T>,
+            report: TechnicalReport<T>,
+            proof: ProofParam<T>,
+        ) -> DispatchResultWithPostInfo {
+            let relayer = ensure_signed(origin)?;
+            let record = Self::check_open_report(index, &report, None, &proof)?;
+            let promisee = record.agreement.promisee().clone();
+            let promisor = record.agreement.promisor().clone();
+
+            // Bounty is taken before store, so it isn't refunded there
+            let bounty = <RelayBounty<T>>::take(index);
+            let post_info = Self::store_report(index, record, report, None).map_err(|e| {
+                if let Some(bounty) = bounty {
+                    <RelayBounty<T>>::insert(index, bounty);
+                }
+                e
+            })?;
+            if let Some(bounty) = bounty {
+                if relayer == promisor {
+                    NamedReserve::<T>::unreserve_named(&RESERVE_ID, &promisee, bounty);
+                    Self::deposit_event(RawEvent::RelayBountyRefunded(index.into(), bounty));
+                } else {
+                    let not_moved = NamedReserve::<T>::repatriate_reserved_named(
+                        &RESERVE_ID,
+                        &promisee,
+                        &relayer,
+                        bounty,
+                        BalanceStatus::Free,
+                    )
+                    .unwrap_or(bounty);
+                    let paid = bounty.saturating_sub(not_moved);
+                    Self::deposit_event(RawEvent::RelayBountyPaid(
+                        index.into(),
+                        relayer.clone(),
+                        paid,
+                    ));
+                }
+            }
+            Self::note_submitter(index, Submitter::Signed(relayer));
+            Ok(post_info)
+        }
+
+        /// Publish commitment to technical report, see `report_commitment`, when report
+        /// shouldn't be revealed before payment is locked in. Liability is frozen until
+        /// report is revealed by `reveal_report` in `RevealWindow` blocks, it's finalized
+        /// as failed after them.
+        #[weight = {
+            let p = commit_payload(index, commitment).len() as u32;
+            Module::<T>::proofs_weight(T::WeightInfo::commit_report(), &[proof], p)
+        }]
+        fn commit_report(
+            origin,
+            #[compact] index: LiabilityIndex<T>,
+            commitment: H256,
+            proof: ProofParam<T>,
+        ) {
+            ensure_none(origin)?;
+            Self::check_rate_limits(None)?;
+            let record = Self::record_of(index)?;
+            Self::check_commit(index, &record, &commitment, &proof)?;
+
+            let block_number = <frame_system::Module<T>>::block_number();
+            let deadline = block_number.saturating_add(T::RevealWindow::get());
+            <ReportCommitments<T>>::insert(index, (commitment, deadline));
+            <RevealExpiringAt<T>>::mutate(deadline.saturating_add(One::one()), |indices| {
+                indices.push(index)
+            });
+            Self::record_transition(index, block_number, Transition::Committed(commitment));
+            Self::release_acknowledgement(index, record.agreement.promisor());
+            Self::note_unsigned_call(None);
+            Self::deposit_event(RawEvent::ReportCommitted(index.into(), commitment, deadline));
+        }
+
+        /// Reveal committed technical report with its salt and finalize liability, anyone
+        /// knowing them could do it.
+        #[weight = T::WeightInfo::reveal_report(report.using_encoded(|r| r.len() as u32))]
+        fn reveal_report(
+            origin,
+            #[compact] index: LiabilityIndex<T>,
+            report: TechnicalReport<T>,
+            salt: H256,
+        ) -> DispatchResultWithPostInfo {
+            ensure_none(origin)?;
+            Self::check_rate_limits(None)?;
+            ensure!(
+                report.encoded_size() <= T::MaxReportLength::get() as usize,
+                Error::<T>::ReportTooLong
+            );
+            Self::check_reveal(index, &report, &salt)?;
+            let record = Self::record_of(index)?;
+            <ReportCommitments<T>>::remove(index);
+            Self::note_unsigned_call(None);
+            Self::store_report(index, record, report, None)
+        }
+
+        /// Approve promisor account to take new liabilities.
+        #[weight = T::WeightInfo::add_promisor()]
+        fn add_promisor(origin, who: AccountId<T>) {
+            T::AdminOrigin::ensure_origin(origin)?;
+            <ApprovedPromisors<T>>::insert(&who, ());
+            Self::deposit_event(RawEvent::PromisorAdded(who));
+        }
+
+        /// Finalize stuck liability without report, e.g. when promisor key is lost.
+        #[weight = T::WeightInfo::force_finalize()]
+        fn force_finalize(
+            origin,
+            #[compact] index: LiabilityIndex<T>,
+            success: bool,
+        ) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+
+            // Liability that can't be finalized is rejected by a single read
+            let rejected_weight = T::DbWeight::get().reads(1);
+            let record = Self::record_of(index).map_err(|e| e.with_weight(rejected_weight))?;
+            ensure!(
+                record.state == RecordState::Open,
+                Error::<T>::AlreadyFinalized.with_weight(rejected_weight)
+            );
+
+            let block_number = <frame_system::Module<T>>::block_number();
+            let (_, market) = Self::liability_market(index, &record.agreement);
+            ensure!(
+                block_number.saturating_sub(record.created_at) >= market.force_finalize_age,
+                Error::<T>::LiabilityTooYoung.with_weight(rejected_weight)
+            );
+
+            Self::force_record(index, record, success)?;
+            Ok(().into())
+        }
+
+        /// Settle quarantined liability with agreement restored by governance, e.g. decoded
+        /// by the previous runtime. Economics of restored agreement is finished with given
+        /// outcome, its record keeps no creation block.
+        #[weight = T::WeightInfo::force_finalize()]
+        fn force_resolve(
+            origin,
+            #[compact] index: LiabilityIndex<T>,
+            technics: TechnicalParam<T>,
+            economics: EconomicalParam<T>,
+            promisee: AccountId<T>,
+            promisor: AccountId<T>,
+            success: bool,
+        ) {
+            ensure_root(origin)?;
+            // Record could be repaired by storage migration after it's quarantined
+            ensure!(
+                <Quarantined<T>>::get(index) && <Liabilities<T>>::get(index).is_none(),
+                Error::<T>::NotQuarantined
+            );
+
+            let record = LiabilityRecord {
+                report_hash: None,
+                state: RecordState::Open,
+                created_at: Zero::zero(),
+                finalized_at: None,
+                agreement: T::Liability::new(technics, economics, promisee, promisor),
+            };
+            Self::force_record(index, record, success)?;
+            <Quarantined<T>>::remove(index);
+        }
+
+        /// Prune record, history and per-index entries of finalized liability, e.g. to
+        /// reclaim state, see `prune`. Index is tombstoned, it isn't allocated again and
+        /// calls of it fail with `Pruned`.
+        #[weight = T::WeightInfo::prune_liability()]
+        fn prune_liability(origin, #[compact] index: LiabilityIndex<T>) {
+            T::AdminOrigin::ensure_origin(origin)?;
+            let record = Self::record_of(index)?;
+            ensure!(
+                record.finalized_at.is_some() && !<PendingSettlement<T>>::contains_key(index),
+                Error::<T>::NotFinalized
+            );
+            Self::prune(index, record);
+        }
+
+        /// Fund deferred funding liability by its promisee: economics is started, e.g. escrow
+        /// is reserved, and promisor could report from now.
+        #[weight = T::WeightInfo::fund()]
+        fn fund(origin, #[compact] index: LiabilityIndex<T>) {
+            let sender = ensure_signed(origin)?;
+            ensure!(
+                <PendingFunding<T>>::contains_key(index),
+                Error::<T>::NotPendingFunding
+            );
+            let record = Self::record_of(index)?;
+            ensure!(
+                record.agreement.promisee() == &sender,
+                Error::<T>::NotPromisee
+            );
+
+            {
+                sp_tracing::enter_span!("liability::economics");
+                record.agreement.on_start()?;
+            }
+            <PendingFunding<T>>::remove(index);
+            let block_number = <frame_system::Module<T>>::block_number();
+            Self::record_transition(index, block_number, Transition::Funded);
+            Self::deposit_event(RawEvent::LiabilityFunded(index.into()));
+        }
+
+        /// Cancel liability before report with parties proofs valid until `expires_at` block
+        /// inclusive, see `cancel_payload`. Mutual cancel, proven by both parties, is
+        /// fee-free. Unilateral cancel pays cancellation fee of liability market to
+        /// counterparty, unfunded liability is cancelled without economical processing.
+        #[weight = {
+            let p = cancel_payload(index, expires_at).len() as u32;
+            let proofs: Vec<_> = promisee_proof.iter().chain(promisor_proof.iter()).collect();
+            Module::<T>::proofs_weight(T::WeightInfo::cancel(), &proofs, p)
+        }]
+        fn cancel(
+            origin,
+            #[compact] index: LiabilityIndex<T>,
+            expires_at: BlockNumber<T>,
+            promisee_proof: Option<ProofParam<T>>,
+            promisor_proof: Option<ProofParam<T>>,
+        ) {
+            ensure_none(origin)?;
+            Self::check_rate_limits(None)?;
+            let block_number = <frame_system::Module<T>>::block_number();
+            ensure!(block_number <= expires_at, Error::<T>::ProofExpired);
+
+            let mut record = Self::record_of(index)?;
+            ensure!(
+                record.state == RecordState::Open,
+                Error::<T>::AlreadyFinalized
+            );
+            ensure!(
+                !<ReportCommitments<T>>::contains_key(index),
+                Error::<T>::ReportCommitted
+            );
+            let party = Self::check_cancel_proofs(
+                &record.agreement,
+                &index,
+                &expires_at,
+                promisee_proof.as_ref(),
+                promisor_proof.as_ref(),
+            )?;
+
+            // Escrow of unfunded liability isn't reserved
+            let mut fee = Zero::zero();
+            if <PendingFunding<T>>::take(index).is_none() {
+                if party.is_some() {
+                    let (_, market) = Self::liability_market(index, &record.agreement);
+                    let price: u128 =
+                        Self::liability_value(record.agreement.economics()).unique_saturated_into();
+                    fee = market
+                        .cancellation_fee
+                        .amount(BalanceOf::<T>::unique_saturated_from(price));
+                }
+                sp_tracing::enter_span!("liability::economics");
+                record.agreement.on_cancel(party.map(|party| (party, fee)))?;
+            }
+
+            Self::refund_relay_bounty(index, record.agreement.promisee());
+            Self::release_zero_value_deposit(index, record.agreement.promisee());
+            Self::release_acknowledgement(index, record.agreement.promisor());
+            record.state = RecordState::Cancelled;
+            record.finalized_at = Some(block_number);
+            Self::schedule_prune(index, &record.agreement, block_number);
+            <Liabilities<T>>::insert(index, record);
+            <Counters<T>>::mutate(|counters| counters.cancelled += 1);
+            Self::record_transition(index, block_number, Transition::Cancelled);
+            Self::note_unsigned_call(None);
+            Self::deposit_event(RawEvent::LiabilityCancelled(index.into(), party, fee));
+        }
+
+        /// Amend economics of liability before report with proofs of both parties, see
+        /// `amend_payload`, e.g. price of grown job. Economics of started liability is
+        /// amended by its economical processing, e.g. escrow difference is reserved from
+        /// promisee or released to it, liability is left intact when it fails. Unfunded
+        /// liability is amended without economical processing.
+        #[weight = {
+            let p = amend_payload(index, 0, new_economics).len() as u32;
+            let proofs = [promisee_proof, promisor_proof];
+            Module::<T>::proofs_weight(T::WeightInfo::amend_economics(), &proofs, p)
+        }]
+        fn amend_economics(
+            origin,
+            #[compact] index: LiabilityIndex<T>,
+            new_economics: EconomicalParam<T>,
+            promisee_proof: ProofParam<T>,
+            promisor_proof: ProofParam<T>,
+        ) {
+            ensure_signed(origin)?;
+            let mut record = Self::record_of(index)?;
+            ensure!(
+                record.state == RecordState::Open,
+                Error::<T>::AlreadyFinalized
+            );
+            ensure!(
+                !<ReportCommitments<T>>::contains_key(index),
+                Error::<T>::ReportCommitted
+            );
+
+            // Market and funding of liability are fixed at creation
+            let liability = &record.agreement;
+            let technics = liability.technics();
+            ensure!(
+                Self::market_of(technics, &new_economics)
+                    == Self::market_of(technics, liability.economics())
+                    && T::Economics::deferred_funding(&new_economics)
+                        == T::Economics::deferred_funding(liability.economics()),
+                Error::<T>::AmendmentMismatch
+            );
+            let nonce = <AmendmentsOf<T>>::get(index);
+            let (promisee, promisor) = (liability.promisee(), liability.promisor());
+            ensure!(
+                liability.check_amendment(&index, nonce, &new_economics, &promisee_proof, promisee),
+                Error::<T>::BadPromiseeProof
+            );
+            ensure!(
+                liability.check_amendment(&index, nonce, &new_economics, &promisor_proof, promisor),
+                Error::<T>::BadPromisorProof
+            );
+
+            let amended = T::Liability::new(
+                technics.clone(),
+                new_economics.clone(),
+                promisee.clone(),
+                promisor.clone(),
+            );
+            if !<PendingFunding<T>>::contains_key(index) {
+                sp_tracing::enter_span!("liability::economics");
+                liability.on_amend(&amended)?;
+            }
+
+            record.agreement = amended;
+            <Liabilities<T>>::insert(index, record);
+            <AmendmentsOf<T>>::insert(index, nonce + 1);
+            let block_number = <frame_system::Module<T>>::block_number();
+            Self::record_transition(index, block_number, Transition::Amended(nonce + 1));
+            Self::deposit_event(RawEvent::EconomicsAmended(index.into(), new_economics));
+        }
+
+        /// Acknowledge liability by its promisor in `AcknowledgementWindow` blocks of creation,
+        /// see `acknowledge_payload`. Deposit of promisor is returned and promisee couldn't
+        /// `reclaim` liability anymore.
+        #[weight = {
+            let p = acknowledge_payload(index).len() as u32;
+            Module::<T>::proofs_weight(T::WeightInfo::acknowledge(), &[proof], p)
+        }]
+        fn acknowledge(
+            origin,
+            #[compact] index: LiabilityIndex<T>,
+            proof: ProofParam<T>,
+        ) {
+            ensure_none(origin)?;
+            Self::check_rate_limits(None)?;
+            let record = Self::record_of(index)?;
+            Self::check_acknowledge(index, &record, &proof)?;
+
+            Self::release_acknowledgement(index, record.agreement.promisor());
+            let block_number = <frame_system::Module<T>>::block_number();
+            Self::record_transition(index, block_number, Transition::Acknowledged);
+            Self::note_unsigned_call(None);
+            Self::deposit_event(RawEvent::LiabilityAcknowledged(index.into()));
+        }
+
+        /// Cancel liability not acknowledged by its promisor in `AcknowledgementWindow` by
+        /// promisee: escrow is fully returned, without cancellation fee, and deposit of
+        /// promisor is paid to promisee. Unfunded liability is cancelled without economical
+        /// processing.
+        #[weight = T::WeightInfo::reclaim()]
+        fn reclaim(origin, #[compact] index: LiabilityIndex<T>) {
+            let sender = ensure_signed(origin)?;
+            let mut record = Self::record_of(index)?;
+            ensure!(
+                record.agreement.promisee() == &sender,
+                Error::<T>::NotPromisee
+            );
+            ensure!(
+                record.state == RecordState::Open,
+                Error::<T>::AlreadyFinalized
+            );
+            let (deadline, deposit) = <PendingAcknowledgement<T>>::get(index)
+                .ok_or(Error::<T>::NotPendingAcknowledgement)?;
+            let block_number = <frame_system::Module<T>>::block_number();
+            ensure!(block_number > deadline, Error::<T>::AcknowledgementWindowOpen);
+
+            // Escrow of unfunded liability isn't reserved
+            if <PendingFunding<T>>::take(index).is_none() {
+                sp_tracing::enter_span!("liability::economics");
+                record.agreement.on_cancel(None)?;
+            }
+
+            // Deposit that couldn't be paid returns to promisor
+            let promisor = record.agreement.promisor().clone();
+            <PendingAcknowledgement<T>>::remove(index);
+            let not_paid = NamedReserve::<T>::repatriate_reserved_named(
+                &RESERVE_ID,
+                &promisor,
+                &sender,
+                deposit,
+                BalanceStatus::Free,
+            )
+            .unwrap_or(deposit);
+            NamedReserve::<T>::unreserve_named(&RESERVE_ID, &promisor, not_paid);
+
+            Self::refund_relay_bounty(index, &sender);
+            Self::release_zero_value_deposit(index, record.agreement.promisee());
+            record.state = RecordState::Cancelled;
+            record.finalized_at = Some(block_number);
+            Self::schedule_prune(index, &record.agreement, block_number);
+            <Liabilities<T>>::insert(index, record);
+            <Counters<T>>::mutate(|counters| counters.cancelled += 1);
+            Self::record_transition(index, block_number, Transition::Cancelled);
+            let paid = deposit.saturating_sub(not_paid);
+            Self::deposit_event(RawEvent::LiabilityReclaimed(index.into(), paid));
+        }
+
+        /// Cancel liability open for more than `MaxLiabilityLifetime` blocks of its creation
+        /// by any account: escrow is fully returned, without cancellation fee, and
+        /// `ExpiryIncentive` of promisor deposit still reserved for acknowledgement is paid to
+        /// caller. Unfunded liability is cancelled without economical processing. Witnessed
+        /// report in challenge window is disputed, it's overturned or settled by governance
+        /// and never expires.
+        #[weight = T::WeightInfo::force_expire()]
+        fn force_expire(origin, #[compact] index: LiabilityIndex<T>) {
+            let sender = ensure_signed(origin)?;
+            ensure!(
+                !<PendingSettlement<T>>::contains_key(index),
+                Error::<T>::SettlementDisputed
+            );
+            let mut record = Self::record_of(index)?;
+            ensure!(
+                record.state == RecordState::Open,
+                Error::<T>::AlreadyFinalized
+            );
+            ensure!(
+                !<ReportCommitments<T>>::contains_key(index),
+                Error::<T>::ReportCommitted
+            );
+            let block_number = <frame_system::Module<T>>::block_number();
+            ensure!(
+                Self::lifetime_end(&record).map_or(false, |end| block_number > end),
+                Error::<T>::LifetimeNotExceeded
+            );
+
+            // Escrow of unfunded liability isn't reserved
+            if <PendingFunding<T>>::take(index).is_none() {
+                sp_tracing::enter_span!("liability::economics");
+                record.agreement.on_cancel(None)?;
+            }
+
+            // Incentive is taken from promisor deposit, the rest returns to promisor
+            let mut incentive = Zero::zero();
+            if let Some((_, deposit)) = <PendingAcknowledgement<T>>::take(index) {
+                let promisor = record.agreement.promisor();
+                let offered = T::ExpiryIncentive::get().min(deposit);
+                let not_paid = NamedReserve::<T>::repatriate_reserved_named(
+                    &RESERVE_ID,
+                    promisor,
+                    &sender,
+                    offered,
+                    BalanceStatus::Free,
+                )
+                .unwrap_or(offered);
+                incentive = offered.saturating_sub(not_paid);
+                NamedReserve::<T>::unreserve_named(
+                    &RESERVE_ID,
+                    promisor,
+                    deposit.saturating_sub(incentive),
+                );
+            }
+
+            Self::refund_relay_bounty(index, record.agreement.promisee());
+            Self::release_zero_value_deposit(index, record.agreement.promisee());
+            record.state = RecordState::Cancelled;
+            record.finalized_at = Some(block_number);
+            Self::schedule_prune(index, &record.agreement, block_number);
+            <Liabilities<T>>::insert(index, record);
+            <Counters<T>>::mutate(|counters| counters.cancelled += 1);
+            Self::record_transition(index, block_number, Transition::Cancelled);
+            Self::deposit_event(RawEvent::LiabilityExpired(index.into(), sender, incentive));
+        }
+
+        /// Post promisee demand: liability parameters with per-unit economics, maximal
+        /// quantity, order deadline and promisee proof of parameters for the whole quantity,
+        /// i.e. proof of `create` when quantity is one. Order deposit and matching reward are
+        /// reserved until it's removed. The worst demand of full market book is evicted.
+        #[weight = T::WeightInfo::demand(technics.using_encoded(|t| t.len() as u32))]
+        fn demand(
+            origin,
+            technics: TechnicalParam<T>,
+            economics: EconomicalParam<T>,
+            quantity: u32,
+            deadline: BlockNumber<T>,
+            proof: ProofParam<T>,
+        ) {
+            let sender = ensure_signed(origin)?;
+            let market_id = Self::market_of(&technics, &economics);
+            let reward = Self::match_escrow(market_id, &T::Economics::scale(&economics, quantity));
+            let order =
+                Self::new_order(sender, technics, economics, quantity, deadline, proof, None)?;
+            Self::post_order(Side::Demand, Order { escrow: reward, ..order })?;
+        }
+
+        /// Post promisee demand with requirement to promisor, only offers of promisors
+        /// satisfying it are matched. Promisee proof of parameters for the whole quantity
+        /// should cover requirement, see `restricted_params_payload`.
+        #[weight = {
+            let t = technics.using_encoded(|t| t.len() as u32);
+            let r = requirement.using_encoded(|r| r.len() as u32);
+            T::WeightInfo::demand(t.saturating_add(r))
+                .saturating_add(T::DbWeight::get().writes(1))
+        }]
+        fn demand_restricted(
+            origin,
+            technics: TechnicalParam<T>,
+            economics: EconomicalParam<T>,
+            quantity: u32,
+            deadline: BlockNumber<T>,
+            requirement: PromisorRequirementOf<T>,
+            proof: ProofParam<T>,
+        ) {
+            let sender = ensure_signed(origin)?;
+            let market_id = Self::market_of(&technics, &economics);
+            let reward = Self::match_escrow(market_id, &T::Economics::scale(&economics, quantity));
+            let order = Self::new_order(
+                sender,
+                technics,
+                economics,
+                quantity,
+                deadline,
+                proof,
+                Some(&requirement),
+            )?;
+            let order_id = Self::post_order(Side::Demand, Order { escrow: reward, ..order })?;
+            <DemandRequirements<T>>::insert(order_id, requirement);
+        }
+
+        /// Remove demand and return its deposit: by creator at any time before matching or
+        /// by anyone after deadline.
+        #[weight = T::WeightInfo::cancel_demand()]
+        fn cancel_demand(origin, order_id: OrderId) {
+            let sender = ensure_signed(origin)?;
+            let order = <Asks<T>>::get(order_id).ok_or(Error::<T>::UnknownOrder)?;
+            Self::ensure_removable(&order, &sender)?;
+            let market_id = Self::market_of(&order.technics, &order.economics);
+            Self::unlist_order(Side::Demand, market_id, order_id);
+            Self::drop_order(Side::Demand, order_id, &order);
+        }
+
+        /// Post promisor offer: liability parameters with per-unit economics, maximal
+        /// quantity, order deadline and promisor proof of parameters for the whole quantity.
+        /// Proof of single unit offer is accepted by `create` as promisor proof.
+        /// The worst offer of full market book is evicted.
+        #[weight = T::WeightInfo::offer(technics.using_encoded(|t| t.len() as u32))]
+        fn offer(
+            origin,
+            technics: TechnicalParam<T>,
+            economics: EconomicalParam<T>,
+            quantity: u32,
+            deadline: BlockNumber<T>,
+            proof: ProofParam<T>,
+        ) {
+            let sender = ensure_signed(origin)?;
+            let order =
+                Self::new_order(sender, technics, economics, quantity, deadline, proof, None)?;
+            Self::post_order(Side::Offer, order)?;
+        }
+
+        /// Remove offer and return its deposit: by creator at any time before matching or
+        /// by anyone after deadline.
+        #[weight = T::WeightInfo::cancel_offer()]
+        fn cancel_offer(origin, order_id: OrderId) {
+            let sender = ensure_signed(origin)?;
+            let order = <Bids<T>>::get(order_id).ok_or(Error::<T>::UnknownOrder)?;
+            Self::ensure_removable(&order, &sender)?;
+            let market_id = Self::market_of(&order.technics, &order.economics);
+            Self::unlist_order(Side::Offer, market_id, order_id);
+            Self::drop_order(Side::Offer, order_id, &order);
+        }
+
+        /// Create liability of demand and offer with the same parameters using their proofs,
+        /// callable by anyone, e.g. lighthouse. Liability takes the lowest of orders quantities
+        /// with proportional economics, order residuals are kept open. Consumed orders deposits
+        /// are returned, caller receives part of matching reward from promisee escrow when it's
+        /// scheduled lighthouse of market. In markets without lighthouses reward is paid
+        /// only to caller of `reveal_match`, so copied transaction earns nothing.
+        #[weight = T::WeightInfo::match_orders()]
+        fn match_orders(origin, demand_id: OrderId, offer_id: OrderId) {
+            let sender = ensure_signed(origin)?;
+            Self::match_pair(sender, demand_id, offer_id, false)
+        }
+
+        /// Commit to match of demand and offer by hash of their ids with sender account and
+        /// salt, see `match_commitment`. It's revealed by `reveal_match` in the next
+        /// `MatchCommitWindow` blocks, so the pair isn't known before the reveal.
+        #[weight = T::WeightInfo::commit_match()]
+        fn commit_match(origin, commitment: H256) {
+            let sender = ensure_signed(origin)?;
+            ensure!(
+                !<MatchCommitments<T>>::contains_key(&sender, commitment),
+                Error::<T>::MatchCommitted
+            );
+            let count = <MatchCommitCount<T>>::get(&sender);
+            ensure!(
+                count < T::MaxMatchCommits::get(),
+                Error::<T>::TooManyMatchCommits
+            );
+
+            let now = <frame_system::Module<T>>::block_number();
+            <MatchCommitments<T>>::insert(&sender, commitment, now);
+            <MatchCommitCount<T>>::insert(&sender, count + 1);
+            let expires_at = now
+                .saturating_add(T::MatchCommitWindow::get())
+                .saturating_add(One::one());
+            <MatchCommitsExpiringAt<T>>::mutate(expires_at, |commits| {
+                commits.push((sender.clone(), commitment))
+            });
+            Self::deposit_event(RawEvent::MatchCommitted(sender, commitment));
+        }
+
+        /// Match demand and offer committed by sender in one of previous `MatchCommitWindow`
+        /// blocks as `match_orders` does, sender is rewarded in markets without lighthouses.
+        #[weight = T::WeightInfo::reveal_match()]
+        fn reveal_match(origin, demand_id: OrderId, offer_id: OrderId, salt: H256) {
+            let sender = ensure_signed(origin)?;
+            let commitment = match_commitment(&demand_id, &offer_id, &sender, &salt);
+            let committed_at = <MatchCommitments<T>>::get(&sender, commitment)
+                .ok_or(Error::<T>::NoMatchCommitment)?;
+            let now = <frame_system::Module<T>>::block_number();
+            ensure!(committed_at < now, Error::<T>::MatchCommitTooRecent);
+            ensure!(
+                now <= committed_at.saturating_add(T::MatchCommitWindow::get()),
+                Error::<T>::MatchCommitExpired
+            );
+
+            Self::match_pair(sender.clone(), demand_id, offer_id, true)?;
+            Self::remove_match_commit(&sender, commitment);
+        }
+
+        /// Register sender as lighthouse of market, stake is reserved until unregistered.
+        #[weight = T::WeightInfo::register_lighthouse()]
+        fn register_lighthouse(origin, market_id: MarketId, stake: BalanceOf<T>) {
+            let sender = ensure_signed(origin)?;
+            ensure!(stake >= T::LighthouseMinStake::get(), Error::<T>::StakeTooLow);
+            ensure!(
+                !<LighthouseStake<T>>::contains_key(market_id, &sender),
+                Error::<T>::AlreadyRegistered
+            );
+            let mut lighthouses = <Lighthouses<T>>::get(market_id);
+            ensure!(
+                lighthouses.len() < T::MaxLighthouses::get() as usize,
+                Error::<T>::TooManyLighthouses
+            );
+
+            NamedReserve::<T>::reserve_named(&RESERVE_ID, &sender, stake)?;
+            lighthouses.push(sender.clone());
+            <Lighthouses<T>>::insert(market_id, lighthouses);
+            <LighthouseStake<T>>::insert(market_id, &sender, stake);
+            Self::deposit_event(RawEvent::LighthouseRegistered(market_id, sender, stake));
+        }
+
+        /// Unregister sender from lighthouses of market and return its stake.
+        #[weight = T::WeightInfo::unregister_lighthouse()]
+        fn unregister_lighthouse(origin, market_id: MarketId) {
+            let sender = ensure_signed(origin)?;
+            let stake = <LighthouseStake<T>>::take(market_id, &sender)
+                .ok_or(Error::<T>::NotLighthouse)?;
+            <Lighthouses<T>>::mutate(market_id, |lighthouses| {
+                lighthouses.retain(|lighthouse| lighthouse != &sender)
+            });
+            NamedReserve::<T>::unreserve_named(&RESERVE_ID, &sender, stake);
+            Self::deposit_event(RawEvent::LighthouseUnregistered(market_id, sender));
+        }
+
+        /// Join assignment pool of market with standing offer of sender, stake is reserved
+        /// until sender leaves and weights selection of sender for assigned demands.
+        #[weight = T::WeightInfo::join_pool()]
+        fn join_pool(origin, market_id: MarketId, offer_id: OrderId, stake: BalanceOf<T>) {
+            let sender = ensure_signed(origin)?;
+            ensure!(stake >= T::PoolMinStake::get(), Error::<T>::StakeTooLow);
+            let offer = Self::open_order(<Bids<T>>::get(offer_id), offer_id)?;
+            ensure!(offer.sender == sender, Error::<T>::NotOrderOwner);
+            ensure!(
+                Self::market_of(&offer.technics, &offer.economics) == market_id,
+                Error::<T>::OrderMismatch
+            );
+            let mut pool = <Pools<T>>::get(market_id);
+            ensure!(
+                pool.iter().all(|(member, ..)| member != &sender),
+                Error::<T>::AlreadyPooled
+            );
+            ensure!(
+                pool.len() < T::MaxPoolSize::get() as usize,
+                Error::<T>::PoolFull
+            );
+
+            NamedReserve::<T>::reserve_named(&RESERVE_ID, &sender, stake)?;
+            let now = <frame_system::Module<T>>::block_number();
+            pool.push((sender.clone(), offer_id, stake, now));
+            <Pools<T>>::insert(market_id, pool);
+            Self::deposit_event(RawEvent::PoolJoined(sender, market_id, offer_id, stake));
+        }
+
+        /// Leave assignment pool of market and return stake of sender.
+        #[weight = T::WeightInfo::leave_pool()]
+        fn leave_pool(origin, market_id: MarketId) {
+            let sender = ensure_signed(origin)?;
+            let mut pool = <Pools<T>>::get(market_id);
+            let position = pool
+                .iter()
+                .position(|(member, ..)| member == &sender)
+                .ok_or(Error::<T>::NotPooled)?;
+            let (_, _, stake, _) = pool.remove(position);
+            <Pools<T>>::insert(market_id, pool);
+            NamedReserve::<T>::unreserve_named(&RESERVE_ID, &sender, stake);
+            Self::deposit_event(RawEvent::PoolLeft(sender, market_id));
+        }
+
+        /// Request demand of sender to be assigned to promisor of market assignment pool
+        /// instead of first-come matching. Promisor is selected by stake-weighted randomness
+        /// after `AssignmentDelay` blocks among promisors joined before the request with
+        /// standing offer of demand parameters, selected offer is matched to demand as
+        /// `match_orders` does. Demand is left in market book when no one qualifies.
+        #[weight = T::WeightInfo::request_assignment()]
+        fn request_assignment(origin, demand_id: OrderId) {
+            let sender = ensure_signed(origin)?;
+            let demand = Self::open_order(<Asks<T>>::get(demand_id), demand_id)?;
+            ensure!(demand.sender == sender, Error::<T>::NotOrderOwner);
+            ensure!(
+                !<PendingAssignment<T>>::contains_key(demand_id),
+                Error::<T>::AssignmentPending
+            );
+            let market_id = Self::market_of(&demand.technics, &demand.economics);
+            ensure!(
+                !<Pools<T>>::get(market_id).is_empty(),
+                Error::<T>::EmptyPool
+            );
+
+            let now = <frame_system::Module<T>>::block_number();
+            let selected_at = now.saturating_add(T::AssignmentDelay::get().max(One::one()));
+            <PendingAssignment<T>>::insert(demand_id, now);
+            <AssignmentsAt<T>>::mutate(selected_at, |demands| demands.push(demand_id));
+            Self::deposit_event(RawEvent::AssignmentRequested(demand_id, selected_at));
+        }
+
+        /// Set default auxiliary parameters of sender liabilities, empty preferences are
+        /// removed. Payout account is fixed by liabilities at creation, so open liabilities
+        /// aren't affected by changes.
+        #[weight = T::WeightInfo::set_preferences()]
+        fn set_preferences(origin, prefs: AccountPrefsOf<T>) {
+            let sender = ensure_signed(origin)?;
+            if prefs.is_empty() {
+                <Preferences<T>>::remove(&sender);
+                Self::deposit_event(RawEvent::PreferencesCleared(sender));
+            } else {
+                <Preferences<T>>::insert(&sender, prefs);
+                Self::deposit_event(RawEvent::PreferencesSet(sender));
+            }
+        }
+
+        /// Register sender as report witness, stake is reserved until unregistered and
+        /// slashed when notarized report is overturned.
+        #[weight = T::WeightInfo::register_witness()]
+        fn register_witness(origin, stake: BalanceOf<T>) {
+            let sender = ensure_signed(origin)?;
+            ensure!(stake >= T::WitnessMinStake::get(), Error::<T>::StakeTooLow);
+            ensure!(
+                !<WitnessStake<T>>::contains_key(&sender),
+                Error::<T>::AlreadyWitness
+            );
+            let mut witnesses = <Witnesses<T>>::get();
+            ensure!(
+                witnesses.len() < T::MaxWitnesses::get() as usize,
+                Error::<T>::TooManyWitnesses
+            );
+
+            NamedReserve::<T>::reserve_named(&RESERVE_ID, &sender, stake)?;
+            witnesses.push(sender.clone());
+            <Witnesses<T>>::put(witnesses);
+            <WitnessStake<T>>::insert(&sender, stake);
+            Self::deposit_event(RawEvent::WitnessRegistered(sender, stake));
+        }
+
+        /// Unregister sender from report witnesses and return the rest of its stake, it's
+        /// kept until notarized reports are settled.
+        #[weight = T::WeightInfo::unregister_witness()]
+        fn unregister_witness(origin) {
+            let sender = ensure_signed(origin)?;
+            ensure!(
+                <WitnessStake<T>>::contains_key(&sender),
+                Error::<T>::NotWitness
+            );
+            ensure!(
+                <WitnessPending<T>>::get(&sender) == 0,
+                Error::<T>::WitnessBusy
+            );
+            let stake = <WitnessStake<T>>::take(&sender).unwrap_or_else(Zero::zero);
+            <Witnesses<T>>::mutate(|witnesses| witnesses.retain(|witness| witness != &sender));
+            NamedReserve::<T>::unreserve_named(&RESERVE_ID, &sender, stake);
+            Self::deposit_event(RawEvent::WitnessUnregistered(sender));
+        }
+
+        /// Require report of open liability to be notarized by quorum of witnesses, called
+        /// by promisee with promisor consent, see `witnessing_payload`. Report of witnessed
+        /// liability is settled after challenge window of its market, in promisor favor
+        /// only when it's notarized by quorum.
+        #[weight = {
+            let p = witnessing_payload(index, *quorum).len() as u32;
+            Module::<T>::proofs_weight(T::WeightInfo::require_witnesses(), &[promisor_proof], p)
+        }]
+        fn require_witnesses(
+            origin,
+            #[compact] index: LiabilityIndex<T>,
+            quorum: u32,
+            promisor_proof: ProofParam<T>,
+        ) {
+            let sender = ensure_signed(origin)?;
+            let record = Self::record_of(index)?;
+            ensure!(
+                record.state == RecordState::Open,
+                Error::<T>::AlreadyFinalized
+            );
+            let liability = &record.agreement;
+            ensure!(liability.promisee() == &sender, Error::<T>::NotPromisee);
+            ensure!(
+                quorum > 0 && quorum <= T::MaxWitnesses::get(),
+                Error::<T>::InvalidQuorum
+            );
+            ensure!(
+                !<WitnessQuorum<T>>::contains_key(index),
+                Error::<T>::AlreadyWitnessed
+            );
+            ensure!(
+                liability.check_witnessing(&index, quorum, &promisor_proof),
+                Error::<T>::BadPromisorProof
+            );
+
+            <WitnessQuorum<T>>::insert(index, quorum);
+            Self::deposit_event(RawEvent::WitnessingRequired(index.into(), quorum));
+        }
+
+        /// Notarize that report of witnessed liability was retrievable and matched its hash,
+        /// see `notarize_payload`. Witness notarizes report during its challenge window,
+        /// parties couldn't witness own liability.
+        #[weight = {
+            let p = notarize_payload(index, &H256::default()).len() as u32;
+            Module::<T>::proofs_weight(T::WeightInfo::notarize(), &[proof], p)
+        }]
+        fn notarize(origin, #[compact] index: LiabilityIndex<T>, proof: ProofParam<T>) {
+            let sender = ensure_signed(origin)?;
+            ensure!(
+                <WitnessStake<T>>::contains_key(&sender),
+                Error::<T>::NotWitness
+            );
+            let deadline = <PendingSettlement<T>>::get(index)
+                .ok_or(Error::<T>::NotarizationClosed)?;
+            let now = <frame_system::Module<T>>::block_number();
+            ensure!(now <= deadline, Error::<T>::NotarizationClosed);
+            let record = Self::record_of(index)?;
+            let liability = &record.agreement;
+            ensure!(
+                liability.promisee() != &sender && liability.promisor() != &sender,
+                Error::<T>::PartyWitness
+            );
+            let mut notaries = <Notarizations<T>>::get(index);
+            ensure!(!notaries.contains(&sender), Error::<T>::AlreadyNotarized);
+            let report_hash = record.report_hash.ok_or(Error::<T>::NotarizationClosed)?;
+            ensure!(
+                T::Liability::verify_notarization(&sender, &index, &report_hash, &proof),
+                Error::<T>::BadNotaryProof
+            );
+
+            notaries.push(sender.clone());
+            <Notarizations<T>>::insert(index, notaries);
+            <WitnessPending<T>>::mutate(&sender, |pending| *pending += 1);
+            Self::deposit_event(RawEvent::Notarized(index.into(), sender));
+        }
+
+        /// Overturn witnessed report during its challenge window, e.g. by dispute resolution:
+        /// liability is settled as failed, witnesses notarized the report are slashed
+        /// by `WitnessSlash` to promisee.
+        #[weight = T::WeightInfo::overturn_report(T::MaxWitnesses::get())]
+        fn overturn_report(origin, #[compact] index: LiabilityIndex<T>) {
+            T::AdminOrigin::ensure_origin(origin)?;
+            Self::resolve_settlement(index, false)?;
+        }
+
+        /// Uphold witnessed report held from settlement, e.g. dispute escalated by jury:
+        /// liability is settled in promisor favor regardless of notarizations.
+        #[weight = T::WeightInfo::overturn_report(T::MaxWitnesses::get())]
+        fn uphold_report(origin, #[compact] index: LiabilityIndex<T>) {
+            T::AdminOrigin::ensure_origin(origin)?;
+            ensure!(
+                <HeldSettlements<T>>::contains_key(index),
+                Error::<T>::SettlementNotHeld
+            );
+            Self::resolve_settlement(index, true)?;
+        }
+
+        /// Set fee of market charged on matching from settled value of liability, already
+        /// posted demands pay no more than their escrow. Market without configuration
+        /// takes default one with given fee.
+        #[weight = T::WeightInfo::set_market_fee()]
+        fn set_market_fee(origin, market_id: MarketId, fee: Perbill) {
+            T::AdminOrigin::ensure_origin(origin)?;
+            let market = MarketConfig {
+                fee,
+                ..Self::market_config(market_id)
+            };
+            if market == Self::default_market_config() {
+                <Markets<T>>::remove(market_id);
+            } else {
+                <Markets<T>>::insert(market_id, market);
+            }
+            Self::deposit_event(RawEvent::MarketFeeSet(market_id, fee));
+        }
+
+        /// Create configuration of market, e.g. job category with its own fee and challenge
+        /// window. Liabilities created before keep default configuration.
+        #[weight = T::WeightInfo::create_market()]
+        fn create_market(origin, market_id: MarketId, config: MarketConfigOf<T>) {
+            T::AdminOrigin::ensure_origin(origin)?;
+            ensure!(!<Markets<T>>::contains_key(market_id), Error::<T>::MarketExists);
+            <Markets<T>>::insert(market_id, config);
+            Self::deposit_event(RawEvent::MarketCreated(market_id));
+        }
+
+        /// Update configuration of market, open liabilities keep configuration taken
+        /// at their creation, open orders pay no more than their escrow.
+        #[weight = T::WeightInfo::update_market()]
+        fn update_market(origin, market_id: MarketId, config: MarketConfigOf<T>) {
+            T::AdminOrigin::ensure_origin(origin)?;
+            ensure!(<Markets<T>>::contains_key(market_id), Error::<T>::UnknownMarket);
+            <Markets<T>>::insert(market_id, config);
+            Self::deposit_event(RawEvent::MarketUpdated(market_id));
+        }
+
+        /// Reserve funds slashed by breach claims of sender signed orders, bond unlocking
+        /// is cancelled.
+        #[weight = T::WeightInfo::bond_orders()]
+        fn bond_orders(origin, amount: BalanceOf<T>) {
+            let sender = ensure_signed(origin)?;
+            NamedReserve::<T>::reserve_named(&RESERVE_ID, &sender, amount)?;
+            <OrderBond<T>>::mutate(&sender, |bond| *bond = bond.saturating_add(amount));
+            <BondUnlockAt<T>>::remove(&sender);
+            Self::deposit_event(RawEvent::OrderBonded(sender, amount));
+        }
+
+        /// Return signed orders bond in two steps: the first call locks it for
+        /// `BreachClaimPeriod` blocks, when it's still slashable, the call after lock returns it.
+        #[weight = T::WeightInfo::unbond_orders()]
+        fn unbond_orders(origin) {
+            let sender = ensure_signed(origin)?;
+            ensure!(<OrderBond<T>>::contains_key(&sender), Error::<T>::NotBonded);
+
+            let now = <frame_system::Module<T>>::block_number();
+            match <BondUnlockAt<T>>::get(&sender) {
+                Some(unlock_at) => {
+                    ensure!(now >= unlock_at, Error::<T>::BondLocked);
+                    <BondUnlockAt<T>>::remove(&sender);
+                    let bond = <OrderBond<T>>::take(&sender);
+                    NamedReserve::<T>::unreserve_named(&RESERVE_ID, &sender, bond);
+                    Self::deposit_event(RawEvent::OrderUnbonded(sender, bond));
+                }
+                None => {
+                    let unlock_at = now.saturating_add(T::BreachClaimPeriod::get());
+                    <BondUnlockAt<T>>::insert(&sender, unlock_at);
+                    Self::deposit_event(RawEvent::BondUnlocking(sender, unlock_at));
+                }
+            }
+        }
+
+        /// Claim breach of agreement proven by sender `order` and compatible `counter_order`
+        /// of other party: both expired and no liability of agreement was created before.
+        /// `OrderBreachSlash` of counter party bond is paid to sender. Each order is claimed
+        /// once during `BreachClaimPeriod` after the earliest expiry.
+        #[weight = T::WeightInfo::claim_order_breach()]
+        fn claim_order_breach(origin, order: SignedOrderOf<T>, counter_order: SignedOrderOf<T>) {
+            let sender = ensure_signed(origin)?;
+            ensure!(order.sender == sender, Error::<T>::NotOrderOwner);
+            ensure!(
+                signed::verify_order(&order) && signed::verify_order(&counter_order),
+                Error::<T>::BadOrderProof
+            );
+            ensure!(
+                order.side != counter_order.side
+                    && order.sender != counter_order.sender
+                    && order.technics == counter_order.technics
+                    && order.economics == counter_order.economics,
+                Error::<T>::OrderMismatch
+            );
+            ensure!(
+                !<ClaimedOrders<T>>::contains_key(&order.sender, order.nonce)
+                    && !<ClaimedOrders<T>>::contains_key(&counter_order.sender, counter_order.nonce),
+                Error::<T>::OrderClaimed
+            );
+
+            let now = <frame_system::Module<T>>::block_number();
+            let expiry = order.expiry.min(counter_order.expiry);
+            ensure!(now > expiry, Error::<T>::OrderNotExpired);
+            ensure!(
+                now <= expiry.saturating_add(T::BreachClaimPeriod::get()),
+                Error::<T>::ClaimPeriodPassed
+            );
+
+            let (promisee, promisor) = match order.side {
+                Side::Demand => (order.sender.clone(), counter_order.sender.clone()),
+                Side::Offer => (counter_order.sender.clone(), order.sender.clone()),
+            };
+            let liability = T::Liability::new(order.technics, order.economics, promisee, promisor);
+            let created_at = <AgreementCreatedAt<T>>::get(Self::agreement_hash(&liability));
+            ensure!(
+                created_at.map_or(true, |created_at| created_at > expiry),
+                Error::<T>::NoBreach
+            );
+
+            <ClaimedOrders<T>>::insert(&order.sender, order.nonce, ());
+            <ClaimedOrders<T>>::insert(&counter_order.sender, counter_order.nonce, ());
+
+            let reneging = counter_order.sender;
+            let bond = <OrderBond<T>>::get(&reneging);
+            let slash = T::OrderBreachSlash::get().min(bond);
+            let not_moved = NamedReserve::<T>::repatriate_reserved_named(
+                &RESERVE_ID,
+                &reneging,
+                &sender,
+                slash,
+                BalanceStatus::Free,
+            )
+            .unwrap_or(slash);
+            let moved = slash.saturating_sub(not_moved);
+            if bond == moved {
+                <OrderBond<T>>::remove(&reneging);
+                <BondUnlockAt<T>>::remove(&reneging);
+            } else {
+                <OrderBond<T>>::insert(&reneging, bond - moved);
+            }
+            Self::deposit_event(RawEvent::OrderBreachClaimed(sender, reneging, moved));
+        }
+
+        /// Revoke promisor account approval, already open liabilities aren't affected.
+        #[weight = T::WeightInfo::remove_promisor()]
+        fn remove_promisor(origin, who: AccountId<T>) {
+            T::AdminOrigin::ensure_origin(origin)?;
+            ensure!(<ApprovedPromisors<T>>::contains_key(&who), Error::<T>::UnapprovedPromisor);
+            <ApprovedPromisors<T>>::remove(&who);
+            Self::deposit_event(RawEvent::PromisorRemoved(who));
+        }
+
+        /// Exclude account from new liabilities and orders network-wide. Open liabilities
+        /// and orders of account still could be finalized or cancelled.
+        #[weight = T::WeightInfo::blacklist()]
+        fn blacklist(origin, who: AccountId<T>) {
+            T::AdminOrigin::ensure_origin(origin)?;
+            <Blacklist<T>>::insert(&who, true);
+            Self::deposit_event(RawEvent::AccountBlacklisted(who));
+        }
+
+        /// Remove account from blacklist.
+        #[weight = T::WeightInfo::unblacklist()]
+        fn unblacklist(origin, who: AccountId<T>) {
+            T::AdminOrigin::ensure_origin(origin)?;
+            ensure!(<Blacklist<T>>::get(&who), Error::<T>::NotBlacklisted);
+            <Blacklist<T>>::remove(&who);
+            Self::deposit_event(RawEvent::AccountUnblacklisted(who));
+        }
+
+        /// Approve firmware for robot model, i.e. market.
+        #[weight = T::WeightInfo::approve_firmware()]
+        fn approve_firmware(origin, model: MarketId, firmware: T::Hash) {
+            T::AdminOrigin::ensure_origin(origin)?;
+            <ApprovedFirmware<T>>::insert(model, firmware, true);
+            Self::deposit_event(RawEvent::FirmwareApproved(model, firmware));
+        }
+
+        /// Revoke firmware approval for robot model, reports already accepted aren't affected.
+        #[weight = T::WeightInfo::revoke_firmware()]
+        fn revoke_firmware(origin, model: MarketId, firmware: T::Hash) {
+            T::AdminOrigin::ensure_origin(origin)?;
+            ensure!(
+                <ApprovedFirmware<T>>::get(model, firmware),
+                Error::<T>::FirmwareNotApproved
+            );
+            <ApprovedFirmware<T>>::remove(model, firmware);
+            Self::deposit_event(RawEvent::FirmwareRevoked(model, firmware));
+        }
+
+        /// Publish technical report by hash of preimage noted before, so report bytes are
+        /// sent once with `note_report_preimage`.
+        #[weight = {
+            let r = T::MaxReportLength::get();
+            Module::<T>::proofs_weight(T::WeightInfo::finalize(r), &[proof], r)
+                .saturating_add(T::DbWeight::get().reads(1))
+        }]
+        fn finalize_noted(
+            origin,
+            #[compact] index: LiabilityIndex<T>,
+            report_hash: H256,
+            proof: ProofParam<T>,
+        ) -> DispatchResultWithPostInfo {
+            ensure_none(origin)?;
+            let preimage = <ReportPreimage<T>>::get(report_hash)
+                .ok_or(Error::<T>::UnknownPreimage.with_weight(T::DbWeight::get().reads(1)))?;
+            Self::accept_report(index, preimage.report, None, proof)
+        }
+
+        /// Note technical report preimage, `ReportPreimageDeposit` is reserved from sender
+        /// until it's pruned. Pruned report of liability could be noted again to reveal it.
+        #[weight = T::WeightInfo::note_report_preimage(report.using_encoded(|r| r.len() as u32))]
+        fn note_report_preimage(origin, report: TechnicalReport<T>) {
+            let sender = ensure_signed(origin)?;
+            ensure!(
+                report.encoded_size() <= T::MaxReportLength::get() as usize,
+                Error::<T>::ReportTooLong
+            );
+            let hash = report_hash(&report);
+            ensure!(!<ReportPreimage<T>>::contains_key(hash), Error::<T>::PreimageExists);
+
+            let deposit = T::ReportPreimageDeposit::get();
+            NamedReserve::<T>::reserve_named(&RESERVE_ID, &sender, deposit)?;
+            let now = <frame_system::Module<T>>::block_number();
+            let expires_at = now.saturating_add(T::ReportChallengeWindow::get());
+            let preimage = Preimage {
+                report,
+                depositor: Some((sender.clone(), deposit)),
+                expires_at,
+            };
+            <ReportPreimage<T>>::insert(hash, preimage);
+            <PreimagesExpiringAt<T>>::mutate(expires_at, |hashes| hashes.push(hash));
+            Self::deposit_event(RawEvent::ReportPreimageNoted(hash, sender));
+        }
+
+        /// Remove report preimage after its retention, deposit is returned to depositor.
+        /// Any account could prune expired preimage, expired preimages are also pruned
+        /// automatically when block has spare weight.
+        #[weight = T::WeightInfo::prune_report_preimage()]
+        fn prune_report_preimage(origin, report_hash: H256) {
+            ensure_signed(origin)?;
+            let preimage = <ReportPreimage<T>>::get(report_hash)
+                .ok_or(Error::<T>::UnknownPreimage)?;
+            let now = <frame_system::Module<T>>::block_number();
+            ensure!(now > preimage.expires_at, Error::<T>::PreimageNotExpired);
+
+            Self::remove_preimage(report_hash, preimage);
+        }
+
+        /// Create liability in privacy mode: parties are kept as their commitments, see
+        /// `party_commitment`, so state doesn't show who hired whom. Both parties sign
+        /// `blinded_params_payload` naming payout account, successful liability pays it
+        /// instead of promisor. Blinded liabilities are indexed apart from others.
+        #[weight = {
+            let t = technics.using_encoded(|t| t.len() as u32);
+            let proofs = [promisee_proof, promisor_proof];
+            Module::<T>::proofs_weight(T::WeightInfo::create(t), &proofs, t)
+        }]
+        fn create_blinded(
+            origin,
+            technics: TechnicalParam<T>,
+            economics: EconomicalParam<T>,
+            promisee: AccountId<T>,
+            promisee_salt: H256,
+            promisor: AccountId<T>,
+            promisor_salt: H256,
+            payout: AccountId<T>,
+            promisee_proof: ProofParam<T>,
+            promisor_proof: ProofParam<T>,
+        ) {
+            ensure_none(origin)?;
+            let (agreement, blinded) = Self::check_blinded_create(
+                technics,
+                economics,
+                (&promisee, &promisee_salt),
+                (&promisor, &promisor_salt),
+                payout,
+                &promisee_proof,
+                &promisor_proof,
+            )?;
+
+            // Escrow of promisee is settled with payout account
+            {
+                sp_tracing::enter_span!("liability::economics");
+                Self::blinded_agreement(&blinded, promisee).on_start()?;
+            }
+
+            let index = <BlindedCount<T>>::get();
+            <BlindedCount<T>>::put(index + One::one());
+            <BlindedAgreements<T>>::insert(agreement, ());
+            let parties = (blinded.promisee, blinded.promisor);
+            <BlindedLiabilities<T>>::insert(index, blinded);
+            Self::note_unsigned_call(None);
+            Self::deposit_event(RawEvent::BlindedLiabilityCreated(index.into(), parties.0, parties.1));
+        }
+
+        /// Publish technical report of blinded liability, both parties accounts are
+        /// disclosed in call with their salts to open the commitments. Report proof is
+        /// made for `blinded_report_payload`. Like `finalize`, report is settled with
+        /// outcome of `report_outcome`, i.e. in promisor favour.
+        #[weight = {
+            let r = report.using_encoded(|r| r.len() as u32);
+            Module::<T>::proofs_weight(T::WeightInfo::finalize(r), &[proof], r)
+        }]
+        fn finalize_blinded(
+            origin,
+            #[compact] index: LiabilityIndex<T>,
+            report: TechnicalReport<T>,
+            promisee: AccountId<T>,
+            promisee_salt: H256,
+            promisor: AccountId<T>,
+            promisor_salt: H256,
+            proof: ProofParam<T>,
+        ) {
+            ensure_none(origin)?;
+            let mut blinded = Self::check_blinded_report(
+                index,
+                &report,
+                (&promisee, &promisee_salt),
+                (&promisor, &promisor_salt),
+                &proof,
+            )?;
+
+            {
+                sp_tracing::enter_span!("liability::economics");
+                Self::blinded_agreement(&blinded, promisee).on_finish(Self::report_outcome())?;
+            }
+
+            let hash = report_hash(&report);
+            blinded.report = Some(hash);
+            <BlindedLiabilities<T>>::insert(index, blinded);
+            Self::note_unsigned_call(None);
+            Self::deposit_event(RawEvent::BlindedLiabilityFinalized(index.into(), hash));
+        }
+
+        /// Rotate the next `MAX_ROTATION_BATCH` liabilities of rotated promisor key by any
+        /// account, see `registry::rotate_promisor_key`.
+        #[weight = Module::<T>::rotation_weight()]
+        fn continue_rotation(origin, old: AccountId<T>) {
+            ensure_signed(origin)?;
+            Self::rotate_batch(&old)?;
+        }
+
+        /// Anchor Ethereum state root with factory of legacy liabilities, e.g. state of
+        /// finalized Ethereum block approved by governance.
+        #[weight = T::DbWeight::get().writes(1).saturating_add(10_000_000)]
+        fn anchor_legacy_state(origin, factory: H160, state_root: H256) {
+            T::AdminOrigin::ensure_origin(origin)?;
+            <LegacyAnchor>::put((factory, state_root));
+            Self::deposit_event(RawEvent::LegacyStateAnchored(factory, state_root));
+        }
+
+        /// Import finalized liability of legacy Ethereum contracts by bridge. Contract
+        /// storage is proven by trie nodes of anchored Ethereum state, see
+        /// `robonomics_liability_core::eth`. Imported liability is read-only.
+        #[weight = {
+            let p = inclusion_proof.using_encoded(|p| p.len() as u32);
+            Module::<T>::import_legacy_weight(p)
+        }]
+        fn import_legacy(origin, eth_liability: LegacyRecord, inclusion_proof: Vec<Vec<u8>>) {
+            T::BridgeOrigin::ensure_origin(origin)?;
+            Self::import_legacy_record(eth_liability, inclusion_proof)?;
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Check unsigned creation of blinded liability with its parties proofs, returns hash of
+    /// agreement and blinded liability to store.
+    fn check_blinded_create(
+        technics: TechnicalParam<T>,
+        economics: EconomicalParam<T>,
+        (promisee, promisee_salt): (&AccountId<T>, &H256),
+        (promisor, promisor_salt): (&AccountId<T>, &H256),
+        payout: AccountId<T>,
+        promisee_proof: &ProofParam<T>,
+        promisor_proof: &ProofParam<T>,
+    ) -> Result<(H256, BlindedLiabilityOf<T>), Error<T>> {
+        Self::check_rate_limits(None)?;
+        Self::ensure_technics_length(&technics)?;
+        Self::ensure_not_blacklisted(promisee)?;
+        Self::ensure_not_blacklisted(promisor)?;
+        Self::ensure_approved(promisor)?;
+        Self::ensure_capable(&technics, promisor)?;
+        Self::ensure_identity(&economics, promisee)?;
+
+        // Agreement is never started twice, salts are fresh for each liability
+        let promisee_commitment = party_commitment(promisee, promisee_salt);
+        let promisor_commitment = party_commitment(promisor, promisor_salt);
+        let agreement: H256 = blinded_params_payload(
+            &technics,
+            &economics,
+            &promisee_commitment,
+            &promisor_commitment,
+            &payout,
+        )
+        .using_encoded(sp_io::hashing::blake2_256)
+        .into();
+        ensure!(
+            !<BlindedAgreements<T>>::contains_key(agreement),
+            Error::<T>::DuplicateAgreement
+        );
+
+        sp_tracing::enter_span!("liability::verify_proofs");
+        let parties = (&promisee_commitment, &promisor_commitment);
+        ensure!(
+            T::Liability::verify_blinded_params(
+                &technics,
+                &economics,
+                parties,
+                &payout,
+                promisee_proof,
+                promisee,
+            ),
+            Error::<T>::BadPromiseeProof
+        );
+        ensure!(
+            T::Liability::verify_blinded_params(
+                &technics,
+                &economics,
+                parties,
+                &payout,
+                promisor_proof,
+                promisor,
+            ),
+            Error::<T>::BadPromisorProof
+        );
+
+        let blinded = BlindedLiability {
+            technics,
+            economics,
+            promisee: promisee_commitment,
+            promisor: promisor_commitment,
+            payout,
+            created_at: <frame_system::Module<T>>::block_number(),
+            report: None,
+        };
+        Ok((agreement, blinded))
+    }
+
+    /// Check unsigned report of open blinded liability by its disclosed parties, returns
+    /// the liability.
+    fn check_blinded_report(
+        index: LiabilityIndex<T>,
+        report: &TechnicalReport<T>,
+        (promisee, promisee_salt): (&AccountId<T>, &H256),
+        (promisor, promisor_salt): (&AccountId<T>, &H256),
+        proof: &ProofParam<T>,
+    ) -> Result<BlindedLiabilityOf<T>, Error<T>> {
+        Self::check_rate_limits(None)?;
+        ensure!(
+            report.encoded_size() <= T::MaxReportLength::get() as usize,
+            Error::<T>::ReportTooLong
+        );
+        let blinded = <BlindedLiabilities<T>>::get(index).ok_or(Error::<T>::UnknownLiability)?;
+        ensure!(blinded.report.is_none(), Error::<T>::AlreadyFinalized);
+        ensure!(
+            party_commitment(promisee, promisee_salt) == blinded.promisee
+                && party_commitment(promisor, promisor_salt) == blinded.promisor,
+            Error::<T>::PartyCommitmentMismatch
+        );
+
+        sp_tracing::enter_span!("liability::verify_proofs");
+        ensure!(
+            T::Liability::verify_blinded_report(promisor, &index, report, proof),
+            Error::<T>::BadReportProof
+        );
+        Ok(blinded)
+    }
+
+    /// Agreement of blinded liability economics: escrow of disclosed promisee is settled
+    /// with payout account.
+    fn blinded_agreement(blinded: &BlindedLiabilityOf<T>, promisee: AccountId<T>) -> T::Liability {
+        T::Liability::new(
+            blinded.technics.clone(),
+            blinded.economics.clone(),
+            promisee,
+            blinded.payout.clone(),
+        )
+    }
+
+    /// Transaction pool validity of unsigned creation of blinded liability.
+    fn validate_blinded_create(
+        technics: &TechnicalParam<T>,
+        economics: &EconomicalParam<T>,
+        promisee: (&AccountId<T>, &H256),
+        promisor: (&AccountId<T>, &H256),
+        payout: &AccountId<T>,
+        promisee_proof: &ProofParam<T>,
+        promisor_proof: &ProofParam<T>,
+    ) -> TransactionValidity {
+        let (agreement, _) = Self::check_blinded_create(
+            technics.clone(),
+            economics.clone(),
+            promisee,
+            promisor,
+            payout.clone(),
+            promisee_proof,
+            promisor_proof,
+        )
+        .map_err(|e| match e {
+            Error::<T>::TooManyUnsignedCalls => {
+                TransactionValidityError::from(ValidityError::RateLimited)
+            }
+            Error::<T>::Blacklisted
+            | Error::<T>::UnapprovedPromisor
+            | Error::<T>::PromisorKeyRotated
+            | Error::<T>::IdentityRequired
+            | Error::<T>::CapabilitiesMismatch => ValidityError::NotPermitted.into(),
+            Error::<T>::DuplicateAgreement => ValidityError::NonceMismatch.into(),
+            Error::<T>::BadPromiseeProof => ValidityError::BadPromiseeProof.into(),
+            Error::<T>::BadPromisorProof => ValidityError::BadPromisorProof.into(),
+            _ => InvalidTransaction::Call.into(),
+        })?;
+
+        Ok(ValidTransaction {
+            priority: T::CreatePriority::get(),
+            requires: Default::default(),
+            provides: vec![(BLINDED_TAG, agreement).encode()],
+            longevity: 64,
+            propagate: true,
+        })
+    }
+
+    /// Transaction pool validity of unsigned report of blinded liability.
+    fn validate_blinded_report(
+        index: &LiabilityIndex<T>,
+        report: &TechnicalReport<T>,
+        promisee: (&AccountId<T>, &H256),
+        promisor: (&AccountId<T>, &H256),
+        proof: &ProofParam<T>,
+    ) -> TransactionValidity {
+        Self::check_blinded_report(*index, report, promisee, promisor, proof).map_err(
+            |e| match e {
+                Error::<T>::TooManyUnsignedCalls => {
+                    TransactionValidityError::from(ValidityError::RateLimited)
+                }
+                Error::<T>::UnknownLiability if *index >= <BlindedCount<T>>::get() => {
+                    InvalidTransaction::Future.into()
+                }
+                Error::<T>::UnknownLiability => ValidityError::UnknownLiability.into(),
+                Error::<T>::AlreadyFinalized => ValidityError::AlreadySettled.into(),
+                Error::<T>::PartyCommitmentMismatch => ValidityError::CommitmentMismatch.into(),
+                Error::<T>::BadReportProof => ValidityError::BadReportProof.into(),
+                _ => InvalidTransaction::Call.into(),
+            },
+        )?;
+
+        Ok(ValidTransaction {
+            priority: T::FinalizePriority::get(),
+            requires: Default::default(),
+            provides: vec![(BLINDED_TAG, index).encode()],
+            longevity: 64,
+            propagate: true,
+        })
+    }
+
+    /// Check liability report with optional attested firmware, finalize liability
+    /// and notify other modules.
+    ///
+    /// Report rejected before proof verification pays for reads only, rejected proof pays
+    /// `finalize_bad_proof` weight, unused weight of worst case is refunded.
+    fn accept_report(
+        index: LiabilityIndex<T>,
+        report: TechnicalReport<T>,
+        firmware: Option<T::Hash>,
+        proof: ProofParam<T>,
+    ) -> DispatchResultWithPostInfo {
+        let early_weight = T::DbWeight::get().reads(2);
+        Self::check_rate_limits(None).map_err(|e| e.with_weight(early_weight))?;
+        let record = Self::check_open_report(index, &report, firmware.as_ref(), &proof)?;
+        Self::note_unsigned_call(None);
+        Self::store_report(index, record, report, firmware)
+    }
+
+    /// Check that report of open liability could be accepted with given promisor proof,
+    /// returns liability record.
+    fn check_open_report(
+        index: LiabilityIndex<T>,
+        report: &TechnicalReport<T>,
+        firmware: Option<&T::Hash>,
+        proof: &ProofParam<T>,
+    ) -> Result<LiabilityRecordOf<T>, DispatchErrorWithPostInfo> {
+        let report_len = report.encoded_size();
+        let early_weight = T::DbWeight::get().reads(2);
+        ensure!(
+            report_len <= T::MaxReportLength::get() as usize,
+            Error::<T>::ReportTooLong.with_weight(early_weight)
+        );
+
+        // Is liability already finalized?
+        let record = Self::record_of(index).map_err(|e| e.with_weight(early_weight))?;
+        ensure!(
+            record.state == RecordState::Open,
+            "already finalized".with_weight(early_weight)
+        );
+
+        // Promisor isn't obligated until liability is funded
+        ensure!(
+            !<PendingFunding<T>>::contains_key(index),
+            Error::<T>::FundingPending.with_weight(early_weight)
+        );
+        ensure!(
+            !<ReportCommitments<T>>::contains_key(index),
+            Error::<T>::ReportCommitted.with_weight(early_weight)
+        );
+        Self::check_report_delay(&record).map_err(|e| e.with_weight(early_weight))?;
+        {
+            sp_tracing::enter_span!("liability::verify_proofs");
+            Self::check_report_proof(&record.agreement, &index, report, firmware, proof).map_err(
+                |e| {
+                    let r = report_len as u32;
+                    let weight = T::WeightInfo::finalize_bad_proof(r);
+                    e.with_weight(Self::proofs_weight(weight, &[proof], r))
+                },
+            )?;
+        }
+        Ok(record)
+    }
+
+    /// Check that liability is at least `MinReportDelay` blocks old, so parties can't
+    /// create and report it at once.
+    fn check_report_delay(record: &LiabilityRecordOf<T>) -> Result<(), Error<T>> {
+        let block_number = <frame_system::Module<T>>::block_number();
+        ensure!(
+            block_number.saturating_sub(record.created_at) >= T::MinReportDelay::get(),
+            Error::<T>::ReportTooEarly
+        );
+        Ok(())
+    }
+
+    /// Check promisor claim of report arrival against tolerance window around current
+    /// block, returns report arrival: the earlier of claimed and current blocks.
+    fn check_claim(
+        index: LiabilityIndex<T>,
+        record: &LiabilityRecordOf<T>,
+        claimed_at: BlockNumber<T>,
+        proof: &ProofParam<T>,
+    ) -> Result<BlockNumber<T>, Error<T>> {
+        let block_number = <frame_system::Module<T>>::block_number();
+        let tolerance = T::ClaimTolerance::get();
+        ensure!(
+            claimed_at >= record.created_at,
+            Error::<T>::ClaimBeforeCreation
+        );
+        ensure!(
+            block_number.saturating_sub(claimed_at) <= tolerance,
+            Error::<T>::ClaimTooOld
+        );
+        ensure!(
+            claimed_at.saturating_sub(block_number) <= tolerance,
+            Error::<T>::ClaimInFuture
+        );
+        ensure!(
+            record.agreement.check_claim(&index, &claimed_at, proof),
+            Error::<T>::BadClaimProof
+        );
+        Ok(claimed_at.min(block_number))
+    }
+
+    /// Arrival block of liability report that late-penalty economics should use: claimed
+    /// arrival of `finalize_claimed` or block of finalization.
+    pub fn report_arrival(index: LiabilityIndex<T>) -> Option<BlockNumber<T>> {
+        <ClaimedArrival<T>>::get(index)
+            .or_else(|| <Liabilities<T>>::get(index).and_then(|record| record.finalized_at))
+    }
+
+    /// Outcome of reported liability, report is accepted in promisor favour.
+    fn report_outcome() -> bool {
+        // TODO: get parameter from oracle
+        true
+    }
+
+    /// Finalize open liability record with checked report: run economical processing,
+    /// keep report preimage and notify other modules.
+    fn store_report(
+        index: LiabilityIndex<T>,
+        mut record: LiabilityRecordOf<T>,
+        report: TechnicalReport<T>,
+        firmware: Option<T::Hash>,
+    ) -> DispatchResultWithPostInfo {
+        let liability = &record.agreement;
+
+        // Run economical processing, witnessed liability is settled after challenge window
+        let success = Self::report_outcome();
+        let witnessed = <WitnessQuorum<T>>::contains_key(index);
+        if !witnessed {
+            sp_tracing::enter_span!("liability::economics");
+            liability.on_finish(success)?;
+        }
+
+        let block_number = <frame_system::Module<T>>::block_number();
+        let promisor = liability.promisor().clone();
+        let hash = report_hash(&report);
+        {
+            sp_tracing::enter_span!("liability::storage");
+
+            // Index report off-chain to keep it available
+            // on archive nodes when state is pruned
+            report.using_encoded(|encoded| {
+                sp_io::offchain_index::set(&offchain_report_key(&index), encoded)
+            });
+
+            Self::record_transition(index, block_number, Transition::Reported(promisor.clone()));
+
+            // Keep report preimage during retention of liability market, record stores
+            // its hash
+            let (_, market) = Self::liability_market(index, liability);
+            let challenged_until = block_number.saturating_add(market.challenge_window);
+            if witnessed {
+                <PendingSettlement<T>>::insert(index, challenged_until);
+                <SettlementsAt<T>>::mutate(
+                    challenged_until.saturating_add(One::one()),
+                    |indices| indices.push(index),
+                );
+            }
+            let expires_at =
+                challenged_until.max(block_number.saturating_add(market.report_retention));
+            let extended = <ReportPreimage<T>>::mutate(hash, |preimage| match preimage {
+                Some(preimage) if preimage.expires_at >= expires_at => false,
+                Some(preimage) => {
+                    preimage.expires_at = expires_at;
+                    true
+                }
+                None => {
+                    *preimage = Some(Preimage {
+                        report: report.clone(),
+                        depositor: None,
+                        expires_at,
+                    });
+                    true
+                }
+            });
+            if extended {
+                <PreimagesExpiringAt<T>>::mutate(expires_at, |hashes| hashes.push(hash));
+            }
+
+            // Store report hash with finalization block
+            record.report_hash = Some(hash);
+            record.state = RecordState::Reported;
+            record.finalized_at = Some(block_number);
+            Self::schedule_prune(index, &record.agreement, block_number);
+            <Liabilities<T>>::insert(index, &record);
+        }
+        if !witnessed {
+            Self::note_report_outcome(index, &record, success);
+        }
+        Self::refund_relay_bounty(index, record.agreement.promisee());
+        Self::release_zero_value_deposit(index, record.agreement.promisee());
+        Self::release_acknowledgement(index, &promisor);
+        debug::native::trace!(
+            target: LOG_TARGET,
+            "liability finalized: index={:?}, promisor={:?}, success={}, witnessed={}",
+            index,
+            promisor,
+            success,
+            witnessed,
+        );
+
+        // Emit events
+        if let Some(firmware) = firmware {
+            <FirmwareOf<T>>::insert(index, firmware);
+            Self::deposit_event(RawEvent::FirmwareAttested(index.into(), firmware));
+        }
+        Self::deposit_event(RawEvent::NewReport(index.into(), hash, block_number));
+        Ok(().into())
+    }
+
+    /// Count outcome of economical processing of liability report and notify other modules.
+    fn note_report_outcome(index: LiabilityIndex<T>, record: &LiabilityRecordOf<T>, success: bool) {
+        let liability = &record.agreement;
+        let settled = if success {
+            T::SettledValue::convert(liability.economics().clone())
+        } else {
+            Zero::zero()
+        };
+        <Counters<T>>::mutate(|counters| {
+            counters.finalized += 1;
+            counters.settled = counters.settled.saturating_add(settled);
+        });
+        Self::note_stats(liability, success, false);
+        if success {
+            Self::pay_out(index, liability);
+        }
+        Self::note_finalization(index, liability.promisor(), record.report_hash, success);
+
+        let hooks = T::Hooks::isolated_report_accepted(
+            &index,
+            liability.promisee(),
+            liability.promisor(),
+            success,
+            T::MaxHooksWeight::get(),
+        );
+        Self::note_hooks(index, hooks);
+    }
+
+    /// Charge weight of lifecycle handlers of liability and tell their failures.
+    fn note_hooks(index: LiabilityIndex<T>, (weight, failures): (Weight, Vec<HookFailure>)) {
+        <frame_system::Module<T>>::register_extra_weight_unchecked(weight);
+        for (position, error) in failures {
+            debug::native::warn!(
+                target: LOG_TARGET,
+                "liability hook failed: index={:?}, position={}, error={:?}",
+                index,
+                position,
+                error,
+            );
+            Self::deposit_event(RawEvent::HookFailed(index.into(), position, error));
+        }
+    }
+
+    /// Pass settled value of successful liability from promisor to its payout account, it's
+    /// skipped when promisor funds are short.
+    fn pay_out(index: LiabilityIndex<T>, liability: &T::Liability) {
+        if let Some((promisor, payout, value)) = Self::payout_transfer(index, liability) {
+            let transfer =
+                T::Currency::transfer(&promisor, &payout, value, ExistenceRequirement::KeepAlive);
+            if transfer.is_ok() {
+                Self::deposit_event(RawEvent::PayoutRedirected(index.into(), payout, value));
+            }
+        }
+    }
+
+    /// Transfer of settled value of successful liability from promisor to its payout account.
+    fn payout_transfer(
+        index: LiabilityIndex<T>,
+        liability: &T::Liability,
+    ) -> Option<(AccountId<T>, AccountId<T>, BalanceOf<T>)> {
+        let payout = <PayoutOf<T>>::get(index)?;
+        let value: u128 = Self::liability_value(liability.economics()).unique_saturated_into();
+        let value = BalanceOf::<T>::unique_saturated_from(value);
+        Some((liability.promisor().clone(), payout, value))
+    }
+
+    /// Replace promisor key of open liabilities, old key is invalidated at once and its
+    /// first `MAX_ROTATION_BATCH` liabilities are rotated.
+    fn rotate_promisor(old: &AccountId<T>, new: &AccountId<T>) -> Result<(), Error<T>> {
+        ensure!(
+            !<RotatedPromisor<T>>::contains_key(old) && !<RotatedPromisor<T>>::contains_key(new),
+            Error::<T>::PromisorKeyRotated
+        );
+        <RotatedPromisor<T>>::insert(old, new);
+        <PendingRotation<T>>::insert(old, LiabilityIndex::<T>::zero());
+        Self::rotate_batch(old)
+    }
+
+    /// Rotate the next batch of liabilities of rotated promisor key: open liabilities are
+    /// indexed by the new key and its acknowledgement deposits are moved to the new key.
+    fn rotate_batch(old: &AccountId<T>) -> Result<(), Error<T>> {
+        let mut local_index =
+            <PendingRotation<T>>::get(old).ok_or(Error::<T>::NoPendingRotation)?;
+        let new = <RotatedPromisor<T>>::get(old).ok_or(Error::<T>::NoPendingRotation)?;
+        let count = <PromisorCount<T>>::get(old);
+        let block_number = <frame_system::Module<T>>::block_number();
+        for _ in 0..MAX_ROTATION_BATCH {
+            if local_index >= count {
+                break;
+            }
+            let local = local_index;
+            local_index += One::one();
+            let index = match <PromisorLiabilities<T>>::get(old, local) {
+                Some(index) => index,
+                None => continue,
+            };
+            let mut record = match Self::record_of(index) {
+                Ok(record) if record.state == RecordState::Open => record,
+                _ => continue,
+            };
+
+            let liability = &record.agreement;
+            record.agreement = T::Liability::new(
+                liability.technics().clone(),
+                liability.economics().clone(),
+                liability.promisee().clone(),
+                new.clone(),
+            );
+            <Liabilities<T>>::insert(index, record);
+            <PromisorLiabilities<T>>::remove(old, local);
+            let new_local = <PromisorCount<T>>::get(&new);
+            <PromisorCount<T>>::insert(&new, new_local + One::one());
+            <PromisorLiabilities<T>>::insert(&new, new_local, index);
+            <AccountLiabilities<T>>::remove(old, index);
+            <AccountLiabilities<T>>::insert(&new, index, ());
+
+            // Deposit is kept reserved, under the new key
+            if let Some((deadline, deposit)) = <PendingAcknowledgement<T>>::get(index) {
+                let not_moved = NamedReserve::<T>::repatriate_reserved_named(
+                    &RESERVE_ID,
+                    old,
+                    &new,
+                    deposit,
+                    BalanceStatus::Reserved,
+                )
+                .unwrap_or(deposit);
+                // Deposit that can't be moved is released to the old key
+                NamedReserve::<T>::unreserve_named(&RESERVE_ID, old, not_moved);
+                <PendingAcknowledgement<T>>::insert(
+                    index,
+                    (deadline, deposit.saturating_sub(not_moved)),
+                );
+            }
+            Self::record_transition(index, block_number, Transition::Rotated(new.clone()));
+        }
+
+        let finished = local_index >= count;
+        if finished {
+            <PendingRotation<T>>::remove(old);
+        } else {
+            <PendingRotation<T>>::insert(old, local_index);
+        }
+        Self::deposit_event(RawEvent::PromisorRotated(old.clone(), new, finished));
+        Ok(())
+    }
+
+    /// Weight of rotation of `MAX_ROTATION_BATCH` liabilities of promisor key.
+    pub fn rotation_weight() -> Weight {
+        let batch = Weight::from(MAX_ROTATION_BATCH);
+        T::DbWeight::get().reads_writes(4 + 5 * batch, 2 + 9 * batch)
+    }
+
+    /// Append outcome of finalized liability to finalizations range.
+    fn note_finalization(
+        index: LiabilityIndex<T>,
+        promisor: &AccountId<T>,
+        report_hash: Option<H256>,
+        success: bool,
+    ) {
+        let leaf = FinalizationLeaf {
+            index,
+            promisor: promisor.clone(),
+            report_hash: report_hash.unwrap_or_default(),
+            success,
+        };
+        let leaf_index = <FinalizationLeafCount>::get();
+        mmr::append_leaf(
+            leaf_index,
+            leaf.hash(),
+            |position| <FinalizationNodes>::get(position),
+            |position, node| <FinalizationNodes>::insert(position, node),
+        );
+        let leaf_count = leaf_index + 1;
+        let peaks: Vec<_> = mmr::peaks(leaf_count)
+            .into_iter()
+            .map(|(position, _)| <FinalizationNodes>::get(position))
+            .collect();
+        let root = mmr::bag_peaks(&peaks);
+        <FinalizationLeafCount>::put(leaf_count);
+        <FinalizationRoot>::put(root);
+        <FinalizationLeaves<T>>::insert(leaf_index, leaf);
+        <FinalizationLeafIndex<T>>::insert(index, leaf_index);
+
+        // Parents of completed mountains are appended with the leaf
+        let parents = Weight::from((!leaf_index).trailing_zeros());
+        let weight = T::DbWeight::get().reads_writes(
+            2 + parents + Weight::from(leaf_count.count_ones()),
+            5 + parents,
+        );
+        <frame_system::Module<T>>::register_extra_weight_unchecked(weight);
+        Self::deposit_event(RawEvent::FinalizationLeafAdded(
+            index.into(),
+            leaf_index,
+            root,
+        ));
+    }
+
+    /// Run economical processing of witnessed report after its challenge window or
+    /// overturn, the report is kept when economics fails.
+    fn settle_report(index: LiabilityIndex<T>, record: LiabilityRecordOf<T>, success: bool) {
+        let liability = &record.agreement;
+        if let Err(e) = liability.on_finish(success) {
+            debug::native::warn!(
+                target: LOG_TARGET,
+                "witnessed liability isn't settled: index={:?}, error={:?}",
+                index,
+                e,
+            );
+            return;
+        }
+        Self::note_report_outcome(index, &record, success);
+        Self::deposit_event(RawEvent::ReportSettled(index.into(), success));
+    }
+
+    /// Settle witnessed reports with passed challenge window from `SettlementSweepCursor`,
+    /// report notarized by quorum is settled in promisor favor.
+    fn settle_witnessed(now: BlockNumber<T>) -> SweepBudget {
+        Self::sweep_list::<_, SettlementsAt<T>, SettlementSweepCursor<T>>(now, |index, budget| {
+            // Overturned reports are left in the list, held ones wait for resolution
+            budget.access(2, 0);
+            if <HeldSettlements<T>>::contains_key(index)
+                || <PendingSettlement<T>>::take(index).is_none()
+            {
+                return;
+            }
+            budget.charge(T::WeightInfo::overturn_report(T::MaxWitnesses::get()));
+            let quorum = <WitnessQuorum<T>>::get(index).unwrap_or_default();
+            let notaries = Self::release_notaries(index);
+            if let Ok(record) = Self::record_of(index) {
+                Self::settle_report(index, record, notaries.len() >= quorum as usize);
+            }
+        })
+    }
+
+    /// Hold witnessed report in challenge window from settlement at the end of the window,
+    /// it's settled by `resolve_settlement` instead.
+    fn hold_settlement(index: LiabilityIndex<T>) -> Result<(), Error<T>> {
+        let deadline = <PendingSettlement<T>>::get(index).ok_or(Error::<T>::NotarizationClosed)?;
+        ensure!(
+            <frame_system::Module<T>>::block_number() <= deadline,
+            Error::<T>::NotarizationClosed
+        );
+        <HeldSettlements<T>>::insert(index, true);
+        Ok(())
+    }
+
+    /// Settle witnessed report waiting for settlement with given outcome, witnesses notarized
+    /// overturned report are slashed by `WitnessSlash` to promisee.
+    fn resolve_settlement(index: LiabilityIndex<T>, success: bool) -> DispatchResult {
+        ensure!(
+            <PendingSettlement<T>>::contains_key(index),
+            Error::<T>::NotarizationClosed
+        );
+        let record = Self::record_of(index)?;
+        let promisee = record.agreement.promisee().clone();
+
+        <PendingSettlement<T>>::remove(index);
+        <HeldSettlements<T>>::remove(index);
+        let notaries = Self::release_notaries(index);
+        if success {
+            Self::deposit_event(RawEvent::ReportUpheld(index.into()));
+        } else {
+            for witness in notaries {
+                Self::slash_witness(&witness, &promisee);
+            }
+            Self::deposit_event(RawEvent::ReportOverturned(index.into()));
+        }
+        Self::settle_report(index, record, success);
+        Ok(())
+    }
+
+    /// Take witnesses notarized report of liability, their stakes aren't kept for it anymore.
+    fn release_notaries(index: LiabilityIndex<T>) -> Vec<AccountId<T>> {
+        let notaries = <Notarizations<T>>::take(index);
+        for witness in notaries.iter() {
+            <WitnessPending<T>>::mutate_exists(witness, |pending| {
+                *pending = pending
+                    .and_then(|count| count.checked_sub(1))
+                    .filter(|count| *count > 0)
+            });
+        }
+        notaries
+    }
+
+    /// Slash `WitnessSlash` of witness stake to beneficiary, at most the whole stake.
+    fn slash_witness(witness: &AccountId<T>, beneficiary: &AccountId<T>) {
+        let stake = <WitnessStake<T>>::get(witness).unwrap_or_else(Zero::zero);
+        let slash = T::WitnessSlash::get().min(stake);
+        let not_moved = NamedReserve::<T>::repatriate_reserved_named(
+            &RESERVE_ID,
+            witness,
+            beneficiary,
+            slash,
+            BalanceStatus::Free,
+        )
+        .unwrap_or(slash);
+        let slashed = slash.saturating_sub(not_moved);
+        <WitnessStake<T>>::insert(witness, stake.saturating_sub(slashed));
+        Self::deposit_event(RawEvent::WitnessSlashed(witness.clone(), slashed));
+    }
+
+    /// Weight of call measured with sr25519 proofs adjusted to schemes of given proofs,
+    /// payload of `p` encoded bytes.
+    fn proofs_weight(weight: Weight, proofs: &[&ProofParam<T>], p: u32) -> Weight {
+        let measured = T::WeightInfo::verify_sr25519(p);
+        proofs.iter().fold(weight, |weight, proof| {
+            weight
+                .saturating_sub(measured)
+                .saturating_add(verify_weight::<T::WeightInfo>(proof.scheme(), p))
+        })
+    }
+
+    /// Stored liability record, missing record is told apart from undecodable and pruned ones.
+    fn record_of(index: LiabilityIndex<T>) -> Result<LiabilityRecordOf<T>, Error<T>> {
+        <Liabilities<T>>::get(index).ok_or_else(|| {
+            // Extra read is paid by failed calls only
+            if <Liabilities<T>>::contains_key(index) {
+                Self::quarantine(index);
+                Error::<T>::LiabilityDecodeFailure
+            } else if Self::is_pruned(index) {
+                Error::<T>::Pruned
+            } else {
+                Error::<T>::UnknownLiability
+            }
+        })
+    }
+
+    /// Quarantine liability record that can't be decoded, e.g. when runtime upgrade changed
+    /// liability type without migration, until governance resolves it.
+    fn quarantine(index: LiabilityIndex<T>) {
+        if <Quarantined<T>>::get(index) {
+            return;
+        }
+        <Quarantined<T>>::insert(index, true);
+        debug::native::warn!(
+            target: LOG_TARGET,
+            "liability record quarantined: index={:?}",
+            index,
+        );
+        Self::deposit_event(RawEvent::CorruptEntry(index.into()));
+    }
+
+    /// Check report proof of liability, report of strict firmware liability should attest
+    /// firmware approved for its robot model.
+    ///
+    /// Proof is verified before economics of liability is touched.
+    fn check_report_proof(
+        liability: &T::Liability,
+        index: &LiabilityIndex<T>,
+        report: &TechnicalReport<T>,
+        firmware: Option<&T::Hash>,
+        proof: &ProofParam<T>,
+    ) -> Result<(), Error<T>> {
+        let valid = match firmware {
+            None => liability.check_report(index, report, proof),
+            Some(firmware) => liability.check_attested_report(index, report, firmware, proof),
+        };
+        ensure!(
+            !<RotatedPromisor<T>>::contains_key(liability.promisor()),
+            Error::<T>::PromisorKeyRotated
+        );
+        ensure!(valid, Error::<T>::BadReportProof);
+
+        let strict = T::Economics::strict_firmware(liability.economics());
+        match firmware {
+            None => ensure!(!strict, Error::<T>::FirmwareRequired),
+            Some(firmware) => {
+                let model = T::Market::convert(liability.technics().clone());
+                ensure!(
+                    !strict || <ApprovedFirmware<T>>::get(model, firmware),
+                    Error::<T>::FirmwareNotApproved
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Run economical processing of new liability, store it with its tags and notify other
+    /// modules.
+    fn start_liability(
+        liability: T::Liability,
+        tags: Vec<Tag>,
+    ) -> Result<LiabilityIndex<T>, DispatchError> {
+        // The same agreement included twice in a block should not be started twice
+        let block_number = <frame_system::Module<T>>::block_number();
+        let agreement = Self::agreement_hash(&liability);
+        ensure!(
+            !<AgreementsInBlock<T>>::contains_key(block_number, agreement),
+            Error::<T>::DuplicateAgreement
+        );
+        let min_reputation = T::Economics::min_reputation(liability.economics());
+        let reputation = T::ReputationProvider::reputation(liability.promisor());
+        ensure!(reputation >= min_reputation, Error::<T>::ReputationTooLow);
+
+        // Promisor deposit is kept until liability is acknowledged
+        let window = T::AcknowledgementWindow::get();
+        let deposit = if window.is_zero() {
+            Zero::zero()
+        } else {
+            T::AcknowledgementDeposit::get()
+        };
+        let zero_value = Self::is_zero_value(liability.economics());
+        let zero_value_count = if zero_value {
+            Self::check_zero_value_count(liability.promisee())?
+        } else {
+            0
+        };
+        NamedReserve::<T>::reserve_named(&RESERVE_ID, liability.promisor(), deposit)?;
+
+        // Zero value liability has no price to limit it, its promisee deposits instead
+        let spam_deposit = if zero_value {
+            T::ZeroValueDeposit::get()
+        } else {
+            Zero::zero()
+        };
+        NamedReserve::<T>::reserve_named(&RESERVE_ID, liability.promisee(), spam_deposit).map_err(
+            |e| {
+                NamedReserve::<T>::unreserve_named(&RESERVE_ID, liability.promisor(), deposit);
+                e
+            },
+        )?;
+
+        // Economics of deferred funding liability is started when it's funded
+        let deferred = T::Economics::deferred_funding(liability.economics());
+        if !deferred {
+            sp_tracing::enter_span!("liability::economics");
+            liability.on_start().map_err(|e| {
+                NamedReserve::<T>::unreserve_named(&RESERVE_ID, liability.promisor(), deposit);
+                NamedReserve::<T>::unreserve_named(&RESERVE_ID, liability.promisee(), spam_deposit);
+                e
+            })?;
+        }
+
+        let technics = technics_hash(liability.technics());
+        Self::note_model(liability.technics(), |stats| {
+            stats.executions = stats.executions.saturating_add(1)
+        });
+        let economics = liability.economics().clone();
+        let promisee = liability.promisee().clone();
+        let promisor = liability.promisor().clone();
+
+        // Store liability and remember creation block
+        let latest_index = {
+            sp_tracing::enter_span!("liability::storage");
+            <AgreementsInBlock<T>>::insert(block_number, agreement, ());
+            <AgreementCreatedAt<T>>::insert(agreement, block_number);
+            let (latest_index, id) = Self::store_liability(liability, block_number);
+            if min_reputation > 0 {
+                <ReputationSnapshot<T>>::insert(latest_index, (min_reputation, reputation));
+            }
+            if let Some(robot_id) = T::Robots::robot_id(&promisor) {
+                <LiabilityRobot<T>>::insert(latest_index, robot_id);
+            }
+            // Payout account of promisor preferences is snapshotted, its later changes don't
+            // affect the liability
+            let payout = T::Economics::payout(&economics)
+                .or_else(|| <Preferences<T>>::get(&promisor).and_then(|prefs| prefs.payout));
+            if let Some(payout) = payout.filter(|payout| payout != &promisor) {
+                <PayoutOf<T>>::insert(latest_index, payout);
+            }
+            if !tags.is_empty() {
+                Self::index_tags(latest_index, &tags);
+                <TagsOf<T>>::insert(latest_index, &tags);
+            }
+            if !window.is_zero() {
+                let deadline = block_number.saturating_add(window);
+                <PendingAcknowledgement<T>>::insert(latest_index, (deadline, deposit));
+            }
+            if zero_value {
+                <ZeroValueCount<T>>::insert(&promisee, (Self::stats_era(), zero_value_count + 1));
+                if !spam_deposit.is_zero() {
+                    <ZeroValueDeposits<T>>::insert(latest_index, spam_deposit);
+                }
+            }
+            if deferred {
+                let deadline = block_number.saturating_add(T::FundingDeadline::get());
+                <PendingFunding<T>>::insert(latest_index, deadline);
+                <FundingExpiringAt<T>>::mutate(deadline.saturating_add(One::one()), |indices| {
+                    indices.push(latest_index)
+                });
+            }
+            latest_index
+        };
+        debug::native::trace!(
+            target: LOG_TARGET,
+            "liability created: index={:?}, promisee={:?}, promisor={:?}",
+            latest_index,
+            promisee,
+            promisor,
+        );
+
+        // Notify other modules
+        let hooks = T::Hooks::isolated_liability_created(
+            &latest_index,
+            &promisee,
+            &promisor,
+            T::MaxHooksWeight::get(),
+        );
+        Self::note_hooks(latest_index, hooks);
+
+        Self::deposit_event(RawEvent::NewLiability(
+            latest_index.into(),
+            id,
+            technics,
+            economics,
+            promisee,
+            promisor,
+            block_number,
+            tags,
+        ));
+        Ok(latest_index)
+    }
+
+    /// Push liability to index of each its tag, the oldest liability of tag is pruned
+    /// when limit reached.
+    fn index_tags(index: LiabilityIndex<T>, tags: &[Tag]) {
+        let max_tagged = T::MaxTaggedLiabilities::get() as usize;
+        for tag in tags {
+            <ByTag<T>>::mutate(tag, |indices| {
+                indices.push(index);
+                if indices.len() > max_tagged {
+                    let pruned = indices.len() - max_tagged;
+                    indices.drain(..pruned);
+                }
+            });
+        }
+    }
+
+    /// Finalize open liability record without report: run economical processing, store
+    /// forced outcome and notify other modules.
+    fn force_record(
+        index: LiabilityIndex<T>,
+        mut record: LiabilityRecordOf<T>,
+        success: bool,
+    ) -> DispatchResult {
+        // Economics of unfunded liability isn't started
+        ensure!(
+            !<PendingFunding<T>>::contains_key(index),
+            Error::<T>::FundingPending
+        );
+        ensure!(
+            !<ReportCommitments<T>>::contains_key(index),
+            Error::<T>::ReportCommitted
+        );
+
+        // Run economical processing
+        let liability = &record.agreement;
+        liability.on_finish(success)?;
+
+        if success {
+            let settled = T::SettledValue::convert(liability.economics().clone());
+            <Counters<T>>::mutate(|counters| {
+                counters.finalized += 1;
+                counters.settled = counters.settled.saturating_add(settled);
+            });
+        } else {
+            <Counters<T>>::mutate(|counters| counters.cancelled += 1);
+        }
+        Self::note_stats(liability, success, true);
+        if success {
+            Self::pay_out(index, liability);
+        }
+        let (promisee, promisor) = (liability.promisee().clone(), liability.promisor().clone());
+        Self::refund_relay_bounty(index, &promisee);
+        Self::release_zero_value_deposit(index, &promisee);
+        Self::release_acknowledgement(index, &promisor);
+        let block_number = <frame_system::Module<T>>::block_number();
+        record.state = RecordState::Forced(success);
+        record.finalized_at = Some(block_number);
+        Self::schedule_prune(index, &record.agreement, block_number);
+        <Liabilities<T>>::insert(index, record);
+        Self::record_transition(index, block_number, Transition::ForceFinalized(success));
+        Self::note_finalization(index, &promisor, None, success);
+
+        // Notify other modules
+        let hooks = T::Hooks::isolated_report_accepted(
+            &index,
+            &promisee,
+            &promisor,
+            success,
+            T::MaxHooksWeight::get(),
+        );
+        Self::note_hooks(index, hooks);
+
+        Self::deposit_event(RawEvent::ForceFinalized(
+            index.into(),
+            success,
+            block_number,
+        ));
+        Ok(())
+    }
+
+    /// Check agreement of unsigned `create` calls with both parties proofs optionally
+    /// expiring at given block, then start liability.
+    fn create_agreement(
+        liability: T::Liability,
+        expires_at: Option<BlockNumber<T>>,
+        tags: Vec<Tag>,
+        bounty: Option<BalanceOf<T>>,
+        requirement: Option<&PromisorRequirementOf<T>>,
+        promisee_proof: ProofParam<T>,
+        promisor_proof: ProofParam<T>,
+    ) -> Result<LiabilityIndex<T>, DispatchError> {
+        Self::pre_validate_create(&liability, expires_at, &tags, bounty.as_ref(), requirement)?;
+        let promisee = liability.promisee().clone();
+        let promisor = liability.promisor().clone();
+
+        {
+            sp_tracing::enter_span!("liability::verify_proofs");
+
+            // Check promisee proof, tags, bounty and requirement are agreed by promisee
+            let promisee_valid = Self::check_promisee_proof(
+                &liability,
+                &promisee_proof,
+                expires_at,
+                &tags,
+                bounty.as_ref(),
+                requirement,
+            );
+            if !promisee_valid {
+                Err(Error::<T>::BadPromiseeProof)?
+            }
+
+            // Check promisor proof
+            if !Self::check_party_proof(&liability, &promisor_proof, &promisor, expires_at) {
+                Err(Error::<T>::BadPromisorProof)?
+            }
+        }
+
+        // Bounty is released when liability can't be started
+        let bounty = bounty.filter(|bounty| !bounty.is_zero());
+        if let Some(bounty) = bounty {
+            NamedReserve::<T>::reserve_named(&RESERVE_ID, &promisee, bounty)?;
+        }
+        let index = Self::start_liability(liability, tags).map_err(|e| {
+            if let Some(bounty) = bounty {
+                NamedReserve::<T>::unreserve_named(&RESERVE_ID, &promisee, bounty);
+            }
+            e
+        })?;
+        if let Some(bounty) = bounty {
+            <RelayBounty<T>>::insert(index, bounty);
+        }
+        Self::note_unsigned_call(Some((&promisee, &promisor)));
+        Ok(index)
+    }
+
+    /// Checks of unsigned `create` calls made before parties proofs verification, so
+    /// rejected calls don't pay for it. All of them are constant time storage and arithmetic
+    /// checks, in order: rate limits, parameters sizes, proofs expiry, parties permissions,
+    /// parties funds and duplicate agreement of the block.
+    fn pre_validate_create(
+        liability: &T::Liability,
+        expires_at: Option<BlockNumber<T>>,
+        tags: &[Tag],
+        bounty: Option<&BalanceOf<T>>,
+        requirement: Option<&PromisorRequirementOf<T>>,
+    ) -> Result<(), Error<T>> {
+        let (promisee, promisor) = (liability.promisee(), liability.promisor());
+        Self::check_rate_limits(Some((promisee, promisor)))?;
+        Self::ensure_technics_length(liability.technics())?;
+        Self::ensure_tags(tags)?;
+        Self::ensure_requirement_length(requirement)?;
+
+        let block_number = <frame_system::Module<T>>::block_number();
+        if let Some(expires_at) = expires_at {
+            ensure!(block_number <= expires_at, Error::<T>::ProofExpired);
+        }
+
+        Self::ensure_not_blacklisted(promisee)?;
+        Self::ensure_not_blacklisted(promisor)?;
+        Self::ensure_approved(promisor)?;
+        Self::ensure_capable(liability.technics(), promisor)?;
+        Self::ensure_allowed_promisor(requirement, promisor)?;
+        Self::ensure_identity(liability.economics(), promisee)?;
+
+        // Promisee should afford relay bounty and promisor acknowledgement deposit
+        if let Some(bounty) = bounty {
+            ensure!(
+                T::Currency::can_reserve(promisee, *bounty),
+                Error::<T>::CannotAffordDeposit
+            );
+        }
+        if Self::is_zero_value(liability.economics()) {
+            Self::check_zero_value_count(promisee)?;
+            ensure!(
+                T::Currency::can_reserve(promisee, T::ZeroValueDeposit::get()),
+                Error::<T>::CannotAffordDeposit
+            );
+        }
+        ensure!(
+            T::AcknowledgementWindow::get().is_zero()
+                || T::Currency::can_reserve(promisor, T::AcknowledgementDeposit::get()),
+            Error::<T>::CannotAffordDeposit
+        );
+
+        ensure!(
+            !<AgreementsInBlock<T>>::contains_key(block_number, Self::agreement_hash(liability)),
+            Error::<T>::DuplicateAgreement
+        );
+        Ok(())
+    }
+
+    /// Check promisee proof of liability parameters with tags, relay bounty or requirement
+    /// to promisor, optionally expiring at given block.
+    fn check_promisee_proof(
+        liability: &T::Liability,
+        proof: &ProofParam<T>,
+        expires_at: Option<BlockNumber<T>>,
+        tags: &[Tag],
+        bounty: Option<&BalanceOf<T>>,
+        requirement: Option<&PromisorRequirementOf<T>>,
+    ) -> bool {
+        let promisee = liability.promisee();
+        match (bounty, requirement) {
+            (Some(bounty), _) => liability.check_bounty_params(proof, promisee, bounty),
+            (None, Some(requirement)) => {
+                liability.check_restricted_params(proof, promisee, requirement)
+            }
+            (None, None) if tags.is_empty() => {
+                Self::check_party_proof(liability, proof, promisee, expires_at)
+            }
+            (None, None) => liability.check_tagged_params(proof, promisee, tags),
+        }
+    }
+
+    /// Return relay bounty of liability to promisee, see `create_with_bounty`.
+    fn refund_relay_bounty(index: LiabilityIndex<T>, promisee: &AccountId<T>) {
+        if let Some(bounty) = <RelayBounty<T>>::take(index) {
+            NamedReserve::<T>::unreserve_named(&RESERVE_ID, promisee, bounty);
+            Self::deposit_event(RawEvent::RelayBountyRefunded(index.into(), bounty));
+        }
+    }
+
+    /// Return deposit of zero value liability to promisee.
+    fn release_zero_value_deposit(index: LiabilityIndex<T>, promisee: &AccountId<T>) {
+        if let Some(deposit) = <ZeroValueDeposits<T>>::take(index) {
+            NamedReserve::<T>::unreserve_named(&RESERVE_ID, promisee, deposit);
+        }
+    }
+
+    /// Count of zero value liabilities created by promisee in current statistics era,
+    /// fails when promisee can't create more of them.
+    fn check_zero_value_count(promisee: &AccountId<T>) -> Result<u32, Error<T>> {
+        let (era, count) = <ZeroValueCount<T>>::get(promisee);
+        let count = if era == Self::stats_era() { count } else { 0 };
+        let max = T::MaxZeroValuePerEra::get();
+        ensure!(max == 0 || count < max, Error::<T>::TooManyZeroValue);
+        Ok(count)
+    }
+
+    /// Return acknowledgement deposit of liability to promisor, any promisor action on
+    /// liability proves it's online.
+    fn release_acknowledgement(index: LiabilityIndex<T>, promisor: &AccountId<T>) {
+        if let Some((_, deposit)) = <PendingAcknowledgement<T>>::take(index) {
+            NamedReserve::<T>::unreserve_named(&RESERVE_ID, promisor, deposit);
+        }
+    }
+
+    /// Check that liability record waits for acknowledgement and promisor proof of it is
+    /// valid, returns acknowledgement deadline and deposit.
+    fn check_acknowledge(
+        index: LiabilityIndex<T>,
+        record: &LiabilityRecordOf<T>,
+        proof: &ProofParam<T>,
+    ) -> Result<(BlockNumber<T>, BalanceOf<T>), Error<T>> {
+        ensure!(
+            record.state == RecordState::Open,
+            Error::<T>::AlreadyFinalized
+        );
+        let (deadline, deposit) =
+            <PendingAcknowledgement<T>>::get(index).ok_or(Error::<T>::NotPendingAcknowledgement)?;
+        let block_number = <frame_system::Module<T>>::block_number();
+        ensure!(block_number <= deadline, Error::<T>::AcknowledgementExpired);
+        ensure!(
+            record.agreement.check_acknowledgement(&index, proof),
+            Error::<T>::BadPromisorProof
+        );
+        Ok((deadline, deposit))
+    }
+
+    /// Check party proof of liability parameters, optionally expiring at given block.
+    fn check_party_proof(
+        liability: &T::Liability,
+        proof: &ProofParam<T>,
+        sender: &AccountId<T>,
+        expires_at: Option<BlockNumber<T>>,
+    ) -> bool {
+        match expires_at {
+            Some(expires_at) => liability.check_params_until(proof, sender, &expires_at),
+            None => liability.check_params(proof, sender),
+        }
+    }
+
+    /// Check parties proofs of liability cancel, returns party of unilateral cancel.
+    fn check_cancel_proofs(
+        liability: &T::Liability,
+        index: &LiabilityIndex<T>,
+        expires_at: &BlockNumber<T>,
+        promisee_proof: Option<&ProofParam<T>>,
+        promisor_proof: Option<&ProofParam<T>>,
+    ) -> Result<Option<Party>, Error<T>> {
+        if let Some(proof) = promisee_proof {
+            ensure!(
+                liability.check_cancel(index, expires_at, proof, liability.promisee()),
+                Error::<T>::BadPromiseeProof
+            );
+        }
+        if let Some(proof) = promisor_proof {
+            ensure!(
+                liability.check_cancel(index, expires_at, proof, liability.promisor()),
+                Error::<T>::BadPromisorProof
+            );
+        }
+        match (promisee_proof, promisor_proof) {
+            (Some(_), Some(_)) => Ok(None),
+            (Some(_), None) => Ok(Some(Party::Promisee)),
+            (None, Some(_)) => Ok(Some(Party::Promisor)),
+            (None, None) => Err(Error::<T>::CancelProofRequired),
+        }
+    }
+
+    /// Check that report of liability record could be committed with given promisor proof.
+    fn check_commit(
+        index: LiabilityIndex<T>,
+        record: &LiabilityRecordOf<T>,
+        commitment: &H256,
+        proof: &ProofParam<T>,
+    ) -> Result<(), Error<T>> {
+        ensure!(
+            record.state == RecordState::Open,
+            Error::<T>::AlreadyFinalized
+        );
+        ensure!(
+            !<PendingFunding<T>>::contains_key(index),
+            Error::<T>::FundingPending
+        );
+        ensure!(
+            !<ReportCommitments<T>>::contains_key(index),
+            Error::<T>::ReportCommitted
+        );
+        Self::check_report_delay(record)?;
+        // Revealed report attests no firmware
+        let liability = &record.agreement;
+        ensure!(
+            !T::Economics::strict_firmware(liability.economics()),
+            Error::<T>::FirmwareRequired
+        );
+        ensure!(
+            !<RotatedPromisor<T>>::contains_key(liability.promisor()),
+            Error::<T>::PromisorKeyRotated
+        );
+        ensure!(
+            liability.check_commitment(&index, commitment, proof),
+            Error::<T>::BadReportProof
+        );
+        Ok(())
+    }
+
+    /// Check that report and salt match commitment of liability in its reveal window,
+    /// returns the last block of the window.
+    fn check_reveal(
+        index: LiabilityIndex<T>,
+        report: &TechnicalReport<T>,
+        salt: &H256,
+    ) -> Result<BlockNumber<T>, Error<T>> {
+        let (commitment, deadline) =
+            <ReportCommitments<T>>::get(index).ok_or(Error::<T>::NoCommitment)?;
+        let block_number = <frame_system::Module<T>>::block_number();
+        ensure!(block_number <= deadline, Error::<T>::RevealWindowPassed);
+        ensure!(
+            report_commitment(report, salt) == commitment,
+            Error::<T>::CommitmentMismatch
+        );
+        Ok(deadline)
+    }
+
+    /// Create liability of demand and offer with the same parameters, see `match_orders`,
+    /// committed caller is rewarded in markets without lighthouses.
+    fn match_pair(
+        sender: AccountId<T>,
+        demand_id: OrderId,
+        offer_id: OrderId,
+        committed: bool,
+    ) -> DispatchResult {
+        let mut demand = Self::open_order(<Asks<T>>::get(demand_id), demand_id)?;
+        let mut offer = Self::open_order(<Bids<T>>::get(offer_id), offer_id)?;
+        ensure!(
+            demand.technics == offer.technics && demand.economics == offer.economics,
+            Error::<T>::OrderMismatch
+        );
+        let quantity = demand.quantity.min(offer.quantity);
+        let economics = T::Economics::scale(&demand.economics, quantity);
+        // Orders posted before blacklisting aren't matched
+        Self::ensure_not_blacklisted(&demand.sender)?;
+        Self::ensure_not_blacklisted(&offer.sender)?;
+        Self::ensure_approved(&offer.sender)?;
+        Self::ensure_capable(&offer.technics, &offer.sender)?;
+        Self::ensure_allowed_promisor(
+            <DemandRequirements<T>>::get(demand_id).as_ref(),
+            &offer.sender,
+        )?;
+        Self::ensure_identity(&economics, &demand.sender)?;
+
+        // Residual below minimal fill couldn't be matched anymore, so it takes the rest
+        // of escrow to leave nothing reserved because of rounding
+        let market_id = Self::market_of(&demand.technics, &demand.economics);
+        demand.quantity -= quantity;
+        offer.quantity -= quantity;
+        let fill_escrow = if Self::is_consumed(&demand) {
+            demand.escrow
+        } else {
+            Self::match_escrow(market_id, &economics).min(demand.escrow)
+        };
+        demand.escrow = demand.escrow.saturating_sub(fill_escrow);
+
+        // Fee is paid first, fee raised after demand posting is limited by its escrow
+        let fee = Self::match_fee(market_id, &economics).min(fill_escrow);
+        let reward = fill_escrow.saturating_sub(fee);
+
+        // Unscheduled callers still match orders, but unrewarded, in markets without
+        // lighthouses reward is left for committed match
+        let now = <frame_system::Module<T>>::block_number();
+        let rewarded = match Self::scheduled_lighthouse(market_id, now) {
+            Some(lighthouse) => lighthouse == sender,
+            None => committed,
+        };
+
+        // Order proofs are checked when orders posted
+        let price = T::SettledValue::convert(demand.economics.clone());
+        let value = T::SettledValue::convert(economics.clone());
+        let liability = T::Liability::new(
+            demand.technics.clone(),
+            economics,
+            demand.sender.clone(),
+            offer.sender.clone(),
+        );
+        let index = Self::start_liability(liability, Vec::new())?;
+
+        if !fee.is_zero() {
+            Self::pay_fee(demand_id, &demand.sender, &sender, rewarded, fee);
+        }
+        if !rewarded || reward.is_zero() {
+            NamedReserve::<T>::unreserve_named(&RESERVE_ID, &demand.sender, reward);
+        } else {
+            // Reward is reserved, so nothing is left unpaid
+            let _ = NamedReserve::<T>::repatriate_reserved_named(
+                &RESERVE_ID,
+                &demand.sender,
+                &sender,
+                reward,
+                BalanceStatus::Free,
+            );
+            Self::deposit_event(RawEvent::LighthouseRewarded(
+                demand_id,
+                sender.clone(),
+                reward,
+            ));
+        }
+        Self::fill_order(Side::Demand, market_id, demand_id, demand, index);
+        Self::fill_order(Side::Offer, market_id, offer_id, offer, index);
+        Self::record_match(
+            market_id,
+            MatchInfo {
+                demand_id,
+                offer_id,
+                index,
+                price,
+                quantity,
+                value,
+                block_number: now,
+            },
+        );
+
+        Self::deposit_event(RawEvent::Matched(
+            demand_id,
+            offer_id,
+            index.into(),
+            market_id,
+            price,
+            quantity,
+            sender,
+        ));
+        Ok(())
+    }
+
+    /// Reserve order funds and list it in market book of given side, returns order id.
+    /// The worst order of full book is evicted, new order should be better than it.
+    fn post_order(side: Side, order: OrderOf<T>) -> Result<OrderId, DispatchError> {
+        let market_id = Self::market_of(&order.technics, &order.economics);
+        let price = T::SettledValue::convert(order.economics.clone());
+        let max_orders = T::MaxOrdersPerMarket::get() as usize;
+
+        // Orders of the same price keep posting order
+        let book = Self::order_book(side, market_id);
+        let position = book
+            .iter()
+            .position(|(listed, _)| side.is_better(&price, listed))
+            .unwrap_or(book.len());
+        ensure!(position < max_orders, Error::<T>::OrderBookFull);
+
+        NamedReserve::<T>::reserve_named(
+            &RESERVE_ID,
+            &order.sender,
+            order.deposit.saturating_add(order.escrow),
+        )?;
+        <OpenOrders<T>>::mutate(&order.sender, |open| *open += 1);
+        let order_id = Self::next_order_id();
+        <OrdersExpiringAt<T>>::mutate(order.deadline.saturating_add(One::one()), |orders| {
+            orders.push(order_id)
+        });
+        Self::deposit_event(RawEvent::OrderPosted(
+            order_id,
+            side,
+            market_id,
+            technics_hash(&order.technics),
+            order.economics.clone(),
+            order.quantity,
+            price,
+            order.sender.clone(),
+            order.deadline,
+        ));
+        match side {
+            Side::Demand => <Asks<T>>::insert(order_id, order),
+            Side::Offer => <Bids<T>>::insert(order_id, order),
+        }
+        let evicted = Self::mutate_order_book(side, market_id, |book| {
+            book.insert(position, (price, order_id));
+            if book.len() > max_orders {
+                book.pop()
+            } else {
+                None
+            }
+        });
+        if let Some((_, evicted_id)) = evicted {
+            let evicted = match side {
+                Side::Demand => <Asks<T>>::get(evicted_id),
+                Side::Offer => <Bids<T>>::get(evicted_id),
+            };
+            if let Some(evicted) = evicted {
+                Self::drop_order(side, evicted_id, &evicted);
+            }
+        }
+        Ok(order_id)
+    }
+
+    /// Remove order of given side, return its funds and notify about it with open quantity.
+    fn drop_order(side: Side, order_id: OrderId, order: &OrderOf<T>) {
+        NamedReserve::<T>::unreserve_named(
+            &RESERVE_ID,
+            &order.sender,
+            order.deposit.saturating_add(order.escrow),
+        );
+        Self::close_order(&order.sender);
+        match side {
+            Side::Demand => {
+                <Asks<T>>::remove(order_id);
+                <DemandRequirements<T>>::remove(order_id);
+            }
+            Side::Offer => <Bids<T>>::remove(order_id),
+        }
+        let market_id = Self::market_of(&order.technics, &order.economics);
+        Self::deposit_event(RawEvent::OrderCancelled(
+            order_id,
+            side,
+            market_id,
+            order.quantity,
+        ));
+    }
+
+    /// Sweep block indexed list `L` up to given block from cursor `C`: block swept next and
+    /// count of its items already swept. Each item and each empty block takes an item of
+    /// budget, swept blocks are removed from the list. Items are only pushed to lists, so
+    /// count of swept items of block is kept when it's continued in the next block.
+    fn sweep_list<K, L, C>(
+        now: BlockNumber<T>,
+        mut sweep: impl FnMut(K, &mut SweepBudget),
+    ) -> SweepBudget
+    where
+        K: Clone,
+        Vec<K>: FullCodec,
+        L: StorageMap<BlockNumber<T>, Vec<K>, Query = Vec<K>>,
+        C: StorageValue<(BlockNumber<T>, u32), Query = (BlockNumber<T>, u32)>,
+    {
+        let mut budget = SweepBudget::new::<T>();
+        let cursor = C::get();
+        budget.access(1, 0);
+
+        let (mut block, mut swept) = cursor;
+        while block <= now && budget.left > 0 {
+            let items = L::get(block);
+            budget.access(1, 0);
+
+            if items.is_empty() {
+                budget.take();
+            }
+            for item in items.iter().skip(swept as usize) {
+                if !budget.take() {
+                    break;
+                }
+                sweep(item.clone(), &mut budget);
+                swept += 1;
+            }
+            if (swept as usize) < items.len() {
+                break;
+            }
+
+            if !items.is_empty() {
+                L::remove(block);
+                budget.access(0, 1);
+            }
+            block += One::one();
+            swept = 0;
+        }
+
+        if (block, swept) != cursor {
+            C::put((block, swept));
+            budget.access(0, 1);
+        }
+        budget
+    }
+
+    /// Remove orders expired up to given block from `ExpirySweepCursor`, see `sweep_list`.
+    fn sweep_expired_orders(now: BlockNumber<T>) -> SweepBudget {
+        Self::sweep_list::<_, OrdersExpiringAt<T>, ExpirySweepCursor<T>>(now, |order_id, budget| {
+            // Consumed orders are already removed, residuals of filled ones are expired too
+            if let Some(order) = <Asks<T>>::take(order_id) {
+                <DemandRequirements<T>>::remove(order_id);
+                Self::expire_order(Side::Demand, order_id, order);
+            } else if let Some(order) = <Bids<T>>::take(order_id) {
+                Self::expire_order(Side::Offer, order_id, order);
+            }
+            budget.access(3, 4);
+        })
+    }
+
+    /// Unlist removed expired order, slash share of its deposit and return the rest of funds.
+    fn expire_order(side: Side, order_id: OrderId, order: OrderOf<T>) {
+        let market_id = Self::market_of(&order.technics, &order.economics);
+        Self::unlist_order(side, market_id, order_id);
+        let slash = T::ExpiredOrderSlash::get().mul_floor(order.deposit);
+        let (imbalance, _) =
+            NamedReserve::<T>::slash_reserved_named(&RESERVE_ID, &order.sender, slash);
+        T::MarketFeeDestination::on_unbalanced(imbalance);
+        NamedReserve::<T>::unreserve_named(
+            &RESERVE_ID,
+            &order.sender,
+            order
+                .deposit
+                .saturating_sub(slash)
+                .saturating_add(order.escrow),
+        );
+        Self::close_order(&order.sender);
+        Self::deposit_event(RawEvent::OrderExpired(
+            order_id,
+            side,
+            market_id,
+            order.quantity,
+            slash,
+        ));
+    }
+
+    /// Store residual of filled order, consumed order is removed with its funds returned.
+    fn fill_order(
+        side: Side,
+        market_id: MarketId,
+        order_id: OrderId,
+        order: OrderOf<T>,
+        index: LiabilityIndex<T>,
+    ) {
+        if !Self::is_consumed(&order) {
+            match side {
+                Side::Demand => <Asks<T>>::insert(order_id, order),
+                Side::Offer => <Bids<T>>::insert(order_id, order),
+            }
+            return;
+        }
+
+        Self::unlist_order(side, market_id, order_id);
+        <MatchedOrders<T>>::insert(order_id, index);
+        if order.quantity.is_zero() {
+            match side {
+                Side::Demand => {
+                    <Asks<T>>::remove(order_id);
+                    <DemandRequirements<T>>::remove(order_id);
+                }
+                Side::Offer => <Bids<T>>::remove(order_id),
+            }
+            NamedReserve::<T>::unreserve_named(
+                &RESERVE_ID,
+                &order.sender,
+                order.deposit.saturating_add(order.escrow),
+            );
+            Self::close_order(&order.sender);
+        } else {
+            Self::drop_order(side, order_id, &order);
+        }
+    }
+
+    /// Select promisors of demands requested for assignment up to given block from
+    /// `AssignmentSweepCursor` and match their offers to demands.
+    fn assign_demands(now: BlockNumber<T>) -> SweepBudget {
+        Self::sweep_list::<_, AssignmentsAt<T>, AssignmentSweepCursor<T>>(
+            now,
+            |demand_id, budget| {
+                budget.access(1, 1);
+                if let Some(requested_at) = <PendingAssignment<T>>::take(demand_id) {
+                    budget.charge(Self::assign_demand(demand_id, requested_at));
+                }
+            },
+        )
+    }
+
+    /// Match demand to offer of promisor selected from assignment pool by stake-weighted
+    /// randomness. Promisors joined at request block or later aren't selected, so pool
+    /// couldn't be changed after selection randomness is known.
+    fn assign_demand(demand_id: OrderId, requested_at: BlockNumber<T>) -> Weight {
+        // Demand removed since request is left unassigned
+        let demand = match <Asks<T>>::get(demand_id) {
+            Some(demand) => demand,
+            None => return T::DbWeight::get().reads_writes(2, 1),
+        };
+        let market_id = Self::market_of(&demand.technics, &demand.economics);
+        let pool = <Pools<T>>::get(market_id);
+        let requirement = <DemandRequirements<T>>::get(demand_id);
+        let registry_reads = match requirement {
+            None | Some(PromisorRequirement::AllowList(_)) => 0,
+            Some(_) => 4,
+        };
+        let members = pool.len() as Weight;
+        let mut weight = T::DbWeight::get().reads_writes(4 + (1 + registry_reads) * members, 1);
+
+        // Promisors not allowed by demand requirement aren't selected
+        let qualified: Vec<_> = pool
+            .into_iter()
+            .filter(|(member, offer_id, _, joined_at)| {
+                *joined_at < requested_at
+                    && <Bids<T>>::get(offer_id).map_or(false, |offer| {
+                        &offer.sender == member
+                            && offer.technics == demand.technics
+                            && offer.economics == demand.economics
+                    })
+                    && Self::ensure_allowed_promisor(requirement.as_ref(), member).is_ok()
+            })
+            .collect();
+        let stakes: Vec<u128> = qualified
+            .iter()
+            .map(|(_, _, stake, _)| (*stake).unique_saturated_into())
+            .collect();
+        let random = T::Randomness::random(&(ASSIGNMENT_SUBJECT, demand_id).encode());
+        let seed = u128::decode(&mut random.as_ref()).unwrap_or_default();
+
+        let assigned = select_weighted(&stakes, seed).and_then(|selected| {
+            let (promisor, offer_id, _, _) = qualified[selected].clone();
+            Self::match_pair(demand.sender.clone(), demand_id, offer_id, false)
+                .ok()
+                .map(|_| promisor)
+        });
+        match assigned {
+            Some(promisor) => {
+                weight = weight.saturating_add(T::WeightInfo::match_orders());
+                Self::deposit_event(RawEvent::DemandAssigned(demand_id, promisor));
+            }
+            None => Self::deposit_event(RawEvent::AssignmentFailed(demand_id)),
+        }
+        weight
+    }
+
+    /// Push match to recent matches of market, the oldest one is evicted when limit reached.
+    fn record_match(market_id: MarketId, info: MatchInfoOf<T>) {
+        <MarketStats<T>>::mutate(|totals| {
+            totals.matches = totals.matches.saturating_add(1);
+            totals.quantity = totals.quantity.saturating_add(info.quantity as u64);
+            totals.value = totals.value.saturating_add(info.value);
+        });
+        let max_matches = T::MaxRecentMatches::get() as usize;
+        <RecentMatches<T>>::mutate(market_id, |matches| {
+            matches.push(info);
+            if matches.len() > max_matches {
+                let evicted = matches.len() - max_matches;
+                if let Some(latest) = matches.drain(..evicted).last() {
+                    <EvictedMatchAt<T>>::insert(market_id, latest.block_number);
+                }
+            }
+        });
+    }
+
+    /// Release open order slot of account.
+    fn close_order(who: &AccountId<T>) {
+        <OpenOrders<T>>::mutate_exists(who, |open| {
+            *open = open
+                .and_then(|open| open.checked_sub(1))
+                .filter(|open| *open > 0)
+        });
+    }
+
+    /// Promisee escrow of matching reward and market fee for given liability economics.
+    fn match_escrow(market_id: MarketId, economics: &EconomicalParam<T>) -> BalanceOf<T> {
+        T::MatchingReward::convert(economics.clone())
+            .saturating_add(Self::match_fee(market_id, economics))
+    }
+
+    /// Market fee of liability with given economics, settled value is taken in market funds.
+    fn match_fee(market_id: MarketId, economics: &EconomicalParam<T>) -> BalanceOf<T> {
+        let value: u128 = T::SettledValue::convert(economics.clone()).unique_saturated_into();
+        Self::market_config(market_id)
+            .fee
+            .mul_floor(BalanceOf::<T>::unique_saturated_from(value))
+    }
+
+    /// Pay reserved market fee of promisee, lighthouse share goes to rewarded caller.
+    fn pay_fee(
+        demand_id: OrderId,
+        promisee: &AccountId<T>,
+        caller: &AccountId<T>,
+        rewarded: bool,
+        fee: BalanceOf<T>,
+    ) {
+        let share = if rewarded {
+            T::LighthouseFeeShare::get().mul_floor(fee)
+        } else {
+            Zero::zero()
+        };
+        let (imbalance, _) = NamedReserve::<T>::slash_reserved_named(
+            &RESERVE_ID,
+            promisee,
+            fee.saturating_sub(share),
+        );
+        let treasury = imbalance.peek();
+        T::MarketFeeDestination::on_unbalanced(imbalance);
+        let paid: u128 = treasury.saturating_add(share).unique_saturated_into();
+        <MarketStats<T>>::mutate(|totals| {
+            totals.fees = totals
+                .fees
+                .saturating_add(T::Balance::unique_saturated_from(paid))
+        });
+        if !share.is_zero() {
+            let _ = NamedReserve::<T>::repatriate_reserved_named(
+                &RESERVE_ID,
+                promisee,
+                caller,
+                share,
+                BalanceStatus::Free,
+            );
+        }
+        Self::deposit_event(RawEvent::MatchFeePaid(
+            demand_id,
+            caller.clone(),
+            share,
+            treasury,
+        ));
+    }
+
+    /// Order couldn't be filled anymore.
+    fn is_consumed(order: &OrderOf<T>) -> bool {
+        order.quantity < T::MinFillQuantity::get().max(1)
+    }
+
+    /// Remove order from market book of given side.
+    fn unlist_order(side: Side, market_id: MarketId, order_id: OrderId) {
+        Self::mutate_order_book(side, market_id, |book| {
+            book.retain(|(_, listed)| *listed != order_id)
+        });
+    }
+
+    fn order_book(side: Side, market_id: MarketId) -> Vec<(T::Balance, OrderId)> {
+        match side {
+            Side::Demand => <AsksByModel<T>>::get(market_id),
+            Side::Offer => <BidsByModel<T>>::get(market_id),
+        }
+    }
+
+    fn mutate_order_book<R>(
+        side: Side,
+        market_id: MarketId,
+        f: impl FnOnce(&mut Vec<(T::Balance, OrderId)>) -> R,
+    ) -> R {
+        match side {
+            Side::Demand => <AsksByModel<T>>::mutate(market_id, f),
+            Side::Offer => <BidsByModel<T>>::mutate(market_id, f),
+        }
+    }
+
+    /// Check order quantity, deadline, open orders of sender and proof optionally covering
+    /// requirement to promisor, funds aren't reserved yet and order has no escrow.
+    fn new_order(
+        sender: AccountId<T>,
+        technics: TechnicalParam<T>,
+        economics: EconomicalParam<T>,
+        quantity: u32,
+        deadline: BlockNumber<T>,
+        proof: ProofParam<T>,
+        requirement: Option<&PromisorRequirementOf<T>>,
+    ) -> Result<OrderOf<T>, Error<T>> {
+        Self::ensure_not_blacklisted(&sender)?;
+        Self::ensure_technics_length(&technics)?;
+        Self::ensure_requirement_length(requirement)?;
+        ensure!(
+            quantity >= T::MinFillQuantity::get().max(1),
+            Error::<T>::InvalidQuantity
+        );
+        ensure!(
+            deadline > <frame_system::Module<T>>::block_number(),
+            Error::<T>::DeadlineInPast
+        );
+        let open = <OpenOrders<T>>::get(&sender);
+        ensure!(
+            open < T::MaxOpenOrdersPerAccount::get(),
+            Error::<T>::TooManyOpenOrders
+        );
+        let market = Self::market_config(Self::market_of(&technics, &economics));
+
+        // Proof covers parameters of the whole quantity, so order creator stands for both parties
+        let liability = T::Liability::new(
+            technics.clone(),
+            T::Economics::scale(&economics, quantity),
+            sender.clone(),
+            sender.clone(),
+        );
+        let proof_valid = match requirement {
+            Some(requirement) => liability.check_restricted_params(&proof, &sender, requirement),
+            None => liability.check_params(&proof, &sender),
+        };
+        ensure!(proof_valid, Error::<T>::BadOrderProof);
+
+        Ok(Order {
+            technics,
+            economics,
+            quantity,
+            sender,
+            proof,
+            deadline,
+            deposit: market
+                .order_deposit
+                .saturating_mul((open + 1).saturating_pow(2).into()),
+            escrow: Zero::zero(),
+        })
+    }
+
+    /// Allocate id of new market order.
+    fn next_order_id() -> OrderId {
+        <OrderCount>::mutate(|count| {
+            let order_id = *count;
+            *count += 1;
+            order_id
+        })
+    }
+
+    /// Order that could be matched at current block.
+    fn open_order(order: Option<OrderOf<T>>, order_id: OrderId) -> Result<OrderOf<T>, Error<T>> {
+        let order = match order {
+            Some(order) => order,
+            None if <MatchedOrders<T>>::contains_key(order_id) => Err(Error::<T>::OrderConsumed)?,
+            None => Err(Error::<T>::UnknownOrder)?,
+        };
+        ensure!(
+            order.deadline >= <frame_system::Module<T>>::block_number(),
+            Error::<T>::OrderExpired
+        );
+        Ok(order)
+    }
+
+    /// Check that order could be removed by given account.
+    fn ensure_removable(order: &OrderOf<T>, who: &AccountId<T>) -> Result<(), Error<T>> {
+        let expired = order.deadline < <frame_system::Module<T>>::block_number();
+        ensure!(expired || order.sender == *who, Error::<T>::NotOrderOwner);
+        Ok(())
+    }
+
+    /// Hash of liability parties and parameters.
+    fn agreement_hash(liability: &T::Liability) -> [u8; 32] {
+        (
+            liability.promisee(),
+            liability.promisor(),
+            liability.technics(),
+            liability.economics(),
+        )
+            .using_encoded(sp_io::hashing::blake2_256)
+    }
+
+    /// Allocate index of new liability, it's the only place where `LatestIndex` is advanced.
+    ///
+    /// Release builds rely on `LatestIndex` being monotonic: indices below it are never
+    /// allocated again, so neither live nor pruned index is taken. It's checked by
+    /// `try_state`, the assertions only catch it early in tests.
+    fn next_index() -> LiabilityIndex<T> {
+        <LatestIndex<T>>::mutate(|latest| {
+            let index = *latest;
+            *latest += 1.into();
+            debug_assert!(
+                !<Liabilities<T>>::contains_key(index),
+                "liability index is allocated twice"
+            );
+            debug_assert!(
+                !Self::is_pruned(index),
+                "pruned liability index is allocated"
+            );
+            index
+        })
+    }
+
+    /// Liability index is pruned, see `Tombstones`.
+    pub fn is_pruned(index: LiabilityIndex<T>) -> bool {
+        let ranges = <Tombstones<T>>::get();
+        match ranges.binary_search_by(|(start, _)| start.cmp(&index)) {
+            Ok(_) => true,
+            Err(0) => false,
+            Err(position) => index < ranges[position - 1].1,
+        }
+    }
+
+    /// Prune finalized liability: release its zero value deposit, remove record, history
+    /// and per-index entries, and tombstone the index.
+    ///
+    /// Kept entries: `PromisorLiabilities`, local indices of promisor stay dense up to
+    /// `PromisorCount` and lookups of pruned index find no record; `IdToIndex`, so id of
+    /// pruned liability isn't taken again and calls by it fail with `Pruned`;
+    /// `FinalizationLeafIndex`, finalizations range keeps proofs of pruned liabilities.
+    fn prune(index: LiabilityIndex<T>, record: LiabilityRecordOf<T>) {
+        let liability = &record.agreement;
+        Self::release_zero_value_deposit(index, liability.promisee());
+        <AccountLiabilities<T>>::remove(liability.promisee(), index);
+        <AccountLiabilities<T>>::remove(liability.promisor(), index);
+        for tag in <TagsOf<T>>::take(index) {
+            <ByTag<T>>::mutate_exists(tag, |indices| {
+                if let Some(list) = indices {
+                    list.retain(|tagged| *tagged != index);
+                    if list.is_empty() {
+                        *indices = None;
+                    }
+                }
+            });
+        }
+        <Liabilities<T>>::remove(index);
+        <HistoryOf<T>>::remove(index);
+        <ImportedFrom<T>>::remove(index);
+        <IndexToId<T>>::remove(index);
+        <PayoutOf<T>>::remove(index);
+        <LiabilityRobot<T>>::remove(index);
+        <ReputationSnapshot<T>>::remove(index);
+        <AmendmentsOf<T>>::remove(index);
+        <LiabilityMarket<T>>::remove(index);
+        <FirmwareOf<T>>::remove(index);
+        <ClaimedArrival<T>>::remove(index);
+        Self::tombstone(index);
+        Self::deposit_event(RawEvent::LiabilityPruned(index.into()));
+    }
+
+    /// Record pruned index in `Tombstones`, adjacent ranges are merged, so storage grows
+    /// with count of gaps between pruned indices only.
+    fn tombstone(index: LiabilityIndex<T>) {
+        debug_assert!(
+            index < <LatestIndex<T>>::get(),
+            "unallocated index is pruned"
+        );
+        <Tombstones<T>>::mutate(|ranges| {
+            let end = index + One::one();
+            let position = match ranges.binary_search_by(|(start, _)| start.cmp(&index)) {
+                Ok(_) => return,
+                Err(position) => position,
+            };
+            let previous = position.checked_sub(1).map(|previous| ranges[previous].1);
+            if previous.map_or(false, |previous_end| previous_end > index) {
+                return;
+            }
+            let joins_previous = previous == Some(index);
+            let joins_next = ranges
+                .get(position)
+                .map_or(false, |(start, _)| *start == end);
+            match (joins_previous, joins_next) {
+                (true, true) => {
+                    ranges[position - 1].1 = ranges[position].1;
+                    ranges.remove(position);
+                }
+                (true, false) => ranges[position - 1].1 = end,
+                (false, true) => ranges[position].0 = index,
+                (false, false) => ranges.insert(position, (index, end)),
+            }
+        });
+    }
+
+    /// Store new liability created at given block, returns its index and id.
+    fn store_liability(
+        liability: T::Liability,
+        block_number: BlockNumber<T>,
+    ) -> (LiabilityIndex<T>, H256) {
+        let latest_index = Self::next_index();
+        <CreatedInBlock<T>>::mutate(block_number, |indices| indices.push(latest_index));
+        Self::record_transition(latest_index, block_number, Transition::Created);
+        <Counters<T>>::mutate(|counters| counters.created += 1);
+
+        // Index liability for both parties
+        let promisor = liability.promisor().clone();
+        <AccountLiabilities<T>>::insert(liability.promisee(), latest_index, ());
+        <AccountLiabilities<T>>::insert(&promisor, latest_index, ());
+
+        // Index liability under promisor local index
+        let local_index = <PromisorCount<T>>::get(&promisor);
+        <PromisorCount<T>>::insert(&promisor, local_index + 1.into());
+        <PromisorLiabilities<T>>::insert(&promisor, local_index, latest_index);
+
+        // Taken id is rehashed with unique index, so every node chooses the same one
+        let mut id = liability_id(
+            liability.promisee(),
+            &promisor,
+            liability.technics(),
+            liability.economics(),
+            &local_index,
+        );
+        while <IdToIndex<T>>::contains_key(id) {
+            id = (id, latest_index)
+                .using_encoded(sp_io::hashing::blake2_256)
+                .into();
+        }
+        <IdToIndex<T>>::insert(id, latest_index);
+        <IndexToId<T>>::insert(latest_index, id);
+
+        // Later market updates don't change conditions of liability
+        let market_id = Self::market_of(liability.technics(), liability.economics());
+        let market = Self::market_config(market_id);
+        <LiabilityMarket<T>>::insert(latest_index, (market_id, market));
+
+        let record = LiabilityRecord {
+            report_hash: None,
+            state: RecordState::Open,
+            created_at: block_number,
+            finalized_at: None,
+            agreement: liability,
+        };
+        <Liabilities<T>>::insert(latest_index, record);
+
+        (latest_index, id)
+    }
+
+    /// Check that liability tags fit into `MaxTags` and each of them is given once.
+    fn ensure_tags(tags: &[Tag]) -> Result<(), Error<T>> {
+        ensure!(
+            tags.len() <= T::MaxTags::get() as usize,
+            Error::<T>::TooManyTags
+        );
+        let mut sorted = tags.to_vec();
+        sorted.sort();
+        sorted.dedup();
+        ensure!(sorted.len() == tags.len(), Error::<T>::DuplicateTag);
+        Ok(())
+    }
+
+    /// Check that technical parameter fits into `MaxTechnicsLength`.
+    fn ensure_technics_length(technics: &TechnicalParam<T>) -> Result<(), Error<T>> {
+        ensure!(
+            technics.encoded_size() <= T::MaxTechnicsLength::get() as usize,
+            Error::<T>::TechnicsTooLong
+        );
+        Ok(())
+    }
+
+    /// Check unsigned calls limits of current block, parties are given for `create`.
+    fn check_rate_limits(parties: Option<(&AccountId<T>, &AccountId<T>)>) -> Result<(), Error<T>> {
+        ensure!(
+            <UnsignedInBlock>::get() < T::MaxUnsignedPerBlock::get(),
+            Error::<T>::TooManyUnsignedCalls
+        );
+        if let Some(parties) = parties {
+            let block_number = <frame_system::Module<T>>::block_number();
+            ensure!(
+                <PairCreatesInBlock<T>>::get(block_number, parties) < T::MaxPairPerBlock::get(),
+                Error::<T>::TooManyPairLiabilities
+            );
+        }
+        Ok(())
+    }
+
+    /// Check that promisor could take new liabilities.
+    fn ensure_not_blacklisted(who: &AccountId<T>) -> Result<(), Error<T>> {
+        ensure!(!<Blacklist<T>>::get(who), Error::<T>::Blacklisted);
+        Ok(())
+    }
+
+    fn ensure_approved(promisor: &AccountId<T>) -> Result<(), Error<T>> {
+        ensure!(
+            !T::PromisorWhitelist::get() || <ApprovedPromisors<T>>::contains_key(promisor),
+            Error::<T>::UnapprovedPromisor
+        );
+        ensure!(
+            !<RotatedPromisor<T>>::contains_key(promisor),
+            Error::<T>::PromisorKeyRotated
+        );
+        Ok(())
+    }
+
+    /// Check that registered robot of promisor declares capabilities required by technics.
+    fn ensure_capable(
+        technics: &TechnicalParam<T>,
+        promisor: &AccountId<T>,
+    ) -> Result<(), Error<T>> {
+        Self::ensure_capabilities(technics, || T::Robots::capabilities(promisor))
+    }
+
+    /// Check that requirement to promisor lists at most `MaxAllowedPromisors` promisors.
+    fn ensure_requirement_length(
+        requirement: Option<&PromisorRequirementOf<T>>,
+    ) -> Result<(), Error<T>> {
+        ensure!(
+            requirement.map_or(0, |r| r.allowed_count()) <= T::MaxAllowedPromisors::get() as usize,
+            Error::<T>::TooManyAllowedPromisors
+        );
+        Ok(())
+    }
+
+    /// Check that promisor satisfies requirement signed by promisee, registry entry of
+    /// promisor is read for registry requirements only.
+    fn ensure_allowed_promisor(
+        requirement: Option<&PromisorRequirementOf<T>>,
+        promisor: &AccountId<T>,
+    ) -> Result<(), Error<T>> {
+        let allowed = match requirement {
+            None => true,
+            Some(requirement @ PromisorRequirement::AllowList(_)) => {
+                requirement.is_satisfied(promisor, None, None)
+            }
+            Some(requirement) => requirement.is_satisfied(
+                promisor,
+                T::Robots::owner(promisor).as_ref(),
+                T::Robots::model(promisor).as_ref(),
+            ),
+        };
+        ensure!(allowed, Error::<T>::PromisorNotAllowed);
+        Ok(())
+    }
+
+    /// Check that declared capabilities cover capabilities required by technics, declared
+    /// ones aren't read when nothing is required.
+    fn ensure_capabilities(
+        technics: &TechnicalParam<T>,
+        declared: impl FnOnce() -> Capabilities,
+    ) -> Result<(), Error<T>> {
+        let required = T::Technics::required_capabilities(technics);
+        ensure!(
+            required.is_empty() || declared().covers(&required),
+            Error::<T>::CapabilitiesMismatch
+        );
+        Ok(())
+    }
+
+    /// Value of liability with given economics, it's settled value of runtime
+    /// for non-monetary economics.
+    pub fn liability_value(economics: &EconomicalParam<T>) -> T::Balance {
+        <T::Economics as Valuable<T::Balance>>::value(economics)
+            .unwrap_or_else(|| T::SettledValue::convert(economics.clone()))
+    }
+
+    /// Liability of economics is of zero value, e.g. volunteer work. It isn't limited by
+    /// price, so it's limited by `ZeroValueDeposit` and `MaxZeroValuePerEra`.
+    pub fn is_zero_value(economics: &EconomicalParam<T>) -> bool {
+        Self::liability_value(economics).is_zero()
+    }
+
+    /// Market of liability parameters: market referenced by economics or market
+    /// of technics.
+    pub fn market_of(technics: &TechnicalParam<T>, economics: &EconomicalParam<T>) -> MarketId {
+        T::Economics::market(economics).unwrap_or_else(|| T::Market::convert(technics.clone()))
+    }
+
+    /// Configuration of market, `default_market_config` when it isn't created.
+    pub fn market_config(market_id: MarketId) -> MarketConfigOf<T> {
+        <Markets<T>>::get(market_id).unwrap_or_else(Self::default_market_config)
+    }
+
+    /// Configuration of markets without one: fee exempt and free cancellation, module
+    /// constants for the rest.
+    pub fn default_market_config() -> MarketConfigOf<T> {
+        MarketConfig {
+            fee: Perbill::zero(),
+            order_deposit: T::OrderDeposit::get(),
+            challenge_window: T::ReportChallengeWindow::get(),
+            force_finalize_age: T::MinForceFinalizeAge::get(),
+            cancellation_fee: Default::default(),
+            report_retention: Zero::zero(),
+        }
+    }
+
+    /// Fee of market charged on matching from settled value of liability.
+    pub fn market_fee(market_id: MarketId) -> Perbill {
+        Self::market_config(market_id).fee
+    }
+
+    /// Market of liability and its configuration taken at creation, liabilities created
+    /// before configurations were kept take the current one.
+    pub fn liability_market(
+        index: LiabilityIndex<T>,
+        liability: &T::Liability,
+    ) -> (MarketId, MarketConfigOf<T>) {
+        <LiabilityMarket<T>>::get(index).unwrap_or_else(|| {
+            let market_id = Self::market_of(liability.technics(), liability.economics());
+            (market_id, Self::market_config(market_id))
+        })
+    }
+
+    /// Check that promisee has verified identity when liability value exceeds threshold.
+    fn ensure_identity(
+        economics: &EconomicalParam<T>,
+        promisee: &AccountId<T>,
+    ) -> Result<(), Error<T>> {
+        let value = Self::liability_value(economics);
+        ensure!(
+            value <= T::IdentityThreshold::get() || T::IdentityVerifier::is_verified(promisee),
+            Error::<T>::IdentityRequired
+        );
+        Ok(())
+    }
+
+    /// Remove transient state of the block: unsigned calls limits and agreements of
+    /// duplicate guard, each item by a single write.
+    ///
+    /// It's done at the end of the block, so transaction pool validates calls of the next
+    /// block against empty limits.
+    fn clear_block_state(n: BlockNumber<T>) {
+        <UnsignedInBlock>::kill();
+        <PairCreatesInBlock<T>>::remove_prefix(n);
+        <AgreementsInBlock<T>>::remove_prefix(n);
+    }
+
+    /// Snapshot running totals at the first block of era for the era ended before it, the
+    /// snapshot out of `StatsRetention` is removed. Totals are kept as they change, so
+    /// snapshot doesn't depend on count of accounts or markets.
+    fn snapshot_era(now: BlockNumber<T>) -> Weight {
+        let length = T::StatsEraLength::get();
+        if length.is_zero() || now.is_zero() || !(now % length).is_zero() {
+            return 0;
+        }
+        let era: u32 = (now / length).unique_saturated_into();
+        let ended = era.saturating_sub(1);
+        let stats = EraStats {
+            until: now.saturating_sub(One::one()),
+            counters: <Counters<T>>::get(),
+            outcomes: <Outcomes>::get(),
+            market: <MarketStats<T>>::get(),
+        };
+        <EraSnapshots<T>>::insert(ended, stats);
+        if let Some(pruned) = ended.checked_sub(T::StatsRetention::get().max(1)) {
+            <EraSnapshots<T>>::remove(pruned);
+        }
+        T::DbWeight::get().reads_writes(3, 2)
+    }
+
+    /// Statistics era of current block, see `StatsEraLength`.
+    pub fn stats_era() -> u32 {
+        let now = <frame_system::Module<T>>::block_number();
+        (now / T::StatsEraLength::get().max(One::one())).unique_saturated_into()
+    }
+
+    /// Weight of `on_finalize`: transient state removal and counters read of `EpochStats`.
+    fn on_finalize_weight() -> Weight {
+        T::DbWeight::get().reads_writes(1, 3)
+    }
+
+    /// Deposit filter of parties of liabilities changed in block into block digest, so light
+    /// clients learn of changes by headers, see `AccountBloom`. Blocks without changes
+    /// have no filter.
+    fn deposit_bloom() {
+        let touched = <TouchedInBlock<T>>::take();
+        if touched.is_empty() {
+            return;
+        }
+        let mut bloom = AccountBloom::new(T::DigestBloomBits::get(), T::DigestBloomHashes::get());
+        for index in touched.iter() {
+            if let Some(record) = <Liabilities<T>>::get(index) {
+                bloom.insert(record.agreement.promisee());
+                bloom.insert(record.agreement.promisor());
+            }
+        }
+        <frame_system::Module<T>>::deposit_log(sp_runtime::generic::DigestItem::Other(
+            bloom.to_digest(),
+        ));
+        <frame_system::Module<T>>::register_extra_weight_unchecked(
+            T::DbWeight::get().reads_writes(touched.len() as Weight + 1, 2),
+        );
+    }
+
+    /// Check that transient state of previous blocks is removed, it should hold at the start
+    /// of every block.
+    #[cfg(any(test, feature = "try-runtime"))]
+    pub fn ensure_block_state_cleared() -> Result<(), &'static str> {
+        ensure!(!<UnsignedInBlock>::exists(), "unsigned calls count is left");
+        ensure!(
+            <PairCreatesInBlock<T>>::iter().next().is_none(),
+            "pair creates are left"
+        );
+        ensure!(
+            <AgreementsInBlock<T>>::iter().next().is_none(),
+            "agreements are left"
+        );
+        ensure!(
+            !<TouchedInBlock<T>>::exists(),
+            "touched liabilities are left"
+        );
+        Ok(())
+    }
+
+    /// Count dispatched unsigned call against current block limits.
+    fn note_unsigned_call(parties: Option<(&AccountId<T>, &AccountId<T>)>) {
+        <UnsignedInBlock>::mutate(|count| *count += 1);
+        if let Some(parties) = parties {
+            let block_number = <frame_system::Module<T>>::block_number();
+            <PairCreatesInBlock<T>>::mutate(block_number, parties, |count| *count += 1);
+        }
+    }
+
+    /// Append transition to liability history, the oldest one is dropped when history is full.
+    fn record_transition(
+        index: LiabilityIndex<T>,
+        block_number: BlockNumber<T>,
+        transition: Transition<AccountId<T>>,
+    ) {
+        if T::DigestBloomBits::get() > 0 {
+            <TouchedInBlock<T>>::mutate(|touched| {
+                if !touched.contains(&index) {
+                    touched.push(index)
+                }
+            });
+        }
+        <HistoryOf<T>>::mutate(index, |history| {
+            if history.len() >= MAX_HISTORY_LENGTH as usize {
+                history.remove(0);
+            }
+            history.push((block_number, transition));
+        });
+    }
+
+    /// Record submitter of liability report in its history.
+    fn note_submitter(index: LiabilityIndex<T>, submitter: Submitter<AccountId<T>>) {
+        let block_number = <frame_system::Module<T>>::block_number();
+        Self::record_transition(
+            index,
+            block_number,
+            Transition::Submitted(submitter.clone()),
+        );
+        Self::deposit_event(RawEvent::ReportSubmitted(index.into(), submitter));
+    }
+
+    /// Cancel deferred funding liabilities not funded before deadline from
+    /// `FundingSweepCursor`, their economics wasn't started.
+    fn expire_funding(now: BlockNumber<T>) -> SweepBudget {
+        Self::sweep_list::<_, FundingExpiringAt<T>, FundingSweepCursor<T>>(now, |index, budget| {
+            // Funded liabilities are left in the list
+            budget.access(1, 1);
+            if <PendingFunding<T>>::take(index).is_none() {
+                return;
+            }
+            if let Ok(mut record) = Self::record_of(index) {
+                Self::refund_relay_bounty(index, record.agreement.promisee());
+                Self::release_zero_value_deposit(index, record.agreement.promisee());
+                Self::release_acknowledgement(index, record.agreement.promisor());
+                record.state = RecordState::Cancelled;
+                record.finalized_at = Some(now);
+                Self::schedule_prune(index, &record.agreement, now);
+                <Liabilities<T>>::insert(index, record);
+                <Counters<T>>::mutate(|counters| counters.cancelled += 1);
+                Self::record_transition(index, now, Transition::Cancelled);
+                Self::deposit_event(RawEvent::FundingExpired(index.into()));
+                budget.access(5, 6);
+            }
+        })
+    }
+
+    /// Finalize as failed liabilities with committed report not revealed in time from
+    /// `RevealSweepCursor`, escrow returns to promisee.
+    fn expire_reveals(now: BlockNumber<T>) -> SweepBudget {
+        Self::sweep_list::<_, RevealExpiringAt<T>, RevealSweepCursor<T>>(now, |index, budget| {
+            // Revealed reports are left in the list
+            budget.access(1, 1);
+            if <ReportCommitments<T>>::take(index).is_none() {
+                return;
+            }
+            budget.charge(T::WeightInfo::force_finalize());
+            let forced =
+                Self::record_of(index).map(|record| Self::force_record(index, record, false));
+            if let Ok(Ok(())) = forced {
+                Self::deposit_event(RawEvent::RevealExpired(index.into()));
+            }
+        })
+    }
+
+    /// Remove match commitments not revealed in their window from `MatchCommitSweepCursor`.
+    fn expire_match_commits(now: BlockNumber<T>) -> SweepBudget {
+        let window = T::MatchCommitWindow::get();
+        Self::sweep_list::<_, MatchCommitsExpiringAt<T>, MatchCommitSweepCursor<T>>(
+            now,
+            |(matcher, commitment), budget| {
+                // Revealed commitments are left in the list, they could be committed again
+                budget.access(1, 0);
+                let expired = <MatchCommitments<T>>::get(&matcher, commitment)
+                    .map_or(false, |committed_at| {
+                        committed_at.saturating_add(window) < now
+                    });
+                if expired {
+                    Self::remove_match_commit(&matcher, commitment);
+                    budget.access(1, 2);
+                }
+            },
+        )
+    }
+
+    /// Remove match commitment of matcher, it isn't counted by `MaxMatchCommits` anymore.
+    fn remove_match_commit(matcher: &AccountId<T>, commitment: H256) {
+        <MatchCommitments<T>>::remove(matcher, commitment);
+        <MatchCommitCount<T>>::mutate_exists(matcher, |count| {
+            *count = count
+                .and_then(|count| count.checked_sub(1))
+                .filter(|count| *count > 0)
+        });
+    }
+
+    /// Remove report preimage and return its deposit to depositor.
+    fn remove_preimage(report_hash: H256, preimage: PreimageOf<T>) {
+        <ReportPreimage<T>>::remove(report_hash);
+        if let Some((depositor, deposit)) = preimage.depositor {
+            NamedReserve::<T>::unreserve_named(&RESERVE_ID, &depositor, deposit);
+        }
+        Self::deposit_event(RawEvent::ReportPreimagePruned(report_hash));
+    }
+
+    /// Prune expired report preimages and then finalized liabilities out of retention while
+    /// `remaining_weight` allows, see `prune_preimages` and `prune_liabilities`.
+    ///
+    /// Returns consumed weight.
+    pub fn on_idle(now: BlockNumber<T>, remaining_weight: Weight) -> Weight {
+        let consumed = Self::prune_preimages(now, remaining_weight);
+        consumed.saturating_add(Self::prune_liabilities(
+            now,
+            remaining_weight.saturating_sub(consumed),
+        ))
+    }
+
+    /// Prune report preimages expired before given block while `remaining_weight` allows,
+    /// the same way as `prune_report_preimage`. Blocks of `PreimagesExpiringAt` are visited
+    /// from `PreimagesPrunedUntil`, block out of weight is continued later.
+    ///
+    /// Returns consumed weight, it's zero when remaining weight doesn't fit a single item.
+    fn prune_preimages(now: BlockNumber<T>, remaining_weight: Weight) -> Weight {
+        let db_weight = T::DbWeight::get();
+        let block_weight = db_weight.reads_writes(1, 1);
+        let item_weight = T::WeightInfo::prune_report_preimage();
+        let mut consumed = db_weight.reads_writes(1, 1);
+        if consumed
+            .saturating_add(block_weight)
+            .saturating_add(item_weight)
+            > remaining_weight
+        {
+            return 0;
+        }
+
+        let mut until = <PreimagesPrunedUntil<T>>::get();
+        while until < now {
+            if consumed.saturating_add(block_weight) > remaining_weight {
+                break;
+            }
+            consumed = consumed.saturating_add(block_weight);
+            let mut hashes = <PreimagesExpiringAt<T>>::take(until);
+            while let Some(hash) = hashes.last().cloned() {
+                if consumed.saturating_add(item_weight) > remaining_weight {
+                    break;
+                }
+                consumed = consumed.saturating_add(item_weight);
+                hashes.pop();
+                // Preimage could be pruned manually or its expiry extended
+                if let Some(preimage) = <ReportPreimage<T>>::get(hash) {
+                    if now > preimage.expires_at {
+                        Self::remove_preimage(hash, preimage);
+                    }
+                }
+            }
+            if !hashes.is_empty() {
+                <PreimagesExpiringAt<T>>::insert(until, hashes);
+                break;
+            }
+            until += One::one();
+        }
+        <PreimagesPrunedUntil<T>>::put(until);
+        consumed
+    }
+
+    /// Prune liabilities retained until blocks before given one while `remaining_weight`
+    /// allows, the same way as `prune_liability`. Blocks of `PrunableAt` are visited from
+    /// `LiabilitiesPrunedUntil`, block out of weight is continued later. Liability waiting
+    /// for witnessed settlement is pruned after it's settled.
+    ///
+    /// Returns consumed weight, it's zero when remaining weight doesn't fit a single item.
+    fn prune_liabilities(now: BlockNumber<T>, remaining_weight: Weight) -> Weight {
+        let db_weight = T::DbWeight::get();
+        let block_weight = db_weight.reads_writes(1, 1);
+        let item_weight = T::WeightInfo::prune_liability().saturating_add(db_weight.writes(1));
+        let mut consumed = db_weight.reads_writes(1, 1);
+        if consumed
+            .saturating_add(block_weight)
+            .saturating_add(item_weight)
+            > remaining_weight
+        {
+            return 0;
+        }
+
+        let mut until = <LiabilitiesPrunedUntil<T>>::get();
+        while until < now {
+            if consumed.saturating_add(block_weight) > remaining_weight {
+                break;
+            }
+            consumed = consumed.saturating_add(block_weight);
+            let mut indices = <PrunableAt<T>>::take(until);
+            while let Some(index) = indices.last().cloned() {
+                if consumed.saturating_add(item_weight) > remaining_weight {
+                    break;
+                }
+                consumed = consumed.saturating_add(item_weight);
+                indices.pop();
+                // Liability could be pruned manually
+                let record = match <Liabilities<T>>::get(index) {
+                    Some(record) if record.finalized_at.is_some() => record,
+                    _ => continue,
+                };
+                match <PendingSettlement<T>>::get(index) {
+                    Some(deadline) => {
+                        let retained_until = deadline.max(now);
+                        <PrunableAt<T>>::mutate(retained_until, |indices| indices.push(index));
+                    }
+                    None => Self::prune(index, record),
+                }
+            }
+            if !indices.is_empty() {
+                <PrunableAt<T>>::insert(until, indices);
+                break;
+            }
+            until += One::one();
+        }
+        <LiabilitiesPrunedUntil<T>>::put(until);
+        consumed
+    }
+
+    /// Schedule pruning of liability finalized at given block after report retention of
+    /// its market, liabilities of markets without retention aren't pruned automatically.
+    fn schedule_prune(
+        index: LiabilityIndex<T>,
+        liability: &T::Liability,
+        finalized_at: BlockNumber<T>,
+    ) {
+        let (_, market) = Self::liability_market(index, liability);
+        if !market.report_retention.is_zero() {
+            let retained_until = finalized_at.saturating_add(market.report_retention);
+            <PrunableAt<T>>::mutate(retained_until, |indices| indices.push(index));
+        }
+    }
+
+    /// Drop `CreatedInBlock` entries out of retention window, each block takes an item
+    /// of budget.
+    fn prune_created_index(now: BlockNumber<T>) -> SweepBudget {
+        let mut budget = SweepBudget::new::<T>();
+        let retention = T::CreatedRetention::get();
+        if retention.is_zero() {
+            return budget;
+        }
+
+        let limit = now.saturating_sub(retention);
+        let mut until = <CreatedPrunedUntil<T>>::get();
+        budget.access(1, 0);
+        let mut pruned: Weight = 0;
+        while until < limit && budget.take() {
+            <CreatedInBlock<T>>::remove(until);
+            until += One::one();
+            pruned += 1;
+        }
+        if pruned > 0 {
+            <CreatedPrunedUntil<T>>::put(until);
+            budget.access(0, pruned + 1);
+        }
+        budget
+    }
+
+    /// Decoded liability for given global index.
+    pub fn liability(index: LiabilityIndex<T>) -> Option<T::Liability> {
+        <Liabilities<T>>::get(index).map(|record| record.agreement)
+    }
+
+    /// Liability report is published or liability is finalized by root.
+    pub fn is_finalized(index: LiabilityIndex<T>) -> bool {
+        <Liabilities<T>>::get(index).map_or(false, |record| record.state != RecordState::Open)
+    }
+
+    /// Hash of liability technical report.
+    pub fn report_hash(index: LiabilityIndex<T>) -> Option<H256> {
+        <Liabilities<T>>::get(index).and_then(|record| record.report_hash)
+    }
+
+    /// Liability technical report, `None` when its preimage is pruned.
+    pub fn report_of(index: LiabilityIndex<T>) -> Option<TechnicalReport<T>> {
+        let hash = Self::report_hash(index)?;
+        <ReportPreimage<T>>::get(hash).map(|preimage| preimage.report)
+    }
+
+    /// Technics of liability chained to parent, derived from its accepted report.
+    fn chained_technics(parent: LiabilityIndex<T>) -> Result<TechnicalParam<T>, Error<T>> {
+        let record = Self::record_of(parent)?;
+        // Witnessed report is accepted when its challenge window is over
+        ensure!(
+            record.state == RecordState::Reported && !<PendingSettlement<T>>::contains_key(parent),
+            Error::<T>::UnchainableReport
+        );
+        Self::report_of(parent)
+            .and_then(|report| T::Technics::derive_next(&report))
+            .ok_or(Error::<T>::UnchainableReport)
+    }
+
+    /// Economical outcome of liability finalized by root, it has no report.
+    pub fn forced_outcome(index: LiabilityIndex<T>) -> Option<bool> {
+        match <Liabilities<T>>::get(index)?.state {
+            RecordState::Forced(success) => Some(success),
+            _ => None,
+        }
+    }
+
+    /// Block number when liability created.
+    ///
+    /// Zero means that liability was created at genesis or before this value was tracked.
+    pub fn created_at(index: LiabilityIndex<T>) -> BlockNumber<T> {
+        <Liabilities<T>>::get(index).map_or_else(Zero::zero, |record| record.created_at)
+    }
+
+    /// The last block liability could stay open in, `None` when expiry is disabled.
+    fn lifetime_end(record: &LiabilityRecordOf<T>) -> Option<BlockNumber<T>> {
+        let lifetime = T::MaxLiabilityLifetime::get();
+        if lifetime.is_zero() {
+            None
+        } else {
+            Some(record.created_at.saturating_add(lifetime))
+        }
+    }
+
+    /// Leaf of finalized liability and its inclusion proof against current root of
+    /// finalizations range.
+    pub fn finalization_proof(
+        index: LiabilityIndex<T>,
+    ) -> Option<(FinalizationLeafOf<T>, MmrProof)> {
+        let leaf_index = <FinalizationLeafIndex<T>>::get(index)?;
+        let leaf = <FinalizationLeaves<T>>::get(leaf_index)?;
+        let leaf_count = <FinalizationLeafCount>::get();
+        let node = |position| <FinalizationNodes>::get(position);
+        let proof = MmrProof {
+            leaf_index,
+            leaf_count,
+            items: mmr::proof_positions(leaf_index, leaf_count)?
+                .into_iter()
+                .map(node)
+                .collect(),
+            peaks: mmr::peaks(leaf_count)
+                .into_iter()
+                .map(|(position, _)| node(position))
+                .collect(),
+        };
+        Some((leaf, proof))
+    }
+
+    /// Deadlines of liability, the same that its sweeps and calls are checked against.
+    pub fn timeline(index: LiabilityIndex<T>) -> Option<LiabilityTimeline<BlockNumber<T>>> {
+        let record = <Liabilities<T>>::get(index)?;
+        let reveal_deadline = <ReportCommitments<T>>::get(index).map(|(_, deadline)| deadline);
+        let challenge_end = <PendingSettlement<T>>::get(index);
+        let expirable = record.state == RecordState::Open
+            && reveal_deadline.is_none()
+            && challenge_end.is_none();
+        Some(LiabilityTimeline {
+            created_at: record.created_at,
+            funding_deadline: <PendingFunding<T>>::get(index),
+            acknowledgement_deadline: <PendingAcknowledgement<T>>::get(index)
+                .map(|(deadline, _)| deadline),
+            reveal_deadline,
+            challenge_end,
+            lifetime_end: if expirable {
+                Self::lifetime_end(&record)
+            } else {
+                None
+            },
+        })
+    }
+
+    /// Funds moved when open liability is finalized with given outcome, computed by the same
+    /// functions that move them: transfers of economics, payout account and report hooks,
+    /// e.g. operator share, then released relay bounty and acknowledgement deposit.
+    /// Transfers skipped on finalization when funds are short are listed anyway. `None` when
+    /// liability isn't open or isn't funded yet.
+    pub fn preview(
+        index: LiabilityIndex<T>,
+        success: bool,
+    ) -> Option<Payouts<AccountId<T>, BalanceOf<T>>> {
+        let record = <Liabilities<T>>::get(index)?;
+        if record.state != RecordState::Open || <PendingFunding<T>>::contains_key(index) {
+            return None;
+        }
+        let liability = &record.agreement;
+        let (promisee, promisor) = (liability.promisee(), liability.promisor());
+        let mut payouts = liability.preview::<BalanceOf<T>>(success);
+        if success {
+            payouts
+                .transfers
+                .extend(Self::payout_transfer(index, liability));
+        }
+        let hooks_transfers =
+            T::Hooks::preview_report_accepted(&index, promisee, promisor, success);
+        payouts.transfers.extend(
+            hooks_transfers
+                .into_iter()
+                .map(|(from, to, value)| (from, to, BalanceOf::<T>::unique_saturated_from(value))),
+        );
+        if let Some(bounty) = <RelayBounty<T>>::get(index) {
+            payouts.refunds.push((promisee.clone(), bounty));
+        }
+        if let Some((_, deposit)) = <PendingAcknowledgement<T>>::get(index) {
+            payouts.refunds.push((promisor.clone(), deposit));
+        }
+        Some(payouts)
+    }
+
+    /// Update execution statistics of model of liability technics and its places at
+    /// leaderboards.
+    fn note_model(technics: &TechnicalParam<T>, update: impl FnOnce(&mut ModelStats<T::Balance>)) {
+        let model = match T::Technics::model(technics) {
+            Some(model) => model,
+            None => return,
+        };
+        let stats = <ModelStatistics<T>>::mutate(model, |stats| {
+            update(stats);
+            stats.clone()
+        });
+        for by in ModelRanking::ALL.iter() {
+            let score = Self::model_score(&stats, *by);
+            <ModelLeaderboard>::mutate(by, |board| Self::rank_model(board, model, score));
+        }
+        let boards = ModelRanking::ALL.len() as Weight;
+        let weight = T::DbWeight::get().reads_writes(1 + boards, 1 + boards);
+        <frame_system::Module<T>>::register_extra_weight_unchecked(weight);
+    }
+
+    /// Score of model statistics by given ranking.
+    fn model_score(stats: &ModelStats<T::Balance>, by: ModelRanking) -> u128 {
+        let outcomes = &stats.outcomes;
+        match by {
+            ModelRanking::Executions => stats.executions.into(),
+            ModelRanking::Completed => outcomes.completed.into(),
+            ModelRanking::SuccessRate => {
+                let finalized = outcomes
+                    .completed
+                    .saturating_add(outcomes.failed)
+                    .saturating_add(outcomes.disputed);
+                if finalized == 0 {
+                    0
+                } else {
+                    u128::from(outcomes.completed) * 1_000_000_000 / u128::from(finalized)
+                }
+            }
+            ModelRanking::Settled => stats.settled.unique_saturated_into(),
+        }
+    }
+
+    /// Place model with given score at leaderboard sorted by score, the model updated later
+    /// is placed after others with equal score. Models with zero score aren't placed, the
+    /// lowest one is evicted from full leaderboard.
+    ///
+    /// Success rate of model out of leaderboard is placed again on its next update only,
+    /// so that leaderboard could miss models overtaken by falling ones.
+    fn rank_model(board: &mut Vec<(u128, H256)>, model: H256, score: u128) {
+        if let Some(position) = board.iter().position(|(_, placed)| *placed == model) {
+            board.remove(position);
+        }
+        if score == 0 {
+            return;
+        }
+        let position = board
+            .binary_search_by(|(placed, _)| {
+                if *placed >= score {
+                    sp_std::cmp::Ordering::Less
+                } else {
+                    sp_std::cmp::Ordering::Greater
+                }
+            })
+            .unwrap_or_else(|position| position);
+        if position < MAX_LEADERBOARD_LENGTH as usize {
+            board.insert(position, (score, model));
+            board.truncate(MAX_LEADERBOARD_LENGTH as usize);
+        }
+    }
+
+    /// At most `count` the best technics models by given ranking with their statistics.
+    pub fn top_models(by: ModelRanking, count: u32) -> Vec<(H256, ModelStats<T::Balance>)> {
+        <ModelLeaderboard>::get(by)
+            .into_iter()
+            .take(count as usize)
+            .map(|(_, model)| (model, <ModelStatistics<T>>::get(model)))
+            .collect()
+    }
+
+    /// Block number when liability finalized, zero sentinel as for `created_at`.
+    pub fn finalized_at(index: LiabilityIndex<T>) -> BlockNumber<T> {
+        <Liabilities<T>>::get(index)
+            .and_then(|record| record.finalized_at)
+            .unwrap_or_else(Zero::zero)
+    }
+
+    /// Count outcome of finalized liability in statistics of its parties, liability failed
+    /// when it's force finalized is disputed.
+    fn note_stats(liability: &T::Liability, success: bool, forced: bool) {
+        let settled = if success {
+            T::SettledValue::convert(liability.economics().clone())
+        } else {
+            Zero::zero()
+        };
+        let count = |counts: &mut OutcomeCounts| {
+            let counter = match (success, forced) {
+                (true, _) => &mut counts.completed,
+                (false, true) => &mut counts.disputed,
+                (false, false) => &mut counts.failed,
+            };
+            *counter = counter.saturating_add(1);
+        };
+
+        <Outcomes>::mutate(count);
+        Self::note_model(liability.technics(), |stats| {
+            count(&mut stats.outcomes);
+            stats.settled = stats.settled.saturating_add(settled);
+        });
+        <StatsOf<T>>::mutate(liability.promisor(), |stats| {
+            count(&mut stats.as_promisor);
+            stats.settled = stats.settled.saturating_add(settled);
+        });
+        <StatsOf<T>>::mutate(liability.promisee(), |stats| {
+            count(&mut stats.as_promisee);
+            // Value settled between account and itself is counted once
+            if liability.promisee() != liability.promisor() {
+                stats.settled = stats.settled.saturating_add(settled);
+            }
+        });
+    }
+
+    /// Decoded liability view for given index, `None` when it isn't exist.
+    pub fn liability_info(index: LiabilityIndex<T>) -> Option<LiabilityInfoOf<T>> {
+        let record = <Liabilities<T>>::get(index)?;
+        let liability = &record.agreement;
+        let (state, finalized_at) = match record.state {
+            RecordState::Open => (LiabilityState::Open, None),
+            RecordState::Imported(_) => (LiabilityState::Imported, record.finalized_at),
+            _ => (
+                LiabilityState::Finalized,
+                Some(record.finalized_at.unwrap_or_else(Zero::zero)),
+            ),
+        };
+        let economics = liability.economics();
+        let economics_summary = EconomicsSummary {
+            value: <T::Economics as Valuable<T::Balance>>::value(economics),
+            settled: T::SettledValue::convert(economics.clone()),
+            market: Self::liability_market(index, liability).0,
+            deferred_funding: T::Economics::deferred_funding(economics),
+        };
+        Some(LiabilityInfo {
+            technics: liability.technics().clone(),
+            technics_hash: technics_hash(liability.technics()),
+            economics: economics.clone(),
+            economics_summary,
+            promisee: liability.promisee().clone(),
+            promisor: liability.promisor().clone(),
+            state,
+            created_at: record.created_at,
+            finalized_at,
+        })
+    }
+
+    /// Decoded report view for given liability index, `None` when it isn't finalized
+    /// or its report preimage is pruned.
+    pub fn report_info(index: LiabilityIndex<T>) -> Option<ReportInfoOf<T>> {
+        let record = <Liabilities<T>>::get(index)?;
+        let preimage = <ReportPreimage<T>>::get(record.report_hash?)?;
+        Some(ReportInfo {
+            report: preimage.report,
+            finalized_at: record.finalized_at.unwrap_or_else(Zero::zero),
+        })
+    }
+
+    /// Technics or report of liability committed to by `hash` in its events, `None` when
+    /// hash doesn't match or report preimage is pruned.
+    pub fn event_payload(
+        index: LiabilityIndex<T>,
+        hash: H256,
+    ) -> Option<EventPayload<TechnicalParam<T>, TechnicalReport<T>>> {
+        let record = <Liabilities<T>>::get(index)?;
+        let technics = record.agreement.technics();
+        if technics_hash(technics) == hash {
+            return Some(EventPayload::Technics(technics.clone()));
+        }
+        if record.report_hash != Some(hash) {
+            return None;
+        }
+        <ReportPreimage<T>>::get(hash).map(|preimage| EventPayload::Report(preimage.report))
+    }
+
+    /// Page of items at ascending positions not less than `start`, at most `count` items
+    /// of at most `MAX_PAGE_SCAN` positions. Positions without item, e.g. of pruned
+    /// liabilities, are skipped.
+    fn page<Item>(
+        start: LiabilityIndex<T>,
+        positions: impl Iterator<Item = LiabilityIndex<T>>,
+        count: u32,
+        item: impl Fn(LiabilityIndex<T>) -> Option<Item>,
+    ) -> Page<LiabilityIndex<T>, Item> {
+        let count = count.min(MAX_PAGE_ITEMS) as usize;
+        let mut positions = positions.filter(|position| *position >= start).peekable();
+        let mut items = Vec::new();
+        let mut next = start;
+        for _ in 0..MAX_PAGE_SCAN {
+            if items.len() >= count {
+                break;
+            }
+            let position = match positions.next() {
+                Some(position) => position,
+                None => break,
+            };
+            items.extend(item(position));
+            next = position + 1.into();
+        }
+        Page {
+            items,
+            next,
+            has_more: positions.peek().is_some(),
+        }
+    }
+
+    /// Positions from `start` until `end`.
+    fn positions(
+        start: LiabilityIndex<T>,
+        end: LiabilityIndex<T>,
+    ) -> impl Iterator<Item = LiabilityIndex<T>> {
+        sp_std::iter::successors(Some(start), |position| Some(*position + 1.into()))
+            .take_while(move |position| *position < end)
+    }
+
+    /// Liability with its index, `None` when it's pruned.
+    fn indexed_info(index: LiabilityIndex<T>) -> Option<(LiabilityIndex<T>, LiabilityInfoOf<T>)> {
+        Self::liability_info(index).map(|info| (index, info))
+    }
+
+    /// List page of at most `count` liabilities starting from `start` index.
+    pub fn liability_list(start: LiabilityIndex<T>, count: u32) -> LiabilityPageOf<T> {
+        let positions = Self::positions(start, <LatestIndex<T>>::get());
+        Self::page(start, positions, count, Self::indexed_info)
+    }
+
+    /// List page of at most `count` liabilities of given account with index not less than
+    /// `start`. Indices of account are read at once, records only of the page.
+    pub fn account_liability_list(
+        account: &AccountId<T>,
+        start: LiabilityIndex<T>,
+        count: u32,
+    ) -> LiabilityPageOf<T> {
+        let mut indexes: Vec<_> = <AccountLiabilities<T>>::iter_prefix(account)
+            .map(|(index, _)| index)
+            .filter(|index| *index >= start)
+            .collect();
+        indexes.sort();
+        Self::page(start, indexes.into_iter(), count, Self::indexed_info)
+    }
+
+    /// List page of at most `count` liabilities of given tag with index not less than `start`,
+    /// only the latest `MaxTaggedLiabilities` ones are kept.
+    pub fn tag_liability_list(
+        tag: Tag,
+        start: LiabilityIndex<T>,
+        count: u32,
+    ) -> LiabilityPageOf<T> {
+        Self::page(
+            start,
+            <ByTag<T>>::get(tag).into_iter(),
+            count,
+            Self::indexed_info,
+        )
+    }
+
+    /// List page of at most `count` liabilities of given promisor with local index not less
+    /// than `start`, page cursor is local index too.
+    pub fn promisor_liability_list(
+        promisor: &AccountId<T>,
+        start: LiabilityIndex<T>,
+        count: u32,
+    ) -> LiabilityPageOf<T> {
+        let positions = Self::positions(start, <PromisorCount<T>>::get(promisor));
+        Self::page(start, positions, count, |local_index| {
+            <PromisorLiabilities<T>>::get(promisor, local_index).and_then(Self::indexed_info)
+        })
+    }
+
+    /// Liabilities created in blocks from `from` to `to` inclusive, page is finished on
+    /// block boundary when it has at least `count` (at most `MAX_PAGE_ITEMS`) indices or
+    /// `MAX_PAGE_SCAN` blocks.
+    pub fn created_between(
+        from: BlockNumber<T>,
+        to: BlockNumber<T>,
+        count: u32,
+    ) -> CreatedBetween<LiabilityIndex<T>, BlockNumber<T>> {
+        let pruned_until = <CreatedPrunedUntil<T>>::get();
+        let truncated = from < pruned_until;
+        let mut block = from.max(pruned_until);
+        let count = count.min(MAX_PAGE_ITEMS) as usize;
+        let mut indices = Vec::new();
+        let mut next = None;
+        let mut scanned = 0;
+        while block <= to {
+            if indices.len() >= count || scanned >= MAX_PAGE_SCAN {
+                next = Some(block);
+                break;
+            }
+            indices.extend(<CreatedInBlock<T>>::get(block));
+            scanned += 1;
+            if block == to {
+                break;
+            }
+            block += One::one();
+        }
+        CreatedBetween {
+            indices,
+            truncated,
+            next,
+        }
+    }
+
+    /// Estimate cost of liability with given economics.
+    /// Best demand of market, it has the highest price.
+    pub fn best_ask(market_id: MarketId) -> Option<OrderBookEntry<T::Balance>> {
+        <AsksByModel<T>>::get(market_id)
+            .first()
+            .map(|&(price, order_id)| OrderBookEntry { order_id, price })
+    }
+
+    /// Best offer of market, it has the lowest price.
+    pub fn best_bid(market_id: MarketId) -> Option<OrderBookEntry<T::Balance>> {
+        <BidsByModel<T>>::get(market_id)
+            .first()
+            .map(|&(price, order_id)| OrderBookEntry { order_id, price })
+    }
+
+    /// At most `levels` (and `MAX_PAGE_ITEMS`) best price levels of market demands and offers.
+    pub fn depth(market_id: MarketId, levels: u32) -> OrderBookDepth<T::Balance> {
+        let levels = levels.min(MAX_PAGE_ITEMS);
+        OrderBookDepth {
+            asks: Self::price_levels(<AsksByModel<T>>::get(market_id), levels),
+            bids: Self::price_levels(<BidsByModel<T>>::get(market_id), levels),
+        }
+    }
+
+    fn price_levels(book: Vec<(T::Balance, OrderId)>, levels: u32) -> Vec<PriceLevel<T::Balance>> {
+        let mut aggregated: Vec<PriceLevel<T::Balance>> = Vec::new();
+        for (price, _) in book {
+            match aggregated.last_mut() {
+                Some(level) if level.price == price => level.orders += 1,
+                _ if aggregated.len() >= levels as usize => break,
+                _ => aggregated.push(PriceLevel { price, orders: 1 }),
+            }
+        }
+        aggregated
+    }
+
+    /// At most `count` (and `MAX_PAGE_ITEMS`) recent matches of market, the latest first.
+    pub fn recent_matches(market_id: MarketId, count: u32) -> Vec<MatchInfoOf<T>> {
+        <RecentMatches<T>>::get(market_id)
+            .into_iter()
+            .rev()
+            .take(count.min(MAX_PAGE_ITEMS) as usize)
+            .collect()
+    }
+
+    /// Matches of market in the last `blocks` blocks including the current one,
+    /// counted from `RecentMatches`.
+    pub fn volume(
+        market_id: MarketId,
+        blocks: BlockNumber<T>,
+    ) -> MarketVolume<BlockNumber<T>, T::Balance> {
+        let now = <frame_system::Module<T>>::block_number();
+        let from = now.saturating_add(One::one()).saturating_sub(blocks);
+        let mut volume = MarketVolume {
+            from,
+            truncated: <EvictedMatchAt<T>>::get(market_id).map_or(false, |block| block >= from),
+            ..Default::default()
+        };
+        for info in <RecentMatches<T>>::get(market_id) {
+            if info.block_number >= from {
+                volume.matches += 1;
+                volume.quantity += info.quantity as u64;
+                volume.value = volume.value.saturating_add(info.value);
+            }
+        }
+        volume
+    }
+
+    /// Lighthouse of market scheduled at given block, `None` when market has no lighthouses.
+    pub fn scheduled_lighthouse(market_id: MarketId, now: BlockNumber<T>) -> Option<AccountId<T>> {
+        let lighthouses = <Lighthouses<T>>::get(market_id);
+        if lighthouses.is_empty() {
+            return None;
+        }
+        let slot = T::LighthouseSlot::get().max(One::one());
+        let turn: u64 = (now / slot).unique_saturated_into();
+        lighthouses
+            .get((turn % lighthouses.len() as u64) as usize)
+            .cloned()
+    }
+
+    pub fn estimate_cost(economics: EconomicalParam<T>) -> CostInfo<EconomicalParam<T>> {
+        CostInfo {
+            create_weight: T::WeightInfo::create(0),
+            finalize_weight: T::WeightInfo::finalize(0),
+            escrow: economics,
+        }
+    }
+
+    /// Dry run of unsigned `create` call: liability is checked and started as by the call,
+    /// then storage changes are rolled back.
+    pub fn dry_run_create(
+        technics: TechnicalParam<T>,
+        economics: EconomicalParam<T>,
+        promisee: AccountId<T>,
+        promisor: AccountId<T>,
+        promisee_proof: ProofParam<T>,
+        promisor_proof: ProofParam<T>,
+    ) -> Result<DryRun<LiabilityIndex<T>>, DispatchError> {
+        let call = Call::<T>::create(
+            technics.clone(),
+            economics.clone(),
+            promisee.clone(),
+            promisor.clone(),
+            promisee_proof.clone(),
+            promisor_proof.clone(),
+        );
+        let weight = call.get_dispatch_info().weight;
+        with_transaction(|| {
+            let liability = T::Liability::new(technics, economics, promisee, promisor);
+            TransactionOutcome::Rollback(Self::create_agreement(
+                liability,
+                None,
+                Vec::new(),
+                None,
+                None,
+                promisee_proof,
+                promisor_proof,
+            ))
+        })
+        .map(|index| DryRun { index, weight })
+    }
+
+    /// Dry run of unsigned `finalize` call: report is checked and liability is settled as by
+    /// the call, then storage changes are rolled back.
+    pub fn dry_run_finalize(
+        index: LiabilityIndex<T>,
+        report: TechnicalReport<T>,
+        proof: ProofParam<T>,
+    ) -> Result<DryRun<LiabilityIndex<T>>, DispatchError> {
+        let call = Call::<T>::finalize(index, report.clone(), proof.clone());
+        let weight = call.get_dispatch_info().weight;
+        with_transaction(|| {
+            TransactionOutcome::Rollback(Self::accept_report(index, report, None, proof))
+        })
+        .map(|info| DryRun {
+            index,
+            weight: info.actual_weight.unwrap_or(weight),
+        })
+        .map_err(|e| e.error)
+    }
+
+    /// Configuration of module, funds of currency are given as settled value balance.
+    pub fn constants() -> LiabilityConstants<BlockNumber<T>, T::Balance> {
+        let funds = |funds: BalanceOf<T>| {
+            T::Balance::unique_saturated_from(UniqueSaturatedInto::<u128>::unique_saturated_into(
+                funds,
+            ))
+        };
+        LiabilityConstants {
+            max_unsigned_per_block: T::MaxUnsignedPerBlock::get(),
+            max_pair_per_block: T::MaxPairPerBlock::get(),
+            max_technics_length: T::MaxTechnicsLength::get(),
+            max_report_length: T::MaxReportLength::get(),
+            max_tags: T::MaxTags::get(),
+            max_tagged_liabilities: T::MaxTaggedLiabilities::get(),
+            promisor_whitelist: T::PromisorWhitelist::get(),
+            identity_threshold: T::IdentityThreshold::get(),
+            min_force_finalize_age: T::MinForceFinalizeAge::get(),
+            order_deposit: funds(T::OrderDeposit::get()),
+            max_open_orders_per_account: T::MaxOpenOrdersPerAccount::get(),
+            max_orders_per_market: T::MaxOrdersPerMarket::get(),
+            min_fill_quantity: T::MinFillQuantity::get(),
+            expired_order_slash: T::ExpiredOrderSlash::get(),
+            report_preimage_deposit: funds(T::ReportPreimageDeposit::get()),
+            report_challenge_window: T::ReportChallengeWindow::get(),
+            max_recent_matches: T::MaxRecentMatches::get(),
+            lighthouse_min_stake: funds(T::LighthouseMinStake::get()),
+            max_lighthouses: T::MaxLighthouses::get(),
+            lighthouse_slot: T::LighthouseSlot::get(),
+            lighthouse_fee_share: T::LighthouseFeeShare::get(),
+            match_commit_window: T::MatchCommitWindow::get(),
+            max_match_commits: T::MaxMatchCommits::get(),
+            breach_claim_period: T::BreachClaimPeriod::get(),
+            order_breach_slash: funds(T::OrderBreachSlash::get()),
+            funding_deadline: T::FundingDeadline::get(),
+            reveal_window: T::RevealWindow::get(),
+            claim_tolerance: T::ClaimTolerance::get(),
+            witness_min_stake: funds(T::WitnessMinStake::get()),
+            max_witnesses: T::MaxWitnesses::get(),
+            witness_slash: funds(T::WitnessSlash::get()),
+            stats_era_length: T::StatsEraLength::get(),
+            stats_retention: T::StatsRetention::get(),
+            pool_min_stake: funds(T::PoolMinStake::get()),
+            max_pool_size: T::MaxPoolSize::get(),
+            assignment_delay: T::AssignmentDelay::get(),
+            acknowledgement_window: T::AcknowledgementWindow::get(),
+            acknowledgement_deposit: funds(T::AcknowledgementDeposit::get()),
+            max_liability_lifetime: T::MaxLiabilityLifetime::get(),
+            expiry_incentive: funds(T::ExpiryIncentive::get()),
+            digest_bloom_bits: T::DigestBloomBits::get(),
+            digest_bloom_hashes: T::DigestBloomHashes::get(),
+            min_report_delay: T::MinReportDelay::get(),
+            zero_value_deposit: funds(T::ZeroValueDeposit::get()),
+            max_zero_value_per_era: T::MaxZeroValuePerEra::get(),
+        }
+    }
+}
+
+impl<T: Trait>
+    LiabilityIssuer<TechnicalParam<T>, EconomicalParam<T>, AccountId<T>, LiabilityIndex<T>>
+    for Module<T>
+{
+    /// Start liability of trusted caller, checks of `create` are run except parties proofs
+    /// and unsigned calls limits.
+    fn issue(
+        technics: TechnicalParam<T>,
+        economics: EconomicalParam<T>,
+        promisee: AccountId<T>,
+        promisor: AccountId<T>,
+    ) -> Result<LiabilityIndex<T>, DispatchError> {
+        Self::ensure_technics_length(&technics)?;
+        Self::ensure_not_blacklisted(&promisee)?;
+        Self::ensure_not_blacklisted(&promisor)?;
+        Self::ensure_approved(&promisor)?;
+        Self::ensure_capable(&technics, &promisor)?;
+        Self::ensure_identity(&economics, &promisee)?;
+        let liability = T::Liability::new(technics, economics, promisee, promisor);
+        Self::start_liability(liability, Vec::new())
+    }
+
+    /// Finalize open liability of trusted caller, it's recorded as force finalized
+    /// regardless of its age.
+    fn settle(index: LiabilityIndex<T>, success: bool) -> DispatchResult {
+        let record = Self::record_of(index)?;
+        ensure!(
+            record.state == RecordState::Open,
+            Error::<T>::AlreadyFinalized
+        );
+        Self::force_record(index, record, success)
+    }
+}
+
+/// Transaction pool tag prefix of liability `create` call.
+const CREATE_TAG: &[u8] = b"liability/create";
+
+/// Transaction pool tag prefix of liability `finalize` call.
+const FINALIZE_TAG: &[u8] = b"liability/finalize";
+
+/// Transaction pool tag prefix of liability `reveal_report` call.
+const REVEAL_TAG: &[u8] = b"liability/reveal";
+
+/// Transaction pool tag prefix of liability `acknowledge` call.
+const ACKNOWLEDGE_TAG: &[u8] = b"liability/acknowledge";
+
+/// Transaction pool tag prefix of blinded liability calls.
+const BLINDED_TAG: &[u8] = b"liability/blinded";
+
+impl<T: Trait> Module<T> {
+    /// Transaction pool validity of unsigned liability creation with parties proofs
+    /// optionally expiring at given block, expiring call leaves the pool after it.
+    fn validate_create(
+        liability: &T::Liability,
+        expires_at: Option<BlockNumber<T>>,
+        tags: &[Tag],
+        bounty: Option<&BalanceOf<T>>,
+        requirement: Option<&PromisorRequirementOf<T>>,
+        promisee_proof: &ProofParam<T>,
+        promisor_proof: &ProofParam<T>,
+    ) -> TransactionValidity {
+        let (promisee, promisor) = (liability.promisee(), liability.promisor());
+        let economics = liability.economics();
+
+        // Excess calls of the block never propagate
+        let pre_validated =
+            Self::pre_validate_create(liability, expires_at, tags, bounty, requirement);
+        pre_validated.map_err(|e| match e {
+            Error::<T>::TooManyUnsignedCalls
+            | Error::<T>::TooManyPairLiabilities
+            | Error::<T>::TooManyZeroValue => {
+                TransactionValidityError::from(ValidityError::RateLimited)
+            }
+            Error::<T>::ProofExpired => ValidityError::Expired.into(),
+            Error::<T>::Blacklisted
+            | Error::<T>::UnapprovedPromisor
+            | Error::<T>::PromisorKeyRotated
+            | Error::<T>::IdentityRequired
+            | Error::<T>::CapabilitiesMismatch
+            | Error::<T>::PromisorNotAllowed => ValidityError::NotPermitted.into(),
+            Error::<T>::CannotAffordDeposit => InvalidTransaction::Payment.into(),
+            Error::<T>::DuplicateAgreement => ValidityError::NonceMismatch.into(),
+            _ => InvalidTransaction::Call.into(),
+        })?;
+
+        let block_number = <frame_system::Module<T>>::block_number();
+        let mut longevity = 64_u64;
+        if let Some(expires_at) = expires_at {
+            let blocks_left: u64 = (expires_at - block_number).unique_saturated_into();
+            longevity = longevity.min(blocks_left.saturating_add(1));
+        }
+
+        let promisee_valid = Self::check_promisee_proof(
+            liability,
+            promisee_proof,
+            expires_at,
+            tags,
+            bounty,
+            requirement,
+        );
+        if !promisee_valid {
+            return ValidityError::BadPromiseeProof.into();
+        }
+
+        if !Self::check_party_proof(liability, promisor_proof, promisor, expires_at) {
+            return ValidityError::BadPromisorProof.into();
+        }
+
+        // Higher-value jobs first, but reports always win
+        let value: u64 = Self::liability_value(economics).unique_saturated_into();
+        let priority = T::CreatePriority::get()
+            .saturating_add(value)
+            .min(T::FinalizePriority::get().saturating_sub(1));
+
+        // Same agreement for the same promisor nonce is a duplicate
+        let nonce = <PromisorCount<T>>::get(promisor);
+        let tag = (CREATE_TAG, promisee, promisor, liability.technics(), nonce)
+            .using_encoded(sp_io::hashing::blake2_256);
+
+        Ok(ValidTransaction {
+            priority,
+            requires: Default::default(),
+            provides: vec![tag.to_vec()],
+            longevity,
+            propagate: true,
+        })
+    }
+
+    /// Transaction pool validity of unsigned liability report.
+    fn validate_report(
+        index: &LiabilityIndex<T>,
+        report: &TechnicalReport<T>,
+        firmware: Option<&T::Hash>,
+        proof: &ProofParam<T>,
+    ) -> TransactionValidity {
+        Self::check_rate_limits(None).map_err(|_| ValidityError::RateLimited)?;
+
+        // Global index is assigned when create is included, so report for
+        // liability that isn't created yet can't be checked or depend on it
+        let record = match <Liabilities<T>>::get(index) {
+            Some(record) => record,
+            None if *index >= <LatestIndex<T>>::get() => return InvalidTransaction::Future.into(),
+            None if Self::is_pruned(*index) => return ValidityError::Pruned.into(),
+            None => return ValidityError::UnknownLiability.into(),
+        };
+        if record.state != RecordState::Open {
+            return ValidityError::AlreadySettled.into();
+        }
+        // Report is valid when liability is funded and old enough
+        if <PendingFunding<T>>::contains_key(index) || Self::check_report_delay(&record).is_err() {
+            return InvalidTransaction::Future.into();
+        }
+        // Committed report is finalized by reveal only
+        if <ReportCommitments<T>>::contains_key(index) {
+            return ValidityError::AlreadySettled.into();
+        }
+
+        match Self::check_report_proof(&record.agreement, index, report, firmware, proof) {
+            Ok(()) => (),
+            Err(Error::<T>::BadReportProof) => return ValidityError::BadReportProof.into(),
+            Err(Error::<T>::PromisorKeyRotated) => return ValidityError::NotPermitted.into(),
+            Err(_) => return InvalidTransaction::Call.into(),
+        }
+
+        // Only one report per liability could be accepted
+        Ok(ValidTransaction {
+            priority: T::FinalizePriority::get(),
+            requires: Default::default(),
+            provides: vec![(FINALIZE_TAG, index).encode()],
+            longevity: 64_u64,
+            propagate: true,
+        })
+    }
+
+    /// Transaction pool validity of unsigned liability cancel, it leaves the pool after
+    /// proofs expire.
+    fn validate_cancel(
+        index: &LiabilityIndex<T>,
+        expires_at: &BlockNumber<T>,
+        promisee_proof: Option<&ProofParam<T>>,
+        promisor_proof: Option<&ProofParam<T>>,
+    ) -> TransactionValidity {
+        Self::check_rate_limits(None).map_err(|_| ValidityError::RateLimited)?;
+
+        let block_number = <frame_system::Module<T>>::block_number();
+        if block_number > *expires_at {
+            return ValidityError::Expired.into();
+        }
+        let blocks_left: u64 = (*expires_at - block_number).unique_saturated_into();
+
+        let record = match <Liabilities<T>>::get(index) {
+            Some(record) => record,
+            None if *index >= <LatestIndex<T>>::get() => return InvalidTransaction::Future.into(),
+            None if Self::is_pruned(*index) => return ValidityError::Pruned.into(),
+            None => return ValidityError::UnknownLiability.into(),
+        };
+        if record.state != RecordState::Open || <ReportCommitments<T>>::contains_key(index) {
+            return ValidityError::AlreadySettled.into();
+        }
+
+        let liability = &record.agreement;
+        match Self::check_cancel_proofs(
+            liability,
+            index,
+            expires_at,
+            promisee_proof,
+            promisor_proof,
+        ) {
+            Ok(_) => (),
+            Err(Error::<T>::BadPromiseeProof) => return ValidityError::BadPromiseeProof.into(),
+            Err(Error::<T>::BadPromisorProof) => return ValidityError::BadPromisorProof.into(),
+            Err(_) => return InvalidTransaction::Call.into(),
+        }
+
+        // Cancel and report of liability exclude each other, report wins
+        Ok(ValidTransaction {
+            priority: T::FinalizePriority::get().saturating_sub(1),
+            requires: Default::default(),
+            provides: vec![(FINALIZE_TAG, index).encode()],
+            longevity: blocks_left.saturating_add(1).min(64),
+            propagate: true,
+        })
+    }
+
+    /// Transaction pool validity of unsigned commitment to liability report.
+    fn validate_commit(
+        index: &LiabilityIndex<T>,
+        commitment: &H256,
+        proof: &ProofParam<T>,
+    ) -> TransactionValidity {
+        Self::check_rate_limits(None).map_err(|_| ValidityError::RateLimited)?;
+
+        let record = match <Liabilities<T>>::get(index) {
+            Some(record) => record,
+            None if *index >= <LatestIndex<T>>::get() => return InvalidTransaction::Future.into(),
+            None if Self::is_pruned(*index) => return ValidityError::Pruned.into(),
+            None => return ValidityError::UnknownLiability.into(),
+        };
+        match Self::check_commit(*index, &record, commitment, proof) {
+            Ok(()) => (),
+            Err(Error::<T>::AlreadyFinalized) | Err(Error::<T>::ReportCommitted) => {
+                return ValidityError::AlreadySettled.into()
+            }
+            Err(Error::<T>::FundingPending) | Err(Error::<T>::ReportTooEarly) => {
+                return InvalidTransaction::Future.into()
+            }
+            Err(Error::<T>::BadReportProof) => return ValidityError::BadReportProof.into(),
+            Err(Error::<T>::PromisorKeyRotated) => return ValidityError::NotPermitted.into(),
+            Err(_) => return InvalidTransaction::Call.into(),
+        }
+
+        // Commitment and report of liability exclude each other
+        Ok(ValidTransaction {
+            priority: T::FinalizePriority::get(),
+            requires: Default::default(),
+            provides: vec![(FINALIZE_TAG, index).encode()],
+            longevity: 64_u64,
+            propagate: true,
+        })
+    }
+
+    /// Transaction pool validity of unsigned liability acknowledgement, it leaves the pool
+    /// after acknowledgement window.
+    fn validate_acknowledge(
+        index: &LiabilityIndex<T>,
+        proof: &ProofParam<T>,
+    ) -> TransactionValidity {
+        Self::check_rate_limits(None).map_err(|_| ValidityError::RateLimited)?;
+
+        let record = match <Liabilities<T>>::get(index) {
+            Some(record) => record,
+            None if *index >= <LatestIndex<T>>::get() => return InvalidTransaction::Future.into(),
+            None if Self::is_pruned(*index) => return ValidityError::Pruned.into(),
+            None => return ValidityError::UnknownLiability.into(),
+        };
+        let deadline = match Self::check_acknowledge(*index, &record, proof) {
+            Ok((deadline, _)) => deadline,
+            Err(Error::<T>::AlreadyFinalized) | Err(Error::<T>::NotPendingAcknowledgement) => {
+                return ValidityError::AlreadySettled.into()
+            }
+            Err(Error::<T>::AcknowledgementExpired) => return ValidityError::Expired.into(),
+            Err(Error::<T>::BadPromisorProof) => return ValidityError::BadPromisorProof.into(),
+            Err(_) => return InvalidTransaction::Call.into(),
+        };
+        let block_number = <frame_system::Module<T>>::block_number();
+        let blocks_left: u64 = (deadline - block_number).unique_saturated_into();
+
+        Ok(ValidTransaction {
+            priority: T::FinalizePriority::get(),
+            requires: Default::default(),
+            provides: vec![(ACKNOWLEDGE_TAG, index).encode()],
+            longevity: blocks_left.saturating_add(1).min(64),
+            propagate: true,
+        })
+    }
+
+    /// Transaction pool validity of unsigned reveal of committed report, it leaves the pool
+    /// after reveal window.
+    fn validate_reveal(
+        index: &LiabilityIndex<T>,
+        report: &TechnicalReport<T>,
+        salt: &H256,
+    ) -> TransactionValidity {
+        Self::check_rate_limits(None).map_err(|_| ValidityError::RateLimited)?;
+
+        let deadline = match Self::check_reveal(*index, report, salt) {
+            Ok(deadline) => deadline,
+            // Commitment of open liability could be included before reveal
+            Err(Error::<T>::NoCommitment) => match <Liabilities<T>>::get(index) {
+                Some(record) if record.state == RecordState::Open => {
+                    return InvalidTransaction::Future.into()
+                }
+                _ => return ValidityError::AlreadySettled.into(),
+            },
+            Err(Error::<T>::RevealWindowPassed) => return ValidityError::Expired.into(),
+            Err(Error::<T>::CommitmentMismatch) => return ValidityError::CommitmentMismatch.into(),
+            Err(_) => return InvalidTransaction::Call.into(),
+        };
+
+        let block_number = <frame_system::Module<T>>::block_number();
+        let blocks_left: u64 = (deadline - block_number).unique_saturated_into();
+        Ok(ValidTransaction {
+            priority: T::FinalizePriority::get(),
+            requires: Default::default(),
+            provides: vec![(REVEAL_TAG, index).encode()],
+            longevity: blocks_left.saturating_add(1).min(64),
+            propagate: true,
+        })
+    }
+}
+
+/// Unsigned calls rejected for liability reasons carry `ValidityError` code, see
+/// `InvalidTransaction::Custom`.
+impl<T: Trait> frame_support::unsigned::ValidateUnsigned for Module<T> {
+    type Call = Call<T>;
+
+    /// Rate limited calls aren't dropped by block author, they exhaust resources of the block
+    /// and wait for the next one.
+    fn pre_dispatch(call: &Self::Call) -> Result<(), TransactionValidityError> {
+        let rate_limited = TransactionValidityError::from(ValidityError::RateLimited);
+        match Self::validate_unsigned(TransactionSource::InBlock, call) {
+            Ok(_) => Ok(()),
+            Err(error) if error == rate_limited => {
+                Err(InvalidTransaction::ExhaustsResources.into())
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    fn validate_unsigned(source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+        match call {
+            Call::create(
+                technics,
+                economics,
+                promisee,
+                promisor,
+                promisee_proof,
+                promisor_proof,
+            ) => {
+                let liability = T::Liability::new(
+                    technics.clone(),
+                    economics.clone(),
+                    promisee.clone(),
+                    promisor.clone(),
+                );
+                Self::validate_create(
+                    &liability,
+                    None,
+                    &[],
+                    None,
+                    None,
+                    promisee_proof,
+                    promisor_proof,
+                )
+            }
+
+            Call::create_tagged(
+                technics,
+                economics,
+                tags,
+                promisee,
+                promisor,
+                promisee_proof,
+                promisor_proof,
+            ) => {
+                let liability = T::Liability::new(
+                    technics.clone(),
+                    economics.clone(),
+                    promisee.clone(),
+                    promisor.clone(),
+                );
+                Self::validate_create(
+                    &liability,
+                    None,
+                    tags,
+                    None,
+                    None,
+                    promisee_proof,
+                    promisor_proof,
+                )
+            }
+
+            Call::create_restricted(
+                technics,
+                economics,
+                promisee,
+                promisor,
+                requirement,
+                promisee_proof,
+                promisor_proof,
+            ) => {
+                let liability = T::Liability::new(
+                    technics.clone(),
+                    economics.clone(),
+                    promisee.clone(),
+                    promisor.clone(),
+                );
+                Self::validate_create(
+                    &liability,
+                    None,
+                    &[],
+                    None,
+                    Some(requirement),
+                    promisee_proof,
+                    promisor_proof,
+                )
+            }
+
+            Call::create_with_bounty(
+                technics,
+                economics,
+                promisee,
+                promisor,
+                bounty,
+                promisee_proof,
+                promisor_proof,
+            ) => {
+                let liability = T::Liability::new(
+                    technics.clone(),
+                    economics.clone(),
+                    promisee.clone(),
+                    promisor.clone(),
+                );
+                Self::validate_create(
+                    &liability,
+                    None,
+                    &[],
+                    Some(bounty),
+                    None,
+                    promisee_proof,
+                    promisor_proof,
+                )
+            }
+
+            Call::create_expiring(
+                technics,
+                economics,
+                promisee,
+                promisor,
+                expires_at,
+                promisee_proof,
+                promisor_proof,
+            ) => {
+                let liability = T::Liability::new(
+                    technics.clone(),
+                    economics.clone(),
+                    promisee.clone(),
+                    promisor.clone(),
+                );
+                Self::validate_create(
+                    &liability,
+                    Some(*expires_at),
+                    &[],
+                    None,
+                    None,
+                    promisee_proof,
+                    promisor_proof,
+                )
+            }
+
+            Call::finalize(index, report, proof) => {
+                Self::validate_report(index, report, None, proof)
+            }
+
+            Call::create_chained(
+                parent,
+                economics,
+                promisee,
+                promisor,
+                promisee_proof,
+                promisor_proof,
+            ) => {
+                let technics = match Self::chained_technics(*parent) {
+                    Ok(technics) => technics,
+                    // Report of open or witnessed parent could be accepted later
+                    Err(_)
+                        if !Self::is_finalized(*parent)
+                            || <PendingSettlement<T>>::contains_key(*parent) =>
+                    {
+                        return InvalidTransaction::Future.into()
+                    }
+                    Err(_) => return InvalidTransaction::Call.into(),
+                };
+                let create = Call::create(
+                    technics,
+                    economics.clone(),
+                    promisee.clone(),
+                    promisor.clone(),
+                    promisee_proof.clone(),
+                    promisor_proof.clone(),
+                );
+                Self::validate_unsigned(source, &create)
+            }
+
+            Call::finalize_attributed(index, report, proof, _) => {
+                Self::validate_report(index, report, None, proof)
+            }
+
+            Call::finalize_attested(index, report, firmware, proof) => {
+                Self::validate_report(index, report, Some(firmware), proof)
+            }
+
+            Call::finalize_by_id(id, report, proof) => match <IdToIndex<T>>::get(id) {
+                Some(index) => Self::validate_report(&index, report, None, proof),
+                None => ValidityError::UnknownLiability.into(),
+            },
+
+            Call::finalize_noted(index, report_hash, proof) => {
+                match <ReportPreimage<T>>::get(report_hash) {
+                    Some(preimage) => Self::validate_report(index, &preimage.report, None, proof),
+                    None => ValidityError::UnknownPreimage.into(),
+                }
+            }
+
+            Call::commit_report(index, commitment, proof) => {
+                Self::validate_commit(index, commitment, proof)
+            }
+
+            Call::reveal_report(index, report, salt) => Self::validate_reveal(index, report, salt),
+
+            Call::acknowledge(index, proof) => Self::validate_acknowledge(index, proof),
+
+            Call::create_blinded(
+                technics,
+                economics,
+                promisee,
+                promisee_salt,
+                promisor,
+                promisor_salt,
+                payout,
+                promisee_proof,
+                promisor_proof,
+            ) => Self::validate_blinded_create(
+                technics,
+                economics,
+                (promisee, promisee_salt),
+                (promisor, promisor_salt),
+                payout,
+                promisee_proof,
+                promisor_proof,
+            ),
+
+            Call::finalize_blinded(
+                index,
+                report,
+                promisee,
+                promisee_salt,
+                promisor,
+                promisor_salt,
+                proof,
+            ) => Self::validate_blinded_report(
+                index,
+                report,
+                (promisee, promisee_salt),
+                (promisor, promisor_salt),
+                proof,
+            ),
+
+            Call::cancel(index, expires_at, promisee_proof, promisor_proof) => {
+                Self::validate_cancel(
+                    index,
+                    expires_at,
+                    promisee_proof.as_ref(),
+                    promisor_proof.as_ref(),
+                )
+            }
+
+            _ => InvalidTransaction::Call.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::registry;
+    use super::reputation::{self, Outcome};
+    use super::signed::{ProofSigner, SignedLiability};
+    use super::technics::{PureIPFS, RequiredCapabilities};
+    use super::*;
+    use crate as liability;
+    use base58::FromBase58;
+    use frame_support::traits::OffchainWorker;
+    use frame_support::{
+        assert_err, assert_err_ignore_postinfo, assert_ok, impl_outer_dispatch, impl_outer_event,
+        impl_outer_origin, parameter_types, weights::Weight,
+    };
+    use frame_system::offchain::SendTransactionTypes;
+    use node_primitives::{AccountId, Signature};
+    use sp_core::{
+        crypto::Pair,
+        offchain::{testing, OffchainExt, StorageKind, TransactionPoolExt},
+        sr25519,
+        testing::KeyStore,
+        traits::KeystoreExt,
+        H256,
+    };
+    use sp_runtime::{
+        testing::{Header, TestXt},
+        traits::{IdentifyAccount, IdentityLookup, Verify},
+        BuildStorage, DispatchResult, Perbill,
+    };
+    use std::cell::RefCell;
+
+    impl_outer_event! {
+        pub enum MetaEvent for Runtime {
+            frame_system<T>, pallet_balances<T>, liability<T>, reputation<T>, registry<T>, jury<T>,
+        }
+    }
+
+    impl_outer_origin! {
+        pub enum Origin for Runtime {}
+    }
+
+    impl_outer_dispatch! {
+        pub enum OuterCall for Runtime where origin: Origin {
+            liability::Liability,
+        }
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    pub struct Runtime;
+
+    thread_local! {
+        static PROMISOR_WHITELIST: RefCell<bool> = RefCell::new(false);
+    }
+
+    /// Promisor whitelist flag switchable by tests.
+    pub struct PromisorWhitelist;
+    impl Get<bool> for PromisorWhitelist {
+        fn get() -> bool {
+            PROMISOR_WHITELIST.with(|enabled| *enabled.borrow())
+        }
+    }
+
+    thread_local! {
+        static SWEEP_ITEMS: RefCell<u32> = RefCell::new(16);
+    }
+
+    /// Sweep budget switchable by tests.
+    pub struct MaxSweepItemsPerBlock;
+    impl Get<u32> for MaxSweepItemsPerBlock {
+        fn get() -> u32 {
+            SWEEP_ITEMS.with(|items| *items.borrow())
+        }
+    }
+
+    thread_local! {
+        static ACK_WINDOW: RefCell<u64> = RefCell::new(0);
+        static REPORT_DELAY: RefCell<u64> = RefCell::new(0);
+    }
+
+    /// Acknowledgement window switchable by tests, disabled by default.
+    pub struct AcknowledgementWindow;
+    impl Get<u64> for AcknowledgementWindow {
+        fn get() -> u64 {
+            ACK_WINDOW.with(|window| *window.borrow())
+        }
+    }
+
+    /// Report delay switchable by tests, disabled by default.
+    pub struct MinReportDelay;
+    impl Get<u64> for MinReportDelay {
+        fn get() -> u64 {
+            REPORT_DELAY.with(|delay| *delay.borrow())
+        }
+    }
+
+    thread_local! {
+        static IDENTITY_THRESHOLD: RefCell<u128> = RefCell::new(u128::max_value());
+    }
+
+    /// Liability identity threshold switchable by tests.
+    pub struct IdentityThreshold;
+    impl Get<u128> for IdentityThreshold {
+        fn get() -> u128 {
+            IDENTITY_THRESHOLD.with(|threshold| *threshold.borrow())
+        }
+    }
+
+    thread_local! {
+        static HOOK_CALLS: RefCell<Vec<(u64, Option<bool>)>> = RefCell::new(Vec::new());
+    }
+
+    /// Records liabilities lifecycle: index and report success, if any.
+    pub struct RecordingHook;
+    impl OnLiabilityCreated<u64, AccountId> for RecordingHook {
+        fn on_liability_created(index: &u64, _: &AccountId, _: &AccountId) -> HookResult {
+            HOOK_CALLS.with(|calls| calls.borrow_mut().push((*index, None)));
+            Ok(10)
+        }
+    }
+    impl OnReportAccepted<u64, AccountId> for RecordingHook {
+        fn on_report_accepted(
+            index: &u64,
+            _: &AccountId,
+            _: &AccountId,
+            success: bool,
+        ) -> HookResult {
+            HOOK_CALLS.with(|calls| calls.borrow_mut().push((*index, Some(success))));
+            Ok(10)
+        }
+    }
+
+    /// Failure mode of `FailingHook`.
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    pub enum HookFault {
+        None,
+        Error,
+        Overweight,
+    }
+
+    thread_local! {
+        static HOOK_FAULT: RefCell<HookFault> = RefCell::new(HookFault::None);
+    }
+
+    /// Key written by `FailingHook` before it fails.
+    pub const FAILING_HOOK_KEY: &[u8] = b":failing_hook:";
+
+    /// Writes storage and fails as switched by tests.
+    pub struct FailingHook;
+    impl FailingHook {
+        fn handle(index: &u64) -> HookResult {
+            sp_io::storage::set(FAILING_HOOK_KEY, &index.encode());
+            match HOOK_FAULT.with(|fault| *fault.borrow()) {
+                HookFault::None => Ok(0),
+                HookFault::Error => Err((5, DispatchError::Other("failing hook"))),
+                HookFault::Overweight => Ok(MaxHooksWeight::get() + 1),
+            }
+        }
+    }
+    impl OnLiabilityCreated<u64, AccountId> for FailingHook {
+        fn on_liability_created(index: &u64, _: &AccountId, _: &AccountId) -> HookResult {
+            Self::handle(index)
+        }
+    }
+    impl OnReportAccepted<u64, AccountId> for FailingHook {
+        fn on_report_accepted(index: &u64, _: &AccountId, _: &AccountId, _: bool) -> HookResult {
+            Self::handle(index)
+        }
+    }
+
+    /// Listens nothing, uses default handlers.
+    pub struct SilentHook;
+    impl OnLiabilityCreated<u64, AccountId> for SilentHook {}
+    impl OnReportAccepted<u64, AccountId> for SilentHook {}
+
+    thread_local! {
+        static REQUIRED_CAPABILITIES: RefCell<Capabilities> = RefCell::new(Default::default());
+    }
+
+    /// IPFS technics with capabilities requirement switchable by tests.
+    pub struct CapableIPFS;
+    impl Technical for CapableIPFS {
+        type Parameter = Vec<u8>;
+        type Report = Vec<u8>;
+        type Oracle = ();
+
+        fn derive_next(report: &Vec<u8>) -> Option<Vec<u8>> {
+            PureIPFS::derive_next(report)
+        }
+
+        fn model(technics: &Vec<u8>) -> Option<H256> {
+            PureIPFS::model(technics)
+        }
+
+        fn required_capabilities(_technics: &Vec<u8>) -> Capabilities {
+            REQUIRED_CAPABILITIES.with(|required| required.borrow().clone())
+        }
+    }
+
+    thread_local! {
+        static MIN_REPUTATION: RefCell<u32> = RefCell::new(0);
+        static STRICT_FIRMWARE: RefCell<bool> = RefCell::new(false);
+        static DEFERRED_FUNDING: RefCell<bool> = RefCell::new(false);
+        static ECONOMICS_PAYOUT: RefCell<Option<AccountId>> = RefCell::new(None);
+        static ECONOMICS_VALUE: RefCell<Option<u128>> = RefCell::new(None);
+    }
+
+    /// Free economics with promisor reputation, firmware, funding requirements, payout
+    /// account and value switchable by tests.
+    pub struct ReputableCommunism;
+    impl Economical for ReputableCommunism {
+        type Parameter = ();
+    }
+    impl Divisible for ReputableCommunism {
+        fn scale(_unit: &(), _quantity: u32) {}
+    }
+    impl Reputable for ReputableCommunism {
+        fn min_reputation(_economics: &()) -> u32 {
+            MIN_REPUTATION.with(|min| *min.borrow())
+        }
+    }
+    impl Attestable for ReputableCommunism {
+        fn strict_firmware(_economics: &()) -> bool {
+            STRICT_FIRMWARE.with(|strict| *strict.borrow())
+        }
+    }
+    impl Valuable<u128> for ReputableCommunism {
+        fn value(_economics: &()) -> Option<u128> {
+            ECONOMICS_VALUE.with(|value| *value.borrow())
+        }
+    }
+    impl Marketable for ReputableCommunism {}
+    impl Deferrable for ReputableCommunism {
+        fn deferred_funding(_economics: &()) -> bool {
+            DEFERRED_FUNDING.with(|deferred| *deferred.borrow())
+        }
+    }
+    impl Payable<AccountId> for ReputableCommunism {
+        fn payout(_economics: &()) -> Option<AccountId> {
+            ECONOMICS_PAYOUT.with(|payout| payout.borrow().clone())
+        }
+    }
+    impl Settlement<AccountId> for ReputableCommunism {
+        fn on_start(
+            _economics: &(),
+            _promisee: &AccountId,
+            _promisor: &AccountId,
+        ) -> DispatchResult {
+            Ok(())
+        }
+        fn on_finish(
+            _economics: &(),
+            _promisee: &AccountId,
+            _promisor: &AccountId,
+            _success: bool,
+        ) -> DispatchResult {
+            Ok(())
+        }
+    }
+
+    /// Only Alice has verified identity.
+    pub struct AliceVerified;
+    impl IdentityVerifier<AccountId> for AliceVerified {
+        fn is_verified(who: &AccountId) -> bool {
+            let alice = sr25519::Pair::from_string("//Alice", None).unwrap();
+            *who == <Signature as Verify>::Signer::from(alice.public()).into_account()
+        }
+    }
+
+    parameter_types! {
+        pub const BlockHashCount: u64 = 250;
+        pub const MaximumBlockWeight: Weight = 1024;
+        pub const MaximumBlockLength: u32 = 2 * 1024;
+        pub const AvailableBlockRatio: Perbill = Perbill::one();
+        pub const StatsPeriod: u64 = 5;
+        pub const CreatedRetention: u64 = 10;
+        pub const DigestBloomBits: u32 = 1024;
+        pub const DigestBloomHashes: u8 = 11;
+        pub const MaxUnsignedPerBlock: u32 = 8;
+        pub const MaxPairPerBlock: u32 = 3;
+        pub const MaxTechnicsLength: u32 = 4_100;
+        pub const MaxReportLength: u32 = 4_100;
+        pub const MaxTags: u32 = 3;
+        pub const MaxTaggedLiabilities: u32 = 2;
+        pub const MaxAllowedPromisors: u32 = 3;
+        pub const MaxHooksWeight: Weight = 100;
+        pub const MinForceFinalizeAge: u64 = 5;
+        pub const ExistentialDeposit: u128 = 1;
+        pub const OrderDeposit: u128 = 10;
+        pub const ReportPreimageDeposit: u128 = 20;
+        pub const ReportChallengeWindow: u64 = 5;
+        pub const MaxOrdersPerMarket: u32 = 2;
+        pub const MaxOpenOrdersPerAccount: u32 = 3;
+        pub const LighthouseMinStake: u128 = 50;
+        pub const MaxLighthouses: u32 = 2;
+        pub const LighthouseSlot: u64 = 10;
+        pub const LighthouseFeeShare: Perbill = Perbill::from_percent(40);
+        pub const MatchCommitWindow: u64 = 3;
+        pub const MaxMatchCommits: u32 = 2;
+        pub const BreachClaimPeriod: u64 = 10;
+        pub const OrderBreachSlash: u128 = 30;
+        pub const FundingDeadline: u64 = 5;
+        pub const RevealWindow: u64 = 3;
+        pub const ClaimTolerance: u64 = 2;
+        pub const WitnessMinStake: u128 = 50;
+        pub const MaxWitnesses: u32 = 3;
+        pub const WitnessSlash: u128 = 20;
+        pub const StatsEraLength: u64 = 10;
+        pub const StatsRetention: u32 = 2;
+        pub const PoolMinStake: u128 = 30;
+        pub const MaxPoolSize: u32 = 3;
+        pub const AssignmentDelay: u64 = 2;
+        pub const AcknowledgementDeposit: u128 = 10;
+        pub const MaxLiabilityLifetime: u64 = 20;
+        pub const ExpiryIncentive: u128 = 4;
+        pub const ZeroValueDeposit: u128 = 25;
+        pub const MaxZeroValuePerEra: u32 = 2;
+        pub const ValueFee: Perbill = Perbill::from_percent(10);
+        pub const MinValueFee: u128 = 5;
+        pub const MaxValueFee: u128 = 50;
+        pub const ExpiredOrderSlash: Perbill = Perbill::from_percent(10);
+        pub const MaxRecentMatches: u32 = 2;
+        pub const CompletedReward: u32 = 10;
+        pub const FailedPenalty: u32 = 15;
+        pub const DisputedPenalty: u32 = 25;
+        pub const MaxScore: u32 = 30;
+        pub const ScoreStep: u32 = 20;
+        pub const RatingWindow: u64 = 5;
+        pub const ScoreDecay: Perbill = Perbill::from_percent(50);
+        pub const ReputationEra: u64 = 10;
+        pub const ZeroValueQuota: u32 = 1;
+        pub const RobotDeposit: u128 = 50;
+        pub const MaxSerialLength: u32 = 8;
+        pub const JurorStake: u128 = 100;
+        pub const MaxJurors: u32 = 4;
+        pub const UnbondingPeriod: u64 = 3;
+        pub const JurySize: u32 = 3;
+        pub const JuryQuorum: u32 = 2;
+        pub const JuryCommitWindow: u64 = 2;
+        pub const JuryRevealWindow: u64 = 2;
+        pub const DisputeFee: u128 = 30;
+        pub const MisalignedSlash: Perbill = Perbill::from_percent(50);
+        pub const CreatePriority: TransactionPriority = 100;
+        pub const FinalizePriority: TransactionPriority = 1_000;
+    }
+
+    impl frame_system::Trait for Runtime {
+        type Origin = Origin;
+        type Index = u64;
+        type BlockNumber = u64;
+        type Call = OuterCall;
+        type Hash = H256;
+        type Hashing = ::sp_runtime::traits::BlakeTwo256;
+        type AccountId = AccountId;
+        type Lookup = IdentityLookup<Self::AccountId>;
+        type Header = Header;
+        type Event = MetaEvent;
+        type BlockHashCount = BlockHashCount;
+        type MaximumBlockWeight = MaximumBlockWeight;
+        type MaximumBlockLength = MaximumBlockLength;
+        type AvailableBlockRatio = AvailableBlockRatio;
+        type Version = ();
+        type ModuleToIndex = ();
+        type AccountData = pallet_balances::AccountData<u128>;
+        type OnNewAccount = ();
+        type OnKilledAccount = ();
+        type DbWeight = ();
+        type BlockExecutionWeight = ();
+        type ExtrinsicBaseWeight = ();
+        type MaximumExtrinsicWeight = ();
+        type BaseCallFilter = ();
+        type SystemWeightInfo = ();
+    }
+
+    impl SigningTypes for Runtime {
+        type Public = <Signature as Verify>::Signer;
+        type Signature = Signature;
+    }
+
+    type Extrinsic = TestXt<Call<Runtime>, ()>;
+
+    impl<C> SendTransactionTypes<C> for Runtime
+    where
+        Call<Runtime>: From<C>,
+    {
+        type OverarchingCall = Call<Runtime>;
+        type Extrinsic = Extrinsic;
+    }
+
+    impl<C> CreateSignedTransaction<C> for Runtime
+    where
+        Call<Runtime>: From<C>,
+    {
+        fn create_transaction<A: AppCrypto<Self::Public, Self::Signature>>(
+            call: Call<Runtime>,
+            _public: Self::Public,
+            _account: AccountId,
+            nonce: u64,
+        ) -> Option<(Call<Runtime>, (u64, ()))> {
+            Some((call, (nonce, ())))
+        }
+    }
+
+    impl pallet_balances::Trait for Runtime {
+        type Balance = u128;
+        type DustRemoval = ();
+        type Event = MetaEvent;
+        type ExistentialDeposit = ExistentialDeposit;
+        type AccountStore = frame_system::Module<Runtime>;
+        type WeightInfo = ();
+    }
+
+    thread_local! {
+        static SETTLED_VALUE: RefCell<u128> = RefCell::new(10);
+    }
+
+    /// Every liability settles ten units unless switched by tests.
+    pub struct SettledValue;
+    impl Convert<(), u128> for SettledValue {
+        fn convert(_: ()) -> u128 {
+            SETTLED_VALUE.with(|value| *value.borrow())
+        }
+    }
+
+    thread_local! {
+        static MIN_FILL_QUANTITY: RefCell<u32> = RefCell::new(1);
+    }
+
+    /// Minimal order fill switchable by tests.
+    pub struct MinFillQuantity;
+    impl Get<u32> for MinFillQuantity {
+        fn get() -> u32 {
+            MIN_FILL_QUANTITY.with(|min| *min.borrow())
+        }
+    }
+
+    /// Market fees are sent to treasury account.
+    pub struct MockTreasury;
+    impl OnUnbalanced<pallet_balances::NegativeImbalance<Runtime>> for MockTreasury {
+        fn on_nonzero_unbalanced(amount: pallet_balances::NegativeImbalance<Runtime>) {
+            Balances::resolve_creating(&account("//Treasury"), amount);
+        }
+    }
+
+    thread_local! {
+        static TECHNICS_MARKETS: RefCell<Vec<(Vec<u8>, MarketId)>> = RefCell::new(vec![]);
+    }
+
+    /// Markets of technics set by tests, the rest is traded on market zero.
+    pub struct TechnicsMarket;
+    impl Convert<Vec<u8>, MarketId> for TechnicsMarket {
+        fn convert(technics: Vec<u8>) -> MarketId {
+            TECHNICS_MARKETS.with(|markets| {
+                markets
+                    .borrow()
+                    .iter()
+                    .find(|(listed, _)| *listed == technics)
+                    .map(|(_, market_id)| *market_id)
+                    .unwrap_or(0)
+            })
+        }
+    }
+
+    /// Matching of every demand is rewarded by two units.
+    pub struct TwoUnits;
+    impl Convert<(), u128> for TwoUnits {
+        fn convert(_: ()) -> u128 {
+            2
+        }
+    }
+
+    impl Trait for Runtime {
+        type Event = MetaEvent;
+        type Balance = u128;
+        type SettledValue = SettledValue;
+        type StatsPeriod = StatsPeriod;
+        type CreatedRetention = CreatedRetention;
+        type DigestBloomBits = DigestBloomBits;
+        type DigestBloomHashes = DigestBloomHashes;
+        type MaxUnsignedPerBlock = MaxUnsignedPerBlock;
+        type MaxPairPerBlock = MaxPairPerBlock;
+        type MaxTechnicsLength = MaxTechnicsLength;
+        type MaxReportLength = MaxReportLength;
+        type MaxTags = MaxTags;
+        type MaxTaggedLiabilities = MaxTaggedLiabilities;
+        type MaxAllowedPromisors = MaxAllowedPromisors;
+        type PromisorWhitelist = PromisorWhitelist;
+        type AdminOrigin = frame_system::EnsureRoot<AccountId>;
+        type IdentityVerifier = AliceVerified;
+        type IdentityThreshold = IdentityThreshold;
+        type ReputationProvider = Reputation;
+        type Robots = Registry;
+        type MinForceFinalizeAge = MinForceFinalizeAge;
+        type MinReportDelay = MinReportDelay;
+        type Currency = pallet_balances::Module<Runtime>;
+        type OrderDeposit = OrderDeposit;
+        type MaxOrdersPerMarket = MaxOrdersPerMarket;
+        type MaxOpenOrdersPerAccount = MaxOpenOrdersPerAccount;
+        type ReportPreimageDeposit = ReportPreimageDeposit;
+        type ReportChallengeWindow = ReportChallengeWindow;
+        type MinFillQuantity = MinFillQuantity;
+        type ExpiredOrderSlash = ExpiredOrderSlash;
+        type MaxSweepItemsPerBlock = MaxSweepItemsPerBlock;
+        type MaxRecentMatches = MaxRecentMatches;
+        type MatchingReward = TwoUnits;
+        type Market = TechnicsMarket;
+        type LighthouseMinStake = LighthouseMinStake;
+        type MaxLighthouses = MaxLighthouses;
+        type LighthouseSlot = LighthouseSlot;
+        type LighthouseFeeShare = LighthouseFeeShare;
+        type MatchCommitWindow = MatchCommitWindow;
+        type MaxMatchCommits = MaxMatchCommits;
+        type BreachClaimPeriod = BreachClaimPeriod;
+        type OrderBreachSlash = OrderBreachSlash;
+        type FundingDeadline = FundingDeadline;
+        type RevealWindow = RevealWindow;
+        type ClaimTolerance = ClaimTolerance;
+        type WitnessMinStake = WitnessMinStake;
+        type MaxWitnesses = MaxWitnesses;
+        type WitnessSlash = WitnessSlash;
+        type StatsEraLength = StatsEraLength;
+        type StatsRetention = StatsRetention;
+        type Randomness = RandomnessCollectiveFlip;
+        type PoolMinStake = PoolMinStake;
+        type MaxPoolSize = MaxPoolSize;
+        type AssignmentDelay = AssignmentDelay;
+        type AcknowledgementWindow = AcknowledgementWindow;
+        type AcknowledgementDeposit = AcknowledgementDeposit;
+        type MaxLiabilityLifetime = MaxLiabilityLifetime;
+        type ExpiryIncentive = ExpiryIncentive;
+        type ZeroValueDeposit = ZeroValueDeposit;
+        type MaxZeroValuePerEra = MaxZeroValuePerEra;
+        type MarketFeeDestination = MockTreasury;
+        type ValueFee = ValueFee;
+        type MinValueFee = MinValueFee;
+        type MaxValueFee = MaxValueFee;
+        type CreatePriority = CreatePriority;
+        type FinalizePriority = FinalizePriority;
+        type RemoteOrigin = frame_system::EnsureSigned<AccountId>;
+        type BridgeOrigin = frame_system::EnsureRoot<AccountId>;
+        type Legacy = legacy::LegacyObjective;
+        type AuthorityId = crypto::ReportAuthId;
+        type Hooks = (RecordingHook, SilentHook, Reputation, Registry, FailingHook);
+        type MaxHooksWeight = MaxHooksWeight;
+        type WeightInfo = ();
+        type Technics = CapableIPFS;
+        type Economics = ReputableCommunism;
+        type Liability = SignedLiability<
+            Self::Technics,
+            Self::Economics,
+            Signature,
+            <Signature as Verify>::Signer,
+            AccountId,
+        >;
+    }
+
+    impl reputation::Trait for Runtime {
+        type CompletedReward = CompletedReward;
+        type FailedPenalty = FailedPenalty;
+        type DisputedPenalty = DisputedPenalty;
+        type MaxScore = MaxScore;
+        type ScoreStep = ScoreStep;
+        type RatingWindow = RatingWindow;
+        type ScoreDecay = ScoreDecay;
+        type EraLength = ReputationEra;
+        type ZeroValueQuota = ZeroValueQuota;
+        type Event = MetaEvent;
+    }
+
+    impl registry::Trait for Runtime {
+        type RobotDeposit = RobotDeposit;
+        type MaxSerialLength = MaxSerialLength;
+        type Event = MetaEvent;
+    }
+
+    impl jury::Trait for Runtime {
+        type JurorStake = JurorStake;
+        type MaxJurors = MaxJurors;
+        type UnbondingPeriod = UnbondingPeriod;
+        type JurySize = JurySize;
+        type JuryQuorum = JuryQuorum;
+        type CommitWindow = JuryCommitWindow;
+        type RevealWindow = JuryRevealWindow;
+        type DisputeFee = DisputeFee;
+        type MisalignedSlash = MisalignedSlash;
+        type Event = MetaEvent;
+    }
+
+    /// Mission scheduler opening liabilities of root motions, consumer of `LiabilityIssuer`.
+    mod scheduler {
+        use super::*;
+
+        pub trait Trait: frame_system::Trait {
+            type Issuer: LiabilityIssuer<Vec<u8>, (), AccountId, u64>;
+        }
+
+        decl_module! {
+            pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+                #[weight = 0]
+                fn schedule(
+                    origin,
+                    technics: Vec<u8>,
+                    promisee: AccountId,
+                    promisor: AccountId,
+                ) {
+                    ensure_root(origin)?;
+                    T::Issuer::issue(technics, (), promisee, promisor)?;
+                }
+
+                #[weight = 0]
+                fn complete(origin, index: u64, success: bool) {
+                    ensure_root(origin)?;
+                    T::Issuer::settle(index, success)?;
+                }
+            }
+        }
+    }
+
+    impl scheduler::Trait for Runtime {
+        type Issuer = Liability;
+    }
+
+    /// Account of given sr25519 secret uri.
+    pub fn account(uri: &str) -> AccountId {
+        crate::testing::make_account(uri)
+    }
+
+    pub fn new_test_ext() -> sp_io::TestExternalities {
+        let mut storage = frame_system::GenesisConfig::default()
+            .build_storage::<Runtime>()
+            .unwrap();
+        pallet_balances::GenesisConfig::<Runtime> {
+            balances: vec![
+                (account("//Alice"), 1_000),
+                (account("//Bob"), 1_000),
+                (account("//Charlie"), 1_000),
+            ],
+        }
+        .assimilate_storage(&mut storage)
+        .unwrap();
+        storage.into()
+    }
+
+    type Liability = Module<Runtime>;
+    type Reputation = reputation::Module<Runtime>;
+    type Registry = registry::Module<Runtime>;
+    type Jury = jury::Module<Runtime>;
+    type Scheduler = scheduler::Module<Runtime>;
+    type System = frame_system::Module<Runtime>;
+    type Balances = pallet_balances::Module<Runtime>;
+    type RandomnessCollectiveFlip = pallet_randomness_collective_flip::Module<Runtime>;
+
+    #[test]
+    fn test_initial_setup() {
+        new_test_ext().execute_with(|| {
+            assert_eq!(Liability::latest_index(), 0);
+        });
+    }
+
+    fn get_params_proof(
+        uri: &str,
+        technics: &TechnicalParam<Runtime>,
+        economics: &EconomicalParam<Runtime>,
+    ) -> (AccountId, ProofParam<Runtime>) {
+        crate::testing::make_party_proof(uri, technics, economics)
+    }
+
+    fn get_bounty_proof(
+        uri: &str,
+        technics: &TechnicalParam<Runtime>,
+        bounty: &BalanceOf<Runtime>,
+    ) -> (AccountId, ProofParam<Runtime>) {
+        crate::testing::make_bounty_party_proof(uri, technics, &(), bounty)
+    }
+
+    fn get_report_proof(
+        uri: &str,
+        index: &LiabilityIndex<Runtime>,
+        report: &TechnicalReport<Runtime>,
+    ) -> ProofParam<Runtime> {
+        crate::testing::make_report_proof(uri, index, report)
+    }
+
+    fn get_claim_proof(
+        uri: &str,
+        index: &LiabilityIndex<Runtime>,
+        claimed_at: &u64,
+    ) -> ProofParam<Runtime> {
+        crate::testing::make_claim_proof(uri, index, claimed_at)
+    }
+
+    fn get_cancel_proof(
+        uri: &str,
+        index: &LiabilityIndex<Runtime>,
+        expires_at: &BlockNumber<Runtime>,
+    ) -> ProofParam<Runtime> {
+        crate::testing::make_cancel_proof(uri, index, expires_at)
+    }
+
+    fn get_commit_proof(
+        uri: &str,
+        index: &LiabilityIndex<Runtime>,
+        commitment: &H256,
+    ) -> ProofParam<Runtime> {
+        crate::testing::make_commit_proof(uri, index, commitment)
+    }
+
+    /// Create liabilities of Alice and Bob with distinct technics, one per block from the
+    /// first one.
+    fn create_liabilities(count: u64) {
+        for block in 1..=count {
+            create_liability(block);
+        }
+    }
+
+    /// Create liability of Alice and Bob at given block, technics is block number.
+    fn create_liability(block: u64) {
+        System::set_block_number(block);
+        let technics = vec![block as u8];
+        let (alice, promisee_proof) = get_params_proof("//Alice", &technics, &());
+        let (bob, promisor_proof) = get_params_proof("//Bob", &technics, &());
+        assert_ok!(Liability::create(
+            Origin::none(),
+            technics,
+            (),
+            alice,
+            bob,
+            promisee_proof,
+            promisor_proof,
+        ));
+    }
+
+    /// Match pairs of orders by account commitments made in the previous block.
+    fn commit_and_reveal(who: &AccountId, pairs: &[(OrderId, OrderId)]) {
+        let salt = H256::repeat_byte(9);
+        for (demand_id, offer_id) in pairs {
+            let commitment = match_commitment(demand_id, offer_id, who, &salt);
+            assert_ok!(Liability::commit_match(
+                Origin::signed(who.clone()),
+                commitment
+            ));
+        }
+        System::set_block_number(System::block_number() + 1);
+        for (demand_id, offer_id) in pairs {
+            assert_ok!(Liability::reveal_match(
+                Origin::signed(who.clone()),
+                *demand_id,
+                *offer_id,
+                salt
+            ));
+        }
+    }
+
+    fn get_attested_report_proof(
+        uri: &str,
+        index: &LiabilityIndex<Runtime>,
+        report: &TechnicalReport<Runtime>,
+        firmware: &H256,
+    ) -> ProofParam<Runtime> {
+        let pair = sr25519::Pair::from_string(uri, None).unwrap();
+        <ProofSigner<sr25519::Pair> as ProofBuilder<
+            <Runtime as Trait>::Technics,
+            <Runtime as Trait>::Economics,
+            LiabilityIndex<Runtime>,
+            _,
+            _,
+        >>::proof_attested_report(index, report, firmware, pair)
+        .into()
+    }
+
+    #[test]
+    fn test_genesis_liabilities() {
+        let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+            .from_base58()
+            .unwrap();
+        let alice = get_params_proof("//Alice", &technics, &()).0;
+        let bob = get_params_proof("//Bob", &technics, &()).0;
+
+        let mut storage = frame_system::GenesisConfig::default()
+            .build_storage::<Runtime>()
+            .unwrap();
+        GenesisConfig::<Runtime> {
+            liabilities: vec![(technics.clone(), (), alice.clone(), bob.clone())],
+            lighthouses: vec![],
+            market_fees: vec![],
+        }
+        .assimilate_storage(&mut storage)
+        .unwrap();
+
+        sp_io::TestExternalities::from(storage).execute_with(|| {
+            assert_eq!(Liability::storage_version(), STORAGE_VERSION);
+            assert_eq!(Liability::latest_index(), 1);
+            let info = Liability::liability_info(0).unwrap();
+            assert_eq!(info.technics, technics);
+            assert_eq!(info.promisee, alice);
+            assert_eq!(info.promisor, bob);
+            assert_eq!(info.state, LiabilityState::Open);
+            assert_eq!(info.created_at, 0);
+            assert_eq!(
+                Liability::account_liability_list(&bob, 0, 10).items.len(),
+                1
+            );
+        });
+    }
+
+    #[test]
+    fn test_genesis_market() {
+        let charlie = account("//Charlie");
+        let mut storage = frame_system::GenesisConfig::default()
+            .build_storage::<Runtime>()
+            .unwrap();
+        // Lighthouse stake is reserved from genesis balance
+        pallet_balances::GenesisConfig::<Runtime> {
+            balances: vec![(charlie.clone(), 1_000)],
+        }
+        .assimilate_storage(&mut storage)
+        .unwrap();
+        GenesisConfig::<Runtime> {
+            liabilities: vec![],
+            lighthouses: vec![(0, charlie.clone(), 200)],
+            market_fees: vec![(0, Perbill::from_percent(5))],
+        }
+        .assimilate_storage(&mut storage)
+        .unwrap();
+
+        sp_io::TestExternalities::from(storage).execute_with(|| {
+            assert_eq!(Liability::lighthouses(0), vec![charlie.clone()]);
+            assert_eq!(Liability::lighthouse_stake(0, &charlie), Some(200));
+            assert_eq!(Balances::reserved_balance(&charlie), 200);
+            assert_eq!(Liability::market_fee(0), Perbill::from_percent(5));
+            assert_eq!(Liability::market_fee(1), Perbill::zero());
+        });
+    }
+
+    #[test]
+    fn test_liability_proofs() {
+        let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+            .from_base58()
+            .unwrap();
+        let economics = ();
+        let (sender, params_proof) = get_params_proof("//Alice", &technics, &economics);
+        let liability =
+            <Runtime as Trait>::Liability::new(technics, economics, sender.clone(), sender.clone());
+        assert_eq!(liability.check_params(&params_proof, &sender), true);
+
+        let index = 1;
+        let report = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+            .from_base58()
+            .unwrap();
+        let report_proof = get_report_proof("//Alice", &index, &report);
+        assert_eq!(liability.check_report(&index, &report, &report_proof), true);
+    }
+
+    #[test]
+    fn test_liability_lifecycle() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            assert_eq!(Liability::latest_index(), 0);
+
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let economics = ();
+
+            let (promisee, promisee_proof) = get_params_proof("//Alice", &technics, &economics);
+            let (promisor, promisor_proof) = get_params_proof("//Bob", &technics, &economics);
+
+            assert_err!(
+                Liability::create(
+                    Origin::none(),
+                    technics.clone(),
+                    economics.clone(),
+                    promisee.clone(),
+                    promisor.clone(),
+                    promisor_proof.clone(),
+                    promisor_proof.clone(),
+                ),
+                Error::<Runtime>::BadPromiseeProof
+            );
+            assert_eq!(Liability::latest_index(), 0);
+
+            assert_err!(
+                Liability::create(
+                    Origin::none(),
+                    technics.clone(),
+                    economics.clone(),
+                    promisee.clone(),
+                    promisor.clone(),
+                    promisee_proof.clone(),
+                    promisee_proof.clone(),
+                ),
+                Error::<Runtime>::BadPromisorProof
+            );
+            assert_eq!(Liability::latest_index(), 0);
+
+            assert_ok!(Liability::create(
+                Origin::none(),
+                technics,
+                economics,
+                promisee,
+                promisor,
+                promisee_proof,
+                promisor_proof
+            ));
+            assert_eq!(Liability::latest_index(), 1);
+            assert_eq!(Liability::is_finalized(0), false);
+            assert_eq!(Liability::created_at(0), 1);
+
+            System::set_block_number(2);
+
+            let index = 0;
+            let report = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let bad_proof = get_report_proof("//Alice", &index, &report);
+            let good_proof = get_report_proof("//Bob", &index, &report);
+
+            assert_err_ignore_postinfo!(
+                Liability::finalize(Origin::none(), 0, report.clone(), bad_proof),
+                Error::<Runtime>::BadReportProof
+            );
+            assert_eq!(Liability::is_finalized(0), false);
+
+            assert_ok!(Liability::finalize(Origin::none(), 0, report, good_proof));
+            assert_eq!(Liability::is_finalized(0), true);
+            assert_eq!(Liability::finalized_at(0), 2);
+        })
+    }
+
+    #[test]
+    fn test_agreement_getters() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (promisee, promisee_proof) = get_params_proof("//Alice", &technics, &());
+            let (promisor, promisor_proof) = get_params_proof("//Bob", &technics, &());
+            assert_ok!(Liability::create(
+                Origin::none(),
+                technics.clone(),
+                (),
+                promisee.clone(),
+                promisor.clone(),
+                promisee_proof,
+                promisor_proof
+            ));
+
+            // Parties and parameters are read back from decoded agreement
+            let encoded = Liability::liability(0).unwrap().encode();
+            let liability = <Runtime as Trait>::Liability::decode(&mut &encoded[..]).unwrap();
+            assert_eq!(liability.technics(), &technics);
+            assert_eq!(liability.economics(), &());
+            assert_eq!(liability.promisee(), &promisee);
+            assert_eq!(liability.promisor(), &promisor);
+        })
+    }
+
+    #[test]
+    fn test_liability_derives() {
+        let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+            .from_base58()
+            .unwrap();
+        let liability =
+            <Runtime as Trait>::Liability::new(technics, (), account("//Alice"), account("//Bob"));
+
+        // Decoded liability is cloned, compared and printed without approach bounds
+        let encoded = liability.encode();
+        let decoded = <Runtime as Trait>::Liability::decode(&mut &encoded[..]).unwrap();
+        assert_eq!(decoded.clone(), liability);
+        assert!(format!("{:?}", decoded).starts_with("SignedLiability"));
+        assert_eq!(format!("{:?}", PureIPFS), "PureIPFS");
+    }
+
+    #[test]
+    fn test_derive_next() {
+        let report = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+            .from_base58()
+            .unwrap();
+        assert_eq!(PureIPFS::derive_next(&report), Some(report));
+        assert_eq!(PureIPFS::derive_next(&Vec::new()), None);
+
+        // Next liability requires nothing
+        type Capable = RequiredCapabilities<PureIPFS>;
+        let required = Capabilities::from_ids(&[1]);
+        let next = Capable::derive_next(&report).unwrap();
+        assert_eq!(next, (report.clone(), Capabilities::default()));
+        assert_eq!(
+            Capable::required_capabilities(&(report.clone(), required.clone())),
+            required
+        );
+        assert_eq!(Capable::model(&next), PureIPFS::model(&report));
+    }
+
+    #[test]
+    fn test_create_chained() {
+        use frame_support::unsigned::ValidateUnsigned;
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let proofs = |technics: &Vec<u8>| {
+                let (promisee, promisee_proof) = get_params_proof("//Alice", technics, &());
+                let (promisor, promisor_proof) = get_params_proof("//Bob", technics, &());
+                (promisee, promisor, promisee_proof, promisor_proof)
+            };
+            let create = |technics: &Vec<u8>| {
+                let (promisee, promisor, promisee_proof, promisor_proof) = proofs(technics);
+                Liability::create(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    promisee,
+                    promisor,
+                    promisee_proof,
+                    promisor_proof,
+                )
+            };
+            let create_chained = |parent: u64, technics: &Vec<u8>| {
+                let (promisee, promisor, promisee_proof, promisor_proof) = proofs(technics);
+                Liability::create_chained(
+                    Origin::none(),
+                    parent,
+                    (),
+                    promisee,
+                    promisor,
+                    promisee_proof,
+                    promisor_proof,
+                )
+            };
+            let finalize = |index: u64, report: Vec<u8>| {
+                let proof = get_report_proof("//Bob", &index, &report);
+                Liability::finalize(Origin::none(), index, report, proof)
+            };
+            let next = b"QmNext".to_vec();
+
+            // Open liability has no report to seed follow-up yet
+            assert_ok!(create(&vec![1]));
+            let (promisee, promisor, promisee_proof, promisor_proof) = proofs(&next);
+            let call =
+                Call::create_chained(0, (), promisee, promisor, promisee_proof, promisor_proof);
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &call).map(|_| ()),
+                Err(InvalidTransaction::Future.into())
+            );
+            assert_err!(
+                create_chained(0, &next),
+                Error::<Runtime>::UnchainableReport
+            );
+
+            // Report of complete works is technics of chained liability
+            assert_ok!(finalize(0, next.clone()));
+            assert!(Liability::validate_unsigned(TransactionSource::External, &call).is_ok());
+            assert_ok!(create_chained(0, &next));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::LiabilityChained(Compact(1), Compact(0)))
+            );
+            let record = Liability::liability_record(1).unwrap();
+            assert_eq!(record.agreement.technics(), &next);
+
+            // Parties proofs are made for derived technics
+            System::set_block_number(2);
+            assert_err!(
+                create_chained(0, &vec![1]),
+                Error::<Runtime>::BadPromiseeProof
+            );
+
+            // Empty report can't seed follow-up
+            System::set_block_number(3);
+            assert_ok!(create(&vec![3]));
+            assert_ok!(finalize(2, Vec::new()));
+            assert_err!(
+                create_chained(2, &Vec::new()),
+                Error::<Runtime>::UnchainableReport
+            );
+        })
+    }
+
+    #[test]
+    fn test_liability_value() {
+        use super::economics::{Communism, MinReputation, OpenMarket};
+        type Market = OpenMarket<NamedReserve<Runtime>, AccountId>;
+
+        // Only monetary economics are valued
+        assert_eq!(<Communism as Valuable<u128>>::value(&()), None);
+        assert_eq!(<Market as Valuable<u128>>::value(&42), Some(42));
+        assert_eq!(
+            <MinReputation<Market> as Valuable<u128>>::value(&(42, 3)),
+            Some(42)
+        );
+
+        // Liability of free economics is worth its settled value
+        assert_eq!(Liability::liability_value(&()), 10);
+    }
+
+    #[test]
+    fn test_in_market_economics() {
+        use super::economics::{Communism, InMarket, MinReputation};
+
+        // Market is signed with economics and kept by scaling
+        assert_eq!(<Communism as Marketable>::market(&()), None);
+        assert_eq!(
+            <InMarket<Communism> as Marketable>::market(&((), 3)),
+            Some(3)
+        );
+        assert_eq!(
+            <MinReputation<InMarket<Communism>> as Marketable>::market(&(((), 3), 10)),
+            Some(3)
+        );
+        assert_eq!(
+            <InMarket<Communism> as Divisible>::scale(&((), 3), 5),
+            ((), 3)
+        );
+
+        // Economics without market take market of technics
+        let technics = b"delivery".to_vec();
+        TECHNICS_MARKETS.with(|markets| *markets.borrow_mut() = vec![(technics.clone(), 1)]);
+        assert_eq!(Liability::market_of(&technics, &()), 1);
+        assert_eq!(Liability::market_of(&b"sensing".to_vec(), &()), 0);
+    }
+
+    #[test]
+    fn test_named_reserve() {
+        new_test_ext().execute_with(|| {
+            let alice = account("//Alice");
+            let bob = account("//Bob");
+            type Reserve = NamedReserve<Runtime>;
+
+            // Deposit of another module is kept apart from liability reserves
+            assert_ok!(Balances::reserve(&alice, 300));
+            assert_ok!(Reserve::reserve_named(&RESERVE_ID, &alice, 100));
+            assert_eq!(Balances::reserved_balance(&alice), 400);
+            assert_eq!(Reserve::reserved_balance_named(&RESERVE_ID, &alice), 100);
+            assert_eq!(Reserve::reserved_balance_named(b"another0", &alice), 0);
+
+            // Releases are limited by funds reserved under identifier
+            assert_eq!(Reserve::unreserve_named(b"another0", &alice, 50), 50);
+            assert_eq!(Reserve::unreserve_named(&RESERVE_ID, &alice, 150), 50);
+            assert_eq!(Balances::reserved_balance(&alice), 300);
+            assert_eq!(Liability::named_reserve(RESERVE_ID, &alice), 0);
+
+            assert_ok!(Reserve::reserve_named(&RESERVE_ID, &alice, 100));
+            let (imbalance, not_slashed) = Reserve::slash_reserved_named(&RESERVE_ID, &alice, 130);
+            assert_eq!((imbalance.peek(), not_slashed), (100, 30));
+            assert_eq!(Balances::reserved_balance(&alice), 300);
+
+            // Funds moved to reserve of beneficiary stay under identifier
+            assert_ok!(Reserve::reserve_named(&RESERVE_ID, &alice, 100));
+            let status = BalanceStatus::Reserved;
+            assert_eq!(
+                Reserve::repatriate_reserved_named(&RESERVE_ID, &alice, &bob, 120, status),
+                Ok(20)
+            );
+            assert_eq!(Balances::reserved_balance(&alice), 300);
+            assert_eq!(Balances::reserved_balance(&bob), 100);
+            assert_eq!(Reserve::reserved_balance_named(&RESERVE_ID, &bob), 100);
+        });
+    }
+
+    #[test]
+    fn test_named_reserve_settlement() {
+        use super::economics::OpenMarket;
+        type Market = OpenMarket<NamedReserve<Runtime>, AccountId>;
+
+        new_test_ext().execute_with(|| {
+            let alice = account("//Alice");
+            let bob = account("//Bob");
+            assert_ok!(Balances::reserve(&alice, 300));
+
+            // Only liability escrow moves on success
+            assert_ok!(<Market as Settlement<AccountId>>::on_start(
+                &100, &alice, &bob
+            ));
+            assert_eq!(Balances::reserved_balance(&alice), 400);
+            let preview = |success| {
+                <Market as Settlement<AccountId>>::preview::<u128>(&100, &alice, &bob, success)
+            };
+            assert_eq!(
+                preview(true),
+                Payouts {
+                    transfers: vec![(alice.clone(), bob.clone(), 100)],
+                    refunds: vec![],
+                }
+            );
+            assert_eq!(
+                preview(false),
+                Payouts {
+                    transfers: vec![],
+                    refunds: vec![(alice.clone(), 100)],
+                }
+            );
+            assert_ok!(<Market as Settlement<AccountId>>::on_finish(
+                &100, &alice, &bob, true
+            ));
+            assert_eq!(Balances::reserved_balance(&alice), 300);
+            assert_eq!(Balances::free_balance(&bob), 1_100);
+
+            // Failed liability returns escrow, but never deposit of another module
+            assert_ok!(<Market as Settlement<AccountId>>::on_start(
+                &100, &alice, &bob
+            ));
+            assert_ok!(<Market as Settlement<AccountId>>::on_finish(
+                &100, &alice, &bob, false
+            ));
+            assert_eq!(Balances::reserved_balance(&alice), 300);
+            assert!(
+                <Market as Settlement<AccountId>>::on_finish(&100, &alice, &bob, false).is_err()
+            );
+            assert_eq!(Balances::reserved_balance(&alice), 300);
+
+            // Module calls release liability reserves only
+            assert_ok!(Liability::bond_orders(Origin::signed(alice.clone()), 50));
+            assert_eq!(Balances::reserved_balance(&alice), 350);
+            System::set_block_number(1);
+            assert_ok!(Liability::unbond_orders(Origin::signed(alice.clone())));
+            System::set_block_number(1 + BreachClaimPeriod::get());
+            assert_ok!(Liability::unbond_orders(Origin::signed(alice.clone())));
+            assert_eq!(Balances::reserved_balance(&alice), 300);
+            assert_eq!(Liability::named_reserve(RESERVE_ID, &alice), 0);
+        });
+    }
+
+    #[test]
+    fn test_dry_run() {
+        use frame_support::weights::GetDispatchInfo;
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = vec![1];
+            let (promisee, promisee_proof) = get_params_proof("//Alice", &technics, &());
+            let (promisor, promisor_proof) = get_params_proof("//Bob", &technics, &());
+            let dry_run_create = |promisee_proof| {
+                Liability::dry_run_create(
+                    technics.clone(),
+                    (),
+                    promisee.clone(),
+                    promisor.clone(),
+                    promisee_proof,
+                    promisor_proof.clone(),
+                )
+            };
+
+            // Dry run reports liability it would create and changes nothing
+            let root = sp_io::storage::root();
+            let create = Call::create(
+                technics.clone(),
+                (),
+                promisee.clone(),
+                promisor.clone(),
+                promisee_proof.clone(),
+                promisor_proof.clone(),
+            );
+            assert_eq!(
+                dry_run_create(promisee_proof.clone()),
+                Ok(DryRun {
+                    index: 0,
+                    weight: create.get_dispatch_info().weight,
+                })
+            );
+            assert_eq!(
+                dry_run_create(promisor_proof.clone()),
+                Err(Error::<Runtime>::BadPromiseeProof.into())
+            );
+            assert_eq!(sp_io::storage::root(), root);
+            assert_eq!(Liability::latest_index(), 0);
+            assert!(System::events().is_empty());
+
+            assert_ok!(Liability::create(
+                Origin::none(),
+                technics.clone(),
+                (),
+                promisee.clone(),
+                promisor.clone(),
+                promisee_proof.clone(),
+                promisor_proof.clone(),
+            ));
+            assert_eq!(
+                dry_run_create(promisee_proof),
+                Err(Error::<Runtime>::DuplicateAgreement.into())
+            );
+
+            let report = b"report".to_vec();
+            let proof = get_report_proof("//Bob", &0, &report);
+            let root = sp_io::storage::root();
+            let result = Liability::dry_run_finalize(0, report.clone(), proof.clone()).unwrap();
+            assert_eq!(result.index, 0);
+            let finalize = Call::finalize(0, report.clone(), proof.clone());
+            assert!(result.weight <= finalize.get_dispatch_info().weight);
+            let alice_proof = get_report_proof("//Alice", &0, &report);
+            assert_eq!(
+                Liability::dry_run_finalize(0, report.clone(), alice_proof),
+                Err(Error::<Runtime>::BadReportProof.into())
+            );
+            assert_eq!(
+                Liability::dry_run_finalize(1, report.clone(), proof.clone()),
+                Err(Error::<Runtime>::UnknownLiability.into())
+            );
+            assert_eq!(sp_io::storage::root(), root);
+            assert!(!Liability::is_finalized(0));
+
+            assert_ok!(Liability::finalize(
+                Origin::none(),
+                0,
+                report.clone(),
+                proof.clone()
+            ));
+            assert_eq!(
+                Liability::dry_run_finalize(0, report, proof),
+                Err(DispatchError::Other("already finalized"))
+            );
+        });
+    }
+
+    #[test]
+    fn test_liability_info() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            assert_eq!(Liability::liability_info(0), None);
+            assert_eq!(Liability::report_info(0), None);
+
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (promisee, promisee_proof) = get_params_proof("//Alice", &technics, &());
+            let (promisor, promisor_proof) = get_params_proof("//Bob", &technics, &());
+            for block in 1..=3 {
+                System::set_block_number(block);
+                assert_ok!(Liability::create(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    promisee.clone(),
+                    promisor.clone(),
+                    promisee_proof.clone(),
+                    promisor_proof.clone(),
+                ));
+            }
+
+            let info = Liability::liability_info(1).unwrap();
+            assert_eq!(info.technics, technics);
+            assert_eq!(info.technics_hash, technics_hash(&technics));
+            assert_eq!(
+                info.economics_summary,
+                EconomicsSummary {
+                    value: None,
+                    settled: 10,
+                    market: 0,
+                    deferred_funding: false,
+                }
+            );
+            assert_eq!(info.promisee, promisee);
+            assert_eq!(info.promisor, promisor);
+            assert_eq!(info.state, LiabilityState::Open);
+            assert_eq!(info.created_at, 2);
+            assert_eq!(info.finalized_at, None);
+
+            System::set_block_number(5);
+            let report = technics.clone();
+            let proof = get_report_proof("//Bob", &1, &report);
+            assert_ok!(Liability::finalize(
+                Origin::none(),
+                1,
+                report.clone(),
+                proof
+            ));
+
+            let info = Liability::liability_info(1).unwrap();
+            assert_eq!(info.state, LiabilityState::Finalized);
+            assert_eq!(info.finalized_at, Some(5));
+            assert_eq!(
+                Liability::report_info(1),
+                Some(ReportInfo {
+                    report,
+                    finalized_at: 5
+                })
+            );
+
+            let list = Liability::liability_list(1, 10);
+            assert_eq!(list.items.len(), 2);
+            assert_eq!(list.items[0].0, 1);
+            assert_eq!(list.items[1].0, 2);
+            assert_eq!((list.next, list.has_more), (3, false));
+            let list = Liability::liability_list(0, 1);
+            assert_eq!(list.items.len(), 1);
+            assert_eq!((list.next, list.has_more), (1, true));
+            assert_eq!(Liability::liability_list(3, 10).items.len(), 0);
+
+            let list = Liability::account_liability_list(&promisor, 1, 1);
+            assert_eq!(list.items.len(), 1);
+            assert_eq!(list.items[0].0, 1);
+            assert_eq!((list.next, list.has_more), (2, true));
+            assert_eq!(
+                Liability::account_liability_list(&promisee, 0, 10)
+                    .items
+                    .len(),
+                3
+            );
+            let (stranger, _) = get_params_proof("//Charlie", &technics, &());
+            let list = Liability::account_liability_list(&stranger, 0, 10);
+            assert_eq!(list.items.len(), 0);
+            assert_eq!((list.next, list.has_more), (0, false));
+        })
+    }
+
+    #[test]
+    fn test_counters() {
+        use frame_support::traits::OnFinalize;
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (promisee, promisee_proof) = get_params_proof("//Alice", &technics, &());
+            let (promisor, promisor_proof) = get_params_proof("//Bob", &technics, &());
+            for block in 1..=2 {
+                System::set_block_number(block);
+                assert_ok!(Liability::create(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    promisee.clone(),
+                    promisor.clone(),
+                    promisee_proof.clone(),
+                    promisor_proof.clone(),
+                ));
+            }
+            assert_eq!(
+                Liability::counters(),
+                LiabilityCounters {
+                    created: 2,
+                    finalized: 0,
+                    cancelled: 0,
+                    settled: 0,
+                }
+            );
+
+            // Failed report changes nothing
+            let bad_proof = get_report_proof("//Alice", &0, &technics);
+            assert_err_ignore_postinfo!(
+                Liability::finalize(Origin::none(), 0, technics.clone(), bad_proof),
+                Error::<Runtime>::BadReportProof
+            );
+            assert_eq!(Liability::counters().finalized, 0);
+
+            let proof = get_report_proof("//Bob", &0, &technics);
+            assert_ok!(Liability::finalize(
+                Origin::none(),
+                0,
+                technics.clone(),
+                proof
+            ));
+            let counters = LiabilityCounters {
+                created: 2,
+                finalized: 1,
+                cancelled: 0,
+                settled: 10,
+            };
+            assert_eq!(Liability::counters(), counters);
+
+            // Statistics emitted only at period end
+            Liability::on_finalize(4);
+            assert!(System::events().iter().all(|record| record.event
+                != MetaEvent::liability(RawEvent::EpochStats(counters.clone()))));
+            Liability::on_finalize(5);
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::EpochStats(counters))
+            );
+        })
+    }
+
+    #[test]
+    fn test_era_stats() {
+        new_test_ext().execute_with(|| {
+            create_liabilities(2);
+            let report = b"model".to_vec();
+            let proof = get_report_proof("//Bob", &0, &report);
+            assert_ok!(Liability::finalize(Origin::none(), 0, report, proof));
+            assert_eq!(Liability::outcomes().completed, 1);
+
+            // Snapshot is taken at the first block of the next era
+            Liability::on_initialize(9);
+            assert_eq!(Liability::era_stats(0), None);
+            System::set_block_number(10);
+            Liability::on_initialize(10);
+            assert_eq!(Liability::stats_era(), 1);
+            let snapshot = Liability::era_stats(0).unwrap();
+            assert_eq!(snapshot.until, 9);
+            assert_eq!(snapshot.counters, Liability::counters());
+            assert_eq!(snapshot.outcomes, Liability::outcomes());
+            assert_eq!(snapshot.market, Liability::market_totals());
+
+            // Era statistics is the difference of snapshots
+            let promisee_proof = get_cancel_proof("//Alice", &1, &15);
+            assert_ok!(Liability::cancel(
+                Origin::none(),
+                1,
+                15,
+                Some(promisee_proof),
+                None
+            ));
+            System::set_block_number(20);
+            Liability::on_initialize(20);
+            let next = Liability::era_stats(1).unwrap();
+            assert_eq!(next.counters.cancelled - snapshot.counters.cancelled, 1);
+            assert_eq!(next.counters.created, snapshot.counters.created);
+
+            // The oldest snapshot out of retention is removed
+            System::set_block_number(30);
+            Liability::on_initialize(30);
+            assert_eq!(Liability::era_stats(0), None);
+            assert!(Liability::era_stats(1).is_some());
+            assert!(Liability::era_stats(2).is_some());
+        })
+    }
+
+    #[test]
+    fn test_promisor_local_index() {
+        new_test_ext().execute_with(|| {
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (promisee, promisee_proof) = get_params_proof("//Alice", &technics, &());
+            let (bob, bob_proof) = get_params_proof("//Bob", &technics, &());
+            let (charlie, charlie_proof) = get_params_proof("//Charlie", &technics, &());
+            for (block, (promisor, promisor_proof)) in [
+                (bob.clone(), bob_proof.clone()),
+                (charlie.clone(), charlie_proof),
+                (bob.clone(), bob_proof),
+            ]
+            .iter()
+            .enumerate()
+            {
+                System::set_block_number(block as u64);
+                assert_ok!(Liability::create(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    promisee.clone(),
+                    promisor.clone(),
+                    promisee_proof.clone(),
+                    promisor_proof.clone(),
+                ));
+            }
+
+            assert_eq!(Liability::promisor_count(&bob), 2);
+            assert_eq!(Liability::promisor_count(&charlie), 1);
+            assert_eq!(Liability::promisor_liability(&bob, 1), Some(2));
+            assert_eq!(Liability::promisor_liability(&charlie, 0), Some(1));
+
+            let list = Liability::promisor_liability_list(&bob, 0, 10);
+            assert_eq!(list.items.len(), 2);
+            assert_eq!(list.items[0].0, 0);
+            assert_eq!(list.items[1].0, 2);
+            assert_eq!(list.next, 2);
+            let list = Liability::promisor_liability_list(&bob, 1, 10);
+            assert_eq!(list.items.len(), 1);
+            assert_eq!((list.next, list.has_more), (2, false));
+            assert_eq!(
+                Liability::promisor_liability_list(&promisee, 0, 10)
+                    .items
+                    .len(),
+                0
+            );
+        })
+    }
+
+    #[test]
+    fn test_list_pages() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let alice = account("//Alice");
+            let bob = account("//Bob");
+            let total = 2 * MAX_PAGE_ITEMS as u64 + 50;
+            for index in 0..total {
+                assert_ok!(Liability::issue(
+                    index.encode(),
+                    (),
+                    alice.clone(),
+                    bob.clone()
+                ));
+            }
+            for index in (0..total).filter(|index| index % 10 == 3) {
+                <Liabilities<Runtime>>::remove(index);
+            }
+            let expected: Vec<u64> = (0..total).filter(|index| index % 10 != 3).collect();
+
+            // Requests are cut to the cap, pruned liabilities are skipped by cursors
+            let follow = |list: &dyn Fn(u64) -> LiabilityPageOf<Runtime>| {
+                let mut indices = Vec::new();
+                let mut cursor = 0;
+                loop {
+                    let page = list(cursor);
+                    assert!(page.items.len() <= MAX_PAGE_ITEMS as usize);
+                    indices.extend(page.items.into_iter().map(|(index, _)| index));
+                    cursor = page.next;
+                    if !page.has_more {
+                        return (indices, cursor);
+                    }
+                }
+            };
+            let page = Liability::liability_list(0, u32::max_value());
+            assert_eq!(page.items.len(), MAX_PAGE_ITEMS as usize);
+            assert_eq!(page.next, 111);
+            assert!(page.has_more);
+            assert_eq!(
+                follow(&|start| Liability::liability_list(start, u32::max_value())),
+                (expected.clone(), total)
+            );
+            assert_eq!(
+                follow(&|start| Liability::account_liability_list(&alice, start, 1_000)),
+                (expected.clone(), total)
+            );
+            assert_eq!(
+                follow(&|start| Liability::promisor_liability_list(&bob, start, 1_000)),
+                (expected.clone(), total)
+            );
+
+            // The last page cursor lists liabilities created later
+            assert_ok!(Liability::issue(vec![], (), alice.clone(), bob.clone()));
+            let page = Liability::liability_list(total, 10);
+            assert_eq!(page.items.len(), 1);
+            assert_eq!(page.items[0].0, total);
+            assert_eq!((page.next, page.has_more), (total + 1, false));
+
+            // Scan of pruned range is bounded too
+            <LatestIndex<Runtime>>::put(total + 1 + 2 * MAX_PAGE_SCAN as u64);
+            let page = Liability::liability_list(total + 1, 10);
+            assert!(page.items.is_empty());
+            assert_eq!(page.next, total + 1 + MAX_PAGE_SCAN as u64);
+            assert!(page.has_more);
+            let page = Liability::created_between(2, u64::max_value(), 10);
+            assert!(page.indices.is_empty());
+            assert_eq!(page.next, Some(2 + MAX_PAGE_SCAN as u64));
+            assert_eq!(
+                Liability::created_between(1, 1, 1_000).indices.len(),
+                total as usize + 1
+            );
+        })
+    }
+
+    #[test]
+    fn test_created_between() {
+        use frame_support::traits::OnInitialize;
+
+        new_test_ext().execute_with(|| {
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (promisee, promisee_proof) = get_params_proof("//Alice", &technics, &());
+            for (block, promisor_uri) in
+                &[(1, "//Bob"), (1, "//Charlie"), (2, "//Bob"), (4, "//Bob")]
+            {
+                let (promisor, promisor_proof) = get_params_proof(promisor_uri, &technics, &());
+                System::set_block_number(*block);
+                assert_ok!(Liability::create(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    promisee.clone(),
+                    promisor,
+                    promisee_proof.clone(),
+                    promisor_proof,
+                ));
+            }
+
+            // Page is finished on block boundary
+            let page = Liability::created_between(1, 10, 1);
+            assert_eq!(page.indices, vec![0, 1]);
+            assert_eq!(page.next, Some(2));
+            assert!(!page.truncated);
+            let page = Liability::created_between(2, 10, 10);
+            assert_eq!(page.indices, vec![2, 3]);
+            assert_eq!(page.next, None);
+            assert_eq!(Liability::created_between(3, 3, 10).indices.len(), 0);
+
+            // Blocks out of retention window are pruned
+            Liability::on_initialize(12);
+            assert_eq!(Liability::created_pruned_until(), 2);
+            let page = Liability::created_between(1, 10, 10);
+            assert_eq!(page.indices, vec![2, 3]);
+            assert!(page.truncated);
+
+            // Pruning is bounded per block
+            Liability::on_initialize(100);
+            assert_eq!(
+                Liability::created_pruned_until(),
+                2 + MaxSweepItemsPerBlock::get() as u64
+            );
+        })
+    }
+
+    #[test]
+    fn test_history() {
+        new_test_ext().execute_with(|| {
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (promisee, promisee_proof) = get_params_proof("//Alice", &technics, &());
+            let (promisor, promisor_proof) = get_params_proof("//Bob", &technics, &());
+            System::set_block_number(3);
+            assert_ok!(Liability::create(
+                Origin::none(),
+                technics.clone(),
+                (),
+                promisee,
+                promisor.clone(),
+                promisee_proof,
+                promisor_proof,
+            ));
+            assert_eq!(Liability::history_of(0), vec![(3, Transition::Created)]);
+
+            System::set_block_number(5);
+            let proof = get_report_proof("//Bob", &0, &technics);
+            assert_ok!(Liability::finalize(Origin::none(), 0, technics, proof));
+            assert_eq!(
+                Liability::history_of(0),
+                vec![
+                    (3, Transition::Created),
+                    (5, Transition::Reported(promisor.clone()))
+                ]
+            );
+
+            // History length is bounded, the oldest transitions are dropped
+            for block in 0..MAX_HISTORY_LENGTH as u64 {
+                Liability::record_transition(0, 10 + block, Transition::Reported(promisor.clone()));
+            }
+            let history = Liability::history_of(0);
+            assert_eq!(history.len(), MAX_HISTORY_LENGTH as usize);
+            assert_eq!(history[0].0, 10);
+        })
+    }
+
+    #[test]
+    fn test_unsigned_tags() {
+        use frame_support::unsigned::ValidateUnsigned;
+
+        new_test_ext().execute_with(|| {
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (promisee, promisee_proof) = get_params_proof("//Alice", &technics, &());
+            let (promisor, promisor_proof) = get_params_proof("//Bob", &technics, &());
+            let create = Call::create(
+                technics.clone(),
+                (),
+                promisee.clone(),
+                promisor.clone(),
+                promisee_proof.clone(),
+                promisor_proof.clone(),
+            );
+            let provides = |call| {
+                Liability::validate_unsigned(TransactionSource::External, call)
+                    .unwrap()
+                    .provides
+            };
+
+            // Identical creates conflict in the pool
+            let tags = provides(&create);
+            assert_eq!(tags, provides(&create));
+
+            // Report for liability that isn't created yet
+            let proof = get_report_proof("//Bob", &0, &technics);
+            let finalize = Call::finalize(0, technics.clone(), proof.clone());
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &finalize),
+                InvalidTransaction::Future.into()
+            );
+
+            // The same agreement is a new liability once previous one is included
+            assert_ok!(Liability::create(
+                Origin::none(),
+                technics.clone(),
+                (),
+                promisee,
+                promisor,
+                promisee_proof,
+                promisor_proof,
+            ));
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &create),
+                ValidityError::NonceMismatch.into()
+            );
+            System::set_block_number(1);
+            assert_ne!(tags, provides(&create));
+
+            // Any reports for the same liability conflict
+            let finalize_tags = provides(&finalize);
+            let other_report = vec![0xde, 0xad];
+            let other_proof = get_report_proof("//Bob", &0, &other_report);
+            assert_eq!(
+                finalize_tags,
+                provides(&Call::finalize(0, other_report, other_proof))
+            );
+
+            assert_ok!(Liability::finalize(Origin::none(), 0, technics, proof));
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &finalize),
+                ValidityError::AlreadySettled.into()
+            );
+        })
+    }
+
+    /// Value of module constant declared in metadata.
+    fn metadata_constant<V: Decode>(name: &str) -> V {
+        use frame_support::metadata::{DecodeDifferent, DefaultByte};
+
+        let constant = Liability::module_constants_metadata()
+            .iter()
+            .find(|constant| matches!(constant.name, DecodeDifferent::Encode(n) if n == name))
+            .expect("constant is declared");
+        match &constant.value {
+            DecodeDifferent::Encode(getter) => {
+                V::decode(&mut &getter.0.default_byte()[..]).expect("constant is decodable")
+            }
+            DecodeDifferent::Decoded(_) => unreachable!("module metadata is static"),
+        }
+    }
+
+    #[test]
+    fn test_module_constants() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let constants = Liability::constants();
+            assert_eq!(
+                metadata_constant::<u32>("MaxUnsignedPerBlock"),
+                constants.max_unsigned_per_block
+            );
+            assert_eq!(
+                metadata_constant::<u32>("MaxPairPerBlock"),
+                constants.max_pair_per_block
+            );
+            assert_eq!(
+                metadata_constant::<u32>("MaxTechnicsLength"),
+                constants.max_technics_length
+            );
+            assert_eq!(
+                metadata_constant::<u32>("MaxReportLength"),
+                constants.max_report_length
+            );
+            assert_eq!(metadata_constant::<u32>("MaxTags"), constants.max_tags);
+            assert_eq!(
+                metadata_constant::<u32>("MaxTaggedLiabilities"),
+                constants.max_tagged_liabilities
+            );
+            assert_eq!(
+                metadata_constant::<bool>("PromisorWhitelist"),
+                constants.promisor_whitelist
+            );
+            assert_eq!(
+                metadata_constant::<u128>("IdentityThreshold"),
+                constants.identity_threshold
+            );
+            assert_eq!(
+                metadata_constant::<u64>("MinForceFinalizeAge"),
+                constants.min_force_finalize_age
+            );
+            assert_eq!(
+                metadata_constant::<u128>("OrderDeposit"),
+                constants.order_deposit
+            );
+            assert_eq!(
+                metadata_constant::<u32>("MaxOpenOrdersPerAccount"),
+                constants.max_open_orders_per_account
+            );
+            assert_eq!(
+                metadata_constant::<u32>("MaxOrdersPerMarket"),
+                constants.max_orders_per_market
+            );
+            assert_eq!(
+                metadata_constant::<u32>("MinFillQuantity"),
+                constants.min_fill_quantity
+            );
+            assert_eq!(
+                metadata_constant::<Perbill>("ExpiredOrderSlash"),
+                constants.expired_order_slash
+            );
+            assert_eq!(
+                metadata_constant::<u128>("ReportPreimageDeposit"),
+                constants.report_preimage_deposit
+            );
+            assert_eq!(
+                metadata_constant::<u64>("ReportChallengeWindow"),
+                constants.report_challenge_window
+            );
+            assert_eq!(
+                metadata_constant::<u32>("MaxRecentMatches"),
+                constants.max_recent_matches
+            );
+            assert_eq!(
+                metadata_constant::<u128>("LighthouseMinStake"),
+                constants.lighthouse_min_stake
+            );
+            assert_eq!(
+                metadata_constant::<u32>("MaxLighthouses"),
+                constants.max_lighthouses
+            );
+            assert_eq!(
+                metadata_constant::<u64>("LighthouseSlot"),
+                constants.lighthouse_slot
+            );
+            assert_eq!(
+                metadata_constant::<Perbill>("LighthouseFeeShare"),
+                constants.lighthouse_fee_share
+            );
+            assert_eq!(
+                metadata_constant::<u64>("BreachClaimPeriod"),
+                constants.breach_claim_period
+            );
+            assert_eq!(
+                metadata_constant::<u128>("OrderBreachSlash"),
+                constants.order_breach_slash
+            );
+            assert_eq!(
+                metadata_constant::<u64>("FundingDeadline"),
+                constants.funding_deadline
+            );
+            assert_eq!(
+                metadata_constant::<u64>("RevealWindow"),
+                constants.reveal_window
+            );
+            assert_eq!(
+                metadata_constant::<u64>("ClaimTolerance"),
+                constants.claim_tolerance
+            );
+            assert_eq!(
+                metadata_constant::<u64>("MatchCommitWindow"),
+                constants.match_commit_window
+            );
+            assert_eq!(
+                metadata_constant::<u32>("MaxMatchCommits"),
+                constants.max_match_commits
+            );
+            assert_eq!(
+                metadata_constant::<u128>("WitnessMinStake"),
+                constants.witness_min_stake
+            );
+            assert_eq!(
+                metadata_constant::<u32>("MaxWitnesses"),
+                constants.max_witnesses
+            );
+            assert_eq!(
+                metadata_constant::<u128>("WitnessSlash"),
+                constants.witness_slash
+            );
+            assert_eq!(metadata_constant::<u64>("StatsPeriod"), StatsPeriod::get());
+            assert_eq!(
+                metadata_constant::<u64>("StatsEraLength"),
+                constants.stats_era_length
+            );
+            assert_eq!(
+                metadata_constant::<u32>("StatsRetention"),
+                constants.stats_retention
+            );
+            assert_eq!(
+                metadata_constant::<u128>("PoolMinStake"),
+                constants.pool_min_stake
+            );
+            assert_eq!(
+                metadata_constant::<u32>("MaxPoolSize"),
+                constants.max_pool_size
+            );
+            assert_eq!(
+                metadata_constant::<u64>("AssignmentDelay"),
+                constants.assignment_delay
+            );
+            assert_eq!(
+                metadata_constant::<u64>("AcknowledgementWindow"),
+                constants.acknowledgement_window
+            );
+            assert_eq!(
+                metadata_constant::<u128>("AcknowledgementDeposit"),
+                constants.acknowledgement_deposit
+            );
+            assert_eq!(
+                metadata_constant::<u64>("MaxLiabilityLifetime"),
+                constants.max_liability_lifetime
+            );
+            assert_eq!(
+                metadata_constant::<u128>("ExpiryIncentive"),
+                constants.expiry_incentive
+            );
+            assert_eq!(
+                metadata_constant::<u32>("DigestBloomBits"),
+                constants.digest_bloom_bits
+            );
+            assert_eq!(
+                metadata_constant::<u8>("DigestBloomHashes"),
+                constants.digest_bloom_hashes
+            );
+            assert_eq!(
+                metadata_constant::<u64>("MinReportDelay"),
+                constants.min_report_delay
+            );
+            assert_eq!(
+                metadata_constant::<u128>("ZeroValueDeposit"),
+                constants.zero_value_deposit
+            );
+            assert_eq!(
+                metadata_constant::<u32>("MaxZeroValuePerEra"),
+                constants.max_zero_value_per_era
+            );
+            assert_eq!(
+                metadata_constant::<TransactionPriority>("FinalizePriority"),
+                FinalizePriority::get()
+            );
+
+            // Limits enforced by calls are the declared ones
+            let max_technics = constants.max_technics_length as usize;
+            assert_ok!(Liability::ensure_technics_length(&vec![
+                0;
+                max_technics - 2
+            ]));
+            assert_err!(
+                Liability::ensure_technics_length(&vec![0; max_technics - 1]),
+                Error::<Runtime>::TechnicsTooLong
+            );
+            let report = vec![0; constants.max_report_length as usize - 1];
+            let proof = get_report_proof("//Bob", &0, &report);
+            assert_err_ignore_postinfo!(
+                Liability::finalize(Origin::none(), 0, report, proof),
+                Error::<Runtime>::ReportTooLong
+            );
+            let tags = [Tag::Delivery, Tag::Sensing, Tag::Compute, Tag::Inspection];
+            let max_tags = constants.max_tags as usize;
+            assert_ok!(Liability::ensure_tags(&tags[..max_tags]));
+            assert_err!(
+                Liability::ensure_tags(&tags[..max_tags + 1]),
+                Error::<Runtime>::TooManyTags
+            );
+            let parties = (account("//Alice"), account("//Bob"));
+            <PairCreatesInBlock<Runtime>>::insert(1, &parties, constants.max_pair_per_block - 1);
+            assert_ok!(Liability::check_rate_limits(Some((&parties.0, &parties.1))));
+            <PairCreatesInBlock<Runtime>>::insert(1, &parties, constants.max_pair_per_block);
+            assert_err!(
+                Liability::check_rate_limits(Some((&parties.0, &parties.1))),
+                Error::<Runtime>::TooManyPairLiabilities
+            );
+            <UnsignedInBlock>::put(constants.max_unsigned_per_block);
+            assert_err!(
+                Liability::check_rate_limits(None),
+                Error::<Runtime>::TooManyUnsignedCalls
+            );
+        })
+    }
+
+    #[test]
+    fn test_pre_validate_create() {
+        use frame_support::unsigned::ValidateUnsigned;
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = b"QmTask".to_vec();
+            let (alice, bob, dave) = (account("//Alice"), account("//Bob"), account("//Dave"));
+            // Proofs of another agreement are never verified by cheap rejections
+            let (_, bad_promisee_proof) = get_params_proof("//Alice", &b"QmOther".to_vec(), &());
+            let (_, bad_promisor_proof) = get_params_proof("//Bob", &b"QmOther".to_vec(), &());
+            let check = |promisor: &AccountId,
+                         error: Error<Runtime>,
+                         validity: TransactionValidityError| {
+                let call = Call::create(
+                    technics.clone(),
+                    (),
+                    alice.clone(),
+                    promisor.clone(),
+                    bad_promisee_proof.clone(),
+                    bad_promisor_proof.clone(),
+                );
+                assert_eq!(
+                    Liability::validate_unsigned(TransactionSource::External, &call).map(|_| ()),
+                    Err(validity)
+                );
+                assert_err!(
+                    Liability::create(
+                        Origin::none(),
+                        technics.clone(),
+                        (),
+                        alice.clone(),
+                        promisor.clone(),
+                        bad_promisee_proof.clone(),
+                        bad_promisor_proof.clone(),
+                    ),
+                    error
+                );
+            };
+
+            <UnsignedInBlock>::put(8);
+            check(
+                &bob,
+                Error::<Runtime>::TooManyUnsignedCalls,
+                ValidityError::RateLimited.into(),
+            );
+            <UnsignedInBlock>::put(0);
+
+            assert_ok!(Liability::blacklist(Origin::root(), bob.clone()));
+            check(
+                &bob,
+                Error::<Runtime>::Blacklisted,
+                ValidityError::NotPermitted.into(),
+            );
+            assert_ok!(Liability::unblacklist(Origin::root(), bob.clone()));
+
+            ACK_WINDOW.with(|window| *window.borrow_mut() = 5);
+            check(
+                &dave,
+                Error::<Runtime>::CannotAffordDeposit,
+                InvalidTransaction::Payment.into(),
+            );
+            ACK_WINDOW.with(|window| *window.borrow_mut() = 0);
+
+            let (_, promisee_proof) = get_params_proof("//Alice", &technics, &());
+            let (_, promisor_proof) = get_params_proof("//Bob", &technics, &());
+            assert_ok!(Liability::create(
+                Origin::none(),
+                technics.clone(),
+                (),
+                alice.clone(),
+                bob.clone(),
+                promisee_proof,
+                promisor_proof,
+            ));
+            check(
+                &bob,
+                Error::<Runtime>::DuplicateAgreement,
+                ValidityError::NonceMismatch.into(),
+            );
+
+            // Proofs are verified when cheap checks pass
+            System::set_block_number(2);
+            check(
+                &bob,
+                Error::<Runtime>::BadPromiseeProof,
+                ValidityError::BadPromiseeProof.into(),
+            );
+        })
+    }
+
+    #[test]
+    fn test_rate_limits() {
+        use frame_support::{traits::OnInitialize, unsigned::ValidateUnsigned};
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            // Distinct agreements of the same parties differ by technics nonce
+            let params = |promisee_uri, promisor_uri, nonce: u8| {
+                let technics = [&technics[..], &[nonce]].concat();
+                let (promisee, promisee_proof) = get_params_proof(promisee_uri, &technics, &());
+                let (promisor, promisor_proof) = get_params_proof(promisor_uri, &technics, &());
+                (technics, promisee, promisor, promisee_proof, promisor_proof)
+            };
+            let create = |promisee_uri, promisor_uri, nonce| {
+                let (technics, promisee, promisor, promisee_proof, promisor_proof) =
+                    params(promisee_uri, promisor_uri, nonce);
+                Liability::create(
+                    Origin::none(),
+                    technics,
+                    (),
+                    promisee,
+                    promisor,
+                    promisee_proof,
+                    promisor_proof,
+                )
+            };
+            let validate = |promisee_uri, promisor_uri, nonce| {
+                let (technics, promisee, promisor, promisee_proof, promisor_proof) =
+                    params(promisee_uri, promisor_uri, nonce);
+                let call = Call::create(
+                    technics,
+                    (),
+                    promisee,
+                    promisor,
+                    promisee_proof,
+                    promisor_proof,
+                );
+                Liability::validate_unsigned(TransactionSource::External, &call).map(|_| ())
+            };
+
+            // Pair limit
+            for nonce in 0..3 {
+                assert_ok!(create("//Alice", "//Bob", nonce));
+            }
+            assert_eq!(
+                validate("//Alice", "//Bob", 3),
+                Err(ValidityError::RateLimited.into())
+            );
+            assert_err!(
+                create("//Alice", "//Bob", 3),
+                Error::<Runtime>::TooManyPairLiabilities
+            );
+
+            // Block limit
+            for nonce in 0..3 {
+                assert_ok!(create("//Alice", "//Charlie", nonce));
+            }
+            for nonce in 0..2 {
+                assert_ok!(create("//Bob", "//Charlie", nonce));
+            }
+            assert_eq!(Liability::unsigned_in_block(), 8);
+            assert_eq!(
+                validate("//Bob", "//Charlie", 2),
+                Err(ValidityError::RateLimited.into())
+            );
+            let (limited, promisee, promisor, promisee_proof, promisor_proof) =
+                params("//Bob", "//Charlie", 2);
+            assert_eq!(
+                Liability::pre_dispatch(&Call::create(
+                    limited,
+                    (),
+                    promisee,
+                    promisor,
+                    promisee_proof,
+                    promisor_proof,
+                )),
+                Err(InvalidTransaction::ExhaustsResources.into())
+            );
+            assert_err!(
+                create("//Bob", "//Charlie", 2),
+                Error::<Runtime>::TooManyUnsignedCalls
+            );
+            let proof = get_report_proof("//Bob", &0, &technics);
+            assert_err_ignore_postinfo!(
+                Liability::finalize(Origin::none(), 0, technics.clone(), proof.clone()),
+                Error::<Runtime>::TooManyUnsignedCalls
+            );
+
+            // Limits are reset at the end of the block
+            Liability::on_finalize(1);
+            System::set_block_number(2);
+            assert_ok!(Liability::ensure_block_state_cleared());
+            let (_, alice, bob, _, _) = params("//Alice", "//Bob", 0);
+            assert_eq!(Liability::pair_creates_in_block(1, (alice, bob)), 0);
+            assert_eq!(Liability::unsigned_in_block(), 0);
+            assert_ok!(validate("//Alice", "//Bob", 3));
+            assert_ok!(Liability::finalize(
+                Origin::none(),
+                0,
+                technics.clone(),
+                proof
+            ));
+        })
+    }
+
+    #[test]
+    fn test_promisor_whitelist() {
+        use frame_support::unsigned::ValidateUnsigned;
+
+        new_test_ext().execute_with(|| {
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (promisee, promisee_proof) = get_params_proof("//Alice", &technics, &());
+            let (promisor, promisor_proof) = get_params_proof("//Bob", &technics, &());
+            let create = || {
+                Liability::create(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    promisee.clone(),
+                    promisor.clone(),
+                    promisee_proof.clone(),
+                    promisor_proof.clone(),
+                )
+            };
+
+            // Whitelist is disabled, any promisor accepted
+            assert_ok!(create());
+
+            PROMISOR_WHITELIST.with(|enabled| *enabled.borrow_mut() = true);
+            assert_err!(create(), Error::<Runtime>::UnapprovedPromisor);
+            let call = Call::create(
+                technics.clone(),
+                (),
+                promisee.clone(),
+                promisor.clone(),
+                promisee_proof.clone(),
+                promisor_proof.clone(),
+            );
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &call).map(|_| ()),
+                Err(ValidityError::NotPermitted.into())
+            );
+
+            // Only admin approves promisors
+            assert_err!(
+                Liability::add_promisor(Origin::none(), promisor.clone()),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_ok!(Liability::add_promisor(Origin::root(), promisor.clone()));
+            System::set_block_number(1);
+            assert_ok!(create());
+
+            // Open liabilities could be finalized after approval revoked
+            assert_ok!(Liability::remove_promisor(Origin::root(), promisor.clone()));
+            assert_err!(create(), Error::<Runtime>::UnapprovedPromisor);
+            let proof = get_report_proof("//Bob", &1, &technics);
+            assert_ok!(Liability::finalize(
+                Origin::none(),
+                1,
+                technics.clone(),
+                proof
+            ));
+
+            PROMISOR_WHITELIST.with(|enabled| *enabled.borrow_mut() = false);
+        })
+    }
+
+    #[test]
+    fn test_identity_threshold() {
+        new_test_ext().execute_with(|| {
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let create = |promisee_uri| {
+                let (promisee, promisee_proof) = get_params_proof(promisee_uri, &technics, &());
+                let (promisor, promisor_proof) = get_params_proof("//Charlie", &technics, &());
+                Liability::create(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    promisee,
+                    promisor,
+                    promisee_proof,
+                    promisor_proof,
+                )
+            };
+
+            // Liability value is exactly at the threshold
+            IDENTITY_THRESHOLD.with(|threshold| *threshold.borrow_mut() = 10);
+            assert_ok!(create("//Bob"));
+
+            // Liability value exceeds the threshold
+            IDENTITY_THRESHOLD.with(|threshold| *threshold.borrow_mut() = 9);
+            assert_err!(create("//Bob"), Error::<Runtime>::IdentityRequired);
+            assert_ok!(create("//Alice"));
+
+            IDENTITY_THRESHOLD.with(|threshold| *threshold.borrow_mut() = u128::max_value());
+        })
+    }
+
+    #[test]
+    fn test_hooks() {
+        new_test_ext().execute_with(|| {
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (promisee, promisee_proof) = get_params_proof("//Alice", &technics, &());
+            let (promisor, promisor_proof) = get_params_proof("//Bob", &technics, &());
+            assert_ok!(Liability::create(
+                Origin::none(),
+                technics.clone(),
+                (),
+                promisee,
+                promisor,
+                promisee_proof,
+                promisor_proof,
+            ));
+            HOOK_CALLS.with(|calls| assert_eq!(*calls.borrow(), vec![(0, None)]));
+
+            // Rejected report isn't passed to handlers
+            let bad_proof = get_report_proof("//Alice", &0, &technics);
+            assert_err_ignore_postinfo!(
+                Liability::finalize(Origin::none(), 0, technics.clone(), bad_proof),
+                Error::<Runtime>::BadReportProof
+            );
+            HOOK_CALLS.with(|calls| assert_eq!(calls.borrow().len(), 1));
+
+            let proof = get_report_proof("//Bob", &0, &technics);
+            assert_ok!(Liability::finalize(Origin::none(), 0, technics, proof));
+            HOOK_CALLS.with(|calls| assert_eq!(*calls.borrow(), vec![(0, None), (0, Some(true))]));
+        })
+    }
+
+    #[test]
+    fn test_failing_hook() {
+        new_test_ext().execute_with(|| {
+            let dave = account("//Dave");
+            let hook_failed = |index, error| {
+                System::events().iter().any(|record| {
+                    record.event
+                        == MetaEvent::liability(RawEvent::HookFailed(Compact(index), 4, error))
+                })
+            };
+            let hook_written = || sp_io::storage::get(FAILING_HOOK_KEY).is_some();
+
+            // Liability is created when handler fails, handler writes are reverted
+            ECONOMICS_PAYOUT.with(|payout| *payout.borrow_mut() = Some(dave.clone()));
+            HOOK_FAULT.with(|fault| *fault.borrow_mut() = HookFault::Error);
+            create_liability(1);
+            assert!(Liability::liability_record(0).is_some());
+            assert!(hook_failed(0, DispatchError::Other("failing hook")));
+            assert!(!hook_written());
+
+            // Report settles and other handlers run when handler fails
+            let proof = get_report_proof("//Bob", &0, &vec![1]);
+            assert_ok!(Liability::finalize(Origin::none(), 0, vec![1], proof));
+            assert_eq!(
+                Liability::liability_record(0).unwrap().state,
+                RecordState::Reported
+            );
+            assert_eq!(Balances::free_balance(&dave), 10);
+            assert_eq!(Reputation::reputation_of(&account("//Bob")).completed, 1);
+            HOOK_CALLS.with(|calls| assert_eq!(*calls.borrow(), vec![(0, None), (0, Some(true))]));
+            assert!(!hook_written());
+
+            // Handler exceeding weight budget is reverted
+            HOOK_FAULT.with(|fault| *fault.borrow_mut() = HookFault::Overweight);
+            create_liability(2);
+            assert!(hook_failed(1, HOOK_OVERWEIGHT));
+            assert!(!hook_written());
+
+            // Writes of succeeded handler are kept
+            HOOK_FAULT.with(|fault| *fault.borrow_mut() = HookFault::None);
+            create_liability(3);
+            assert!(hook_written());
+            assert!(!hook_failed(2, DispatchError::Other("failing hook")));
+            ECONOMICS_PAYOUT.with(|payout| *payout.borrow_mut() = None);
+        })
+    }
+
+    #[test]
+    fn test_min_report_delay() {
+        use frame_support::unsigned::ValidateUnsigned;
+
+        new_test_ext().execute_with(|| {
+            REPORT_DELAY.with(|delay| *delay.borrow_mut() = 2);
+            create_liability(1);
+            let report = b"model".to_vec();
+            let proof = get_report_proof("//Bob", &0, &report);
+            let finalize = Call::finalize(0, report.clone(), proof.clone());
+            let commitment = report_commitment(&report, &H256::repeat_byte(7));
+            let commit_proof = get_commit_proof("//Bob", &0, &commitment);
+            let commit = Call::commit_report(0, commitment, commit_proof.clone());
+
+            // Report of too young liability is rejected, it waits in the pool
+            for block in 1..3 {
+                System::set_block_number(block);
+                assert_err_ignore_postinfo!(
+                    Liability::finalize(Origin::none(), 0, report.clone(), proof.clone()),
+                    Error::<Runtime>::ReportTooEarly
+                );
+                assert_err!(
+                    Liability::commit_report(Origin::none(), 0, commitment, commit_proof.clone()),
+                    Error::<Runtime>::ReportTooEarly
+                );
+                for call in &[&finalize, &commit] {
+                    assert_eq!(
+                        Liability::validate_unsigned(TransactionSource::External, call),
+                        InvalidTransaction::Future.into()
+                    );
+                }
+            }
+
+            // Report is accepted exactly at the delay
+            System::set_block_number(3);
+            assert!(Liability::validate_unsigned(TransactionSource::External, &finalize).is_ok());
+            assert!(Liability::validate_unsigned(TransactionSource::External, &commit).is_ok());
+            assert_ok!(Liability::finalize(Origin::none(), 0, report, proof));
+
+            // Zero delay allows report in the block of creation
+            REPORT_DELAY.with(|delay| *delay.borrow_mut() = 0);
+            create_liability(4);
+            let report = vec![4];
+            let proof = get_report_proof("//Bob", &1, &report);
+            assert_ok!(Liability::finalize(Origin::none(), 1, report, proof));
+        })
+    }
+
+    /// Sign payload by seed of promisor firmware through C ABI.
+    fn ffi_sign(seed: &[u8; 32], payload: &[u8]) -> Signature {
+        use robonomics_liability_ffi::*;
+
+        let mut signature = [0u8; LIABILITY_SIGNATURE_LEN];
+        let written = unsafe {
+            liability_sign_sr25519(
+                seed.as_ptr(),
+                payload.as_ptr(),
+                payload.len(),
+                signature.as_mut_ptr(),
+            )
+        };
+        assert_eq!(written, LIABILITY_SIGNATURE_LEN as isize);
+        sr25519::Signature::from_raw(signature).into()
+    }
+
+    #[test]
+    fn test_ffi_proofs() {
+        use robonomics_liability_ffi::*;
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let seed = [7u8; 32];
+            let mut public = [0u8; LIABILITY_PUBLIC_LEN];
+            unsafe { liability_public_sr25519(seed.as_ptr(), public.as_mut_ptr()) };
+            let firmware: AccountId = sr25519::Public::from_raw(public).into();
+
+            // Firmware signs parameters of liability
+            let technics = vec![1];
+            let mut payload = [0u8; 64];
+            let len = unsafe {
+                liability_params_payload(
+                    technics.as_ptr(),
+                    technics.len(),
+                    std::ptr::null(),
+                    0,
+                    payload.as_mut_ptr(),
+                    payload.len(),
+                )
+            };
+            let promisor_proof = ffi_sign(&seed, &payload[..len as usize]);
+            let (alice, promisee_proof) = get_params_proof("//Alice", &technics, &());
+            assert_ok!(Liability::create(
+                Origin::none(),
+                technics,
+                (),
+                alice,
+                firmware,
+                promisee_proof,
+                promisor_proof,
+            ));
+
+            // Firmware signs its report
+            let report = b"done".to_vec();
+            let mut reported = |index| {
+                let len = unsafe {
+                    liability_report_payload(
+                        index,
+                        report.as_ptr(),
+                        report.len(),
+                        payload.as_mut_ptr(),
+                        payload.len(),
+                    )
+                };
+                ffi_sign(&seed, &payload[..len as usize])
+            };
+            assert_err_ignore_postinfo!(
+                Liability::finalize(Origin::none(), 0, report.clone(), reported(1)),
+                Error::<Runtime>::BadReportProof
+            );
+            assert_ok!(Liability::finalize(
+                Origin::none(),
+                0,
+                report.clone(),
+                reported(0)
+            ));
+        })
+    }
+
+    #[test]
+    fn test_force_finalize() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (promisor, promisor_proof) = get_params_proof("//Bob", &technics, &());
+            for promisee_uri in &["//Alice", "//Charlie"] {
+                let (promisee, promisee_proof) = get_params_proof(promisee_uri, &technics, &());
+                assert_ok!(Liability::create(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    promisee,
+                    promisor.clone(),
+                    promisee_proof,
+                    promisor_proof.clone(),
+                ));
+            }
+
+            assert_err_ignore_postinfo!(
+                Liability::force_finalize(Origin::none(), 0, true),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_err_ignore_postinfo!(
+                Liability::force_finalize(Origin::root(), 0, true),
+                Error::<Runtime>::LiabilityTooYoung
+            );
+
+            System::set_block_number(6);
+            assert_ok!(Liability::force_finalize(Origin::root(), 0, true));
+            assert_ok!(Liability::force_finalize(Origin::root(), 1, false));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::ForceFinalized(Compact(1), false, 6))
+            );
+            assert!(Liability::is_finalized(0));
+            assert_eq!(Liability::forced_outcome(0), Some(true));
+            assert_eq!(Liability::forced_outcome(1), Some(false));
+            assert_eq!(Liability::report_info(0), None);
+            assert_eq!(Liability::finalized_at(1), 6);
+            assert_eq!(
+                Liability::history_of(1).last(),
+                Some(&(6, Transition::ForceFinalized(false)))
+            );
+            let counters = Liability::counters();
+            assert_eq!(counters.finalized, 1);
+            assert_eq!(counters.cancelled, 1);
+            assert_eq!(counters.settled, 10);
+
+            assert_err_ignore_postinfo!(
+                Liability::force_finalize(Origin::root(), 0, true),
+                Error::<Runtime>::AlreadyFinalized
+            );
+            let proof = get_report_proof("//Bob", &0, &technics);
+            assert!(Liability::finalize(Origin::none(), 0, technics, proof).is_err());
+            assert_err_ignore_postinfo!(
+                Liability::force_finalize(Origin::root(), 2, true),
+                Error::<Runtime>::UnknownLiability
+            );
+        })
+    }
+
+    #[test]
+    fn test_finalization_proof() {
+        new_test_ext().execute_with(|| {
+            create_liabilities(3);
+            let report = b"QmReport".to_vec();
+            let proof = get_report_proof("//Bob", &0, &report);
+            assert_ok!(Liability::finalize(
+                Origin::none(),
+                0,
+                report.clone(),
+                proof
+            ));
+            let (leaf, early_proof) = Liability::finalization_proof(0).unwrap();
+            assert_eq!(
+                leaf,
+                FinalizationLeaf {
+                    index: 0,
+                    promisor: account("//Bob"),
+                    report_hash: report_hash(&report),
+                    success: true,
+                }
+            );
+            assert_eq!(Liability::finalization_root(), leaf.hash());
+            assert_eq!(Liability::finalization_proof(1), None);
+
+            // Liability finalized without report has zero report hash
+            System::set_block_number(10);
+            assert_ok!(Liability::force_finalize(Origin::root(), 1, false));
+            let proof = get_report_proof("//Bob", &2, &report);
+            assert_ok!(Liability::finalize(
+                Origin::none(),
+                2,
+                report.clone(),
+                proof
+            ));
+            let root = Liability::finalization_root();
+            assert!(System::events().iter().any(|record| record.event
+                == MetaEvent::liability(RawEvent::FinalizationLeafAdded(Compact(2), 2, root))));
+            assert_eq!(Liability::finalization_leaf_count(), 3);
+            assert_eq!(Liability::finalization_leaf_index(1), Some(1));
+
+            for index in 0..3 {
+                let (leaf, proof) = Liability::finalization_proof(index).unwrap();
+                assert_eq!(leaf.index, index);
+                assert_eq!(proof.leaf_count, 3);
+                assert!(mmr::verify_proof(&root, &leaf.hash(), &proof));
+            }
+            let (leaf, _) = Liability::finalization_proof(1).unwrap();
+            assert_eq!((leaf.report_hash, leaf.success), (H256::zero(), false));
+
+            // Proof is made against root of its range size
+            let (leaf, _) = Liability::finalization_proof(0).unwrap();
+            assert!(!mmr::verify_proof(&root, &leaf.hash(), &early_proof));
+        })
+    }
+
+    #[test]
+    fn test_deferred_funding() {
+        use super::economics::{Communism, DeferredFunding};
+        use frame_support::{traits::OnInitialize, unsigned::ValidateUnsigned};
+
+        // Deferred funding is accepted by signing economics with the flag
+        assert!(!<Communism as Deferrable>::deferred_funding(&()));
+        assert!(<DeferredFunding<Communism> as Deferrable>::deferred_funding(&((), true)));
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            DEFERRED_FUNDING.with(|deferred| *deferred.borrow_mut() = true);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (bob, bob_proof) = get_params_proof("//Bob", &technics, &());
+            for promisee_uri in &["//Alice", "//Charlie"] {
+                let (promisee, promisee_proof) = get_params_proof(promisee_uri, &technics, &());
+                assert_ok!(Liability::create(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    promisee,
+                    bob.clone(),
+                    promisee_proof,
+                    bob_proof.clone(),
+                ));
+            }
+            let alice = account("//Alice");
+            assert_eq!(Liability::funding_deadline(0), Some(6));
+            assert_eq!(Liability::funding_deadline(1), Some(6));
+
+            // Promisor isn't obligated before funding
+            let report = b"report".to_vec();
+            let proof = get_report_proof("//Bob", &0, &report);
+            let finalize = Call::finalize(0, report.clone(), proof.clone());
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &finalize),
+                InvalidTransaction::Future.into()
+            );
+            assert_err_ignore_postinfo!(
+                Liability::finalize(Origin::none(), 0, report.clone(), proof.clone()),
+                Error::<Runtime>::FundingPending
+            );
+
+            assert_err!(
+                Liability::fund(Origin::signed(bob.clone()), 0),
+                Error::<Runtime>::NotPromisee
+            );
+            assert_err!(
+                Liability::fund(Origin::signed(alice.clone()), 2),
+                Error::<Runtime>::NotPendingFunding
+            );
+            assert_ok!(Liability::fund(Origin::signed(alice.clone()), 0));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::LiabilityFunded(Compact(0)))
+            );
+            assert_eq!(Liability::funding_deadline(0), None);
+            assert_eq!(
+                Liability::history_of(0).last(),
+                Some(&(1, Transition::Funded))
+            );
+            assert_err!(
+                Liability::fund(Origin::signed(alice), 0),
+                Error::<Runtime>::NotPendingFunding
+            );
+            assert_ok!(Liability::finalize(Origin::none(), 0, report, proof));
+
+            // Liability not funded before deadline is cancelled
+            System::set_block_number(6);
+            Liability::on_initialize(6);
+            assert_eq!(Liability::funding_deadline(1), Some(6));
+            System::set_block_number(7);
+            Liability::on_initialize(7);
+            assert!(System::events()
+                .iter()
+                .any(|record| record.event
+                    == MetaEvent::liability(RawEvent::FundingExpired(Compact(1)))));
+            assert_eq!(Liability::funding_deadline(1), None);
+            assert_eq!(Liability::funding_sweep_cursor(), (8, 0));
+            assert_eq!(
+                Liability::liability_record(1).map(|record| record.state),
+                Some(RecordState::Cancelled)
+            );
+            assert_eq!(
+                Liability::history_of(1).last(),
+                Some(&(7, Transition::Cancelled))
+            );
+            assert_eq!(Liability::counters().cancelled, 1);
+            assert_err!(
+                Liability::fund(Origin::signed(account("//Charlie")), 1),
+                Error::<Runtime>::NotPendingFunding
+            );
+            assert_err_ignore_postinfo!(
+                Liability::force_finalize(Origin::root(), 1, true),
+                Error::<Runtime>::AlreadyFinalized
+            );
+        })
+    }
+
+    #[test]
+    fn test_acknowledge() {
+        use frame_support::unsigned::ValidateUnsigned;
+
+        new_test_ext().execute_with(|| {
+            ACK_WINDOW.with(|window| *window.borrow_mut() = 5);
+            create_liabilities(1);
+            let (alice, bob) = (account("//Alice"), account("//Bob"));
+            assert_eq!(Liability::pending_acknowledgement(0), Some((6, 10)));
+            assert_eq!(Liability::named_reserve(RESERVE_ID, &bob), 10);
+
+            // Acknowledgement is proven by promisor
+            let alice_proof = crate::testing::make_acknowledge_proof("//Alice", &0u64);
+            assert_eq!(
+                Liability::validate_unsigned(
+                    TransactionSource::External,
+                    &Call::acknowledge(0, alice_proof.clone())
+                ),
+                ValidityError::BadPromisorProof.into()
+            );
+            assert_err!(
+                Liability::acknowledge(Origin::none(), 0, alice_proof),
+                Error::<Runtime>::BadPromisorProof
+            );
+
+            // Promisee waits until acknowledgement window is over
+            System::set_block_number(6);
+            assert_err!(
+                Liability::reclaim(Origin::signed(alice.clone()), 0),
+                Error::<Runtime>::AcknowledgementWindowOpen
+            );
+
+            let proof = crate::testing::make_acknowledge_proof("//Bob", &0u64);
+            let acknowledge = Call::acknowledge(0, proof.clone());
+            assert!(
+                Liability::validate_unsigned(TransactionSource::External, &acknowledge).is_ok()
+            );
+            assert_ok!(Liability::acknowledge(Origin::none(), 0, proof.clone()));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::LiabilityAcknowledged(Compact(0)))
+            );
+            assert_eq!(Liability::pending_acknowledgement(0), None);
+            assert_eq!(Liability::named_reserve(RESERVE_ID, &bob), 0);
+            assert_eq!(
+                Liability::history_of(0).last(),
+                Some(&(6, Transition::Acknowledged))
+            );
+
+            // Acknowledged liability isn't reclaimed
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &acknowledge),
+                ValidityError::AlreadySettled.into()
+            );
+            assert_err!(
+                Liability::acknowledge(Origin::none(), 0, proof),
+                Error::<Runtime>::NotPendingAcknowledgement
+            );
+            System::set_block_number(7);
+            assert_err!(
+                Liability::reclaim(Origin::signed(alice), 0),
+                Error::<Runtime>::NotPendingAcknowledgement
+            );
+        })
+    }
+
+    #[test]
+    fn test_reclaim() {
+        new_test_ext().execute_with(|| {
+            ACK_WINDOW.with(|window| *window.borrow_mut() = 5);
+            create_liabilities(2);
+            let (alice, bob) = (account("//Alice"), account("//Bob"));
+            assert_eq!(Liability::named_reserve(RESERVE_ID, &bob), 20);
+
+            // Late acknowledgement is rejected
+            System::set_block_number(7);
+            let proof = crate::testing::make_acknowledge_proof("//Bob", &0u64);
+            assert_err!(
+                Liability::acknowledge(Origin::none(), 0, proof),
+                Error::<Runtime>::AcknowledgementExpired
+            );
+
+            assert_err!(
+                Liability::reclaim(Origin::signed(bob.clone()), 0),
+                Error::<Runtime>::NotPromisee
+            );
+            let free = Balances::free_balance(&alice);
+            assert_ok!(Liability::reclaim(Origin::signed(alice.clone()), 0));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::LiabilityReclaimed(Compact(0), 10))
+            );
+            assert_eq!(Balances::free_balance(&alice), free + 10);
+            assert_eq!(Liability::named_reserve(RESERVE_ID, &bob), 10);
+            assert_eq!(
+                Liability::liability_record(0).unwrap().state,
+                RecordState::Cancelled
+            );
+            assert_eq!(Liability::counters().cancelled, 1);
+            assert_eq!(
+                Liability::history_of(0).last(),
+                Some(&(7, Transition::Cancelled))
+            );
+            assert_err!(
+                Liability::reclaim(Origin::signed(alice.clone()), 0),
+                Error::<Runtime>::AlreadyFinalized
+            );
+
+            // Report proves promisor is online, its deposit is returned
+            let report = b"report".to_vec();
+            let proof = get_report_proof("//Bob", &1, &report);
+            assert_ok!(Liability::finalize(Origin::none(), 1, report, proof));
+            assert_eq!(Liability::pending_acknowledgement(1), None);
+            assert_eq!(Liability::named_reserve(RESERVE_ID, &bob), 0);
+            assert_err!(
+                Liability::reclaim(Origin::signed(alice), 1),
+                Error::<Runtime>::AlreadyFinalized
+            );
+        })
+    }
+
+    #[test]
+    fn test_reclaim_deferred_funding() {
+        use frame_support::traits::OnInitialize;
+
+        new_test_ext().execute_with(|| {
+            ACK_WINDOW.with(|window| *window.borrow_mut() = 2);
+            DEFERRED_FUNDING.with(|deferred| *deferred.borrow_mut() = true);
+            create_liabilities(2);
+            let (alice, bob) = (account("//Alice"), account("//Bob"));
+            assert_eq!(Liability::funding_deadline(0), Some(6));
+            assert_eq!(Liability::pending_acknowledgement(0), Some((3, 10)));
+
+            // Unfunded liability is reclaimed without economical processing
+            System::set_block_number(4);
+            assert_ok!(Liability::reclaim(Origin::signed(alice.clone()), 0));
+            assert_eq!(Liability::funding_deadline(0), None);
+            assert_err!(
+                Liability::reclaim(Origin::signed(alice.clone()), 1),
+                Error::<Runtime>::AcknowledgementWindowOpen
+            );
+
+            // Expired funding returns deposit to promisor, the reclaimed one isn't expired
+            System::set_block_number(7);
+            Liability::on_initialize(7);
+            assert!(!System::events()
+                .iter()
+                .any(|record| record.event
+                    == MetaEvent::liability(RawEvent::FundingExpired(Compact(0)))));
+            assert_eq!(Liability::counters().cancelled, 1);
+            System::set_block_number(8);
+            Liability::on_initialize(8);
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::FundingExpired(Compact(1)))
+            );
+            assert_eq!(Liability::counters().cancelled, 2);
+            assert_eq!(Liability::pending_acknowledgement(1), None);
+            assert_eq!(Liability::named_reserve(RESERVE_ID, &bob), 0);
+            assert_err!(
+                Liability::reclaim(Origin::signed(alice), 1),
+                Error::<Runtime>::AlreadyFinalized
+            );
+        })
+    }
+    #[test]
+    fn test_force_expire() {
+        new_test_ext().execute_with(|| {
+            ACK_WINDOW.with(|window| *window.borrow_mut() = 5);
+            create_liabilities(3);
+            let (alice, bob, charlie) =
+                (account("//Alice"), account("//Bob"), account("//Charlie"));
+            let created_at = Liability::liability_record(0).unwrap().created_at;
+            let proof = crate::testing::make_acknowledge_proof("//Bob", &2u64);
+            assert_ok!(Liability::acknowledge(Origin::none(), 2, proof));
+            assert_eq!(Liability::named_reserve(RESERVE_ID, &bob), 20);
+
+            // Witnessed report of liability 1 is disputed in challenge window
+            assert_ok!(Liability::register_witness(
+                Origin::signed(charlie.clone()),
+                50
+            ));
+            assert_ok!(Liability::require_witnesses(
+                Origin::signed(alice.clone()),
+                1,
+                1,
+                get_witnessing_proof("//Bob", &1, 1)
+            ));
+            let report = b"report".to_vec();
+            assert_ok!(Liability::finalize(
+                Origin::none(),
+                1,
+                report.clone(),
+                get_report_proof("//Bob", &1, &report),
+            ));
+            assert_eq!(Liability::named_reserve(RESERVE_ID, &bob), 10);
+
+            System::set_block_number(created_at + 20);
+            assert_err!(
+                Liability::force_expire(Origin::signed(charlie.clone()), 0),
+                Error::<Runtime>::LifetimeNotExceeded
+            );
+
+            // Any account expires liability and gets incentive of promisor deposit
+            System::set_block_number(created_at + 21);
+            let free = Balances::free_balance(&charlie);
+            assert_ok!(Liability::force_expire(Origin::signed(charlie.clone()), 0));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::LiabilityExpired(Compact(0), charlie.clone(), 4))
+            );
+            assert_eq!(Balances::free_balance(&charlie), free + 4);
+            assert_eq!(Liability::named_reserve(RESERVE_ID, &bob), 0);
+            assert_eq!(Liability::pending_acknowledgement(0), None);
+            assert_eq!(
+                Liability::liability_record(0).unwrap().state,
+                RecordState::Cancelled
+            );
+            assert_eq!(Liability::counters().cancelled, 1);
+            assert_eq!(
+                Liability::history_of(0).last(),
+                Some(&(created_at + 21, Transition::Cancelled))
+            );
+            assert_err!(
+                Liability::force_expire(Origin::signed(charlie.clone()), 0),
+                Error::<Runtime>::AlreadyFinalized
+            );
+
+            // Disputed liability is left to governance
+            assert_err!(
+                Liability::force_expire(Origin::signed(charlie.clone()), 1),
+                Error::<Runtime>::SettlementDisputed
+            );
+            assert_ok!(Liability::overturn_report(Origin::root(), 1));
+            assert_err!(
+                Liability::force_expire(Origin::signed(charlie.clone()), 1),
+                Error::<Runtime>::AlreadyFinalized
+            );
+
+            // Acknowledged liability expires without incentive
+            assert_ok!(Liability::force_expire(Origin::signed(charlie.clone()), 2));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::LiabilityExpired(Compact(2), charlie, 0))
+            );
+        })
+    }
+
+    #[test]
+    fn test_force_expire_deferred_funding() {
+        new_test_ext().execute_with(|| {
+            DEFERRED_FUNDING.with(|deferred| *deferred.borrow_mut() = true);
+            create_liabilities(1);
+            let created_at = Liability::liability_record(0).unwrap().created_at;
+            assert!(Liability::funding_deadline(0).is_some());
+
+            // Unfunded liability is expired without economical processing
+            System::set_block_number(created_at + 21);
+            assert_ok!(Liability::force_expire(
+                Origin::signed(account("//Dave")),
+                0
+            ));
+            assert_eq!(Liability::funding_deadline(0), None);
+            assert_eq!(
+                Liability::liability_record(0).unwrap().state,
+                RecordState::Cancelled
+            );
+        })
+    }
+
+    #[test]
+    fn test_liability_timeline() {
+        new_test_ext().execute_with(|| {
+            ACK_WINDOW.with(|window| *window.borrow_mut() = 5);
+            create_liabilities(1);
+            assert_eq!(Liability::timeline(1), None);
+            assert_eq!(
+                Liability::timeline(0),
+                Some(LiabilityTimeline {
+                    created_at: 1,
+                    funding_deadline: None,
+                    acknowledgement_deadline: Some(6),
+                    reveal_deadline: None,
+                    challenge_end: None,
+                    lifetime_end: Some(21),
+                })
+            );
+
+            // Committed report is revealed or failed, liability doesn't expire
+            let proof = crate::testing::make_acknowledge_proof("//Bob", &0u64);
+            assert_ok!(Liability::acknowledge(Origin::none(), 0, proof));
+            let commitment = report_commitment(&b"model".to_vec(), &H256::repeat_byte(7));
+            let proof = get_commit_proof("//Bob", &0, &commitment);
+            assert_ok!(Liability::commit_report(
+                Origin::none(),
+                0,
+                commitment,
+                proof
+            ));
+            let timeline = Liability::timeline(0).unwrap();
+            assert_eq!(timeline.acknowledgement_deadline, None);
+            assert_eq!(timeline.reveal_deadline, Some(4));
+            assert_eq!(timeline.lifetime_end, None);
+
+            // Expired deferred funding liability
+            DEFERRED_FUNDING.with(|deferred| *deferred.borrow_mut() = true);
+            create_liability(2);
+            let timeline = Liability::timeline(1).unwrap();
+            assert_eq!(timeline.funding_deadline, Some(7));
+            assert_eq!(timeline.lifetime_end, Some(22));
+            System::set_block_number(23);
+            assert_ok!(Liability::force_expire(
+                Origin::signed(account("//Dave")),
+                1
+            ));
+            let timeline = Liability::timeline(1).unwrap();
+            assert_eq!(timeline.funding_deadline, None);
+            assert_eq!(timeline.lifetime_end, None);
+        })
+    }
+
+    #[test]
+    fn test_amend_economics() {
+        new_test_ext().execute_with(|| {
+            create_liability(1);
+            let alice = account("//Alice");
+
+            // Amendment is proven by both parties
+            let promisee_proof = crate::testing::make_amend_proof("//Alice", &0u64, 0, &());
+            let promisor_proof = crate::testing::make_amend_proof("//Bob", &0u64, 0, &());
+            assert_err!(
+                Liability::amend_economics(
+                    Origin::signed(alice.clone()),
+                    0,
+                    (),
+                    promisor_proof.clone(),
+                    promisor_proof.clone(),
+                ),
+                Error::<Runtime>::BadPromiseeProof
+            );
+            assert_err!(
+                Liability::amend_economics(
+                    Origin::signed(alice.clone()),
+                    0,
+                    (),
+                    promisee_proof.clone(),
+                    promisee_proof.clone(),
+                ),
+                Error::<Runtime>::BadPromisorProof
+            );
+
+            System::set_block_number(2);
+            assert_ok!(Liability::amend_economics(
+                Origin::signed(alice.clone()),
+                0,
+                (),
+                promisee_proof.clone(),
+                promisor_proof.clone(),
+            ));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::EconomicsAmended(Compact(0), ()))
+            );
+            assert_eq!(Liability::amendments_of(0), 1);
+            assert_eq!(
+                Liability::history_of(0).last(),
+                Some(&(2, Transition::Amended(1)))
+            );
+
+            // Proofs of previous amendment couldn't be replayed
+            assert_err!(
+                Liability::amend_economics(
+                    Origin::signed(alice.clone()),
+                    0,
+                    (),
+                    promisee_proof,
+                    promisor_proof,
+                ),
+                Error::<Runtime>::BadPromiseeProof
+            );
+
+            let report = b"report".to_vec();
+            let proof = get_report_proof("//Bob", &0, &report);
+            assert_ok!(Liability::finalize(Origin::none(), 0, report, proof));
+            assert_err!(
+                Liability::amend_economics(
+                    Origin::signed(alice),
+                    0,
+                    (),
+                    crate::testing::make_amend_proof("//Alice", &0u64, 1, &()),
+                    crate::testing::make_amend_proof("//Bob", &0u64, 1, &()),
+                ),
+                Error::<Runtime>::AlreadyFinalized
+            );
+        })
+    }
+
+    #[test]
+    fn test_open_market_amend() {
+        use super::economics::OpenMarket;
+        type Market = OpenMarket<NamedReserve<Runtime>, AccountId>;
+
+        new_test_ext().execute_with(|| {
+            let alice = account("//Alice");
+            let bob = account("//Bob");
+            assert_ok!(<Market as Settlement<AccountId>>::on_start(
+                &100, &alice, &bob
+            ));
+
+            // Grown price reserves the difference only
+            assert_ok!(<Market as Settlement<AccountId>>::on_amend(
+                &100, &150, &alice, &bob
+            ));
+            assert_eq!(Liability::named_reserve(RESERVE_ID, &alice), 150);
+
+            // Top-up promisee couldn't afford keeps previous escrow
+            assert!(
+                <Market as Settlement<AccountId>>::on_amend(&150, &2_000, &alice, &bob).is_err()
+            );
+            assert_eq!(Liability::named_reserve(RESERVE_ID, &alice), 150);
+
+            // Reduced price returns the difference
+            assert_ok!(<Market as Settlement<AccountId>>::on_amend(
+                &150, &80, &alice, &bob
+            ));
+            assert_eq!(Liability::named_reserve(RESERVE_ID, &alice), 80);
+            assert_eq!(Balances::free_balance(&alice), 920);
+            assert!(<Market as Settlement<AccountId>>::on_amend(&100, &50, &alice, &bob).is_err());
+
+            assert_ok!(<Market as Settlement<AccountId>>::on_finish(
+                &80, &alice, &bob, true
+            ));
+            assert_eq!(Balances::free_balance(&bob), 1_080);
+        });
+    }
+
+    #[test]
+    fn test_preferences() {
+        new_test_ext().execute_with(|| {
+            let (bob, charlie, dave) = (account("//Bob"), account("//Charlie"), account("//Dave"));
+            Balances::make_free_balance_be(&dave, 1000);
+            let prefs = AccountPrefs {
+                arbitrator: Some(dave.clone()),
+                payout: Some(charlie.clone()),
+                topic: Some(H256::repeat_byte(1)),
+            };
+            assert_ok!(Liability::set_preferences(
+                Origin::signed(bob.clone()),
+                prefs.clone()
+            ));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::PreferencesSet(bob.clone()))
+            );
+            assert_eq!(Liability::preferences(&bob), Some(prefs.clone()));
+
+            // Payout account is snapshotted at creation
+            create_liability(1);
+            assert_eq!(Liability::payout_of(0), Some(charlie.clone()));
+            let payout = AccountPrefs {
+                payout: Some(dave.clone()),
+                ..prefs
+            };
+            assert_ok!(Liability::set_preferences(
+                Origin::signed(bob.clone()),
+                payout
+            ));
+            create_liability(2);
+            assert_eq!(Liability::payout_of(0), Some(charlie.clone()));
+            assert_eq!(Liability::payout_of(1), Some(dave.clone()));
+
+            // Settled value goes to payout account of liability
+            let (charlie_free, dave_free) = (
+                Balances::free_balance(&charlie),
+                Balances::free_balance(&dave),
+            );
+            for index in 0..2 {
+                let report = b"report".to_vec();
+                let proof = get_report_proof("//Bob", &index, &report);
+                assert_ok!(Liability::finalize(Origin::none(), index, report, proof));
+            }
+            assert!(System::events().iter().any(|record| record.event
+                == MetaEvent::liability(RawEvent::PayoutRedirected(
+                    Compact(0),
+                    charlie.clone(),
+                    10
+                ))));
+            assert_eq!(Balances::free_balance(&charlie), charlie_free + 10);
+            assert_eq!(Balances::free_balance(&dave), dave_free + 10);
+            assert_eq!(Balances::free_balance(&bob), 980);
+
+            // Empty preferences are removed
+            let empty = AccountPrefs {
+                arbitrator: None,
+                payout: None,
+                topic: None,
+            };
+            assert_ok!(Liability::set_preferences(
+                Origin::signed(bob.clone()),
+                empty
+            ));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::PreferencesCleared(bob.clone()))
+            );
+            assert_eq!(Liability::preferences(&bob), None);
+            create_liability(3);
+            assert_eq!(Liability::payout_of(2), None);
+        })
+    }
+
+    #[test]
+    fn test_economics_payout() {
+        new_test_ext().execute_with(|| {
+            let (bob, charlie, dave) = (account("//Bob"), account("//Charlie"), account("//Dave"));
+            let prefs = AccountPrefs {
+                arbitrator: None,
+                payout: Some(charlie),
+                topic: None,
+            };
+            assert_ok!(Liability::set_preferences(
+                Origin::signed(bob.clone()),
+                prefs
+            ));
+
+            // Payout account named by economics overrides preferences
+            ECONOMICS_PAYOUT.with(|payout| *payout.borrow_mut() = Some(dave.clone()));
+            ACK_WINDOW.with(|window| *window.borrow_mut() = 5);
+            create_liability(1);
+            ACK_WINDOW.with(|window| *window.borrow_mut() = 0);
+            assert_eq!(Liability::payout_of(0), Some(dave.clone()));
+
+            // Redirected payout is previewed on success, deposit is released anyway
+            assert_eq!(
+                Liability::preview(0, true),
+                Some(Payouts {
+                    transfers: vec![(bob.clone(), dave, 10)],
+                    refunds: vec![(bob.clone(), 10)],
+                })
+            );
+            assert_eq!(
+                Liability::preview(0, false),
+                Some(Payouts {
+                    transfers: vec![],
+                    refunds: vec![(bob.clone(), 10)],
+                })
+            );
+
+            // Payout to promisor itself isn't redirected
+            ECONOMICS_PAYOUT.with(|payout| *payout.borrow_mut() = Some(bob));
+            create_liability(2);
+            assert_eq!(Liability::payout_of(1), None);
+        })
+    }
+
+    #[test]
+    fn test_zero_value_liability() {
+        use frame_support::unsigned::ValidateUnsigned;
+
+        new_test_ext().execute_with(|| {
+            let (alice, bob) = (account("//Alice"), account("//Bob"));
+            let finalize = |index: u64, report: Vec<u8>| {
+                let proof = get_report_proof("//Bob", &index, &report);
+                assert_ok!(Liability::finalize(Origin::none(), index, report, proof));
+            };
+
+            // Priced liability reserves no deposit
+            create_liability(1);
+            assert_eq!(Liability::zero_value_deposit(0), None);
+            assert_eq!(Balances::reserved_balance(&alice), 0);
+
+            // Deposit of zero value liability returns to promisee at settlement
+            ECONOMICS_VALUE.with(|value| *value.borrow_mut() = Some(0));
+            assert!(Liability::is_zero_value(&()));
+            create_liability(2);
+            assert_eq!(
+                Liability::zero_value_deposit(1),
+                Some(ZeroValueDeposit::get())
+            );
+            assert_eq!(Balances::reserved_balance(&alice), ZeroValueDeposit::get());
+            finalize(1, vec![2]);
+            assert_eq!(Liability::zero_value_deposit(1), None);
+            assert_eq!(Balances::reserved_balance(&alice), 0);
+            assert_eq!(Balances::free_balance(&alice), 1_000);
+
+            // Only quota of zero value liabilities of era is counted by reputation
+            assert_eq!(Reputation::reputation_of(&bob).completed, 1);
+            create_liability(3);
+            finalize(2, vec![3]);
+            assert_eq!(Reputation::reputation_of(&bob).completed, 1);
+            assert_eq!(Liability::zero_value_count(&alice), (0, 2));
+
+            // Promisee creates limited count of zero value liabilities per era, excess
+            // calls and calls of promisee without deposit never enter the pool
+            System::set_block_number(4);
+            let technics = vec![4];
+            let (promisee, promisee_proof) = get_params_proof("//Alice", &technics, &());
+            let (promisor, promisor_proof) = get_params_proof("//Bob", &technics, &());
+            let (dave, dave_proof) = get_params_proof("//Dave", &technics, &());
+            let validity = |promisee: &AccountId, promisee_proof: &ProofParam<Runtime>| {
+                let call = Call::create(
+                    technics.clone(),
+                    (),
+                    promisee.clone(),
+                    promisor.clone(),
+                    promisee_proof.clone(),
+                    promisor_proof.clone(),
+                );
+                Liability::validate_unsigned(TransactionSource::External, &call).map(|_| ())
+            };
+            assert_eq!(
+                validity(&promisee, &promisee_proof),
+                Err(ValidityError::RateLimited.into())
+            );
+            assert_eq!(
+                validity(&dave, &dave_proof),
+                Err(InvalidTransaction::Payment.into())
+            );
+            assert_err!(
+                Liability::create(
+                    Origin::none(),
+                    technics,
+                    (),
+                    promisee,
+                    promisor,
+                    promisee_proof,
+                    promisor_proof,
+                ),
+                Error::<Runtime>::TooManyZeroValue
+            );
+            assert_eq!(Balances::reserved_balance(&alice), 0);
+
+            // Limit and quota are reset in the next era
+            create_liability(StatsEraLength::get());
+            assert_eq!(Liability::zero_value_count(&alice), (1, 1));
+            finalize(3, vec![StatsEraLength::get() as u8]);
+            assert_eq!(Reputation::reputation_of(&bob).completed, 2);
+            assert_eq!(Balances::reserved_balance(&alice), 0);
+            assert_ok!(Liability::try_state());
+            ECONOMICS_VALUE.with(|value| *value.borrow_mut() = None);
+        })
+    }
+
+    #[test]
+    fn test_cancellation_fee() {
+        use super::economics::{MinReputation, OpenMarket};
+        type Market = OpenMarket<NamedReserve<Runtime>, AccountId>;
+
+        // Fixed fee is limited by price
+        assert_eq!(CancellationFee::Fixed(30u128).amount(100), 30);
+        assert_eq!(CancellationFee::Fixed(300u128).amount(100), 100);
+        assert_eq!(
+            CancellationFee::Share(Perbill::from_percent(10)).amount(100u128),
+            10
+        );
+
+        new_test_ext().execute_with(|| {
+            let alice = account("//Alice");
+            let bob = account("//Bob");
+
+            // Fee of promisee is taken from escrow, the rest is refunded
+            assert_ok!(<Market as Settlement<AccountId>>::on_start(
+                &100, &alice, &bob
+            ));
+            let fee = Some((Party::Promisee, 30u128));
+            assert_ok!(<Market as Settlement<AccountId>>::on_cancel(
+                &100, &alice, &bob, fee
+            ));
+            assert_eq!(Balances::reserved_balance(&alice), 0);
+            assert_eq!(Balances::free_balance(&alice), 970);
+            assert_eq!(Balances::free_balance(&bob), 1_030);
+
+            // Fee of promisor is paid from its funds, escrow is refunded
+            assert_ok!(<Market as Settlement<AccountId>>::on_start(
+                &100, &alice, &bob
+            ));
+            let fee = Some((Party::Promisor, 30u128));
+            assert_ok!(<MinReputation<Market> as Settlement<AccountId>>::on_cancel(
+                &(100, 0),
+                &alice,
+                &bob,
+                fee
+            ));
+            assert_eq!(Balances::free_balance(&alice), 1_000);
+            assert_eq!(Balances::free_balance(&bob), 1_000);
+            assert_eq!(Liability::named_reserve(RESERVE_ID, &bob), 0);
+
+            // Fee never exceeds price, mutual cancel is fee-free
+            assert_ok!(<Market as Settlement<AccountId>>::on_start(
+                &100, &alice, &bob
+            ));
+            let fee = Some((Party::Promisee, 500u128));
+            assert_ok!(<Market as Settlement<AccountId>>::on_cancel(
+                &100, &alice, &bob, fee
+            ));
+            assert_eq!(Balances::free_balance(&alice), 900);
+            assert_eq!(Balances::free_balance(&bob), 1_100);
+            assert_ok!(<Market as Settlement<AccountId>>::on_start(
+                &100, &alice, &bob
+            ));
+            assert_ok!(<Market as Settlement<AccountId>>::on_cancel(
+                &100,
+                &alice,
+                &bob,
+                None::<(Party, u128)>
+            ));
+            assert_eq!(Balances::free_balance(&alice), 900);
+            assert_eq!(Liability::named_reserve(RESERVE_ID, &alice), 0);
+
+            // Cancel without escrow is rejected
+            assert!(<Market as Settlement<AccountId>>::on_cancel(
+                &100,
+                &alice,
+                &bob,
+                None::<(Party, u128)>
+            )
+            .is_err());
+        })
+    }
+
+    #[test]
+    fn test_cancel() {
+        use frame_support::unsigned::ValidateUnsigned;
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let config = MarketConfig {
+                cancellation_fee: CancellationFee::Share(Perbill::from_percent(50)),
+                ..Liability::default_market_config()
+            };
+            assert_ok!(Liability::create_market(Origin::root(), 0, config));
+
+            create_liabilities(3);
+
+            // Cancel should be proven by party with fresh proof
+            let promisee_proof = get_cancel_proof("//Alice", &0, &5);
+            assert_err!(
+                Liability::cancel(Origin::none(), 0, 5, None, None),
+                Error::<Runtime>::CancelProofRequired
+            );
+            assert_err!(
+                Liability::cancel(Origin::none(), 0, 4, Some(promisee_proof.clone()), None),
+                Error::<Runtime>::BadPromiseeProof
+            );
+            let charlie_proof = get_cancel_proof("//Charlie", &0, &5);
+            assert_err!(
+                Liability::cancel(Origin::none(), 0, 5, None, Some(charlie_proof.clone())),
+                Error::<Runtime>::BadPromisorProof
+            );
+            let cancel = Call::cancel(0, 5, None, Some(charlie_proof));
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &cancel),
+                ValidityError::BadPromisorProof.into()
+            );
+            System::set_block_number(6);
+            assert_err!(
+                Liability::cancel(Origin::none(), 0, 5, Some(promisee_proof.clone()), None),
+                Error::<Runtime>::ProofExpired
+            );
+            let cancel = Call::cancel(0, 5, Some(promisee_proof), None);
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &cancel),
+                ValidityError::Expired.into()
+            );
+
+            // Unilateral cancel pays fee of liability market to counterparty
+            let promisee_proof = get_cancel_proof("//Alice", &0, &10);
+            let cancel = Call::cancel(0, 10, Some(promisee_proof.clone()), None);
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &cancel)
+                    .map(|validity| (validity.provides, validity.longevity)),
+                Ok((vec![(FINALIZE_TAG, 0u64).encode()], 5))
+            );
+            assert_ok!(Liability::cancel(
+                Origin::none(),
+                0,
+                10,
+                Some(promisee_proof),
+                None
+            ));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::LiabilityCancelled(
+                    Compact(0),
+                    Some(Party::Promisee),
+                    5
+                ))
+            );
+            let record = Liability::liability_record(0).unwrap();
+            assert_eq!(record.state, RecordState::Cancelled);
+            assert_eq!(record.finalized_at, Some(6));
+            assert_eq!(
+                Liability::history_of(0).last(),
+                Some(&(6, Transition::Cancelled))
+            );
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &cancel),
+                ValidityError::AlreadySettled.into()
+            );
+
+            // Cancelled liability can't be reported or cancelled again
+            let report = b"report".to_vec();
+            let proof = get_report_proof("//Bob", &0, &report);
+            assert_err_ignore_postinfo!(
+                Liability::finalize(Origin::none(), 0, report, proof),
+                "already finalized"
+            );
+            let promisor_proof = get_cancel_proof("//Bob", &0, &10);
+            assert_err!(
+                Liability::cancel(Origin::none(), 0, 10, None, Some(promisor_proof)),
+                Error::<Runtime>::AlreadyFinalized
+            );
+
+            // Promisor cancel mirrors promisee one, mutual cancel is fee-free
+            let promisor_proof = get_cancel_proof("//Bob", &1, &10);
+            assert_ok!(Liability::cancel(
+                Origin::none(),
+                1,
+                10,
+                None,
+                Some(promisor_proof)
+            ));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::LiabilityCancelled(
+                    Compact(1),
+                    Some(Party::Promisor),
+                    5
+                ))
+            );
+            let promisee_proof = get_cancel_proof("//Alice", &2, &10);
+            let promisor_proof = get_cancel_proof("//Bob", &2, &10);
+            assert_ok!(Liability::cancel(
+                Origin::none(),
+                2,
+                10,
+                Some(promisee_proof),
+                Some(promisor_proof)
+            ));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::LiabilityCancelled(Compact(2), None, 0))
+            );
+            assert_eq!(Liability::counters().cancelled, 3);
+        })
+    }
+
+    #[test]
+    fn test_create_tagged() {
+        use frame_support::unsigned::ValidateUnsigned;
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = vec![1];
+            let tags = vec![Tag::Delivery, Tag::Sensing];
+            let (promisee, promisee_proof) =
+                crate::testing::make_tagged_party_proof("//Alice", &technics, &(), &tags);
+            let (promisor, promisor_proof) = get_params_proof("//Bob", &technics, &());
+
+            // Tags should be covered by promisee proof
+            let (_, untagged_proof) = get_params_proof("//Alice", &technics, &());
+            assert_err!(
+                Liability::create_tagged(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    tags.clone(),
+                    promisee.clone(),
+                    promisor.clone(),
+                    untagged_proof.clone(),
+                    promisor_proof.clone(),
+                ),
+                Error::<Runtime>::BadPromiseeProof
+            );
+            let call = Call::create_tagged(
+                technics.clone(),
+                (),
+                tags.clone(),
+                promisee.clone(),
+                promisor.clone(),
+                untagged_proof,
+                promisor_proof.clone(),
+            );
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &call),
+                ValidityError::BadPromiseeProof.into()
+            );
+
+            // Tags are bounded and unique
+            let many = vec![Tag::Delivery, Tag::Sensing, Tag::Compute, Tag::Inspection];
+            let (_, many_proof) =
+                crate::testing::make_tagged_party_proof("//Alice", &technics, &(), &many);
+            assert_err!(
+                Liability::create_tagged(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    many,
+                    promisee.clone(),
+                    promisor.clone(),
+                    many_proof,
+                    promisor_proof.clone(),
+                ),
+                Error::<Runtime>::TooManyTags
+            );
+            let twice = vec![Tag::Compute, Tag::Compute];
+            let (_, twice_proof) =
+                crate::testing::make_tagged_party_proof("//Alice", &technics, &(), &twice);
+            assert_err!(
+                Liability::create_tagged(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    twice,
+                    promisee.clone(),
+                    promisor.clone(),
+                    twice_proof,
+                    promisor_proof.clone(),
+                ),
+                Error::<Runtime>::DuplicateTag
+            );
+
+            let call = Call::create_tagged(
+                technics.clone(),
+                (),
+                tags.clone(),
+                promisee.clone(),
+                promisor.clone(),
+                promisee_proof.clone(),
+                promisor_proof.clone(),
+            );
+            assert!(Liability::validate_unsigned(TransactionSource::External, &call).is_ok());
+            assert_ok!(Liability::create_tagged(
+                Origin::none(),
+                technics.clone(),
+                (),
+                tags.clone(),
+                promisee.clone(),
+                promisor.clone(),
+                promisee_proof,
+                promisor_proof,
+            ));
+            assert_eq!(Liability::tags_of(0), tags);
+            assert_eq!(Liability::by_tag(Tag::Delivery), vec![0]);
+            assert_eq!(Liability::by_tag(Tag::Sensing), vec![0]);
+            assert_eq!(Liability::by_tag(Tag::Compute), Vec::<u64>::new());
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::NewLiability(
+                    Compact(0),
+                    Liability::id_of(0).unwrap(),
+                    technics_hash(&technics),
+                    (),
+                    promisee,
+                    promisor,
+                    1,
+                    tags,
+                ))
+            );
+        })
+    }
+
+    #[test]
+    fn test_create_restricted() {
+        use frame_support::unsigned::ValidateUnsigned;
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = vec![1];
+            let (alice, bob, charlie) =
+                (account("//Alice"), account("//Bob"), account("//Charlie"));
+            let (_, promisor_proof) = get_params_proof("//Bob", &technics, &());
+            let restricted_proof = |requirement: &PromisorRequirementOf<Runtime>| {
+                crate::testing::make_restricted_party_proof("//Alice", &technics, &(), requirement)
+                    .1
+            };
+            let create = |requirement: PromisorRequirementOf<Runtime>, promisee_proof| {
+                Liability::create_restricted(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    alice.clone(),
+                    bob.clone(),
+                    requirement,
+                    promisee_proof,
+                    promisor_proof.clone(),
+                )
+            };
+
+            // Requirement should be covered by promisee proof
+            let allowed = PromisorRequirement::AllowList(vec![charlie.clone(), bob.clone()]);
+            let (_, plain_proof) = get_params_proof("//Alice", &technics, &());
+            assert_err!(
+                create(allowed.clone(), plain_proof),
+                Error::<Runtime>::BadPromiseeProof
+            );
+            let narrowed = PromisorRequirement::AllowList(vec![charlie.clone()]);
+            assert_err!(
+                create(allowed.clone(), restricted_proof(&narrowed)),
+                Error::<Runtime>::BadPromiseeProof
+            );
+
+            // Allow list is bounded, promisor not listed isn't allowed
+            let many = PromisorRequirement::AllowList(vec![charlie.clone(); 4]);
+            assert_err!(
+                create(many.clone(), restricted_proof(&many)),
+                Error::<Runtime>::TooManyAllowedPromisors
+            );
+            assert_err!(
+                create(narrowed.clone(), restricted_proof(&narrowed)),
+                Error::<Runtime>::PromisorNotAllowed
+            );
+            let call = Call::create_restricted(
+                technics.clone(),
+                (),
+                alice.clone(),
+                bob.clone(),
+                narrowed.clone(),
+                restricted_proof(&narrowed),
+                promisor_proof.clone(),
+            );
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &call),
+                ValidityError::NotPermitted.into()
+            );
+
+            let call = Call::create_restricted(
+                technics.clone(),
+                (),
+                alice.clone(),
+                bob.clone(),
+                allowed.clone(),
+                restricted_proof(&allowed),
+                promisor_proof.clone(),
+            );
+            assert!(Liability::validate_unsigned(TransactionSource::External, &call).is_ok());
+            assert_ok!(create(allowed.clone(), restricted_proof(&allowed)));
+            assert_eq!(
+                Liability::liability(0).map(|l| l.promisor().clone()),
+                Some(bob.clone())
+            );
+
+            // Registry requirements are checked against promisor robot entry
+            System::set_block_number(2);
+            let manufacturer = PromisorRequirement::Manufacturer(charlie.clone());
+            assert_err!(
+                create(manufacturer.clone(), restricted_proof(&manufacturer)),
+                Error::<Runtime>::PromisorNotAllowed
+            );
+            assert_ok!(Registry::register_robot(
+                Origin::signed(charlie.clone()),
+                bob.clone(),
+                b"SN-0001".to_vec(),
+                H256::repeat_byte(1),
+            ));
+            assert_ok!(create(
+                manufacturer.clone(),
+                restricted_proof(&manufacturer)
+            ));
+
+            System::set_block_number(3);
+            let other_model = PromisorRequirement::Model(H256::repeat_byte(2));
+            assert_err!(
+                create(other_model.clone(), restricted_proof(&other_model)),
+                Error::<Runtime>::PromisorNotAllowed
+            );
+            let model = PromisorRequirement::Model(H256::repeat_byte(1));
+            assert_ok!(create(model.clone(), restricted_proof(&model)));
+            assert_eq!(Liability::promisor_count(&bob), 3);
+        })
+    }
+
+    #[test]
+    fn test_list_by_tag() {
+        new_test_ext().execute_with(|| {
+            let (promisee, promisor) = (account("//Alice"), account("//Bob"));
+            for block in 1..=4 {
+                System::set_block_number(block);
+                let technics = vec![block as u8];
+                // The second liability isn't tagged, its proof doesn't cover tags
+                let (tags, (_, promisee_proof)) = if block == 2 {
+                    (vec![], get_params_proof("//Alice", &technics, &()))
+                } else {
+                    let tags = vec![Tag::Delivery];
+                    let proof =
+                        crate::testing::make_tagged_party_proof("//Alice", &technics, &(), &tags);
+                    (tags, proof)
+                };
+                let (_, promisor_proof) = get_params_proof("//Bob", &technics, &());
+                assert_ok!(Liability::create_tagged(
+                    Origin::none(),
+                    technics,
+                    (),
+                    tags,
+                    promisee.clone(),
+                    promisor.clone(),
+                    promisee_proof,
+                    promisor_proof,
+                ));
+            }
+            assert_eq!(Liability::tags_of(1), Vec::<Tag>::new());
+
+            // The oldest tagged liability is pruned from tag index
+            assert_eq!(Liability::by_tag(Tag::Delivery), vec![2, 3]);
+            let indices = |start, count| {
+                Liability::tag_liability_list(Tag::Delivery, start, count)
+                    .items
+                    .into_iter()
+                    .map(|(index, _)| index)
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(indices(0, 10), vec![2, 3]);
+            assert_eq!(indices(0, 1), vec![2]);
+            assert_eq!(indices(3, 10), vec![3]);
+            assert_eq!(indices(4, 10), Vec::<u64>::new());
+            assert_eq!(
+                Liability::tag_liability_list(Tag::Delivery, 3, 1),
+                Page {
+                    items: vec![(3, Liability::liability_info(3).unwrap())],
+                    next: 4,
+                    has_more: false,
+                }
+            );
+            assert!(Liability::tag_liability_list(Tag::Sensing, 0, 10)
+                .items
+                .is_empty());
+        })
+    }
+
+    #[test]
+    fn test_commit_report_window() {
+        use frame_support::unsigned::ValidateUnsigned;
+
+        new_test_ext().execute_with(|| {
+            create_liabilities(1);
+            let report = b"model".to_vec();
+            let salt = H256::repeat_byte(7);
+            let commitment = report_commitment(&report, &salt);
+
+            // Commitment is proven by promisor
+            let alice_proof = get_commit_proof("//Alice", &0, &commitment);
+            assert_err!(
+                Liability::commit_report(Origin::none(), 0, commitment, alice_proof.clone()),
+                Error::<Runtime>::BadReportProof
+            );
+            let commit = Call::commit_report(0, commitment, alice_proof);
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &commit),
+                ValidityError::BadReportProof.into()
+            );
+            let proof = get_commit_proof("//Bob", &0, &commitment);
+            let commit = Call::commit_report(0, commitment, proof.clone());
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &commit)
+                    .map(|validity| validity.provides),
+                Ok(vec![(FINALIZE_TAG, 0u64).encode()])
+            );
+            assert_ok!(Liability::commit_report(
+                Origin::none(),
+                0,
+                commitment,
+                proof.clone()
+            ));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::ReportCommitted(Compact(0), commitment, 4))
+            );
+            assert_eq!(Liability::commitment_of(0), Some((commitment, 4)));
+            assert_eq!(<RevealExpiringAt<Runtime>>::get(5), vec![0]);
+            assert_eq!(
+                Liability::history_of(0).last(),
+                Some(&(1, Transition::Committed(commitment)))
+            );
+
+            // Liability is frozen until reveal
+            assert_err!(
+                Liability::commit_report(Origin::none(), 0, commitment, proof),
+                Error::<Runtime>::ReportCommitted
+            );
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &commit),
+                ValidityError::AlreadySettled.into()
+            );
+            let report_proof = get_report_proof("//Bob", &0, &report);
+            let finalize = Call::finalize(0, report.clone(), report_proof.clone());
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &finalize),
+                ValidityError::AlreadySettled.into()
+            );
+            assert_err_ignore_postinfo!(
+                Liability::finalize(Origin::none(), 0, report.clone(), report_proof),
+                Error::<Runtime>::ReportCommitted
+            );
+            let cancel_proof = get_cancel_proof("//Alice", &0, &10);
+            assert_err!(
+                Liability::cancel(Origin::none(), 0, 10, Some(cancel_proof), None),
+                Error::<Runtime>::ReportCommitted
+            );
+            System::set_block_number(MinForceFinalizeAge::get() + 1);
+            assert_err_ignore_postinfo!(
+                Liability::force_finalize(Origin::root(), 0, true),
+                Error::<Runtime>::ReportCommitted
+            );
+
+            // Report revealed at the last block of window finalizes liability
+            System::set_block_number(4);
+            let reveal = Call::reveal_report(0, report.clone(), salt);
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &reveal)
+                    .map(|validity| (validity.provides, validity.longevity)),
+                Ok((vec![(REVEAL_TAG, 0u64).encode()], 1))
+            );
+            assert_ok!(Liability::reveal_report(
+                Origin::none(),
+                0,
+                report.clone(),
+                salt
+            ));
+            assert_eq!(Liability::commitment_of(0), None);
+            let record = Liability::liability_record(0).unwrap();
+            assert_eq!(record.state, RecordState::Reported);
+            assert_eq!(record.report_hash, Some(report_hash(&report)));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::NewReport(Compact(0), report_hash(&report), 4))
+            );
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &reveal),
+                ValidityError::AlreadySettled.into()
+            );
+
+            // Revealed liability is left by expiry
+            System::set_block_number(5);
+            Liability::on_initialize(5);
+            assert_eq!(
+                Liability::liability_record(0).map(|record| record.state),
+                Some(RecordState::Reported)
+            );
+            assert_eq!(<RevealExpiringAt<Runtime>>::get(5), Vec::<u64>::new());
+        })
+    }
+
+    #[test]
+    fn test_reveal_report_hash() {
+        use frame_support::unsigned::ValidateUnsigned;
+
+        // Commitment binds both report bytes and salt
+        let salt = H256::repeat_byte(7);
+        let commitment = report_commitment(&b"model".to_vec(), &salt);
+        assert_ne!(commitment, report_commitment(&b"model2".to_vec(), &salt));
+        assert_ne!(
+            commitment,
+            report_commitment(&b"model".to_vec(), &H256::repeat_byte(8))
+        );
+        assert_eq!(
+            commitment,
+            sp_io::hashing::blake2_256(&[&b"model".to_vec().encode()[..], &[7; 32]].concat())
+                .into()
+        );
+
+        new_test_ext().execute_with(|| {
+            create_liabilities(1);
+            let report = b"model".to_vec();
+            assert_err_ignore_postinfo!(
+                Liability::reveal_report(Origin::none(), 0, report.clone(), salt),
+                Error::<Runtime>::NoCommitment
+            );
+            let reveal = Call::reveal_report(0, report.clone(), salt);
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &reveal),
+                InvalidTransaction::Future.into()
+            );
+
+            let proof = get_commit_proof("//Bob", &0, &commitment);
+            assert_ok!(Liability::commit_report(
+                Origin::none(),
+                0,
+                commitment,
+                proof
+            ));
+            let wrong_salt = H256::repeat_byte(8);
+            assert_err_ignore_postinfo!(
+                Liability::reveal_report(Origin::none(), 0, report.clone(), wrong_salt),
+                Error::<Runtime>::CommitmentMismatch
+            );
+            let wrong_report = b"model2".to_vec();
+            assert_err_ignore_postinfo!(
+                Liability::reveal_report(Origin::none(), 0, wrong_report.clone(), salt),
+                Error::<Runtime>::CommitmentMismatch
+            );
+            let reveal = Call::reveal_report(0, wrong_report, salt);
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &reveal),
+                ValidityError::CommitmentMismatch.into()
+            );
+            assert_eq!(Liability::commitment_of(0), Some((commitment, 4)));
+            assert_ok!(Liability::reveal_report(Origin::none(), 0, report, salt));
+            assert!(Liability::is_finalized(0));
+        })
+    }
+
+    #[test]
+    fn test_reveal_timeout() {
+        new_test_ext().execute_with(|| {
+            create_liabilities(1);
+            let report = b"model".to_vec();
+            let salt = H256::repeat_byte(7);
+            let commitment = report_commitment(&report, &salt);
+            let proof = get_commit_proof("//Bob", &0, &commitment);
+            assert_ok!(Liability::commit_report(
+                Origin::none(),
+                0,
+                commitment,
+                proof
+            ));
+
+            // Reveal after window is rejected even before expiry
+            System::set_block_number(5);
+            assert_err_ignore_postinfo!(
+                Liability::reveal_report(Origin::none(), 0, report.clone(), salt),
+                Error::<Runtime>::RevealWindowPassed
+            );
+
+            // Not revealed liability is finalized in promisee favor
+            Liability::on_initialize(5);
+            assert!(System::events()
+                .iter()
+                .any(|record| record.event
+                    == MetaEvent::liability(RawEvent::RevealExpired(Compact(0)))));
+            assert_eq!(Liability::commitment_of(0), None);
+            let record = Liability::liability_record(0).unwrap();
+            assert_eq!(record.state, RecordState::Forced(false));
+            assert_eq!(record.finalized_at, Some(5));
+            assert_eq!(Liability::counters().cancelled, 1);
+            assert_eq!(Liability::reveal_sweep_cursor(), (6, 0));
+            assert_err_ignore_postinfo!(
+                Liability::reveal_report(Origin::none(), 0, report, salt),
+                Error::<Runtime>::NoCommitment
+            );
+        })
+    }
+
+    #[test]
+    fn test_finalize_claimed() {
+        new_test_ext().execute_with(|| {
+            create_liabilities(1);
+            let report = b"model".to_vec();
+            let proof = get_report_proof("//Bob", &0, &report);
+            let relayer = Origin::signed(account("//Charlie"));
+            let unsigned = UnsignedInBlock::get();
+            System::set_block_number(6);
+
+            // Claimed path is signed
+            assert_err_ignore_postinfo!(
+                Liability::finalize_claimed(
+                    Origin::none(),
+                    0,
+                    report.clone(),
+                    None,
+                    proof.clone(),
+                    4,
+                    get_claim_proof("//Bob", &0, &4),
+                ),
+                sp_runtime::DispatchError::BadOrigin
+            );
+
+            // Claim withheld by relayer longer than tolerance is stale
+            assert_err_ignore_postinfo!(
+                Liability::finalize_claimed(
+                    relayer.clone(),
+                    0,
+                    report.clone(),
+                    None,
+                    proof.clone(),
+                    3,
+                    get_claim_proof("//Bob", &0, &3),
+                ),
+                Error::<Runtime>::ClaimTooOld
+            );
+            assert_err_ignore_postinfo!(
+                Liability::finalize_claimed(
+                    relayer.clone(),
+                    0,
+                    report.clone(),
+                    None,
+                    proof.clone(),
+                    9,
+                    get_claim_proof("//Bob", &0, &9),
+                ),
+                Error::<Runtime>::ClaimInFuture
+            );
+
+            // Claim is signed by promisor for the claimed block
+            assert_err_ignore_postinfo!(
+                Liability::finalize_claimed(
+                    relayer.clone(),
+                    0,
+                    report.clone(),
+                    None,
+                    proof.clone(),
+                    4,
+                    get_claim_proof("//Alice", &0, &4),
+                ),
+                Error::<Runtime>::BadClaimProof
+            );
+            assert_err_ignore_postinfo!(
+                Liability::finalize_claimed(
+                    relayer.clone(),
+                    0,
+                    report.clone(),
+                    None,
+                    proof.clone(),
+                    4,
+                    get_claim_proof("//Bob", &0, &5),
+                ),
+                Error::<Runtime>::BadClaimProof
+            );
+            assert_eq!(
+                Liability::liability_record(0).unwrap().state,
+                RecordState::Open
+            );
+            assert_eq!(Liability::claimed_arrival(0), None);
+
+            // The oldest claim in tolerance is the report arrival
+            assert_ok!(Liability::finalize_claimed(
+                relayer.clone(),
+                0,
+                report.clone(),
+                None,
+                proof.clone(),
+                4,
+                get_claim_proof("//Bob", &0, &4),
+            ));
+            let record = Liability::liability_record(0).unwrap();
+            assert_eq!(record.state, RecordState::Reported);
+            assert_eq!(record.finalized_at, Some(6));
+            assert_eq!(Liability::claimed_arrival(0), Some(4));
+            assert_eq!(Liability::report_arrival(0), Some(4));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::ArrivalClaimed(Compact(0), 4))
+            );
+            // Signed report isn't counted by unsigned calls limit
+            assert_eq!(UnsignedInBlock::get(), unsigned);
+
+            assert!(Liability::finalize_claimed(
+                relayer,
+                0,
+                report,
+                None,
+                proof,
+                5,
+                get_claim_proof("//Bob", &0, &5),
+            )
+            .is_err());
+            assert_eq!(Liability::claimed_arrival(0), Some(4));
+        })
+    }
+
+    /// Create liability of Alice and Bob with relay bounty at current block.
+    fn create_with_bounty(technics: Vec<u8>, bounty: BalanceOf<Runtime>) -> DispatchResult {
+        let (alice, promisee_proof) = get_bounty_proof("//Alice", &technics, &bounty);
+        let (bob, promisor_proof) = get_params_proof("//Bob", &technics, &());
+        Liability::create_with_bounty(
+            Origin::none(),
+            technics,
+            (),
+            alice,
+            bob,
+            bounty,
+            promisee_proof,
+            promisor_proof,
+        )
+    }
+
+    #[test]
+    fn test_create_with_bounty() {
+        use frame_support::unsigned::ValidateUnsigned;
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let alice = account("//Alice");
+            let technics = vec![1];
+
+            // Bounty is agreed by promisee
+            let (_, promisee_proof) = get_bounty_proof("//Alice", &technics, &20);
+            let (_, promisor_proof) = get_params_proof("//Bob", &technics, &());
+            let call = Call::create_with_bounty(
+                technics.clone(),
+                (),
+                alice.clone(),
+                account("//Bob"),
+                30,
+                promisee_proof.clone(),
+                promisor_proof.clone(),
+            );
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &call),
+                ValidityError::BadPromiseeProof.into()
+            );
+            assert_err!(
+                Liability::create_with_bounty(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    alice.clone(),
+                    account("//Bob"),
+                    30,
+                    promisee_proof,
+                    promisor_proof,
+                ),
+                Error::<Runtime>::BadPromiseeProof
+            );
+
+            // Promisee should afford the bounty
+            let (_, promisee_proof) = get_bounty_proof("//Alice", &technics, &2000);
+            let (_, promisor_proof) = get_params_proof("//Bob", &technics, &());
+            let call = Call::create_with_bounty(
+                technics.clone(),
+                (),
+                alice.clone(),
+                account("//Bob"),
+                2000,
+                promisee_proof,
+                promisor_proof,
+            );
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &call),
+                InvalidTransaction::Payment.into()
+            );
+            assert!(create_with_bounty(technics.clone(), 2000).is_err());
+            assert_eq!(Balances::reserved_balance(&alice), 0);
+
+            assert_ok!(create_with_bounty(technics, 20));
+            assert_eq!(Liability::relay_bounty(0), Some(20));
+            assert_eq!(Balances::reserved_balance(&alice), 20);
+            assert_eq!(Liability::named_reserve(RESERVE_ID, &alice), 20);
+
+            // Zero bounty isn't kept
+            System::set_block_number(2);
+            assert_ok!(create_with_bounty(vec![2], 0));
+            assert_eq!(Liability::relay_bounty(1), None);
+        })
+    }
+
+    #[test]
+    fn test_finalize_relayed() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let (alice, charlie) = (account("//Alice"), account("//Charlie"));
+            assert_ok!(create_with_bounty(vec![1], 20));
+            let report = b"model".to_vec();
+            let proof = get_report_proof("//Bob", &0, &report);
+
+            // Relay path is signed
+            assert_err_ignore_postinfo!(
+                Liability::finalize_relayed(Origin::none(), 0, report.clone(), proof.clone()),
+                sp_runtime::DispatchError::BadOrigin
+            );
+
+            // Mangled report isn't covered by promisor proof
+            assert!(Liability::finalize_relayed(
+                Origin::signed(charlie.clone()),
+                0,
+                b"forged".to_vec(),
+                proof.clone(),
+            )
+            .is_err());
+            assert_eq!(Liability::relay_bounty(0), Some(20));
+            assert_eq!(Balances::free_balance(&charlie), 1000);
+
+            assert_ok!(Liability::finalize_relayed(
+                Origin::signed(charlie.clone()),
+                0,
+                report.clone(),
+                proof.clone(),
+            ));
+            assert_eq!(Liability::relay_bounty(0), None);
+            assert_eq!(Balances::free_balance(&charlie), 1020);
+            assert_eq!(Balances::free_balance(&alice), 980);
+            assert_eq!(Balances::reserved_balance(&alice), 0);
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::RelayBountyPaid(Compact(0), charlie.clone(), 20))
+            );
+
+            // The first relayer wins
+            assert!(Liability::finalize_relayed(
+                Origin::signed(account("//Dave")),
+                0,
+                report,
+                proof,
+            )
+            .is_err());
+            assert_eq!(Balances::free_balance(&account("//Dave")), 0);
+        })
+    }
+
+    #[test]
+    fn test_report_submitter() {
+        new_test_ext().execute_with(|| {
+            create_liabilities(3);
+            let (alice, charlie) = (account("//Alice"), account("//Charlie"));
+            let report = b"report".to_vec();
+            let submitted = |index| {
+                Liability::history_of(index).into_iter().find_map(
+                    |(_, transition)| match transition {
+                        Transition::Submitted(submitter) => Some(submitter),
+                        _ => None,
+                    },
+                )
+            };
+
+            // Submitter of unsigned report is unknown
+            System::set_block_number(4);
+            let proof = get_report_proof("//Bob", &0, &report);
+            assert_ok!(Liability::finalize(
+                Origin::none(),
+                0,
+                report.clone(),
+                proof
+            ));
+            assert_eq!(submitted(0), None);
+
+            // Self-identified submitter isn't proven by report proof
+            System::set_block_number(5);
+            let proof = get_report_proof("//Bob", &1, &report);
+            let call = Call::finalize_attributed(1, report.clone(), proof.clone(), charlie.clone());
+            assert!(Liability::validate_unsigned(TransactionSource::External, &call).is_ok());
+            assert_ok!(Liability::finalize_attributed(
+                Origin::none(),
+                1,
+                report.clone(),
+                proof,
+                charlie.clone(),
+            ));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::ReportSubmitted(
+                    Compact(1),
+                    Submitter::Unauthenticated(charlie.clone())
+                ))
+            );
+            assert_eq!(
+                submitted(1),
+                Some(Submitter::Unauthenticated(charlie.clone()))
+            );
+            assert_eq!(
+                Liability::history_of(1).last(),
+                Some(&(
+                    5,
+                    Transition::Submitted(Submitter::Unauthenticated(charlie))
+                ))
+            );
+
+            // Signer of report transaction is its submitter
+            System::set_block_number(6);
+            let proof = get_report_proof("//Bob", &2, &report);
+            assert_ok!(Liability::finalize_relayed(
+                Origin::signed(alice.clone()),
+                2,
+                report,
+                proof,
+            ));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::ReportSubmitted(
+                    Compact(2),
+                    Submitter::Signed(alice.clone())
+                ))
+            );
+            assert_eq!(submitted(2), Some(Submitter::Signed(alice)));
+        })
+    }
+
+    #[test]
+    fn test_blinded_liability() {
+        use crate::testing::{make_blinded_params_proof, make_blinded_report_proof};
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let (alice, bob, dave) = (account("//Alice"), account("//Bob"), account("//Dave"));
+            let technics = b"QmPrivate".to_vec();
+            let (alice_salt, bob_salt) = (H256::repeat_byte(1), H256::repeat_byte(2));
+            let promisee = party_commitment(&alice, &alice_salt);
+            let promisor = party_commitment(&bob, &bob_salt);
+            let parties = (&promisee, &promisor);
+            let promisee_proof =
+                make_blinded_params_proof("//Alice", &technics, &(), parties, &dave);
+            let promisor_proof = make_blinded_params_proof("//Bob", &technics, &(), parties, &dave);
+            let create = |promisee_proof: &ProofParam<Runtime>,
+                          promisor_proof: &ProofParam<Runtime>| {
+                Call::create_blinded(
+                    technics.clone(),
+                    (),
+                    alice.clone(),
+                    alice_salt,
+                    bob.clone(),
+                    bob_salt,
+                    dave.clone(),
+                    promisee_proof.clone(),
+                    promisor_proof.clone(),
+                )
+            };
+
+            // Parties proofs cover payout account
+            let (_, plain_proof) = get_params_proof("//Bob", &technics, &());
+            assert_eq!(
+                Liability::validate_unsigned(
+                    TransactionSource::External,
+                    &create(&promisee_proof, &plain_proof)
+                ),
+                ValidityError::BadPromisorProof.into()
+            );
+            assert!(Liability::validate_unsigned(
+                TransactionSource::External,
+                &create(&promisee_proof, &promisor_proof)
+            )
+            .is_ok());
+            let create_blinded = || {
+                Liability::create_blinded(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    alice.clone(),
+                    alice_salt,
+                    bob.clone(),
+                    bob_salt,
+                    dave.clone(),
+                    promisee_proof.clone(),
+                    promisor_proof.clone(),
+                )
+            };
+            assert_ok!(create_blinded());
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::BlindedLiabilityCreated(
+                    Compact(0),
+                    promisee,
+                    promisor
+                ))
+            );
+
+            // Parties accounts aren't kept in state
+            let blinded = Liability::blinded_liability(0).unwrap();
+            assert_eq!((blinded.promisee, blinded.promisor), (promisee, promisor));
+            assert_eq!(blinded.payout, dave);
+            assert_eq!(Liability::blinded_count(), 1);
+            assert_eq!(Liability::latest_index(), 0);
+            let encoded = blinded.encode();
+            for party in &[&alice, &bob] {
+                let party = party.encode();
+                assert!(!encoded
+                    .windows(party.len())
+                    .any(|window| window == &party[..]));
+            }
+
+            // Agreement proofs aren't replayed
+            assert_err!(create_blinded(), Error::<Runtime>::DuplicateAgreement);
+
+            // Report is accepted when both commitments are opened by promisor proof
+            let report = b"QmReport".to_vec();
+            let proof = make_blinded_report_proof("//Bob", &0u64, &report);
+            let finalize = |salt, proof: &ProofParam<Runtime>| {
+                Liability::finalize_blinded(
+                    Origin::none(),
+                    0,
+                    report.clone(),
+                    alice.clone(),
+                    salt,
+                    bob.clone(),
+                    bob_salt,
+                    proof.clone(),
+                )
+            };
+            assert_err!(
+                finalize(bob_salt, &proof),
+                Error::<Runtime>::PartyCommitmentMismatch
+            );
+            let plain_proof = get_report_proof("//Bob", &0, &report);
+            assert_err!(
+                finalize(alice_salt, &plain_proof),
+                Error::<Runtime>::BadReportProof
+            );
+            assert_ok!(finalize(alice_salt, &proof));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::BlindedLiabilityFinalized(
+                    Compact(0),
+                    report_hash(&report)
+                ))
+            );
+            assert_eq!(
+                Liability::blinded_liability(0).unwrap().report,
+                Some(report_hash(&report))
+            );
+            assert_err!(
+                finalize(alice_salt, &proof),
+                Error::<Runtime>::AlreadyFinalized
+            );
+        })
+    }
+
+    #[test]
+    fn test_relay_bounty_refund() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let alice = account("//Alice");
+            let report = b"model".to_vec();
+
+            // Promisor self-finalizes by unsigned report
+            assert_ok!(create_with_bounty(vec![1], 20));
+            let proof = get_report_proof("//Bob", &0, &report);
+            assert_ok!(Liability::finalize(
+                Origin::none(),
+                0,
+                report.clone(),
+                proof
+            ));
+            assert_eq!(Liability::relay_bounty(0), None);
+            assert_eq!(Balances::free_balance(&alice), 1000);
+
+            // Promisor relays its report
+            System::set_block_number(2);
+            assert_ok!(create_with_bounty(vec![2], 20));
+            let proof = get_report_proof("//Bob", &1, &report);
+            assert_ok!(Liability::finalize_relayed(
+                Origin::signed(account("//Bob")),
+                1,
+                report,
+                proof,
+            ));
+            assert_eq!(Balances::free_balance(&alice), 1000);
+            assert_eq!(Balances::free_balance(&account("//Bob")), 1000);
+
+            // Cancelled liability returns bounty
+            System::set_block_number(3);
+            assert_ok!(create_with_bounty(vec![3], 20));
+            assert_eq!(Balances::reserved_balance(&alice), 20);
+            let promisee_proof = get_cancel_proof("//Alice", &2, &5);
+            assert_ok!(Liability::cancel(
+                Origin::none(),
+                2,
+                5,
+                Some(promisee_proof),
+                None
+            ));
+            assert_eq!(Liability::relay_bounty(2), None);
+            assert_eq!(Balances::reserved_balance(&alice), 0);
+            assert_eq!(Liability::named_reserve(RESERVE_ID, &alice), 0);
+        })
+    }
+
+    #[test]
+    fn test_claim_abuse() {
+        new_test_ext().execute_with(|| {
+            create_liabilities(2);
+            let relayer = Origin::signed(account("//Charlie"));
+            let report = b"model".to_vec();
+            let proof = get_report_proof("//Bob", &1, &report);
+
+            // Report couldn't arrive before liability is created, even in tolerance
+            assert_err_ignore_postinfo!(
+                Liability::finalize_claimed(
+                    relayer.clone(),
+                    1,
+                    report.clone(),
+                    None,
+                    proof.clone(),
+                    1,
+                    get_claim_proof("//Bob", &1, &1),
+                ),
+                Error::<Runtime>::ClaimBeforeCreation
+            );
+
+            // Claim of one liability isn't valid for another one
+            assert_err_ignore_postinfo!(
+                Liability::finalize_claimed(
+                    relayer.clone(),
+                    1,
+                    report.clone(),
+                    None,
+                    proof.clone(),
+                    2,
+                    get_claim_proof("//Bob", &0, &2),
+                ),
+                Error::<Runtime>::BadClaimProof
+            );
+
+            // Claim ahead in tolerance arrives at inclusion block
+            assert_err_ignore_postinfo!(
+                Liability::finalize_claimed(
+                    relayer.clone(),
+                    1,
+                    report.clone(),
+                    None,
+                    proof.clone(),
+                    5,
+                    get_claim_proof("//Bob", &1, &5),
+                ),
+                Error::<Runtime>::ClaimInFuture
+            );
+            assert_ok!(Liability::finalize_claimed(
+                relayer,
+                1,
+                report.clone(),
+                None,
+                proof,
+                4,
+                get_claim_proof("//Bob", &1, &4),
+            ));
+            assert_eq!(Liability::report_arrival(1), Some(2));
+
+            // Unclaimed report arrives at its inclusion block
+            let unsigned = UnsignedInBlock::get();
+            System::set_block_number(3);
+            assert_ok!(Liability::finalize(
+                Origin::none(),
+                0,
+                report.clone(),
+                get_report_proof("//Bob", &0, &report),
+            ));
+            assert_eq!(Liability::claimed_arrival(0), None);
+            assert_eq!(Liability::report_arrival(0), Some(3));
+            assert_eq!(UnsignedInBlock::get(), unsigned + 1);
+        })
+    }
+
+    fn get_witnessing_proof(
+        uri: &str,
+        index: &LiabilityIndex<Runtime>,
+        quorum: u32,
+    ) -> ProofParam<Runtime> {
+        crate::testing::make_witnessing_proof(uri, index, quorum)
+    }
+
+    fn get_notarize_proof(
+        uri: &str,
+        index: &LiabilityIndex<Runtime>,
+        report_hash: &H256,
+    ) -> ProofParam<Runtime> {
+        crate::testing::make_notarize_proof(uri, index, report_hash)
+    }
+
+    #[test]
+    fn test_witness_registry() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let alice = account("//Alice");
+            let bob = account("//Bob");
+            let charlie = account("//Charlie");
+            let dave = account("//Dave");
+            let register = |who: &AccountId, stake| {
+                Liability::register_witness(Origin::signed(who.clone()), stake)
+            };
+
+            assert_err!(register(&charlie, 40), Error::<Runtime>::StakeTooLow);
+            assert_ok!(register(&charlie, 50));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::WitnessRegistered(charlie.clone(), 50))
+            );
+            assert_err!(register(&charlie, 50), Error::<Runtime>::AlreadyWitness);
+            assert_ok!(register(&alice, 100));
+            assert_ok!(register(&bob, 60));
+            assert_err!(register(&dave, 50), Error::<Runtime>::TooManyWitnesses);
+            assert_eq!(Balances::reserved_balance(&alice), 100);
+            assert_eq!(
+                Liability::witnesses(),
+                vec![charlie.clone(), alice.clone(), bob.clone()]
+            );
+
+            assert_err!(
+                Liability::unregister_witness(Origin::signed(dave)),
+                Error::<Runtime>::NotWitness
+            );
+            assert_ok!(Liability::unregister_witness(Origin::signed(alice.clone())));
+            assert_eq!(Balances::reserved_balance(&alice), 0);
+            assert_eq!(Liability::witness_stake(&alice), None);
+            assert_eq!(Liability::witnesses(), vec![charlie, bob]);
+        })
+    }
+
+    #[test]
+    fn test_witnessed_report() {
+        use frame_support::traits::OnInitialize;
+        new_test_ext().execute_with(|| {
+            create_liabilities(2);
+            let alice = account("//Alice");
+            let bob = account("//Bob");
+            let charlie = account("//Charlie");
+            let dave = account("//Dave");
+            Balances::make_free_balance_be(&dave, 1000);
+            for who in [&charlie, &dave, &bob].iter() {
+                assert_ok!(Liability::register_witness(
+                    Origin::signed((*who).clone()),
+                    50
+                ));
+            }
+
+            // Promisee requires witnesses with promisor consent
+            let require = |index, quorum, proof| {
+                Liability::require_witnesses(Origin::signed(alice.clone()), index, quorum, proof)
+            };
+            assert_err!(
+                Liability::require_witnesses(
+                    Origin::signed(charlie.clone()),
+                    0,
+                    2,
+                    get_witnessing_proof("//Bob", &0, 2)
+                ),
+                Error::<Runtime>::NotPromisee
+            );
+            assert_err!(
+                require(0, 4, get_witnessing_proof("//Bob", &0, 4)),
+                Error::<Runtime>::InvalidQuorum
+            );
+            assert_err!(
+                require(0, 2, get_witnessing_proof("//Bob", &0, 1)),
+                Error::<Runtime>::BadPromisorProof
+            );
+            assert_ok!(require(0, 2, get_witnessing_proof("//Bob", &0, 2)));
+            assert_ok!(require(1, 2, get_witnessing_proof("//Bob", &1, 2)));
+            assert_err!(
+                require(0, 1, get_witnessing_proof("//Bob", &0, 1)),
+                Error::<Runtime>::AlreadyWitnessed
+            );
+
+            // Reports wait for settlement during challenge window
+            let report = b"done".to_vec();
+            let hash = report_hash(&report);
+            let notarize = |who: &AccountId, uri, index| {
+                let proof = get_notarize_proof(uri, &index, &hash);
+                Liability::notarize(Origin::signed(who.clone()), index, proof)
+            };
+            assert_err!(
+                notarize(&charlie, "//Charlie", 0),
+                Error::<Runtime>::NotarizationClosed
+            );
+            for index in 0..2 {
+                assert_ok!(Liability::finalize(
+                    Origin::none(),
+                    index,
+                    report.clone(),
+                    get_report_proof("//Bob", &index, &report),
+                ));
+            }
+            assert_eq!(
+                Liability::liability_record(0).map(|record| record.state),
+                Some(RecordState::Reported)
+            );
+            assert_eq!(Liability::counters().finalized, 0);
+            assert_eq!(Liability::settlement_deadline(0), Some(7));
+
+            // Only independent witnesses notarize once with own proof
+            assert_err!(notarize(&alice, "//Alice", 0), Error::<Runtime>::NotWitness);
+            assert_err!(notarize(&bob, "//Bob", 0), Error::<Runtime>::PartyWitness);
+            assert_err!(
+                notarize(&charlie, "//Dave", 0),
+                Error::<Runtime>::BadNotaryProof
+            );
+            assert_ok!(notarize(&charlie, "//Charlie", 0));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::Notarized(Compact(0), charlie.clone()))
+            );
+            assert_err!(
+                notarize(&charlie, "//Charlie", 0),
+                Error::<Runtime>::AlreadyNotarized
+            );
+            assert_ok!(notarize(&dave, "//Dave", 0));
+            assert_ok!(notarize(&charlie, "//Charlie", 1));
+            assert_eq!(Liability::witness_pending(&charlie), 2);
+            assert_err!(
+                Liability::unregister_witness(Origin::signed(charlie.clone())),
+                Error::<Runtime>::WitnessBusy
+            );
+
+            // Report notarized by quorum is settled in promisor favor
+            System::set_block_number(8);
+            assert_err!(
+                notarize(&dave, "//Dave", 1),
+                Error::<Runtime>::NotarizationClosed
+            );
+            Liability::on_initialize(8);
+            let events: Vec<_> = System::events().into_iter().map(|r| r.event).collect();
+            assert!(
+                events.contains(&MetaEvent::liability(RawEvent::ReportSettled(
+                    Compact(0),
+                    true
+                )))
+            );
+            assert!(
+                events.contains(&MetaEvent::liability(RawEvent::ReportSettled(
+                    Compact(1),
+                    false
+                )))
+            );
+            assert_eq!(Liability::counters().finalized, 2);
+            assert_eq!(Liability::settlement_deadline(0), None);
+            assert_eq!(Liability::settlement_sweep_cursor(), (9, 0));
+            assert!(Liability::notarizations(0).is_empty());
+            assert_eq!(Liability::witness_pending(&charlie), 0);
+            assert_ok!(Liability::unregister_witness(Origin::signed(charlie)));
+        })
+    }
+
+    #[test]
+    fn test_overturn_report() {
+        use frame_support::traits::OnInitialize;
+        new_test_ext().execute_with(|| {
+            create_liabilities(1);
+            let alice = account("//Alice");
+            let charlie = account("//Charlie");
+            assert_ok!(Liability::register_witness(
+                Origin::signed(charlie.clone()),
+                50
+            ));
+            assert_ok!(Liability::require_witnesses(
+                Origin::signed(alice.clone()),
+                0,
+                1,
+                get_witnessing_proof("//Bob", &0, 1)
+            ));
+            let report = b"forged".to_vec();
+            assert_ok!(Liability::finalize(
+                Origin::none(),
+                0,
+                report.clone(),
+                get_report_proof("//Bob", &0, &report),
+            ));
+            assert_ok!(Liability::notarize(
+                Origin::signed(charlie.clone()),
+                0,
+                get_notarize_proof("//Charlie", &0, &report_hash(&report))
+            ));
+
+            // Dispute resolution overturns notarized report and slashes its notaries
+            assert_err!(
+                Liability::overturn_report(Origin::signed(alice.clone()), 0),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_ok!(Liability::overturn_report(Origin::root(), 0));
+            assert_eq!(Balances::free_balance(&alice), 1020);
+            assert_eq!(Balances::reserved_balance(&charlie), 30);
+            assert_eq!(Liability::witness_stake(&charlie), Some(30));
+            assert_eq!(Liability::witness_pending(&charlie), 0);
+            let events: Vec<_> = System::events().into_iter().map(|r| r.event).collect();
+            assert!(
+                events.contains(&MetaEvent::liability(RawEvent::WitnessSlashed(
+                    charlie.clone(),
+                    20
+                )))
+            );
+            assert!(
+                events.contains(&MetaEvent::liability(RawEvent::ReportSettled(
+                    Compact(0),
+                    false
+                )))
+            );
+            assert_eq!(Liability::counters().finalized, 1);
+            assert_err!(
+                Liability::overturn_report(Origin::root(), 0),
+                Error::<Runtime>::NotarizationClosed
+            );
+
+            // Overturned report isn't settled again after challenge window
+            System::set_block_number(7);
+            Liability::on_initialize(7);
+            assert_eq!(Liability::counters().finalized, 1);
+
+            // The rest of slashed stake is returned
+            assert_ok!(Liability::unregister_witness(Origin::signed(
+                charlie.clone()
+            )));
+            assert_eq!(Balances::free_balance(&charlie), 980);
+        })
+    }
+
+    #[test]
+    fn test_draw_jury() {
+        let candidates: Vec<u32> = (0..10).collect();
+        let jury = jury::draw_jury(candidates.clone(), 3, b"seed");
+        assert_eq!(jury.len(), 3);
+        assert!(jury.iter().all(|juror| candidates.contains(juror)));
+        let mut distinct = jury.clone();
+        distinct.sort();
+        distinct.dedup();
+        assert_eq!(distinct.len(), 3);
+
+        // Drawing is determined by seed only
+        assert_eq!(jury::draw_jury(candidates.clone(), 3, b"seed"), jury);
+        assert_ne!(
+            jury::draw_jury(candidates.clone(), 10, b"other"),
+            candidates
+        );
+        assert_eq!(jury::draw_jury(candidates.clone(), 20, b"seed").len(), 10);
+        assert!(jury::draw_jury(Vec::<u32>::new(), 3, b"seed").is_empty());
+    }
+
+    /// Liability 0 with report notarized by Charlie waiting for settlement at block 7,
+    /// Dave and Eve in juror pool, Ferdie funded to join it.
+    fn create_disputable_report() -> MarketId {
+        create_liabilities(1);
+        let charlie = account("//Charlie");
+        assert_ok!(Liability::register_witness(
+            Origin::signed(charlie.clone()),
+            50
+        ));
+        assert_ok!(Liability::require_witnesses(
+            Origin::signed(account("//Alice")),
+            0,
+            1,
+            get_witnessing_proof("//Bob", &0, 1)
+        ));
+        let report = b"done".to_vec();
+        assert_ok!(Liability::finalize(
+            Origin::none(),
+            0,
+            report.clone(),
+            get_report_proof("//Bob", &0, &report),
+        ));
+        assert_ok!(Liability::notarize(
+            Origin::signed(charlie),
+            0,
+            get_notarize_proof("//Charlie", &0, &report_hash(&report))
+        ));
+
+        for uri in &["//Dave", "//Eve", "//Ferdie"] {
+            Balances::make_free_balance_be(&account(uri), 1000);
+        }
+        assert_ok!(Jury::join_jury(Origin::signed(account("//Dave"))));
+        assert_ok!(Jury::join_jury(Origin::signed(account("//Eve"))));
+        let record = Liability::liability_record(0).unwrap();
+        Liability::liability_market(0, &record.agreement).0
+    }
+
+    fn not_delivered() -> jury::EvidenceOf<Runtime> {
+        jury::Evidence {
+            reason: jury::ReasonCode::NotDelivered,
+            reference: None,
+            counter_liability: None,
+        }
+    }
+
+    fn vote(uri: &str, index: LiabilityIndex<Runtime>, uphold: bool) -> (H256, H256) {
+        let salt = H256::repeat_byte(uri.len() as u8);
+        let commitment = crate::vote_commitment(&index, &account(uri), uphold, &salt);
+        (commitment, salt)
+    }
+
+    #[test]
+    fn test_jury_dispute() {
+        use frame_support::traits::OnInitialize;
+        new_test_ext().execute_with(|| {
+            let market_id = create_disputable_report();
+            let alice = account("//Alice");
+            let charlie = account("//Charlie");
+            let dave = account("//Dave");
+            let eve = account("//Eve");
+            let ferdie = account("//Ferdie");
+            assert_eq!(Jury::jurors(), vec![dave.clone(), eve.clone()]);
+            assert_eq!(Balances::reserved_balance(&dave), 100);
+            assert_err!(
+                Jury::join_jury(Origin::signed(dave.clone())),
+                jury::Error::<Runtime>::AlreadyJuror
+            );
+
+            // Only parties dispute reports of jury markets before enough jurors
+            System::set_block_number(3);
+            assert_err!(
+                Jury::open_dispute(Origin::signed(alice.clone()), 0, not_delivered()),
+                jury::Error::<Runtime>::NotJuryMarket
+            );
+            assert_err!(
+                Jury::set_jury_market(Origin::signed(alice.clone()), market_id, true),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_ok!(Jury::set_jury_market(Origin::root(), market_id, true));
+            assert_err!(
+                Jury::open_dispute(Origin::signed(charlie.clone()), 0, not_delivered()),
+                jury::Error::<Runtime>::NotParty
+            );
+            assert_ok!(Jury::join_jury(Origin::signed(alice.clone())));
+            assert_err!(
+                Jury::open_dispute(Origin::signed(alice.clone()), 0, not_delivered()),
+                jury::Error::<Runtime>::NotEnoughJurors
+            );
+            assert_ok!(Jury::join_jury(Origin::signed(ferdie.clone())));
+            assert_err!(
+                Jury::join_jury(Origin::signed(charlie.clone())),
+                jury::Error::<Runtime>::PoolFull
+            );
+
+            // Dispute holds report from settlement, parties aren't drawn
+            assert_ok!(Jury::open_dispute(
+                Origin::signed(alice.clone()),
+                0,
+                not_delivered()
+            ));
+            assert_err!(
+                Jury::open_dispute(Origin::signed(alice.clone()), 0, not_delivered()),
+                jury::Error::<Runtime>::AlreadyDisputed
+            );
+            assert!(Liability::is_settlement_held(0));
+            assert_eq!(Balances::reserved_balance(&alice), 130);
+            let dispute = Jury::dispute(0).unwrap();
+            let mut jury = dispute.jury.clone();
+            jury.sort();
+            let mut drawn = vec![dave.clone(), eve.clone(), ferdie.clone()];
+            drawn.sort();
+            assert_eq!(jury, drawn);
+            assert_eq!((dispute.commit_end, dispute.reveal_end), (5, 7));
+            assert_eq!(Jury::juror_disputes(&dave), 1);
+
+            // Votes are committed first and revealed after commit window
+            for (uri, uphold) in &[("//Dave", false), ("//Eve", false), ("//Ferdie", true)] {
+                let (commitment, _) = vote(uri, 0, *uphold);
+                assert_ok!(Jury::commit_vote(
+                    Origin::signed(account(uri)),
+                    0,
+                    commitment
+                ));
+            }
+            assert_err!(
+                Jury::commit_vote(Origin::signed(charlie.clone()), 0, H256::zero()),
+                jury::Error::<Runtime>::NotDrawn
+            );
+            assert_err!(
+                Jury::commit_vote(Origin::signed(dave.clone()), 0, H256::zero()),
+                jury::Error::<Runtime>::AlreadyCommitted
+            );
+            let (_, salt) = vote("//Dave", 0, false);
+            assert_err!(
+                Jury::reveal_vote(Origin::signed(dave.clone()), 0, false, salt),
+                jury::Error::<Runtime>::RevealClosed
+            );
+            System::set_block_number(6);
+            assert_err!(
+                Jury::commit_vote(Origin::signed(ferdie.clone()), 0, H256::zero()),
+                jury::Error::<Runtime>::CommitClosed
+            );
+            assert_err!(
+                Jury::reveal_vote(Origin::signed(dave.clone()), 0, true, salt),
+                jury::Error::<Runtime>::BadReveal
+            );
+            for (uri, uphold) in &[("//Dave", false), ("//Eve", false), ("//Ferdie", true)] {
+                let (_, salt) = vote(uri, 0, *uphold);
+                assert_ok!(Jury::reveal_vote(
+                    Origin::signed(account(uri)),
+                    0,
+                    *uphold,
+                    salt
+                ));
+            }
+            assert_err!(
+                Jury::reveal_vote(Origin::signed(dave.clone()), 0, false, salt),
+                jury::Error::<Runtime>::AlreadyRevealed
+            );
+            assert_eq!(Jury::vote(0, &ferdie), Some(true));
+
+            // Held report isn't settled at the end of challenge window
+            System::set_block_number(7);
+            Liability::on_initialize(7);
+            assert_eq!(Liability::counters().finalized, 0);
+
+            // Majority overturns report, aligned jurors share fee and misaligned stake
+            System::set_block_number(8);
+            Jury::on_initialize(8);
+            let events: Vec<_> = System::events().into_iter().map(|r| r.event).collect();
+            assert!(
+                events.contains(&MetaEvent::jury(jury::RawEvent::DisputeDecided(
+                    Compact(0),
+                    false,
+                    jury::ReasonCode::NotDelivered,
+                )))
+            );
+            assert!(
+                events.contains(&MetaEvent::liability(RawEvent::ReportSettled(
+                    Compact(0),
+                    false
+                )))
+            );
+            assert_eq!(Liability::counters().finalized, 1);
+            assert!(!Liability::is_settlement_held(0));
+            assert_eq!(Jury::dispute(0), None);
+            assert_eq!(Jury::vote(0, &ferdie), None);
+            assert_eq!(Balances::free_balance(&alice), 890);
+            assert_eq!(Balances::reserved_balance(&alice), 100);
+            assert_eq!(Balances::free_balance(&dave), 940);
+            assert_eq!(Balances::free_balance(&eve), 940);
+            assert_eq!(Balances::reserved_balance(&ferdie), 50);
+            assert_eq!(Jury::juror_stake(&ferdie), Some(50));
+            assert_eq!(Liability::witness_stake(&charlie), Some(30));
+            assert_eq!(Jury::juror_disputes(&dave), 0);
+            assert_eq!(Jury::reason_stats(jury::ReasonCode::NotDelivered), (1, 0));
+            assert_eq!(
+                Jury::acceptance_rate(jury::ReasonCode::NotDelivered),
+                Perbill::one()
+            );
+            assert_eq!(
+                Jury::acceptance_rate(jury::ReasonCode::Other),
+                Perbill::zero()
+            );
+
+            // Stake is returned after unbonding period only
+            assert_err!(
+                Jury::withdraw_stake(Origin::signed(dave.clone())),
+                jury::Error::<Runtime>::NotUnbonding
+            );
+            assert_ok!(Jury::leave_jury(Origin::signed(dave.clone())));
+            assert_err!(
+                Jury::leave_jury(Origin::signed(dave.clone())),
+                jury::Error::<Runtime>::NotJuror
+            );
+            assert_err!(
+                Jury::withdraw_stake(Origin::signed(dave.clone())),
+                jury::Error::<Runtime>::StakeUnbonding
+            );
+            System::set_block_number(12);
+            assert_ok!(Jury::withdraw_stake(Origin::signed(dave.clone())));
+            assert_eq!(Balances::free_balance(&dave), 1040);
+            assert_eq!(Jury::juror_stake(&dave), None);
+        })
+    }
+
+    #[test]
+    fn test_dispute_evidence() {
+        new_test_ext().execute_with(|| {
+            let market_id = create_disputable_report();
+            create_liability(2);
+            let alice = account("//Alice");
+            assert_ok!(Jury::set_jury_market(Origin::root(), market_id, true));
+            assert_ok!(Jury::join_jury(Origin::signed(account("//Ferdie"))));
+            System::set_block_number(3);
+
+            // Other reason is described by off-chain evidence
+            let mut evidence = jury::Evidence {
+                reason: jury::ReasonCode::Other,
+                reference: None,
+                counter_liability: None,
+            };
+            assert_err!(
+                Jury::open_dispute(Origin::signed(alice.clone()), 0, evidence.clone()),
+                jury::Error::<Runtime>::MissingReference
+            );
+            evidence.reference = Some(H256::zero());
+            assert_err!(
+                Jury::open_dispute(Origin::signed(alice.clone()), 0, evidence.clone()),
+                jury::Error::<Runtime>::MissingReference
+            );
+
+            // Counter-liability is another existing liability
+            evidence.reference = Some(H256::repeat_byte(1));
+            for counter in &[0, 2] {
+                evidence.counter_liability = Some(*counter);
+                assert_err!(
+                    Jury::open_dispute(Origin::signed(alice.clone()), 0, evidence.clone()),
+                    jury::Error::<Runtime>::BadCounterLiability
+                );
+            }
+            assert_eq!(Balances::reserved_balance(&alice), 0);
+
+            evidence.counter_liability = Some(1);
+            assert_ok!(Jury::open_dispute(
+                Origin::signed(alice.clone()),
+                0,
+                evidence.clone()
+            ));
+            let dispute = Jury::dispute(0).unwrap();
+            assert_eq!(dispute.evidence, evidence);
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::jury(jury::RawEvent::DisputeOpened(
+                    Compact(0),
+                    alice,
+                    jury::ReasonCode::Other,
+                    dispute.jury,
+                ))
+            );
+        })
+    }
+
+    #[test]
+    fn test_jury_escalation() {
+        use frame_support::traits::OnInitialize;
+        new_test_ext().execute_with(|| {
+            let market_id = create_disputable_report();
+            let bob = account("//Bob");
+            let charlie = account("//Charlie");
+            let dave = account("//Dave");
+            let eve = account("//Eve");
+            assert_ok!(Jury::set_jury_market(Origin::root(), market_id, true));
+            assert_ok!(Jury::join_jury(Origin::signed(account("//Ferdie"))));
+            assert_err!(
+                Liability::uphold_report(Origin::root(), 0),
+                Error::<Runtime>::SettlementNotHeld
+            );
+
+            // Promisor disputes report, drawn juror stays busy after leaving pool
+            System::set_block_number(3);
+            assert_ok!(Jury::open_dispute(
+                Origin::signed(bob.clone()),
+                0,
+                not_delivered()
+            ));
+            assert_ok!(Jury::leave_jury(Origin::signed(dave.clone())));
+            let (commitment, salt) = vote("//Dave", 0, true);
+            assert_ok!(Jury::commit_vote(
+                Origin::signed(dave.clone()),
+                0,
+                commitment
+            ));
+            System::set_block_number(7);
+            assert_ok!(Jury::reveal_vote(
+                Origin::signed(dave.clone()),
+                0,
+                true,
+                salt
+            ));
+            assert_err!(
+                Jury::withdraw_stake(Origin::signed(dave.clone())),
+                jury::Error::<Runtime>::JurorBusy
+            );
+            Liability::on_initialize(7);
+
+            // Without quorum fee is returned, jurors not revealed are slashed
+            let issuance = Balances::total_issuance();
+            System::set_block_number(8);
+            Jury::on_initialize(8);
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::jury(jury::RawEvent::DisputeEscalated(
+                    Compact(0),
+                    jury::ReasonCode::NotDelivered
+                ))
+            );
+            assert_eq!(Balances::free_balance(&bob), 1000);
+            assert_eq!(Balances::reserved_balance(&eve), 50);
+            assert_eq!(Balances::total_issuance(), issuance - 100);
+            assert_eq!(Jury::juror_stake(&dave), Some(100));
+            assert_eq!(Liability::counters().finalized, 0);
+            assert!(Liability::is_settlement_held(0));
+            assert_eq!(Jury::reason_stats(jury::ReasonCode::NotDelivered), (0, 0));
+            assert_ok!(Jury::withdraw_stake(Origin::signed(dave.clone())));
+
+            // Governance resolves escalated dispute
+            assert_ok!(Liability::uphold_report(Origin::root(), 0));
+            let events: Vec<_> = System::events().into_iter().map(|r| r.event).collect();
+            assert!(events.contains(&MetaEvent::liability(RawEvent::ReportUpheld(Compact(0)))));
+            assert!(
+                events.contains(&MetaEvent::liability(RawEvent::ReportSettled(
+                    Compact(0),
+                    true
+                )))
+            );
+            assert_eq!(Liability::witness_stake(&charlie), Some(50));
+            assert_err!(
+                Liability::uphold_report(Origin::root(), 0),
+                Error::<Runtime>::SettlementNotHeld
+            );
+        })
+    }
+
+    #[test]
+    fn test_liability_issuer() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = b"mission".to_vec();
+            let alice = account("//Alice");
+            let bob = account("//Bob");
+
+            // Scheduler is trusted, parties proofs aren't required
+            assert_err!(
+                Scheduler::schedule(
+                    Origin::signed(alice.clone()),
+                    technics.clone(),
+                    alice.clone(),
+                    bob.clone()
+                ),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_ok!(Scheduler::schedule(
+                Origin::root(),
+                technics.clone(),
+                alice.clone(),
+                bob.clone()
+            ));
+            assert_eq!(Liability::latest_index(), 1);
+            assert_eq!(
+                Liability::liability(0).map(|liability| liability.promisor().clone()),
+                Some(bob.clone())
+            );
+            assert!(<AccountLiabilities<Runtime>>::contains_key(&alice, 0));
+            assert_eq!(Liability::counters().created, 1);
+
+            // Issued liability is validated as created one
+            assert_ok!(Liability::blacklist(Origin::root(), bob.clone()));
+            assert_err!(
+                Scheduler::schedule(Origin::root(), technics.clone(), alice.clone(), bob.clone()),
+                Error::<Runtime>::Blacklisted
+            );
+            assert_ok!(Liability::unblacklist(Origin::root(), bob.clone()));
+            assert_err!(
+                Scheduler::schedule(Origin::root(), technics, alice, bob),
+                Error::<Runtime>::DuplicateAgreement
+            );
+
+            // Settled liability is force finalized regardless of its age
+            assert_ok!(Scheduler::complete(Origin::root(), 0, true));
+            assert_eq!(Liability::forced_outcome(0), Some(true));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::ForceFinalized(Compact(0), true, 1))
+            );
+            assert_err!(
+                Scheduler::complete(Origin::root(), 0, false),
+                Error::<Runtime>::AlreadyFinalized
+            );
+            assert_err!(
+                Scheduler::complete(Origin::root(), 1, true),
+                Error::<Runtime>::UnknownLiability
+            );
+        })
+    }
+
+    #[test]
+    fn test_offchain_finalize() {
+        const GATEWAY: &[u8] = b"http://127.0.0.1:8080/ipns/robot/";
+        let report = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz5"
+            .from_base58()
+            .unwrap();
+
+        let (offchain, offchain_state) = testing::TestOffchainExt::new();
+        let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+        let keystore = KeyStore::new();
+        keystore
+            .write()
+            .sr25519_generate_new(KEY_TYPE, Some("//Bob"))
+            .unwrap();
+        offchain_state
+            .write()
+            .expect_request(testing::PendingRequest {
+                method: "GET".into(),
+                uri: "http://127.0.0.1:8080/ipns/robot/1".into(),
+                response: Some(report.clone()),
+                sent: true,
+                ..Default::default()
+            });
+
+        let mut ext = new_test_ext();
+        ext.register_extension(OffchainExt::new(offchain));
+        ext.register_extension(TransactionPoolExt::new(pool));
+        ext.register_extension(KeystoreExt(keystore));
+        ext.execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (alice, alice_proof) = get_params_proof("//Alice", &technics, &());
+            let (bob, bob_proof) = get_params_proof("//Bob", &technics, &());
+            // Alice is promisor of the first liability, Bob is promisor of the second one
+            for (promisee, promisor) in vec![
+                (
+                    (bob.clone(), bob_proof.clone()),
+                    (alice.clone(), alice_proof.clone()),
+                ),
+                ((alice, alice_proof), (bob, bob_proof)),
+            ] {
+                assert_ok!(Liability::create(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    promisee.0,
+                    promisor.0,
+                    promisee.1,
+                    promisor.1,
+                ));
+            }
+
+            // Disabled without gateway
+            Liability::offchain_worker(1);
+            assert!(pool_state.read().transactions.is_empty());
+
+            sp_io::offchain::local_storage_set(
+                StorageKind::PERSISTENT,
+                OFFCHAIN_GATEWAY_KEY,
+                GATEWAY,
+            );
+            Liability::offchain_worker(1);
+            let tx = pool_state.write().transactions.pop().unwrap();
+            assert!(pool_state.read().transactions.is_empty());
+
+            // Processed liability isn't fetched again, unexpected request panics
+            Liability::offchain_worker(2);
+            assert!(pool_state.read().transactions.is_empty());
+
+            let tx = Extrinsic::decode(&mut &*tx).unwrap();
+            assert_eq!(tx.signature, None);
+            match tx.call {
+                crate::Call::finalize(index, tx_report, proof) => {
+                    assert_eq!(index, 1);
+                    assert_eq!(tx_report, report);
+                    assert_ok!(Liability::finalize(Origin::none(), index, tx_report, proof));
+                }
+                call => panic!("unexpected call: {:?}", call),
+            }
+            assert!(Liability::is_finalized(1));
+            assert!(!Liability::is_finalized(0));
+        })
+    }
+
+    #[test]
+    fn test_index_allocation() {
+        let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+            .from_base58()
+            .unwrap();
+        let (alice, alice_proof) = get_params_proof("//Alice", &technics, &());
+        let (bob, bob_proof) = get_params_proof("//Bob", &technics, &());
+        let charlie = account("//Charlie");
+
+        let mut storage = frame_system::GenesisConfig::default()
+            .build_storage::<Runtime>()
+            .unwrap();
+        pallet_balances::GenesisConfig::<Runtime> {
+            balances: vec![(alice.clone(), 1_000), (bob.clone(), 1_000)],
+        }
+        .assimilate_storage(&mut storage)
+        .unwrap();
+        GenesisConfig::<Runtime> {
+            liabilities: vec![(technics.clone(), (), alice.clone(), bob.clone())],
+            lighthouses: vec![],
+            market_fees: vec![],
+        }
+        .assimilate_storage(&mut storage)
+        .unwrap();
+
+        sp_io::TestExternalities::from(storage).execute_with(|| {
+            assert_eq!(Liability::latest_index(), 1);
+
+            // Every creation path takes the next index of the same counter
+            System::set_block_number(1);
+            assert_ok!(Liability::create(
+                Origin::none(),
+                technics.clone(),
+                (),
+                alice.clone(),
+                bob.clone(),
+                alice_proof.clone(),
+                bob_proof.clone()
+            ));
+            System::set_block_number(2);
+            assert_ok!(Liability::create_remote(
+                Origin::signed(alice.clone()),
+                7,
+                technics.clone(),
+                (),
+                bob.clone(),
+                bob_proof.clone(),
+            ));
+            System::set_block_number(3);
+            assert_ok!(Liability::demand(
+                Origin::signed(alice.clone()),
+                technics.clone(),
+                (),
+                1,
+                5,
+                alice_proof
+            ));
+            assert_ok!(Liability::offer(
+                Origin::signed(bob.clone()),
+                technics.clone(),
+                (),
+                1,
+                5,
+                bob_proof
+            ));
+            assert_ok!(Liability::match_orders(Origin::signed(charlie), 0, 1));
+
+            assert_eq!(Liability::latest_index(), 4);
+            assert_eq!(Liability::remote_request(&alice, 7), Some(2));
+            assert_eq!(Liability::matched_order(0), Some(3));
+            for index in 0..4 {
+                let info = Liability::liability_info(index).unwrap();
+                assert_eq!((info.promisee, info.promisor), (alice.clone(), bob.clone()));
+                assert_eq!(info.created_at, index);
+            }
+            assert_eq!(Liability::promisor_liability(&bob, 3), Some(3));
+            assert_eq!(Liability::liability_info(4), None);
+        });
+    }
+
+    #[test]
+    fn test_liability_id() {
+        use frame_support::unsigned::ValidateUnsigned;
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (promisee, promisee_proof) = get_params_proof("//Alice", &technics, &());
+            let (promisor, promisor_proof) = get_params_proof("//Bob", &technics, &());
+            let create = || {
+                Liability::create(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    promisee.clone(),
+                    promisor.clone(),
+                    promisee_proof.clone(),
+                    promisor_proof.clone(),
+                )
+            };
+
+            // Id is derived from agreement and promisor nonce
+            assert_ok!(create());
+            let id = liability_id(&promisee, &promisor, &technics, &(), &0u64);
+            assert_eq!(Liability::id_of(0), Some(id));
+            assert_eq!(Liability::index_of_id(id), Some(0));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::NewLiability(
+                    Compact(0),
+                    id,
+                    technics_hash(&technics),
+                    (),
+                    promisee.clone(),
+                    promisor.clone(),
+                    1,
+                    Vec::new(),
+                ))
+            );
+
+            // Taken id is rehashed with liability index
+            System::set_block_number(2);
+            let next_id = liability_id(&promisee, &promisor, &technics, &(), &1u64);
+            <IdToIndex<Runtime>>::insert(next_id, 99);
+            assert_ok!(create());
+            let rehashed: H256 = (next_id, 1u64)
+                .using_encoded(sp_io::hashing::blake2_256)
+                .into();
+            assert_eq!(Liability::id_of(1), Some(rehashed));
+            assert_eq!(Liability::index_of_id(rehashed), Some(1));
+            assert_eq!(Liability::index_of_id(next_id), Some(99));
+
+            // Liability is finalized by id with proof for its index
+            let report = b"report".to_vec();
+            assert_err_ignore_postinfo!(
+                Liability::finalize_by_id(
+                    Origin::none(),
+                    H256::repeat_byte(1),
+                    report.clone(),
+                    get_report_proof("//Bob", &1, &report)
+                ),
+                Error::<Runtime>::UnknownLiability
+            );
+            let call = Call::finalize_by_id(
+                rehashed,
+                report.clone(),
+                get_report_proof("//Bob", &0, &report),
+            );
+            assert!(Liability::validate_unsigned(TransactionSource::External, &call).is_err());
+            let proof = get_report_proof("//Bob", &1, &report);
+            let call = Call::finalize_by_id(rehashed, report.clone(), proof.clone());
+            assert!(Liability::validate_unsigned(TransactionSource::External, &call).is_ok());
+            assert_ok!(Liability::finalize_by_id(
+                Origin::none(),
+                rehashed,
+                report.clone(),
+                proof
+            ));
+            assert_eq!(Liability::report_of(1), Some(report));
+            assert!(Liability::is_finalized(1));
+            assert!(!Liability::is_finalized(0));
+        });
+    }
+
+    #[test]
+    fn test_create_expiring() {
+        use frame_support::unsigned::ValidateUnsigned;
+
+        new_test_ext().execute_with(|| {
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (promisee, promisee_proof) =
+                crate::testing::make_expiring_party_proof("//Alice", &technics, &(), &5u64);
+            let (promisor, promisor_proof) =
+                crate::testing::make_expiring_party_proof("//Bob", &technics, &(), &5u64);
+            let call = |expires_at| {
+                Call::create_expiring(
+                    technics.clone(),
+                    (),
+                    promisee.clone(),
+                    promisor.clone(),
+                    expires_at,
+                    promisee_proof.clone(),
+                    promisor_proof.clone(),
+                )
+            };
+            let create = |expires_at| {
+                Liability::create_expiring(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    promisee.clone(),
+                    promisor.clone(),
+                    expires_at,
+                    promisee_proof.clone(),
+                    promisor_proof.clone(),
+                )
+            };
+
+            // Proofs cover expiry block, so they're valid neither for another one
+            // nor for plain `create`
+            System::set_block_number(1);
+            assert_err!(create(6), Error::<Runtime>::BadPromiseeProof);
+            assert_err!(
+                Liability::create(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    promisee.clone(),
+                    promisor.clone(),
+                    promisee_proof.clone(),
+                    promisor_proof.clone()
+                ),
+                Error::<Runtime>::BadPromiseeProof
+            );
+            let valid =
+                Liability::validate_unsigned(TransactionSource::External, &call(5)).unwrap();
+            assert_eq!(valid.longevity, 5);
+
+            // Proofs are expired the next block after expiry
+            System::set_block_number(6);
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &call(5)),
+                ValidityError::Expired.into()
+            );
+            assert_err!(create(5), Error::<Runtime>::ProofExpired);
+
+            // Expiry block itself is the last one of proofs
+            System::set_block_number(5);
+            let valid =
+                Liability::validate_unsigned(TransactionSource::External, &call(5)).unwrap();
+            assert_eq!(valid.longevity, 1);
+            assert_ok!(create(5));
+            assert_eq!(Liability::latest_index(), 1);
+            assert_eq!(
+                Liability::liability_info(0).map(|info| info.promisor),
+                Some(promisor.clone())
+            );
+        });
+    }
+
+    #[test]
+    fn test_remote_create() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let alice = sr25519::Pair::from_string("//Alice", None).unwrap();
+            let promisee = <Signature as Verify>::Signer::from(alice.public()).into_account();
+            let (promisor, promisor_proof) = get_params_proof("//Bob", &technics, &());
+
+            assert_err_ignore_postinfo!(
+                Liability::create_remote(
+                    Origin::none(),
+                    7,
+                    technics.clone(),
+                    (),
+                    promisor.clone(),
+                    promisor_proof.clone(),
+                ),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_err_ignore_postinfo!(
+                Liability::create_remote(
+                    Origin::signed(promisee.clone()),
+                    7,
+                    technics.clone(),
+                    (),
+                    promisor.clone(),
+                    get_params_proof("//Alice", &technics, &()).1,
+                ),
+                Error::<Runtime>::BadPromisorProof
+            );
+
+            assert_eq!(Liability::remote_request(&promisee, 7), None);
+            assert_ok!(Liability::create_remote(
+                Origin::signed(promisee.clone()),
+                7,
+                technics.clone(),
+                (),
+                promisor.clone(),
+                promisor_proof.clone(),
+            ));
+            assert_eq!(Liability::remote_request(&promisee, 7), Some(0));
+            assert_eq!(
+                Liability::liability(0).map(|l| l.promisee().clone()),
+                Some(promisee.clone())
+            );
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::NewLiability(
+                    Compact(0),
+                    liability_id(&promisee, &promisor, &technics, &(), &0u64),
+                    technics_hash(&technics),
+                    (),
+                    promisee.clone(),
+                    promisor.clone(),
+                    1,
+                    Vec::new(),
+                ))
+            );
+
+            assert_err_ignore_postinfo!(
+                Liability::create_remote(
+                    Origin::signed(promisee.clone()),
+                    7,
+                    technics.clone(),
+                    (),
+                    promisor.clone(),
+                    promisor_proof.clone(),
+                ),
+                Error::<Runtime>::DuplicateRequest
+            );
+            // Request ids are scoped by promisee
+            let (other, _) = get_params_proof("//Charlie", &technics, &());
+            assert_ok!(Liability::create_remote(
+                Origin::signed(other.clone()),
+                7,
+                technics,
+                (),
+                promisor,
+                promisor_proof,
+            ));
+            assert_eq!(Liability::remote_request(&other, 7), Some(1));
+        })
+    }
+
+    #[test]
+    fn test_duplicate_agreement() {
+        use frame_support::{traits::OnFinalize, unsigned::ValidateUnsigned};
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (promisee, promisee_proof) = get_params_proof("//Alice", &technics, &());
+            let (promisor, promisor_proof) = get_params_proof("//Bob", &technics, &());
+            let call = Call::create(
+                technics.clone(),
+                (),
+                promisee.clone(),
+                promisor.clone(),
+                promisee_proof.clone(),
+                promisor_proof.clone(),
+            );
+            let create = || {
+                Liability::create(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    promisee.clone(),
+                    promisor.clone(),
+                    promisee_proof.clone(),
+                    promisor_proof.clone(),
+                )
+            };
+
+            // The second relayed copy in the same block is rejected
+            assert_ok!(create());
+            assert_err!(create(), Error::<Runtime>::DuplicateAgreement);
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &call),
+                ValidityError::NonceMismatch.into()
+            );
+            assert_eq!(Liability::latest_index(), 1);
+            assert_eq!(Liability::counters().created, 1);
+            assert_eq!(
+                Liability::account_liability_list(&promisee, 0, 10)
+                    .items
+                    .len(),
+                1
+            );
+            assert_eq!(
+                HOOK_CALLS.with(|calls| calls.borrow().clone()),
+                vec![(0, None)]
+            );
+
+            // Agreements of the block are forgotten at its end
+            Liability::on_finalize(1);
+            System::set_block_number(2);
+            assert_ok!(Liability::validate_unsigned(
+                TransactionSource::External,
+                &call
+            ));
+            assert_ok!(create());
+            assert_eq!(Liability::latest_index(), 2);
+        })
+    }
+
+    #[test]
+    fn test_block_state_cleared() {
+        use frame_support::traits::{OnFinalize, OnInitialize};
+
+        new_test_ext().execute_with(|| {
+            let (alice, bob) = (account("//Alice"), account("//Bob"));
+            for block in 1..=4u64 {
+                // Invariant: transient state of previous blocks never leaks into the next one
+                assert_ok!(Liability::ensure_block_state_cleared());
+                System::set_block_number(block);
+                Liability::on_initialize(block);
+
+                let technics = vec![block as u8];
+                let (_, promisee_proof) = get_params_proof("//Alice", &technics, &());
+                let (_, promisor_proof) = get_params_proof("//Bob", &technics, &());
+                let create = || {
+                    Liability::create(
+                        Origin::none(),
+                        technics.clone(),
+                        (),
+                        alice.clone(),
+                        bob.clone(),
+                        promisee_proof.clone(),
+                        promisor_proof.clone(),
+                    )
+                };
+                assert_ok!(create());
+
+                // Rejected calls of the block leave no transient state behind
+                assert_err!(create(), Error::<Runtime>::DuplicateAgreement);
+                assert_err!(
+                    Liability::create(
+                        Origin::none(),
+                        technics.clone(),
+                        (),
+                        alice.clone(),
+                        bob.clone(),
+                        promisor_proof.clone(),
+                        promisor_proof.clone(),
+                    ),
+                    Error::<Runtime>::BadPromiseeProof
+                );
+                let index = block - 1;
+                let bad_proof = get_report_proof("//Alice", &index, &technics);
+                assert_err_ignore_postinfo!(
+                    Liability::finalize(Origin::none(), index, technics.clone(), bad_proof),
+                    Error::<Runtime>::BadReportProof
+                );
+                let proof = get_report_proof("//Bob", &index, &technics);
+                assert_ok!(Liability::finalize(
+                    Origin::none(),
+                    index,
+                    technics.clone(),
+                    proof
+                ));
+                assert_eq!(Liability::unsigned_in_block(), 2);
+                assert_eq!(
+                    Liability::pair_creates_in_block(block, (alice.clone(), bob.clone())),
+                    1
+                );
+                assert_eq!(
+                    Liability::ensure_block_state_cleared(),
+                    Err("unsigned calls count is left")
+                );
+
+                Liability::on_finalize(block);
+            }
+            assert_ok!(Liability::ensure_block_state_cleared());
+            assert_eq!(Liability::counters().finalized, 4);
+        })
+    }
+
+    #[test]
+    fn test_try_state() {
+        new_test_ext().execute_with(|| {
+            let (alice, bob, charlie) =
+                (account("//Alice"), account("//Bob"), account("//Charlie"));
+            create_liabilities(3);
+            assert_ok!(Liability::force_finalize(Origin::root(), 0, true));
+            assert_ok!(Liability::prune_liability(Origin::root(), 0));
+            assert_ok!(Liability::force_finalize(Origin::root(), 1, false));
+            let technics = vec![9];
+            assert_ok!(Liability::demand(
+                Origin::signed(alice.clone()),
+                technics.clone(),
+                (),
+                1,
+                5,
+                get_params_proof("//Alice", &technics, &()).1
+            ));
+            assert_ok!(Liability::offer(
+                Origin::signed(bob.clone()),
+                technics.clone(),
+                (),
+                1,
+                5,
+                get_params_proof("//Bob", &technics, &()).1
+            ));
+            assert_ok!(Liability::try_state());
+
+            // Each violated invariant is told by its error
+            <AccountLiabilities<Runtime>>::remove(&alice, 2);
+            assert_eq!(
+                Liability::try_state(),
+                Err("open liability isn't indexed for its parties")
+            );
+            <AccountLiabilities<Runtime>>::insert(&alice, 2, ());
+            <PromisorLiabilities<Runtime>>::insert(&bob, 3, 2);
+            assert_eq!(
+                Liability::try_state(),
+                Err("promisor local index isn't below promisor count")
+            );
+            <PromisorLiabilities<Runtime>>::remove(&bob, 3);
+            <PromisorLiabilities<Runtime>>::remove(&bob, 2);
+            assert_eq!(
+                Liability::try_state(),
+                Err("open liability isn't indexed once by promisor")
+            );
+            <PromisorLiabilities<Runtime>>::insert(&bob, 2, 2);
+            assert_ok!(Liability::try_state());
+
+            <OpenOrders<Runtime>>::insert(&charlie, 1);
+            assert_eq!(Liability::try_state(), Err("open orders count mismatch"));
+            <OpenOrders<Runtime>>::remove(&charlie);
+
+            <NamedReserves<Runtime>>::insert(&RESERVE_ID, &charlie, 1);
+            assert_eq!(
+                Liability::try_state(),
+                Err("tracked reserve isn't backed by reserved balance")
+            );
+            <NamedReserves<Runtime>>::remove(&RESERVE_ID, &charlie);
+            let reserved = Liability::named_reserve(&RESERVE_ID, &alice);
+            <NamedReserves<Runtime>>::insert(&RESERVE_ID, &alice, reserved - 1);
+            assert_eq!(
+                Liability::try_state(),
+                Err("tracked reserve doesn't equal module reservations")
+            );
+            assert_ok!(Balances::reserve(&alice, 1));
+            <NamedReserves<Runtime>>::insert(&RESERVE_ID, &alice, reserved + 1);
+            assert_eq!(
+                Liability::try_state(),
+                Err("tracked reserve doesn't equal module reservations")
+            );
+            <NamedReserves<Runtime>>::insert(&RESERVE_ID, &alice, reserved);
+            Balances::unreserve(&alice, 1);
+            assert_ok!(Liability::try_state());
+
+            // Escrow of open liability 2 is required from its promisee
+            ECONOMICS_VALUE.with(|value| *value.borrow_mut() = Some(5));
+            assert_eq!(
+                Liability::try_state(),
+                Err("tracked reserve doesn't equal module reservations")
+            );
+            assert_ok!(NamedReserve::<Runtime>::reserve_named(
+                &RESERVE_ID,
+                &alice,
+                5
+            ));
+            assert_ok!(Liability::try_state());
+            NamedReserve::<Runtime>::unreserve_named(&RESERVE_ID, &alice, 5);
+            ECONOMICS_VALUE.with(|value| *value.borrow_mut() = None);
+
+            let record = Liability::liability_record(1).unwrap();
+            <Liabilities<Runtime>>::insert(0, record);
+            assert_eq!(
+                Liability::try_state(),
+                Err("tombstoned index has live record")
+            );
+            <Liabilities<Runtime>>::remove(0);
+            <Tombstones<Runtime>>::put(vec![(0, 1), (1, 1)]);
+            assert_eq!(
+                Liability::try_state(),
+                Err("tombstone range isn't of allocated indices")
+            );
+            <Tombstones<Runtime>>::put(vec![(0, 1)]);
+            assert_ok!(Liability::try_state());
+            <PayoutOf<Runtime>>::insert(0, &charlie);
+            assert_eq!(
+                Liability::try_state(),
+                Err("tombstoned index has per-index entry")
+            );
+            <PayoutOf<Runtime>>::remove(0);
+            <AccountLiabilities<Runtime>>::insert(&alice, 0, ());
+            assert_eq!(Liability::try_state(), Err("tombstoned index is indexed"));
+            <AccountLiabilities<Runtime>>::remove(&alice, 0);
+            let latest = Liability::latest_index();
+            <Liabilities<Runtime>>::insert(latest, Liability::liability_record(1).unwrap());
+            assert_eq!(
+                Liability::try_state(),
+                Err("record is stored at unallocated index")
+            );
+            <Liabilities<Runtime>>::remove(latest);
+            assert_ok!(Liability::try_state());
+
+            let counters = Liability::counters();
+            <Counters<Runtime>>::mutate(|counters| counters.created += 1);
+            assert_eq!(
+                Liability::try_state(),
+                Err("created counter isn't count of indices")
+            );
+            <Counters<Runtime>>::mutate(|counters| {
+                counters.created -= 1;
+                counters.finalized = 0;
+                counters.cancelled = 0;
+            });
+            assert_eq!(
+                Liability::try_state(),
+                Err("closed liabilities of sample exceed closed counters")
+            );
+            <Counters<Runtime>>::put(counters);
+            assert_ok!(Liability::try_state());
+        })
+    }
+
+    #[test]
+    fn test_import_legacy() {
+        use robonomics_liability_core::eth::legacy_state;
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let factory = H160::repeat_byte(0xf0);
+            let completed = LegacyRecord {
+                address: H160::repeat_byte(1),
+                model: b"QmModel".to_vec(),
+                objective: b"QmObjective".to_vec(),
+                result: b"QmResult".to_vec(),
+                cost: 1_000,
+                promisor: H160::repeat_byte(0xa0),
+                promisee: H160::repeat_byte(0xb0),
+                success: true,
+            };
+            let failed = LegacyRecord {
+                address: H160::repeat_byte(2),
+                success: false,
+                ..completed.clone()
+            };
+            let (state_root, proof) = legacy_state(&factory, &[completed.clone(), failed.clone()]);
+            let import = |record: &LegacyRecord| {
+                Liability::import_legacy(Origin::root(), record.clone(), proof.clone())
+            };
+
+            // State root is anchored by governance, liabilities are imported by bridge
+            assert_err!(import(&completed), Error::<Runtime>::LegacyStateUnset);
+            assert_err!(
+                Liability::anchor_legacy_state(
+                    Origin::signed(account("//Alice")),
+                    factory,
+                    state_root
+                ),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_ok!(Liability::anchor_legacy_state(
+                Origin::root(),
+                factory,
+                state_root
+            ));
+            assert_eq!(Liability::legacy_anchor(), Some((factory, state_root)));
+            assert_err!(
+                Liability::import_legacy(
+                    Origin::signed(account("//Alice")),
+                    completed.clone(),
+                    proof.clone()
+                ),
+                sp_runtime::DispatchError::BadOrigin
+            );
+
+            // Record should match proven contract storage
+            let forged = LegacyRecord {
+                result: b"QmForged".to_vec(),
+                ..completed.clone()
+            };
+            assert_err!(import(&forged), Error::<Runtime>::BadLegacyProof);
+            let oversized = vec![vec![0x80]; legacy::MAX_LEGACY_PROOF_NODES as usize + 1];
+            assert_err!(
+                Liability::import_legacy(Origin::root(), completed.clone(), oversized),
+                Error::<Runtime>::LegacyProofTooLarge
+            );
+            assert!(Liability::liability_record(0).is_none());
+
+            assert_ok!(import(&completed));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::LegacyImported(Compact(0), completed.address))
+            );
+            assert_ok!(import(&failed));
+            assert_err!(import(&completed), Error::<Runtime>::AlreadyImported);
+
+            // Imported liability is finalized by outcome of contract with mapped accounts
+            let promisee: AccountId = legacy::evm_account(&completed.promisee);
+            let promisor: AccountId = legacy::evm_account(&completed.promisor);
+            let info = Liability::liability_info(0).unwrap();
+            assert_eq!(info.state, LiabilityState::Imported);
+            assert_eq!(info.technics, b"QmObjective".to_vec());
+            assert_eq!(info.promisee, promisee);
+            assert_eq!(info.promisor, promisor);
+            assert_eq!(info.finalized_at, Some(1));
+            assert_eq!(
+                Liability::liability_record(1).unwrap().state,
+                RecordState::Imported(false)
+            );
+            assert_eq!(
+                Liability::history_of(0).last(),
+                Some(&(1, Transition::Imported(completed.address)))
+            );
+            assert_eq!(Liability::imported_from(0), Some(completed.address));
+            assert_eq!(Liability::legacy_import(failed.address), Some(1));
+            let list = Liability::account_liability_list(&promisor, 0, 10);
+            assert_eq!(list.items.len(), 2);
+            assert_eq!(list.items[0].1.state, LiabilityState::Imported);
+            let counters = Liability::counters();
+            assert_eq!(counters.finalized, 1);
+            assert_eq!(counters.cancelled, 1);
+
+            // Imported liability is read-only
+            let report = vec![1];
+            let report_proof = get_report_proof("//Bob", &0, &report);
+            assert!(Liability::finalize(Origin::none(), 0, report, report_proof).is_err());
+            assert_err_ignore_postinfo!(
+                Liability::force_finalize(Origin::root(), 0, false),
+                Error::<Runtime>::AlreadyFinalized
+            );
+            assert_eq!(
+                Liability::liability_record(0).unwrap().state,
+                RecordState::Imported(true)
+            );
+            assert_ok!(Liability::try_state());
+
+            // Pruned liability isn't imported again
+            assert_ok!(Liability::prune_liability(Origin::root(), 0));
+            assert_eq!(Liability::imported_from(0), None);
+            assert_err!(import(&completed), Error::<Runtime>::AlreadyImported);
+        });
+    }
+
+    #[test]
+    fn test_digest_bloom() {
+        use frame_support::traits::OnFinalize;
+
+        new_test_ext().execute_with(|| {
+            let blooms = || -> Vec<AccountBloom> {
+                System::digest()
+                    .logs
+                    .iter()
+                    .filter_map(|item| match item {
+                        sp_runtime::generic::DigestItem::Other(payload) => {
+                            AccountBloom::from_digest(payload)
+                        }
+                        _ => None,
+                    })
+                    .collect()
+            };
+
+            // Parties of liabilities changed in block are deposited into digest
+            create_liability(1);
+            assert_eq!(Liability::touched_in_block(), vec![0]);
+            Liability::on_finalize(1);
+            assert_eq!(Liability::touched_in_block(), Vec::<u64>::new());
+            let bloom = blooms().pop().unwrap();
+            assert_eq!(bloom.bits.len(), 128);
+            assert_eq!(bloom.hashes, 11);
+            assert!(bloom.contains(&account("//Alice")));
+            assert!(bloom.contains(&account("//Bob")));
+            assert!(!bloom.contains(&account("//Charlie")));
+
+            // Block without liability transitions has no filter
+            System::set_block_number(2);
+            Liability::on_finalize(2);
+            assert_eq!(blooms().len(), 1);
+        })
+    }
+
+    #[test]
+    fn test_migrate_block_state() {
+        new_test_ext().execute_with(|| {
+            // Release 12 removed limits of the block at the start of the next one
+            System::set_block_number(5);
+            let (alice, bob) = (account("//Alice"), account("//Bob"));
+            <UnsignedInBlock>::put(3);
+            <PairCreatesInBlock<Runtime>>::insert(4, (alice, bob), 1);
+            <StorageVersion>::put(Releases::V12_0_0);
+
+            migration::migrate::<Runtime>();
+            assert_eq!(Liability::storage_version(), STORAGE_VERSION);
+            assert_ok!(Liability::ensure_block_state_cleared());
+        })
+    }
+
+    #[test]
+    fn test_unsigned_priority() {
+        use frame_support::unsigned::ValidateUnsigned;
+
+        new_test_ext().execute_with(|| {
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (promisee, promisee_proof) = get_params_proof("//Alice", &technics, &());
+            let (promisor, promisor_proof) = get_params_proof("//Bob", &technics, &());
+            assert_ok!(Liability::create(
+                Origin::none(),
+                technics.clone(),
+                (),
+                promisee.clone(),
+                promisor.clone(),
+                promisee_proof.clone(),
+                promisor_proof.clone(),
+            ));
+            System::set_block_number(1);
+
+            let (charlie, charlie_proof) = get_params_proof("//Charlie", &technics, &());
+            let report = vec![0xde, 0xad];
+            let pool = vec![
+                Call::create(
+                    technics.clone(),
+                    (),
+                    promisee.clone(),
+                    promisor.clone(),
+                    promisee_proof.clone(),
+                    promisor_proof,
+                ),
+                Call::finalize(0, report.clone(), get_report_proof("//Bob", &0, &report)),
+                Call::create(
+                    technics.clone(),
+                    (),
+                    promisee,
+                    charlie,
+                    promisee_proof,
+                    charlie_proof,
+                ),
+            ];
+            let mut ordered: Vec<_> = pool
+                .iter()
+                .map(|call| {
+                    let priority = Liability::validate_unsigned(TransactionSource::External, call)
+                        .unwrap()
+                        .priority;
+                    (priority, call)
+                })
+                .collect();
+            ordered.sort_by(|a, b| b.0.cmp(&a.0));
+
+            // Base priority increased by liability value, reports go first
+            assert_eq!(ordered[0].0, FinalizePriority::get());
+            assert!(matches!(ordered[0].1, Call::finalize(..)));
+            assert_eq!(ordered[1].0, CreatePriority::get() + 10);
+            assert_eq!(ordered[2].0, CreatePriority::get() + 10);
+        })
+    }
+
+    #[test]
+    fn test_demand() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (alice, proof) = get_params_proof("//Alice", &technics, &());
+            let bob = account("//Bob");
+            let demand = |deadline, proof| {
+                Liability::demand(
+                    Origin::signed(alice.clone()),
+                    technics.clone(),
+                    (),
+                    1,
+                    deadline,
+                    proof,
+                )
+            };
+
+            assert_err!(
+                Liability::demand(Origin::none(), technics.clone(), (), 1, 5, proof.clone()),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_err!(demand(1, proof.clone()), Error::<Runtime>::DeadlineInPast);
+            let bad_proof = get_params_proof("//Bob", &technics, &()).1;
+            assert_err!(demand(5, bad_proof), Error::<Runtime>::BadOrderProof);
+            assert_eq!(Balances::reserved_balance(&alice), 0);
+
+            assert_ok!(demand(5, proof.clone()));
+            assert_ok!(demand(5, proof.clone()));
+            assert_eq!(Liability::order_count(), 2);
+            assert_eq!(Balances::reserved_balance(&alice), 54);
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::OrderPosted(
+                    1,
+                    Side::Demand,
+                    0,
+                    technics_hash(&technics),
+                    (),
+                    1,
+                    10,
+                    alice.clone(),
+                    5
+                ))
+            );
+            let order = Liability::ask(0).unwrap();
+            assert_eq!(order.sender, alice);
+            assert_eq!(order.deadline, 5);
+            assert_eq!((order.quantity, order.deposit, order.escrow), (1, 10, 2));
+
+            // Only creator removes order before deadline
+            assert_err!(
+                Liability::cancel_demand(Origin::signed(bob.clone()), 0),
+                Error::<Runtime>::NotOrderOwner
+            );
+            assert_ok!(Liability::cancel_demand(Origin::signed(alice.clone()), 0));
+            assert_eq!(Liability::ask(0), None);
+            assert_eq!(Balances::reserved_balance(&alice), 42);
+            assert_err!(
+                Liability::cancel_demand(Origin::signed(alice.clone()), 0),
+                Error::<Runtime>::UnknownOrder
+            );
+
+            // Anyone drops expired order
+            System::set_block_number(6);
+            assert_ok!(Liability::cancel_demand(Origin::signed(bob), 1));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::OrderCancelled(1, Side::Demand, 0, 1))
+            );
+            assert_eq!(Balances::reserved_balance(&alice), 0);
+            assert_eq!(Balances::free_balance(&alice), 1_000);
+        })
+    }
+
+    #[test]
+    fn test_offer() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (bob, proof) = get_params_proof("//Bob", &technics, &());
+            let charlie = account("//Charlie");
+
+            let bad_proof = get_params_proof("//Alice", &technics, &()).1;
+            assert_err!(
+                Liability::offer(
+                    Origin::signed(bob.clone()),
+                    technics.clone(),
+                    (),
+                    1,
+                    5,
+                    bad_proof
+                ),
+                Error::<Runtime>::BadOrderProof
+            );
+            assert_ok!(Liability::offer(
+                Origin::signed(bob.clone()),
+                technics.clone(),
+                (),
+                1,
+                5,
+                proof.clone()
+            ));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::OrderPosted(
+                    0,
+                    Side::Offer,
+                    0,
+                    technics_hash(&technics),
+                    (),
+                    1,
+                    10,
+                    bob.clone(),
+                    5
+                ))
+            );
+            assert_eq!(Balances::reserved_balance(&bob), 10);
+            // Demands and offers share order ids
+            assert_eq!(Liability::ask(0), None);
+
+            // Offer proof is reusable as promisor proof of `create`
+            let order = Liability::bid(0).unwrap();
+            let (alice, alice_proof) = get_params_proof("//Alice", &technics, &());
+            assert_ok!(Liability::create(
+                Origin::none(),
+                order.technics.clone(),
+                order.economics,
+                alice,
+                order.sender.clone(),
+                alice_proof,
+                order.proof.clone(),
+            ));
+            assert_eq!(
+                Liability::liability(0).map(|l| l.promisor().clone()),
+                Some(bob.clone())
+            );
+
+            assert_err!(
+                Liability::cancel_offer(Origin::signed(charlie.clone()), 0),
+                Error::<Runtime>::NotOrderOwner
+            );
+            System::set_block_number(6);
+            assert_ok!(Liability::cancel_offer(Origin::signed(charlie), 0));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::OrderCancelled(0, Side::Offer, 0, 1))
+            );
+            assert_eq!(Liability::bid(0), None);
+            assert_eq!(Balances::reserved_balance(&bob), 0);
+        })
+    }
+
+    #[test]
+    fn test_match_orders() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let other = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz5"
+                .from_base58()
+                .unwrap();
+            let (alice, alice_proof) = get_params_proof("//Alice", &technics, &());
+            let (bob, bob_proof) = get_params_proof("//Bob", &technics, &());
+            let (_, other_proof) = get_params_proof("//Bob", &other, &());
+            let charlie = account("//Charlie");
+
+            // Demand escrow covers matching reward
+            assert_ok!(Liability::demand(
+                Origin::signed(alice.clone()),
+                technics.clone(),
+                (),
+                1,
+                5,
+                alice_proof.clone()
+            ));
+            assert_eq!(Balances::reserved_balance(&alice), 12);
+            assert_ok!(Liability::offer(
+                Origin::signed(bob.clone()),
+                other.clone(),
+                (),
+                1,
+                5,
+                other_proof
+            ));
+            assert_ok!(Liability::offer(
+                Origin::signed(bob.clone()),
+                technics.clone(),
+                (),
+                1,
+                5,
+                bob_proof.clone()
+            ));
+
+            assert_err!(
+                Liability::match_orders(Origin::signed(charlie.clone()), 0, 1),
+                Error::<Runtime>::OrderMismatch
+            );
+            assert_err!(
+                Liability::match_orders(Origin::signed(charlie.clone()), 0, 3),
+                Error::<Runtime>::UnknownOrder
+            );
+            // Demand id isn't an offer id
+            assert_err!(
+                Liability::match_orders(Origin::signed(charlie.clone()), 2, 0),
+                Error::<Runtime>::UnknownOrder
+            );
+
+            commit_and_reveal(&charlie, &[(0, 2)]);
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::Matched(
+                    0,
+                    2,
+                    Compact(0),
+                    0,
+                    10,
+                    1,
+                    charlie.clone()
+                ))
+            );
+            assert_eq!(Liability::matched_order(0), Some(0));
+            assert_eq!(Liability::matched_order(2), Some(0));
+            assert_eq!(Liability::ask(0), None);
+            assert_eq!(Liability::bid(2), None);
+            assert_eq!(Balances::reserved_balance(&alice), 0);
+            assert_eq!(Balances::free_balance(&alice), 998);
+            assert_eq!(Balances::free_balance(&charlie), 1_002);
+            assert_eq!(Balances::reserved_balance(&bob), 10);
+            assert_eq!(
+                Liability::liability(0).map(|l| (l.promisee().clone(), l.promisor().clone())),
+                Some((alice.clone(), bob.clone()))
+            );
+
+            assert_err!(
+                Liability::match_orders(Origin::signed(charlie.clone()), 0, 2),
+                Error::<Runtime>::OrderConsumed
+            );
+
+            // Matched liability is finalized by promisor as usual
+            let report = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let report_proof = get_report_proof("//Bob", &0, &report);
+            assert_ok!(Liability::finalize(Origin::none(), 0, report, report_proof));
+            assert!(Liability::is_finalized(0));
+
+            // Expired orders couldn't be matched
+            System::set_block_number(2);
+            assert_ok!(Liability::demand(
+                Origin::signed(alice.clone()),
+                other.clone(),
+                (),
+                1,
+                3,
+                get_params_proof("//Alice", &other, &()).1
+            ));
+            System::set_block_number(4);
+            assert_err!(
+                Liability::match_orders(Origin::signed(charlie), 3, 1),
+                Error::<Runtime>::OrderExpired
+            );
+        })
+    }
+
+    #[test]
+    fn test_demand_restricted() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (alice, alice_proof) = get_params_proof("//Alice", &technics, &());
+            let (bob, bob_proof) = get_params_proof("//Bob", &technics, &());
+            let (charlie, charlie_proof) = get_params_proof("//Charlie", &technics, &());
+            let allowed = PromisorRequirement::AllowList(vec![charlie.clone()]);
+            let (_, restricted_proof) =
+                crate::testing::make_restricted_party_proof("//Alice", &technics, &(), &allowed);
+            let demand_restricted = |requirement: PromisorRequirementOf<Runtime>, proof| {
+                Liability::demand_restricted(
+                    Origin::signed(alice.clone()),
+                    technics.clone(),
+                    (),
+                    1,
+                    5,
+                    requirement,
+                    proof,
+                )
+            };
+
+            // Demand proof covers requirement, it's bounded too
+            assert_err!(
+                demand_restricted(allowed.clone(), alice_proof),
+                Error::<Runtime>::BadOrderProof
+            );
+            let many = PromisorRequirement::AllowList(vec![charlie.clone(); 4]);
+            let (_, many_proof) =
+                crate::testing::make_restricted_party_proof("//Alice", &technics, &(), &many);
+            assert_err!(
+                demand_restricted(many, many_proof),
+                Error::<Runtime>::TooManyAllowedPromisors
+            );
+            assert_ok!(demand_restricted(allowed.clone(), restricted_proof.clone()));
+            assert_eq!(Liability::demand_requirement(0), Some(allowed.clone()));
+
+            // Offer of promisor not allowed by demand isn't matched
+            assert_ok!(Liability::offer(
+                Origin::signed(bob.clone()),
+                technics.clone(),
+                (),
+                1,
+                5,
+                bob_proof
+            ));
+            assert_err!(
+                Liability::match_orders(Origin::signed(bob.clone()), 0, 1),
+                Error::<Runtime>::PromisorNotAllowed
+            );
+            assert_ok!(Liability::offer(
+                Origin::signed(charlie.clone()),
+                technics.clone(),
+                (),
+                1,
+                5,
+                charlie_proof
+            ));
+            commit_and_reveal(&bob, &[(0, 2)]);
+            assert_eq!(Liability::matched_order(0), Some(0));
+            assert_eq!(Liability::demand_requirement(0), None);
+            assert_eq!(
+                Liability::liability(0).map(|l| (l.promisee().clone(), l.promisor().clone())),
+                Some((alice.clone(), charlie))
+            );
+
+            // Requirement is removed with cancelled demand
+            assert_ok!(demand_restricted(allowed.clone(), restricted_proof));
+            assert_eq!(Liability::demand_requirement(3), Some(allowed));
+            assert_ok!(Liability::cancel_demand(Origin::signed(alice), 3));
+            assert_eq!(Liability::demand_requirement(3), None);
+            assert_eq!(Liability::bid(1).map(|o| o.sender), Some(bob));
+        })
+    }
+
+    #[test]
+    fn test_assignment_pool() {
+        use super::market::select_weighted;
+
+        // Selection is proportional to weight
+        assert_eq!(select_weighted(&[], 7), None);
+        assert_eq!(select_weighted(&[0, 0], 7), None);
+        assert_eq!(select_weighted(&[10, 30], 9), Some(0));
+        assert_eq!(select_weighted(&[10, 30], 10), Some(1));
+        assert_eq!(select_weighted(&[10, 30], 39), Some(1));
+        assert_eq!(select_weighted(&[10, 30], 40), Some(0));
+        assert_eq!(select_weighted(&[0, 30], 5), Some(1));
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let (technics, other) = (b"delivery".to_vec(), b"other".to_vec());
+            let (alice, alice_proof) = get_params_proof("//Alice", &technics, &());
+            let (bob, bob_proof) = get_params_proof("//Bob", &technics, &());
+            let (charlie, charlie_proof) = get_params_proof("//Charlie", &other, &());
+            let (dave, dave_proof) = get_params_proof("//Dave", &technics, &());
+            Balances::make_free_balance_be(&dave, 1000);
+            assert_ok!(Liability::demand(
+                Origin::signed(alice.clone()),
+                technics.clone(),
+                (),
+                1,
+                10,
+                alice_proof
+            ));
+            assert_ok!(Liability::offer(
+                Origin::signed(bob.clone()),
+                technics.clone(),
+                (),
+                1,
+                10,
+                bob_proof
+            ));
+            assert_ok!(Liability::offer(
+                Origin::signed(charlie.clone()),
+                other,
+                (),
+                1,
+                10,
+                charlie_proof
+            ));
+            assert_ok!(Liability::offer(
+                Origin::signed(dave.clone()),
+                technics,
+                (),
+                1,
+                10,
+                dave_proof
+            ));
+
+            // Empty pool couldn't assign demand
+            assert_err!(
+                Liability::request_assignment(Origin::signed(alice.clone()), 0),
+                Error::<Runtime>::EmptyPool
+            );
+
+            // Pool is joined with standing offer of sender
+            assert_err!(
+                Liability::join_pool(Origin::signed(bob.clone()), 0, 1, 20),
+                Error::<Runtime>::StakeTooLow
+            );
+            assert_err!(
+                Liability::join_pool(Origin::signed(bob.clone()), 0, 2, 40),
+                Error::<Runtime>::NotOrderOwner
+            );
+            let reserved = Balances::reserved_balance(&bob);
+            assert_ok!(Liability::join_pool(Origin::signed(bob.clone()), 0, 1, 40));
+            assert_eq!(Balances::reserved_balance(&bob), reserved + 40);
+            assert_err!(
+                Liability::join_pool(Origin::signed(bob.clone()), 0, 1, 40),
+                Error::<Runtime>::AlreadyPooled
+            );
+            assert_ok!(Liability::join_pool(
+                Origin::signed(charlie.clone()),
+                0,
+                2,
+                100
+            ));
+
+            System::set_block_number(2);
+            assert_err!(
+                Liability::request_assignment(Origin::signed(bob.clone()), 0),
+                Error::<Runtime>::NotOrderOwner
+            );
+            assert_ok!(Liability::request_assignment(
+                Origin::signed(alice.clone()),
+                0
+            ));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::AssignmentRequested(0, 4))
+            );
+            assert_err!(
+                Liability::request_assignment(Origin::signed(alice.clone()), 0),
+                Error::<Runtime>::AssignmentPending
+            );
+
+            // Promisor joined since request isn't selected
+            assert_ok!(Liability::join_pool(
+                Origin::signed(dave.clone()),
+                0,
+                3,
+                500
+            ));
+
+            // Charlie offer doesn't match demand, so Bob is the only qualified promisor
+            System::set_block_number(4);
+            Liability::on_initialize(4);
+            assert_eq!(Liability::pending_assignment(0), None);
+            assert_eq!(Liability::assignment_sweep_cursor(), (5, 0));
+            assert!(System::events().iter().any(|record| record.event
+                == MetaEvent::liability(RawEvent::DemandAssigned(0, bob.clone()))));
+            assert_eq!(Liability::liability(0).unwrap().promisor(), &bob);
+            assert!(!<Asks<Runtime>>::contains_key(0));
+
+            // Member with consumed offer doesn't qualify, its stake is returned on leave
+            assert_err!(
+                Liability::leave_pool(Origin::signed(alice.clone()), 0),
+                Error::<Runtime>::NotPooled
+            );
+            assert_ok!(Liability::leave_pool(Origin::signed(bob.clone()), 0));
+            assert_eq!(Liability::pool(0).len(), 2);
+            assert_eq!(Liability::named_reserve(RESERVE_ID, &bob), 0);
+        })
+    }
+
+    #[test]
+    fn test_assignment_failed() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let (technics, other) = (b"delivery".to_vec(), b"other".to_vec());
+            let (alice, alice_proof) = get_params_proof("//Alice", &technics, &());
+            let (bob, bob_proof) = get_params_proof("//Bob", &other, &());
+            assert_ok!(Liability::demand(
+                Origin::signed(alice.clone()),
+                technics,
+                (),
+                1,
+                10,
+                alice_proof
+            ));
+            assert_ok!(Liability::offer(
+                Origin::signed(bob.clone()),
+                other,
+                (),
+                1,
+                10,
+                bob_proof
+            ));
+            assert_ok!(Liability::join_pool(Origin::signed(bob), 0, 1, 40));
+
+            System::set_block_number(2);
+            assert_ok!(Liability::request_assignment(Origin::signed(alice), 0));
+            System::set_block_number(4);
+            Liability::on_initialize(4);
+
+            // Demand is left for regular matching
+            assert!(System::events()
+                .iter()
+                .any(|record| record.event == MetaEvent::liability(RawEvent::AssignmentFailed(0))));
+            assert!(<Asks<Runtime>>::contains_key(0));
+            assert_eq!(Liability::pending_assignment(0), None);
+        })
+    }
+
+    #[test]
+    fn test_lighthouse_schedule() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let alice = account("//Alice");
+            let bob = account("//Bob");
+            let charlie = account("//Charlie");
+            let register = |who: &AccountId, stake| {
+                Liability::register_lighthouse(Origin::signed(who.clone()), 0, stake)
+            };
+
+            assert_err!(register(&alice, 40), Error::<Runtime>::StakeTooLow);
+            assert_ok!(register(&alice, 50));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::LighthouseRegistered(0, alice.clone(), 50))
+            );
+            assert_err!(register(&alice, 50), Error::<Runtime>::AlreadyRegistered);
+            assert_ok!(register(&bob, 60));
+            assert_err!(register(&charlie, 50), Error::<Runtime>::TooManyLighthouses);
+            assert_eq!(Balances::reserved_balance(&alice), 50);
+            assert_eq!(Liability::lighthouses(0), vec![alice.clone(), bob.clone()]);
+
+            // Lighthouses take turns every slot
+            assert_eq!(Liability::scheduled_lighthouse(0, 1), Some(alice.clone()));
+            assert_eq!(Liability::scheduled_lighthouse(0, 10), Some(bob.clone()));
+            assert_eq!(Liability::scheduled_lighthouse(0, 25), Some(alice.clone()));
+            assert_eq!(Liability::scheduled_lighthouse(1, 1), None);
+
+            let post = |technics: Vec<u8>| {
+                let (_, demand_proof) = get_params_proof("//Charlie", &technics, &());
+                let (_, offer_proof) = get_params_proof("//Bob", &technics, &());
+                assert_ok!(Liability::demand(
+                    Origin::signed(charlie.clone()),
+                    technics.clone(),
+                    (),
+                    1,
+                    5,
+                    demand_proof
+                ));
+                assert_ok!(Liability::offer(
+                    Origin::signed(bob.clone()),
+                    technics,
+                    (),
+                    1,
+                    5,
+                    offer_proof
+                ));
+            };
+
+            // Unscheduled lighthouse matches without reward
+            post(vec![1]);
+            assert_ok!(Liability::match_orders(Origin::signed(bob.clone()), 0, 1));
+            assert_eq!(Balances::free_balance(&charlie), 1_000);
+            assert_eq!(Balances::free_balance(&bob), 940);
+
+            post(vec![2]);
+            assert_ok!(Liability::match_orders(Origin::signed(alice.clone()), 2, 3));
+            assert!(System::events().iter().any(|record| record.event
+                == MetaEvent::liability(RawEvent::LighthouseRewarded(2, alice.clone(), 2))));
+            assert_eq!(Balances::free_balance(&charlie), 998);
+            assert_eq!(Balances::free_balance(&alice), 952);
+
+            assert_ok!(Liability::unregister_lighthouse(
+                Origin::signed(bob.clone()),
+                0
+            ));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::LighthouseUnregistered(0, bob.clone()))
+            );
+            assert_eq!(Balances::reserved_balance(&bob), 0);
+            assert_err!(
+                Liability::unregister_lighthouse(Origin::signed(bob), 0),
+                Error::<Runtime>::NotLighthouse
+            );
+            assert_eq!(Liability::scheduled_lighthouse(0, 10), Some(alice));
+        })
+    }
+
+    #[test]
+    fn test_order_book() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (alice, alice_proof) = get_params_proof("//Alice", &technics, &());
+            let (bob, bob_proof) = get_params_proof("//Bob", &technics, &());
+            let demand = |price| {
+                SETTLED_VALUE.with(|value| *value.borrow_mut() = price);
+                Liability::demand(
+                    Origin::signed(alice.clone()),
+                    technics.clone(),
+                    (),
+                    1,
+                    5,
+                    alice_proof.clone(),
+                )
+            };
+            let offer = |price| {
+                SETTLED_VALUE.with(|value| *value.borrow_mut() = price);
+                Liability::offer(
+                    Origin::signed(bob.clone()),
+                    technics.clone(),
+                    (),
+                    1,
+                    5,
+                    bob_proof.clone(),
+                )
+            };
+            let entry = |order_id, price| OrderBookEntry { order_id, price };
+            let level = |price, orders| PriceLevel { price, orders };
+
+            // The highest demand is the best one
+            assert_ok!(demand(10));
+            assert_ok!(demand(20));
+            assert_eq!(Liability::asks_by_model(0), vec![(20, 1), (10, 0)]);
+            assert_eq!(Liability::best_ask(0), Some(entry(1, 20)));
+
+            // Full book evicts the worst order for better one
+            assert_err!(demand(5), Error::<Runtime>::OrderBookFull);
+            assert_err!(demand(10), Error::<Runtime>::OrderBookFull);
+            assert_ok!(demand(15));
+            assert_eq!(Liability::asks_by_model(0), vec![(20, 1), (15, 2)]);
+            assert_eq!(Liability::ask(0), None);
+            assert!(System::events().iter().any(|record| record.event
+                == MetaEvent::liability(RawEvent::OrderCancelled(0, Side::Demand, 0, 1))));
+            assert_eq!(Balances::reserved_balance(&alice), 134);
+
+            // The lowest offer is the best one, equal prices keep posting order
+            assert_ok!(offer(30));
+            assert_ok!(offer(15));
+            assert_ok!(offer(15));
+            assert_eq!(Liability::bids_by_model(0), vec![(15, 4), (15, 5)]);
+            assert_eq!(Liability::bid(3), None);
+            assert_eq!(Liability::best_bid(0), Some(entry(4, 15)));
+            assert_eq!(
+                Liability::depth(0, 1),
+                OrderBookDepth {
+                    asks: vec![level(20, 1)],
+                    bids: vec![level(15, 2)],
+                }
+            );
+            assert_eq!(
+                Liability::depth(0, 10).asks,
+                vec![level(20, 1), level(15, 1)]
+            );
+            assert_eq!(Liability::best_ask(1), None);
+
+            assert_ok!(Liability::cancel_demand(Origin::signed(alice.clone()), 1));
+            assert_eq!(Liability::asks_by_model(0), vec![(15, 2)]);
+
+            assert_ok!(Liability::match_orders(Origin::signed(alice), 2, 4));
+            assert_eq!(Liability::asks_by_model(0), vec![]);
+            assert_eq!(Liability::bids_by_model(0), vec![(15, 5)]);
+
+            System::set_block_number(6);
+            assert_ok!(Liability::cancel_offer(Origin::signed(bob), 5));
+            assert_eq!(Liability::best_bid(0), None);
+        })
+    }
+
+    #[test]
+    fn test_partial_fill() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            MIN_FILL_QUANTITY.with(|min| *min.borrow_mut() = 10);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (alice, alice_proof) = get_params_proof("//Alice", &technics, &());
+            let (bob, bob_proof) = get_params_proof("//Bob", &technics, &());
+            let charlie = account("//Charlie");
+            let demand = |quantity| {
+                Liability::demand(
+                    Origin::signed(alice.clone()),
+                    technics.clone(),
+                    (),
+                    quantity,
+                    5,
+                    alice_proof.clone(),
+                )
+            };
+            let offer = |quantity| {
+                Liability::offer(
+                    Origin::signed(bob.clone()),
+                    technics.clone(),
+                    (),
+                    quantity,
+                    5,
+                    bob_proof.clone(),
+                )
+            };
+
+            assert_err!(demand(0), Error::<Runtime>::InvalidQuantity);
+            assert_err!(demand(5), Error::<Runtime>::InvalidQuantity);
+            assert_ok!(demand(100));
+            assert_eq!(Liability::ask(0).map(|order| order.escrow), Some(2));
+            assert_ok!(offer(30));
+            assert_ok!(offer(30));
+
+            // Demand keeps residual open, filled offer is consumed
+            commit_and_reveal(&charlie, &[(0, 1)]);
+            assert_eq!(Liability::ask(0).map(|order| order.quantity), Some(70));
+            assert_eq!(Liability::matched_order(0), None);
+            assert_eq!(Liability::matched_order(1), Some(0));
+            assert_eq!(Liability::bid(1), None);
+            // The whole reward fits the first fill, nothing is reserved for the rest
+            assert_eq!(Liability::ask(0).map(|order| order.escrow), Some(0));
+            assert_eq!(Balances::free_balance(&charlie), 1_002);
+            assert_eq!(Balances::reserved_balance(&alice), 10);
+            assert_ok!(offer(35));
+
+            System::set_block_number(2);
+            assert_ok!(Liability::match_orders(
+                Origin::signed(charlie.clone()),
+                0,
+                2
+            ));
+            assert_eq!(Liability::ask(0).map(|order| order.quantity), Some(40));
+
+            // Residual below minimal fill is closed
+            System::set_block_number(3);
+            assert_ok!(Liability::match_orders(
+                Origin::signed(charlie.clone()),
+                0,
+                3
+            ));
+            assert!(System::events().iter().any(|record| record.event
+                == MetaEvent::liability(RawEvent::OrderCancelled(0, Side::Demand, 0, 5))));
+            assert_eq!(Liability::ask(0), None);
+            assert_eq!(Liability::asks_by_model(0), vec![]);
+            assert_eq!(Liability::matched_order(0), Some(2));
+            assert_err!(
+                Liability::match_orders(Origin::signed(charlie), 0, 3),
+                Error::<Runtime>::OrderConsumed
+            );
+
+            assert_eq!(Liability::latest_index(), 3);
+            assert_eq!(Balances::reserved_balance(&alice), 0);
+            assert_eq!(Balances::free_balance(&alice), 998);
+            assert_eq!(Balances::reserved_balance(&bob), 0);
+        })
+    }
+
+    #[test]
+    fn test_market_fee() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (alice, alice_proof) = get_params_proof("//Alice", &technics, &());
+            let (bob, bob_proof) = get_params_proof("//Bob", &technics, &());
+            let charlie = account("//Charlie");
+            let treasury = account("//Treasury");
+
+            assert_err!(
+                Liability::set_market_fee(
+                    Origin::signed(alice.clone()),
+                    0,
+                    Perbill::from_percent(50)
+                ),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_ok!(Liability::set_market_fee(
+                Origin::root(),
+                0,
+                Perbill::from_percent(50)
+            ));
+            assert_eq!(Liability::market_fee(0), Perbill::from_percent(50));
+
+            // Escrow covers matching reward and half of settled value
+            assert_ok!(Liability::demand(
+                Origin::signed(alice.clone()),
+                technics.clone(),
+                (),
+                1,
+                5,
+                alice_proof
+            ));
+            assert_eq!(Liability::ask(0).map(|order| order.escrow), Some(7));
+            assert_eq!(Balances::reserved_balance(&alice), 17);
+            assert_ok!(Liability::offer(
+                Origin::signed(bob.clone()),
+                technics,
+                (),
+                1,
+                5,
+                bob_proof
+            ));
+
+            commit_and_reveal(&charlie, &[(0, 1)]);
+            assert!(System::events().iter().any(|record| record.event
+                == MetaEvent::liability(RawEvent::MatchFeePaid(0, charlie.clone(), 2, 3))));
+            let totals = Liability::market_totals();
+            assert_eq!((totals.matches, totals.quantity, totals.fees), (1, 1, 5));
+            assert_eq!(totals.value, Liability::recent_matches(0, 1)[0].value);
+            assert_eq!(Balances::free_balance(&charlie), 1_004);
+            assert_eq!(Balances::free_balance(&treasury), 3);
+            assert_eq!(Balances::reserved_balance(&alice), 0);
+            assert_eq!(Balances::free_balance(&alice), 993);
+        })
+    }
+
+    #[test]
+    fn test_match_commit_reveal() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = b"delivery".to_vec();
+            let (alice, alice_proof) = get_params_proof("//Alice", &technics, &());
+            let (bob, bob_proof) = get_params_proof("//Bob", &technics, &());
+            let charlie = account("//Charlie");
+            let dave = account("//Dave");
+            for _ in 0..2 {
+                assert_ok!(Liability::demand(
+                    Origin::signed(alice.clone()),
+                    technics.clone(),
+                    (),
+                    1,
+                    10,
+                    alice_proof.clone()
+                ));
+                assert_ok!(Liability::offer(
+                    Origin::signed(bob.clone()),
+                    technics.clone(),
+                    (),
+                    1,
+                    10,
+                    bob_proof.clone()
+                ));
+            }
+            assert_eq!(Balances::free_balance(&alice), 976);
+
+            // Commitment hides the pair and binds it to matcher
+            let salt = H256::repeat_byte(9);
+            let commitment = match_commitment(&0u64, &1u64, &charlie, &salt);
+            assert_ne!(commitment, match_commitment(&0u64, &1u64, &dave, &salt));
+            assert_ok!(Liability::commit_match(
+                Origin::signed(charlie.clone()),
+                commitment
+            ));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::MatchCommitted(charlie.clone(), commitment))
+            );
+            assert_eq!(Liability::match_committed_at(&charlie, commitment), Some(1));
+            assert_eq!(
+                <MatchCommitsExpiringAt<Runtime>>::get(5),
+                vec![(charlie.clone(), commitment)]
+            );
+            assert_err!(
+                Liability::commit_match(Origin::signed(charlie.clone()), commitment),
+                Error::<Runtime>::MatchCommitted
+            );
+            assert_err!(
+                Liability::reveal_match(Origin::signed(charlie.clone()), 0, 1, salt),
+                Error::<Runtime>::MatchCommitTooRecent
+            );
+
+            // Attacker copying commitment and reveal has no commitment of its own
+            assert_ok!(Liability::commit_match(
+                Origin::signed(dave.clone()),
+                commitment
+            ));
+
+            // Commitments waiting for reveal are limited per account
+            assert_ok!(Liability::commit_match(
+                Origin::signed(dave.clone()),
+                H256::repeat_byte(1)
+            ));
+            assert_err!(
+                Liability::commit_match(Origin::signed(dave.clone()), H256::repeat_byte(2)),
+                Error::<Runtime>::TooManyMatchCommits
+            );
+            assert_eq!(Liability::match_commit_count(&dave), 2);
+            System::set_block_number(2);
+            assert_err!(
+                Liability::reveal_match(Origin::signed(dave.clone()), 0, 1, salt),
+                Error::<Runtime>::NoMatchCommitment
+            );
+            assert_ok!(Liability::reveal_match(
+                Origin::signed(charlie.clone()),
+                0,
+                1,
+                salt
+            ));
+            assert!(System::events().iter().any(|record| record.event
+                == MetaEvent::liability(RawEvent::LighthouseRewarded(0, charlie.clone(), 2))));
+            assert_eq!(Balances::free_balance(&charlie), 1_002);
+            assert_eq!(Liability::match_committed_at(&charlie, commitment), None);
+            assert_err!(
+                Liability::reveal_match(Origin::signed(charlie.clone()), 0, 1, salt),
+                Error::<Runtime>::NoMatchCommitment
+            );
+
+            // Copied plain match of known ids isn't rewarded, reward returns to promisee
+            assert_ok!(Liability::match_orders(Origin::signed(dave.clone()), 2, 3));
+            assert!(!System::events().iter().any(|record| record.event
+                == MetaEvent::liability(RawEvent::LighthouseRewarded(2, dave.clone(), 2))));
+            assert_eq!(Balances::free_balance(&dave), 0);
+            assert_eq!(Balances::reserved_balance(&alice), 0);
+            assert_eq!(Balances::free_balance(&alice), 998);
+
+            // Commitment is revealed only in its window, stale one is removed
+            let stale = match_commitment(&4u64, &5u64, &charlie, &salt);
+            assert_ok!(Liability::commit_match(
+                Origin::signed(charlie.clone()),
+                stale
+            ));
+            System::set_block_number(6);
+            assert_err!(
+                Liability::reveal_match(Origin::signed(charlie.clone()), 4, 5, salt),
+                Error::<Runtime>::MatchCommitExpired
+            );
+            Liability::on_initialize(5);
+            assert_eq!(Liability::match_committed_at(&dave, commitment), None);
+            assert_eq!(Liability::match_commit_count(&dave), 0);
+            assert_eq!(Liability::match_committed_at(&charlie, stale), Some(2));
+            assert_eq!(Liability::match_commit_count(&charlie), 1);
+            Liability::on_initialize(6);
+            assert_eq!(Liability::match_committed_at(&charlie, stale), None);
+            assert_eq!(Liability::match_commit_count(&charlie), 0);
+            assert_eq!(<MatchCommitsExpiringAt<Runtime>>::get(6), vec![]);
+            assert_eq!(Liability::match_commit_sweep_cursor(), (7, 0));
+            assert_err!(
+                Liability::reveal_match(Origin::signed(charlie), 4, 5, salt),
+                Error::<Runtime>::NoMatchCommitment
+            );
+        })
+    }
+
+    #[test]
+    fn test_market_config() {
+        new_test_ext().execute_with(|| {
+            let alice = account("//Alice");
+            let config = MarketConfig {
+                fee: Perbill::from_percent(10),
+                order_deposit: 4,
+                challenge_window: 20,
+                force_finalize_age: 2,
+                cancellation_fee: CancellationFee::Fixed(3),
+                report_retention: 40,
+            };
+
+            // Markets without configuration take module constants
+            assert_eq!(
+                Liability::market_config(1),
+                MarketConfig {
+                    fee: Perbill::zero(),
+                    order_deposit: OrderDeposit::get(),
+                    challenge_window: ReportChallengeWindow::get(),
+                    force_finalize_age: MinForceFinalizeAge::get(),
+                    cancellation_fee: CancellationFee::Share(Perbill::zero()),
+                    report_retention: 0,
+                }
+            );
+
+            assert_err!(
+                Liability::create_market(Origin::signed(alice.clone()), 1, config.clone()),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_err!(
+                Liability::update_market(Origin::root(), 1, config.clone()),
+                Error::<Runtime>::UnknownMarket
+            );
+            assert_ok!(Liability::create_market(Origin::root(), 1, config.clone()));
+            assert_eq!(Liability::market_config(1), config);
+            assert_eq!(Liability::market_fee(1), Perbill::from_percent(10));
+            assert_err!(
+                Liability::create_market(Origin::root(), 1, config.clone()),
+                Error::<Runtime>::MarketExists
+            );
+
+            // Fee is set without touching the rest of configuration
+            assert_ok!(Liability::set_market_fee(
+                Origin::root(),
+                1,
+                Perbill::from_percent(20)
+            ));
+            assert_eq!(Liability::market_config(1).order_deposit, 4);
+            assert_eq!(Liability::market_fee(1), Perbill::from_percent(20));
+
+            let config = MarketConfig {
+                challenge_window: 30,
+                ..config
+            };
+            assert_err!(
+                Liability::update_market(Origin::signed(alice), 1, config.clone()),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_ok!(Liability::update_market(Origin::root(), 1, config.clone()));
+            assert_eq!(Liability::market_config(1), config);
+            assert_eq!(
+                Liability::market_config(0),
+                Liability::default_market_config()
+            );
+        })
+    }
+
+    #[test]
+    fn test_markets_side_by_side() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let delivery = b"delivery".to_vec();
+            let sensing = b"sensing".to_vec();
+            TECHNICS_MARKETS.with(|markets| {
+                *markets.borrow_mut() = vec![(delivery.clone(), 1), (sensing.clone(), 2)]
+            });
+            let alice = account("//Alice");
+            let bob = account("//Bob");
+            let charlie = account("//Charlie");
+            let treasury = account("//Treasury");
+
+            let delivery_market = MarketConfig {
+                fee: Perbill::from_percent(50),
+                order_deposit: 10,
+                challenge_window: 5,
+                force_finalize_age: 5,
+                cancellation_fee: Default::default(),
+                report_retention: 0,
+            };
+            let sensing_market = MarketConfig {
+                fee: Perbill::from_percent(30),
+                order_deposit: 4,
+                challenge_window: 20,
+                force_finalize_age: 5,
+                cancellation_fee: Default::default(),
+                report_retention: 0,
+            };
+            assert_ok!(Liability::create_market(
+                Origin::root(),
+                1,
+                delivery_market.clone()
+            ));
+            assert_ok!(Liability::create_market(
+                Origin::root(),
+                2,
+                sensing_market.clone()
+            ));
+
+            // Each demand escrow covers matching reward and fee of its market
+            for technics in [&delivery, &sensing].iter() {
+                let (_, proof) = get_params_proof("//Alice", technics, &());
+                assert_ok!(Liability::demand(
+                    Origin::signed(alice.clone()),
+                    (*technics).clone(),
+                    (),
+                    1,
+                    5,
+                    proof
+                ));
+            }
+            assert_eq!(
+                Liability::ask(0).map(|order| (order.deposit, order.escrow)),
+                Some((10, 7))
+            );
+            assert_eq!(
+                Liability::ask(1).map(|order| (order.deposit, order.escrow)),
+                Some((16, 5))
+            );
+            assert_eq!(Liability::asks_by_model(1), vec![(10, 0)]);
+            assert_eq!(Liability::asks_by_model(2), vec![(10, 1)]);
+            for technics in [&delivery, &sensing].iter() {
+                let (_, proof) = get_params_proof("//Bob", technics, &());
+                assert_ok!(Liability::offer(
+                    Origin::signed(bob.clone()),
+                    (*technics).clone(),
+                    (),
+                    1,
+                    5,
+                    proof
+                ));
+            }
+
+            commit_and_reveal(&charlie, &[(0, 2), (1, 3)]);
+            let events = System::events();
+            assert!(events.iter().any(|record| record.event
+                == MetaEvent::liability(RawEvent::MatchFeePaid(0, charlie.clone(), 2, 3))));
+            assert!(events.iter().any(|record| record.event
+                == MetaEvent::liability(RawEvent::MatchFeePaid(1, charlie.clone(), 1, 2))));
+            assert_eq!(Balances::free_balance(&charlie), 1_007);
+            assert_eq!(Balances::free_balance(&treasury), 5);
+            assert_eq!(Balances::reserved_balance(&alice), 0);
+            assert_eq!(Balances::free_balance(&alice), 988);
+            assert_eq!(
+                <LiabilityMarket<Runtime>>::get(0),
+                Some((1, delivery_market.clone()))
+            );
+            assert_eq!(
+                <LiabilityMarket<Runtime>>::get(1),
+                Some((2, sensing_market))
+            );
+
+            // Liabilities keep challenge window of their market at creation
+            assert_ok!(Liability::update_market(
+                Origin::root(),
+                1,
+                MarketConfig {
+                    challenge_window: 30,
+                    ..delivery_market
+                }
+            ));
+            for (index, report) in [b"delivered".to_vec(), b"sensed".to_vec()]
+                .iter()
+                .enumerate()
+            {
+                let index = index as u64;
+                let proof = get_report_proof("//Bob", &index, report);
+                assert_ok!(Liability::finalize(
+                    Origin::none(),
+                    index,
+                    report.clone(),
+                    proof
+                ));
+            }
+            let expires_at = |report: &[u8]| {
+                Liability::report_preimage(report_hash(&report.to_vec())).map(|p| p.expires_at)
+            };
+            assert_eq!(expires_at(b"delivered"), Some(7));
+            assert_eq!(expires_at(b"sensed"), Some(22));
+        })
+    }
+
+    #[test]
+    fn test_offchain_match() {
+        let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+        let (offchain, _) = testing::TestOffchainExt::new();
+        let keystore = KeyStore::new();
+        keystore
+            .write()
+            .sr25519_generate_new(KEY_TYPE, Some("//Bob"))
+            .unwrap();
+
+        let mut ext = new_test_ext();
+        ext.register_extension(OffchainExt::new(offchain));
+        ext.register_extension(TransactionPoolExt::new(pool));
+        ext.register_extension(KeystoreExt(keystore));
+        ext.execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let other = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz5"
+                .from_base58()
+                .unwrap();
+            let (alice, alice_proof) = get_params_proof("//Alice", &technics, &());
+            let (charlie, charlie_proof) = get_params_proof("//Charlie", &technics, &());
+            let (_, other_proof) = get_params_proof("//Charlie", &other, &());
+            let bob = account("//Bob");
+
+            assert_ok!(Liability::demand(
+                Origin::signed(alice),
+                technics.clone(),
+                (),
+                1,
+                5,
+                alice_proof
+            ));
+            assert_ok!(Liability::offer(
+                Origin::signed(charlie.clone()),
+                other,
+                (),
+                1,
+                5,
+                other_proof
+            ));
+            assert_ok!(Liability::offer(
+                Origin::signed(charlie),
+                technics,
+                (),
+                1,
+                5,
+                charlie_proof
+            ));
+
+            // Disabled without lighthouse mode, local key should be lighthouse
+            Liability::offchain_worker(1);
+            assert!(pool_state.read().transactions.is_empty());
+            sp_io::offchain::local_storage_set(
+                StorageKind::PERSISTENT,
+                OFFCHAIN_LIGHTHOUSE_KEY,
+                &[1],
+            );
+            Liability::offchain_worker(1);
+            assert!(pool_state.read().transactions.is_empty());
+
+            assert_ok!(Liability::register_lighthouse(
+                Origin::signed(bob.clone()),
+                0,
+                50
+            ));
+            assert_eq!(offchain::market_matches::<Runtime>(0, 1), vec![(0, 2)]);
+            Liability::offchain_worker(1);
+            let tx = pool_state.write().transactions.pop().unwrap();
+            assert!(pool_state.read().transactions.is_empty());
+
+            // Submitted match is locked
+            Liability::offchain_worker(2);
+            assert!(pool_state.read().transactions.is_empty());
+
+            let tx = Extrinsic::decode(&mut &*tx).unwrap();
+            assert_eq!(tx.signature, Some((0, ())));
+            match tx.call {
+                crate::Call::match_orders(demand_id, offer_id) => {
+                    assert_eq!((demand_id, offer_id), (0, 2));
+                    assert_ok!(Liability::match_orders(
+                        Origin::signed(bob),
+                        demand_id,
+                        offer_id
+                    ));
+                }
+                call => panic!("unexpected call: {:?}", call),
+            }
+            assert_eq!(offchain::market_matches::<Runtime>(0, 2), vec![]);
+        })
+    }
+
+    #[test]
+    fn test_order_expiry() {
+        use frame_support::traits::OnInitialize;
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let other = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz5"
+                .from_base58()
+                .unwrap();
+            let (alice, alice_proof) = get_params_proof("//Alice", &technics, &());
+            let (_, other_proof) = get_params_proof("//Alice", &other, &());
+            let treasury = account("//Treasury");
+
+            for (technics, proof) in vec![(technics, alice_proof), (other, other_proof)] {
+                assert_ok!(Liability::demand(
+                    Origin::signed(alice.clone()),
+                    technics,
+                    (),
+                    1,
+                    3,
+                    proof
+                ));
+            }
+            assert_eq!(Liability::orders_expiring_at(4), vec![0, 1]);
+            assert_eq!(Balances::reserved_balance(&alice), 54);
+
+            // Orders are open until deadline
+            Liability::on_initialize(3);
+            assert!(Liability::ask(0).is_some());
+
+            // Expired orders out of block budget are carried over
+            SWEEP_ITEMS.with(|items| *items.borrow_mut() = 1);
+            System::set_block_number(4);
+            Liability::on_initialize(4);
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::OrderExpired(0, Side::Demand, 0, 1, 1))
+            );
+            assert_eq!(Liability::ask(0), None);
+            assert_eq!(Liability::asks_by_model(0).len(), 1);
+            assert_eq!(Liability::orders_expiring_at(4), vec![0, 1]);
+            assert_eq!(Liability::expiry_sweep_cursor(), (4, 1));
+            assert_eq!(Balances::reserved_balance(&alice), 42);
+            assert_eq!(Balances::free_balance(&treasury), 1);
+
+            System::set_block_number(5);
+            Liability::on_initialize(5);
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::OrderExpired(1, Side::Demand, 0, 1, 4))
+            );
+            assert_eq!(Liability::ask(1), None);
+            assert_eq!(Liability::asks_by_model(0), vec![]);
+            assert_eq!(Liability::orders_expiring_at(4), vec![]);
+            assert_eq!(Liability::expiry_sweep_cursor(), (5, 0));
+            assert_eq!(Balances::reserved_balance(&alice), 0);
+            assert_eq!(Balances::free_balance(&alice), 995);
+            assert_eq!(Balances::free_balance(&treasury), 5);
+        })
+    }
+
+    #[test]
+    fn test_sweep_burst() {
+        use frame_support::{traits::OnInitialize, IterableStorageMap};
+
+        for &(burst, items) in &[(0, 1), (1, 1), (7, 2), (50, 16), (100, 3)] {
+            new_test_ext().execute_with(|| {
+                SWEEP_ITEMS.with(|budget| *budget.borrow_mut() = items);
+                let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                    .from_base58()
+                    .unwrap();
+                let (alice, proof) = get_params_proof("//Alice", &technics, &());
+
+                // Burst of items of each block indexed list at the same block
+                for id in 0..burst {
+                    let order = Order {
+                        technics: technics.clone(),
+                        economics: (),
+                        quantity: 1,
+                        sender: alice.clone(),
+                        proof: proof.clone(),
+                        deadline: 4,
+                        deposit: 0,
+                        escrow: 0,
+                    };
+                    <Asks<Runtime>>::insert(id, order);
+                    <OrdersExpiringAt<Runtime>>::mutate(5, |orders| orders.push(id));
+                    <PendingFunding<Runtime>>::insert(id, 4);
+                    <FundingExpiringAt<Runtime>>::mutate(5, |indices| indices.push(id));
+                    <ReportCommitments<Runtime>>::insert(id, (H256::zero(), 4));
+                    <RevealExpiringAt<Runtime>>::mutate(5, |indices| indices.push(id));
+                    let commitment = H256::from_low_u64_be(id);
+                    <MatchCommitments<Runtime>>::insert(&alice, commitment, 1);
+                    <MatchCommitCount<Runtime>>::mutate(&alice, |count| *count += 1);
+                    <MatchCommitsExpiringAt<Runtime>>::mutate(5, |commits| {
+                        commits.push((alice.clone(), commitment))
+                    });
+                    <PendingSettlement<Runtime>>::insert(id, 4);
+                    <SettlementsAt<Runtime>>::mutate(5, |indices| indices.push(id));
+                    <PendingAssignment<Runtime>>::insert(1_000 + id, 0);
+                    <AssignmentsAt<Runtime>>::mutate(5, |demands| demands.push(1_000 + id));
+                }
+                let pending = || {
+                    vec![
+                        <Asks<Runtime>>::iter().count(),
+                        <PendingFunding<Runtime>>::iter().count(),
+                        <ReportCommitments<Runtime>>::iter().count(),
+                        <MatchCommitments<Runtime>>::iter().count(),
+                        <PendingSettlement<Runtime>>::iter().count(),
+                        <PendingAssignment<Runtime>>::iter().count(),
+                    ]
+                };
+                let cursors = || {
+                    vec![
+                        Liability::expiry_sweep_cursor(),
+                        Liability::funding_sweep_cursor(),
+                        Liability::reveal_sweep_cursor(),
+                        Liability::match_commit_sweep_cursor(),
+                        Liability::settlement_sweep_cursor(),
+                        Liability::assignment_sweep_cursor(),
+                    ]
+                };
+
+                // Each sweep reads its cursor and blocks of list, the heaviest item is
+                // assignment of demand walking market pool
+                let db = <Runtime as frame_system::Trait>::DbWeight::get();
+                let item = db
+                    .reads_writes(6 + 5 * MaxPoolSize::get() as Weight, 8)
+                    .saturating_add(
+                        <() as WeightInfo>::force_finalize()
+                            .max(<() as WeightInfo>::overturn_report(MaxWitnesses::get()))
+                            .max(<() as WeightInfo>::match_orders()),
+                    );
+                let sweep = db
+                    .reads_writes(1, 1)
+                    .saturating_add(item.saturating_mul(items as Weight));
+                let budget = sweep
+                    .saturating_mul(7)
+                    .saturating_add(db.reads_writes(5, 6));
+
+                let mut expired = vec![];
+                let mut n = 0;
+                while cursors().iter().any(|(block, _)| *block <= 5) {
+                    // Every block sweeps at least one item of each list
+                    n += 1;
+                    assert!(n <= 6 + burst);
+                    System::set_block_number(n);
+                    System::reset_events();
+                    let before = pending();
+                    assert!(Liability::on_initialize(n) <= budget);
+                    for (before, after) in before.iter().zip(pending()) {
+                        assert!((before - after) as u32 <= items);
+                    }
+
+                    let events: Vec<_> = System::events()
+                        .into_iter()
+                        .filter_map(|record| match record.event {
+                            MetaEvent::liability(RawEvent::OrderExpired(order_id, ..)) => {
+                                Some(order_id)
+                            }
+                            _ => None,
+                        })
+                        .collect();
+                    assert!(events.len() as u32 <= items);
+                    expired.extend(events);
+                }
+
+                // Every item is swept exactly once
+                assert_eq!(expired, (0..burst).collect::<Vec<_>>());
+                assert_eq!(pending(), vec![0; 6]);
+                assert_eq!(cursors(), vec![(n + 1, 0); 6]);
+                assert_eq!(Liability::match_commit_count(&alice), 0);
+                assert_eq!(Liability::orders_expiring_at(5), vec![]);
+                assert!(!<FundingExpiringAt<Runtime>>::contains_key(5));
+                assert!(!<RevealExpiringAt<Runtime>>::contains_key(5));
+                assert!(!<MatchCommitsExpiringAt<Runtime>>::contains_key(5));
+                assert!(!<SettlementsAt<Runtime>>::contains_key(5));
+                assert!(!<AssignmentsAt<Runtime>>::contains_key(5));
+            });
+        }
+    }
+
+    #[test]
+    fn test_open_orders_limit() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (alice, proof) = get_params_proof("//Alice", &technics, &());
+            let demand = || {
+                Liability::demand(
+                    Origin::signed(alice.clone()),
+                    technics.clone(),
+                    (),
+                    1,
+                    5,
+                    proof.clone(),
+                )
+            };
+            let offer = || {
+                Liability::offer(
+                    Origin::signed(alice.clone()),
+                    technics.clone(),
+                    (),
+                    1,
+                    5,
+                    proof.clone(),
+                )
+            };
+
+            // Deposit grows quadratically with open orders of both sides
+            assert_ok!(demand());
+            assert_ok!(demand());
+            assert_ok!(offer());
+            assert_eq!(
+                (0..3)
+                    .map(|order_id| Liability::ask(order_id)
+                        .or(Liability::bid(order_id))
+                        .map(|order| order.deposit))
+                    .collect::<Vec<_>>(),
+                vec![Some(10), Some(40), Some(90)]
+            );
+            assert_eq!(Balances::reserved_balance(&alice), 144);
+            assert_eq!(Liability::open_orders(&alice), 3);
+            assert_err!(offer(), Error::<Runtime>::TooManyOpenOrders);
+
+            // Cancelled order frees a slot
+            assert_ok!(Liability::cancel_demand(Origin::signed(alice.clone()), 0));
+            assert_eq!(Liability::open_orders(&alice), 2);
+            assert_ok!(offer());
+            assert_eq!(Liability::bid(3).map(|order| order.deposit), Some(90));
+            assert_eq!(Balances::reserved_balance(&alice), 222);
+
+            assert_ok!(Liability::match_orders(Origin::signed(alice.clone()), 1, 2));
+            assert_eq!(Liability::open_orders(&alice), 1);
+            assert_ok!(Liability::cancel_offer(Origin::signed(alice.clone()), 3));
+            assert_eq!(Liability::open_orders(&alice), 0);
+            assert_eq!(Balances::reserved_balance(&alice), 0);
+        })
+    }
+
+    #[test]
+    fn test_order_breach() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let other = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz5"
+                .from_base58()
+                .unwrap();
+            let alice = account("//Alice");
+            let bob = account("//Bob");
+            let signed_order = |uri: &str, side, technics: &Vec<u8>, nonce| {
+                let pair = sr25519::Pair::from_string(uri, None).unwrap();
+                let mut order = SignedOrder {
+                    side,
+                    technics: technics.clone(),
+                    economics: (),
+                    sender: account(uri),
+                    expiry: 5,
+                    nonce,
+                    signature: pair.sign(&[]).into(),
+                };
+                order.signature = order
+                    .payload()
+                    .using_encoded(|payload| pair.sign(payload))
+                    .into();
+                order
+            };
+            let claim = |order, counter_order| {
+                Liability::claim_order_breach(Origin::signed(alice.clone()), order, counter_order)
+            };
+
+            let demand = signed_order("//Alice", Side::Demand, &technics, 0);
+            let offer = signed_order("//Bob", Side::Offer, &technics, 0);
+            assert!(signed::verify_order(&demand) && signed::verify_order(&offer));
+            let mut forged = offer.clone();
+            forged.nonce = 1;
+            assert!(!signed::verify_order(&forged));
+
+            assert_ok!(Liability::bond_orders(Origin::signed(bob.clone()), 100));
+            assert_eq!(Liability::order_bond(&bob), 100);
+
+            // Liability created before expiry fulfills orders
+            let (_, alice_proof) = get_params_proof("//Alice", &technics, &());
+            let (_, bob_proof) = get_params_proof("//Bob", &technics, &());
+            assert_ok!(Liability::create(
+                Origin::none(),
+                technics.clone(),
+                (),
+                alice.clone(),
+                bob.clone(),
+                alice_proof,
+                bob_proof,
+            ));
+            assert_err!(
+                claim(demand.clone(), offer.clone()),
+                Error::<Runtime>::OrderNotExpired
+            );
+            System::set_block_number(6);
+            assert_err!(
+                claim(demand.clone(), offer.clone()),
+                Error::<Runtime>::NoBreach
+            );
+
+            // Orders without liability are breached
+            let demand = signed_order("//Alice", Side::Demand, &other, 1);
+            let offer = signed_order("//Bob", Side::Offer, &other, 1);
+            assert_err!(
+                claim(offer.clone(), demand.clone()),
+                Error::<Runtime>::NotOrderOwner
+            );
+            assert_err!(
+                claim(demand.clone(), demand.clone()),
+                Error::<Runtime>::OrderMismatch
+            );
+            assert_err!(
+                claim(demand.clone(), forged),
+                Error::<Runtime>::BadOrderProof
+            );
+
+            // Bond is slashable until unlocked
+            assert_ok!(Liability::unbond_orders(Origin::signed(bob.clone())));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::BondUnlocking(bob.clone(), 16))
+            );
+            assert_ok!(claim(demand.clone(), offer.clone()));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::OrderBreachClaimed(alice.clone(), bob.clone(), 30))
+            );
+            assert_eq!(Balances::free_balance(&alice), 1_030);
+            assert_eq!(Balances::reserved_balance(&bob), 70);
+            assert_err!(claim(demand, offer), Error::<Runtime>::OrderClaimed);
+
+            assert_err!(
+                Liability::unbond_orders(Origin::signed(bob.clone())),
+                Error::<Runtime>::BondLocked
+            );
+            System::set_block_number(16);
+            assert_ok!(Liability::unbond_orders(Origin::signed(bob.clone())));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::OrderUnbonded(bob.clone(), 70))
+            );
+            assert_eq!(Balances::reserved_balance(&bob), 0);
+            assert_err!(
+                Liability::unbond_orders(Origin::signed(bob)),
+                Error::<Runtime>::NotBonded
+            );
+
+            assert_err!(
+                claim(
+                    signed_order("//Alice", Side::Demand, &other, 3),
+                    signed_order("//Bob", Side::Offer, &other, 3)
+                ),
+                Error::<Runtime>::ClaimPeriodPassed
+            );
+        })
+    }
+
+    #[test]
+    fn test_recent_matches() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (alice, alice_proof) = get_params_proof("//Alice", &technics, &());
+            let (bob, bob_proof) = get_params_proof("//Bob", &technics, &());
+            let charlie = account("//Charlie");
+            let offer = || {
+                Liability::offer(
+                    Origin::signed(bob.clone()),
+                    technics.clone(),
+                    (),
+                    30,
+                    5,
+                    bob_proof.clone(),
+                )
+            };
+            let match_offer =
+                |offer_id| Liability::match_orders(Origin::signed(charlie.clone()), 0, offer_id);
+            let info = |offer_id, index, block_number| MatchInfo {
+                demand_id: 0,
+                offer_id,
+                index,
+                price: 10,
+                quantity: 30,
+                value: 10,
+                block_number,
+            };
+
+            assert_ok!(Liability::demand(
+                Origin::signed(alice.clone()),
+                technics.clone(),
+                (),
+                100,
+                5,
+                alice_proof.clone(),
+            ));
+            assert_ok!(offer());
+            assert_ok!(match_offer(1));
+            assert_ok!(offer());
+            System::set_block_number(2);
+            assert_ok!(match_offer(2));
+            assert_ok!(offer());
+            System::set_block_number(4);
+            assert_ok!(match_offer(3));
+
+            // The oldest match is evicted
+            assert_eq!(
+                Liability::recent_matches(0, 10),
+                vec![info(3, 2, 4), info(2, 1, 2)]
+            );
+            assert_eq!(Liability::recent_matches(0, 1), vec![info(3, 2, 4)]);
+            assert_eq!(Liability::recent_matches(1, 10), vec![]);
+
+            let volume = |from, matches, quantity, value, truncated| MarketVolume {
+                from,
+                matches,
+                quantity,
+                value,
+                truncated,
+            };
+            assert_eq!(Liability::volume(0, 1), volume(4, 1, 30, 10, false));
+            assert_eq!(Liability::volume(0, 3), volume(2, 2, 60, 20, false));
+            // Evicted match is within window
+            assert_eq!(Liability::volume(0, 4), volume(1, 2, 60, 20, true));
+            assert_eq!(Liability::volume(0, 0), volume(5, 0, 0, 0, false));
+            assert_eq!(Liability::volume(1, 10), volume(0, 0, 0, 0, false));
+        })
+    }
+
+    #[test]
+    fn test_reputation() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (promisor, promisor_proof) = get_params_proof("//Bob", &technics, &());
+            for promisee_uri in &["//Alice", "//Charlie"] {
+                let (promisee, promisee_proof) = get_params_proof(promisee_uri, &technics, &());
+                assert_ok!(Liability::create(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    promisee,
+                    promisor.clone(),
+                    promisee_proof,
+                    promisor_proof.clone(),
+                ));
+            }
+            let reputation = |score, completed, failed, disputed| ReputationInfo {
+                score,
+                completed,
+                failed,
+                disputed,
+                ..Default::default()
+            };
+            let score_changed = |score| {
+                MetaEvent::reputation(reputation::RawEvent::ScoreChanged(promisor.clone(), score))
+            };
+
+            // Liability outcomes are passed by hooks
+            let proof = get_report_proof("//Bob", &0, &technics);
+            assert_ok!(Liability::finalize(Origin::none(), 0, technics, proof));
+            assert_eq!(
+                Reputation::reputation_of(&promisor),
+                reputation(10, 1, 0, 0)
+            );
+            System::set_block_number(6);
+            assert_ok!(Liability::force_finalize(Origin::root(), 1, false));
+            assert_eq!(Reputation::reputation_of(&promisor), reputation(0, 1, 0, 1));
+            assert_eq!(
+                Reputation::reputation_of(&account("//Alice")),
+                reputation(0, 0, 0, 0)
+            );
+
+            // Score is bounded, crossing step is notified
+            Reputation::note_outcome(&promisor, Outcome::Completed);
+            Reputation::note_outcome(&promisor, Outcome::Completed);
+            assert_eq!(System::events().last().unwrap().event, score_changed(20));
+            Reputation::note_outcome(&promisor, Outcome::Completed);
+            Reputation::note_outcome(&promisor, Outcome::Completed);
+            assert_eq!(
+                Reputation::reputation_of(&promisor),
+                reputation(30, 5, 0, 1)
+            );
+            Reputation::note_outcome(&promisor, Outcome::Failed);
+            assert_eq!(System::events().last().unwrap().event, score_changed(15));
+            assert_eq!(
+                Reputation::reputation_of(&promisor),
+                reputation(15, 5, 1, 1)
+            );
+            assert_eq!(
+                System::events()
+                    .iter()
+                    .filter(|record| matches!(record.event, MetaEvent::reputation(_)))
+                    .count(),
+                2
+            );
+        })
+    }
+
+    #[test]
+    fn test_rating() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (promisor, promisor_proof) = get_params_proof("//Bob", &technics, &());
+            let alice = account("//Alice");
+            let charlie = account("//Charlie");
+            for promisee_uri in &["//Alice", "//Charlie"] {
+                let (promisee, promisee_proof) = get_params_proof(promisee_uri, &technics, &());
+                assert_ok!(Liability::create(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    promisee,
+                    promisor.clone(),
+                    promisee_proof,
+                    promisor_proof.clone(),
+                ));
+            }
+            let rate = |who: &AccountId, index, rating| {
+                Reputation::rate(Origin::signed(who.clone()), index, rating)
+            };
+
+            assert_err!(
+                rate(&alice, 0, 4),
+                reputation::Error::<Runtime>::NotFinalized
+            );
+            for index in 0..2 {
+                let proof = get_report_proof("//Bob", &index, &technics);
+                assert_ok!(Liability::finalize(
+                    Origin::none(),
+                    index,
+                    technics.clone(),
+                    proof
+                ));
+            }
+
+            assert_err!(
+                rate(&alice, 0, 0),
+                reputation::Error::<Runtime>::InvalidRating
+            );
+            assert_err!(
+                rate(&alice, 0, 6),
+                reputation::Error::<Runtime>::InvalidRating
+            );
+            assert_err!(
+                rate(&alice, 2, 4),
+                reputation::Error::<Runtime>::UnknownLiability
+            );
+            assert_err!(
+                rate(&charlie, 0, 4),
+                reputation::Error::<Runtime>::NotPromisee
+            );
+            assert_err!(
+                rate(&promisor, 0, 4),
+                reputation::Error::<Runtime>::NotPromisee
+            );
+
+            // The last block of rating window
+            System::set_block_number(6);
+            assert_ok!(rate(&alice, 0, 4));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::reputation(reputation::RawEvent::Rated(Compact(0), promisor.clone(), 4))
+            );
+            assert_eq!(Reputation::rating(0), Some(4));
+            assert_err!(
+                rate(&alice, 0, 5),
+                reputation::Error::<Runtime>::AlreadyRated
+            );
+
+            System::set_block_number(7);
+            assert_err!(
+                rate(&charlie, 1, 5),
+                reputation::Error::<Runtime>::RatingWindowPassed
+            );
+            assert_eq!(Reputation::rating(1), None);
+
+            let reputation = Reputation::reputation_of(&promisor);
+            assert_eq!((reputation.ratings, reputation.rating_sum), (1, 4));
+            assert_eq!(reputation.completed, 2);
+        })
+    }
+
+    #[test]
+    fn test_reputation_decay() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let promisor = account("//Bob");
+            for _ in 0..3 {
+                Reputation::note_outcome(&promisor, Outcome::Completed);
+            }
+            assert_eq!(Reputation::reputation_of(&promisor).score, 30);
+
+            // Decay of passed eras is applied on read
+            System::set_block_number(25);
+            let reputation = Reputation::reputation_of(&promisor);
+            assert_eq!((reputation.score, reputation.era), (7, 2));
+
+            // and before update
+            Reputation::note_outcome(&promisor, Outcome::Completed);
+            assert_eq!(Reputation::reputation_of(&promisor).score, 17);
+            System::set_block_number(35);
+            assert_eq!(Reputation::reputation_of(&promisor).score, 8);
+            assert_eq!(Reputation::reputation_of(&promisor).completed, 4);
+        })
+    }
+
+    #[test]
+    fn test_lazy_decay_agrees_with_eager() {
+        let decays = [
+            Perbill::zero(),
+            Perbill::from_parts(1),
+            Perbill::from_percent(50),
+            Perbill::from_percent(90),
+            Perbill::from_percent(99),
+            Perbill::from_parts(999_999_999),
+            Perbill::one(),
+        ];
+        let scores = [0, 1, 7, 30, 1_000, 65_537, 1_000_000];
+        for &per_era in decays.iter() {
+            for &score in scores.iter() {
+                let mut eager = score;
+                for eras in 0..100u32 {
+                    let lazy = reputation::decay_factor(per_era, eras).mul_floor(score);
+                    // Eager decay rounds down each era, lazy one rounds down its factor
+                    assert!(
+                        lazy + 1 >= eager && lazy <= eager + eras,
+                        "decay {:?} of {} after {} eras: lazy {}, eager {}",
+                        per_era,
+                        score,
+                        eras,
+                        lazy,
+                        eager
+                    );
+
+                    // Decay applied by several updates differs by rounding only
+                    let info = ReputationInfo {
+                        score,
+                        ..Default::default()
+                    };
+                    let split = reputation::decay(
+                        reputation::decay(info, per_era, eras / 2),
+                        per_era,
+                        eras,
+                    );
+                    let once = reputation::decay(info, per_era, eras);
+                    assert_eq!(once.era, eras);
+                    assert!(split.score <= once.score + 1 && once.score <= split.score + 1);
+                    eager = per_era.mul_floor(eager);
+                }
+            }
+        }
+
+        assert_eq!(
+            reputation::decay_factor(Perbill::one(), u32::max_value()),
+            Perbill::one()
+        );
+        assert_eq!(
+            reputation::decay_factor(Perbill::from_percent(50), 3),
+            Perbill::from_parts(125_000_000)
+        );
+        assert_eq!(
+            reputation::decay_factor(Perbill::from_percent(99), u32::max_value()),
+            Perbill::zero()
+        );
+    }
+
+    #[test]
+    fn test_min_reputation() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            MIN_REPUTATION.with(|min| *min.borrow_mut() = 15);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (promisor, promisor_proof) = get_params_proof("//Bob", &technics, &());
+            let create = |promisee_uri| {
+                let (promisee, promisee_proof) = get_params_proof(promisee_uri, &technics, &());
+                Liability::create(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    promisee,
+                    promisor.clone(),
+                    promisee_proof,
+                    promisor_proof.clone(),
+                )
+            };
+
+            assert_err!(create("//Alice"), Error::<Runtime>::ReputationTooLow);
+            assert_eq!(Liability::latest_index(), 0);
+
+            Reputation::note_outcome(&promisor, Outcome::Completed);
+            Reputation::note_outcome(&promisor, Outcome::Completed);
+            assert_ok!(create("//Alice"));
+            // Reputation seen at creation is kept for audit
+            Reputation::note_outcome(&promisor, Outcome::Failed);
+            assert_eq!(Liability::reputation_snapshot(0), Some((15, 20)));
+
+            MIN_REPUTATION.with(|min| *min.borrow_mut() = 0);
+            assert_ok!(create("//Charlie"));
+            assert_eq!(Liability::reputation_snapshot(1), None);
+        })
+    }
+
+    #[test]
+    fn test_blacklist() {
+        use frame_support::unsigned::ValidateUnsigned;
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (alice, alice_proof) = get_params_proof("//Alice", &technics, &());
+            let (bob, bob_proof) = get_params_proof("//Bob", &technics, &());
+            let (charlie, charlie_proof) = get_params_proof("//Charlie", &technics, &());
+            let create = |promisee: &AccountId, promisee_proof: &ProofParam<Runtime>| {
+                Liability::create(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    promisee.clone(),
+                    bob.clone(),
+                    promisee_proof.clone(),
+                    bob_proof.clone(),
+                )
+            };
+
+            // Open liabilities and order of Bob before blacklisting
+            assert_ok!(create(&alice, &alice_proof));
+            assert_ok!(create(&charlie, &charlie_proof));
+            assert_ok!(Liability::offer(
+                Origin::signed(bob.clone()),
+                technics.clone(),
+                (),
+                1,
+                20,
+                bob_proof.clone()
+            ));
+            assert_eq!(Balances::reserved_balance(&bob), 10);
+
+            // Only admin blacklists accounts
+            assert_err!(
+                Liability::blacklist(Origin::signed(alice.clone()), bob.clone()),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_ok!(Liability::blacklist(Origin::root(), bob.clone()));
+            assert!(Liability::is_blacklisted(&bob));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::AccountBlacklisted(bob.clone()))
+            );
+
+            // Blacklisted account takes no new liabilities and orders
+            System::set_block_number(2);
+            assert_err!(create(&alice, &alice_proof), Error::<Runtime>::Blacklisted);
+            let call = Call::create(
+                technics.clone(),
+                (),
+                alice.clone(),
+                bob.clone(),
+                alice_proof.clone(),
+                bob_proof.clone(),
+            );
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &call).map(|_| ()),
+                Err(ValidityError::NotPermitted.into())
+            );
+            assert_err!(
+                Liability::offer(
+                    Origin::signed(bob.clone()),
+                    technics.clone(),
+                    (),
+                    1,
+                    20,
+                    bob_proof.clone()
+                ),
+                Error::<Runtime>::Blacklisted
+            );
+            assert_ok!(Liability::demand(
+                Origin::signed(alice.clone()),
+                technics.clone(),
+                (),
+                1,
+                20,
+                alice_proof.clone()
+            ));
+            assert_err!(
+                Liability::match_orders(Origin::signed(charlie.clone()), 1, 0),
+                Error::<Runtime>::Blacklisted
+            );
+            assert_eq!(Liability::latest_index(), 2);
+
+            // Open liabilities and orders of blacklisted account still settle
+            let proof = get_report_proof("//Bob", &0, &technics);
+            assert_ok!(Liability::finalize(
+                Origin::none(),
+                0,
+                technics.clone(),
+                proof
+            ));
+            System::set_block_number(6);
+            assert_ok!(Liability::force_finalize(Origin::root(), 1, false));
+            assert_ok!(Liability::cancel_offer(Origin::signed(bob.clone()), 0));
+            assert_eq!(Balances::reserved_balance(&bob), 0);
+
+            assert_ok!(Liability::unblacklist(Origin::root(), bob.clone()));
+            assert!(!Liability::is_blacklisted(&bob));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::AccountUnblacklisted(bob.clone()))
+            );
+            assert_ok!(create(&alice, &alice_proof));
+        })
+    }
+
+    #[test]
+    fn test_robot_registry() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (alice, alice_proof) = get_params_proof("//Alice", &technics, &());
+            let (bob, bob_proof) = get_params_proof("//Bob", &technics, &());
+            let charlie = account("//Charlie");
+            let metadata = H256::repeat_byte(1);
+            let register = |owner: &AccountId, robot: &AccountId, serial: &[u8]| {
+                Registry::register_robot(
+                    Origin::signed(owner.clone()),
+                    robot.clone(),
+                    serial.to_vec(),
+                    metadata,
+                )
+            };
+
+            assert_err!(
+                register(&alice, &bob, b"SN-000001"),
+                registry::Error::<Runtime>::SerialTooLong
+            );
+            assert_ok!(register(&alice, &bob, b"SN-0001"));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::registry(registry::RawEvent::RobotRegistered(
+                    0,
+                    bob.clone(),
+                    alice.clone()
+                ))
+            );
+            assert_eq!(Balances::reserved_balance(&alice), 50);
+            assert_err!(
+                register(&charlie, &bob, b"SN-0002"),
+                registry::Error::<Runtime>::RobotRegistered
+            );
+            assert_err!(
+                register(&alice, &charlie, b"SN-0001"),
+                registry::Error::<Runtime>::DuplicateSerial
+            );
+            // Serial numbers are unique by owner
+            assert_ok!(register(&charlie, &charlie, b"SN-0001"));
+
+            let info = RobotInfo {
+                robot: bob.clone(),
+                owner: alice.clone(),
+                serial: b"SN-0001".to_vec(),
+                metadata,
+                deposit: 50,
+            };
+            assert_eq!(
+                Registry::robot_by_serial(&alice, b"SN-0001"),
+                Some((0, info.clone()))
+            );
+            assert_eq!(Registry::robot_by_serial(&bob, b"SN-0001"), None);
+
+            // Registry entry of promisor is resolved on creation
+            assert_ok!(Liability::create(
+                Origin::none(),
+                technics.clone(),
+                (),
+                alice.clone(),
+                bob.clone(),
+                alice_proof.clone(),
+                bob_proof.clone(),
+            ));
+            assert_eq!(Liability::liability_robot(0), Some(0));
+
+            assert_err!(
+                Registry::transfer_robot(Origin::signed(charlie.clone()), 0, charlie.clone()),
+                registry::Error::<Runtime>::NotOwner
+            );
+            assert_err!(
+                Registry::transfer_robot(Origin::signed(alice.clone()), 0, charlie.clone()),
+                registry::Error::<Runtime>::DuplicateSerial
+            );
+            assert_ok!(Registry::transfer_robot(
+                Origin::signed(alice.clone()),
+                0,
+                bob.clone()
+            ));
+            assert_eq!(Balances::reserved_balance(&alice), 0);
+            assert_eq!(Balances::reserved_balance(&bob), 50);
+            assert_eq!(
+                NamedReserve::<Runtime>::reserved_balance_named(&RESERVE_ID, &bob),
+                50
+            );
+            assert_ok!(Liability::try_state());
+            assert_eq!(Registry::robot_by_serial(&alice, b"SN-0001"), None);
+            assert_eq!(
+                Registry::robot_by_serial(&bob, b"SN-0001").map(|(_, info)| info.owner),
+                Some(bob.clone())
+            );
+
+            assert_err!(
+                Registry::deregister_robot(Origin::signed(alice.clone()), 0),
+                registry::Error::<Runtime>::NotOwner
+            );
+            // Deposit of another module isn't released with robot deposit
+            assert_ok!(Balances::reserve(&bob, 30));
+            assert_ok!(Registry::deregister_robot(Origin::signed(bob.clone()), 0));
+            assert_eq!(Balances::reserved_balance(&bob), 30);
+            assert_eq!(
+                NamedReserve::<Runtime>::reserved_balance_named(&RESERVE_ID, &bob),
+                0
+            );
+            assert_eq!(Registry::robot(0), None);
+            assert_eq!(Registry::robot_of(&bob), None);
+            assert_err!(
+                Registry::deregister_robot(Origin::signed(bob.clone()), 0),
+                registry::Error::<Runtime>::UnknownRobot
+            );
+
+            // Liabilities of deregistered robot keep registry entry id
+            System::set_block_number(2);
+            assert_ok!(Liability::create(
+                Origin::none(),
+                technics.clone(),
+                (),
+                alice,
+                bob,
+                alice_proof,
+                bob_proof,
+            ));
+            assert_eq!(Liability::liability_robot(0), Some(0));
+            assert_eq!(Liability::liability_robot(1), None);
+        })
+    }
+
+    #[test]
+    fn test_rotate_promisor_key() {
+        use frame_support::unsigned::ValidateUnsigned;
+
+        new_test_ext().execute_with(|| {
+            let (bob, charlie, dave) = (account("//Bob"), account("//Charlie"), account("//Dave"));
+            Balances::make_free_balance_be(&dave, 100);
+            assert_ok!(Registry::register_robot(
+                Origin::signed(charlie.clone()),
+                bob.clone(),
+                b"SN-0001".to_vec(),
+                H256::repeat_byte(1),
+            ));
+            ACK_WINDOW.with(|window| *window.borrow_mut() = 5);
+            create_liability(1);
+            ACK_WINDOW.with(|window| *window.borrow_mut() = 0);
+            create_liability(2);
+            assert_eq!(Liability::named_reserve(RESERVE_ID, &bob), 10);
+
+            // Rotation is proven by robot owner
+            let rotate = |proof| {
+                Registry::rotate_promisor_key(
+                    Origin::signed(dave.clone()),
+                    bob.clone(),
+                    dave.clone(),
+                    proof,
+                )
+            };
+            assert_err!(
+                rotate(crate::testing::make_rotation_proof("//Bob", &bob, &dave)),
+                registry::Error::<Runtime>::BadOwnerProof
+            );
+            assert_ok!(rotate(crate::testing::make_rotation_proof(
+                "//Charlie",
+                &bob,
+                &dave
+            )));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::registry(registry::RawEvent::RobotKeyRotated(
+                    0,
+                    bob.clone(),
+                    dave.clone()
+                ))
+            );
+            assert_eq!(Liability::rotated_promisor(&bob), Some(dave.clone()));
+            assert_eq!(Liability::pending_rotation(&bob), None);
+            assert_eq!(
+                Registry::robot(0).map(|info| info.robot),
+                Some(dave.clone())
+            );
+            assert_eq!(
+                Liability::liability(1).map(|l| l.promisor().clone()),
+                Some(dave.clone())
+            );
+            assert_eq!(
+                Liability::promisor_liability_list(&bob, 0, 10).items.len(),
+                0
+            );
+            assert_eq!(
+                Liability::promisor_liability_list(&dave, 0, 10).items.len(),
+                2
+            );
+            assert_eq!(Liability::pending_acknowledgement(0), Some((6, 10)));
+            assert_eq!(Liability::named_reserve(RESERVE_ID, &bob), 0);
+            assert_eq!(Liability::named_reserve(RESERVE_ID, &dave), 10);
+            assert_eq!(
+                Liability::history_of(1).last(),
+                Some(&(2, Transition::Rotated(dave.clone())))
+            );
+
+            // Reports of rotated liabilities are proven by the new key
+            let report = vec![2];
+            let proof = get_report_proof("//Bob", &1, &report);
+            assert_eq!(
+                Liability::validate_unsigned(
+                    TransactionSource::External,
+                    &Call::finalize(1, report.clone(), proof.clone())
+                ),
+                ValidityError::BadReportProof.into()
+            );
+            assert_err!(
+                Liability::finalize(Origin::none(), 1, report.clone(), proof),
+                Error::<Runtime>::BadReportProof
+            );
+            let proof = get_report_proof("//Dave", &1, &report);
+            assert_ok!(Liability::finalize(Origin::none(), 1, report, proof));
+
+            // Old key isn't promisor of new liabilities and isn't rotated twice
+            let technics = vec![3];
+            let (alice, promisee_proof) = get_params_proof("//Alice", &technics, &());
+            let (_, promisor_proof) = get_params_proof("//Bob", &technics, &());
+            assert_err!(
+                Liability::create(
+                    Origin::none(),
+                    technics,
+                    (),
+                    alice,
+                    bob.clone(),
+                    promisee_proof,
+                    promisor_proof,
+                ),
+                Error::<Runtime>::PromisorKeyRotated
+            );
+            assert_err!(
+                Liability::continue_rotation(Origin::signed(dave.clone()), bob.clone()),
+                Error::<Runtime>::NoPendingRotation
+            );
+        })
+    }
+
+    #[test]
+    fn test_robot_capabilities() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let alice = account("//Alice");
+            let bob = account("//Bob");
+            let (_, promisee_proof) = get_params_proof("//Alice", &technics, &());
+            let (_, promisor_proof) = get_params_proof("//Bob", &technics, &());
+            let call = Call::create(
+                technics.clone(),
+                (),
+                alice.clone(),
+                bob.clone(),
+                promisee_proof.clone(),
+                promisor_proof.clone(),
+            );
+            let create = || {
+                Liability::create(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    alice.clone(),
+                    bob.clone(),
+                    promisee_proof.clone(),
+                    promisor_proof.clone(),
+                )
+            };
+            assert_ok!(Registry::register_robot(
+                Origin::signed(alice.clone()),
+                bob.clone(),
+                b"SN-0001".to_vec(),
+                H256::repeat_byte(1),
+            ));
+            REQUIRED_CAPABILITIES.with(|r| *r.borrow_mut() = Capabilities::from_ids(&[2, 9]));
+
+            // Promisor robot declares nothing or only a part of required capabilities
+            assert_err_ignore_postinfo!(create(), Error::<Runtime>::CapabilitiesMismatch);
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &call),
+                ValidityError::NotPermitted.into(),
+            );
+            assert_err!(
+                Registry::set_capabilities(
+                    Origin::signed(bob.clone()),
+                    0,
+                    Capabilities::from_ids(&[2])
+                ),
+                registry::Error::<Runtime>::NotOwner
+            );
+            assert_err!(
+                Registry::set_capabilities(
+                    Origin::signed(alice.clone()),
+                    0,
+                    Capabilities(vec![0; registry::MAX_CAPABILITIES_LENGTH + 1])
+                ),
+                registry::Error::<Runtime>::CapabilitiesTooLong
+            );
+            assert_ok!(Registry::set_capabilities(
+                Origin::signed(alice.clone()),
+                0,
+                Capabilities::from_ids(&[2])
+            ));
+            assert_err_ignore_postinfo!(create(), Error::<Runtime>::CapabilitiesMismatch);
+
+            // Superset of required capabilities covers them
+            let declared = Capabilities::from_ids(&[1, 2, 9]);
+            assert_ok!(Registry::set_capabilities(
+                Origin::signed(alice.clone()),
+                0,
+                declared.clone()
+            ));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::registry(registry::RawEvent::CapabilitiesSet(0, declared.clone()))
+            );
+            assert_eq!(Registry::robot_capabilities(0), declared);
+            assert!(Liability::validate_unsigned(TransactionSource::External, &call).is_ok());
+            assert_ok!(create());
+
+            // Declaration removed later doesn't affect open liability
+            assert_ok!(Registry::set_capabilities(
+                Origin::signed(alice.clone()),
+                0,
+                Capabilities::default()
+            ));
+            assert_eq!(Registry::robot_capabilities(0), Capabilities::default());
+            System::set_block_number(6);
+            assert_ok!(Liability::force_finalize(Origin::root(), 0, true));
+            assert!(Liability::is_finalized(0));
+
+            // Liability requiring nothing is created for any promisor
+            REQUIRED_CAPABILITIES.with(|r| *r.borrow_mut() = Capabilities(vec![0, 0]));
+            assert_ok!(create());
+            REQUIRED_CAPABILITIES.with(|r| *r.borrow_mut() = Capabilities::default());
+        });
+    }
+
+    #[test]
+    fn test_robot_operators() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let alice = account("//Alice");
+            let bob = account("//Bob");
+            let (charlie, charlie_proof) = get_params_proof("//Charlie", &technics, &());
+            let robot = account("//Robot");
+            assert_ok!(Registry::register_robot(
+                Origin::signed(alice.clone()),
+                robot,
+                b"SN-0001".to_vec(),
+                H256::repeat_byte(1),
+            ));
+            let create = || {
+                Registry::create_operated(
+                    Origin::signed(bob.clone()),
+                    0,
+                    technics.clone(),
+                    (),
+                    charlie.clone(),
+                    charlie_proof.clone(),
+                )
+            };
+
+            assert_err!(create(), registry::Error::<Runtime>::NotOperator);
+            assert_err!(
+                Registry::add_operator(Origin::signed(bob.clone()), 0, bob.clone()),
+                registry::Error::<Runtime>::NotOwner
+            );
+            assert_ok!(Registry::add_operator(
+                Origin::signed(alice.clone()),
+                0,
+                bob.clone()
+            ));
+            assert!(Registry::is_operator(0, &bob));
+            assert_ok!(Registry::set_revenue_split(
+                Origin::signed(alice.clone()),
+                0,
+                Perbill::from_percent(20)
+            ));
+            assert_err!(
+                Registry::create_operated(
+                    Origin::signed(bob.clone()),
+                    0,
+                    technics.clone(),
+                    (),
+                    charlie.clone(),
+                    get_params_proof("//Alice", &technics, &()).1,
+                ),
+                Error::<Runtime>::BadPromiseeProof
+            );
+
+            // Owner is promisor of liability created by operator
+            assert_ok!(create());
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::registry(registry::RawEvent::OperatedLiability(
+                    Compact(0),
+                    0,
+                    bob.clone()
+                ))
+            );
+            assert_eq!(
+                Liability::liability(0).map(|l| l.promisor().clone()),
+                Some(alice.clone())
+            );
+            assert_eq!(Liability::liability_robot(0), Some(0));
+            assert_eq!(
+                Registry::liability_operator(0),
+                Some((bob.clone(), Perbill::from_percent(20)))
+            );
+
+            // Revenue split change doesn't affect open liability
+            assert_ok!(Registry::set_revenue_split(
+                Origin::signed(alice.clone()),
+                0,
+                Perbill::from_percent(50)
+            ));
+            assert_eq!(
+                Liability::preview(0, true),
+                Some(Payouts {
+                    transfers: vec![(alice.clone(), bob.clone(), 2)],
+                    refunds: vec![],
+                })
+            );
+            assert_eq!(Liability::preview(0, false), Some(Default::default()));
+            let proof = get_report_proof("//Alice", &0, &technics);
+            assert_ok!(Liability::finalize(
+                Origin::none(),
+                0,
+                technics.clone(),
+                proof
+            ));
+            assert_eq!(Balances::free_balance(&bob), 1_002);
+            assert_eq!(Balances::free_balance(&alice), 1_000 - 50 - 2);
+            assert_eq!(Liability::preview(0, true), None);
+
+            // Removed operator can't create liabilities at once
+            System::set_block_number(2);
+            assert_ok!(Registry::remove_operator(
+                Origin::signed(alice.clone()),
+                0,
+                bob.clone()
+            ));
+            assert_err!(create(), registry::Error::<Runtime>::NotOperator);
+            assert_eq!(Liability::latest_index(), 1);
+            assert_err!(
+                Registry::remove_operator(Origin::signed(alice.clone()), 0, bob.clone()),
+                registry::Error::<Runtime>::NotOperator
+            );
+        })
+    }
+
+    #[test]
+    fn test_account_stats() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (alice, alice_proof) = get_params_proof("//Alice", &technics, &());
+            let (bob, bob_proof) = get_params_proof("//Bob", &technics, &());
+            let (charlie, charlie_proof) = get_params_proof("//Charlie", &technics, &());
+            for (promisee, promisee_proof) in &[
+                (alice.clone(), alice_proof.clone()),
+                (charlie.clone(), charlie_proof),
+                (alice.clone(), alice_proof),
+            ] {
+                assert_ok!(Liability::create(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    promisee.clone(),
+                    bob.clone(),
+                    promisee_proof.clone(),
+                    bob_proof.clone(),
+                ));
+                System::set_block_number(System::block_number() + 1);
+            }
+            let counts = |completed, failed, disputed| OutcomeCounts {
+                completed,
+                failed,
+                disputed,
+            };
+            assert_eq!(Liability::stats_of(&bob), AccountStats::default());
+
+            // Report accepted
+            let proof = get_report_proof("//Bob", &0, &technics);
+            assert_ok!(Liability::finalize(
+                Origin::none(),
+                0,
+                technics.clone(),
+                proof
+            ));
+            assert_eq!(
+                Liability::stats_of(&bob),
+                AccountStats {
+                    as_promisor: counts(1, 0, 0),
+                    as_promisee: counts(0, 0, 0),
+                    settled: 10,
+                }
+            );
+            assert_eq!(
+                Liability::stats_of(&alice),
+                AccountStats {
+                    as_promisor: counts(0, 0, 0),
+                    as_promisee: counts(1, 0, 0),
+                    settled: 10,
+                }
+            );
+
+            // Force finalized liabilities
+            System::set_block_number(10);
+            assert_ok!(Liability::force_finalize(Origin::root(), 1, true));
+            assert_ok!(Liability::force_finalize(Origin::root(), 2, false));
+            assert_eq!(Liability::stats_of(&bob).as_promisor, counts(2, 0, 1));
+            assert_eq!(Liability::stats_of(&bob).settled, 20);
+            assert_eq!(Liability::stats_of(&charlie).as_promisee, counts(1, 0, 0));
+            assert_eq!(
+                Liability::stats_of(&alice),
+                AccountStats {
+                    as_promisor: counts(0, 0, 0),
+                    as_promisee: counts(1, 0, 1),
+                    settled: 10,
+                }
+            );
+
+            // Liability failed without force, counters are saturated
+            let liability = Liability::liability(0).unwrap();
+            Liability::note_stats(&liability, false, false);
+            assert_eq!(Liability::stats_of(&bob).as_promisor, counts(2, 1, 1));
+            <StatsOf<Runtime>>::insert(
+                &bob,
+                AccountStats {
+                    as_promisor: counts(u32::MAX, 0, 0),
+                    as_promisee: counts(0, 0, 0),
+                    settled: u128::MAX,
+                },
+            );
+            Liability::note_stats(&liability, true, false);
+            assert_eq!(
+                Liability::stats_of(&bob).as_promisor,
+                counts(u32::MAX, 0, 0)
+            );
+            assert_eq!(Liability::stats_of(&bob).settled, u128::MAX);
+            assert_eq!(Liability::stats_of(&alice).as_promisee, counts(2, 1, 1));
+        })
+    }
+
+    #[test]
+    fn test_model_stats() {
+        new_test_ext().execute_with(|| {
+            let create = |block: u64, technics: Vec<u8>| {
+                System::set_block_number(block);
+                let (alice, promisee_proof) = get_params_proof("//Alice", &technics, &());
+                let (bob, promisor_proof) = get_params_proof("//Bob", &technics, &());
+                assert_ok!(Liability::create(
+                    Origin::none(),
+                    technics,
+                    (),
+                    alice,
+                    bob,
+                    promisee_proof,
+                    promisor_proof,
+                ));
+            };
+            let (model_a, model_b) = (technics_hash(&vec![1u8]), technics_hash(&vec![2u8]));
+            create(1, vec![1]);
+            create(2, vec![1]);
+            create(3, vec![2]);
+            let board = |by| Liability::model_leaderboard(by);
+            assert_eq!(
+                board(ModelRanking::Executions),
+                vec![(2, model_a), (1, model_b)]
+            );
+            assert_eq!(board(ModelRanking::Completed), vec![]);
+
+            // Report accepted
+            let proof = get_report_proof("//Bob", &2, &vec![2]);
+            assert_ok!(Liability::finalize(Origin::none(), 2, vec![2], proof));
+            assert_eq!(board(ModelRanking::Completed), vec![(1, model_b)]);
+            assert_eq!(
+                board(ModelRanking::SuccessRate),
+                vec![(1_000_000_000, model_b)]
+            );
+
+            // Model updated later is placed after equal one
+            System::set_block_number(10);
+            assert_ok!(Liability::force_finalize(Origin::root(), 0, true));
+            assert_ok!(Liability::force_finalize(Origin::root(), 1, false));
+            assert_eq!(
+                Liability::model_stats(model_a),
+                ModelStats {
+                    executions: 2,
+                    outcomes: OutcomeCounts {
+                        completed: 1,
+                        failed: 0,
+                        disputed: 1,
+                    },
+                    settled: 10,
+                }
+            );
+            assert_eq!(
+                board(ModelRanking::Completed),
+                vec![(1, model_b), (1, model_a)]
+            );
+            assert_eq!(
+                board(ModelRanking::SuccessRate),
+                vec![(1_000_000_000, model_b), (500_000_000, model_a)]
+            );
+            assert_eq!(
+                Liability::top_models(ModelRanking::Settled, 1),
+                vec![(model_b, Liability::model_stats(model_b))]
+            );
+            assert_eq!(
+                Liability::top_models(ModelRanking::Executions, 5)
+                    .into_iter()
+                    .map(|(model, _)| model)
+                    .collect::<Vec<_>>(),
+                vec![model_a, model_b]
+            );
+        })
+    }
+
+    #[test]
+    fn test_model_leaderboard() {
+        let length = MAX_LEADERBOARD_LENGTH as u128;
+        let mut board: Vec<_> = (0..length)
+            .map(|score| (length - score, H256::repeat_byte(score as u8)))
+            .collect();
+
+        // The lowest is evicted from full leaderboard
+        let model = H256::repeat_byte(0xff);
+        Liability::rank_model(&mut board, model, 1);
+        assert_eq!(board.len(), MAX_LEADERBOARD_LENGTH as usize);
+        assert!(!board.iter().any(|(_, placed)| *placed == model));
+        Liability::rank_model(&mut board, model, 50);
+        assert_eq!(board.len(), MAX_LEADERBOARD_LENGTH as usize);
+        assert_eq!(board[length as usize - 50], (50, H256::repeat_byte(50)));
+        assert_eq!(board[length as usize - 49], (50, model));
+        assert_eq!(board.last(), Some(&(2, H256::repeat_byte(98))));
+
+        // Placed model is moved, model with zero score is removed
+        Liability::rank_model(&mut board, model, 200);
+        assert_eq!(board[0], (200, model));
+        assert_eq!(board.len(), MAX_LEADERBOARD_LENGTH as usize);
+        Liability::rank_model(&mut board, model, 0);
+        assert_eq!(board.len(), MAX_LEADERBOARD_LENGTH as usize - 1);
+        assert!(board.windows(2).all(|pair| pair[0].0 >= pair[1].0));
+    }
+
+    #[test]
+    fn test_firmware_attestation() {
+        use frame_support::unsigned::ValidateUnsigned;
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let report = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz5"
+                .from_base58()
+                .unwrap();
+            let firmware = H256::repeat_byte(7);
+            let (alice, alice_proof) = get_params_proof("//Alice", &technics, &());
+            let (bob, bob_proof) = get_params_proof("//Bob", &technics, &());
+            for _ in 0..3 {
+                assert_ok!(Liability::create(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    alice.clone(),
+                    bob.clone(),
+                    alice_proof.clone(),
+                    bob_proof.clone(),
+                ));
+            }
+
+            // Firmware of non-strict liability is recorded as is
+            assert_ok!(Liability::finalize_attested(
+                Origin::none(),
+                0,
+                report.clone(),
+                firmware,
+                get_attested_report_proof("//Bob", &0, &report, &firmware),
+            ));
+            assert_eq!(Liability::firmware_of(0), Some(firmware));
+            assert!(System::events().iter().any(|record| record.event
+                == MetaEvent::liability(RawEvent::FirmwareAttested(Compact(0), firmware))));
+
+            // Attestation is signed by promisor
+            assert_err_ignore_postinfo!(
+                Liability::finalize_attested(
+                    Origin::none(),
+                    1,
+                    report.clone(),
+                    firmware,
+                    get_report_proof("//Bob", &1, &report),
+                ),
+                Error::<Runtime>::BadReportProof
+            );
+
+            // Strict liability requires attestation of approved firmware
+            STRICT_FIRMWARE.with(|strict| *strict.borrow_mut() = true);
+            assert_err_ignore_postinfo!(
+                Liability::finalize(
+                    Origin::none(),
+                    1,
+                    report.clone(),
+                    get_report_proof("//Bob", &1, &report),
+                ),
+                Error::<Runtime>::FirmwareRequired
+            );
+            let proof = get_attested_report_proof("//Bob", &1, &report, &firmware);
+            assert_err_ignore_postinfo!(
+                Liability::finalize_attested(
+                    Origin::none(),
+                    1,
+                    report.clone(),
+                    firmware,
+                    proof.clone(),
+                ),
+                Error::<Runtime>::FirmwareNotApproved
+            );
+            let call = Call::finalize_attested(1, report.clone(), firmware, proof.clone());
+            assert_eq!(
+                Liability::validate_unsigned(TransactionSource::External, &call),
+                InvalidTransaction::Call.into(),
+            );
+
+            // Only admin approves firmware of robot model
+            assert_err!(
+                Liability::approve_firmware(Origin::signed(alice.clone()), 0, firmware),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_ok!(Liability::approve_firmware(Origin::root(), 0, firmware));
+            assert!(Liability::is_firmware_approved(0, firmware));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::FirmwareApproved(0, firmware))
+            );
+            assert!(Liability::validate_unsigned(TransactionSource::External, &call).is_ok());
+            assert_ok!(Liability::finalize_attested(
+                Origin::none(),
+                1,
+                report.clone(),
+                firmware,
+                proof,
+            ));
+            assert_eq!(Liability::firmware_of(1), Some(firmware));
+
+            // Revoked firmware isn't accepted anymore
+            assert_ok!(Liability::revoke_firmware(Origin::root(), 0, firmware));
+            assert!(!Liability::is_firmware_approved(0, firmware));
+            assert_err_ignore_postinfo!(
+                Liability::finalize_attested(
+                    Origin::none(),
+                    2,
+                    report.clone(),
+                    firmware,
+                    get_attested_report_proof("//Bob", &2, &report, &firmware),
+                ),
+                Error::<Runtime>::FirmwareNotApproved
+            );
+            assert_eq!(Liability::firmware_of(2), None);
+            STRICT_FIRMWARE.with(|strict| *strict.borrow_mut() = false);
+        });
+    }
+
+    #[test]
+    fn test_report_storage_key() {
+        assert_eq!(
+            report_storage_key(&42u64),
+            <Liabilities<Runtime>>::hashed_key_for(42)
+        );
+    }
+
+    #[test]
+    fn test_typed_liability_size() {
+        use codec::{Compact, CompactLen};
+
+        let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+            .from_base58()
+            .unwrap();
+        let (alice, _) = get_params_proof("//Alice", &technics, &());
+        let (bob, _) = get_params_proof("//Bob", &technics, &());
+        let liability = <Runtime as Trait>::Liability::new(technics, (), alice, bob);
+
+        // Release 2 layout wrapped encoded liability into byte vector with length prefix
+        let typed = (0u64, &liability).encoded_size();
+        let wrapped = (0u64, liability.encode()).encoded_size();
+        let size = liability.encoded_size();
+        assert!(size >= 64 && size < 16384);
+        assert_eq!(wrapped - typed, Compact::<u32>::compact_len(&(size as u32)));
+        assert_eq!(wrapped - typed, 2);
+    }
+
+    #[test]
+    fn test_length_bounds() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = vec![0u8; 4_100];
+            let (alice, alice_proof) = get_params_proof("//Alice", &technics, &());
+            let (bob, bob_proof) = get_params_proof("//Bob", &technics, &());
+            assert_err!(
+                Liability::create(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    alice.clone(),
+                    bob,
+                    alice_proof.clone(),
+                    bob_proof,
+                ),
+                Error::<Runtime>::TechnicsTooLong
+            );
+            assert_err!(
+                Liability::demand(Origin::signed(alice), technics, (), 1, 5, alice_proof),
+                Error::<Runtime>::TechnicsTooLong
+            );
+
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (alice, alice_proof) = get_params_proof("//Alice", &technics, &());
+            let (bob, bob_proof) = get_params_proof("//Bob", &technics, &());
+            assert_ok!(Liability::create(
+                Origin::none(),
+                technics,
+                (),
+                alice,
+                bob,
+                alice_proof,
+                bob_proof,
+            ));
+            let report = vec![0u8; 4_100];
+            let proof = get_report_proof("//Bob", &0, &report);
+            assert_err_ignore_postinfo!(
+                Liability::finalize(Origin::none(), 0, report, proof),
+                Error::<Runtime>::ReportTooLong
+            );
+        })
+    }
+
+    #[test]
+    fn test_bad_report_keeps_record() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (alice, alice_proof) = get_params_proof("//Alice", &technics, &());
+            let (bob, bob_proof) = get_params_proof("//Bob", &technics, &());
+            assert_ok!(Liability::create(
+                Origin::none(),
+                technics,
+                (),
+                alice,
+                bob,
+                alice_proof,
+                bob_proof,
+            ));
+            let record = Liability::liability_record(0).unwrap();
+            assert_eq!(record.state, RecordState::Open);
+            assert_eq!(record.created_at, 1);
+
+            let report = b"report".to_vec();
+            assert_err_ignore_postinfo!(
+                Liability::finalize(
+                    Origin::none(),
+                    0,
+                    report.clone(),
+                    get_report_proof("//Alice", &0, &report),
+                ),
+                Error::<Runtime>::BadReportProof
+            );
+            assert_eq!(Liability::liability_record(0), Some(record));
+        });
+    }
+
+    #[test]
+    fn test_report_preimage() {
+        use frame_support::unsigned::ValidateUnsigned;
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (alice, alice_proof) = get_params_proof("//Alice", &technics, &());
+            let (bob, bob_proof) = get_params_proof("//Bob", &technics, &());
+            for _ in 0..2 {
+                assert_ok!(Liability::create(
+                    Origin::none(),
+                    technics.clone(),
+                    (),
+                    alice.clone(),
+                    bob.clone(),
+                    alice_proof.clone(),
+                    bob_proof.clone(),
+                ));
+            }
+
+            // Report of the first liability is sent with finalize
+            let report = b"report".to_vec();
+            let hash = report_hash(&report);
+            let proof = get_report_proof("//Bob", &0, &report);
+            assert_ok!(Liability::finalize(
+                Origin::none(),
+                0,
+                report.clone(),
+                proof
+            ));
+            assert_eq!(Liability::report_hash(0), Some(hash));
+            assert_eq!(Liability::report_of(0), Some(report.clone()));
+            let preimage = Liability::report_preimage(hash).unwrap();
+            assert_eq!(preimage.depositor, None);
+            assert_eq!(preimage.expires_at, 6);
+
+            // Report of the second liability is noted before finalize by hash
+            let noted = b"noted report".to_vec();
+            let noted_hash = report_hash(&noted);
+            let proof = get_report_proof("//Bob", &1, &noted);
+            assert_err_ignore_postinfo!(
+                Liability::finalize_noted(Origin::none(), 1, noted_hash, proof.clone()),
+                Error::<Runtime>::UnknownPreimage
+            );
+            System::set_block_number(3);
+            assert_ok!(Liability::note_report_preimage(
+                Origin::signed(alice.clone()),
+                noted.clone()
+            ));
+            assert_eq!(Balances::reserved_balance(&alice), 20);
+            assert_err!(
+                Liability::note_report_preimage(Origin::signed(alice.clone()), noted.clone()),
+                Error::<Runtime>::PreimageExists
+            );
+            assert_eq!(
+                Liability::validate_unsigned(
+                    TransactionSource::External,
+                    &Call::finalize_noted(1, noted_hash, proof.clone())
+                )
+                .map(|v| v.provides),
+                Ok(vec![(FINALIZE_TAG, 1u64).encode()])
+            );
+
+            System::set_block_number(4);
+            assert_ok!(Liability::finalize_noted(
+                Origin::none(),
+                1,
+                noted_hash,
+                proof
+            ));
+            assert_eq!(Liability::report_of(1), Some(noted.clone()));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::NewReport(Compact(1), noted_hash, 4))
+            );
+            // Accepted report extends challenge window of noted preimage
+            assert_eq!(
+                Liability::report_preimage(noted_hash).unwrap().expires_at,
+                9
+            );
+
+            // Preimages are kept during challenge window
+            assert_err!(
+                Liability::prune_report_preimage(Origin::signed(bob.clone()), hash),
+                Error::<Runtime>::PreimageNotExpired
+            );
+            System::set_block_number(7);
+            assert_ok!(Liability::prune_report_preimage(
+                Origin::signed(bob.clone()),
+                hash
+            ));
+            assert_eq!(Liability::report_hash(0), Some(hash));
+            assert_eq!(Liability::report_of(0), None);
+            assert_eq!(Liability::report_info(0), None);
+            assert_err!(
+                Liability::prune_report_preimage(Origin::signed(bob.clone()), hash),
+                Error::<Runtime>::UnknownPreimage
+            );
+
+            // Deposit is returned to depositor whoever prunes preimage
+            System::set_block_number(10);
+            assert_ok!(Liability::prune_report_preimage(
+                Origin::signed(bob.clone()),
+                noted_hash
+            ));
+            assert_eq!(Balances::reserved_balance(&alice), 0);
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::ReportPreimagePruned(noted_hash))
+            );
+
+            // Pruned report is revealed again by noting it
+            assert_ok!(Liability::note_report_preimage(
+                Origin::signed(bob),
+                report.clone()
+            ));
+            assert_eq!(Liability::report_of(0), Some(report));
+        });
+    }
+
+    #[test]
+    fn test_report_retention() {
+        new_test_ext().execute_with(|| {
+            let config = MarketConfig {
+                challenge_window: 2,
+                report_retention: 6,
+                ..Liability::default_market_config()
+            };
+            assert_ok!(Liability::create_market(Origin::root(), 0, config));
+            create_liabilities(2);
+            let charlie = account("//Charlie");
+            let reports = [b"kept0".to_vec(), b"kept1".to_vec()];
+            for (index, report) in reports.iter().enumerate() {
+                let index = index as u64;
+                assert_ok!(Liability::finalize(
+                    Origin::none(),
+                    index,
+                    report.clone(),
+                    get_report_proof("//Bob", &index, report),
+                ));
+            }
+            let noted = [b"noted0".to_vec(), b"noted1".to_vec()];
+            for report in noted.iter() {
+                assert_ok!(Liability::note_report_preimage(
+                    Origin::signed(charlie.clone()),
+                    report.clone()
+                ));
+            }
+            assert_eq!(Balances::reserved_balance(&charlie), 40);
+
+            // Accepted reports are retained by market after challenge window
+            let hashes: Vec<_> = reports.iter().map(report_hash).collect();
+            assert_eq!(Liability::report_preimage(hashes[0]).unwrap().expires_at, 8);
+            System::set_block_number(5);
+            assert_err!(
+                Liability::prune_report_preimage(Origin::signed(charlie.clone()), hashes[0]),
+                Error::<Runtime>::PreimageNotExpired
+            );
+
+            // Idle weight prunes expired preimages item by item, deposits are returned
+            let item_weight = <() as WeightInfo>::prune_report_preimage();
+            System::set_block_number(8);
+            assert_eq!(Liability::on_idle(8, item_weight), item_weight);
+            assert_eq!(Balances::reserved_balance(&charlie), 20);
+            assert_eq!(Liability::preimages_pruned_until(), 7);
+            assert!(Liability::on_idle(8, Weight::max_value()) > 0);
+            assert_eq!(Balances::reserved_balance(&charlie), 0);
+            assert_eq!(Liability::preimages_pruned_until(), 8);
+            for report in noted.iter() {
+                assert!(Liability::report_preimage(report_hash(report)).is_none());
+            }
+            assert!(Liability::report_preimage(hashes[0]).is_some());
+
+            // Nothing is pruned without spare weight
+            System::set_block_number(9);
+            assert_eq!(Liability::on_idle(9, 0), 0);
+            assert_eq!(Liability::on_idle(9, item_weight - 1), 0);
+            assert!(Liability::report_preimage(hashes[1]).is_some());
+            assert_eq!(Liability::preimages_pruned_until(), 8);
+
+            // Manually pruned preimage is skipped, others are pruned the same way
+            assert_ok!(Liability::prune_report_preimage(
+                Origin::signed(charlie.clone()),
+                hashes[0]
+            ));
+            assert_eq!(Liability::report_hash(1), Some(hashes[1]));
+            System::reset_events();
+            Liability::on_idle(9, Weight::max_value());
+            assert_eq!(
+                System::events()
+                    .into_iter()
+                    .map(|record| record.event)
+                    .collect::<Vec<_>>(),
+                vec![
+                    MetaEvent::liability(RawEvent::ReportPreimagePruned(hashes[1])),
+                    MetaEvent::liability(RawEvent::LiabilityPruned(Compact(1))),
+                    MetaEvent::liability(RawEvent::LiabilityPruned(Compact(0))),
+                ]
+            );
+            assert_eq!(Liability::preimages_pruned_until(), 9);
+            assert_eq!(Liability::liabilities_pruned_until(), 9);
+            assert_eq!(Liability::report_hash(1), None);
+            assert_eq!(Liability::tombstones(), vec![(0, 2)]);
+        })
+    }
+
+    #[test]
+    fn test_retention_prune() {
+        let alice = account("//Alice");
+        let prune_with = |auto: bool| {
+            new_test_ext().execute_with(|| {
+                let config = MarketConfig {
+                    report_retention: 3,
+                    ..Liability::default_market_config()
+                };
+                assert_ok!(Liability::create_market(Origin::root(), 0, config));
+                create_liabilities(3);
+                assert_ok!(Liability::settle(0, true));
+                assert_ok!(Liability::settle(1, false));
+                assert_eq!(<PrunableAt<Runtime>>::get(6), vec![0, 1]);
+                <ZeroValueDeposits<Runtime>>::insert(0, 5);
+                assert_ok!(NamedReserve::<Runtime>::reserve_named(
+                    &RESERVE_ID,
+                    &alice,
+                    5
+                ));
+                let reserved = Balances::reserved_balance(&alice);
+
+                // Liabilities are kept during retention and without spare weight
+                let item_weight = <() as WeightInfo>::prune_liability();
+                System::set_block_number(6);
+                assert_eq!(Liability::on_idle(6, Weight::max_value()), 0);
+                assert_eq!(Liability::liabilities_pruned_until(), 6);
+                System::set_block_number(7);
+                assert_eq!(Liability::on_idle(7, item_weight - 1), 0);
+                assert!(Liability::liability_record(1).is_some());
+
+                System::reset_events();
+                if auto {
+                    assert_eq!(Liability::on_idle(7, item_weight), item_weight);
+                    assert!(Liability::liability_record(0).is_some());
+                    assert!(Liability::on_idle(7, Weight::max_value()) > 0);
+                    assert_eq!(Liability::liabilities_pruned_until(), 7);
+                } else {
+                    assert_ok!(Liability::prune_liability(Origin::root(), 1));
+                    assert_ok!(Liability::prune_liability(Origin::root(), 0));
+                }
+                assert_eq!(Balances::reserved_balance(&alice), reserved - 5);
+                assert!(Liability::liability_record(2).is_some());
+                assert_ok!(Liability::try_state());
+                (
+                    System::events()
+                        .into_iter()
+                        .map(|record| record.event)
+                        .collect::<Vec<_>>(),
+                    Liability::tombstones(),
+                    Balances::reserved_balance(&alice),
+                )
+            })
+        };
+
+        // Automatic pruning emits the same events and refunds the same deposits
+        let auto = prune_with(true);
+        assert_eq!(
+            auto.0,
+            vec![
+                MetaEvent::liability(RawEvent::LiabilityPruned(Compact(1))),
+                MetaEvent::liability(RawEvent::LiabilityPruned(Compact(0))),
+            ]
+        );
+        assert_eq!(auto.1, vec![(0, 2)]);
+        assert_eq!(auto, prune_with(false));
+    }
+
+    #[test]
+    fn test_quarantine() {
+        use frame_support::unsigned::ValidateUnsigned;
+
+        new_test_ext().execute_with(|| {
+            create_liabilities(2);
+            let (alice, bob) = (account("//Alice"), account("//Bob"));
+            let resolve = |index, success| {
+                Liability::force_resolve(
+                    Origin::root(),
+                    index,
+                    vec![1],
+                    (),
+                    alice.clone(),
+                    bob.clone(),
+                    success,
+                )
+            };
+            assert_err!(resolve(0, true), Error::<Runtime>::NotQuarantined);
+
+            // Record of incompatible type is quarantined once instead of finalized
+            sp_io::storage::set(&report_storage_key(&0u64), &[0xff, 0x00, 0x01]);
+            System::set_block_number(10);
+            assert_err_ignore_postinfo!(
+                Liability::force_finalize(Origin::root(), 0, true),
+                Error::<Runtime>::LiabilityDecodeFailure
+            );
+            assert!(Liability::is_quarantined(0));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::CorruptEntry(Compact(0)))
+            );
+            let events = System::events().len();
+            let report = vec![1];
+            let proof = get_report_proof("//Bob", &0, &report);
+            assert_eq!(
+                Liability::validate_unsigned(
+                    TransactionSource::External,
+                    &Call::finalize(0, report.clone(), proof.clone())
+                ),
+                ValidityError::UnknownLiability.into()
+            );
+            assert_err_ignore_postinfo!(
+                Liability::finalize(Origin::none(), 0, report, proof),
+                Error::<Runtime>::LiabilityDecodeFailure
+            );
+            assert_eq!(System::events().len(), events);
+            assert!(!Liability::is_quarantined(1));
+
+            // Governance settles it with restored agreement
+            assert_err!(
+                Liability::force_resolve(
+                    Origin::signed(alice.clone()),
+                    0,
+                    vec![1],
+                    (),
+                    alice.clone(),
+                    bob.clone(),
+                    true
+                ),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_err!(resolve(1, true), Error::<Runtime>::NotQuarantined);
+            assert_ok!(resolve(0, true));
+            assert!(!Liability::is_quarantined(0));
+            assert_eq!(
+                Liability::liability_record(0).map(|record| (record.state, record.created_at)),
+                Some((RecordState::Forced(true), 0))
+            );
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::ForceFinalized(Compact(0), true, 10))
+            );
+            assert_err!(resolve(0, true), Error::<Runtime>::NotQuarantined);
+        })
+    }
+
+    #[test]
+    fn test_prune_liability() {
+        use frame_support::unsigned::ValidateUnsigned;
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let (alice, bob) = (account("//Alice"), account("//Bob"));
+            let issue = || Liability::issue(vec![], (), alice.clone(), bob.clone());
+
+            // Only finalized liabilities are pruned by governance
+            assert_eq!(issue(), Ok(0));
+            assert_err!(
+                Liability::prune_liability(Origin::signed(alice.clone()), 0),
+                sp_runtime::DispatchError::BadOrigin
+            );
+            assert_err!(
+                Liability::prune_liability(Origin::root(), 0),
+                Error::<Runtime>::NotFinalized
+            );
+            assert_err!(
+                Liability::prune_liability(Origin::root(), 1),
+                Error::<Runtime>::UnknownLiability
+            );
+            assert_ok!(Liability::settle(0, true));
+            assert_ok!(Liability::prune_liability(Origin::root(), 0));
+            assert_eq!(
+                System::events().last().unwrap().event,
+                MetaEvent::liability(RawEvent::LiabilityPruned(Compact(0)))
+            );
+            assert!(Liability::liability_record(0).is_none());
+            assert!(Liability::history_of(0).is_empty());
+            assert_eq!(Liability::tombstones(), vec![(0, 1)]);
+
+            // Calls of pruned liability tell it apart from unknown one
+            let report = vec![1];
+            let proof = get_report_proof("//Bob", &0, &report);
+            assert_eq!(
+                Liability::validate_unsigned(
+                    TransactionSource::External,
+                    &Call::finalize(0, report.clone(), proof.clone())
+                ),
+                ValidityError::Pruned.into()
+            );
+            assert_err_ignore_postinfo!(
+                Liability::finalize(Origin::none(), 0, report, proof),
+                Error::<Runtime>::Pruned
+            );
+            assert_err!(
+                Liability::prune_liability(Origin::root(), 0),
+                Error::<Runtime>::Pruned
+            );
+            assert_err!(
+                Jury::open_dispute(Origin::signed(alice.clone()), 0, not_delivered()),
+                Error::<Runtime>::Pruned
+            );
+
+            // Allocator never hands out pruned index, whatever order indices are pruned in
+            let mut pruned = std::collections::BTreeSet::new();
+            pruned.insert(0);
+            let mut seed = [0; 32];
+            for round in 0..300u32 {
+                let latest = Liability::latest_index();
+                let index = issue().unwrap();
+                assert_eq!(index, latest);
+                assert!(!Liability::is_pruned(index));
+                assert_ok!(Liability::settle(index, round % 2 == 0));
+
+                seed = sp_io::hashing::blake2_256(&(seed, round).encode());
+                let target = u64::decode(&mut &seed[..]).unwrap() % (index + 1);
+                if pruned.insert(target) {
+                    assert_ok!(Liability::prune_liability(Origin::root(), target));
+                } else {
+                    assert_err!(
+                        Liability::prune_liability(Origin::root(), target),
+                        Error::<Runtime>::Pruned
+                    );
+                }
+            }
+            let latest = Liability::latest_index();
+            assert!(pruned.len() > 100 && (pruned.len() as u64) < latest);
+            for index in 0..latest {
+                assert_eq!(Liability::is_pruned(index), pruned.contains(&index));
+                assert_eq!(
+                    Liability::liability_record(index).is_none(),
+                    pruned.contains(&index)
+                );
+            }
+            assert!(!Liability::is_pruned(latest));
+
+            // Ranges are merged, each range is a run of pruned indices
+            let tombstones = Liability::tombstones();
+            assert!(tombstones
+                .windows(2)
+                .all(|ranges| ranges[0].1 < ranges[1].0));
+            assert_eq!(
+                tombstones
+                    .iter()
+                    .map(|(start, end)| end - start)
+                    .sum::<u64>(),
+                pruned.len() as u64
+            );
+            assert_ok!(Liability::try_state());
+
+            // Per-index entries of pruned liability are removed, its id isn't taken again
+            let charlie = account("//Charlie");
+            let prefs = AccountPrefs {
+                arbitrator: None,
+                payout: Some(charlie.clone()),
+                topic: None,
+            };
+            assert_ok!(Liability::set_preferences(
+                Origin::signed(bob.clone()),
+                prefs
+            ));
+            let liability =
+                <Runtime as Trait>::Liability::new(vec![7], (), alice.clone(), bob.clone());
+            let index = Liability::start_liability(liability, vec![Tag::Delivery]).unwrap();
+            let id = Liability::id_of(index).unwrap();
+            assert_eq!(Liability::payout_of(index), Some(charlie));
+            assert_eq!(Liability::by_tag(Tag::Delivery), vec![index]);
+            assert_ok!(Liability::settle(index, true));
+            assert_ok!(Liability::prune_liability(Origin::root(), index));
+            assert!(!<AccountLiabilities<Runtime>>::contains_key(&alice, index));
+            assert!(!<AccountLiabilities<Runtime>>::contains_key(&bob, index));
+            assert!(Liability::tags_of(index).is_empty());
+            assert!(!<ByTag<Runtime>>::contains_key(Tag::Delivery));
+            assert_eq!(Liability::id_of(index), None);
+            assert_eq!(Liability::index_of_id(id), Some(index));
+            assert_eq!(Liability::payout_of(index), None);
+            assert!(!<LiabilityMarket<Runtime>>::contains_key(index));
+            assert_ok!(Liability::try_state());
+        })
+    }
+
+    /// Charge value scaled fee of runtime call, returns charged amount.
+    fn charge_value_fee(
+        who: &AccountId,
+        call: &OuterCall,
+    ) -> Result<u128, TransactionValidityError> {
+        use sp_runtime::traits::SignedExtension;
+
+        let extension = fee::ChargeLiabilityFee::<Runtime>::new();
+        extension.validate(who, call, &Default::default(), 10)?;
+        let free = Balances::free_balance(who);
+        extension.pre_dispatch(who, call, &Default::default(), 10)?;
+        Ok(free - Balances::free_balance(who))
+    }
+
+    #[test]
+    fn test_value_fee() {
+        new_test_ext().execute_with(|| {
+            create_liabilities(1);
+            let (alice, dave) = (account("//Alice"), account("//Dave"));
+            let technics = vec![2];
+            let (promisor, promisor_proof) = get_params_proof("//Bob", &technics, &());
+            let create = OuterCall::Liability(Call::create_remote(
+                7,
+                technics,
+                (),
+                promisor,
+                promisor_proof,
+            ));
+            let report = b"model".to_vec();
+            let finalize = |index| {
+                OuterCall::Liability(Call::finalize_relayed(
+                    index,
+                    report.clone(),
+                    get_report_proof("//Bob", &index, &report),
+                ))
+            };
+
+            // Non-monetary economics is charged by standard fee only
+            assert_eq!(charge_value_fee(&alice, &create), Ok(0));
+            assert_eq!(charge_value_fee(&alice, &finalize(0)), Ok(0));
+            assert_eq!(charge_value_fee(&dave, &create), Ok(0));
+
+            // Micro-jobs pay the floor, expensive jobs pay the share up to the cap
+            let tiers = [
+                (0, 5),
+                (20, 5),
+                (50, 5),
+                (200, 20),
+                (480, 48),
+                (500, 50),
+                (5_000, 50),
+                (u128::max_value(), 50),
+            ];
+            let mut charged = 0;
+            for (value, fee) in tiers.iter() {
+                ECONOMICS_VALUE.with(|v| *v.borrow_mut() = Some(*value));
+                assert_eq!(charge_value_fee(&alice, &create), Ok(*fee));
+                charged += fee;
+            }
+            assert!(tiers.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+            assert_eq!(Balances::free_balance(&alice), 1000 - charged);
+            assert_eq!(Balances::free_balance(&account("//Treasury")), charged);
+
+            // Finalization is charged by value of stored liability, unknown one by standard fee
+            ECONOMICS_VALUE.with(|v| *v.borrow_mut() = Some(200));
+            assert_eq!(charge_value_fee(&alice, &finalize(0)), Ok(20));
+            assert_eq!(charge_value_fee(&alice, &finalize(1)), Ok(0));
+            assert_eq!(
+                charge_value_fee(&alice, &OuterCall::Liability(Call::prune_liability(0))),
+                Ok(0)
+            );
+
+            // Fee is withdrawn keeping account alive
+            assert_eq!(
+                charge_value_fee(&dave, &create),
+                Err(InvalidTransaction::Payment.into())
+            );
+            Balances::make_free_balance_be(&dave, 20);
+            assert_eq!(
+                charge_value_fee(&dave, &create),
+                Err(InvalidTransaction::Payment.into())
+            );
+            Balances::make_free_balance_be(&dave, 21);
+            assert_eq!(charge_value_fee(&dave, &create), Ok(20));
+            assert_eq!(Balances::free_balance(&dave), 1);
+        })
+    }
+
+    #[test]
+    fn test_missing_keys() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let report = b"report".to_vec();
+            let finalize = |index| {
+                let proof = get_report_proof("//Bob", &index, &report);
+                Liability::finalize(Origin::none(), index, report.clone(), proof)
+            };
+
+            // Liability that never existed
+            assert_err_ignore_postinfo!(finalize(0), Error::<Runtime>::UnknownLiability);
+            assert_err_ignore_postinfo!(
+                Liability::force_finalize(Origin::root(), 0, false),
+                Error::<Runtime>::UnknownLiability
+            );
+            assert_eq!(Liability::report_hash(0), None);
+            assert_eq!(Liability::report_of(0), None);
+            assert!(!Liability::is_finalized(0));
+
+            // Record that exists but can't be decoded
+            sp_io::storage::set(&report_storage_key(&0u64), &[0xff]);
+            assert_err_ignore_postinfo!(finalize(0), Error::<Runtime>::LiabilityDecodeFailure);
+            assert_err_ignore_postinfo!(
+                Liability::force_finalize(Origin::root(), 0, false),
+                Error::<Runtime>::LiabilityDecodeFailure
+            );
+            sp_io::storage::clear(&report_storage_key(&0u64));
+
+            // Preimage pruned before finalize
+            let (alice, alice_proof) = get_params_proof("//Alice", &technics, &());
+            let (bob, bob_proof) = get_params_proof("//Bob", &technics, &());
+            assert_ok!(Liability::create(
+                Origin::none(),
+                technics,
+                (),
+                alice.clone(),
+                bob.clone(),
+                alice_proof,
+                bob_proof,
+            ));
+            let hash = report_hash(&report);
+            assert_ok!(Liability::note_report_preimage(
+                Origin::signed(bob.clone()),
+                report.clone()
+            ));
+            System::set_block_number(7);
+            assert_ok!(Liability::prune_report_preimage(
+                Origin::signed(alice.clone()),
+                hash
+            ));
+            let proof = get_report_proof("//Bob", &0, &report);
+            assert_err_ignore_postinfo!(
+                Liability::finalize_noted(Origin::none(), 0, hash, proof),
+                Error::<Runtime>::UnknownPreimage
+            );
+            assert_eq!(
+                Liability::liability_record(0).map(|record| record.state),
+                Some(RecordState::Open)
             );
-            assert_eq!(Liability::latest_index(), 0);
 
+            // Admin removals of what was never added
             assert_err!(
-                Liability::create(
-                    Origin::none(),
-                    technics.clone(),
-                    economics.clone(),
+                Liability::remove_promisor(Origin::root(), alice.clone()),
+                Error::<Runtime>::UnapprovedPromisor
+            );
+            assert_err!(
+                Liability::unblacklist(Origin::root(), alice),
+                Error::<Runtime>::NotBlacklisted
+            );
+            assert_err!(
+                Liability::revoke_firmware(Origin::root(), 0, H256::repeat_byte(1)),
+                Error::<Runtime>::FirmwareNotApproved
+            );
+        });
+    }
+
+    #[test]
+    fn test_compact_index_encoding() {
+        let report = b"report".to_vec();
+        let proof = get_report_proof("//Bob", &0, &report);
+        let encoded_len = |index: u64| {
+            let call = Call::<Runtime>::finalize(index, report.clone(), proof.clone());
+            let encoded = call.encode();
+            assert_eq!(Call::<Runtime>::decode(&mut &encoded[..]), Ok(call));
+
+            let event: Event<Runtime> =
+                RawEvent::NewReport(Compact(index), report_hash(&report), 1);
+            (encoded.len(), event.encode().len())
+        };
+
+        // Call variant, index, report and proof
+        let fixed_len = 1 + report.encoded_size() + proof.encoded_size();
+        let (small_call, small_event) = encoded_len(1);
+        assert_eq!(small_call, fixed_len + 1);
+        let (huge_call, huge_event) = encoded_len(u64::max_value());
+        assert_eq!(huge_call, fixed_len + 9);
+        assert_eq!(huge_event - small_event, 8);
+    }
+
+    #[test]
+    fn test_event_size() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = vec![0xab; MaxTechnicsLength::get() as usize - 4];
+            let (promisee, promisee_proof) = get_params_proof("//Alice", &technics, &());
+            let (promisor, promisor_proof) = get_params_proof("//Bob", &technics, &());
+            assert_ok!(Liability::create(
+                Origin::none(),
+                technics.clone(),
+                (),
+                promisee.clone(),
+                promisor.clone(),
+                promisee_proof,
+                promisor_proof
+            ));
+            let report = vec![0xcd; MaxReportLength::get() as usize - 4];
+            let proof = get_report_proof("//Bob", &0, &report);
+            assert_ok!(Liability::finalize(
+                Origin::none(),
+                0,
+                report.clone(),
+                proof
+            ));
+
+            let emitted: Vec<Event<Runtime>> = System::events()
+                .into_iter()
+                .filter_map(|record| match record.event {
+                    MetaEvent::liability(event) => Some(event),
+                    _ => None,
+                })
+                .collect();
+            assert!(emitted
+                .iter()
+                .any(|e| matches!(e, RawEvent::NewLiability(..))));
+            assert!(emitted.iter().any(|e| matches!(e, RawEvent::NewReport(..))));
+            for event in emitted {
+                assert!(event.encoded_size() <= MAX_EVENT_SIZE, "{:?}", event);
+            }
+
+            // Hashes carried by events resolve to stored payloads
+            assert_eq!(
+                Liability::event_payload(0, technics_hash(&technics)),
+                Some(EventPayload::Technics(technics.clone()))
+            );
+            assert_eq!(
+                Liability::event_payload(0, report_hash(&report)),
+                Some(EventPayload::Report(report))
+            );
+            assert_eq!(Liability::event_payload(0, H256::repeat_byte(1)), None);
+            assert_eq!(Liability::event_payload(1, technics_hash(&technics)), None);
+
+            // Every event variant with the largest values of its fields
+            let (index, block, balance) = (u64::max_value(), u64::max_value(), u128::max_value());
+            let hash = H256::repeat_byte(0xff);
+            let counters = LiabilityCounters {
+                created: u64::max_value(),
+                finalized: u64::max_value(),
+                cancelled: u64::max_value(),
+                settled: balance,
+            };
+            let events: Vec<Event<Runtime>> = vec![
+                RawEvent::NewLiability(
+                    Compact(index),
+                    hash,
+                    hash,
+                    (),
                     promisee.clone(),
                     promisor.clone(),
-                    promisee_proof.clone(),
-                    promisee_proof.clone(),
+                    block,
+                    vec![Tag::Delivery, Tag::Sensing, Tag::Compute],
                 ),
-                Error::<Runtime>::BadPromisorProof
+                RawEvent::NewReport(Compact(index), hash, block),
+                RawEvent::EpochStats(counters),
+                RawEvent::PromisorAdded(promisor.clone()),
+                RawEvent::PromisorRemoved(promisor.clone()),
+                RawEvent::AccountBlacklisted(promisor.clone()),
+                RawEvent::AccountUnblacklisted(promisor.clone()),
+                RawEvent::FirmwareApproved(MarketId::max_value(), hash),
+                RawEvent::FirmwareRevoked(MarketId::max_value(), hash),
+                RawEvent::FirmwareAttested(Compact(index), hash),
+                RawEvent::ForceFinalized(Compact(index), true, block),
+                RawEvent::LiabilityFunded(Compact(index)),
+                RawEvent::FundingExpired(Compact(index)),
+                RawEvent::LiabilityCancelled(Compact(index), Some(Party::Promisor), balance),
+                RawEvent::ReportCommitted(Compact(index), hash, block),
+                RawEvent::RevealExpired(Compact(index)),
+                RawEvent::ArrivalClaimed(Compact(index), block),
+                RawEvent::ReportSubmitted(Compact(index), Submitter::Signed(promisee.clone())),
+                RawEvent::MatchCommitted(promisee.clone(), hash),
+                RawEvent::WitnessRegistered(promisee.clone(), balance),
+                RawEvent::WitnessUnregistered(promisee.clone()),
+                RawEvent::WitnessingRequired(Compact(index), u32::max_value()),
+                RawEvent::Notarized(Compact(index), promisee.clone()),
+                RawEvent::ReportSettled(Compact(index), true),
+                RawEvent::ReportOverturned(Compact(index)),
+                RawEvent::ReportUpheld(Compact(index)),
+                RawEvent::LiabilityPruned(Compact(index)),
+                RawEvent::WitnessSlashed(promisee.clone(), balance),
+                RawEvent::OrderPosted(
+                    OrderId::max_value(),
+                    Side::Demand,
+                    MarketId::max_value(),
+                    hash,
+                    (),
+                    u32::max_value(),
+                    balance,
+                    promisee.clone(),
+                    block,
+                ),
+                RawEvent::OrderCancelled(
+                    OrderId::max_value(),
+                    Side::Offer,
+                    MarketId::max_value(),
+                    u32::max_value(),
+                ),
+                RawEvent::Matched(
+                    OrderId::max_value(),
+                    OrderId::max_value(),
+                    Compact(index),
+                    MarketId::max_value(),
+                    balance,
+                    u32::max_value(),
+                    promisee.clone(),
+                ),
+                RawEvent::LighthouseRegistered(MarketId::max_value(), promisee.clone(), balance),
+                RawEvent::LighthouseUnregistered(MarketId::max_value(), promisee.clone()),
+                RawEvent::LighthouseRewarded(OrderId::max_value(), promisee.clone(), balance),
+                RawEvent::MarketFeeSet(MarketId::max_value(), Perbill::one()),
+                RawEvent::MarketCreated(MarketId::max_value()),
+                RawEvent::MarketUpdated(MarketId::max_value()),
+                RawEvent::MatchFeePaid(OrderId::max_value(), promisee.clone(), balance, balance),
+                RawEvent::OrderExpired(
+                    OrderId::max_value(),
+                    Side::Demand,
+                    MarketId::max_value(),
+                    u32::max_value(),
+                    balance,
+                ),
+                RawEvent::OrderBonded(promisee.clone(), balance),
+                RawEvent::BondUnlocking(promisee.clone(), block),
+                RawEvent::OrderUnbonded(promisee.clone(), balance),
+                RawEvent::OrderBreachClaimed(promisee.clone(), promisor.clone(), balance),
+                RawEvent::OversizedLiabilityRejected(Compact(index)),
+                RawEvent::OversizedReportRejected(Compact(index)),
+                RawEvent::RelayBountyPaid(Compact(index), promisee.clone(), balance),
+                RawEvent::RelayBountyRefunded(Compact(index), balance),
+                RawEvent::PoolJoined(
+                    promisor.clone(),
+                    MarketId::max_value(),
+                    OrderId::max_value(),
+                    balance,
+                ),
+                RawEvent::PoolLeft(promisor.clone(), MarketId::max_value()),
+                RawEvent::AssignmentRequested(OrderId::max_value(), block),
+                RawEvent::DemandAssigned(OrderId::max_value(), promisor.clone()),
+                RawEvent::AssignmentFailed(OrderId::max_value()),
+                RawEvent::LiabilityAcknowledged(Compact(index)),
+                RawEvent::LiabilityReclaimed(Compact(index), balance),
+                RawEvent::LiabilityExpired(Compact(index), promisor.clone(), balance),
+                RawEvent::BlindedLiabilityCreated(Compact(index), hash, hash),
+                RawEvent::BlindedLiabilityFinalized(Compact(index), hash),
+                RawEvent::FinalizationLeafAdded(Compact(index), u64::max_value(), hash),
+                RawEvent::PromisorRotated(promisee.clone(), promisor.clone(), true),
+                RawEvent::CorruptEntry(Compact(index)),
+                RawEvent::PreferencesSet(promisor.clone()),
+                RawEvent::PreferencesCleared(promisor.clone()),
+                RawEvent::PayoutRedirected(Compact(index), promisor.clone(), balance),
+                RawEvent::EconomicsAmended(Compact(index), ()),
+                RawEvent::ReportPreimageNoted(hash, promisee),
+                RawEvent::ReportPreimagePruned(hash),
+            ];
+            for event in events {
+                assert!(event.encoded_size() <= MAX_EVENT_SIZE, "{:?}", event);
+            }
+        })
+    }
+
+    #[test]
+    fn test_weight_by_scheme() {
+        use frame_support::weights::GetDispatchInfo;
+        use sp_core::{ecdsa, ed25519};
+        use sp_runtime::MultiSignature;
+
+        let sr_proof = MultiSignature::Sr25519(sr25519::Signature::from_raw([0; 64]));
+        let ed_proof = MultiSignature::Ed25519(ed25519::Signature::from_raw([0; 64]));
+        let ecdsa_proof = MultiSignature::Ecdsa(ecdsa::Signature::from_raw([0; 65]));
+        assert_eq!(sr_proof.scheme(), SignatureScheme::Sr25519);
+        assert_eq!(ed_proof.scheme(), SignatureScheme::Ed25519);
+        assert_eq!(ecdsa_proof.scheme(), SignatureScheme::Ecdsa);
+
+        let alice = account("//Alice");
+        let create = |promisee_proof: &MultiSignature, promisor_proof: &MultiSignature| {
+            Call::<Runtime>::create(
+                vec![0u8; 34],
+                (),
+                alice.clone(),
+                alice.clone(),
+                promisee_proof.clone(),
+                promisor_proof.clone(),
+            )
+            .get_dispatch_info()
+            .weight
+        };
+        // Calls are measured with sr25519 proofs
+        assert_eq!(create(&sr_proof, &sr_proof), CREATE_WEIGHT);
+        assert_eq!(create(&ed_proof, &sr_proof), CREATE_WEIGHT - 5_000_000);
+        assert_eq!(create(&ed_proof, &ecdsa_proof), CREATE_WEIGHT + 5_000_000);
+        assert_eq!(
+            create(&ecdsa_proof, &ecdsa_proof),
+            CREATE_WEIGHT + 20_000_000
+        );
+
+        let finalize = |proof: &MultiSignature| {
+            Call::<Runtime>::finalize(0, b"report".to_vec(), proof.clone())
+                .get_dispatch_info()
+                .weight
+        };
+        assert_eq!(finalize(&sr_proof), FINALIZE_WEIGHT);
+        assert_eq!(finalize(&ecdsa_proof), FINALIZE_WEIGHT + 10_000_000);
+
+        // Rejected proof pays verification of its scheme
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (alice, alice_proof) = get_params_proof("//Alice", &technics, &());
+            let (bob, bob_proof) = get_params_proof("//Bob", &technics, &());
+            assert_ok!(Liability::create(
+                Origin::none(),
+                technics,
+                (),
+                alice,
+                bob,
+                alice_proof,
+                bob_proof,
+            ));
+            let report = b"report".to_vec();
+            let r = report.encoded_size() as u32;
+            let info = Liability::finalize(Origin::none(), 0, report, ecdsa_proof)
+                .unwrap_err()
+                .post_info;
+            assert_eq!(
+                info.actual_weight,
+                Some(<() as WeightInfo>::finalize_bad_proof(r) + 10_000_000)
             );
-            assert_eq!(Liability::latest_index(), 0);
+        });
+    }
 
+    #[test]
+    fn test_finalize_refunds_weight() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (alice, alice_proof) = get_params_proof("//Alice", &technics, &());
+            let (bob, bob_proof) = get_params_proof("//Bob", &technics, &());
             assert_ok!(Liability::create(
                 Origin::none(),
                 technics,
-                economics,
-                promisee,
-                promisor,
-                promisee_proof,
-                promisor_proof
+                (),
+                alice,
+                bob,
+                alice_proof,
+                bob_proof,
             ));
-            assert_eq!(Liability::latest_index(), 1);
-            assert_eq!(Liability::is_finalized(0), false);
 
-            let index = 0;
-            let report = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+            let report = b"report".to_vec();
+            let report_len = report.encoded_size() as u32;
+            let bad_proof = get_report_proof("//Alice", &0, &report);
+            let bad_info = Liability::finalize(Origin::none(), 0, report.clone(), bad_proof)
+                .unwrap_err()
+                .post_info;
+            assert_eq!(
+                bad_info.actual_weight,
+                Some(<() as WeightInfo>::finalize_bad_proof(report_len))
+            );
+
+            let good_proof = get_report_proof("//Bob", &0, &report);
+            let good_info =
+                Liability::finalize(Origin::none(), 0, report.clone(), good_proof.clone()).unwrap();
+            assert_eq!(good_info.actual_weight, None);
+
+            let finalized_info = Liability::finalize(Origin::none(), 0, report, good_proof)
+                .unwrap_err()
+                .post_info;
+            assert_eq!(
+                finalized_info.actual_weight,
+                Some(<Runtime as frame_system::Trait>::DbWeight::get().reads(2))
+            );
+            assert_ne!(finalized_info.actual_weight, bad_info.actual_weight);
+
+            let forced_info = Liability::force_finalize(Origin::root(), 0, true)
+                .unwrap_err()
+                .post_info;
+            assert!(forced_info.actual_weight.unwrap() < <() as WeightInfo>::force_finalize());
+        });
+    }
+
+    #[test]
+    fn test_migrate_expiry_sweep_cursor() {
+        use frame_support::storage::migration::{get_storage_value, put_storage_value};
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(10);
+            put_storage_value(b"Liability", b"ExpiredOrders", &[], vec![3u64, 4]);
+            <OrdersExpiringAt<Runtime>>::insert(10, vec![5]);
+            <StorageVersion>::put(Releases::V7_0_0);
+
+            migration::migrate::<Runtime>();
+            assert_eq!(Liability::storage_version(), STORAGE_VERSION);
+            assert_eq!(
+                get_storage_value::<Vec<u64>>(b"Liability", b"ExpiredOrders", &[]),
+                None
+            );
+            assert_eq!(Liability::orders_expiring_at(10), vec![3, 4, 5]);
+            assert_eq!(Liability::expiry_sweep_cursor(), (10, 0));
+        })
+    }
+
+    #[test]
+    fn test_migrate_report_preimages() {
+        use frame_support::storage::migration::put_storage_value;
+        use migration::legacy::{self, LiabilityRecordV8};
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(10);
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
                 .from_base58()
                 .unwrap();
-            let bad_proof = get_report_proof("//Alice", &index, &report);
-            let good_proof = get_report_proof("//Bob", &index, &report);
+            let (alice, _) = get_params_proof("//Alice", &technics, &());
+            let (bob, _) = get_params_proof("//Bob", &technics, &());
+            let liability = <Runtime as Trait>::Liability::new(technics.clone(), (), alice, bob);
 
-            assert_err!(
-                Liability::finalize(Origin::none(), 0, report.clone(), bad_proof),
-                Error::<Runtime>::BadReportProof
+            // Release 8 layout: report is kept in the record
+            let report = b"report".to_vec();
+            for (index, report) in vec![(0u64, None), (1, Some(report.clone()))] {
+                let record = LiabilityRecordV8 {
+                    state: if report.is_some() {
+                        RecordState::Reported
+                    } else {
+                        RecordState::Open
+                    },
+                    report,
+                    created_at: 1u64,
+                    finalized_at: None,
+                    agreement: liability.clone(),
+                };
+                let hash = legacy::index_hash::<Runtime>(&index);
+                put_storage_value(b"Liability", b"Liabilities", &hash, record);
+            }
+            <LatestIndex<Runtime>>::put(2);
+            <StorageVersion>::put(Releases::V8_0_0);
+
+            migration::migrate::<Runtime>();
+            assert_eq!(Liability::storage_version(), STORAGE_VERSION);
+            assert_eq!(Liability::report_hash(0), None);
+            assert_eq!(Liability::liability(0), Some(liability));
+            let hash = report_hash(&report);
+            assert_eq!(Liability::report_hash(1), Some(hash));
+            assert_eq!(
+                Liability::report_preimage(hash),
+                Some(Preimage {
+                    report,
+                    depositor: None,
+                    expires_at: 15,
+                })
             );
-            assert_eq!(Liability::is_finalized(0), false);
+        })
+    }
 
-            assert_ok!(Liability::finalize(Origin::none(), 0, report, good_proof));
-            assert_eq!(Liability::is_finalized(0), true);
+    #[test]
+    fn test_migrate_named_reserves() {
+        new_test_ext().execute_with(|| {
+            let alice = account("//Alice");
+            let bob = account("//Bob");
+
+            // Release 9 layout: module funds are reserved without identifier
+            assert_ok!(Balances::reserve(&alice, 300));
+            assert_ok!(Liability::bond_orders(Origin::signed(alice.clone()), 50));
+            assert_ok!(Liability::register_lighthouse(
+                Origin::signed(bob.clone()),
+                0,
+                LighthouseMinStake::get()
+            ));
+            <NamedReserves<Runtime>>::remove_prefix(RESERVE_ID);
+            <StorageVersion>::put(Releases::V9_0_0);
+
+            migration::migrate::<Runtime>();
+            assert_eq!(Liability::storage_version(), STORAGE_VERSION);
+            assert_eq!(Liability::named_reserve(RESERVE_ID, &alice), 50);
+            assert_eq!(
+                Liability::named_reserve(RESERVE_ID, &bob),
+                LighthouseMinStake::get()
+            );
+        })
+    }
+
+    #[test]
+    fn test_migrate_market_configs() {
+        use frame_support::{storage::migration::put_storage_value, StorageHasher, Twox64Concat};
+
+        new_test_ext().execute_with(|| {
+            // Release 10 layout: fees of markets are kept in `MarketFee`
+            let key = Twox64Concat::hash(&3u32.encode());
+            put_storage_value(b"Liability", b"MarketFee", &key, Perbill::from_percent(5));
+            <StorageVersion>::put(Releases::V10_0_0);
+
+            migration::migrate::<Runtime>();
+            assert_eq!(Liability::storage_version(), STORAGE_VERSION);
+            assert_eq!(
+                Liability::market_config(3),
+                MarketConfig {
+                    fee: Perbill::from_percent(5),
+                    ..Liability::default_market_config()
+                }
+            );
+            assert!(
+                frame_support::storage::migration::get_storage_value::<Perbill>(
+                    b"Liability",
+                    b"MarketFee",
+                    &key
+                )
+                .is_none()
+            );
+        })
+    }
+
+    #[test]
+    fn test_migrate_cancellation_fees() {
+        use frame_support::{
+            storage::migration::put_storage_value, Blake2_128Concat, StorageHasher, Twox64Concat,
+        };
+        use migration::legacy::MarketConfigV11;
+
+        new_test_ext().execute_with(|| {
+            // Release 11 layout: configurations have no cancellation fee
+            let market = MarketConfigV11::<u128, u64> {
+                fee: Perbill::from_percent(5),
+                order_deposit: 4,
+                challenge_window: 20,
+                force_finalize_age: 2,
+            };
+            let key = Twox64Concat::hash(&3u32.encode());
+            put_storage_value(b"Liability", b"Markets", &key, &market);
+            let key = Blake2_128Concat::hash(&0u64.encode());
+            put_storage_value(b"Liability", b"LiabilityMarket", &key, (3u32, &market));
+            <StorageVersion>::put(Releases::V11_0_0);
+
+            migration::migrate::<Runtime>();
+            assert_eq!(Liability::storage_version(), STORAGE_VERSION);
+            let upgraded = MarketConfig {
+                fee: Perbill::from_percent(5),
+                order_deposit: 4,
+                challenge_window: 20,
+                force_finalize_age: 2,
+                cancellation_fee: CancellationFee::Share(Perbill::zero()),
+                report_retention: 0,
+            };
+            assert_eq!(Liability::market_config(3), upgraded);
+            assert_eq!(<LiabilityMarket<Runtime>>::get(0), Some((3, upgraded)));
+        })
+    }
+
+    #[test]
+    fn test_migrate_report_retention() {
+        use frame_support::{
+            storage::migration::put_storage_value, Blake2_128Concat, StorageHasher, Twox64Concat,
+        };
+        use migration::legacy::MarketConfigV13;
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(5);
+            // Release 13 layout: configurations have no report retention
+            let market = MarketConfigV13::<u128, u64> {
+                fee: Perbill::from_percent(5),
+                order_deposit: 4,
+                challenge_window: 20,
+                force_finalize_age: 2,
+                cancellation_fee: CancellationFee::Fixed(3),
+            };
+            let key = Twox64Concat::hash(&3u32.encode());
+            put_storage_value(b"Liability", b"Markets", &key, &market);
+            let key = Blake2_128Concat::hash(&0u64.encode());
+            put_storage_value(b"Liability", b"LiabilityMarket", &key, (3u32, &market));
+            let (expired, kept) = (H256::repeat_byte(1), H256::repeat_byte(2));
+            for (hash, expires_at) in [(expired, 3), (kept, 8)].iter() {
+                let preimage = Preimage {
+                    report: b"report".to_vec(),
+                    depositor: None,
+                    expires_at: *expires_at,
+                };
+                <ReportPreimage<Runtime>>::insert(hash, preimage);
+            }
+            <StorageVersion>::put(Releases::V13_0_0);
+
+            migration::migrate::<Runtime>();
+            assert_eq!(Liability::storage_version(), STORAGE_VERSION);
+            let upgraded = MarketConfig {
+                fee: Perbill::from_percent(5),
+                order_deposit: 4,
+                challenge_window: 20,
+                force_finalize_age: 2,
+                cancellation_fee: CancellationFee::Fixed(3),
+                report_retention: 0,
+            };
+            assert_eq!(Liability::market_config(3), upgraded);
+            assert_eq!(<LiabilityMarket<Runtime>>::get(0), Some((3, upgraded)));
+
+            // Stored preimages are queued for automatic pruning from the upgrade block
+            assert_eq!(Liability::preimages_pruned_until(), 5);
+            assert_eq!(<PreimagesExpiringAt<Runtime>>::get(5), vec![expired]);
+            assert_eq!(<PreimagesExpiringAt<Runtime>>::get(8), vec![kept]);
+            Liability::on_idle(6, Weight::max_value());
+            assert!(Liability::report_preimage(expired).is_none());
+            assert!(Liability::report_preimage(kept).is_some());
+        })
+    }
+
+    #[test]
+    fn test_migrate_sweep_cursors() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1_000);
+            <StorageVersion>::put(Releases::V14_0_0);
+
+            // Sweeps aren't walking empty blocks from genesis
+            migration::migrate::<Runtime>();
+            assert_eq!(Liability::storage_version(), STORAGE_VERSION);
+            assert_eq!(Liability::funding_sweep_cursor(), (1_000, 0));
+            assert_eq!(Liability::reveal_sweep_cursor(), (1_000, 0));
+            assert_eq!(Liability::match_commit_sweep_cursor(), (1_000, 0));
+            assert_eq!(Liability::settlement_sweep_cursor(), (1_000, 0));
+            assert_eq!(Liability::assignment_sweep_cursor(), (1_000, 0));
+        })
+    }
+
+    #[test]
+    fn test_migrate_liability_pruning() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1_000);
+            <StorageVersion>::put(Releases::V15_0_0);
+
+            // Liabilities finalized before release aren't queued, pruning starts from now
+            migration::migrate::<Runtime>();
+            assert_eq!(Liability::storage_version(), STORAGE_VERSION);
+            assert_eq!(Liability::liabilities_pruned_until(), 1_000);
+        })
+    }
+
+    /// Store liability created at genesis in release 6 layout.
+    fn store_legacy_liability(index: u64, liability: &<Runtime as Trait>::Liability) {
+        use frame_support::storage::migration::put_storage_value;
+        use migration::legacy::{self, CREATED_AT, GLOBAL_INDEX_OF, LIABILITY_OF};
+
+        let promisor = liability.promisor().clone();
+        let local_index = Liability::promisor_count(&promisor);
+        <PromisorCount<Runtime>>::insert(&promisor, local_index + 1);
+        let hash = legacy::liability_hash::<Runtime>(&promisor, &local_index);
+        put_storage_value(b"Liability", LIABILITY_OF, &hash, (index, liability));
+        legacy::put::<Runtime, _>(GLOBAL_INDEX_OF, &index, (promisor.clone(), local_index));
+        legacy::put::<Runtime, _>(CREATED_AT, &index, 0u64);
+        <AccountLiabilities<Runtime>>::insert(liability.promisee(), index, ());
+        <AccountLiabilities<Runtime>>::insert(&promisor, index, ());
+        <LatestIndex<Runtime>>::put(index + 1);
+    }
+
+    #[test]
+    fn test_migrate_rejects_oversized() {
+        use frame_support::traits::OnInitialize;
+        use migration::legacy::{self, IS_FINALIZED, REPORT_OF};
+
+        new_test_ext().execute_with(|| {
+            System::set_block_number(1);
+            let liability = |technics| {
+                let (alice, _) = get_params_proof("//Alice", &technics, &());
+                let (bob, _) = get_params_proof("//Bob", &technics, &());
+                <Runtime as Trait>::Liability::new(technics, (), alice, bob)
+            };
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+
+            // Release 5 values weren't bounded
+            store_legacy_liability(0, &liability(vec![0u8; 4_100]));
+            store_legacy_liability(1, &liability(technics.clone()));
+            legacy::put::<Runtime, _>(REPORT_OF, &1, vec![0u8; 4_100]);
+            legacy::put::<Runtime, _>(IS_FINALIZED, &1, true);
+            <StorageVersion>::put(Releases::V5_0_0);
+
+            migration::migrate::<Runtime>();
+            assert_eq!(Liability::storage_version(), STORAGE_VERSION);
+            assert_eq!(Liability::liability_record(0), None);
+            let (alice, _) = get_params_proof("//Alice", &technics, &());
+            assert_eq!(
+                Liability::account_liability_list(&alice, 0, 10).items.len(),
+                1
+            );
+            assert_eq!(Liability::counters().cancelled, 1);
+            assert_eq!(Liability::liability(1), Some(liability(technics)));
+            assert!(Liability::is_finalized(1));
+            assert_eq!(Liability::report_of(1), None);
+
+            // Rejections are reported in the next block
+            System::set_block_number(2);
+            Liability::on_initialize(2);
+            let events: Vec<_> = System::events().into_iter().map(|r| r.event).collect();
+            let liability_rejected = RawEvent::OversizedLiabilityRejected(Compact(0));
+            let report_rejected = RawEvent::OversizedReportRejected(Compact(1));
+            assert!(events.contains(&MetaEvent::liability(liability_rejected)));
+            assert!(events.contains(&MetaEvent::liability(report_rejected)));
+            assert!(<OversizedRejections<Runtime>>::get().is_empty());
+        })
+    }
+
+    #[test]
+    fn test_migrate_from_v1() {
+        use frame_support::{
+            storage::migration::put_storage_value, Blake2_128Concat, StorageHasher,
+        };
+
+        new_test_ext().execute_with(|| {
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (alice, _) = get_params_proof("//Alice", &technics, &());
+            let (bob, _) = get_params_proof("//Bob", &technics, &());
+            let liability = <Runtime as Trait>::Liability::new(
+                technics.clone(),
+                (),
+                alice.clone(),
+                bob.clone(),
+            );
+
+            // Release 1 layout: liability and report encoded into byte vectors by global index
+            for index in 0..2u64 {
+                put_storage_value(
+                    b"Liability",
+                    b"LiabilityOf",
+                    &Blake2_128Concat::hash(&index.encode()),
+                    liability.encode(),
+                );
+            }
+            put_storage_value(
+                b"Liability",
+                b"ReportOf",
+                &Blake2_128Concat::hash(&0u64.encode()),
+                technics.encode(),
+            );
+            migration::legacy::put::<Runtime, _>(migration::legacy::IS_FINALIZED, &0, true);
+            <LatestIndex<Runtime>>::put(2);
+            assert_eq!(Liability::storage_version(), Releases::V1_0_0);
+
+            migration::migrate::<Runtime>();
+            assert_eq!(Liability::storage_version(), STORAGE_VERSION);
+            assert_eq!(Liability::promisor_count(&bob), 2);
+            assert_eq!(Liability::promisor_liability(&bob, 1), Some(1));
+            assert_eq!(
+                Liability::liability(0).map(|l| l.encode()),
+                Some(liability.encode())
+            );
+            assert_eq!(Liability::liability(1), Some(liability));
+            assert_eq!(Liability::report_of(0), Some(technics));
+            assert_eq!(Liability::counters().created, 2);
+            assert_eq!(Liability::counters().finalized, 1);
+            assert_eq!(Liability::counters().settled, 10);
+            assert_eq!(Liability::created_at(1), 0);
+            assert_eq!(
+                Liability::account_liability_list(&alice, 0, 10).items.len(),
+                2
+            );
+
+            // Second run is noop
+            migration::migrate::<Runtime>();
+            assert_eq!(Liability::promisor_count(&bob), 2);
+            assert_eq!(Liability::liability_info(0).unwrap().promisee, alice);
+        })
+    }
+
+    #[test]
+    fn test_migrate_from_v2() {
+        use frame_support::{
+            storage::migration::put_storage_value, Blake2_128Concat, StorageHasher, Twox64Concat,
+        };
+        use migration::legacy::{self, GLOBAL_INDEX_OF};
+
+        new_test_ext().execute_with(|| {
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (alice, _) = get_params_proof("//Alice", &technics, &());
+            let (bob, _) = get_params_proof("//Bob", &technics, &());
+            let liability = <Runtime as Trait>::Liability::new(
+                technics.clone(),
+                (),
+                alice.clone(),
+                bob.clone(),
+            );
+
+            // Release 2 layout: global index and encoded liability by promisor local index
+            let mut key = Blake2_128Concat::hash(&bob.encode());
+            key.extend(Twox64Concat::hash(&0u64.encode()));
+            put_storage_value(
+                b"Liability",
+                b"LiabilityOf",
+                &key,
+                (0u64, liability.encode()),
+            );
+            let mut key = Blake2_128Concat::hash(&bob.encode());
+            key.extend(Twox64Concat::hash(&1u64.encode()));
+            put_storage_value(b"Liability", b"LiabilityOf", &key, (1u64, vec![0xff_u8]));
+            legacy::put::<Runtime, _>(GLOBAL_INDEX_OF, &0, (bob.clone(), 0u64));
+            legacy::put::<Runtime, _>(GLOBAL_INDEX_OF, &1, (bob.clone(), 1u64));
+            <PromisorCount<Runtime>>::insert(&bob, 2);
+            <LatestIndex<Runtime>>::put(2);
+            <StorageVersion>::put(Releases::V2_0_0);
+
+            migration::migrate::<Runtime>();
+            assert_eq!(Liability::storage_version(), STORAGE_VERSION);
+            assert_eq!(Liability::liability(0), Some(liability));
+            // Broken value removed
+            assert_eq!(Liability::promisor_liability(&bob, 1), None);
+            assert_eq!(Liability::liability_info(1), None);
+        })
+    }
+
+    #[test]
+    fn test_block_numbers_backfill() {
+        use migration::legacy::{self, CREATED_AT, FINALIZED_AT, IS_FINALIZED};
+
+        new_test_ext().execute_with(|| {
+            let block = |item, index| legacy::get::<Runtime, u64>(item, &index);
+
+            // Liabilities created before block numbers was tracked
+            legacy::put::<Runtime, _>(IS_FINALIZED, &0, true);
+            <LatestIndex<Runtime>>::put(2);
+
+            migration::backfill_block_numbers::<Runtime>();
+            assert_eq!(block(CREATED_AT, 0), Some(0));
+            assert_eq!(block(CREATED_AT, 1), Some(0));
+            assert_eq!(block(FINALIZED_AT, 0), Some(0));
+            assert_eq!(block(FINALIZED_AT, 1), None);
+
+            // Second run is noop
+            legacy::remove(CREATED_AT, &legacy::index_hash::<Runtime>(&1));
+            migration::backfill_block_numbers::<Runtime>();
+            assert_eq!(block(CREATED_AT, 1), None);
+        })
+    }
+
+    #[test]
+    fn test_migrate_merges_records() {
+        use migration::legacy::{self, FINALIZED_AT, FORCED_OUTCOME, IS_FINALIZED, REPORT_OF};
+
+        new_test_ext().execute_with(|| {
+            let technics = "QmWboFP8XeBtFMbNYK3Ne8Z3gKFBSR5iQzkKgeNgQz3dz4"
+                .from_base58()
+                .unwrap();
+            let (alice, _) = get_params_proof("//Alice", &technics, &());
+            let (bob, _) = get_params_proof("//Bob", &technics, &());
+            let liability =
+                <Runtime as Trait>::Liability::new(technics.clone(), (), alice, bob.clone());
+
+            // Release 6 layout: open, reported and force finalized liabilities
+            for index in 0..3 {
+                store_legacy_liability(index, &liability);
+            }
+            for index in 1..3 {
+                legacy::put::<Runtime, _>(IS_FINALIZED, &index, true);
+                legacy::put::<Runtime, _>(FINALIZED_AT, &index, 5u64);
+            }
+            legacy::put::<Runtime, _>(REPORT_OF, &1, technics.clone());
+            legacy::put::<Runtime, _>(FORCED_OUTCOME, &2, true);
+            <StorageVersion>::put(Releases::V6_0_0);
+
+            migration::migrate::<Runtime>();
+            assert_eq!(Liability::storage_version(), STORAGE_VERSION);
+            let record = |report_hash, state, finalized_at| LiabilityRecord {
+                report_hash,
+                state,
+                created_at: 0,
+                finalized_at,
+                agreement: liability.clone(),
+            };
+            assert_eq!(
+                Liability::liability_record(0),
+                Some(record(None, RecordState::Open, None))
+            );
+            assert_eq!(
+                Liability::liability_record(1),
+                Some(record(
+                    Some(report_hash(&technics)),
+                    RecordState::Reported,
+                    Some(5)
+                ))
+            );
+            assert_eq!(Liability::report_of(1), Some(technics));
+            assert_eq!(
+                Liability::liability_record(2),
+                Some(record(None, RecordState::Forced(true), Some(5)))
+            );
+            assert_eq!(Liability::promisor_liability(&bob, 2), Some(2));
+
+            // Merged items are cleared
+            for item in legacy::MERGED.iter() {
+                let prefix = legacy::storage_prefix(item);
+                assert_eq!(
+                    sp_io::storage::next_key(&prefix).filter(|key| key.starts_with(&prefix)),
+                    None
+                );
+            }
         })
     }
 }
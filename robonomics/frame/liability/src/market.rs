@@ -0,0 +1,148 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Liability market orders: demands of promisees and offers of promisors.
+
+use codec::{Decode, Encode};
+use sp_runtime::{
+    traits::{AtLeast32BitUnsigned, Convert},
+    Perbill, RuntimeDebug,
+};
+
+/// Sequential identifier of market order.
+pub type OrderId = u64;
+
+/// Side of market order book.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Side {
+    /// Demands of promisees, the highest price is the best one.
+    Demand,
+    /// Offers of promisors, the lowest price is the best one.
+    Offer,
+}
+
+impl Side {
+    /// Returns `true` when order with price `a` is better than order with price `b`.
+    pub fn is_better<Price: PartialOrd>(self, a: &Price, b: &Price) -> bool {
+        match self {
+            Side::Demand => a > b,
+            Side::Offer => a < b,
+        }
+    }
+}
+
+/// Identifier of market, e.g. robot model.
+pub type MarketId = u32;
+
+/// All liabilities are traded on market zero.
+pub struct SingleMarket;
+impl<P> Convert<P, MarketId> for SingleMarket {
+    fn convert(_: P) -> MarketId {
+        0
+    }
+}
+
+/// Fee of unilateral liability cancel paid by cancelling party to its counterparty.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum CancellationFee<Balance> {
+    /// Fixed funds, at most liability price.
+    Fixed(Balance),
+    /// Share of liability price.
+    Share(Perbill),
+}
+
+impl<Balance: AtLeast32BitUnsigned + Copy> CancellationFee<Balance> {
+    /// Fee of cancelled liability with given price.
+    pub fn amount(&self, price: Balance) -> Balance {
+        match self {
+            CancellationFee::Fixed(fee) => (*fee).min(price),
+            CancellationFee::Share(share) => share.mul_floor(price),
+        }
+    }
+}
+
+impl<Balance> Default for CancellationFee<Balance> {
+    fn default() -> Self {
+        CancellationFee::Share(Perbill::zero())
+    }
+}
+
+/// Market configuration managed by governance. Liability keeps configuration of its market
+/// taken at creation, so later updates don't change agreed conditions.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct MarketConfig<Balance, BlockNumber> {
+    /// Fee charged on matching from settled value of liability.
+    pub fee: Perbill,
+    /// Deposit reserved for each market order, it grows with open orders of account.
+    pub order_deposit: Balance,
+    /// Count of blocks report of liability could be challenged after it's accepted,
+    /// witnessed report is settled after it.
+    pub challenge_window: BlockNumber,
+    /// Minimal age in blocks of liability that could be force finalized.
+    pub force_finalize_age: BlockNumber,
+    /// Fee of unilateral cancel before report, mutual cancel is fee-free.
+    pub cancellation_fee: CancellationFee<Balance>,
+    /// Count of blocks report preimage of liability is retained after it's accepted, at least
+    /// `challenge_window`. Preimage is prunable after retention, expired preimages are pruned
+    /// automatically when block has spare weight. Finalized liabilities are pruned the same
+    /// way after retention, liabilities of market without retention aren't pruned
+    /// automatically.
+    pub report_retention: BlockNumber,
+}
+
+/// Index of item selected by random seed with probability proportional to its weight,
+/// `None` when total weight is zero.
+pub fn select_weighted(weights: &[u128], seed: u128) -> Option<usize> {
+    let total = weights
+        .iter()
+        .fold(0u128, |total, weight| total.saturating_add(*weight));
+    if total == 0 {
+        return None;
+    }
+    let mut point = seed % total;
+    for (i, weight) in weights.iter().enumerate() {
+        if point < *weight {
+            return Some(i);
+        }
+        point -= weight;
+    }
+    None
+}
+
+/// Signed order of liability market.
+///
+/// Order proof signs technical and economical parameters the same way as parties proofs
+/// of liability `create`, so it's reused when order is matched.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Order<TechnicalParam, EconomicalParam, AccountId, BlockNumber, Proof, Balance> {
+    /// Technical parameter of liability.
+    pub technics: TechnicalParam,
+    /// Economical parameter of single unit liability.
+    pub economics: EconomicalParam,
+    /// Open quantity of units, order is filled by several liabilities.
+    pub quantity: u32,
+    /// Order creator: promisee of demand or promisor of offer.
+    pub sender: AccountId,
+    /// Creator proof of liability parameters.
+    pub proof: Proof,
+    /// Order couldn't be matched after this block.
+    pub deadline: BlockNumber,
+    /// Deposit reserved from order creator.
+    pub deposit: Balance,
+    /// Promisee funds reserved for matching reward of the rest of demand quantity.
+    pub escrow: Balance,
+}
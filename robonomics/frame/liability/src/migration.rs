@@ -0,0 +1,916 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Liability module storage migrations.
+
+use codec::{Decode, Encode};
+use frame_support::{
+    storage::migration::{put_storage_value, take_storage_value, StorageIterator},
+    traits::Get,
+    weights::Weight,
+    IterableStorageDoubleMap, IterableStorageMap, StorageDoubleMap, StorageMap, StorageValue,
+};
+use sp_runtime::traits::{Convert, One, Saturating, UniqueSaturatedInto, Zero};
+
+use crate::*;
+use legacy::{
+    CREATED_AT, FINALIZED_AT, FORCED_OUTCOME, GLOBAL_INDEX_OF, IS_FINALIZED, LIABILITY_OF,
+    REPORT_OF,
+};
+
+const MODULE: &[u8] = b"Liability";
+
+/// Storage items of earlier releases, they are accessed by raw keys.
+pub(crate) mod legacy {
+    use frame_support::{
+        storage::{migration::get_storage_value, unhashed},
+        Blake2_128Concat, StorageHasher, Twox64Concat,
+    };
+
+    use super::*;
+
+    /// Global index and liability parameters by promisor and its local index.
+    pub const LIABILITY_OF: &[u8] = b"LiabilityOf";
+    /// Promisor and local index of liability with given global index.
+    pub const GLOBAL_INDEX_OF: &[u8] = b"GlobalIndexOf";
+    /// Set `true` when liability is finalized.
+    pub const IS_FINALIZED: &[u8] = b"IsFinalized";
+    /// Liability technical report.
+    pub const REPORT_OF: &[u8] = b"ReportOf";
+    /// Economical outcome of liabilities finalized by root.
+    pub const FORCED_OUTCOME: &[u8] = b"ForcedOutcome";
+    /// Block number when liability created.
+    pub const CREATED_AT: &[u8] = b"CreatedAt";
+    /// Block number when liability finalized.
+    pub const FINALIZED_AT: &[u8] = b"FinalizedAt";
+
+    /// Items merged into `Liabilities` by release 7.
+    pub const MERGED: [&[u8]; 7] = [
+        LIABILITY_OF,
+        GLOBAL_INDEX_OF,
+        IS_FINALIZED,
+        REPORT_OF,
+        FORCED_OUTCOME,
+        CREATED_AT,
+        FINALIZED_AT,
+    ];
+
+    /// `Liabilities` record of releases 7 and 8, report is kept in the record.
+    #[derive(Encode, Decode)]
+    pub struct LiabilityRecordV8<Agreement, TechnicalReport, BlockNumber> {
+        /// Technical report, `None` until it's published.
+        pub report: Option<TechnicalReport>,
+        /// Finalization state.
+        pub state: RecordState,
+        /// Block when liability created.
+        pub created_at: BlockNumber,
+        /// Block when liability finalized.
+        pub finalized_at: Option<BlockNumber>,
+        /// Agreement of liability parties.
+        pub agreement: Agreement,
+    }
+
+    /// Release 8 `Liabilities` record of current runtime.
+    pub type RecordV8Of<T> =
+        LiabilityRecordV8<<T as Trait>::Liability, TechnicalReport<T>, BlockNumber<T>>;
+
+    /// Market configuration of release 11, without cancellation fee.
+    #[derive(Encode, Decode)]
+    pub struct MarketConfigV11<Balance, BlockNumber> {
+        /// Fee charged on matching.
+        pub fee: Perbill,
+        /// Deposit reserved for each market order.
+        pub order_deposit: Balance,
+        /// Count of blocks report preimage is kept after it's accepted.
+        pub challenge_window: BlockNumber,
+        /// Minimal age in blocks of liability that could be force finalized.
+        pub force_finalize_age: BlockNumber,
+    }
+
+    impl<Balance, BlockNumber> MarketConfigV11<Balance, BlockNumber> {
+        /// Configuration of release 13 with free cancellation.
+        pub fn upgrade(self) -> MarketConfigV13<Balance, BlockNumber> {
+            MarketConfigV13 {
+                fee: self.fee,
+                order_deposit: self.order_deposit,
+                challenge_window: self.challenge_window,
+                force_finalize_age: self.force_finalize_age,
+                cancellation_fee: Default::default(),
+            }
+        }
+    }
+
+    /// Release 11 market configuration of current runtime.
+    pub type MarketConfigV11Of<T> = MarketConfigV11<BalanceOf<T>, BlockNumber<T>>;
+
+    /// Market configuration of releases 12 and 13, without report retention.
+    #[derive(Encode, Decode)]
+    pub struct MarketConfigV13<Balance, BlockNumber> {
+        /// Fee charged on matching.
+        pub fee: Perbill,
+        /// Deposit reserved for each market order.
+        pub order_deposit: Balance,
+        /// Count of blocks report preimage is kept after it's accepted.
+        pub challenge_window: BlockNumber,
+        /// Minimal age in blocks of liability that could be force finalized.
+        pub force_finalize_age: BlockNumber,
+        /// Fee of unilateral cancel before report.
+        pub cancellation_fee: CancellationFee<Balance>,
+    }
+
+    impl<Balance, BlockNumber: Zero> MarketConfigV13<Balance, BlockNumber> {
+        /// Configuration of the current release, report preimage is retained during
+        /// challenge window only.
+        pub fn upgrade(self) -> MarketConfig<Balance, BlockNumber> {
+            MarketConfig {
+                fee: self.fee,
+                order_deposit: self.order_deposit,
+                challenge_window: self.challenge_window,
+                force_finalize_age: self.force_finalize_age,
+                cancellation_fee: self.cancellation_fee,
+                report_retention: Zero::zero(),
+            }
+        }
+    }
+
+    /// Release 13 market configuration of current runtime.
+    pub type MarketConfigV13Of<T> = MarketConfigV13<BalanceOf<T>, BlockNumber<T>>;
+
+    /// Storage key prefix of item.
+    pub fn storage_prefix(item: &[u8]) -> Vec<u8> {
+        let mut prefix = sp_io::hashing::twox_128(MODULE).to_vec();
+        prefix.extend_from_slice(&sp_io::hashing::twox_128(item));
+        prefix
+    }
+
+    /// Key hash of maps by liability global index.
+    pub fn index_hash<T: Trait>(index: &LiabilityIndex<T>) -> Vec<u8> {
+        Blake2_128Concat::hash(&index.encode())
+    }
+
+    /// Key hash of `LiabilityOf` by promisor and its local index.
+    pub fn liability_hash<T: Trait>(
+        promisor: &AccountId<T>,
+        local_index: &LiabilityIndex<T>,
+    ) -> Vec<u8> {
+        let mut hash = Blake2_128Concat::hash(&promisor.encode());
+        hash.extend(Twox64Concat::hash(&local_index.encode()));
+        hash
+    }
+
+    /// Value of map by liability global index.
+    pub fn get<T: Trait, V: Decode>(item: &[u8], index: &LiabilityIndex<T>) -> Option<V> {
+        get_storage_value(MODULE, item, &index_hash::<T>(index))
+    }
+
+    /// Insert value of map by liability global index.
+    pub fn put<T: Trait, V: Encode>(item: &[u8], index: &LiabilityIndex<T>, value: V) {
+        put_storage_value(MODULE, item, &index_hash::<T>(index), value)
+    }
+
+    /// Value exists under given key hash, even when it can't be decoded.
+    pub fn contains(item: &[u8], hash: &[u8]) -> bool {
+        let mut key = storage_prefix(item);
+        key.extend_from_slice(hash);
+        unhashed::exists(&key)
+    }
+
+    /// Remove value under given key hash, even when it can't be decoded.
+    pub fn remove(item: &[u8], hash: &[u8]) {
+        let mut key = storage_prefix(item);
+        key.extend_from_slice(hash);
+        unhashed::kill(&key)
+    }
+
+    /// Liability finalized flag.
+    pub fn is_finalized<T: Trait>(index: &LiabilityIndex<T>) -> bool {
+        get::<T, bool>(IS_FINALIZED, index).unwrap_or(false)
+    }
+
+    /// Promisor and its local index of liability.
+    pub fn global_index_of<T: Trait>(
+        index: &LiabilityIndex<T>,
+    ) -> Option<(AccountId<T>, LiabilityIndex<T>)> {
+        get::<T, _>(GLOBAL_INDEX_OF, index)
+    }
+
+    /// Typed liability by global index, layout of releases 3 to 6.
+    pub fn liability<T: Trait>(index: &LiabilityIndex<T>) -> Option<T::Liability> {
+        let (promisor, local_index) = global_index_of::<T>(index)?;
+        let hash = liability_hash::<T>(&promisor, &local_index);
+        get_storage_value::<(LiabilityIndex<T>, T::Liability)>(MODULE, LIABILITY_OF, &hash)
+            .map(|(_, liability)| liability)
+    }
+
+    /// Some liability is indexed by `GlobalIndexOf`.
+    pub fn has_global_index<T: Trait>() -> bool {
+        StorageIterator::<(AccountId<T>, LiabilityIndex<T>)>::new(MODULE, GLOBAL_INDEX_OF)
+            .next()
+            .is_some()
+    }
+}
+
+/// Apply storage migrations from current storage release up to `STORAGE_VERSION`.
+pub fn migrate<T: Trait>() -> Weight {
+    #[cfg(feature = "try-runtime")]
+    let liabilities = pre_migrate::<T>();
+
+    let mut weight = T::DbWeight::get().reads(1);
+
+    if <StorageVersion>::get() == Releases::V1_0_0 {
+        weight = weight
+            .saturating_add(relocate_liabilities::<T>())
+            .saturating_add(backfill_block_numbers::<T>())
+            .saturating_add(T::DbWeight::get().writes(1));
+        <StorageVersion>::put(Releases::V2_0_0);
+    }
+
+    if <StorageVersion>::get() == Releases::V2_0_0 {
+        weight = weight
+            .saturating_add(typed_storage::<T>())
+            .saturating_add(T::DbWeight::get().writes(1));
+        <StorageVersion>::put(Releases::V3_0_0);
+    }
+
+    if <StorageVersion>::get() == Releases::V3_0_0 {
+        weight = weight
+            .saturating_add(backfill_counters::<T>())
+            .saturating_add(T::DbWeight::get().writes(1));
+        <StorageVersion>::put(Releases::V4_0_0);
+    }
+
+    if <StorageVersion>::get() == Releases::V4_0_0 {
+        weight = weight
+            .saturating_add(backfill_created_in_block::<T>())
+            .saturating_add(T::DbWeight::get().writes(1));
+        <StorageVersion>::put(Releases::V5_0_0);
+    }
+
+    if <StorageVersion>::get() == Releases::V5_0_0 {
+        weight = weight
+            .saturating_add(reject_oversized::<T>())
+            .saturating_add(T::DbWeight::get().writes(1));
+        <StorageVersion>::put(Releases::V6_0_0);
+    }
+
+    if <StorageVersion>::get() == Releases::V6_0_0 {
+        weight = weight
+            .saturating_add(merge_records::<T>())
+            .saturating_add(T::DbWeight::get().writes(1));
+        <StorageVersion>::put(Releases::V7_0_0);
+    }
+
+    if <StorageVersion>::get() == Releases::V7_0_0 {
+        weight = weight
+            .saturating_add(expiry_sweep_cursor::<T>())
+            .saturating_add(T::DbWeight::get().writes(1));
+        <StorageVersion>::put(Releases::V8_0_0);
+    }
+
+    if <StorageVersion>::get() == Releases::V8_0_0 {
+        weight = weight
+            .saturating_add(report_preimages::<T>())
+            .saturating_add(T::DbWeight::get().writes(1));
+        <StorageVersion>::put(Releases::V9_0_0);
+    }
+
+    if <StorageVersion>::get() == Releases::V9_0_0 {
+        weight = weight
+            .saturating_add(named_reserves::<T>())
+            .saturating_add(T::DbWeight::get().writes(1));
+        <StorageVersion>::put(Releases::V10_0_0);
+    }
+
+    if <StorageVersion>::get() == Releases::V10_0_0 {
+        weight = weight
+            .saturating_add(market_configs::<T>())
+            .saturating_add(T::DbWeight::get().writes(1));
+        <StorageVersion>::put(Releases::V11_0_0);
+    }
+
+    if <StorageVersion>::get() == Releases::V11_0_0 {
+        weight = weight
+            .saturating_add(cancellation_fees::<T>())
+            .saturating_add(T::DbWeight::get().writes(1));
+        <StorageVersion>::put(Releases::V12_0_0);
+    }
+
+    if <StorageVersion>::get() == Releases::V12_0_0 {
+        weight = weight
+            .saturating_add(block_state::<T>())
+            .saturating_add(T::DbWeight::get().writes(1));
+        <StorageVersion>::put(Releases::V13_0_0);
+    }
+
+    if <StorageVersion>::get() == Releases::V13_0_0 {
+        weight = weight
+            .saturating_add(report_retention::<T>())
+            .saturating_add(preimage_expiry_queue::<T>())
+            .saturating_add(T::DbWeight::get().writes(1));
+        <StorageVersion>::put(Releases::V14_0_0);
+    }
+
+    if <StorageVersion>::get() == Releases::V14_0_0 {
+        weight = weight
+            .saturating_add(sweep_cursors::<T>())
+            .saturating_add(T::DbWeight::get().writes(1));
+        <StorageVersion>::put(Releases::V15_0_0);
+    }
+
+    if <StorageVersion>::get() == Releases::V15_0_0 {
+        weight = weight
+            .saturating_add(liability_pruning::<T>())
+            .saturating_add(T::DbWeight::get().writes(1));
+        <StorageVersion>::put(Releases::V16_0_0);
+    }
+
+    #[cfg(feature = "try-runtime")]
+    post_migrate::<T>(liabilities);
+
+    weight
+}
+
+/// Count liabilities in storage before migration.
+#[cfg(feature = "try-runtime")]
+pub fn pre_migrate<T: Trait>() -> usize {
+    let count = match <StorageVersion>::get() {
+        Releases::V1_0_0 if !legacy::has_global_index::<T>() => {
+            StorageIterator::<Vec<u8>>::new(MODULE, LIABILITY_OF).count()
+        }
+        Releases::V1_0_0 | Releases::V2_0_0 => {
+            StorageIterator::<(LiabilityIndex<T>, Vec<u8>)>::new(MODULE, LIABILITY_OF).count()
+        }
+        Releases::V3_0_0 | Releases::V4_0_0 | Releases::V5_0_0 | Releases::V6_0_0 => {
+            StorageIterator::<(AccountId<T>, LiabilityIndex<T>)>::new(MODULE, GLOBAL_INDEX_OF)
+                .count()
+        }
+        Releases::V7_0_0 | Releases::V8_0_0 => {
+            StorageIterator::<legacy::RecordV8Of<T>>::new(MODULE, b"Liabilities").count()
+        }
+        Releases::V9_0_0
+        | Releases::V10_0_0
+        | Releases::V11_0_0
+        | Releases::V12_0_0
+        | Releases::V13_0_0
+        | Releases::V14_0_0
+        | Releases::V15_0_0
+        | Releases::V16_0_0 => {
+            StorageIterator::<LiabilityRecordOf<T>>::new(MODULE, b"Liabilities").count()
+        }
+    };
+    let latest_index: u64 = <LatestIndex<T>>::get().unique_saturated_into();
+    assert_eq!(
+        count as u64, latest_index,
+        "each liability index should be stored"
+    );
+    count
+}
+
+/// Check that every liability and report is decodable after migration,
+/// except oversized ones rejected by it.
+#[cfg(feature = "try-runtime")]
+pub fn post_migrate<T: Trait>(liabilities: usize) {
+    assert_eq!(<StorageVersion>::get(), STORAGE_VERSION);
+
+    let rejections = <OversizedRejections<T>>::get();
+    let rejected = |index, is_report| rejections.contains(&(index, is_report));
+    let mut index = LiabilityIndex::<T>::zero();
+    let mut count = 0;
+    while index < <LatestIndex<T>>::get() {
+        if rejected(index, false) {
+            assert!(Module::<T>::liability(index).is_none());
+            index += One::one();
+            count += 1;
+            continue;
+        }
+        let record = <Liabilities<T>>::get(index).expect("liability should be decodable");
+        let liability = &record.agreement;
+        assert!(<AccountLiabilities<T>>::contains_key(
+            liability.promisee(),
+            index
+        ));
+        assert!(<AccountLiabilities<T>>::contains_key(
+            liability.promisor(),
+            index
+        ));
+        if record.state == RecordState::Reported && !rejected(index, true) {
+            let kept = record
+                .report_hash
+                .map_or(false, |hash| <ReportPreimage<T>>::contains_key(hash));
+            assert!(kept, "report should be kept");
+        }
+        index += One::one();
+        count += 1;
+    }
+    assert_eq!(count, liabilities);
+    Module::<T>::try_state().expect("state invariants should hold after migration");
+}
+
+/// Move liabilities from global index map to `LiabilityOf` double map keyed by promisor
+/// and its local index, global index kept in `GlobalIndexOf`. Both parties are indexed in
+/// `AccountLiabilities` on the way.
+///
+/// Old `LiabilityOf` map shares storage prefix with the new one, so old entries are drained
+/// before any insertion. Liabilities that can't be decoded have no known promisor and are dropped.
+pub fn relocate_liabilities<T: Trait>() -> Weight {
+    let latest_index = <LatestIndex<T>>::get();
+    if latest_index == LiabilityIndex::<T>::zero() || legacy::has_global_index::<T>() {
+        return T::DbWeight::get().reads(2);
+    }
+
+    // Old map was hashed by `blake2_128_concat`: 16 bytes of hash followed by encoded index
+    let mut old: Vec<(LiabilityIndex<T>, Vec<u8>)> =
+        StorageIterator::<Vec<u8>>::new(MODULE, LIABILITY_OF)
+            .drain()
+            .filter_map(|(key, encoded)| {
+                let index = LiabilityIndex::<T>::decode(&mut key.get(16..)?).ok()?;
+                Some((index, encoded))
+            })
+            .collect();
+    old.sort_by_key(|(index, _)| *index);
+
+    let mut reads: Weight = 2 + old.len() as Weight;
+    let mut writes: Weight = old.len() as Weight;
+    for (index, encoded) in old {
+        if let Ok(liability) = T::Liability::decode(&mut &encoded[..]) {
+            let promisor = liability.promisor();
+            let local_index = <PromisorCount<T>>::get(promisor);
+
+            // Release 2 layout: global index and encoded liability
+            let key = legacy::liability_hash::<T>(promisor, &local_index);
+            put_storage_value(MODULE, LIABILITY_OF, &key, (index, encoded));
+
+            <PromisorCount<T>>::insert(promisor, local_index + One::one());
+            legacy::put::<T, _>(GLOBAL_INDEX_OF, &index, (promisor, local_index));
+            <AccountLiabilities<T>>::insert(liability.promisee(), index, ());
+            <AccountLiabilities<T>>::insert(promisor, index, ());
+            reads += 1;
+            writes += 5;
+        }
+    }
+
+    T::DbWeight::get().reads_writes(reads, writes)
+}
+
+/// Replace SCALE-encoded byte vectors of liabilities and reports with typed values.
+///
+/// Values that can't be decoded are removed: they are unusable under both layouts.
+pub fn typed_storage<T: Trait>() -> Weight {
+    let liabilities: Vec<_> =
+        StorageIterator::<(LiabilityIndex<T>, Vec<u8>)>::new(MODULE, LIABILITY_OF).collect();
+    let reports: Vec<_> = StorageIterator::<Vec<u8>>::new(MODULE, REPORT_OF).collect();
+    let reads = (liabilities.len() + reports.len()) as Weight;
+
+    for (key, (index, encoded)) in liabilities {
+        match T::Liability::decode(&mut &encoded[..]) {
+            Ok(liability) => put_storage_value(MODULE, LIABILITY_OF, &key, (index, liability)),
+            Err(_) => {
+                take_storage_value::<(LiabilityIndex<T>, Vec<u8>)>(MODULE, LIABILITY_OF, &key);
+            }
+        }
+    }
+
+    for (key, encoded) in reports {
+        match TechnicalReport::<T>::decode(&mut &encoded[..]) {
+            Ok(report) => put_storage_value(MODULE, REPORT_OF, &key, report),
+            Err(_) => {
+                take_storage_value::<Vec<u8>>(MODULE, REPORT_OF, &key);
+            }
+        }
+    }
+
+    T::DbWeight::get().reads_writes(reads, reads)
+}
+
+/// Backfill `CreatedAt` and `FinalizedAt` for liabilities created before block numbers
+/// were tracked. Missed entries set to zero, see storage docs for sentinel meaning.
+///
+/// Liability with index zero is the first one to get a block number, so when it's already
+/// present the migration was applied and nothing to do.
+pub fn backfill_block_numbers<T: Trait>() -> Weight {
+    let latest_index = <LatestIndex<T>>::get();
+    let zero = LiabilityIndex::<T>::zero();
+    let has_block = |item, index| legacy::get::<T, BlockNumber<T>>(item, index).is_some();
+    if latest_index == zero || has_block(CREATED_AT, &zero) {
+        return T::DbWeight::get().reads(2);
+    }
+
+    let mut reads: Weight = 2;
+    let mut writes: Weight = 0;
+    let mut index = zero;
+    while index < latest_index {
+        reads += 2;
+        if !has_block(CREATED_AT, &index) {
+            legacy::put::<T, _>(CREATED_AT, &index, BlockNumber::<T>::zero());
+            writes += 1;
+        }
+        if legacy::is_finalized::<T>(&index) && !has_block(FINALIZED_AT, &index) {
+            reads += 1;
+            legacy::put::<T, _>(FINALIZED_AT, &index, BlockNumber::<T>::zero());
+            writes += 1;
+        }
+        index += One::one();
+    }
+
+    T::DbWeight::get().reads_writes(reads, writes)
+}
+
+/// Count liabilities created before `Counters` was introduced.
+pub fn backfill_counters<T: Trait>() -> Weight {
+    let latest_index = <LatestIndex<T>>::get();
+    let mut counters = LiabilityCounters::<T::Balance>::default();
+    counters.created = latest_index.unique_saturated_into();
+
+    let mut reads: Weight = 1;
+    let mut index = LiabilityIndex::<T>::zero();
+    while index < latest_index {
+        reads += 1;
+        if legacy::is_finalized::<T>(&index) {
+            reads += 2;
+            counters.finalized += 1;
+            if let Some(liability) = legacy::liability::<T>(&index) {
+                let settled = T::SettledValue::convert(liability.economics().clone());
+                counters.settled = counters.settled.saturating_add(settled);
+            }
+        }
+        index += One::one();
+    }
+    <Counters<T>>::put(counters);
+
+    T::DbWeight::get().reads_writes(reads, 1)
+}
+
+/// Index liabilities created within retention window before `CreatedInBlock` was introduced.
+///
+/// Creation block of older liabilities may be unknown (zero sentinel), so blocks before
+/// the window or at least before block one are marked as pruned.
+pub fn backfill_created_in_block<T: Trait>() -> Weight {
+    let now = <frame_system::Module<T>>::block_number();
+    let retention = T::CreatedRetention::get();
+    let from = if retention.is_zero() {
+        One::one()
+    } else {
+        now.saturating_sub(retention).max(One::one())
+    };
+
+    let latest_index = <LatestIndex<T>>::get();
+    let mut reads: Weight = 2;
+    let mut writes: Weight = 1;
+    let mut index = LiabilityIndex::<T>::zero();
+    while index < latest_index {
+        reads += 1;
+        let created_at = legacy::get::<T, _>(CREATED_AT, &index).unwrap_or_else(Zero::zero);
+        if created_at >= from {
+            <CreatedInBlock<T>>::mutate(created_at, |indices| indices.push(index));
+            reads += 1;
+            writes += 1;
+        }
+        index += One::one();
+    }
+    <CreatedPrunedUntil<T>>::put(from);
+
+    T::DbWeight::get().reads_writes(reads, writes)
+}
+
+/// Reject liabilities and reports exceeding `MaxTechnicsLength` and `MaxReportLength`.
+///
+/// Truncated IPFS hash is meaningless, so oversized values are removed instead: open
+/// liability is finalized as failed and its funds are processed as for failed one.
+/// Values that can't be decoded are rejected the same way without economical processing.
+/// Rejections are kept in `OversizedRejections` until their events are deposited.
+pub fn reject_oversized<T: Trait>() -> Weight {
+    let max_technics = T::MaxTechnicsLength::get() as usize;
+    let max_report = T::MaxReportLength::get() as usize;
+    let now = <frame_system::Module<T>>::block_number();
+
+    let latest_index = <LatestIndex<T>>::get();
+    let mut rejections = Vec::new();
+    let mut reads: Weight = 1;
+    let mut writes: Weight = 1;
+    let mut index = LiabilityIndex::<T>::zero();
+    while index < latest_index {
+        reads += 3;
+        if let Some((promisor, local_index)) = legacy::global_index_of::<T>(&index) {
+            let liability = legacy::liability::<T>(&index);
+            let oversized = match liability {
+                Some(ref liability) => liability.technics().encoded_size() > max_technics,
+                None => legacy::contains(
+                    LIABILITY_OF,
+                    &legacy::liability_hash::<T>(&promisor, &local_index),
+                ),
+            };
+            if oversized {
+                reject_liability::<T>(index, &promisor, local_index, liability, now);
+                rejections.push((index, false));
+                reads += 1;
+                writes += 9;
+            }
+        }
+
+        let report_hash = legacy::index_hash::<T>(&index);
+        let oversized = match legacy::get::<T, TechnicalReport<T>>(REPORT_OF, &index) {
+            Some(report) => report.encoded_size() > max_report,
+            None => legacy::contains(REPORT_OF, &report_hash),
+        };
+        if oversized {
+            legacy::remove(REPORT_OF, &report_hash);
+            rejections.push((index, true));
+            writes += 1;
+        }
+        index += One::one();
+    }
+    <OversizedRejections<T>>::put(rejections);
+
+    T::DbWeight::get().reads_writes(reads, writes)
+}
+
+fn reject_liability<T: Trait>(
+    index: LiabilityIndex<T>,
+    promisor: &AccountId<T>,
+    local_index: LiabilityIndex<T>,
+    liability: Option<T::Liability>,
+    now: BlockNumber<T>,
+) {
+    legacy::remove(
+        LIABILITY_OF,
+        &legacy::liability_hash::<T>(promisor, &local_index),
+    );
+    legacy::remove(GLOBAL_INDEX_OF, &legacy::index_hash::<T>(&index));
+    <AccountLiabilities<T>>::remove(promisor, index);
+    if let Some(ref liability) = liability {
+        <AccountLiabilities<T>>::remove(liability.promisee(), index);
+    }
+
+    if !legacy::is_finalized::<T>(&index) {
+        if let Some(liability) = liability {
+            // Funds can't be kept reserved by removed liability
+            let _ = liability.on_finish(false);
+        }
+        legacy::put::<T, _>(IS_FINALIZED, &index, true);
+        legacy::put::<T, _>(FORCED_OUTCOME, &index, false);
+        legacy::put::<T, _>(FINALIZED_AT, &index, now);
+        <Counters<T>>::mutate(|counters| counters.cancelled += 1);
+        Module::<T>::record_transition(index, now, Transition::ForceFinalized(false));
+    }
+}
+
+/// Merge liability parameters, finalization state, block numbers and report into single
+/// `Liabilities` record, promisor local index is kept in `PromisorLiabilities`.
+///
+/// Liabilities removed by earlier migrations have no parameters, so nothing is left of them
+/// and old items are cleared entirely.
+pub fn merge_records<T: Trait>() -> Weight {
+    let latest_index = <LatestIndex<T>>::get();
+    let mut reads: Weight = 1;
+    let mut writes: Weight = legacy::MERGED.len() as Weight;
+    let mut index = LiabilityIndex::<T>::zero();
+    while index < latest_index {
+        reads += 1;
+        if let Some((promisor, local_index)) = legacy::global_index_of::<T>(&index) {
+            reads += 6;
+            if let Some(agreement) = legacy::liability::<T>(&index) {
+                let finalized = legacy::is_finalized::<T>(&index);
+                let state = match legacy::get::<T, bool>(FORCED_OUTCOME, &index) {
+                    _ if !finalized => RecordState::Open,
+                    Some(success) => RecordState::Forced(success),
+                    None => RecordState::Reported,
+                };
+                let finalized_at = if finalized {
+                    Some(legacy::get::<T, _>(FINALIZED_AT, &index).unwrap_or_else(Zero::zero))
+                } else {
+                    None
+                };
+                let record = legacy::LiabilityRecordV8 {
+                    report: legacy::get::<T, TechnicalReport<T>>(REPORT_OF, &index),
+                    state,
+                    created_at: legacy::get::<T, _>(CREATED_AT, &index).unwrap_or_else(Zero::zero),
+                    finalized_at,
+                    agreement,
+                };
+                let hash = legacy::index_hash::<T>(&index);
+                put_storage_value(MODULE, b"Liabilities", &hash, record);
+                <PromisorLiabilities<T>>::insert(promisor, local_index, index);
+                writes += 2;
+            }
+        }
+        index += One::one();
+    }
+
+    for item in legacy::MERGED.iter() {
+        sp_io::storage::clear_prefix(&legacy::storage_prefix(item));
+    }
+
+    T::DbWeight::get().reads_writes(reads, writes)
+}
+
+/// Start `ExpirySweepCursor` from the current block, orders carried over by release 7 sweep
+/// are expired first in it.
+pub fn expiry_sweep_cursor<T: Trait>() -> Weight {
+    let now = <frame_system::Module<T>>::block_number();
+    let carried: Vec<OrderId> =
+        take_storage_value(MODULE, b"ExpiredOrders", &[]).unwrap_or_default();
+    if !carried.is_empty() {
+        <OrdersExpiringAt<T>>::mutate(now, |orders| {
+            *orders = carried.into_iter().chain(orders.drain(..)).collect()
+        });
+    }
+    <ExpirySweepCursor<T>>::put((now, 0));
+    T::DbWeight::get().reads_writes(3, 3)
+}
+
+/// Start sweep cursors of block indexed lists from the current block, lists of previous
+/// blocks were taken by their blocks.
+pub fn sweep_cursors<T: Trait>() -> Weight {
+    let now = <frame_system::Module<T>>::block_number();
+    <FundingSweepCursor<T>>::put((now, 0));
+    <RevealSweepCursor<T>>::put((now, 0));
+    <MatchCommitSweepCursor<T>>::put((now, 0));
+    <SettlementSweepCursor<T>>::put((now, 0));
+    <AssignmentSweepCursor<T>>::put((now, 0));
+    T::DbWeight::get().writes(5)
+}
+
+/// Start automatic pruning of liabilities from the current block, liabilities finalized
+/// before aren't queued in `PrunableAt` and are pruned manually.
+pub fn liability_pruning<T: Trait>() -> Weight {
+    let now = <frame_system::Module<T>>::block_number();
+    <LiabilitiesPrunedUntil<T>>::put(now);
+    T::DbWeight::get().writes(1)
+}
+
+/// Move reports of `Liabilities` records to `ReportPreimage`, records keep report hash.
+///
+/// Migrated preimages have no depositor, they're kept `ReportChallengeWindow` blocks
+/// from migration.
+pub fn report_preimages<T: Trait>() -> Weight {
+    let now = <frame_system::Module<T>>::block_number();
+    let expires_at = now.saturating_add(T::ReportChallengeWindow::get());
+    <Liabilities<T>>::translate::<legacy::RecordV8Of<T>, _>(|_, record| {
+        let stored = record.report.map(|report| {
+            let hash = report_hash(&report);
+            let preimage = Preimage {
+                report,
+                depositor: None,
+                expires_at,
+            };
+            <ReportPreimage<T>>::insert(hash, preimage);
+            hash
+        });
+        Some(LiabilityRecord {
+            report_hash: stored,
+            state: record.state,
+            created_at: record.created_at,
+            finalized_at: record.finalized_at,
+            agreement: record.agreement,
+        })
+    });
+
+    let records: Weight = <LatestIndex<T>>::get().unique_saturated_into();
+    T::DbWeight::get().reads_writes(records.saturating_add(2), records.saturating_mul(2))
+}
+
+/// Track funds already reserved by module under `RESERVE_ID`: open orders deposits and
+/// escrow, lighthouse stakes, signed orders bonds, report preimage deposits and robot
+/// deposits of registry.
+pub fn named_reserves<T: Trait>() -> Weight {
+    let mut reads: Weight = 0;
+    let mut writes: Weight = 0;
+    let mut track = |who: &AccountId<T>, amount: BalanceOf<T>| {
+        if !amount.is_zero() {
+            <NamedReserves<T>>::mutate(RESERVE_ID, who, |reserved| {
+                *reserved = reserved.saturating_add(amount)
+            });
+            writes += 1;
+        }
+    };
+
+    for (_, order) in <Asks<T>>::iter().chain(<Bids<T>>::iter()) {
+        reads += 1;
+        track(&order.sender, order.deposit.saturating_add(order.escrow));
+    }
+    for (_, lighthouse, stake) in <LighthouseStake<T>>::iter() {
+        reads += 1;
+        track(&lighthouse, stake);
+    }
+    for (account, bond) in <OrderBond<T>>::iter() {
+        reads += 1;
+        track(&account, bond);
+    }
+    for (_, preimage) in <ReportPreimage<T>>::iter() {
+        reads += 1;
+        if let Some((depositor, deposit)) = preimage.depositor {
+            track(&depositor, deposit);
+        }
+    }
+    for (owner, deposit) in T::Robots::deposits() {
+        reads += 1;
+        track(&owner, deposit);
+    }
+
+    T::DbWeight::get().reads_writes(reads.saturating_add(writes), writes)
+}
+
+/// Move fees of `MarketFee` to `Markets` configurations, the rest of configuration
+/// is default one.
+pub fn market_configs<T: Trait>() -> Weight {
+    let fees: Vec<_> = StorageIterator::<Perbill>::new(MODULE, b"MarketFee").collect();
+    let reads = fees.len() as Weight;
+
+    for (key, fee) in fees {
+        take_storage_value::<Perbill>(MODULE, b"MarketFee", &key);
+        // Key is hashed by `twox_64_concat`, market id follows the hash
+        if let Some(market_id) = key
+            .get(8..)
+            .and_then(|mut id| MarketId::decode(&mut id).ok())
+        {
+            let market = MarketConfig {
+                fee,
+                ..Module::<T>::default_market_config()
+            };
+            <Markets<T>>::insert(market_id, market);
+        }
+    }
+
+    T::DbWeight::get().reads_writes(reads, reads.saturating_mul(2))
+}
+
+/// Add free cancellation to market configurations and their liability snapshots.
+///
+/// Configurations are written in release 13 layout, it isn't the stored type of the
+/// current release, so they're accessed by raw keys.
+pub fn cancellation_fees<T: Trait>() -> Weight {
+    let markets: Vec<_> =
+        StorageIterator::<legacy::MarketConfigV11Of<T>>::new(MODULE, b"Markets").collect();
+    let snapshots: Vec<_> = StorageIterator::<(MarketId, legacy::MarketConfigV11Of<T>)>::new(
+        MODULE,
+        b"LiabilityMarket",
+    )
+    .collect();
+    let entries = (markets.len() + snapshots.len()) as Weight;
+
+    for (key, market) in markets {
+        put_storage_value(MODULE, b"Markets", &key, market.upgrade());
+    }
+    for (key, (market_id, market)) in snapshots {
+        put_storage_value(
+            MODULE,
+            b"LiabilityMarket",
+            &key,
+            (market_id, market.upgrade()),
+        );
+    }
+    T::DbWeight::get().reads_writes(entries, entries)
+}
+
+/// Add report retention to market configurations and their liability snapshots, preimages
+/// are retained during challenge window as before.
+pub fn report_retention<T: Trait>() -> Weight {
+    let mut entries: Weight = 0;
+    <Markets<T>>::translate::<legacy::MarketConfigV13Of<T>, _>(|_, market| {
+        entries += 1;
+        Some(market.upgrade())
+    });
+    <LiabilityMarket<T>>::translate::<(MarketId, legacy::MarketConfigV13Of<T>), _>(
+        |_, (market_id, market)| {
+            entries += 1;
+            Some((market_id, market.upgrade()))
+        },
+    );
+    T::DbWeight::get().reads_writes(entries, entries)
+}
+
+/// Queue stored report preimages by expiry block for automatic pruning, already expired
+/// ones are queued at the current block. Blocks before the current one have nothing queued.
+pub fn preimage_expiry_queue<T: Trait>() -> Weight {
+    let now = <frame_system::Module<T>>::block_number();
+    let mut preimages: Weight = 0;
+    for (hash, preimage) in <ReportPreimage<T>>::iter() {
+        <PreimagesExpiringAt<T>>::mutate(preimage.expires_at.max(now), |hashes| hashes.push(hash));
+        preimages += 1;
+    }
+    <PreimagesPrunedUntil<T>>::put(now);
+    T::DbWeight::get().reads_writes(preimages, preimages.saturating_add(1))
+}
+
+/// Remove unsigned calls limits left by the last block of previous release, they were
+/// removed at the start of the next block.
+pub fn block_state<T: Trait>() -> Weight {
+    let previous = <frame_system::Module<T>>::block_number().saturating_sub(One::one());
+    <UnsignedInBlock>::kill();
+    <PairCreatesInBlock<T>>::remove_prefix(previous);
+    T::DbWeight::get().writes(2)
+}
@@ -0,0 +1,275 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Liability off-chain worker: finalizes liabilities of local promisor keys
+//! using results published on IPFS and matches market orders by local lighthouse keys.
+//!
+//! Result of liability is fetched from `<gateway>/<index>`, where gateway is set by node
+//! `--liability-ipfs-gateway` option, e.g. `http://127.0.0.1:8080/ipns/<robot key>`.
+//! Response body is liability report, e.g. IPFS hash of the work results.
+//!
+//! Lighthouse mode is enabled by node `--liability-lighthouse` flag: orders of markets
+//! where local key is scheduled lighthouse are matched by signed `match_orders`.
+
+use codec::{Decode, Encode};
+use frame_support::{debug, IterableStorageDoubleMap, IterableStorageMap, StorageMap};
+use frame_system::offchain::{AppCrypto, CreateSignedTransaction, SubmitTransaction};
+use sp_runtime::{
+    offchain::{http, storage::StorageValueRef, Duration, StorageKind},
+    traits::{IdentifyAccount, Saturating, UniqueSaturatedInto},
+    RuntimeAppPublic,
+};
+use sp_std::prelude::*;
+
+use crate::*;
+
+/// Off-chain storage key prefix of liabilities with submitted reports.
+const OFFCHAIN_SUBMITTED_PREFIX: &[u8] = b"robonomics::liability::submitted";
+
+/// Off-chain storage key prefix of submitted order matches.
+const OFFCHAIN_MATCHED_PREFIX: &[u8] = b"robonomics::liability::matched";
+
+/// Maximal count of results fetched by off-chain worker in a block.
+pub const MAX_FETCHES_PER_BLOCK: usize = 4;
+
+/// Maximal count of `match_orders` submitted by off-chain worker in a block.
+pub const MAX_MATCHES_PER_BLOCK: usize = 4;
+
+/// Count of blocks before the same liability or match is processed again, e.g. when result
+/// wasn't published yet or transaction was dropped from the pool.
+pub const RESUBMIT_PERIOD: u32 = 16;
+
+/// Deadline of IPFS gateway request in milliseconds.
+pub const FETCH_TIMEOUT: u64 = 2_000;
+
+/// Submit unsigned `finalize` for open liabilities of local promisor keys,
+/// does nothing when IPFS gateway isn't set.
+pub fn auto_finalize<T: Trait>(now: BlockNumber<T>) {
+    let gateway =
+        match sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, OFFCHAIN_GATEWAY_KEY) {
+            Some(gateway) => gateway,
+            None => return,
+        };
+
+    let mut fetches = 0;
+    for (public, account) in local_accounts::<T>() {
+        for (index, _) in <AccountLiabilities<T>>::iter_prefix(&account) {
+            if fetches >= MAX_FETCHES_PER_BLOCK {
+                return;
+            }
+            match <Liabilities<T>>::get(index) {
+                Some(record)
+                    if record.state == RecordState::Open
+                        && record.agreement.promisor() == &account
+                        && !<PendingFunding<T>>::contains_key(index)
+                        && !<ReportCommitments<T>>::contains_key(index) => {}
+                _ => continue,
+            }
+            if !lock_liability::<T>(index, now) {
+                continue;
+            }
+
+            fetches += 1;
+            if let Err(e) = submit_report::<T>(&gateway, index, public.clone()) {
+                debug::warn!("liability {:?} isn't finalized: {}", index, e);
+            }
+        }
+    }
+}
+
+/// Submit signed `match_orders` for markets where local key is scheduled lighthouse,
+/// does nothing when lighthouse mode isn't enabled.
+pub fn auto_match<T: Trait>(now: BlockNumber<T>) {
+    if sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, OFFCHAIN_LIGHTHOUSE_KEY)
+        .is_none()
+    {
+        return;
+    }
+
+    let accounts = local_accounts::<T>();
+    let mut submitted = 0;
+    for (market_id, _) in <Lighthouses<T>>::iter() {
+        let lighthouse = match Module::<T>::scheduled_lighthouse(market_id, now) {
+            Some(lighthouse) => lighthouse,
+            None => continue,
+        };
+        let public = match accounts.iter().find(|(_, account)| account == &lighthouse) {
+            Some((public, _)) => public.clone(),
+            None => continue,
+        };
+
+        // Transactions of the same block are submitted with consecutive nonces
+        let mut nonce = <frame_system::Module<T>>::account_nonce(&lighthouse);
+        for (demand_id, offer_id) in market_matches::<T>(market_id, now) {
+            if submitted >= MAX_MATCHES_PER_BLOCK {
+                return;
+            }
+            let key = (OFFCHAIN_MATCHED_PREFIX, demand_id, offer_id).encode();
+            if !lock::<T>(&key, now) {
+                continue;
+            }
+
+            submitted += 1;
+            let call = Call::match_orders(demand_id, offer_id);
+            match submit_signed::<T>(call, public.clone(), lighthouse.clone(), nonce) {
+                Ok(()) => nonce += One::one(),
+                Err(e) => debug::warn!(
+                    "orders {} and {} aren't matched: {}",
+                    demand_id,
+                    offer_id,
+                    e
+                ),
+            }
+        }
+    }
+}
+
+/// Compatible demand and offer pairs of market by price-time priority: demands are taken
+/// from the best one and each takes the best open offer of the same parameters.
+/// Every order is paired once, residuals of partial fills are matched in next blocks.
+pub fn market_matches<T: Trait>(
+    market_id: MarketId,
+    now: BlockNumber<T>,
+) -> Vec<(OrderId, OrderId)> {
+    let offers: Vec<(OrderId, OrderOf<T>)> = <BidsByModel<T>>::get(market_id)
+        .into_iter()
+        .filter_map(|(_, offer_id)| Some((offer_id, <Bids<T>>::get(offer_id)?)))
+        .filter(|(_, offer)| offer.deadline >= now)
+        .collect();
+    let mut paired = vec![false; offers.len()];
+
+    let mut matches = Vec::new();
+    for (_, demand_id) in <AsksByModel<T>>::get(market_id) {
+        let demand = match <Asks<T>>::get(demand_id) {
+            Some(demand) if demand.deadline >= now => demand,
+            _ => continue,
+        };
+        let position = offers.iter().zip(&paired).position(|((_, offer), paired)| {
+            !paired && offer.technics == demand.technics && offer.economics == demand.economics
+        });
+        if let Some(position) = position {
+            paired[position] = true;
+            matches.push((demand_id, offers[position].0));
+        }
+    }
+    matches
+}
+
+/// Public keys and accounts of locally held liability keys.
+fn local_accounts<T: Trait>() -> Vec<(T::Public, AccountId<T>)> {
+    <T::AuthorityId as AppCrypto<T::Public, T::Signature>>::RuntimeAppPublic::all()
+        .into_iter()
+        .map(|key| {
+            let generic =
+                <T::AuthorityId as AppCrypto<T::Public, T::Signature>>::GenericPublic::from(key);
+            let public = T::Public::from(generic);
+            (public.clone(), public.into_account())
+        })
+        .collect()
+}
+
+/// Returns `false` when liability was processed during the last `RESUBMIT_PERIOD` blocks,
+/// otherwise marks it processed at given block.
+fn lock_liability<T: Trait>(index: LiabilityIndex<T>, now: BlockNumber<T>) -> bool {
+    lock::<T>(&(OFFCHAIN_SUBMITTED_PREFIX, index).encode(), now)
+}
+
+/// Returns `false` when off-chain storage key was locked during the last `RESUBMIT_PERIOD`
+/// blocks, otherwise locks it at given block. Off-chain storage is node local: nodes sharing
+/// lighthouse key submit transactions with the same nonce, so only one of them is included.
+fn lock<T: Trait>(key: &[u8], now: BlockNumber<T>) -> bool {
+    let unlocked_before = now.saturating_sub(RESUBMIT_PERIOD.into());
+    StorageValueRef::persistent(key)
+        .mutate(
+            |processed: Option<Option<BlockNumber<T>>>| match processed {
+                Some(Some(block)) if block > unlocked_before => Err(()),
+                _ => Ok(now),
+            },
+        )
+        .map(|locked| locked.is_ok())
+        .unwrap_or(false)
+}
+
+/// Fetch liability result, sign it by promisor key and submit unsigned `finalize`.
+fn submit_report<T: Trait>(
+    gateway: &[u8],
+    index: LiabilityIndex<T>,
+    promisor: T::Public,
+) -> Result<(), &'static str> {
+    let payload = fetch_result(gateway, index.unique_saturated_into())?;
+    // Payload is decoded as SCALE byte vector, i.e. it's taken as is by `Vec<u8>` reports
+    let report = TechnicalReport::<T>::decode(&mut &payload.encode()[..])
+        .map_err(|_| "result isn't a report")?;
+    let proof = T::AuthorityId::sign(&report_payload(&index, &report), promisor)
+        .ok_or("unable to sign report")?;
+
+    let call = Call::finalize(index, report, proof);
+    SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into())
+        .map_err(|_| "unable to submit transaction")
+}
+
+/// Sign call by given local key and submit it from account with given nonce.
+fn submit_signed<T: Trait>(
+    call: Call<T>,
+    public: T::Public,
+    account: AccountId<T>,
+    nonce: T::Index,
+) -> Result<(), &'static str> {
+    let (call, signature) = <T as CreateSignedTransaction<Call<T>>>::create_transaction::<
+        T::AuthorityId,
+    >(call.into(), public, account, nonce)
+    .ok_or("unable to sign transaction")?;
+    SubmitTransaction::<T, Call<T>>::submit_transaction(call, Some(signature))
+        .map_err(|_| "unable to submit transaction")
+}
+
+/// Request liability result from IPFS gateway.
+fn fetch_result(gateway: &[u8], index: u64) -> Result<Vec<u8>, &'static str> {
+    let mut url = gateway.to_vec();
+    while url.last() == Some(&b'/') {
+        url.pop();
+    }
+    url.push(b'/');
+    push_decimal(&mut url, index);
+    let url = sp_std::str::from_utf8(&url).map_err(|_| "gateway isn't utf-8 string")?;
+
+    let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(FETCH_TIMEOUT));
+    let response = http::Request::get(url)
+        .deadline(deadline)
+        .send()
+        .map_err(|_| "unable to send request")?
+        .try_wait(deadline)
+        .map_err(|_| "gateway deadline reached")?
+        .map_err(|_| "gateway request failed")?;
+    if response.code != 200 {
+        Err("result isn't published")?
+    }
+
+    Ok(response.body().collect())
+}
+
+fn push_decimal(buffer: &mut Vec<u8>, mut value: u64) {
+    let start = buffer.len();
+    loop {
+        buffer.push(b'0' + (value % 10) as u8);
+        value /= 10;
+        if value == 0 {
+            break;
+        }
+    }
+    buffer[start..].reverse();
+}
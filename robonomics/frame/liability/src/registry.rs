@@ -0,0 +1,447 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Registry of robots identity: robot account, its owner, serial number and metadata.
+//!
+//! Owner registers robot with a deposit reserved until robot is deregistered. Serial number
+//! is unique among robots of the same owner, robot account is registered once.
+//!
+//! Owner delegates daily operation of robot to operators. Operator creates liabilities
+//! of robot on behalf of owner: owner is liability promisor, so payouts go to owner.
+//! Owner could share revenue with operator, the share is fixed when liability is created
+//! and paid from owner to operator when report is accepted.
+//!
+//! Owner declares capabilities of robot, e.g. its hardware. Liability with technics
+//! requiring capabilities is created only for robot declaring them, declarations are
+//! checked at creation, so liabilities already open aren't affected by updates.
+//!
+//! Owner rotates compromised robot key: registry entry and open liabilities of robot are
+//! moved to the new key, old key can't report liabilities anymore.
+//!
+//! Module is robots source of liability `Robots`: registry entry of promisor is stored
+//! with new liability. It's also liability lifecycle handler paying operators share,
+//! it should be set in liability `Hooks`.
+
+use codec::{Compact, Decode};
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage, ensure,
+    traits::{BalanceStatus, Currency, ExistenceRequirement, Get},
+    IterableStorageMap, StorageDoubleMap, StorageMap, StorageValue,
+};
+use frame_system::ensure_signed;
+use sp_core::H256;
+use sp_runtime::{
+    traits::{Convert, UniqueSaturatedFrom, UniqueSaturatedInto, Zero},
+    Perbill,
+};
+use sp_std::prelude::*;
+
+use crate::traits::{
+    Agreement, HookResult, NamedReservableCurrency, OnLiabilityCreated, OnReportAccepted,
+    RobotRegistry,
+};
+use crate::{
+    AccountId, BalanceOf, Capabilities, EconomicalParam, LiabilityIndex, LiabilityRobot,
+    NamedReserve, ProofParam, RobotId, RobotInfo, TechnicalParam, RESERVE_ID,
+};
+
+/// Maximal length of robot capabilities encoding in bytes, 256 capability ids.
+pub const MAX_CAPABILITIES_LENGTH: usize = 32;
+
+/// Registry entry of robot.
+pub type RobotInfoOf<T> = RobotInfo<AccountId<T>, <T as frame_system::Trait>::Hash, BalanceOf<T>>;
+
+/// Robot registry module main trait.
+pub trait Trait: crate::Trait {
+    /// Deposit reserved from owner for registered robot.
+    type RobotDeposit: Get<BalanceOf<Self>>;
+
+    /// Maximal length of robot serial number.
+    type MaxSerialLength: Get<u32>;
+
+    /// The overarching event type.
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+}
+
+decl_event! {
+    pub enum Event<T>
+    where AccountId = AccountId<T>,
+          LiabilityIndex = LiabilityIndex<T>,
+    {
+        /// Robot registered: id, robot account, owner.
+        RobotRegistered(RobotId, AccountId, AccountId),
+
+        /// Robot owner changed: id, old owner, new owner.
+        RobotTransferred(RobotId, AccountId, AccountId),
+
+        /// Robot deregistered and its deposit returned: id.
+        RobotDeregistered(RobotId),
+
+        /// Operator of robot added: id, operator.
+        OperatorAdded(RobotId, AccountId),
+
+        /// Operator of robot removed: id, operator.
+        OperatorRemoved(RobotId, AccountId),
+
+        /// Operator share of robot revenue set: id, share.
+        RevenueSplitSet(RobotId, Perbill),
+
+        /// Liability of robot created by operator: index, robot id, operator.
+        OperatedLiability(Compact<LiabilityIndex>, RobotId, AccountId),
+
+        /// Robot key rotated by owner: id, old key, new key.
+        RobotKeyRotated(RobotId, AccountId, AccountId),
+
+        /// Capabilities of robot declared by owner: id, capabilities.
+        CapabilitiesSet(RobotId, Capabilities),
+    }
+}
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        /// Serial number is longer than `MaxSerialLength`.
+        SerialTooLong,
+        /// Robot account is already registered.
+        RobotRegistered,
+        /// Owner already has robot with the same serial number.
+        DuplicateSerial,
+        /// Robot with given id isn't registered.
+        UnknownRobot,
+        /// Only owner manages robot.
+        NotOwner,
+        /// Account isn't operator of robot.
+        NotOperator,
+        /// Key rotation isn't proven by robot owner.
+        BadOwnerProof,
+        /// Capabilities are longer than `MAX_CAPABILITIES_LENGTH`.
+        CapabilitiesTooLong,
+    }
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as RobotRegistry {
+        /// Count of registered robots, next robot id.
+        RobotCount get(fn robot_count): RobotId;
+        /// Registry entry of robot.
+        Robots get(fn robot): map hasher(twox_64_concat) RobotId => Option<RobotInfoOf<T>>;
+        /// Registry entry id of robot account.
+        RobotOf get(fn robot_of): map hasher(blake2_128_concat) AccountId<T> => Option<RobotId>;
+        /// Registry entry id of robot by owner and serial number.
+        SerialOf: double_map hasher(blake2_128_concat) AccountId<T>,
+                             hasher(blake2_128_concat) Vec<u8> => Option<RobotId>;
+        /// Operators of robot.
+        Operators get(fn is_operator): double_map hasher(twox_64_concat) RobotId,
+                                       hasher(blake2_128_concat) AccountId<T> => bool;
+        /// Operators share of robot revenue, robot revenue goes to owner by default.
+        RevenueSplit get(fn revenue_split): map hasher(twox_64_concat) RobotId => Perbill;
+        /// Capabilities declared by robot owner.
+        RobotCapabilities get(fn robot_capabilities): map hasher(twox_64_concat) RobotId => Capabilities;
+        /// Operator and its revenue share of liability created by operator.
+        LiabilityOperator get(fn liability_operator): map hasher(blake2_128_concat)
+                                                      LiabilityIndex<T> => Option<(AccountId<T>, Perbill)>;
+    }
+}
+
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        type Error = Error<T>;
+
+        fn deposit_event() = default;
+
+        /// Register robot account owned by sender, `RobotDeposit` is reserved from sender.
+        #[weight = 50_000_000]
+        fn register_robot(origin, robot: AccountId<T>, serial: Vec<u8>, metadata: T::Hash) {
+            let owner = ensure_signed(origin)?;
+            ensure!(
+                serial.len() <= T::MaxSerialLength::get() as usize,
+                Error::<T>::SerialTooLong
+            );
+            ensure!(!<RobotOf<T>>::contains_key(&robot), Error::<T>::RobotRegistered);
+            ensure!(!<SerialOf<T>>::contains_key(&owner, &serial), Error::<T>::DuplicateSerial);
+
+            let deposit = T::RobotDeposit::get();
+            NamedReserve::<T>::reserve_named(&RESERVE_ID, &owner, deposit)?;
+
+            let id = <RobotCount>::get();
+            <RobotCount>::put(id + 1);
+            <RobotOf<T>>::insert(&robot, id);
+            <SerialOf<T>>::insert(&owner, &serial, id);
+            <Robots<T>>::insert(id, RobotInfo {
+                robot: robot.clone(),
+                owner: owner.clone(),
+                serial,
+                metadata,
+                deposit,
+            });
+            Self::deposit_event(RawEvent::RobotRegistered(id, robot, owner));
+        }
+
+        /// Transfer robot ownership, deposit is moved to new owner and returned to it
+        /// on deregistration. Operators and revenue split are reset.
+        #[weight = 50_000_000]
+        fn transfer_robot(origin, id: RobotId, new_owner: AccountId<T>) {
+            let sender = ensure_signed(origin)?;
+            let mut info = Self::owned_robot(id, &sender)?;
+            ensure!(
+                !<SerialOf<T>>::contains_key(&new_owner, &info.serial),
+                Error::<T>::DuplicateSerial
+            );
+
+            // Deposit is tracked by named reserve of new owner, only moved part is kept
+            let not_moved = NamedReserve::<T>::repatriate_reserved_named(
+                &RESERVE_ID,
+                &sender,
+                &new_owner,
+                info.deposit,
+                BalanceStatus::Reserved,
+            )?;
+            info.deposit -= not_moved;
+            <SerialOf<T>>::remove(&sender, &info.serial);
+            <SerialOf<T>>::insert(&new_owner, &info.serial, id);
+            <Operators<T>>::remove_prefix(id);
+            <RevenueSplit>::remove(id);
+            info.owner = new_owner.clone();
+            <Robots<T>>::insert(id, info);
+            Self::deposit_event(RawEvent::RobotTransferred(id, sender, new_owner));
+        }
+
+        /// Remove robot from registry and return its deposit, liabilities already created
+        /// keep registry entry id.
+        #[weight = 50_000_000]
+        fn deregister_robot(origin, id: RobotId) {
+            let sender = ensure_signed(origin)?;
+            let info = Self::owned_robot(id, &sender)?;
+
+            NamedReserve::<T>::unreserve_named(&RESERVE_ID, &sender, info.deposit);
+            <RobotOf<T>>::remove(&info.robot);
+            <SerialOf<T>>::remove(&sender, &info.serial);
+            <Operators<T>>::remove_prefix(id);
+            <RevenueSplit>::remove(id);
+            <RobotCapabilities>::remove(id);
+            <Robots<T>>::remove(id);
+            Self::deposit_event(RawEvent::RobotDeregistered(id));
+        }
+
+        /// Add operator of robot by owner.
+        #[weight = 50_000_000]
+        fn add_operator(origin, id: RobotId, operator: AccountId<T>) {
+            let sender = ensure_signed(origin)?;
+            Self::owned_robot(id, &sender)?;
+            <Operators<T>>::insert(id, &operator, true);
+            Self::deposit_event(RawEvent::OperatorAdded(id, operator));
+        }
+
+        /// Remove operator of robot by owner, removed operator can't create new liabilities
+        /// of robot, liabilities already created keep its share.
+        #[weight = 50_000_000]
+        fn remove_operator(origin, id: RobotId, operator: AccountId<T>) {
+            let sender = ensure_signed(origin)?;
+            Self::owned_robot(id, &sender)?;
+            ensure!(<Operators<T>>::get(id, &operator), Error::<T>::NotOperator);
+            <Operators<T>>::remove(id, &operator);
+            Self::deposit_event(RawEvent::OperatorRemoved(id, operator));
+        }
+
+        /// Set operators share of robot revenue by owner, only new liabilities are affected.
+        #[weight = 50_000_000]
+        fn set_revenue_split(origin, id: RobotId, share: Perbill) {
+            let sender = ensure_signed(origin)?;
+            Self::owned_robot(id, &sender)?;
+            <RevenueSplit>::insert(id, share);
+            Self::deposit_event(RawEvent::RevenueSplitSet(id, share));
+        }
+
+        /// Declare capabilities of robot by owner, empty capabilities remove declaration.
+        /// Only new liabilities are checked against them.
+        #[weight = 50_000_000]
+        fn set_capabilities(origin, id: RobotId, capabilities: Capabilities) {
+            let sender = ensure_signed(origin)?;
+            Self::owned_robot(id, &sender)?;
+            ensure!(
+                capabilities.0.len() <= MAX_CAPABILITIES_LENGTH,
+                Error::<T>::CapabilitiesTooLong
+            );
+            if capabilities.is_empty() {
+                <RobotCapabilities>::remove(id);
+            } else {
+                <RobotCapabilities>::insert(id, &capabilities);
+            }
+            Self::deposit_event(RawEvent::CapabilitiesSet(id, capabilities));
+        }
+
+        /// Rotate compromised robot key by any account with proof of robot owner: registry
+        /// entry is moved to the new key at once, open liabilities of robot with their
+        /// acknowledgement deposits are moved in batches, see `continue_rotation` of liability
+        /// module. Reports signed by the old key are rejected from now.
+        #[weight = 50_000_000 + <crate::Module<T>>::rotation_weight()]
+        fn rotate_promisor_key(
+            origin,
+            old: AccountId<T>,
+            new: AccountId<T>,
+            owner_proof: ProofParam<T>,
+        ) {
+            ensure_signed(origin)?;
+            let id = <RobotOf<T>>::get(&old).ok_or(Error::<T>::UnknownRobot)?;
+            let mut info = <Robots<T>>::get(id).ok_or(Error::<T>::UnknownRobot)?;
+            ensure!(!<RobotOf<T>>::contains_key(&new), Error::<T>::RobotRegistered);
+            ensure!(
+                T::Liability::verify_rotation(&old, &new, &owner_proof, &info.owner),
+                Error::<T>::BadOwnerProof
+            );
+
+            <crate::Module<T>>::rotate_promisor(&old, &new)?;
+            <RobotOf<T>>::remove(&old);
+            <RobotOf<T>>::insert(&new, id);
+            info.robot = new.clone();
+            <Robots<T>>::insert(id, info);
+            Self::deposit_event(RawEvent::RobotKeyRotated(id, old, new));
+        }
+
+        /// Create liability of robot by its operator: robot owner is promisor and operator
+        /// signs the call instead of promisor proof.
+        #[weight = crate::CREATE_WEIGHT]
+        fn create_operated(
+            origin,
+            id: RobotId,
+            technics: TechnicalParam<T>,
+            economics: EconomicalParam<T>,
+            promisee: AccountId<T>,
+            promisee_proof: ProofParam<T>,
+        ) {
+            let operator = ensure_signed(origin)?;
+            let owner = <Robots<T>>::get(id).ok_or(Error::<T>::UnknownRobot)?.owner;
+            ensure!(<Operators<T>>::get(id, &operator), Error::<T>::NotOperator);
+            <crate::Module<T>>::ensure_not_blacklisted(&promisee)?;
+            <crate::Module<T>>::ensure_not_blacklisted(&owner)?;
+            <crate::Module<T>>::ensure_not_blacklisted(&operator)?;
+            <crate::Module<T>>::ensure_approved(&owner)?;
+            <crate::Module<T>>::ensure_capabilities(&technics, || <RobotCapabilities>::get(id))?;
+            <crate::Module<T>>::ensure_identity(&economics, &promisee)?;
+
+            let liability = T::Liability::new(technics, economics, promisee.clone(), owner);
+            if !liability.check_params(&promisee_proof, &promisee) {
+                Err(crate::Error::<T>::BadPromiseeProof)?
+            }
+
+            let index = <crate::Module<T>>::start_liability(liability, Vec::new())?;
+            <LiabilityRobot<T>>::insert(index, id);
+            <LiabilityOperator<T>>::insert(index, (operator.clone(), <RevenueSplit>::get(id)));
+            Self::deposit_event(RawEvent::OperatedLiability(index.into(), id, operator));
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Registry entry of robot with given owner and serial number.
+    pub fn robot_by_serial(
+        owner: &AccountId<T>,
+        serial: &[u8],
+    ) -> Option<(RobotId, RobotInfoOf<T>)> {
+        let id = <SerialOf<T>>::get(owner, serial)?;
+        Some((id, <Robots<T>>::get(id)?))
+    }
+
+    /// Registry entry of robot managed by given owner.
+    fn owned_robot(id: RobotId, owner: &AccountId<T>) -> Result<RobotInfoOf<T>, Error<T>> {
+        let info = <Robots<T>>::get(id).ok_or(Error::<T>::UnknownRobot)?;
+        ensure!(&info.owner == owner, Error::<T>::NotOwner);
+        Ok(info)
+    }
+
+    /// Operator of liability and its share taken in settled value, the share is paid by
+    /// owner when liability succeeds.
+    fn operator_payout(index: &LiabilityIndex<T>) -> Option<(AccountId<T>, BalanceOf<T>)> {
+        let (operator, share) = <LiabilityOperator<T>>::get(index)?;
+        let liability = <crate::Module<T>>::liability(index)?;
+        let value: u128 =
+            T::SettledValue::convert(liability.economics().clone()).unique_saturated_into();
+        Some((
+            operator,
+            share.mul_floor(BalanceOf::<T>::unique_saturated_from(value)),
+        ))
+    }
+}
+
+impl<T: Trait> RobotRegistry<AccountId<T>, BalanceOf<T>> for Module<T> {
+    fn robot_id(robot: &AccountId<T>) -> Option<RobotId> {
+        <RobotOf<T>>::get(robot)
+    }
+
+    fn capabilities(robot: &AccountId<T>) -> Capabilities {
+        <RobotOf<T>>::get(robot)
+            .map(<RobotCapabilities>::get)
+            .unwrap_or_default()
+    }
+
+    fn owner(robot: &AccountId<T>) -> Option<AccountId<T>> {
+        <RobotOf<T>>::get(robot)
+            .and_then(<Robots<T>>::get)
+            .map(|info| info.owner)
+    }
+
+    fn model(robot: &AccountId<T>) -> Option<H256> {
+        <RobotOf<T>>::get(robot)
+            .and_then(<Robots<T>>::get)
+            .and_then(|info| H256::decode(&mut info.metadata.as_ref()).ok())
+    }
+
+    fn deposits() -> Vec<(AccountId<T>, BalanceOf<T>)> {
+        <Robots<T>>::iter()
+            .map(|(_, info)| (info.owner, info.deposit))
+            .collect()
+    }
+}
+
+impl<T: Trait> OnLiabilityCreated<LiabilityIndex<T>, AccountId<T>> for Module<T> {}
+
+impl<T: Trait> OnReportAccepted<LiabilityIndex<T>, AccountId<T>> for Module<T> {
+    fn on_report_accepted(
+        index: &LiabilityIndex<T>,
+        _promisee: &AccountId<T>,
+        promisor: &AccountId<T>,
+        success: bool,
+    ) -> HookResult {
+        if !success {
+            return Ok(0);
+        }
+        let (operator, payout) = match Self::operator_payout(index) {
+            Some(operator) => operator,
+            None => return Ok(T::DbWeight::get().reads(2)),
+        };
+
+        // Owner keeps the rest or pays nothing when its funds are short
+        if !payout.is_zero() {
+            let _ =
+                T::Currency::transfer(promisor, &operator, payout, ExistenceRequirement::KeepAlive);
+        }
+        Ok(T::DbWeight::get().reads_writes(3, 2))
+    }
+
+    fn preview_report_accepted(
+        index: &LiabilityIndex<T>,
+        _promisee: &AccountId<T>,
+        promisor: &AccountId<T>,
+        success: bool,
+    ) -> Vec<(AccountId<T>, AccountId<T>, u128)> {
+        match Self::operator_payout(index) {
+            Some((operator, payout)) if success && !payout.is_zero() => {
+                vec![(promisor.clone(), operator, payout.unique_saturated_into())]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,307 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Reputation of liability promisors fed by liability outcomes.
+//!
+//! Module is liability lifecycle handler, it should be set in liability `Hooks`.
+//! Accepted report increases promisor score, failed liability decreases it. Liability force
+//! finalized as failed is a dispute lost by promisor, it has its own penalty.
+//!
+//! Promisee rates finalized liability once within `RatingWindow`, ratings are aggregated
+//! separately from score.
+//!
+//! Score decays by `ScoreDecay` each era. Decay is lazy: reputation keeps era of the last
+//! update and decay of eras passed since then is applied when reputation is read or updated.
+//!
+//! Zero value liabilities are cheap to farm, only the first `ZeroValueQuota` of them
+//! finalized by promisor in era are counted by reputation.
+
+use codec::Compact;
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage, ensure, traits::Get, StorageMap,
+};
+use frame_system::ensure_signed;
+use sp_runtime::{
+    traits::{One, Saturating, UniqueSaturatedInto, Zero},
+    PerThing, Perbill,
+};
+
+use crate::traits::{
+    Agreement, HookResult, OnLiabilityCreated, OnReportAccepted, ReputationProvider,
+};
+use crate::{AccountId, BlockNumber, Liabilities, LiabilityIndex, RecordState, ReputationInfo};
+
+/// The lowest promisee rating.
+pub const MIN_RATING: u8 = 1;
+
+/// The highest promisee rating.
+pub const MAX_RATING: u8 = 5;
+
+/// Reputation module main trait.
+pub trait Trait: crate::Trait {
+    /// Score added by successfully finalized liability.
+    type CompletedReward: Get<u32>;
+
+    /// Score removed by failed liability.
+    type FailedPenalty: Get<u32>;
+
+    /// Score removed by lost dispute.
+    type DisputedPenalty: Get<u32>;
+
+    /// Maximal reputation score.
+    type MaxScore: Get<u32>;
+
+    /// Score change is notified when score crosses multiple of this step.
+    type ScoreStep: Get<u32>;
+
+    /// Count of blocks after liability finalization promisee could rate it.
+    type RatingWindow: Get<BlockNumber<Self>>;
+
+    /// Share of score retained after each era.
+    type ScoreDecay: Get<Perbill>;
+
+    /// Count of blocks of reputation era.
+    type EraLength: Get<BlockNumber<Self>>;
+
+    /// Count of zero value liabilities of promisor counted by reputation in era.
+    type ZeroValueQuota: Get<u32>;
+
+    /// The overarching event type.
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+}
+
+decl_event! {
+    pub enum Event<T>
+    where AccountId = AccountId<T>,
+          LiabilityIndex = LiabilityIndex<T>,
+    {
+        /// Promisor score crossed multiple of `ScoreStep`: account, new score.
+        ScoreChanged(AccountId, u32),
+
+        /// Liability rated by promisee: index, promisor, rating.
+        Rated(Compact<LiabilityIndex>, AccountId, u8),
+    }
+}
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        /// Rating isn't in range from `MIN_RATING` to `MAX_RATING`.
+        InvalidRating,
+        /// Liability with given index doesn't exist.
+        UnknownLiability,
+        /// Only promisee rates liability.
+        NotPromisee,
+        /// Liability isn't finalized yet.
+        NotFinalized,
+        /// Rating window of liability passed.
+        RatingWindowPassed,
+        /// Liability is already rated.
+        AlreadyRated,
+    }
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as Reputation {
+        /// Reputation of promisor account, see `reputation_of` for decayed one.
+        Reputation: map hasher(blake2_128_concat) AccountId<T> => ReputationInfo;
+        /// Promisee rating of liability.
+        Rating get(fn rating): map hasher(blake2_128_concat) LiabilityIndex<T> => Option<u8>;
+        /// Era and count of zero value liabilities of promisor counted in it.
+        ZeroValueCounted get(fn zero_value_counted): map hasher(blake2_128_concat)
+                                                     AccountId<T> => (u32, u32);
+    }
+}
+
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        type Error = Error<T>;
+
+        fn deposit_event() = default;
+
+        /// Rate finalized liability by its promisee, rating is added to aggregate rating
+        /// of promisor.
+        #[weight = 50_000_000]
+        fn rate(origin, #[compact] index: LiabilityIndex<T>, rating: u8) {
+            let sender = ensure_signed(origin)?;
+            ensure!(
+                rating >= MIN_RATING && rating <= MAX_RATING,
+                Error::<T>::InvalidRating
+            );
+            let record = <Liabilities<T>>::get(index).ok_or(Error::<T>::UnknownLiability)?;
+            let liability = &record.agreement;
+            ensure!(liability.promisee() == &sender, Error::<T>::NotPromisee);
+            ensure!(
+                !matches!(record.state, RecordState::Open | RecordState::Cancelled),
+                Error::<T>::NotFinalized
+            );
+            let now = <frame_system::Module<T>>::block_number();
+            let finalized_at = record.finalized_at.unwrap_or_else(Zero::zero);
+            ensure!(
+                now <= finalized_at.saturating_add(T::RatingWindow::get()),
+                Error::<T>::RatingWindowPassed
+            );
+            ensure!(!<Rating<T>>::contains_key(index), Error::<T>::AlreadyRated);
+
+            <Rating<T>>::insert(index, rating);
+            let promisor = liability.promisor();
+            Self::mutate_reputation(promisor, |reputation| {
+                reputation.ratings = reputation.ratings.saturating_add(1);
+                reputation.rating_sum = reputation.rating_sum.saturating_add(rating.into());
+            });
+            Self::deposit_event(RawEvent::Rated(index.into(), promisor.clone(), rating));
+        }
+    }
+}
+
+/// Outcome of finalized liability.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Outcome {
+    /// Liability finalized successfully.
+    Completed,
+    /// Liability failed.
+    Failed,
+    /// Liability force finalized as failed.
+    Disputed,
+}
+
+impl<T: Trait> Module<T> {
+    /// Reputation of account with score decayed to current era.
+    pub fn reputation_of(who: &AccountId<T>) -> ReputationInfo {
+        decay(
+            <Reputation<T>>::get(who),
+            T::ScoreDecay::get(),
+            Self::current_era(),
+        )
+    }
+
+    /// Reputation era of current block.
+    pub fn current_era() -> u32 {
+        let now = <frame_system::Module<T>>::block_number();
+        (now / T::EraLength::get().max(One::one())).unique_saturated_into()
+    }
+
+    /// Update reputation of account decayed to current era.
+    fn mutate_reputation<R>(who: &AccountId<T>, f: impl FnOnce(&mut ReputationInfo) -> R) -> R {
+        let mut reputation = Self::reputation_of(who);
+        let result = f(&mut reputation);
+        <Reputation<T>>::insert(who, reputation);
+        result
+    }
+
+    /// Count zero value liability of promisor in current era, returns `false` when quota
+    /// of era is used up.
+    fn count_zero_value(promisor: &AccountId<T>) -> bool {
+        let era = Self::current_era();
+        let (counted_era, count) = <ZeroValueCounted<T>>::get(promisor);
+        let count = if counted_era == era { count } else { 0 };
+        if count >= T::ZeroValueQuota::get() {
+            return false;
+        }
+        <ZeroValueCounted<T>>::insert(promisor, (era, count + 1));
+        true
+    }
+
+    /// Count liability outcome of promisor and update its score.
+    pub fn note_outcome(promisor: &AccountId<T>, outcome: Outcome) {
+        let (old, new) = Self::mutate_reputation(promisor, |reputation| {
+            let old = reputation.score;
+            match outcome {
+                Outcome::Completed => {
+                    reputation.completed = reputation.completed.saturating_add(1);
+                    reputation.score = old
+                        .saturating_add(T::CompletedReward::get())
+                        .min(T::MaxScore::get());
+                }
+                Outcome::Failed => {
+                    reputation.failed = reputation.failed.saturating_add(1);
+                    reputation.score = old.saturating_sub(T::FailedPenalty::get());
+                }
+                Outcome::Disputed => {
+                    reputation.disputed = reputation.disputed.saturating_add(1);
+                    reputation.score = old.saturating_sub(T::DisputedPenalty::get());
+                }
+            }
+            (old, reputation.score)
+        });
+
+        let step = T::ScoreStep::get().max(1);
+        if old / step != new / step {
+            Self::deposit_event(RawEvent::ScoreChanged(promisor.clone(), new));
+        }
+    }
+}
+
+impl<T: Trait> ReputationProvider<AccountId<T>> for Module<T> {
+    fn reputation(who: &AccountId<T>) -> u32 {
+        Self::reputation_of(who).score
+    }
+}
+
+impl<T: Trait> OnLiabilityCreated<LiabilityIndex<T>, AccountId<T>> for Module<T> {}
+
+impl<T: Trait> OnReportAccepted<LiabilityIndex<T>, AccountId<T>> for Module<T> {
+    fn on_report_accepted(
+        index: &LiabilityIndex<T>,
+        _promisee: &AccountId<T>,
+        promisor: &AccountId<T>,
+        success: bool,
+    ) -> HookResult {
+        let zero_value = <Liabilities<T>>::get(index).map_or(false, |record| {
+            <crate::Module<T>>::is_zero_value(record.agreement.economics())
+        });
+        if zero_value && !Self::count_zero_value(promisor) {
+            return Ok(T::DbWeight::get().reads(2));
+        }
+        let outcome = if success {
+            Outcome::Completed
+        } else if <crate::Module<T>>::forced_outcome(*index).is_some() {
+            Outcome::Disputed
+        } else {
+            Outcome::Failed
+        };
+        Self::note_outcome(promisor, outcome);
+        Ok(T::DbWeight::get().reads_writes(4, 2))
+    }
+}
+
+/// Share of score retained after given count of eras: decay to the power of eras.
+///
+/// Power is computed by squaring with rounding down of each product, so it is never above
+/// exact one. Share retained after the whole period is applied once, that's why it could be
+/// a bit higher than share applied each era, e.g. by one point for each era.
+pub fn decay_factor(decay: Perbill, mut eras: u32) -> Perbill {
+    let one = Perbill::ACCURACY as u64;
+    let mut base = decay.deconstruct() as u64;
+    let mut factor = one;
+    while eras > 0 && factor > 0 {
+        if eras & 1 == 1 {
+            factor = factor * base / one;
+        }
+        base = base * base / one;
+        eras >>= 1;
+    }
+    Perbill::from_parts(factor as u32)
+}
+
+/// Apply decay of eras passed since the last reputation update.
+pub fn decay(mut reputation: ReputationInfo, per_era: Perbill, era: u32) -> ReputationInfo {
+    if era > reputation.era {
+        reputation.score = decay_factor(per_era, era - reputation.era).mul_floor(reputation.score);
+        reputation.era = era;
+    }
+    reputation
+}
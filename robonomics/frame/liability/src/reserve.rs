@@ -0,0 +1,118 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Funds of module currency reserved under identifier.
+//!
+//! Reserved balance of account is shared by modules, so module that releases more than
+//! it reserved frees deposits of others. Reserves under identifier are tracked in
+//! `NamedReserves`, any release is limited by the tracked amount.
+
+use frame_support::{
+    traits::{BalanceStatus, Currency, ReservableCurrency},
+    StorageDoubleMap,
+};
+use sp_runtime::{
+    traits::{Saturating, Zero},
+    DispatchError, DispatchResult,
+};
+
+use crate::traits::{NamedReservableCurrency, ReserveIdentifier};
+use crate::{AccountId, BalanceOf, NamedReserves, NegativeImbalanceOf, Trait};
+
+/// Module currency reserves under identifier, see `NamedReservableCurrency`.
+pub struct NamedReserve<T>(sp_std::marker::PhantomData<T>);
+
+impl<T: Trait> NamedReserve<T> {
+    /// Forget amount released from reserve of account under identifier.
+    fn note_released(id: &ReserveIdentifier, who: &AccountId<T>, amount: BalanceOf<T>) {
+        let reserved = <NamedReserves<T>>::get(id, who).saturating_sub(amount);
+        if reserved.is_zero() {
+            <NamedReserves<T>>::remove(id, who);
+        } else {
+            <NamedReserves<T>>::insert(id, who, reserved);
+        }
+    }
+}
+
+impl<T: Trait> NamedReservableCurrency<AccountId<T>> for NamedReserve<T> {
+    type Balance = BalanceOf<T>;
+    type NegativeImbalance = NegativeImbalanceOf<T>;
+
+    fn reserved_balance_named(id: &ReserveIdentifier, who: &AccountId<T>) -> BalanceOf<T> {
+        <NamedReserves<T>>::get(id, who)
+    }
+
+    fn reserve_named(
+        id: &ReserveIdentifier,
+        who: &AccountId<T>,
+        value: BalanceOf<T>,
+    ) -> DispatchResult {
+        if value.is_zero() {
+            return Ok(());
+        }
+        T::Currency::reserve(who, value)?;
+        <NamedReserves<T>>::mutate(id, who, |reserved| {
+            *reserved = reserved.saturating_add(value)
+        });
+        Ok(())
+    }
+
+    fn unreserve_named(
+        id: &ReserveIdentifier,
+        who: &AccountId<T>,
+        value: BalanceOf<T>,
+    ) -> BalanceOf<T> {
+        let to_change = value.min(Self::reserved_balance_named(id, who));
+        let actual = to_change.saturating_sub(T::Currency::unreserve(who, to_change));
+        Self::note_released(id, who, actual);
+        value.saturating_sub(actual)
+    }
+
+    fn slash_reserved_named(
+        id: &ReserveIdentifier,
+        who: &AccountId<T>,
+        value: BalanceOf<T>,
+    ) -> (NegativeImbalanceOf<T>, BalanceOf<T>) {
+        let to_change = value.min(Self::reserved_balance_named(id, who));
+        let (imbalance, not_slashed) = T::Currency::slash_reserved(who, to_change);
+        Self::note_released(id, who, to_change.saturating_sub(not_slashed));
+        (
+            imbalance,
+            value.saturating_sub(to_change).saturating_add(not_slashed),
+        )
+    }
+
+    fn repatriate_reserved_named(
+        id: &ReserveIdentifier,
+        slashed: &AccountId<T>,
+        beneficiary: &AccountId<T>,
+        value: BalanceOf<T>,
+        status: BalanceStatus,
+    ) -> Result<BalanceOf<T>, DispatchError> {
+        let to_change = value.min(Self::reserved_balance_named(id, slashed));
+        let not_moved = T::Currency::repatriate_reserved(slashed, beneficiary, to_change, status)?;
+        let actual = to_change.saturating_sub(not_moved);
+        Self::note_released(id, slashed, actual);
+        // Funds reserved for beneficiary stay under the same identifier
+        if status == BalanceStatus::Reserved && !actual.is_zero() {
+            <NamedReserves<T>>::mutate(id, beneficiary, |reserved| {
+                *reserved = reserved.saturating_add(actual)
+            });
+        }
+        Ok(value.saturating_sub(actual))
+    }
+}
@@ -15,27 +15,35 @@
 //  limitations under the License.
 //
 ///////////////////////////////////////////////////////////////////////////////
-//! Signed liability implementation.
+//! Signed liability implementation and signed market orders.
 
 use codec::{Decode, Encode};
-use frame_support::{
-    dispatch,
-    traits::{BalanceStatus, ReservableCurrency},
-};
+use frame_support::dispatch;
 use frame_system::offchain::AppCrypto;
 #[cfg(feature = "std")]
 use sp_core::crypto::{Pair, Public};
+use sp_core::H256;
 use sp_runtime::{
-    traits::{IdentifyAccount, Verify},
-    DispatchResult, RuntimeDebug,
+    traits::{IdentifyAccount, UniqueSaturatedFrom, UniqueSaturatedInto, Verify},
+    DispatchResult,
 };
 
-use crate::economics::{Communism, OpenMarket};
+use crate::market::Side;
 use crate::traits::*;
+pub use robonomics_liability_core::PrehashedProof;
+use robonomics_liability_core::{
+    acknowledge_payload, amend_payload, attested_report_payload, blinded_params_payload,
+    blinded_report_payload, bounty_params_payload, cancel_payload, claim_payload, commit_payload,
+    expiring_params_payload, notarize_payload, params_payload, report_payload,
+    restricted_params_payload, rotate_payload, tagged_params_payload, witnessing_payload,
+    PromisorRequirement, Tag,
+};
 
 /// Agreement that could be proven by asymmetric cryptography.
-#[cfg_attr(feature = "std", derive(PartialEq, Eq))]
-#[derive(Encode, Decode, RuntimeDebug)]
+///
+/// Standard traits are implemented by hand, derived ones would require them of approach
+/// and signature types, e.g. `PureIPFS`, when only parameters and accounts are kept.
+#[derive(Encode, Decode)]
 pub struct SignedLiability<T, E, V, A, I>
 where
     T: Technical,
@@ -51,49 +59,106 @@ where
     _phantom: sp_std::marker::PhantomData<V>,
 }
 
-impl<T, V, A, I> Processing for SignedLiability<T, Communism, V, A, I>
+impl<T, E, V, A, I> Clone for SignedLiability<T, E, V, A, I>
 where
     T: Technical,
+    E: Economical,
     V: Verify<Signer = A>,
     A: IdentifyAccount<AccountId = I>,
     I: dispatch::Parameter,
 {
-    fn on_start(&self) -> DispatchResult {
-        Ok(())
+    fn clone(&self) -> Self {
+        SignedLiability {
+            technics: self.technics.clone(),
+            economics: self.economics.clone(),
+            promisee: self.promisee.clone(),
+            promisor: self.promisor.clone(),
+            _phantom: Default::default(),
+        }
+    }
+}
+
+impl<T, E, V, A, I> PartialEq for SignedLiability<T, E, V, A, I>
+where
+    T: Technical,
+    E: Economical,
+    V: Verify<Signer = A>,
+    A: IdentifyAccount<AccountId = I>,
+    I: dispatch::Parameter,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.technics == other.technics
+            && self.economics == other.economics
+            && self.promisee == other.promisee
+            && self.promisor == other.promisor
+    }
+}
+
+impl<T, E, V, A, I> Eq for SignedLiability<T, E, V, A, I>
+where
+    T: Technical,
+    E: Economical,
+    V: Verify<Signer = A>,
+    A: IdentifyAccount<AccountId = I>,
+    I: dispatch::Parameter,
+{
+}
+
+impl<T, E, V, A, I> sp_std::fmt::Debug for SignedLiability<T, E, V, A, I>
+where
+    T: Technical,
+    E: Economical,
+    V: Verify<Signer = A>,
+    A: IdentifyAccount<AccountId = I>,
+    I: dispatch::Parameter,
+{
+    #[cfg(feature = "std")]
+    fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+        f.debug_struct("SignedLiability")
+            .field("technics", &self.technics)
+            .field("economics", &self.economics)
+            .field("promisee", &self.promisee)
+            .field("promisor", &self.promisor)
+            .finish()
     }
-    fn on_finish(&self, _success: bool) -> DispatchResult {
-        Ok(())
+
+    #[cfg(not(feature = "std"))]
+    fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+        f.write_str("<wasm:stripped>")
     }
 }
 
-impl<T, V, A, I, C> Processing for SignedLiability<T, OpenMarket<C, I>, V, A, I>
+impl<T, E, V, A, I> Processing<I> for SignedLiability<T, E, V, A, I>
 where
     T: Technical,
+    E: Settlement<I>,
     V: Verify<Signer = A>,
     A: IdentifyAccount<AccountId = I>,
-    C: ReservableCurrency<I>,
     I: dispatch::Parameter,
 {
     fn on_start(&self) -> DispatchResult {
-        C::reserve(&self.promisee, self.economics)
+        E::on_start(&self.economics, &self.promisee, &self.promisor)
     }
 
     fn on_finish(&self, success: bool) -> DispatchResult {
-        if success {
-            C::repatriate_reserved(
-                &self.promisee,
-                &self.promisor,
-                self.economics,
-                BalanceStatus::Free,
-            )
-            .map(|_| ())
-        } else {
-            if C::unreserve(&self.promisee, self.economics) == self.economics {
-                Ok(())
-            } else {
-                Err("reserved less than expected")?
-            }
-        }
+        E::on_finish(&self.economics, &self.promisee, &self.promisor, success)
+    }
+
+    fn preview<B: UniqueSaturatedFrom<u128>>(&self, success: bool) -> Payouts<I, B> {
+        E::preview(&self.economics, &self.promisee, &self.promisor, success)
+    }
+
+    fn on_cancel<B: UniqueSaturatedInto<u128>>(&self, fee: Option<(Party, B)>) -> DispatchResult {
+        E::on_cancel(&self.economics, &self.promisee, &self.promisor, fee)
+    }
+
+    fn on_amend(&self, amended: &Self) -> DispatchResult {
+        E::on_amend(
+            &self.economics,
+            &amended.economics,
+            &self.promisee,
+            &self.promisor,
+        )
     }
 }
 
@@ -102,7 +167,7 @@ where
     T: Technical,
     E: Economical,
     A: IdentifyAccount<AccountId = I>,
-    V: Verify<Signer = A> + dispatch::Parameter,
+    V: Verify<Signer = A> + ProofScheme + dispatch::Parameter,
     I: dispatch::Parameter,
 {
     type Index = u64;
@@ -119,13 +184,167 @@ where
         }
     }
 
+    fn technics(&self) -> &T::Parameter {
+        &self.technics
+    }
+
+    fn economics(&self) -> &E::Parameter {
+        &self.economics
+    }
+
+    fn promisee(&self) -> &I {
+        &self.promisee
+    }
+
+    fn promisor(&self) -> &I {
+        &self.promisor
+    }
+
     fn check_params(&self, proof: &Self::Proof, sender: &Self::AccountId) -> bool {
-        (self.technics.clone(), self.economics.clone())
-            .using_encoded(|params| proof.verify(params, sender))
+        proof.verify(&params_payload(&self.technics, &self.economics)[..], sender)
+    }
+
+    fn check_params_until<B: Encode>(
+        &self,
+        proof: &Self::Proof,
+        sender: &Self::AccountId,
+        expires_at: &B,
+    ) -> bool {
+        let payload = expiring_params_payload(&self.technics, &self.economics, expires_at);
+        proof.verify(&payload[..], sender)
+    }
+
+    fn check_tagged_params(
+        &self,
+        proof: &Self::Proof,
+        sender: &Self::AccountId,
+        tags: &[Tag],
+    ) -> bool {
+        let payload = tagged_params_payload(&self.technics, &self.economics, tags);
+        proof.verify(&payload[..], sender)
+    }
+
+    fn check_bounty_params<B: Encode>(
+        &self,
+        proof: &Self::Proof,
+        sender: &Self::AccountId,
+        bounty: &B,
+    ) -> bool {
+        let payload = bounty_params_payload(&self.technics, &self.economics, bounty);
+        proof.verify(&payload[..], sender)
+    }
+
+    fn check_restricted_params(
+        &self,
+        proof: &Self::Proof,
+        sender: &Self::AccountId,
+        requirement: &PromisorRequirement<Self::AccountId>,
+    ) -> bool {
+        let payload = restricted_params_payload(&self.technics, &self.economics, requirement);
+        proof.verify(&payload[..], sender)
+    }
+
+    fn check_cancel<B: Encode>(
+        &self,
+        index: &Self::Index,
+        expires_at: &B,
+        proof: &Self::Proof,
+        sender: &Self::AccountId,
+    ) -> bool {
+        proof.verify(&cancel_payload(index, expires_at)[..], sender)
+    }
+
+    fn check_amendment(
+        &self,
+        index: &Self::Index,
+        nonce: u32,
+        economics: &E::Parameter,
+        proof: &Self::Proof,
+        sender: &Self::AccountId,
+    ) -> bool {
+        proof.verify(&amend_payload(index, nonce, economics)[..], sender)
+    }
+
+    fn check_commitment<H: Encode>(
+        &self,
+        index: &Self::Index,
+        commitment: &H,
+        proof: &Self::Proof,
+    ) -> bool {
+        proof.verify(&commit_payload(index, commitment)[..], &self.promisor)
+    }
+
+    fn check_witnessing(&self, index: &Self::Index, quorum: u32, proof: &Self::Proof) -> bool {
+        proof.verify(&witnessing_payload(index, quorum)[..], &self.promisor)
+    }
+
+    fn check_claim<B: Encode>(
+        &self,
+        index: &Self::Index,
+        claimed_at: &B,
+        proof: &Self::Proof,
+    ) -> bool {
+        proof.verify(&claim_payload(index, claimed_at)[..], &self.promisor)
+    }
+
+    fn check_acknowledgement(&self, index: &Self::Index, proof: &Self::Proof) -> bool {
+        proof.verify(&acknowledge_payload(index)[..], &self.promisor)
+    }
+
+    fn verify_report(
+        promisor: &I,
+        index: &Self::Index,
+        report: &T::Report,
+        proof: &Self::Proof,
+    ) -> bool {
+        proof.verify(&report_payload(index, report)[..], promisor)
+    }
+
+    fn verify_attested_report<H: Encode>(
+        promisor: &I,
+        index: &Self::Index,
+        report: &T::Report,
+        firmware: &H,
+        proof: &Self::Proof,
+    ) -> bool {
+        proof.verify(
+            &attested_report_payload(index, report, firmware)[..],
+            promisor,
+        )
+    }
+
+    fn verify_notarization<H: Encode>(
+        witness: &I,
+        index: &Self::Index,
+        report_hash: &H,
+        proof: &Self::Proof,
+    ) -> bool {
+        proof.verify(&notarize_payload(index, report_hash)[..], witness)
+    }
+
+    fn verify_blinded_params(
+        technics: &T::Parameter,
+        economics: &E::Parameter,
+        (promisee, promisor): (&H256, &H256),
+        payout: &Self::AccountId,
+        proof: &Self::Proof,
+        sender: &Self::AccountId,
+    ) -> bool {
+        let payload = blinded_params_payload(technics, economics, promisee, promisor, payout);
+        proof.verify(&payload[..], sender)
     }
 
-    fn check_report(&self, index: &Self::Index, report: &T::Report, proof: &Self::Proof) -> bool {
-        (index.clone(), report.clone()).using_encoded(|params| proof.verify(params, &self.promisor))
+    fn verify_blinded_report(
+        promisor: &Self::AccountId,
+        index: &Self::Index,
+        report: &T::Report,
+        proof: &Self::Proof,
+    ) -> bool {
+        proof.verify(&blinded_report_payload(index, report)[..], promisor)
+    }
+
+    fn verify_rotation(old: &I, new: &I, proof: &Self::Proof, owner: &I) -> bool {
+        proof.verify(&rotate_payload(old, new)[..], owner)
     }
 }
 
@@ -144,14 +363,102 @@ where
         economics: &E::Parameter,
         sender: AccountId,
     ) -> Signature {
-        (technics, economics)
-            .using_encoded(|params| AppSigner::sign(&params, sender))
+        AppSigner::sign(&params_payload(technics, economics), sender)
             .expect("unable to sign using runtime application key")
     }
 
+    fn proof_params_until<B: Encode>(
+        technics: &T::Parameter,
+        economics: &E::Parameter,
+        expires_at: &B,
+        sender: AccountId,
+    ) -> Signature {
+        let payload = expiring_params_payload(technics, economics, expires_at);
+        AppSigner::sign(&payload, sender).expect("unable to sign using runtime application key")
+    }
+
+    fn proof_tagged_params(
+        technics: &T::Parameter,
+        economics: &E::Parameter,
+        tags: &[Tag],
+        sender: AccountId,
+    ) -> Signature {
+        let payload = tagged_params_payload(technics, economics, tags);
+        AppSigner::sign(&payload, sender).expect("unable to sign using runtime application key")
+    }
+
+    fn proof_bounty_params<B: Encode>(
+        technics: &T::Parameter,
+        economics: &E::Parameter,
+        bounty: &B,
+        sender: AccountId,
+    ) -> Signature {
+        let payload = bounty_params_payload(technics, economics, bounty);
+        AppSigner::sign(&payload, sender).expect("unable to sign using runtime application key")
+    }
+
+    fn proof_restricted_params<A: Encode>(
+        technics: &T::Parameter,
+        economics: &E::Parameter,
+        requirement: &PromisorRequirement<A>,
+        sender: AccountId,
+    ) -> Signature {
+        let payload = restricted_params_payload(technics, economics, requirement);
+        AppSigner::sign(&payload, sender).expect("unable to sign using runtime application key")
+    }
+
     fn proof_report(index: &I, report: &T::Report, sender: AccountId) -> Signature {
-        (index, report)
-            .using_encoded(|params| AppSigner::sign(&params, sender))
+        AppSigner::sign(&report_payload(index, report), sender)
+            .expect("unable to sign using runtime application key")
+    }
+
+    fn proof_attested_report<H: Encode>(
+        index: &I,
+        report: &T::Report,
+        firmware: &H,
+        sender: AccountId,
+    ) -> Signature {
+        AppSigner::sign(&attested_report_payload(index, report, firmware), sender)
+            .expect("unable to sign using runtime application key")
+    }
+
+    fn proof_cancel<B: Encode>(index: &I, expires_at: &B, sender: AccountId) -> Signature {
+        AppSigner::sign(&cancel_payload(index, expires_at), sender)
+            .expect("unable to sign using runtime application key")
+    }
+
+    fn proof_amendment(
+        index: &I,
+        nonce: u32,
+        economics: &E::Parameter,
+        sender: AccountId,
+    ) -> Signature {
+        AppSigner::sign(&amend_payload(index, nonce, economics), sender)
+            .expect("unable to sign using runtime application key")
+    }
+
+    fn proof_commitment<H: Encode>(index: &I, commitment: &H, sender: AccountId) -> Signature {
+        AppSigner::sign(&commit_payload(index, commitment), sender)
+            .expect("unable to sign using runtime application key")
+    }
+
+    fn proof_claim<B: Encode>(index: &I, claimed_at: &B, sender: AccountId) -> Signature {
+        AppSigner::sign(&claim_payload(index, claimed_at), sender)
+            .expect("unable to sign using runtime application key")
+    }
+
+    fn proof_acknowledgement(index: &I, sender: AccountId) -> Signature {
+        AppSigner::sign(&acknowledge_payload(index), sender)
+            .expect("unable to sign using runtime application key")
+    }
+
+    fn proof_witnessing(index: &I, quorum: u32, sender: AccountId) -> Signature {
+        AppSigner::sign(&witnessing_payload(index, quorum), sender)
+            .expect("unable to sign using runtime application key")
+    }
+
+    fn proof_notarization<H: Encode>(index: &I, report_hash: &H, sender: AccountId) -> Signature {
+        AppSigner::sign(&notarize_payload(index, report_hash), sender)
             .expect("unable to sign using runtime application key")
     }
 }
@@ -173,10 +480,153 @@ where
     Signature: dispatch::Parameter + AsRef<[u8]>,
 {
     fn proof_params(technics: &T::Parameter, economics: &E::Parameter, sender: TPair) -> Signature {
-        (technics, economics).using_encoded(|params| sender.sign(&params))
+        sender.sign(&params_payload(technics, economics))
+    }
+
+    fn proof_params_until<B: Encode>(
+        technics: &T::Parameter,
+        economics: &E::Parameter,
+        expires_at: &B,
+        sender: TPair,
+    ) -> Signature {
+        sender.sign(&expiring_params_payload(technics, economics, expires_at))
+    }
+
+    fn proof_tagged_params(
+        technics: &T::Parameter,
+        economics: &E::Parameter,
+        tags: &[Tag],
+        sender: TPair,
+    ) -> Signature {
+        sender.sign(&tagged_params_payload(technics, economics, tags))
+    }
+
+    fn proof_bounty_params<B: Encode>(
+        technics: &T::Parameter,
+        economics: &E::Parameter,
+        bounty: &B,
+        sender: TPair,
+    ) -> Signature {
+        sender.sign(&bounty_params_payload(technics, economics, bounty))
+    }
+
+    fn proof_restricted_params<A: Encode>(
+        technics: &T::Parameter,
+        economics: &E::Parameter,
+        requirement: &PromisorRequirement<A>,
+        sender: TPair,
+    ) -> Signature {
+        sender.sign(&restricted_params_payload(technics, economics, requirement))
     }
 
     fn proof_report(index: &I, report: &T::Report, sender: TPair) -> Signature {
-        (index, report).using_encoded(|params| sender.sign(&params))
+        sender.sign(&report_payload(index, report))
+    }
+
+    fn proof_attested_report<H: Encode>(
+        index: &I,
+        report: &T::Report,
+        firmware: &H,
+        sender: TPair,
+    ) -> Signature {
+        sender.sign(&attested_report_payload(index, report, firmware))
+    }
+
+    fn proof_cancel<B: Encode>(index: &I, expires_at: &B, sender: TPair) -> Signature {
+        sender.sign(&cancel_payload(index, expires_at))
+    }
+
+    fn proof_amendment(
+        index: &I,
+        nonce: u32,
+        economics: &E::Parameter,
+        sender: TPair,
+    ) -> Signature {
+        sender.sign(&amend_payload(index, nonce, economics))
     }
+
+    fn proof_commitment<H: Encode>(index: &I, commitment: &H, sender: TPair) -> Signature {
+        sender.sign(&commit_payload(index, commitment))
+    }
+
+    fn proof_claim<B: Encode>(index: &I, claimed_at: &B, sender: TPair) -> Signature {
+        sender.sign(&claim_payload(index, claimed_at))
+    }
+
+    fn proof_acknowledgement(index: &I, sender: TPair) -> Signature {
+        sender.sign(&acknowledge_payload(index))
+    }
+
+    fn proof_witnessing(index: &I, quorum: u32, sender: TPair) -> Signature {
+        sender.sign(&witnessing_payload(index, quorum))
+    }
+
+    fn proof_notarization<H: Encode>(index: &I, report_hash: &H, sender: TPair) -> Signature {
+        sender.sign(&notarize_payload(index, report_hash))
+    }
+}
+
+/// Domain of signed order payload, order signature isn't valid as any other proof.
+pub const ORDER_DOMAIN: &[u8] = b"robonomics::liability::order";
+
+/// Market order signed by its creator, e.g. for off-chain matching services that land
+/// only the final `create`. Compatible signed orders of both sides prove an agreement,
+/// so party that reneged on it could be claimed, see `claim_order_breach` call.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct SignedOrder<TechnicalParam, EconomicalParam, AccountId, BlockNumber, Signature> {
+    /// Demand of promisee or offer of promisor.
+    pub side: Side,
+    /// Technical parameter of liability.
+    pub technics: TechnicalParam,
+    /// Economical parameter of liability.
+    pub economics: EconomicalParam,
+    /// Order creator.
+    pub sender: AccountId,
+    /// Liability should be created up to this block.
+    pub expiry: BlockNumber,
+    /// Order number of creator, each order is claimed once.
+    pub nonce: u64,
+    /// Creator signature of order payload.
+    pub signature: Signature,
+}
+
+impl<TechnicalParam, EconomicalParam, AccountId, BlockNumber, Signature>
+    SignedOrder<TechnicalParam, EconomicalParam, AccountId, BlockNumber, Signature>
+{
+    /// Order payload signed by creator: domain, side, parameters, expiry and nonce.
+    pub fn payload(
+        &self,
+    ) -> (
+        &'static [u8],
+        Side,
+        &TechnicalParam,
+        &EconomicalParam,
+        &BlockNumber,
+        u64,
+    ) {
+        (
+            ORDER_DOMAIN,
+            self.side,
+            &self.technics,
+            &self.economics,
+            &self.expiry,
+            self.nonce,
+        )
+    }
+}
+
+/// Returns `true` when order payload is signed by order creator.
+pub fn verify_order<TechnicalParam, EconomicalParam, BlockNumber, V, A, I>(
+    order: &SignedOrder<TechnicalParam, EconomicalParam, I, BlockNumber, V>,
+) -> bool
+where
+    TechnicalParam: Encode,
+    EconomicalParam: Encode,
+    BlockNumber: Encode,
+    V: Verify<Signer = A>,
+    A: IdentifyAccount<AccountId = I>,
+{
+    order
+        .payload()
+        .using_encoded(|payload| order.signature.verify(payload, &order.sender))
 }
@@ -18,9 +18,13 @@
 //! Set of approaches to handle technical aspects of agreement.
 
 use crate::traits::{RealWorldOracle, Technical};
+use crate::{technics_hash, Capabilities};
+use sp_core::H256;
+use sp_runtime::RuntimeDebug;
 use sp_std::prelude::*;
 
 /// Using IPFS to handle technical aspects of agreement without confirmation.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
 pub struct PureIPFS;
 impl Technical for PureIPFS {
     // IPFS hash of objective as parameter for liability.
@@ -29,6 +33,42 @@ impl Technical for PureIPFS {
     type Report = Vec<u8>;
     // No confirmation from real world (unsafe, be careful).
     type Oracle = ();
+
+    // Results of works are objective of the next one, empty report has no results.
+    fn derive_next(report: &Self::Report) -> Option<Self::Parameter> {
+        if report.is_empty() {
+            None
+        } else {
+            Some(report.clone())
+        }
+    }
+
+    // Objective of IPFS liability describes behavioral model of robot.
+    fn model(technics: &Self::Parameter) -> Option<H256> {
+        Some(technics_hash(technics))
+    }
+}
+
+/// Technics of `T` with capabilities required from promisor robot, e.g. hardware
+/// needed by job. The next liability requires no capabilities.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct RequiredCapabilities<T>(sp_std::marker::PhantomData<T>);
+impl<T: Technical> Technical for RequiredCapabilities<T> {
+    type Parameter = (T::Parameter, Capabilities);
+    type Report = T::Report;
+    type Oracle = T::Oracle;
+
+    fn derive_next(report: &Self::Report) -> Option<Self::Parameter> {
+        T::derive_next(report).map(|next| (next, Capabilities::default()))
+    }
+
+    fn model(technics: &Self::Parameter) -> Option<H256> {
+        T::model(&technics.0)
+    }
+
+    fn required_capabilities(technics: &Self::Parameter) -> Capabilities {
+        technics.1.clone()
+    }
 }
 
 /// Noop oracle.
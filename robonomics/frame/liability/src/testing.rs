@@ -0,0 +1,560 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Test utilities of liability module for mock runtimes, enabled by `test-utils` feature.
+//!
+//! Mock runtime with `AccountId32` accounts and `MultiSignature` signing types gets the module
+//! trait implemented by `impl_mock_liability_trait!` with no-op economics and permissive limits.
+
+use codec::Encode;
+use frame_support::traits::Get;
+use sp_core::{crypto::Pair, sr25519, H256};
+use sp_runtime::{
+    traits::{IdentifyAccount, Verify},
+    AccountId32, DispatchResult, MultiSignature, RuntimeDebug,
+};
+
+use crate::signed::{PrehashedProof, SignedLiability};
+use crate::traits::*;
+use pallet_robonomics_liability_rpc_runtime_api::{
+    acknowledge_payload, amend_payload, blinded_params_payload, blinded_report_payload,
+    bounty_params_payload, cancel_payload, claim_payload, commit_payload, expiring_params_payload,
+    notarize_payload, params_payload, report_payload, restricted_params_payload, rotate_payload,
+    tagged_params_payload, witnessing_payload, PayloadHasher, PromisorRequirement, Tag,
+};
+
+#[doc(hidden)]
+pub use frame_system;
+
+/// Deterministic technics of mock runtimes: raw bytes objective and report, report of complete
+/// works is objective of the next one.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct MockTechnics;
+impl Technical for MockTechnics {
+    type Parameter = Vec<u8>;
+    type Report = Vec<u8>;
+    type Oracle = ();
+
+    fn derive_next(report: &Self::Report) -> Option<Self::Parameter> {
+        Some(report.clone())
+    }
+}
+
+/// Economics of mock runtimes, nothing is reserved and nothing is required from parties.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct MockEconomics;
+impl Economical for MockEconomics {
+    type Parameter = ();
+}
+
+impl Divisible for MockEconomics {
+    fn scale(_unit: &(), _quantity: u32) {}
+}
+
+impl Reputable for MockEconomics {}
+
+impl Attestable for MockEconomics {}
+
+impl<B> Valuable<B> for MockEconomics {}
+
+impl Marketable for MockEconomics {}
+
+impl Deferrable for MockEconomics {}
+
+impl<A> Payable<A> for MockEconomics {}
+
+impl<A> Settlement<A> for MockEconomics {
+    fn on_start(_economics: &(), _promisee: &A, _promisor: &A) -> DispatchResult {
+        Ok(())
+    }
+
+    fn on_finish(_economics: &(), _promisee: &A, _promisor: &A, _success: bool) -> DispatchResult {
+        Ok(())
+    }
+}
+
+/// Liability of mock runtimes signed by parties.
+pub type MockLiability = SignedLiability<
+    MockTechnics,
+    MockEconomics,
+    MultiSignature,
+    <MultiSignature as Verify>::Signer,
+    AccountId32,
+>;
+
+macro_rules! mock_constants {
+    ($($(#[$attr:meta])* $name:ident = $value:expr;)*) => {$(
+        $(#[$attr])*
+        pub struct $name;
+        impl<N: From<u32>> Get<N> for $name {
+            fn get() -> N {
+                N::from($value)
+            }
+        }
+    )*};
+}
+
+mock_constants! {
+    /// Periods in blocks of mock runtimes, e.g. challenge window of reports.
+    MockBlocks = 10;
+    /// Count limits of mock runtimes, e.g. unsigned calls in a block.
+    MockLimit = 16;
+    /// Encoded size limits of technics and reports of mock runtimes.
+    MockLength = 4_100;
+    /// Transaction pool priority of unsigned `finalize` in mock runtimes.
+    MockPriority = 1_000;
+}
+
+/// Account of given seed URI, e.g. `//Alice`.
+pub fn make_account(seed: &str) -> AccountId32 {
+    let pair = sr25519::Pair::from_string(seed, None).expect("valid seed URI");
+    <MultiSignature as Verify>::Signer::from(pair.public()).into_account()
+}
+
+/// Account of given seed URI and its proof of liability parameters.
+pub fn make_party_proof<TechnicalParam: Encode, EconomicalParam: Encode>(
+    seed: &str,
+    technics: &TechnicalParam,
+    economics: &EconomicalParam,
+) -> (AccountId32, MultiSignature) {
+    let pair = sr25519::Pair::from_string(seed, None).expect("valid seed URI");
+    let signature = pair.sign(&params_payload(technics, economics));
+    (make_account(seed), signature.into())
+}
+
+/// Account of given seed URI and its proof of liability parameters valid until `expires_at`
+/// block inclusive, ready for `create_expiring` call.
+pub fn make_expiring_party_proof<TechnicalParam, EconomicalParam, BlockNumber>(
+    seed: &str,
+    technics: &TechnicalParam,
+    economics: &EconomicalParam,
+    expires_at: &BlockNumber,
+) -> (AccountId32, MultiSignature)
+where
+    TechnicalParam: Encode,
+    EconomicalParam: Encode,
+    BlockNumber: Encode,
+{
+    let pair = sr25519::Pair::from_string(seed, None).expect("valid seed URI");
+    let signature = pair.sign(&expiring_params_payload(technics, economics, expires_at));
+    (make_account(seed), signature.into())
+}
+
+/// Account of given seed URI and its proof of liability parameters with tags, ready for
+/// promisee proof of `create_tagged` call.
+pub fn make_tagged_party_proof<TechnicalParam: Encode, EconomicalParam: Encode>(
+    seed: &str,
+    technics: &TechnicalParam,
+    economics: &EconomicalParam,
+    tags: &[Tag],
+) -> (AccountId32, MultiSignature) {
+    let pair = sr25519::Pair::from_string(seed, None).expect("valid seed URI");
+    let signature = pair.sign(&tagged_params_payload(technics, economics, tags));
+    (make_account(seed), signature.into())
+}
+
+/// Account of given seed URI and its proof of liability parameters with relay bounty, ready
+/// for promisee proof of `create_with_bounty` call.
+pub fn make_bounty_party_proof<TechnicalParam: Encode, EconomicalParam: Encode, Balance: Encode>(
+    seed: &str,
+    technics: &TechnicalParam,
+    economics: &EconomicalParam,
+    bounty: &Balance,
+) -> (AccountId32, MultiSignature) {
+    let pair = sr25519::Pair::from_string(seed, None).expect("valid seed URI");
+    let signature = pair.sign(&bounty_params_payload(technics, economics, bounty));
+    (make_account(seed), signature.into())
+}
+
+/// Account of given seed URI and its proof of liability parameters with requirement to
+/// promisor, ready for promisee proof of `create_restricted` call.
+pub fn make_restricted_party_proof<TechnicalParam: Encode, EconomicalParam: Encode>(
+    seed: &str,
+    technics: &TechnicalParam,
+    economics: &EconomicalParam,
+    requirement: &PromisorRequirement<AccountId32>,
+) -> (AccountId32, MultiSignature) {
+    let pair = sr25519::Pair::from_string(seed, None).expect("valid seed URI");
+    let signature = pair.sign(&restricted_params_payload(technics, economics, requirement));
+    (make_account(seed), signature.into())
+}
+
+/// Promisee and promisor accounts of given seed URIs with their proofs of liability
+/// parameters, ready for `create` call.
+pub fn make_agreement_proofs<TechnicalParam: Encode, EconomicalParam: Encode>(
+    promisee: &str,
+    promisor: &str,
+    technics: &TechnicalParam,
+    economics: &EconomicalParam,
+) -> ((AccountId32, MultiSignature), (AccountId32, MultiSignature)) {
+    (
+        make_party_proof(promisee, technics, economics),
+        make_party_proof(promisor, technics, economics),
+    )
+}
+
+/// Proof of liability report made by promisor of given seed URI.
+pub fn make_report_proof<Index: Encode, TechnicalReport: Encode>(
+    seed: &str,
+    index: &Index,
+    report: &TechnicalReport,
+) -> MultiSignature {
+    let pair = sr25519::Pair::from_string(seed, None).expect("valid seed URI");
+    pair.sign(&report_payload(index, report)).into()
+}
+
+/// Proof of liability cancel valid until given block made by party of given seed URI.
+pub fn make_cancel_proof<Index: Encode, BlockNumber: Encode>(
+    seed: &str,
+    index: &Index,
+    expires_at: &BlockNumber,
+) -> MultiSignature {
+    let pair = sr25519::Pair::from_string(seed, None).expect("valid seed URI");
+    pair.sign(&cancel_payload(index, expires_at)).into()
+}
+
+/// Proof of commitment to liability report made by promisor of given seed URI.
+pub fn make_commit_proof<Index: Encode, Commitment: Encode>(
+    seed: &str,
+    index: &Index,
+    commitment: &Commitment,
+) -> MultiSignature {
+    let pair = sr25519::Pair::from_string(seed, None).expect("valid seed URI");
+    pair.sign(&commit_payload(index, commitment)).into()
+}
+
+/// Proof of claim that liability report arrived at given block made by promisor of given
+/// seed URI.
+pub fn make_claim_proof<Index: Encode, BlockNumber: Encode>(
+    seed: &str,
+    index: &Index,
+    claimed_at: &BlockNumber,
+) -> MultiSignature {
+    let pair = sr25519::Pair::from_string(seed, None).expect("valid seed URI");
+    pair.sign(&claim_payload(index, claimed_at)).into()
+}
+
+/// Proof of amendment of liability economics made by party of given seed URI.
+pub fn make_amend_proof<Index: Encode, EconomicalParam: Encode>(
+    seed: &str,
+    index: &Index,
+    nonce: u32,
+    economics: &EconomicalParam,
+) -> MultiSignature {
+    let pair = sr25519::Pair::from_string(seed, None).expect("valid seed URI");
+    pair.sign(&amend_payload(index, nonce, economics)).into()
+}
+
+/// Proof of liability acknowledgement made by promisor of given seed URI.
+pub fn make_acknowledge_proof<Index: Encode>(seed: &str, index: &Index) -> MultiSignature {
+    let pair = sr25519::Pair::from_string(seed, None).expect("valid seed URI");
+    pair.sign(&acknowledge_payload(index)).into()
+}
+
+/// Robot owner proof of promisor key rotation made by owner of given seed URI.
+pub fn make_rotation_proof(seed: &str, old: &AccountId32, new: &AccountId32) -> MultiSignature {
+    let pair = sr25519::Pair::from_string(seed, None).expect("valid seed URI");
+    pair.sign(&rotate_payload(old, new)).into()
+}
+
+/// Prehashed proof of given payload, e.g. `params_payload`, made by party of given seed URI
+/// signing payload digest by given hash function.
+pub fn make_prehashed_proof(
+    seed: &str,
+    hasher: PayloadHasher,
+    payload: &[u8],
+) -> PrehashedProof<MultiSignature> {
+    let pair = sr25519::Pair::from_string(seed, None).expect("valid seed URI");
+    PrehashedProof::Prehashed(hasher, pair.sign(&hasher.digest(payload)).into())
+}
+
+/// Proof of consent to settle liability by witness quorum made by promisor of given seed URI.
+pub fn make_witnessing_proof<Index: Encode>(
+    seed: &str,
+    index: &Index,
+    quorum: u32,
+) -> MultiSignature {
+    let pair = sr25519::Pair::from_string(seed, None).expect("valid seed URI");
+    pair.sign(&witnessing_payload(index, quorum)).into()
+}
+
+/// Proof of notarization of liability report made by witness of given seed URI.
+pub fn make_notarize_proof<Index: Encode, Hash: Encode>(
+    seed: &str,
+    index: &Index,
+    report_hash: &Hash,
+) -> MultiSignature {
+    let pair = sr25519::Pair::from_string(seed, None).expect("valid seed URI");
+    pair.sign(&notarize_payload(index, report_hash)).into()
+}
+
+/// Proof of blinded liability parameters with given party commitments and payout account
+/// made by party of given seed URI.
+pub fn make_blinded_params_proof<TechnicalParam: Encode, EconomicalParam: Encode>(
+    seed: &str,
+    technics: &TechnicalParam,
+    economics: &EconomicalParam,
+    parties: (&H256, &H256),
+    payout: &AccountId32,
+) -> MultiSignature {
+    let pair = sr25519::Pair::from_string(seed, None).expect("valid seed URI");
+    let payload = blinded_params_payload(technics, economics, parties.0, parties.1, payout);
+    pair.sign(&payload).into()
+}
+
+/// Proof of blinded liability report made by promisor of given seed URI.
+pub fn make_blinded_report_proof<Index: Encode, TechnicalReport: Encode>(
+    seed: &str,
+    index: &Index,
+    report: &TechnicalReport,
+) -> MultiSignature {
+    let pair = sr25519::Pair::from_string(seed, None).expect("valid seed URI");
+    pair.sign(&blinded_report_payload(index, report)).into()
+}
+
+/// Implement liability module trait for mock runtime with given event and currency types.
+///
+/// Runtime should use `AccountId32` accounts and `MultiSignature` signing types, liabilities
+/// are `MockLiability` with `MockTechnics` and `MockEconomics`. Whitelist, identity,
+/// reputation and robots requirements are disabled, deposits and fees are zero.
+#[macro_export]
+macro_rules! impl_mock_liability_trait {
+    ($runtime:ty, $event:ty, $currency:ty) => {
+        impl $crate::Trait for $runtime {
+            type Event = $event;
+            type Balance = u128;
+            type SettledValue = $crate::economics::Priceless;
+            type StatsPeriod = ();
+            type StatsEraLength = ();
+            type StatsRetention = $crate::testing::MockLimit;
+            type CreatedRetention = ();
+            type DigestBloomBits = ();
+            type DigestBloomHashes = ();
+            type MaxUnsignedPerBlock = $crate::testing::MockLimit;
+            type MaxPairPerBlock = $crate::testing::MockLimit;
+            type MaxTechnicsLength = $crate::testing::MockLength;
+            type MaxReportLength = $crate::testing::MockLength;
+            type MaxTags = $crate::testing::MockLimit;
+            type MaxTaggedLiabilities = $crate::testing::MockLimit;
+            type MaxAllowedPromisors = $crate::testing::MockLimit;
+            type PromisorWhitelist = ();
+            type AdminOrigin = $crate::testing::frame_system::EnsureRoot<$crate::AccountId<Self>>;
+            type IdentityVerifier = ();
+            type IdentityThreshold = ();
+            type ReputationProvider = ();
+            type Robots = ();
+            type MinForceFinalizeAge = ();
+            type MinReportDelay = ();
+            type Currency = $currency;
+            type OrderDeposit = ();
+            type MaxOrdersPerMarket = $crate::testing::MockLimit;
+            type MaxOpenOrdersPerAccount = $crate::testing::MockLimit;
+            type ReportPreimageDeposit = ();
+            type ReportChallengeWindow = $crate::testing::MockBlocks;
+            type MinFillQuantity = ();
+            type ExpiredOrderSlash = ();
+            type MaxSweepItemsPerBlock = $crate::testing::MockLimit;
+            type MaxRecentMatches = $crate::testing::MockLimit;
+            type MatchingReward = $crate::economics::Priceless;
+            type Market = $crate::market::SingleMarket;
+            type LighthouseMinStake = ();
+            type MaxLighthouses = $crate::testing::MockLimit;
+            type LighthouseSlot = $crate::testing::MockBlocks;
+            type LighthouseFeeShare = ();
+            type MatchCommitWindow = $crate::testing::MockBlocks;
+            type MaxMatchCommits = $crate::testing::MockLimit;
+            type MarketFeeDestination = ();
+            type ValueFee = ();
+            type MinValueFee = ();
+            type MaxValueFee = ();
+            type BreachClaimPeriod = $crate::testing::MockBlocks;
+            type FundingDeadline = $crate::testing::MockBlocks;
+            type RevealWindow = $crate::testing::MockBlocks;
+            type ClaimTolerance = $crate::testing::MockBlocks;
+            type WitnessMinStake = ();
+            type MaxWitnesses = $crate::testing::MockLimit;
+            type WitnessSlash = ();
+            type Randomness = ();
+            type PoolMinStake = ();
+            type MaxPoolSize = $crate::testing::MockLimit;
+            type AssignmentDelay = $crate::testing::MockBlocks;
+            type AcknowledgementWindow = ();
+            type AcknowledgementDeposit = ();
+            type MaxLiabilityLifetime = ();
+            type ExpiryIncentive = ();
+            type ZeroValueDeposit = ();
+            type MaxZeroValuePerEra = ();
+            type OrderBreachSlash = ();
+            type CreatePriority = ();
+            type FinalizePriority = $crate::testing::MockPriority;
+            type RemoteOrigin =
+                $crate::testing::frame_system::EnsureSigned<$crate::AccountId<Self>>;
+            type BridgeOrigin = $crate::testing::frame_system::EnsureRoot<$crate::AccountId<Self>>;
+            type Legacy = ();
+            type AuthorityId = $crate::crypto::ReportAuthId;
+            type Hooks = ();
+            type MaxHooksWeight = ();
+            type WeightInfo = ();
+            type Technics = $crate::testing::MockTechnics;
+            type Economics = $crate::testing::MockEconomics;
+            type Liability = $crate::testing::MockLiability;
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate as liability;
+    use crate::{Call, Module, Trait};
+    use frame_support::{assert_ok, impl_outer_event, impl_outer_origin, parameter_types};
+    use frame_system::offchain::{
+        AppCrypto, CreateSignedTransaction, SendTransactionTypes, SigningTypes,
+    };
+    use sp_core::H256;
+    use sp_runtime::{
+        testing::{Header, TestXt},
+        traits::IdentityLookup,
+        BuildStorage, Perbill,
+    };
+
+    impl_outer_event! {
+        pub enum MetaEvent for Runtime {
+            frame_system<T>, pallet_balances<T>, liability<T>,
+        }
+    }
+
+    impl_outer_origin! {
+        pub enum Origin for Runtime {}
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    pub struct Runtime;
+
+    parameter_types! {
+        pub const BlockHashCount: u64 = 250;
+        pub const MaximumBlockWeight: u64 = 1024;
+        pub const MaximumBlockLength: u32 = 2 * 1024;
+        pub const AvailableBlockRatio: Perbill = Perbill::one();
+        pub const ExistentialDeposit: u128 = 1;
+    }
+
+    impl frame_system::Trait for Runtime {
+        type Origin = Origin;
+        type Index = u64;
+        type BlockNumber = u64;
+        type Call = ();
+        type Hash = H256;
+        type Hashing = ::sp_runtime::traits::BlakeTwo256;
+        type AccountId = AccountId32;
+        type Lookup = IdentityLookup<Self::AccountId>;
+        type Header = Header;
+        type Event = MetaEvent;
+        type BlockHashCount = BlockHashCount;
+        type MaximumBlockWeight = MaximumBlockWeight;
+        type MaximumBlockLength = MaximumBlockLength;
+        type AvailableBlockRatio = AvailableBlockRatio;
+        type Version = ();
+        type ModuleToIndex = ();
+        type AccountData = pallet_balances::AccountData<u128>;
+        type OnNewAccount = ();
+        type OnKilledAccount = ();
+        type DbWeight = ();
+        type BlockExecutionWeight = ();
+        type ExtrinsicBaseWeight = ();
+        type MaximumExtrinsicWeight = ();
+        type BaseCallFilter = ();
+        type SystemWeightInfo = ();
+    }
+
+    impl SigningTypes for Runtime {
+        type Public = <MultiSignature as Verify>::Signer;
+        type Signature = MultiSignature;
+    }
+
+    impl<C> SendTransactionTypes<C> for Runtime
+    where
+        Call<Runtime>: From<C>,
+    {
+        type OverarchingCall = Call<Runtime>;
+        type Extrinsic = TestXt<Call<Runtime>, ()>;
+    }
+
+    impl<C> CreateSignedTransaction<C> for Runtime
+    where
+        Call<Runtime>: From<C>,
+    {
+        fn create_transaction<A: AppCrypto<Self::Public, Self::Signature>>(
+            call: Call<Runtime>,
+            _public: Self::Public,
+            _account: AccountId32,
+            nonce: u64,
+        ) -> Option<(Call<Runtime>, (u64, ()))> {
+            Some((call, (nonce, ())))
+        }
+    }
+
+    impl pallet_balances::Trait for Runtime {
+        type Balance = u128;
+        type DustRemoval = ();
+        type Event = MetaEvent;
+        type ExistentialDeposit = ExistentialDeposit;
+        type AccountStore = frame_system::Module<Runtime>;
+        type WeightInfo = ();
+    }
+
+    crate::impl_mock_liability_trait!(Runtime, MetaEvent, pallet_balances::Module<Runtime>);
+
+    type Liability = Module<Runtime>;
+
+    #[test]
+    fn test_mock_runtime() {
+        let storage = frame_system::GenesisConfig::default()
+            .build_storage::<Runtime>()
+            .unwrap();
+        sp_io::TestExternalities::from(storage).execute_with(|| {
+            frame_system::Module::<Runtime>::set_block_number(1);
+            let technics = b"objective".to_vec();
+            let ((promisee, promisee_proof), (promisor, promisor_proof)) =
+                make_agreement_proofs("//Alice", "//Bob", &technics, &());
+            assert_eq!(promisee, make_account("//Alice"));
+            assert_ok!(Liability::create(
+                Origin::none(),
+                technics.clone(),
+                (),
+                promisee,
+                promisor,
+                promisee_proof,
+                promisor_proof
+            ));
+
+            let report = b"result".to_vec();
+            let proof = make_report_proof("//Bob", &0u64, &report);
+            assert_ok!(Liability::finalize(
+                Origin::none(),
+                0,
+                report.clone(),
+                proof
+            ));
+            assert_eq!(Liability::report_of(0), Some(report.clone()));
+            assert_eq!(
+                <<Runtime as Trait>::Technics as Technical>::derive_next(&report),
+                Some(report)
+            );
+        });
+    }
+}
@@ -17,9 +17,24 @@
 ///////////////////////////////////////////////////////////////////////////////
 //! Robonomics runtime traits definitions.
 
-use frame_support::dispatch;
-use sp_arithmetic::traits::BaseArithmetic;
-use sp_runtime::{traits::Member, DispatchResult};
+use codec::{Decode, Encode, HasCompact};
+use frame_support::{
+    dispatch,
+    storage::{with_transaction, TransactionOutcome},
+    traits::BalanceStatus,
+    weights::Weight,
+};
+use impl_trait_for_tuples::impl_for_tuples;
+use sp_arithmetic::traits::{
+    AtLeast32BitUnsigned, BaseArithmetic, UniqueSaturatedFrom, UniqueSaturatedInto,
+};
+use sp_core::H256;
+use sp_runtime::{traits::Member, DispatchError, DispatchResult, RuntimeDebug};
+use sp_std::prelude::*;
+
+use crate::market::MarketId;
+use crate::{Capabilities, LegacyRecord, Payouts, PromisorRequirement, RobotId, Tag};
+pub use robonomics_liability_core::{ProofScheme, SignatureScheme};
 
 /// Technical aspects of agreement between two parties.
 pub trait Technical {
@@ -37,6 +52,27 @@ pub trait Technical {
 
     /// Someone who can confirm task execution in real world.
     type Oracle: RealWorldOracle;
+
+    /// Technical parameter of follow-up liability seeded by report of complete works, e.g.
+    /// for pipelines of liabilities. Reports that couldn't seed it, like failure ones,
+    /// return `None`.
+    fn derive_next(_report: &Self::Report) -> Option<Self::Parameter> {
+        None
+    }
+
+    /// Hash of model executed by liability with given technical parameter, e.g. behavioral
+    /// model of robot, execution statistics are kept per model. Unstructured parameters
+    /// return `None`.
+    fn model(_technics: &Self::Parameter) -> Option<H256> {
+        None
+    }
+
+    /// Capabilities promisor robot should declare to take liability with given technical
+    /// parameter, the parameter is signed by promisee with them. Nothing is required by
+    /// default.
+    fn required_capabilities(_technics: &Self::Parameter) -> Capabilities {
+        Capabilities::default()
+    }
 }
 
 /// The arbiter of the real world.
@@ -51,26 +87,172 @@ pub trait Economical {
     type Parameter: dispatch::Parameter;
 }
 
+/// Economical aspects of agreement for fungible jobs, e.g. price of single sample.
+pub trait Divisible: Economical {
+    /// Economical parameter of `quantity` units with given parameter of single one.
+    fn scale(unit: &Self::Parameter, quantity: u32) -> Self::Parameter;
+}
+
+/// Economical aspects of agreement that could require promisor reputation.
+pub trait Reputable: Economical {
+    /// Minimal promisor reputation score required by economical parameter,
+    /// zero when it isn't required.
+    fn min_reputation(_economics: &Self::Parameter) -> u32 {
+        0
+    }
+}
+
+/// Economical aspects of agreement that could require attested firmware of promisor.
+pub trait Attestable: Economical {
+    /// Returns `true` when report should be attested by firmware approved for robot model.
+    fn strict_firmware(_economics: &Self::Parameter) -> bool {
+        false
+    }
+}
+
+/// Economical aspects of agreement with monetary value in given funds, e.g. price.
+pub trait Valuable<Balance>: Economical {
+    /// Value of economical parameter, `None` for non-monetary economics.
+    fn value(_economics: &Self::Parameter) -> Option<Balance> {
+        None
+    }
+}
+
+/// Economical aspects of agreement that could reference market of liability, e.g. job
+/// category with its own fee and challenge window.
+pub trait Marketable: Economical {
+    /// Market referenced by economical parameter, `None` when market is taken from
+    /// technical parameter.
+    fn market(_economics: &Self::Parameter) -> Option<MarketId> {
+        None
+    }
+}
+
+/// Economical aspects of agreement that could be funded after creation, e.g. when promisee
+/// budget isn't cleared yet.
+pub trait Deferrable: Economical {
+    /// Returns `true` when liability is created without economical processing and started
+    /// when promisee funds it.
+    fn deferred_funding(_economics: &Self::Parameter) -> bool {
+        false
+    }
+}
+
+/// Economical aspects of agreement that could name account paid instead of promisor.
+pub trait Payable<AccountId>: Economical {
+    /// Account receiving settled value of liability instead of promisor, `None` when it's
+    /// taken from promisor preferences.
+    fn payout(_economics: &Self::Parameter) -> Option<AccountId> {
+        None
+    }
+}
+
 /// Transaction processing for economical aspects of agreement. Usually it consists of
 /// balance locking and transfers when liability successfully finished.
-pub trait Processing {
+pub trait Processing<AccountId> {
     /// This method called each time when liability started.
     fn on_start(&self) -> DispatchResult;
 
     /// This method called each time when liability finished.
     fn on_finish(&self, success: bool) -> DispatchResult;
+
+    /// Funds moved by `on_finish` with given outcome, computed without moving them.
+    fn preview<B: UniqueSaturatedFrom<u128>>(&self, success: bool) -> Payouts<AccountId, B>;
+
+    /// This method called when liability cancelled before report, unilateral cancel
+    /// pays given fee of cancelling party to its counterparty.
+    fn on_cancel<B: UniqueSaturatedInto<u128>>(&self, fee: Option<(Party, B)>) -> DispatchResult;
+
+    /// This method called when economics of started liability amended, given agreement
+    /// differs in economics only. Nothing should change when it fails.
+    fn on_amend(&self, amended: &Self) -> DispatchResult;
+}
+
+/// Party of agreement between two participants.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Party {
+    /// Party that pays for the work.
+    Promisee,
+    /// Party that executes the work.
+    Promisor,
+}
+
+/// Processing of economical parameter between agreement parties, see `Processing`.
+pub trait Settlement<AccountId>: Economical {
+    /// Liability with given parameter started.
+    fn on_start(
+        economics: &Self::Parameter,
+        promisee: &AccountId,
+        promisor: &AccountId,
+    ) -> DispatchResult;
+
+    /// Liability with given parameter finished.
+    fn on_finish(
+        economics: &Self::Parameter,
+        promisee: &AccountId,
+        promisor: &AccountId,
+        success: bool,
+    ) -> DispatchResult;
+
+    /// Funds moved by `on_finish` of given parameter, computed without moving them.
+    /// Implementations moving funds apply it in `on_finish`, so it's always accurate.
+    /// By default nothing is moved.
+    fn preview<B: UniqueSaturatedFrom<u128>>(
+        _economics: &Self::Parameter,
+        _promisee: &AccountId,
+        _promisor: &AccountId,
+        _success: bool,
+    ) -> Payouts<AccountId, B> {
+        Payouts::default()
+    }
+
+    /// Liability with given parameter cancelled before report, fee of unilateral cancel
+    /// is paid by given party to its counterparty. By default it's finished as failed
+    /// and fee is waived.
+    fn on_cancel<B: UniqueSaturatedInto<u128>>(
+        economics: &Self::Parameter,
+        promisee: &AccountId,
+        promisor: &AccountId,
+        _fee: Option<(Party, B)>,
+    ) -> DispatchResult {
+        Self::on_finish(economics, promisee, promisor, false)
+    }
+
+    /// Liability with given parameter amended to another one before report. By default
+    /// amended parameter is started before given one is finished as failed, so both are
+    /// kept on failure.
+    fn on_amend(
+        economics: &Self::Parameter,
+        amended: &Self::Parameter,
+        promisee: &AccountId,
+        promisor: &AccountId,
+    ) -> DispatchResult {
+        Self::on_start(amended, promisee, promisor)?;
+        Self::on_finish(economics, promisee, promisor, false).map_err(|e| {
+            let _ = Self::on_finish(amended, promisee, promisor, false);
+            e
+        })
+    }
 }
 
 /// Agreement between two participants around technical/economical aspects.
+///
+/// Messages covered by proofs are chosen by implementation, e.g. `SignedLiability` signs
+/// `params_payload`, `expiring_params_payload`, `report_payload`, `cancel_payload`,
+/// `commit_payload`, `claim_payload`, `witnessing_payload`, `notarize_payload`,
+/// `bounty_params_payload`, `acknowledge_payload` and `amend_payload`.
+/// Implementations with another proof targets
+/// (multi-party, zero-knowledge) encode them in `check_params` and `verify_report`,
+/// prefixed by their own domain tag to keep them apart from the payloads above.
 pub trait Agreement<T: Technical, E: Economical> {
-    /// Indexing type.
-    type Index: dispatch::Parameter + BaseArithmetic + Member + Copy + Default;
+    /// Indexing type, it's compact encoded in calls and events.
+    type Index: dispatch::Parameter + BaseArithmetic + Member + Copy + Default + HasCompact;
 
     /// Pariticipant account address.
     type AccountId: dispatch::Parameter;
 
     /// Some that could be used as proof of participants agreement.
-    type Proof: dispatch::Parameter;
+    type Proof: dispatch::Parameter + ProofScheme;
 
     /// Create new instance for given technical and economical parameters.
     fn new(
@@ -80,11 +262,168 @@ pub trait Agreement<T: Technical, E: Economical> {
         promisor: Self::AccountId,
     ) -> Self;
 
+    /// Technical parameter of agreement.
+    fn technics(&self) -> &T::Parameter;
+
+    /// Economical parameter of agreement.
+    fn economics(&self) -> &E::Parameter;
+
+    /// Party that pays for the work.
+    fn promisee(&self) -> &Self::AccountId;
+
+    /// Party that executes the work.
+    fn promisor(&self) -> &Self::AccountId;
+
     /// Check validity of agreement params proof.
     fn check_params(&self, proof: &Self::Proof, sender: &Self::AccountId) -> bool;
 
+    /// Check validity of agreement params proof that is valid until given block.
+    fn check_params_until<B: Encode>(
+        &self,
+        proof: &Self::Proof,
+        sender: &Self::AccountId,
+        expires_at: &B,
+    ) -> bool;
+
+    /// Check validity of agreement params proof covering liability tags.
+    fn check_tagged_params(
+        &self,
+        proof: &Self::Proof,
+        sender: &Self::AccountId,
+        tags: &[Tag],
+    ) -> bool;
+
+    /// Check validity of agreement params proof covering relay bounty.
+    fn check_bounty_params<B: Encode>(
+        &self,
+        proof: &Self::Proof,
+        sender: &Self::AccountId,
+        bounty: &B,
+    ) -> bool;
+
+    /// Check validity of agreement params proof covering requirement to promisor.
+    fn check_restricted_params(
+        &self,
+        proof: &Self::Proof,
+        sender: &Self::AccountId,
+        requirement: &PromisorRequirement<Self::AccountId>,
+    ) -> bool;
+
+    /// Check validity of party proof of liability cancel that is valid until given block.
+    fn check_cancel<B: Encode>(
+        &self,
+        index: &Self::Index,
+        expires_at: &B,
+        proof: &Self::Proof,
+        sender: &Self::AccountId,
+    ) -> bool;
+
+    /// Check validity of party proof of amendment of agreement economics.
+    fn check_amendment(
+        &self,
+        index: &Self::Index,
+        nonce: u32,
+        economics: &E::Parameter,
+        proof: &Self::Proof,
+        sender: &Self::AccountId,
+    ) -> bool;
+
+    /// Check validity of promisor proof of commitment to liability report.
+    fn check_commitment<H: Encode>(
+        &self,
+        index: &Self::Index,
+        commitment: &H,
+        proof: &Self::Proof,
+    ) -> bool;
+
+    /// Check validity of promisor proof of consent to settle liability by witness quorum.
+    fn check_witnessing(&self, index: &Self::Index, quorum: u32, proof: &Self::Proof) -> bool;
+
+    /// Check validity of promisor proof of claim that report arrived at given block.
+    fn check_claim<B: Encode>(
+        &self,
+        index: &Self::Index,
+        claimed_at: &B,
+        proof: &Self::Proof,
+    ) -> bool;
+
+    /// Check validity of promisor proof of liability acknowledgement.
+    fn check_acknowledgement(&self, index: &Self::Index, proof: &Self::Proof) -> bool;
+
     /// Check validity of agreement report proof.
-    fn check_report(&self, index: &Self::Index, report: &T::Report, proof: &Self::Proof) -> bool;
+    fn check_report(&self, index: &Self::Index, report: &T::Report, proof: &Self::Proof) -> bool {
+        Self::verify_report(self.promisor(), index, report, proof)
+    }
+
+    /// Check validity of agreement report proof attesting firmware that produced the report.
+    fn check_attested_report<H: Encode>(
+        &self,
+        index: &Self::Index,
+        report: &T::Report,
+        firmware: &H,
+        proof: &Self::Proof,
+    ) -> bool {
+        Self::verify_attested_report(self.promisor(), index, report, firmware, proof)
+    }
+
+    /// Check validity of report proof made by agreement promisor, it's used to verify
+    /// report without decoding of whole agreement.
+    fn verify_report(
+        promisor: &Self::AccountId,
+        index: &Self::Index,
+        report: &T::Report,
+        proof: &Self::Proof,
+    ) -> bool;
+
+    /// Check validity of attested report proof made by agreement promisor.
+    fn verify_attested_report<H: Encode>(
+        promisor: &Self::AccountId,
+        index: &Self::Index,
+        report: &T::Report,
+        firmware: &H,
+        proof: &Self::Proof,
+    ) -> bool;
+
+    /// Check validity of witness proof of notarization of report with given hash.
+    fn verify_notarization<H: Encode>(
+        witness: &Self::AccountId,
+        index: &Self::Index,
+        report_hash: &H,
+        proof: &Self::Proof,
+    ) -> bool;
+
+    /// Check validity of party proof of blinded agreement with given party commitments and
+    /// payout account. Agreements without privacy mode reject any proof.
+    fn verify_blinded_params(
+        _technics: &T::Parameter,
+        _economics: &E::Parameter,
+        _parties: (&H256, &H256),
+        _payout: &Self::AccountId,
+        _proof: &Self::Proof,
+        _sender: &Self::AccountId,
+    ) -> bool {
+        false
+    }
+
+    /// Check validity of report proof of blinded agreement made by its promisor.
+    fn verify_blinded_report(
+        _promisor: &Self::AccountId,
+        _index: &Self::Index,
+        _report: &T::Report,
+        _proof: &Self::Proof,
+    ) -> bool {
+        false
+    }
+
+    /// Check validity of robot owner proof of promisor key rotation.
+    fn verify_rotation(
+        _old: &Self::AccountId,
+        _new: &Self::AccountId,
+        _proof: &Self::Proof,
+        _owner: &Self::AccountId,
+    ) -> bool {
+        false
+    }
 }
 
 /// Agreement proovement maker.
@@ -92,6 +431,401 @@ pub trait ProofBuilder<T: Technical, E: Economical, Index, Account, Proof> {
     /// Make proof of technical and economical agreement parameters.
     fn proof_params(technics: &T::Parameter, economics: &E::Parameter, sender: Account) -> Proof;
 
+    /// Make proof of agreement parameters that is valid until given block.
+    fn proof_params_until<B: Encode>(
+        technics: &T::Parameter,
+        economics: &E::Parameter,
+        expires_at: &B,
+        sender: Account,
+    ) -> Proof;
+
+    /// Make proof of agreement parameters with liability tags.
+    fn proof_tagged_params(
+        technics: &T::Parameter,
+        economics: &E::Parameter,
+        tags: &[Tag],
+        sender: Account,
+    ) -> Proof;
+
+    /// Make proof of agreement parameters with relay bounty.
+    fn proof_bounty_params<B: Encode>(
+        technics: &T::Parameter,
+        economics: &E::Parameter,
+        bounty: &B,
+        sender: Account,
+    ) -> Proof;
+
+    /// Make proof of agreement parameters with requirement to promisor.
+    fn proof_restricted_params<A: Encode>(
+        technics: &T::Parameter,
+        economics: &E::Parameter,
+        requirement: &PromisorRequirement<A>,
+        sender: Account,
+    ) -> Proof;
+
     /// Make proof of technical report agrement parameter.
     fn proof_report(index: &Index, report: &T::Report, sender: Account) -> Proof;
+
+    /// Make proof of technical report with firmware hash attested by sender.
+    fn proof_attested_report<H: Encode>(
+        index: &Index,
+        report: &T::Report,
+        firmware: &H,
+        sender: Account,
+    ) -> Proof;
+
+    /// Make proof of liability cancel that is valid until given block.
+    fn proof_cancel<B: Encode>(index: &Index, expires_at: &B, sender: Account) -> Proof;
+
+    /// Make proof of amendment of liability economics.
+    fn proof_amendment(
+        index: &Index,
+        nonce: u32,
+        economics: &E::Parameter,
+        sender: Account,
+    ) -> Proof;
+
+    /// Make proof of commitment to liability report.
+    fn proof_commitment<H: Encode>(index: &Index, commitment: &H, sender: Account) -> Proof;
+
+    /// Make proof of claim that liability report arrived at given block.
+    fn proof_claim<B: Encode>(index: &Index, claimed_at: &B, sender: Account) -> Proof;
+
+    /// Make proof of liability acknowledgement.
+    fn proof_acknowledgement(index: &Index, sender: Account) -> Proof;
+
+    /// Make proof of consent to settle liability by witness quorum.
+    fn proof_witnessing(index: &Index, quorum: u32, sender: Account) -> Proof;
+
+    /// Make proof of notarization of liability report with given hash.
+    fn proof_notarization<H: Encode>(index: &Index, report_hash: &H, sender: Account) -> Proof;
+}
+
+/// Identifier of funds reserved by a module.
+pub type ReserveIdentifier = [u8; 8];
+
+/// Currency that keeps reserves of different identifiers apart, so releasing one of them
+/// never frees funds reserved under another one or by other modules. It follows
+/// `NamedReservableCurrency` of later Substrate releases.
+pub trait NamedReservableCurrency<AccountId> {
+    /// Funds type of currency.
+    type Balance: dispatch::Parameter + AtLeast32BitUnsigned + Copy;
+
+    /// Funds removed from circulation by slash.
+    type NegativeImbalance;
+
+    /// Funds of account reserved under identifier.
+    fn reserved_balance_named(id: &ReserveIdentifier, who: &AccountId) -> Self::Balance;
+
+    /// Reserve free funds of account under identifier.
+    fn reserve_named(
+        id: &ReserveIdentifier,
+        who: &AccountId,
+        value: Self::Balance,
+    ) -> DispatchResult;
+
+    /// Return funds reserved under identifier to free balance, returns amount
+    /// that isn't returned.
+    fn unreserve_named(
+        id: &ReserveIdentifier,
+        who: &AccountId,
+        value: Self::Balance,
+    ) -> Self::Balance;
+
+    /// Slash funds reserved under identifier, returns amount that isn't slashed.
+    fn slash_reserved_named(
+        id: &ReserveIdentifier,
+        who: &AccountId,
+        value: Self::Balance,
+    ) -> (Self::NegativeImbalance, Self::Balance);
+
+    /// Move funds reserved under identifier to beneficiary balance of given status,
+    /// returns amount that isn't moved.
+    fn repatriate_reserved_named(
+        id: &ReserveIdentifier,
+        slashed: &AccountId,
+        beneficiary: &AccountId,
+        value: Self::Balance,
+        status: BalanceStatus,
+    ) -> Result<Self::Balance, DispatchError>;
+}
+
+/// Source of participants identity verification.
+pub trait IdentityVerifier<AccountId> {
+    /// Returns `true` when account has verified identity.
+    fn is_verified(who: &AccountId) -> bool;
+}
+
+/// Nobody is verified.
+impl<AccountId> IdentityVerifier<AccountId> for () {
+    fn is_verified(_who: &AccountId) -> bool {
+        false
+    }
+}
+
+/// Result of liability lifecycle handler: weight consumed by handler, or its error with
+/// weight consumed before it.
+pub type HookResult = Result<Weight, (Weight, DispatchError)>;
+
+/// Failure of handler: position of handler in tuple of handlers and its error.
+pub type HookFailure = (u32, DispatchError);
+
+/// Error of handler consumed more weight than left for handlers.
+pub const HOOK_OVERWEIGHT: DispatchError = DispatchError::Other("hook weight exceeds budget");
+
+/// Run handler in storage transaction, its writes are reverted when it fails or consumes
+/// more than budget. Returns weight charged for handler, it's at most budget.
+pub fn isolate_hook(
+    budget: Weight,
+    handler: impl FnOnce() -> HookResult,
+) -> (Weight, Result<(), DispatchError>) {
+    with_transaction(|| match handler() {
+        Ok(weight) if weight <= budget => TransactionOutcome::Commit((weight, Ok(()))),
+        Ok(_) => TransactionOutcome::Rollback((budget, Err(HOOK_OVERWEIGHT))),
+        Err((weight, error)) => TransactionOutcome::Rollback((weight.min(budget), Err(error))),
+    })
+}
+
+/// Handler of new liabilities.
+///
+/// Handler runs inside liability calls, so its failure is isolated: writes of failed handler
+/// are reverted and liability is created regardless. Panic of handler can't be isolated,
+/// it fails the call.
+pub trait OnLiabilityCreated<Index, AccountId> {
+    /// Liability with given index created, returns weight consumed by handler.
+    fn on_liability_created(
+        _index: &Index,
+        _promisee: &AccountId,
+        _promisor: &AccountId,
+    ) -> HookResult {
+        Ok(0)
+    }
+
+    /// Call handler isolated by `isolate_hook` with given weight budget, returns weight
+    /// charged for handlers and their failures.
+    fn isolated_liability_created(
+        index: &Index,
+        promisee: &AccountId,
+        promisor: &AccountId,
+        budget: Weight,
+    ) -> (Weight, Vec<HookFailure>) {
+        let (weight, result) = isolate_hook(budget, || {
+            Self::on_liability_created(index, promisee, promisor)
+        });
+        (weight, result.err().map(|e| (0, e)).into_iter().collect())
+    }
+}
+
+/// Source of promisors reputation.
+pub trait ReputationProvider<AccountId> {
+    /// Current reputation score of account.
+    fn reputation(who: &AccountId) -> u32;
+}
+
+/// Nobody has reputation.
+impl<AccountId> ReputationProvider<AccountId> for () {
+    fn reputation(_who: &AccountId) -> u32 {
+        0
+    }
+}
+
+/// Source of robots identity.
+pub trait RobotRegistry<AccountId, Balance> {
+    /// Registry entry id of robot account.
+    fn robot_id(robot: &AccountId) -> Option<RobotId>;
+
+    /// Capabilities declared by robot account, unregistered robot declares none.
+    fn capabilities(_robot: &AccountId) -> Capabilities {
+        Capabilities::default()
+    }
+
+    /// Owner of registered robot account, e.g. its manufacturer.
+    fn owner(_robot: &AccountId) -> Option<AccountId> {
+        None
+    }
+
+    /// Metadata hash of registered robot account, e.g. its model id.
+    fn model(_robot: &AccountId) -> Option<H256> {
+        None
+    }
+
+    /// Deposits of robot owners reserved under liability `RESERVE_ID`.
+    fn deposits() -> Vec<(AccountId, Balance)> {
+        Vec::new()
+    }
+}
+
+/// Nobody is registered robot.
+impl<AccountId, Balance> RobotRegistry<AccountId, Balance> for () {
+    fn robot_id(_robot: &AccountId) -> Option<RobotId> {
+        None
+    }
+}
+
+/// Handler of liability reports.
+///
+/// Failure of handler is isolated as of `OnLiabilityCreated`: liability is settled
+/// regardless, e.g. its escrow is moved.
+pub trait OnReportAccepted<Index, AccountId> {
+    /// Report of liability with given index accepted, `success` is result of its
+    /// economical processing. Returns weight consumed by handler.
+    fn on_report_accepted(
+        _index: &Index,
+        _promisee: &AccountId,
+        _promisor: &AccountId,
+        _success: bool,
+    ) -> HookResult {
+        Ok(0)
+    }
+
+    /// Call handler isolated by `isolate_hook` with given weight budget, returns weight
+    /// charged for handlers and their failures.
+    fn isolated_report_accepted(
+        index: &Index,
+        promisee: &AccountId,
+        promisor: &AccountId,
+        success: bool,
+        budget: Weight,
+    ) -> (Weight, Vec<HookFailure>) {
+        let (weight, result) = isolate_hook(budget, || {
+            Self::on_report_accepted(index, promisee, promisor, success)
+        });
+        (weight, result.err().map(|e| (0, e)).into_iter().collect())
+    }
+
+    /// Transfers made by `on_report_accepted` with given arguments, computed without
+    /// making them.
+    fn preview_report_accepted(
+        _index: &Index,
+        _promisee: &AccountId,
+        _promisor: &AccountId,
+        _success: bool,
+    ) -> Vec<(AccountId, AccountId, u128)> {
+        Vec::new()
+    }
+}
+
+/// Liabilities opened and settled by other modules of runtime, e.g. mission scheduler
+/// acting on governance motion.
+///
+/// Calling module is trusted: parties proofs aren't checked, so it's responsible for consent
+/// of both parties, e.g. by its own origin checks or by agreement recorded before. Issued
+/// liability is still validated, processed by economics and indexed as created by `create`.
+/// Settled liability has no report, its outcome is taken as is.
+pub trait LiabilityIssuer<Technics, Economics, AccountId, Index> {
+    /// Open liability of given parameters and parties, returns its index.
+    fn issue(
+        technics: Technics,
+        economics: Economics,
+        promisee: AccountId,
+        promisor: AccountId,
+    ) -> Result<Index, DispatchError>;
+
+    /// Finalize open liability without report, `success` is passed to its economics.
+    fn settle(index: Index, success: bool) -> DispatchResult;
+}
+
+/// Representation of liabilities of legacy Ethereum contracts by module parameters.
+pub trait FromLegacy<Technics, Economics, AccountId> {
+    /// Technics, economics, promisee and promisor of legacy liability, `None` when it
+    /// can't be represented.
+    fn from_legacy(record: &LegacyRecord) -> Option<(Technics, Economics, AccountId, AccountId)>;
+}
+
+/// Legacy liabilities aren't imported.
+impl<Technics, Economics, AccountId> FromLegacy<Technics, Economics, AccountId> for () {
+    fn from_legacy(_record: &LegacyRecord) -> Option<(Technics, Economics, AccountId, AccountId)> {
+        None
+    }
+}
+
+#[impl_for_tuples(30)]
+impl<Index, AccountId> OnLiabilityCreated<Index, AccountId> for Tuple {
+    fn on_liability_created(
+        index: &Index,
+        promisee: &AccountId,
+        promisor: &AccountId,
+    ) -> HookResult {
+        let mut weight: Weight = 0;
+        for_tuples!( #(
+            let consumed = Tuple::on_liability_created(index, promisee, promisor)
+                .map_err(move |(consumed, e)| (weight.saturating_add(consumed), e))?;
+            weight = weight.saturating_add(consumed);
+        )* );
+        Ok(weight)
+    }
+
+    fn isolated_liability_created(
+        index: &Index,
+        promisee: &AccountId,
+        promisor: &AccountId,
+        budget: Weight,
+    ) -> (Weight, Vec<HookFailure>) {
+        let mut weight: Weight = 0;
+        let mut failures = Vec::new();
+        let mut positions = 0..;
+        for_tuples!( #(
+            let position = positions.next().unwrap_or_default();
+            let (consumed, result) = isolate_hook(budget.saturating_sub(weight), || {
+                Tuple::on_liability_created(index, promisee, promisor)
+            });
+            weight = weight.saturating_add(consumed);
+            if let Err(e) = result {
+                failures.push((position, e));
+            }
+        )* );
+        (weight, failures)
+    }
+}
+
+#[impl_for_tuples(30)]
+impl<Index, AccountId> OnReportAccepted<Index, AccountId> for Tuple {
+    fn on_report_accepted(
+        index: &Index,
+        promisee: &AccountId,
+        promisor: &AccountId,
+        success: bool,
+    ) -> HookResult {
+        let mut weight: Weight = 0;
+        for_tuples!( #(
+            let consumed = Tuple::on_report_accepted(index, promisee, promisor, success)
+                .map_err(move |(consumed, e)| (weight.saturating_add(consumed), e))?;
+            weight = weight.saturating_add(consumed);
+        )* );
+        Ok(weight)
+    }
+
+    fn isolated_report_accepted(
+        index: &Index,
+        promisee: &AccountId,
+        promisor: &AccountId,
+        success: bool,
+        budget: Weight,
+    ) -> (Weight, Vec<HookFailure>) {
+        let mut weight: Weight = 0;
+        let mut failures = Vec::new();
+        let mut positions = 0..;
+        for_tuples!( #(
+            let position = positions.next().unwrap_or_default();
+            let (consumed, result) = isolate_hook(budget.saturating_sub(weight), || {
+                Tuple::on_report_accepted(index, promisee, promisor, success)
+            });
+            weight = weight.saturating_add(consumed);
+            if let Err(e) = result {
+                failures.push((position, e));
+            }
+        )* );
+        (weight, failures)
+    }
+
+    fn preview_report_accepted(
+        index: &Index,
+        promisee: &AccountId,
+        promisor: &AccountId,
+        success: bool,
+    ) -> Vec<(AccountId, AccountId, u128)> {
+        let mut transfers = Vec::new();
+        for_tuples!( #( transfers.extend(Tuple::preview_report_accepted(index, promisee, promisor, success)); )* );
+        transfers
+    }
 }
@@ -0,0 +1,287 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Weights for pallet_robonomics_liability in layout of Substrate benchmark CLI output.
+//!
+//! Values aren't measured yet, they're estimated from benchmark setups and storage accesses
+//! of calls. The file should be regenerated on reference hardware by the command below
+//! before weights are relied on.
+
+// Executed Command:
+// target/release/robonomics
+// benchmark
+// --chain=dev
+// --steps=50
+// --repeat=20
+// --pallet=pallet_robonomics_liability
+// --extrinsic=*
+// --execution=wasm
+// --wasm-execution=compiled
+// --heap-pages=4096
+// --output=./robonomics/frame/liability/src/weights.rs
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::Weight};
+use sp_std::marker::PhantomData;
+
+/// Weight functions for pallet_robonomics_liability.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Trait> crate::WeightInfo for SubstrateWeight<T> {
+    fn create(t: u32, ) -> Weight {
+        (182_400_000 as Weight)
+            .saturating_add((2_300 as Weight).saturating_mul(t as Weight))
+            .saturating_add(T::DbWeight::get().reads(21 as Weight))
+            .saturating_add(T::DbWeight::get().writes(18 as Weight))
+    }
+    fn finalize(r: u32, ) -> Weight {
+        (171_900_000 as Weight)
+            .saturating_add((3_100 as Weight).saturating_mul(r as Weight))
+            .saturating_add(T::DbWeight::get().reads(14 as Weight))
+            .saturating_add(T::DbWeight::get().writes(13 as Weight))
+    }
+    fn finalize_bad_proof(r: u32, ) -> Weight {
+        (88_600_000 as Weight)
+            .saturating_add((2_900 as Weight).saturating_mul(r as Weight))
+            .saturating_add(T::DbWeight::get().reads(3 as Weight))
+    }
+    fn verify_sr25519(p: u32, ) -> Weight {
+        (48_300_000 as Weight)
+            .saturating_add((1_100 as Weight).saturating_mul(p as Weight))
+    }
+    fn verify_ed25519(p: u32, ) -> Weight {
+        (44_700_000 as Weight)
+            .saturating_add((1_000 as Weight).saturating_mul(p as Weight))
+    }
+    fn verify_ecdsa(p: u32, ) -> Weight {
+        (59_200_000 as Weight)
+            .saturating_add((1_100 as Weight).saturating_mul(p as Weight))
+    }
+    fn add_promisor() -> Weight {
+        (21_300_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(1 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn remove_promisor() -> Weight {
+        (20_800_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(1 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn force_finalize() -> Weight {
+        (97_500_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(11 as Weight))
+            .saturating_add(T::DbWeight::get().writes(10 as Weight))
+    }
+    fn demand(t: u32, ) -> Weight {
+        (73_600_000 as Weight)
+            .saturating_add((2_200 as Weight).saturating_mul(t as Weight))
+            .saturating_add(T::DbWeight::get().reads(7 as Weight))
+            .saturating_add(T::DbWeight::get().writes(6 as Weight))
+    }
+    fn cancel_demand() -> Weight {
+        (42_100_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(3 as Weight))
+            .saturating_add(T::DbWeight::get().writes(4 as Weight))
+    }
+    fn offer(t: u32, ) -> Weight {
+        (73_900_000 as Weight)
+            .saturating_add((2_200 as Weight).saturating_mul(t as Weight))
+            .saturating_add(T::DbWeight::get().reads(7 as Weight))
+            .saturating_add(T::DbWeight::get().writes(6 as Weight))
+    }
+    fn cancel_offer() -> Weight {
+        (42_300_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(3 as Weight))
+            .saturating_add(T::DbWeight::get().writes(4 as Weight))
+    }
+    fn set_market_fee() -> Weight {
+        (18_200_000 as Weight)
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn create_market() -> Weight {
+        (24_700_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(1 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn update_market() -> Weight {
+        (25_100_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(1 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn fund() -> Weight {
+        (61_400_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(5 as Weight))
+            .saturating_add(T::DbWeight::get().writes(5 as Weight))
+    }
+    fn cancel() -> Weight {
+        (158_200_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(11 as Weight))
+            .saturating_add(T::DbWeight::get().writes(9 as Weight))
+    }
+    fn amend_economics() -> Weight {
+        (149_600_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(5 as Weight))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+    }
+    fn commit_report() -> Weight {
+        (64_800_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(4 as Weight))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+    }
+    fn reveal_report(r: u32, ) -> Weight {
+        (168_300_000 as Weight)
+            .saturating_add((3_100 as Weight).saturating_mul(r as Weight))
+            .saturating_add(T::DbWeight::get().reads(15 as Weight))
+            .saturating_add(T::DbWeight::get().writes(14 as Weight))
+    }
+    fn match_orders() -> Weight {
+        (203_700_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(26 as Weight))
+            .saturating_add(T::DbWeight::get().writes(23 as Weight))
+    }
+    fn commit_match() -> Weight {
+        (31_900_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(3 as Weight))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+    }
+    fn reveal_match() -> Weight {
+        (211_500_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(29 as Weight))
+            .saturating_add(T::DbWeight::get().writes(25 as Weight))
+    }
+    fn register_witness() -> Weight {
+        (39_400_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(3 as Weight))
+            .saturating_add(T::DbWeight::get().writes(2 as Weight))
+    }
+    fn set_preferences() -> Weight {
+        (23_600_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(1 as Weight))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn unregister_witness() -> Weight {
+        (37_800_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(3 as Weight))
+            .saturating_add(T::DbWeight::get().writes(2 as Weight))
+    }
+    fn join_pool() -> Weight {
+        (47_200_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(4 as Weight))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+    }
+    fn leave_pool() -> Weight {
+        (45_600_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(4 as Weight))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+    }
+    fn request_assignment() -> Weight {
+        (52_300_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(4 as Weight))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+    }
+    fn acknowledge() -> Weight {
+        (41_700_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(4 as Weight))
+            .saturating_add(T::DbWeight::get().writes(4 as Weight))
+    }
+    fn reclaim() -> Weight {
+        (89_100_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(10 as Weight))
+            .saturating_add(T::DbWeight::get().writes(9 as Weight))
+    }
+    fn force_expire() -> Weight {
+        (42_900_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(3 as Weight))
+            .saturating_add(T::DbWeight::get().writes(4 as Weight))
+    }
+    fn require_witnesses() -> Weight {
+        (36_500_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(3 as Weight))
+            .saturating_add(T::DbWeight::get().writes(2 as Weight))
+    }
+    fn notarize() -> Weight {
+        (74_300_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(6 as Weight))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+    }
+    fn overturn_report(w: u32, ) -> Weight {
+        (112_800_000 as Weight)
+            .saturating_add((31_400_000 as Weight).saturating_mul(w as Weight))
+            .saturating_add(T::DbWeight::get().reads(9 as Weight))
+            .saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(w as Weight)))
+            .saturating_add(T::DbWeight::get().writes(8 as Weight))
+            .saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(w as Weight)))
+    }
+    fn register_lighthouse() -> Weight {
+        (40_600_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(3 as Weight))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+    }
+    fn unregister_lighthouse() -> Weight {
+        (38_900_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(3 as Weight))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+    }
+    fn bond_orders() -> Weight {
+        (33_200_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(2 as Weight))
+            .saturating_add(T::DbWeight::get().writes(2 as Weight))
+    }
+    fn unbond_orders() -> Weight {
+        (32_700_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(2 as Weight))
+            .saturating_add(T::DbWeight::get().writes(2 as Weight))
+    }
+    fn claim_order_breach() -> Weight {
+        (71_400_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(5 as Weight))
+            .saturating_add(T::DbWeight::get().writes(5 as Weight))
+    }
+    fn blacklist() -> Weight {
+        (19_700_000 as Weight)
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn unblacklist() -> Weight {
+        (18_900_000 as Weight)
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn approve_firmware() -> Weight {
+        (19_300_000 as Weight)
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn revoke_firmware() -> Weight {
+        (18_800_000 as Weight)
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+    }
+    fn note_report_preimage(r: u32, ) -> Weight {
+        (46_900_000 as Weight)
+            .saturating_add((2_700 as Weight).saturating_mul(r as Weight))
+            .saturating_add(T::DbWeight::get().reads(2 as Weight))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+    }
+    fn prune_report_preimage() -> Weight {
+        (39_100_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(2 as Weight))
+            .saturating_add(T::DbWeight::get().writes(3 as Weight))
+    }
+    fn prune_liability() -> Weight {
+        (88_400_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(10 as Weight))
+            .saturating_add(T::DbWeight::get().writes(21 as Weight))
+    }
+}
@@ -0,0 +1,628 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Robonomics Web Services (RWS) subscription runtime module: operator buys a subscription
+//! that lets its devices send a bounded weight of transactions per era without fees.
+//! This can be compiled with `#[no_std]`, ready for Wasm.
+//!
+//! Fees are waived by `ChargeRwsPayment` signed extension that replaces
+//! `ChargeTransactionPayment` in runtime: calls passed by `FeelessCall` filter
+//! from subscribed devices consume subscription quota, any other transaction
+//! (or one exceeding the rest of quota) pays usual fees.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage, ensure,
+    traits::{Currency, ExistenceRequirement, Filter, Get, OnUnbalanced, WithdrawReason},
+    weights::{DispatchInfo, PostDispatchInfo, Weight},
+};
+use frame_system::ensure_signed;
+use pallet_transaction_payment::ChargeTransactionPayment;
+use sp_runtime::{
+    traits::{
+        DispatchInfoOf, Dispatchable, PostDispatchInfoOf, SaturatedConversion, SignedExtension,
+        Zero,
+    },
+    transaction_validity::{TransactionValidity, TransactionValidityError, ValidTransaction},
+    DispatchResult, FixedPointOperand, RuntimeDebug,
+};
+use sp_std::prelude::*;
+
+/// Type synonym for subscription price balance.
+pub type BalanceOf<T> =
+    <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
+
+/// Type synonym for withdrawn subscription payment.
+pub type NegativeImbalanceOf<T> =
+    <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::NegativeImbalance;
+
+/// Type synonym for transaction fee balance.
+pub type FeeBalanceOf<T> = <<T as pallet_transaction_payment::Trait>::Currency as Currency<
+    <T as frame_system::Trait>::AccountId,
+>>::Balance;
+
+/// Sequential number of subscription era, era is `EraLength` blocks long.
+pub type EraIndex = u32;
+
+/// Subscription of operator account.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct Subscription<AccountId> {
+    /// Device accounts sending transactions for free.
+    pub devices: Vec<AccountId>,
+    /// Subscription is active before this era.
+    pub expires: EraIndex,
+    /// Era of `used` quota accounting.
+    pub era: EraIndex,
+    /// Weight of free transactions sent by devices during `era`.
+    pub used: Weight,
+}
+
+/// RWS module main trait.
+pub trait Trait: frame_system::Trait {
+    /// Currency of subscription payments.
+    type Currency: Currency<Self::AccountId>;
+    /// Fixed price of subscription period.
+    type SubscriptionPrice: Get<BalanceOf<Self>>;
+    /// Count of eras bought by single subscription payment.
+    type SubscriptionPeriod: Get<EraIndex>;
+    /// Destination of subscription payments, e.g. treasury.
+    type PaymentDestination: OnUnbalanced<NegativeImbalanceOf<Self>>;
+    /// Count of blocks in quota accounting era.
+    type EraLength: Get<Self::BlockNumber>;
+    /// Weight of free transactions available to devices of subscription in each era.
+    type WeightQuota: Get<Weight>;
+    /// Maximal count of devices in subscription.
+    type MaxDevices: Get<u32>;
+    /// Calls sent by devices for free while quota remains, e.g. liability calls.
+    type FeelessCall: Filter<<Self as frame_system::Trait>::Call>;
+    /// The overarching event type.
+    type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
+}
+
+decl_event! {
+    pub enum Event<T>
+    where AccountId = <T as frame_system::Trait>::AccountId,
+    {
+        /// Subscription bought or renewed: operator, count of devices, expiration era.
+        Subscribed(AccountId, u32, EraIndex),
+    }
+}
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        /// Count of devices exceeds `MaxDevices`.
+        TooManyDevices,
+        /// Device already belongs to active subscription of another operator.
+        DeviceSubscribed,
+    }
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as Rws {
+        /// Subscriptions by operator account.
+        Subscriptions get(fn subscription):
+            map hasher(blake2_128_concat) T::AccountId => Option<Subscription<T::AccountId>>;
+        /// Operator of subscription by device account.
+        Devices get(fn device_operator):
+            map hasher(blake2_128_concat) T::AccountId => Option<T::AccountId>;
+    }
+}
+
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        type Error = Error<T>;
+
+        /// Maximal count of devices in subscription.
+        const MaxDevices: u32 = T::MaxDevices::get();
+
+        /// Weight of free transactions available to devices of subscription in each era.
+        const WeightQuota: Weight = T::WeightQuota::get();
+
+        fn deposit_event() = default;
+
+        /// Buy subscription for given devices or renew it, subscription period is appended
+        /// to active subscription and devices of it are replaced by given ones.
+        #[weight = 50_000_000 + T::DbWeight::get().reads_writes(
+            2 + 3 * T::MaxDevices::get() as Weight,
+            2 + 3 * T::MaxDevices::get() as Weight,
+        )]
+        fn subscribe(origin, devices: Vec<T::AccountId>) {
+            let operator = ensure_signed(origin)?;
+            ensure!(devices.len() <= T::MaxDevices::get() as usize, Error::<T>::TooManyDevices);
+            let era = Self::current_era();
+            for device in devices.iter() {
+                if let Some(other) = <Devices<T>>::get(device) {
+                    ensure!(
+                        other == operator || !Self::is_active(&other, era),
+                        Error::<T>::DeviceSubscribed
+                    );
+                }
+            }
+
+            let price = T::SubscriptionPrice::get();
+            if !price.is_zero() {
+                let imbalance = T::Currency::withdraw(
+                    &operator,
+                    price,
+                    WithdrawReason::Fee.into(),
+                    ExistenceRequirement::KeepAlive,
+                )?;
+                T::PaymentDestination::on_unbalanced(imbalance);
+            }
+
+            let mut subscription = <Subscriptions<T>>::get(&operator).unwrap_or_default();
+            for device in subscription.devices.iter() {
+                <Devices<T>>::remove(device);
+            }
+            for device in devices.iter() {
+                // Devices of expired subscriptions are taken over
+                if let Some(other) = <Devices<T>>::get(device) {
+                    <Subscriptions<T>>::mutate(&other, |expired| {
+                        if let Some(expired) = expired {
+                            expired.devices.retain(|d| d != device);
+                        }
+                    });
+                }
+                <Devices<T>>::insert(device, &operator);
+            }
+            subscription.expires = subscription
+                .expires
+                .max(era)
+                .saturating_add(T::SubscriptionPeriod::get());
+            subscription.devices = devices;
+
+            let count = subscription.devices.len() as u32;
+            let expires = subscription.expires;
+            <Subscriptions<T>>::insert(&operator, subscription);
+            Self::deposit_event(RawEvent::Subscribed(operator, count, expires));
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Current quota accounting era.
+    pub fn current_era() -> EraIndex {
+        let now = <frame_system::Module<T>>::block_number();
+        match T::EraLength::get() {
+            length if length.is_zero() => 0,
+            length => (now / length).saturated_into(),
+        }
+    }
+
+    /// Returns `true` when subscription of operator is active in given era.
+    pub fn is_active(operator: &T::AccountId, era: EraIndex) -> bool {
+        <Subscriptions<T>>::get(operator).map_or(false, |s| s.expires > era)
+    }
+
+    /// Operator and its subscription with quota consumed by call of given weight,
+    /// `None` when call from device isn't free.
+    fn charge_quota(
+        device: &T::AccountId,
+        call: &<T as frame_system::Trait>::Call,
+        weight: Weight,
+    ) -> Option<(T::AccountId, Subscription<T::AccountId>)> {
+        if !T::FeelessCall::filter(call) {
+            return None;
+        }
+        let operator = <Devices<T>>::get(device)?;
+        let mut subscription = <Subscriptions<T>>::get(&operator)?;
+
+        let era = Self::current_era();
+        if subscription.expires <= era {
+            return None;
+        }
+        if subscription.era != era {
+            subscription.era = era;
+            subscription.used = 0;
+        }
+        let used = subscription.used.checked_add(weight)?;
+        if used > T::WeightQuota::get() {
+            return None;
+        }
+        subscription.used = used;
+        Some((operator, subscription))
+    }
+
+    /// Returns `true` when call of given weight from device fits into subscription quota.
+    pub fn is_feeless(
+        device: &T::AccountId,
+        call: &<T as frame_system::Trait>::Call,
+        weight: Weight,
+    ) -> bool {
+        Self::charge_quota(device, call, weight).is_some()
+    }
+
+    /// Consume subscription quota by call of given weight from device,
+    /// returns `false` when call isn't free.
+    pub fn consume_quota(
+        device: &T::AccountId,
+        call: &<T as frame_system::Trait>::Call,
+        weight: Weight,
+    ) -> bool {
+        match Self::charge_quota(device, call, weight) {
+            Some((operator, subscription)) => {
+                <Subscriptions<T>>::insert(&operator, subscription);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Transaction payment of subscribed devices: free calls consume subscription quota,
+/// others are charged by `ChargeTransactionPayment`.
+///
+/// Encoding and identifier are the same as of `ChargeTransactionPayment`, so existing
+/// clients sign transactions without changes. Quota is charged by declared call weight.
+#[derive(Encode, Decode, Clone, Eq, PartialEq)]
+pub struct ChargeRwsPayment<T: Trait + pallet_transaction_payment::Trait + Send + Sync>(
+    ChargeTransactionPayment<T>,
+);
+
+impl<T: Trait + pallet_transaction_payment::Trait + Send + Sync> ChargeRwsPayment<T>
+where
+    <T as frame_system::Trait>::Call:
+        Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+    FeeBalanceOf<T>: Send + Sync + FixedPointOperand,
+{
+    /// Utility constructor, tip is paid only when transaction isn't free.
+    pub fn from(tip: FeeBalanceOf<T>) -> Self {
+        Self(ChargeTransactionPayment::from(tip))
+    }
+}
+
+impl<T: Trait + pallet_transaction_payment::Trait + Send + Sync> sp_std::fmt::Debug
+    for ChargeRwsPayment<T>
+{
+    #[cfg(feature = "std")]
+    fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+        write!(f, "ChargeRwsPayment<{:?}>", self.0)
+    }
+    #[cfg(not(feature = "std"))]
+    fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+        Ok(())
+    }
+}
+
+impl<T: Trait + pallet_transaction_payment::Trait + Send + Sync> SignedExtension
+    for ChargeRwsPayment<T>
+where
+    <T as frame_system::Trait>::Call:
+        Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+    FeeBalanceOf<T>: Send + Sync + From<u64> + FixedPointOperand,
+{
+    const IDENTIFIER: &'static str = <ChargeTransactionPayment<T> as SignedExtension>::IDENTIFIER;
+    type AccountId = T::AccountId;
+    type Call = <T as frame_system::Trait>::Call;
+    type AdditionalSigned = ();
+    /// `None` when transaction is paid by subscription quota.
+    type Pre = Option<<ChargeTransactionPayment<T> as SignedExtension>::Pre>;
+
+    fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+        self.0.additional_signed()
+    }
+
+    fn validate(
+        &self,
+        who: &Self::AccountId,
+        call: &Self::Call,
+        info: &DispatchInfoOf<Self::Call>,
+        len: usize,
+    ) -> TransactionValidity {
+        if <Module<T>>::is_feeless(who, call, info.weight) {
+            Ok(ValidTransaction::default())
+        } else {
+            self.0.validate(who, call, info, len)
+        }
+    }
+
+    fn pre_dispatch(
+        self,
+        who: &Self::AccountId,
+        call: &Self::Call,
+        info: &DispatchInfoOf<Self::Call>,
+        len: usize,
+    ) -> Result<Self::Pre, TransactionValidityError> {
+        if <Module<T>>::consume_quota(who, call, info.weight) {
+            Ok(None)
+        } else {
+            self.0.pre_dispatch(who, call, info, len).map(Some)
+        }
+    }
+
+    fn post_dispatch(
+        pre: Self::Pre,
+        info: &DispatchInfoOf<Self::Call>,
+        post_info: &PostDispatchInfoOf<Self::Call>,
+        len: usize,
+        result: &DispatchResult,
+    ) -> Result<(), TransactionValidityError> {
+        match pre {
+            Some(pre) => {
+                ChargeTransactionPayment::<T>::post_dispatch(pre, info, post_info, len, result)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use frame_support::{
+        assert_err, assert_noop, assert_ok, impl_outer_dispatch, impl_outer_origin,
+        parameter_types, weights::IdentityFee,
+    };
+    use sp_core::H256;
+    use sp_runtime::{
+        testing::Header,
+        traits::IdentityLookup,
+        transaction_validity::{InvalidTransaction, TransactionValidityError},
+        DispatchError, Perbill,
+    };
+
+    impl_outer_origin! {
+        pub enum Origin for Runtime {}
+    }
+
+    impl_outer_dispatch! {
+        pub enum Call for Runtime where origin: Origin {
+            frame_system::System,
+            pallet_balances::Balances,
+        }
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    pub struct Runtime;
+
+    parameter_types! {
+        pub const BlockHashCount: u64 = 250;
+        pub const MaximumBlockWeight: Weight = 1024;
+        pub const MaximumBlockLength: u32 = 2 * 1024;
+        pub const AvailableBlockRatio: Perbill = Perbill::one();
+    }
+
+    impl frame_system::Trait for Runtime {
+        type Origin = Origin;
+        type Index = u64;
+        type BlockNumber = u64;
+        type Call = Call;
+        type Hash = H256;
+        type Hashing = sp_runtime::traits::BlakeTwo256;
+        type AccountId = u64;
+        type Lookup = IdentityLookup<Self::AccountId>;
+        type Header = Header;
+        type Event = ();
+        type BlockHashCount = BlockHashCount;
+        type MaximumBlockWeight = MaximumBlockWeight;
+        type MaximumBlockLength = MaximumBlockLength;
+        type AvailableBlockRatio = AvailableBlockRatio;
+        type Version = ();
+        type ModuleToIndex = ();
+        type AccountData = pallet_balances::AccountData<u64>;
+        type OnNewAccount = ();
+        type OnKilledAccount = ();
+        type DbWeight = ();
+        type BlockExecutionWeight = ();
+        type ExtrinsicBaseWeight = ();
+        type MaximumExtrinsicWeight = ();
+        type BaseCallFilter = ();
+        type SystemWeightInfo = ();
+    }
+
+    parameter_types! {
+        pub const ExistentialDeposit: u64 = 1;
+    }
+
+    impl pallet_balances::Trait for Runtime {
+        type Balance = u64;
+        type Event = ();
+        type DustRemoval = ();
+        type ExistentialDeposit = ExistentialDeposit;
+        type AccountStore = System;
+        type WeightInfo = ();
+    }
+
+    parameter_types! {
+        pub const TransactionByteFee: u64 = 1;
+    }
+
+    impl pallet_transaction_payment::Trait for Runtime {
+        type Currency = Balances;
+        type OnTransactionPayment = ();
+        type TransactionByteFee = TransactionByteFee;
+        type WeightToFee = IdentityFee<u64>;
+        type FeeMultiplierUpdate = ();
+    }
+
+    parameter_types! {
+        pub const SubscriptionPrice: u64 = 10;
+        pub const SubscriptionPeriod: EraIndex = 2;
+        pub const EraLength: u64 = 10;
+        pub const WeightQuota: Weight = 100;
+        pub const MaxDevices: u32 = 2;
+    }
+
+    /// System calls are free for subscribed devices.
+    pub struct SystemCalls;
+    impl Filter<Call> for SystemCalls {
+        fn filter(call: &Call) -> bool {
+            matches!(call, Call::System(_))
+        }
+    }
+
+    /// Subscription payments are sent to treasury account.
+    pub struct MockTreasury;
+    impl OnUnbalanced<pallet_balances::NegativeImbalance<Runtime>> for MockTreasury {
+        fn on_nonzero_unbalanced(amount: pallet_balances::NegativeImbalance<Runtime>) {
+            Balances::resolve_creating(&TREASURY, amount);
+        }
+    }
+
+    impl Trait for Runtime {
+        type Currency = Balances;
+        type SubscriptionPrice = SubscriptionPrice;
+        type SubscriptionPeriod = SubscriptionPeriod;
+        type PaymentDestination = MockTreasury;
+        type EraLength = EraLength;
+        type WeightQuota = WeightQuota;
+        type MaxDevices = MaxDevices;
+        type FeelessCall = SystemCalls;
+        type Event = ();
+    }
+
+    const TREASURY: u64 = 100;
+    const OPERATOR: u64 = 1;
+
+    fn new_test_ext() -> sp_io::TestExternalities {
+        let mut storage = frame_system::GenesisConfig::default()
+            .build_storage::<Runtime>()
+            .unwrap();
+        pallet_balances::GenesisConfig::<Runtime> {
+            balances: vec![(OPERATOR, 100), (2, 100), (11, 100)],
+        }
+        .assimilate_storage(&mut storage)
+        .unwrap();
+        storage.into()
+    }
+
+    type System = frame_system::Module<Runtime>;
+    type Balances = pallet_balances::Module<Runtime>;
+    type Rws = Module<Runtime>;
+
+    fn remark() -> Call {
+        Call::System(frame_system::Call::remark(vec![]))
+    }
+
+    fn transfer() -> Call {
+        Call::Balances(pallet_balances::Call::transfer(2, 1))
+    }
+
+    fn info(weight: Weight) -> DispatchInfo {
+        DispatchInfo {
+            weight,
+            ..Default::default()
+        }
+    }
+
+    /// Dispatch transaction of length 10 from given account, returns `true` when it's free.
+    fn pay(who: u64, call: &Call, weight: Weight) -> Result<bool, TransactionValidityError> {
+        let info = info(weight);
+        let pre = ChargeRwsPayment::<Runtime>::from(0).pre_dispatch(&who, call, &info, 10)?;
+        let is_free = pre.is_none();
+        ChargeRwsPayment::<Runtime>::post_dispatch(pre, &info, &Default::default(), 10, &Ok(()))?;
+        Ok(is_free)
+    }
+
+    #[test]
+    fn test_subscribe() {
+        new_test_ext().execute_with(|| {
+            System::set_block_number(15);
+            assert_ok!(Rws::subscribe(Origin::signed(OPERATOR), vec![11, 12]));
+            assert_eq!(Balances::free_balance(OPERATOR), 90);
+            assert_eq!(Balances::free_balance(TREASURY), 10);
+            assert_eq!(Rws::device_operator(11), Some(OPERATOR));
+            let subscription = Rws::subscription(OPERATOR).unwrap();
+            assert_eq!(subscription.devices, vec![11, 12]);
+            assert_eq!(subscription.expires, 3);
+
+            // Renewal appends period and replaces devices
+            assert_ok!(Rws::subscribe(Origin::signed(OPERATOR), vec![12, 13]));
+            assert_eq!(Rws::subscription(OPERATOR).unwrap().expires, 5);
+            assert_eq!(Rws::device_operator(11), None);
+            assert_eq!(Rws::device_operator(13), Some(OPERATOR));
+            assert_eq!(Balances::free_balance(OPERATOR), 80);
+        })
+    }
+
+    #[test]
+    fn test_subscribe_errors() {
+        new_test_ext().execute_with(|| {
+            assert_err!(
+                Rws::subscribe(Origin::none(), vec![]),
+                DispatchError::BadOrigin
+            );
+            assert_noop!(
+                Rws::subscribe(Origin::signed(OPERATOR), vec![11, 12, 13]),
+                Error::<Runtime>::TooManyDevices
+            );
+            assert_ok!(Rws::subscribe(Origin::signed(OPERATOR), vec![11]));
+            assert_noop!(
+                Rws::subscribe(Origin::signed(2), vec![11]),
+                Error::<Runtime>::DeviceSubscribed
+            );
+
+            // Devices of expired subscription are taken over
+            System::set_block_number(20);
+            assert_ok!(Rws::subscribe(Origin::signed(2), vec![11]));
+            assert_eq!(Rws::device_operator(11), Some(2));
+            assert!(Rws::subscription(OPERATOR).unwrap().devices.is_empty());
+        })
+    }
+
+    #[test]
+    fn test_feeless_quota() {
+        new_test_ext().execute_with(|| {
+            let device = 11;
+            // Not subscribed device pays weight and length fee
+            assert!(!pay(device, &remark(), 40).unwrap());
+            assert_eq!(Balances::free_balance(device), 50);
+
+            assert_ok!(Rws::subscribe(Origin::signed(OPERATOR), vec![device]));
+            assert!(Rws::is_feeless(&device, &remark(), 60));
+            assert!(pay(device, &remark(), 60).unwrap());
+            assert!(pay(device, &remark(), 40).unwrap());
+            assert_eq!(Balances::free_balance(device), 50);
+            assert_eq!(Rws::subscription(OPERATOR).unwrap().used, 100);
+
+            // Exhausted quota falls back to fees
+            assert!(!Rws::is_feeless(&device, &remark(), 1));
+            assert!(!pay(device, &remark(), 1).unwrap());
+            assert_eq!(Balances::free_balance(device), 39);
+
+            // Calls out of filter are always paid
+            assert!(!pay(device, &transfer(), 0).unwrap());
+            assert_eq!(Balances::free_balance(device), 29);
+
+            // Quota is renewed in the next era, expired subscription isn't free
+            System::set_block_number(10);
+            assert!(pay(device, &remark(), 100).unwrap());
+            System::set_block_number(20);
+            assert!(!Rws::is_feeless(&device, &remark(), 0));
+        })
+    }
+
+    #[test]
+    fn test_fee_fallback_rejected_without_funds() {
+        new_test_ext().execute_with(|| {
+            let device = 12;
+            assert_ok!(Rws::subscribe(Origin::signed(OPERATOR), vec![device]));
+            let call = remark();
+            assert!(ChargeRwsPayment::<Runtime>::from(0)
+                .validate(&device, &call, &info(100), 10)
+                .is_ok());
+            assert_eq!(
+                ChargeRwsPayment::<Runtime>::from(0)
+                    .validate(&device, &call, &info(101), 10)
+                    .unwrap_err(),
+                TransactionValidityError::Invalid(InvalidTransaction::Payment)
+            );
+        })
+    }
+}
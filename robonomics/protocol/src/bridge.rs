@@ -0,0 +1,296 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Bridge of pubsub market orders and Robonomics liabilities.
+//!
+//! Signed orders are received from pubsub order topic, compatible demand and offer are
+//! created on chain by unsigned `create`. Liability transitions are published back into
+//! result topic as JSON lines, see `liability::Transition::to_json`.
+//!
+//! Order message is JSON object, e.g.
+//! `{"side": "demand", "technics": "Qm...", "sender": "5F...", "expiry": 100, "nonce": 1,
+//! "signature": "0x...", "proof": "0x..."}`, where `signature` is sr25519 signature of
+//! pallet `SignedOrder` payload and `proof` is signature of liability parameters
+//! checked by `create`.
+
+use crate::error::{Error, Result};
+use crate::liability::{self, decode_cid};
+use crate::pubsub::{Gossipsub, PubSub};
+use crate::runtime::pallet_liability::CreateCall;
+use crate::runtime::{AccountId, Robonomics};
+
+use futures::prelude::*;
+use pallet_robonomics_liability::{
+    economics::Communism,
+    market::Side,
+    signed::{verify_order, SignedLiability, SignedOrder},
+    technics::PureIPFS,
+    traits::Agreement,
+};
+use sp_core::{crypto::Ss58Codec, sr25519};
+use sp_runtime::{MultiSignature, MultiSigner};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use substrate_subxt::Client;
+
+/// Maximal size of order message in bytes.
+pub const MAX_MESSAGE_SIZE: usize = 4096;
+
+/// Maximal count of orders accepted from one sender during `RATE_PERIOD`.
+pub const MAX_ORDERS_PER_PERIOD: u32 = 16;
+
+/// Period of sender orders rate limit.
+pub const RATE_PERIOD: Duration = Duration::from_secs(60);
+
+/// Maximal count of open orders kept by bridge.
+pub const MAX_OPEN_ORDERS: usize = 1024;
+
+/// Market order of Robonomics runtime.
+pub type Order = SignedOrder<Vec<u8>, (), AccountId, u32, MultiSignature>;
+
+/// Liability of Robonomics runtime, it checks parameters proofs.
+type Liability = SignedLiability<PureIPFS, Communism, MultiSignature, MultiSigner, AccountId>;
+
+/// Order message schema.
+#[derive(serde::Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct OrderMessage {
+    side: OrderSide,
+    technics: String,
+    sender: String,
+    expiry: u32,
+    nonce: u64,
+    signature: String,
+    proof: String,
+}
+
+#[derive(serde::Deserialize, Debug)]
+#[serde(rename_all = "lowercase")]
+enum OrderSide {
+    Demand,
+    Offer,
+}
+
+/// Market order of pubsub message checked by pallet rules.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ValidOrder {
+    /// Signed order of sender.
+    pub order: Order,
+    /// Sender proof of liability parameters.
+    pub proof: MultiSignature,
+}
+
+/// Decode order message and check its signatures.
+pub fn validate(data: &[u8]) -> Result<ValidOrder> {
+    if data.len() > MAX_MESSAGE_SIZE {
+        Err("order message is too long")?
+    }
+    let message: OrderMessage =
+        serde_json::from_slice(data).map_err(|e| format!("bad order message: {}", e))?;
+    let order = Order {
+        side: match message.side {
+            OrderSide::Demand => Side::Demand,
+            OrderSide::Offer => Side::Offer,
+        },
+        technics: decode_cid(&message.technics)?,
+        economics: (),
+        sender: AccountId::from_ss58check(&message.sender).map_err(|_| Error::Ss58CodecError)?,
+        expiry: message.expiry,
+        nonce: message.nonce,
+        signature: decode_signature(&message.signature)?,
+    };
+    if !verify_order(&order) {
+        Err("bad order signature")?
+    }
+
+    // Parameters proof is checked the same way as `create` checks party proofs
+    let proof = decode_signature(&message.proof)?;
+    let liability = Liability::new(
+        order.technics.clone(),
+        (),
+        order.sender.clone(),
+        order.sender.clone(),
+    );
+    if !liability.check_params(&proof, &order.sender) {
+        Err("bad liability parameters proof")?
+    }
+    Ok(ValidOrder { order, proof })
+}
+
+/// Hex encoded sr25519 signature.
+fn decode_signature(signature: &str) -> Result<MultiSignature> {
+    let bytes = hex::decode(signature.trim_start_matches("0x"))
+        .map_err(|_| format!("bad signature {}: not a hex string", signature))?;
+    if bytes.len() != 64 {
+        Err(format!("bad signature {}: 64 bytes expected", signature))?
+    }
+    let mut raw = [0u8; 64];
+    raw.copy_from_slice(&bytes);
+    Ok(sr25519::Signature::from_raw(raw).into())
+}
+
+/// Open orders of bridge with orders deduplication and sender rate limits.
+#[derive(Default)]
+pub struct OrderBook {
+    demands: Vec<ValidOrder>,
+    offers: Vec<ValidOrder>,
+    /// Expiry of received orders by sender and nonce, pruned when expired.
+    received: HashMap<(AccountId, u64), u32>,
+    /// Start of current rate period and count of orders received during it by sender.
+    rates: HashMap<AccountId, (Instant, u32)>,
+}
+
+impl OrderBook {
+    /// Insert order received at given time when best block is `block`, returns demand and
+    /// offer pair when compatible order of other side is open.
+    pub fn insert(
+        &mut self,
+        order: ValidOrder,
+        now: Instant,
+        block: u32,
+    ) -> Result<Option<(ValidOrder, ValidOrder)>> {
+        self.prune(now, block);
+
+        let sender = order.order.sender.clone();
+        let key = (sender.clone(), order.order.nonce);
+        if order.order.expiry < block {
+            Err("order is expired")?
+        }
+        if self.received.contains_key(&key) {
+            Err("order is already received")?
+        }
+        let rate = self.rates.entry(sender).or_insert((now, 0));
+        if rate.1 >= MAX_ORDERS_PER_PERIOD {
+            Err("order rate limit of sender is exceeded")?
+        }
+        if self.demands.len() + self.offers.len() >= MAX_OPEN_ORDERS {
+            Err("order book is full")?
+        }
+        rate.1 += 1;
+        self.received.insert(key, order.order.expiry);
+
+        let (orders, counter) = match order.order.side {
+            Side::Demand => (&mut self.demands, &mut self.offers),
+            Side::Offer => (&mut self.offers, &mut self.demands),
+        };
+        let position = counter.iter().position(|open| {
+            open.order.technics == order.order.technics
+                && open.order.economics == order.order.economics
+                && open.order.sender != order.order.sender
+        });
+        Ok(match position {
+            Some(position) => {
+                let open = counter.remove(position);
+                match order.order.side {
+                    Side::Demand => Some((order, open)),
+                    Side::Offer => Some((open, order)),
+                }
+            }
+            None => {
+                orders.push(order);
+                None
+            }
+        })
+    }
+
+    /// Remove expired orders and finished rate periods.
+    fn prune(&mut self, now: Instant, block: u32) {
+        self.demands.retain(|open| open.order.expiry >= block);
+        self.offers.retain(|open| open.order.expiry >= block);
+        self.received.retain(|_, expiry| *expiry >= block);
+        self.rates
+            .retain(|_, (start, _)| now.duration_since(*start) < RATE_PERIOD);
+    }
+}
+
+/// Run bridge of pubsub order topic to remote Robonomics node, liability transitions
+/// are published into result topic.
+pub async fn run(
+    pubsub: Arc<Gossipsub>,
+    remote: String,
+    order_topic: String,
+    result_topic: String,
+) -> Result<()> {
+    let publisher = pubsub.clone();
+    let results = liability::watch(remote.clone(), None, None, false, move |transition| {
+        publisher.publish(&result_topic, transition.to_json().to_string());
+        Ok(())
+    });
+
+    let mut inbox = pubsub.subscribe(&order_topic);
+    let orders = async move {
+        let mut book = OrderBook::default();
+        let mut client = None;
+        while let Some(message) = inbox.next().await {
+            // Client is reconnected on next message when connection drops
+            if let Err(e) = relay(&mut client, &remote, &mut book, &message.data).await {
+                if let Error::SubmitFailure(_) = e {
+                    client = None;
+                }
+                log::debug!(
+                    target: "robonomics-bridge",
+                    "Order message of {} is dropped: {}", message.from, e
+                );
+            }
+        }
+        Ok(())
+    };
+
+    future::try_join(results, orders).await.map(|_| ())
+}
+
+/// Check order message and create liability when it's matched.
+async fn relay(
+    client: &mut Option<Client<Robonomics>>,
+    remote: &str,
+    book: &mut OrderBook,
+    data: &[u8],
+) -> Result<()> {
+    let order = validate(data)?;
+    if client.is_none() {
+        let connected = substrate_subxt::ClientBuilder::<Robonomics>::new()
+            .set_url(remote)
+            .build()
+            .await?;
+        *client = Some(connected);
+    }
+    let client = client.as_ref().ok_or("remote node isn't connected")?;
+    let block = client
+        .header(None::<sp_core::H256>)
+        .await?
+        .ok_or("best block isn't found")?
+        .number;
+
+    if let Some((demand, offer)) = book.insert(order, Instant::now(), block)? {
+        let call = CreateCall::<Robonomics> {
+            technics: demand.order.technics,
+            economics: (),
+            promisee: demand.order.sender,
+            promisor: offer.order.sender,
+            promisee_proof: demand.proof,
+            promisor_proof: offer.proof,
+        };
+        let index = liability::submit_create(client, call).await?;
+        log::info!(
+            target: "robonomics-bridge",
+            "Liability {} created by orders {} and {}",
+            index, demand.order.nonce, offer.order.nonce
+        );
+    }
+    Ok(())
+}
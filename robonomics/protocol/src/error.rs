@@ -47,6 +47,9 @@ pub enum Error {
     Codec(bincode::Error),
     /// Unable to decode address.
     Ss58CodecError,
+    /// Promisee free balance doesn't cover liability price.
+    #[display(fmt = "promisee balance {} is lower than liability price {}", _0, _1)]
+    Underfunded(u128, u128),
     /// Other error.
     Other(String),
 }
@@ -0,0 +1,383 @@
+///////////////////////////////////////////////////////////////////////////////
+//
+//  Copyright 2018-2020 Airalab <research@aira.life>
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//      http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+//
+///////////////////////////////////////////////////////////////////////////////
+//! Create liabilities using Robonomics network.
+
+use crate::error::{Error, Result};
+use crate::runtime::pallet_liability::*;
+use crate::runtime::{AccountId, Robonomics};
+
+use base58::{FromBase58, ToBase58};
+use codec::Decode;
+use futures::future::{self, Either};
+use sp_core::{crypto::Ss58Codec, sr25519, storage::StorageKey};
+use std::path::Path;
+use std::time::Duration;
+use substrate_subxt::{
+    balances::BalancesEventsDecoder, system::AccountStoreExt, Client, EventsDecoder, RawEvent,
+};
+
+pub use pallet_robonomics_liability::KEY_TYPE;
+pub use robonomics_liability_client::Finalization;
+
+/// Client reconnects when no finalized head received during this period.
+const HEAD_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Delay before reconnection to remote node.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Liability job description.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Job {
+    /// IPFS hash of liability objective.
+    pub technics: Vec<u8>,
+    /// Price of liability escrowed from promisee.
+    pub price: u128,
+}
+
+/// Sign liability parameters by both parties and submit unsigned `create` using remote
+/// Robonomics node, returns index of created liability.
+pub async fn create(
+    remote: String,
+    job: Job,
+    promisee: sr25519::Pair,
+    promisor: sr25519::Pair,
+) -> Result<u64> {
+    let client = substrate_subxt::ClientBuilder::<Robonomics>::new()
+        .set_url(remote.as_str())
+        .build()
+        .await?;
+
+    if job.price > 0 {
+        let promisee_account = robonomics_liability_client::account(&promisee);
+        let free = client.account(&promisee_account, None).await?.data.free;
+        if free < job.price {
+            return Err(Error::Underfunded(free, job.price));
+        }
+        // Robonomics runtime uses free economics, i.e. parameter carries no price
+        return Err("Robonomics liabilities are free of charge, job price should be zero".into());
+    }
+
+    let call = CreateCall::<Robonomics>::signed(job.technics, (), &promisee, &promisor);
+    submit_create(&client, call).await
+}
+
+/// Submit unsigned `create` of liability signed by both parties, returns index of created
+/// liability.
+pub async fn submit_create(
+    client: &Client<Robonomics>,
+    call: CreateCall<Robonomics>,
+) -> Result<u64> {
+    let created = robonomics_liability_client::create(client, call)
+        .await
+        .map_err(|e| Error::Other(e.to_string()))?;
+    log::debug!(
+        target: "robonomics-liability",
+        "Liability {} created in extrinsic with hash {}", created.event.index, created.extrinsic
+    );
+    Ok(created.event.index)
+}
+
+/// Add result file to IPFS by HTTP API and finalize liability by promisor key using remote
+/// Robonomics node, the multihash of added file is the liability report.
+pub async fn finalize_with_file(
+    remote: String,
+    ipfs_api: String,
+    index: u64,
+    path: &Path,
+    promisor: sr25519::Pair,
+) -> Result<Finalization<Robonomics>> {
+    let client = substrate_subxt::ClientBuilder::<Robonomics>::new()
+        .set_url(remote.as_str())
+        .build()
+        .await?;
+    let finalization =
+        robonomics_liability_client::finalize_with_file(&client, &ipfs_api, index, path, &promisor)
+            .await
+            .map_err(|e| Error::Other(e.to_string()))?;
+    if let Finalization::Reported(reported, _) = &finalization {
+        log::debug!(
+            target: "robonomics-liability",
+            "Liability {} finalized in extrinsic with hash {}", index, reported.extrinsic
+        );
+    }
+    Ok(finalization)
+}
+
+/// Lifecycle transition of watched liability.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Transition {
+    /// Liability created.
+    Created {
+        /// Liability index.
+        index: u64,
+        /// Creation block.
+        block: u32,
+        /// IPFS hash of liability objective.
+        technics: Vec<u8>,
+        /// Promisee account.
+        promisee: AccountId,
+        /// Promisor account.
+        promisor: AccountId,
+    },
+    /// Liability report published.
+    Reported {
+        /// Liability index.
+        index: u64,
+        /// Report block.
+        block: u32,
+        /// Report read from chain state, when reports fetching is enabled.
+        report: Option<Vec<u8>>,
+    },
+}
+
+impl Transition {
+    /// JSON view of liability transition.
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Transition::Created {
+                index,
+                block,
+                technics,
+                promisee,
+                promisor,
+            } => serde_json::json!({
+                "transition": "created",
+                "index": index,
+                "block": block,
+                "technics": technics.to_base58(),
+                "promisee": promisee.to_ss58check(),
+                "promisor": promisor.to_ss58check(),
+            }),
+            Transition::Reported {
+                index,
+                block,
+                report,
+            } => serde_json::json!({
+                "transition": "reported",
+                "index": index,
+                "block": block,
+                "report": report.as_ref().map(|report| report.to_base58()),
+            }),
+        }
+    }
+}
+
+/// Watch liability transitions of account, or of all accounts when it isn't set, in finalized
+/// blocks of remote Robonomics node starting from given block, or from the current finalized
+/// one when it isn't set.
+///
+/// Node is reconnected after RPC connection drops, blocks finalized meanwhile are
+/// processed in order after reconnection. Returns only when transition handler fails.
+pub async fn watch<F>(
+    remote: String,
+    account: Option<AccountId>,
+    from_block: Option<u32>,
+    fetch_reports: bool,
+    mut on_transition: F,
+) -> Result<()>
+where
+    F: FnMut(Transition) -> Result<()>,
+{
+    let mut next = from_block;
+    loop {
+        match watch_once(
+            &remote,
+            &account,
+            &mut next,
+            fetch_reports,
+            &mut on_transition,
+        )
+        .await
+        {
+            Err(Error::SubmitFailure(e)) => log::warn!(
+                target: "robonomics-liability",
+                "Connection to {} failed: {}", remote, e
+            ),
+            Err(e) => return Err(e),
+            Ok(()) => log::warn!(
+                target: "robonomics-liability",
+                "No finalized heads from {}", remote
+            ),
+        }
+        futures_timer::Delay::new(RECONNECT_DELAY).await;
+    }
+}
+
+/// Process finalized blocks until connection is dropped, `next` is the first unprocessed block.
+async fn watch_once<F>(
+    remote: &str,
+    account: &Option<AccountId>,
+    next: &mut Option<u32>,
+    fetch_reports: bool,
+    on_transition: &mut F,
+) -> Result<()>
+where
+    F: FnMut(Transition) -> Result<()>,
+{
+    let client = substrate_subxt::ClientBuilder::<Robonomics>::new()
+        .set_url(remote)
+        .build()
+        .await?;
+    let mut decoder = EventsDecoder::<Robonomics>::new(client.metadata().clone());
+    decoder.with_balances();
+    decoder.with_liability();
+    let events_key = client
+        .metadata()
+        .module("System")
+        .and_then(|module| module.storage("Events"))
+        .and_then(|storage| storage.plain())
+        .map_err(substrate_subxt::Error::from)?
+        .key();
+
+    let mut heads = client.subscribe_finalized_blocks().await?;
+    loop {
+        let timeout = futures_timer::Delay::new(HEAD_TIMEOUT);
+        let head = match future::select(Box::pin(heads.next()), timeout).await {
+            Either::Left((head, _)) => head,
+            Either::Right(_) => return Ok(()),
+        };
+
+        let mut block = next.unwrap_or(head.number);
+        while block <= head.number {
+            let hash = client
+                .block_hash(Some(block.into()))
+                .await?
+                .ok_or("finalized block isn't found")?;
+            let events = block_events(&client, &decoder, &events_key, hash).await?;
+            for event in events.into_iter().filter(|e| e.module == "Liability") {
+                if let Some(transition) =
+                    transition(&client, account, block, hash, event, fetch_reports).await?
+                {
+                    on_transition(transition)?;
+                }
+            }
+            block += 1;
+            *next = Some(block);
+        }
+    }
+}
+
+/// Decoded events of given block.
+async fn block_events(
+    client: &Client<Robonomics>,
+    decoder: &EventsDecoder<Robonomics>,
+    events_key: &StorageKey,
+    hash: sp_core::H256,
+) -> Result<Vec<RawEvent>> {
+    let changes = client
+        .query_storage(vec![events_key.clone()], hash, Some(hash))
+        .await?;
+    let data = changes
+        .into_iter()
+        .flat_map(|set| set.changes)
+        .find_map(|(key, data)| if &key == events_key { data } else { None });
+    let data = match data {
+        Some(data) => data,
+        None => return Ok(Vec::new()),
+    };
+    Ok(decoder
+        .decode_events(&mut &data.0[..])?
+        .into_iter()
+        .filter_map(|(_, raw)| match raw {
+            substrate_subxt::Raw::Event(event) => Some(event),
+            substrate_subxt::Raw::Error(_) => None,
+        })
+        .collect())
+}
+
+/// Liability transition of event when the account is its party.
+async fn transition(
+    client: &Client<Robonomics>,
+    account: &Option<AccountId>,
+    block: u32,
+    hash: sp_core::H256,
+    event: RawEvent,
+    fetch_reports: bool,
+) -> Result<Option<Transition>> {
+    Ok(match event.variant.as_str() {
+        "NewLiability" => {
+            let created = NewLiabilityEvent::<Robonomics>::decode(&mut &event.data[..])
+                .map_err(|e| Error::Other(e.to_string()))?;
+            let party =
+                |account: &AccountId| &created.promisee == account || &created.promisor == account;
+            if !account.as_ref().map_or(true, party) {
+                return Ok(None);
+            }
+            // Event carries technics hash, objective is read from liability record
+            let store = LiabilitiesStore::<Robonomics> {
+                index: created.index,
+            };
+            let record = client
+                .fetch(&store, Some(hash))
+                .await?
+                .ok_or_else(|| Error::Other(format!("liability {} not found", created.index)))?;
+            Some(Transition::Created {
+                index: created.index,
+                block,
+                technics: record.technics,
+                promisee: created.promisee,
+                promisor: created.promisor,
+            })
+        }
+        "NewReport" => {
+            let reported = NewReportEvent::<Robonomics>::decode(&mut &event.data[..])
+                .map_err(|e| Error::Other(e.to_string()))?;
+            if let Some(account) = account {
+                let party = AccountLiabilitiesStore::<Robonomics> {
+                    account,
+                    index: reported.index,
+                };
+                if client.fetch(&party, Some(hash)).await?.is_none() {
+                    return Ok(None);
+                }
+            }
+            let report = if fetch_reports {
+                let store = ReportPreimageStore::<Robonomics> {
+                    hash: reported.report,
+                    _runtime: Default::default(),
+                };
+                client
+                    .fetch(&store, Some(hash))
+                    .await?
+                    .map(|preimage| preimage.report)
+            } else {
+                None
+            };
+            Some(Transition::Reported {
+                index: reported.index,
+                block,
+                report,
+            })
+        }
+        _ => None,
+    })
+}
+
+/// Decode IPFS hash of CIDv0 form, i.e. base58 encoded sha2-256 multihash.
+pub fn decode_cid(cid: &str) -> Result<Vec<u8>> {
+    let multihash = cid
+        .from_base58()
+        .map_err(|_| format!("bad technics CID {}: not a base58 string", cid))?;
+    if multihash.len() != 34 || multihash[0] != 0x12 || multihash[1] != 0x20 {
+        Err(format!(
+            "bad technics CID {}: sha2-256 multihash expected, e.g. Qm...",
+            cid
+        ))?
+    }
+    Ok(multihash)
+}
@@ -17,9 +17,11 @@
 ///////////////////////////////////////////////////////////////////////////////
 //! Robonomics Network protocol.
 
+pub mod bridge;
 pub mod datalog;
 pub mod error;
 pub mod id;
 pub mod launch;
+pub mod liability;
 pub mod pubsub;
 pub mod runtime;
@@ -30,6 +30,9 @@ pub mod pallet_datalog;
 /// Robonomics Launch pallet.
 pub mod pallet_launch;
 
+/// Robonomics Liability pallet.
+pub use robonomics_liability_client::module as pallet_liability;
+
 /// Robonomics Network family runtimes.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Robonomics;
@@ -65,3 +68,10 @@ impl pallet_datalog::Datalog for Robonomics {
 impl pallet_launch::Launch for Robonomics {
     type Parameter = bool;
 }
+
+impl pallet_liability::Liability for Robonomics {
+    type LiabilityIndex = u64;
+    type TechnicalParam = Vec<u8>;
+    type EconomicalParam = ();
+    type TechnicalReport = Vec<u8>;
+}
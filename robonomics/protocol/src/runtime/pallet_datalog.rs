@@ -60,9 +60,48 @@ pub struct ErasedEvent<T: Datalog> {
     pub sender: <T as System>::AccountId,
 }
 
-///
+/// Positions of account datalog window: records from `start` inclusive to `end` exclusive.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Encode, Decode)]
+pub struct RingBufferIndex {
+    /// Position of the oldest record.
+    pub start: u64,
+    /// Position of the next record.
+    pub end: u64,
+}
+
+/// Datalog record of account window.
+#[derive(Clone, Debug, Eq, PartialEq, Encode, Decode)]
+pub struct DatalogItem<T: Datalog> {
+    /// Record block.
+    pub block: <T as System>::BlockNumber,
+    /// Record block timestamp.
+    pub timestamp: u64,
+    /// Data record.
+    pub record: T::Record,
+}
+
+impl<T: Datalog> Default for DatalogItem<T> {
+    fn default() -> Self {
+        DatalogItem {
+            block: Default::default(),
+            timestamp: Default::default(),
+            record: Default::default(),
+        }
+    }
+}
+
+/// Window positions of account datalog.
+#[derive(Clone, Debug, Eq, PartialEq, Store, Encode)]
+pub struct DatalogIndexStore<'a, T: Datalog> {
+    #[store(returns = RingBufferIndex)]
+    pub account_id: &'a <T as System>::AccountId,
+}
+
+/// Datalog record of account by window position.
 #[derive(Clone, Debug, Eq, PartialEq, Store, Encode)]
-pub struct DatalogStore<'a, T: Datalog> {
-    #[store(returns = Vec<(u64, T::Record)>)]
-    account_id: &'a <T as System>::AccountId,
+pub struct DatalogEntryStore<'a, T: Datalog> {
+    #[store(returns = DatalogItem<T>)]
+    pub account_id: &'a <T as System>::AccountId,
+    /// Window position.
+    pub position: u64,
 }